@@ -0,0 +1,79 @@
+use crate::detection::{format_moves, Explanation, ReferenceMove};
+use crate::models::Stats24h;
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+/// Where the signal behind an alert came from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum SignalSource {
+    /// A strategy's own detection
+    #[default]
+    Detector,
+    /// Injected through the control socket, with the sender's note
+    Manual { note: Option<String> },
+}
+
+impl SignalSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Detector => "detector",
+            Self::Manual { .. } => "manual",
+        }
+    }
+
+    pub fn note(&self) -> Option<&str> {
+        match self {
+            Self::Detector => None,
+            Self::Manual { note } => note.as_deref(),
+        }
+    }
+}
+
+impl fmt::Display for SignalSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.note() {
+            Some(note) => write!(f, "{} ({})", self.name(), note),
+            None => write!(f, "{}", self.name()),
+        }
+    }
+}
+
+/// Notification raised when a strategy starts a new episode
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub strategy: String,
+    /// Id of the episode this alert opened; also in its logs, recording and orders
+    pub episode_id: String,
+    pub symbol: String,
+    pub ratio: f64,
+    pub last_price: f64,
+    pub mark_price: f64,
+    /// 24h volume, high/low and change of the symbol when the alert fired
+    pub stats_24h: Option<Stats24h>,
+    pub explanation: Explanation,
+    /// Reference symbol moves leading up to the trigger, filled in by the dispatcher
+    pub market_moves: Vec<ReferenceMove>,
+    pub timestamp: DateTime<Utc>,
+    pub source: SignalSource,
+}
+
+impl Alert {
+    /// One-line summary suitable for any text-based notifier
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{} [{}] {} | Ratio: {:.4} | Last: {:.8} | Mark: {:.8} | Episode: {}",
+            self.timestamp.format("%H:%M:%S"),
+            self.strategy, self.symbol, self.ratio, self.last_price, self.mark_price, self.episode_id
+        );
+        if let Some(ref stats) = self.stats_24h {
+            summary.push_str(&format!(" | {}", stats));
+        }
+        if !self.market_moves.is_empty() {
+            summary.push_str(&format!(" | Market: {}", format_moves(&self.market_moves)));
+        }
+        if self.source != SignalSource::Detector {
+            summary.push_str(&format!(" | Source: {}", self.source));
+        }
+        summary
+    }
+}
@@ -0,0 +1,154 @@
+use crate::api::MexcRestClient;
+use crate::config::MissedDigestConfig;
+use crate::models::Candle;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+/// A minute during downtime where the exchange klines show a last/fair spread above threshold
+#[derive(Debug, Clone)]
+pub struct MissedAnomaly {
+    pub symbol: String,
+    pub minute: DateTime<Utc>,
+    pub peak_ratio: f64,
+    pub last_high: f64,
+    pub fair_high: f64,
+}
+
+/// What happened on the exchange while the detector was not running
+#[derive(Debug, Clone)]
+pub struct MissedDigest {
+    pub offline_from: DateTime<Utc>,
+    pub offline_until: DateTime<Utc>,
+    /// True when the window was longer than `max_lookback_hours` and only its tail was scanned
+    pub truncated: bool,
+    pub symbols_scanned: usize,
+    pub symbols_failed: usize,
+    /// Strongest missed minute per symbol, highest ratio first
+    pub anomalies: Vec<MissedAnomaly>,
+}
+
+impl MissedDigest {
+    pub fn summary(&self) -> String {
+        let downtime = self.offline_until.signed_duration_since(self.offline_from);
+        let mut lines = vec![format!(
+            "Offline for {}h {:02}m ({} -> {}){} | scanned {} symbols{}",
+            downtime.num_hours(),
+            downtime.num_minutes() % 60,
+            self.offline_from.format("%Y-%m-%d %H:%M:%S"),
+            self.offline_until.format("%Y-%m-%d %H:%M:%S"),
+            if self.truncated { " (only the most recent part scanned)" } else { "" },
+            self.symbols_scanned,
+            if self.symbols_failed > 0 {
+                format!(", {} failed", self.symbols_failed)
+            } else {
+                String::new()
+            }
+        )];
+
+        if self.anomalies.is_empty() {
+            lines.push("No spread anomalies found in exchange klines - nothing was missed".to_string());
+        } else {
+            lines.push(format!("{} symbols had anomalies while offline:", self.anomalies.len()));
+            for anomaly in &self.anomalies {
+                lines.push(format!(
+                    "  {} at {} | Ratio: {:.4} | Last high: {:.8} | Fair high: {:.8}",
+                    anomaly.symbol,
+                    anomaly.minute.format("%H:%M"),
+                    anomaly.peak_ratio,
+                    anomaly.last_high,
+                    anomaly.fair_high
+                ));
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Replays the offline window from exchange 1m klines (last price vs fair price),
+/// since nothing was observed locally while the process was down.
+pub async fn build_missed_digest(
+    rest: &MexcRestClient,
+    symbols: &[String],
+    offline_from: DateTime<Utc>,
+    offline_until: DateTime<Utc>,
+    config: &MissedDigestConfig,
+) -> MissedDigest {
+    let earliest = offline_until - Duration::hours(config.max_lookback_hours as i64);
+    let truncated = offline_from < earliest;
+    let scan_from = offline_from.max(earliest);
+
+    let scanned: Vec<&String> = symbols.iter().take(config.max_symbols).collect();
+    if scanned.len() < symbols.len() {
+        warn!(
+            "[Digest] Scanning only {} of {} symbols for missed anomalies (max_symbols)",
+            scanned.len(),
+            symbols.len()
+        );
+    }
+
+    let mut anomalies = Vec::new();
+    let mut symbols_failed = 0;
+    let delay = std::time::Duration::from_millis(config.request_delay_ms);
+
+    for symbol in &scanned {
+        let start = scan_from.timestamp();
+        let end = offline_until.timestamp();
+
+        let last = rest.get_klines_1m(symbol, start, end).await;
+        tokio::time::sleep(delay).await;
+        let fair = rest.get_fair_price_klines_1m(symbol, start, end).await;
+        tokio::time::sleep(delay).await;
+
+        match (last, fair) {
+            (Ok(last), Ok(fair)) => {
+                if let Some(anomaly) = strongest_spread(symbol, &last, &fair, config.ratio_threshold) {
+                    anomalies.push(anomaly);
+                }
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                symbols_failed += 1;
+                warn!("[Digest] Failed to fetch klines for {}: {:?}", symbol, e);
+            }
+        }
+    }
+
+    anomalies.sort_by(|a, b| b.peak_ratio.total_cmp(&a.peak_ratio));
+    info!(
+        "[Digest] Replayed {} symbols over the offline window - {} with anomalies",
+        scanned.len(),
+        anomalies.len()
+    );
+
+    MissedDigest {
+        offline_from,
+        offline_until,
+        truncated,
+        symbols_scanned: scanned.len(),
+        symbols_failed,
+        anomalies,
+    }
+}
+
+fn strongest_spread(symbol: &str, last: &[Candle], fair: &[Candle], ratio_threshold: f64) -> Option<MissedAnomaly> {
+    let fair_by_minute: HashMap<i64, &Candle> = fair.iter().map(|k| (k.timestamp_ms, k)).collect();
+
+    last.iter()
+        .filter_map(|kline| {
+            let fair_kline = fair_by_minute.get(&kline.timestamp_ms)?;
+            if fair_kline.high <= 0.0 {
+                return None;
+            }
+            Some((kline, fair_kline, kline.high / fair_kline.high))
+        })
+        .filter(|(_, _, ratio)| *ratio >= ratio_threshold)
+        .max_by(|a, b| a.2.total_cmp(&b.2))
+        .map(|(kline, fair_kline, ratio)| MissedAnomaly {
+            symbol: symbol.to_string(),
+            minute: DateTime::from_timestamp_millis(kline.timestamp_ms).unwrap_or_else(Utc::now),
+            peak_ratio: ratio,
+            last_high: kline.high,
+            fair_high: fair_kline.high,
+        })
+}
@@ -0,0 +1,158 @@
+use crate::alerts::Alert;
+use crate::config::DigestModeConfig;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use tracing::{info, warn};
+
+/// One symbol's alerts within a digest
+#[derive(Debug, Clone)]
+pub struct DigestSymbol {
+    pub symbol: String,
+    pub peak_ratio: f64,
+    /// Strategy -> alerts it raised, by name
+    pub strategies: BTreeMap<String, usize>,
+    pub alerts: usize,
+}
+
+/// Alerts collected over one interval of digest mode, sent as a single message
+#[derive(Debug, Clone)]
+pub struct AlertDigest {
+    pub from: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub alerts: usize,
+    /// Highest peak ratio first, at most `max_symbols`
+    pub symbols: Vec<DigestSymbol>,
+    /// Symbols beyond `max_symbols`, and the alerts they raised
+    pub more_symbols: usize,
+    pub more_alerts: usize,
+    /// Activity subsided: alerts are delivered one by one again after this digest
+    pub last: bool,
+}
+
+impl AlertDigest {
+    pub fn summary(&self) -> String {
+        let mut lines = vec![format!(
+            "Alert digest {} - {}: {} alerts on {} symbols",
+            self.from.format("%H:%M:%S"),
+            self.until.format("%H:%M:%S"),
+            self.alerts,
+            self.symbols.len() + self.more_symbols
+        )];
+        for symbol in &self.symbols {
+            let strategies: Vec<String> = symbol
+                .strategies
+                .iter()
+                .map(|(strategy, count)| if *count > 1 { format!("{} x{}", strategy, count) } else { strategy.clone() })
+                .collect();
+            lines.push(format!("  {} | Peak ratio: {:.4} | {}", symbol.symbol, symbol.peak_ratio, strategies.join(", ")));
+        }
+        if self.more_symbols > 0 {
+            lines.push(format!("  ... and {} more symbols ({} alerts)", self.more_symbols, self.more_alerts));
+        }
+        if self.last {
+            lines.push("Activity back to normal - alerts are sent one by one again".to_string());
+        }
+        lines.join("\n")
+    }
+}
+
+/// Collapses alerts into one digest per `interval_secs` while more than
+/// `enter_alerts_per_min` alerts go out within a minute, so a busy market doesn't flood the
+/// external notifiers; it returns to one message per alert once a minute has had at most
+/// `exit_alerts_per_min`. Only alerts that would have been delivered count.
+pub struct DigestMode {
+    config: DigestModeConfig,
+    // Delivered (or digested) alert times within the last minute, oldest first
+    recent: VecDeque<DateTime<Utc>>,
+    // Start of the digest being collected, while in digest mode
+    collecting_since: Option<DateTime<Utc>>,
+    pending: Vec<Alert>,
+}
+
+impl DigestMode {
+    pub fn new(config: DigestModeConfig) -> Self {
+        Self {
+            config,
+            recent: VecDeque::new(),
+            collecting_since: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Counts an alert about to be delivered; true when it goes into the digest instead
+    pub fn collect(&mut self, alert: &Alert) -> bool {
+        self.recent.push_back(alert.timestamp);
+        prune(&mut self.recent, alert.timestamp);
+
+        if self.collecting_since.is_none() {
+            if self.recent.len() <= self.config.enter_alerts_per_min {
+                return false;
+            }
+            warn!(
+                "[Alerts] {} alerts within a minute (limit {}) - switching to digest mode, one message every {}s",
+                self.recent.len(),
+                self.config.enter_alerts_per_min,
+                self.config.interval_secs
+            );
+            self.collecting_since = Some(alert.timestamp);
+        }
+        self.pending.push(alert.clone());
+        true
+    }
+
+    /// The digest collected so far once its interval is over at `now`, leaving digest mode
+    /// if activity subsided
+    pub fn poll(&mut self, now: DateTime<Utc>) -> Option<AlertDigest> {
+        let since = self.collecting_since?;
+        if now - since < Duration::seconds(self.config.interval_secs.max(1) as i64) {
+            return None;
+        }
+        prune(&mut self.recent, now);
+        let last = self.recent.len() <= self.config.exit_alerts_per_min;
+        self.collecting_since = if last { None } else { Some(now) };
+        if last {
+            info!("[Alerts] {} alerts in the last minute - leaving digest mode", self.recent.len());
+        }
+
+        let alerts = std::mem::take(&mut self.pending);
+        if alerts.is_empty() && !last {
+            return None;
+        }
+        Some(self.digest(since, now, alerts, last))
+    }
+
+    fn digest(&self, from: DateTime<Utc>, until: DateTime<Utc>, alerts: Vec<Alert>, last: bool) -> AlertDigest {
+        let mut by_symbol: HashMap<String, DigestSymbol> = HashMap::new();
+        for alert in &alerts {
+            let entry = by_symbol.entry(alert.symbol.clone()).or_insert_with(|| DigestSymbol {
+                symbol: alert.symbol.clone(),
+                peak_ratio: alert.ratio,
+                strategies: BTreeMap::new(),
+                alerts: 0,
+            });
+            entry.peak_ratio = entry.peak_ratio.max(alert.ratio);
+            *entry.strategies.entry(alert.strategy.clone()).or_default() += 1;
+            entry.alerts += 1;
+        }
+        let mut symbols: Vec<DigestSymbol> = by_symbol.into_values().collect();
+        symbols.sort_by(|a, b| b.peak_ratio.total_cmp(&a.peak_ratio).then_with(|| a.symbol.cmp(&b.symbol)));
+        let more: Vec<DigestSymbol> = symbols.split_off(symbols.len().min(self.config.max_symbols.max(1)));
+
+        AlertDigest {
+            from,
+            until,
+            alerts: alerts.len(),
+            symbols,
+            more_symbols: more.len(),
+            more_alerts: more.iter().map(|s| s.alerts).sum(),
+            last,
+        }
+    }
+}
+
+/// Drops alert times older than a minute before `now`
+fn prune(times: &mut VecDeque<DateTime<Utc>>, now: DateTime<Utc>) {
+    while times.front().is_some_and(|t| now - *t > Duration::minutes(1)) {
+        times.pop_front();
+    }
+}
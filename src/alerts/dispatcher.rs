@@ -0,0 +1,343 @@
+use crate::alerts::{
+    Alert, AlertDigest, AlertPacer, AlertSuppressor, DigestMode, KillSwitch, KillSwitchEvent, MissedDigest, Notifier, Paced,
+};
+use crate::detection::{format_moves, MarketContext};
+use crate::models::{ContractStateChange, NewListing};
+use crate::universe::VolumeTiers;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{interval, Duration};
+use tracing::{error, info, warn};
+
+/// Route key for missed-while-offline digests
+pub const DIGEST_ROUTE: &str = "digest";
+
+/// Route key for the digests of alerts collapsed while digest mode is on
+pub const ALERT_DIGEST_ROUTE: &str = "alert_digest";
+
+/// Route key for kill switch state changes
+pub const KILL_SWITCH_ROUTE: &str = "kill_switch";
+
+/// Route key for contract state changes (paused, settling, delisted, ...)
+pub const CONTRACT_STATE_ROUTE: &str = "contract_state";
+
+/// Route key for contracts that appeared in the contract details
+pub const PRE_LISTING_ROUTE: &str = "pre_listing";
+
+// How often a tripped kill switch checks whether the rate has normalized, and digest mode
+// whether its interval is over
+const KILL_SWITCH_POLL_SECS: u64 = 5;
+
+/// Anything the dispatcher can deliver to notifiers
+#[derive(Debug, Clone)]
+pub enum Notification {
+    Alert(Alert),
+    MissedDigest(MissedDigest),
+    ContractState(ContractStateChange),
+    NewListing(NewListing),
+}
+
+impl From<Alert> for Notification {
+    fn from(alert: Alert) -> Self {
+        Notification::Alert(alert)
+    }
+}
+
+impl From<MissedDigest> for Notification {
+    fn from(digest: MissedDigest) -> Self {
+        Notification::MissedDigest(digest)
+    }
+}
+
+impl From<ContractStateChange> for Notification {
+    fn from(change: ContractStateChange) -> Self {
+        Notification::ContractState(change)
+    }
+}
+
+impl From<NewListing> for Notification {
+    fn from(listing: NewListing) -> Self {
+        Notification::NewListing(listing)
+    }
+}
+
+pub type AlertSender = mpsc::UnboundedSender<Notification>;
+
+/// Fans alerts from all strategies out to the configured notifiers
+pub struct AlertDispatcher {
+    notifiers: Vec<Box<dyn Notifier>>,
+    routes: HashMap<String, Vec<String>>,
+    market_context: Option<Arc<MarketContext>>,
+    suppressor: Option<AlertSuppressor>,
+    pacer: Option<AlertPacer>,
+    kill_switch: Option<Arc<KillSwitch>>,
+    digest_mode: Option<DigestMode>,
+    tiers: Option<Arc<VolumeTiers>>,
+    tier_routes: HashMap<String, Vec<String>>,
+}
+
+impl AlertDispatcher {
+    pub fn new(notifiers: Vec<Box<dyn Notifier>>) -> Self {
+        Self {
+            notifiers,
+            routes: HashMap::new(),
+            market_context: None,
+            suppressor: None,
+            pacer: None,
+            kill_switch: None,
+            digest_mode: None,
+            tiers: None,
+            tier_routes: HashMap::new(),
+        }
+    }
+
+    /// Restricts each routed strategy to the named notifiers; unrouted strategies go to all
+    pub fn with_routes(mut self, routes: HashMap<String, Vec<String>>) -> Self {
+        for (route, targets) in &routes {
+            for target in targets {
+                if !self.notifiers.iter().any(|n| n.name() == target) {
+                    warn!("[Alerts] Route '{}' targets notifier '{}', which is not enabled", route, target);
+                }
+            }
+        }
+        self.routes = routes;
+        self
+    }
+
+    /// Annotates alerts with reference symbol moves and withholds those that coincide
+    /// with a market-wide move from all but internal notifiers
+    pub fn with_market_context(mut self, market_context: Arc<MarketContext>) -> Self {
+        self.market_context = Some(market_context);
+        self
+    }
+
+    /// Holds back repeat alerts for a symbol that aren't sufficiently stronger than the
+    /// last one delivered; like market-wide alerts they still reach internal notifiers
+    pub fn with_suppression(mut self, suppressor: AlertSuppressor) -> Self {
+        self.suppressor = Some(suppressor);
+        self
+    }
+
+    /// Holds back alerts within the per-symbol (any strategy) or global cooldown of the
+    /// last delivered alert; like suppressed alerts they still reach internal notifiers
+    pub fn with_pacing(mut self, pacer: AlertPacer) -> Self {
+        self.pacer = Some(pacer);
+        self
+    }
+
+    /// Counts every alert as an episode start and, while the switch is tripped, withholds
+    /// alerts from all but internal notifiers
+    pub fn with_kill_switch(mut self, kill_switch: Arc<KillSwitch>) -> Self {
+        self.kill_switch = Some(kill_switch);
+        self
+    }
+
+    /// Collapses alerts for external notifiers into one digest per interval while many
+    /// alerts go out; internal notifiers still get every alert
+    pub fn with_digest_mode(mut self, digest_mode: DigestMode) -> Self {
+        self.digest_mode = Some(digest_mode);
+        self
+    }
+
+    /// Additionally restricts alerts to the notifiers routed for their symbol's volume tier
+    pub fn with_tiers(mut self, tiers: Arc<VolumeTiers>, routes: HashMap<String, Vec<String>>) -> Self {
+        for (tier, targets) in &routes {
+            for target in targets {
+                if !self.notifiers.iter().any(|n| n.name() == target) {
+                    warn!("[Alerts] Tier route '{}' targets notifier '{}', which is not enabled", tier, target);
+                }
+            }
+        }
+        self.tiers = Some(tiers);
+        self.tier_routes = routes;
+        self
+    }
+
+    /// A notifier must be allowed by the route and, for alerts with a tier, the tier route
+    fn is_routed(&self, notifier: &dyn Notifier, route: &str, tier: Option<&str>) -> bool {
+        if notifier.is_internal() {
+            return true;
+        }
+        let allows = |targets: Option<&Vec<String>>| match targets {
+            Some(targets) => targets.iter().any(|t| t == notifier.name()),
+            None => true,
+        };
+        // Pipeline alerts (`<pipeline>/<strategy>`) fall back to the pipeline's route
+        let strategy_route = self
+            .routes
+            .get(route)
+            .or_else(|| route.split_once('/').and_then(|(pipeline, _)| self.routes.get(pipeline)));
+        allows(strategy_route) && allows(tier.and_then(|tier| self.tier_routes.get(tier)))
+    }
+
+    pub async fn run(mut self, mut alert_rx: mpsc::UnboundedReceiver<Notification>) {
+        let names: Vec<&str> = self.notifiers.iter().map(|n| n.name()).collect();
+        info!("[Alerts] Dispatcher started with notifiers: {:?}", names);
+        for (route, targets) in &self.routes {
+            info!("[Alerts] Route: {} -> {:?}", route, targets);
+        }
+        for (tier, targets) in &self.tier_routes {
+            info!("[Alerts] Tier route: {} -> {:?}", tier, targets);
+        }
+
+        let mut poll = interval(Duration::from_secs(KILL_SWITCH_POLL_SECS));
+        loop {
+            let mut notification = tokio::select! {
+                received = alert_rx.recv() => match received {
+                    Some(notification) => notification,
+                    None => break,
+                },
+                _ = poll.tick(), if self.kill_switch.is_some() || self.digest_mode.is_some() => {
+                    let event = self.kill_switch.as_ref().and_then(|k| k.poll(Utc::now()));
+                    if let Some(event) = event {
+                        self.send_kill_switch_event(&event).await;
+                    }
+                    let digest = self.digest_mode.as_mut().and_then(|d| d.poll(Utc::now()));
+                    if let Some(digest) = digest {
+                        self.send_alert_digest(&digest).await;
+                    }
+                    continue;
+                }
+            };
+
+            // Every alert counts towards the episode rate, whether or not it is delivered
+            let mut withheld = false;
+            if let (Notification::Alert(ref alert), Some(ref kill_switch)) = (&notification, &self.kill_switch) {
+                if let Some(event) = kill_switch.record_start(alert.timestamp) {
+                    self.send_kill_switch_event(&event).await;
+                }
+                withheld = kill_switch.is_tripped();
+                if withheld {
+                    info!("[Alerts] Withholding {} ({}): kill switch tripped", alert.symbol, alert.strategy);
+                }
+            }
+            if let (Notification::Alert(ref mut alert), Some(ref context)) = (&mut notification, &self.market_context) {
+                alert.market_moves = context.moves_before(alert.timestamp);
+                if !withheld && context.is_market_wide(&alert.market_moves) {
+                    withheld = true;
+                    info!(
+                        "[Alerts] Withholding {} ({}): market-wide move {}",
+                        alert.symbol,
+                        alert.strategy,
+                        format_moves(&alert.market_moves)
+                    );
+                }
+            }
+            if let (Notification::Alert(ref alert), Some(ref pacer)) = (&notification, &self.pacer) {
+                if !withheld {
+                    if let Err(paced) = pacer.check(alert) {
+                        withheld = true;
+                        let (scope, remaining) = match paced {
+                            Paced::Symbol { remaining } => ("symbol", remaining),
+                            Paced::Global { remaining } => ("global", remaining),
+                        };
+                        info!(
+                            "[Alerts] Holding back {} ({}): {} cooldown has {}s left",
+                            alert.symbol,
+                            alert.strategy,
+                            scope,
+                            remaining.num_seconds().max(1)
+                        );
+                    }
+                }
+            }
+            // Withheld alerts are never delivered, so they don't count as the last alert either
+            if let (Notification::Alert(ref alert), Some(ref mut suppressor)) = (&notification, &mut self.suppressor) {
+                if !withheld {
+                    if let Err(suppressed) = suppressor.check(alert) {
+                        withheld = true;
+                        info!(
+                            "[Alerts] Suppressing repeat {} ({}): severity {:.2}% below the required {:.2}%",
+                            alert.symbol, alert.strategy, suppressed.severity, suppressed.required
+                        );
+                    }
+                }
+            }
+            if let (Notification::Alert(ref alert), Some(ref mut pacer)) = (&notification, &mut self.pacer) {
+                if !withheld {
+                    pacer.record(alert);
+                }
+            }
+            // Digested alerts reach external notifiers with the next digest instead
+            if let (Notification::Alert(ref alert), Some(ref mut digest_mode)) = (&notification, &mut self.digest_mode) {
+                if !withheld && digest_mode.collect(alert) {
+                    withheld = true;
+                }
+            }
+
+            let route = match notification {
+                Notification::Alert(ref alert) => alert.strategy.as_str(),
+                Notification::MissedDigest(_) => DIGEST_ROUTE,
+                Notification::ContractState(_) => CONTRACT_STATE_ROUTE,
+                Notification::NewListing(_) => PRE_LISTING_ROUTE,
+            };
+            let tier = match (&notification, &self.tiers) {
+                (Notification::Alert(ref alert), Some(tiers)) => Some(tiers.tier_name_of(&alert.symbol)),
+                _ => None,
+            };
+            let recipients = self
+                .notifiers
+                .iter()
+                .filter(|n| self.is_routed(n.as_ref(), route, tier.as_deref()) && (!withheld || n.is_internal()));
+            for notifier in recipients {
+                match notification {
+                    Notification::Alert(ref alert) => {
+                        if let Err(e) = notifier.notify(alert).await {
+                            error!(
+                                "[Alerts] Notifier '{}' failed for {} ({}): {:?}",
+                                notifier.name(), alert.symbol, alert.strategy, e
+                            );
+                        }
+                    }
+                    Notification::MissedDigest(ref digest) => {
+                        if let Err(e) = notifier.notify_digest(digest).await {
+                            error!("[Alerts] Notifier '{}' failed to send missed digest: {:?}", notifier.name(), e);
+                        }
+                    }
+                    Notification::ContractState(ref change) => {
+                        if let Err(e) = notifier.notify_contract_state(change).await {
+                            error!(
+                                "[Alerts] Notifier '{}' failed to send contract state change of {}: {:?}",
+                                notifier.name(), change.symbol, e
+                            );
+                        }
+                    }
+                    Notification::NewListing(ref listing) => {
+                        if let Err(e) = notifier.notify_new_listing(listing).await {
+                            error!(
+                                "[Alerts] Notifier '{}' failed to send new listing {}: {:?}",
+                                notifier.name(), listing.symbol, e
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    async fn send_alert_digest(&self, digest: &AlertDigest) {
+        let recipients = self
+            .notifiers
+            .iter()
+            .filter(|n| !n.is_internal() && self.is_routed(n.as_ref(), ALERT_DIGEST_ROUTE, None));
+        for notifier in recipients {
+            if let Err(e) = notifier.notify_alert_digest(digest).await {
+                error!("[Alerts] Notifier '{}' failed to send alert digest: {:?}", notifier.name(), e);
+            }
+        }
+    }
+
+    async fn send_kill_switch_event(&self, event: &KillSwitchEvent) {
+        let recipients = self
+            .notifiers
+            .iter()
+            .filter(|n| !n.is_internal() && self.is_routed(n.as_ref(), KILL_SWITCH_ROUTE, None));
+        for notifier in recipients {
+            if let Err(e) = notifier.notify_kill_switch(event).await {
+                error!("[Alerts] Notifier '{}' failed to send kill switch event: {:?}", notifier.name(), e);
+            }
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use crate::config::KillSwitchConfig;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// A change of the kill switch state, sent once to notifiers as a meta-alert
+#[derive(Debug, Clone)]
+pub enum KillSwitchEvent {
+    Tripped {
+        #[cfg_attr(not(any(feature = "mqtt", feature = "zmq")), allow(dead_code))]
+        at: DateTime<Utc>,
+        starts_per_min: usize,
+        threshold: usize,
+    },
+    Resumed {
+        at: DateTime<Utc>,
+        paused_since: DateTime<Utc>,
+        /// Episode starts whose alerts and entries were held back while paused
+        held_back: usize,
+    },
+}
+
+impl fmt::Display for KillSwitchEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KillSwitchEvent::Tripped { starts_per_min, threshold, .. } => write!(
+                f,
+                "Kill switch tripped: {} episode starts in the last minute (limit {}) - likely a feed problem; \
+                 notifications and execution paused",
+                starts_per_min, threshold
+            ),
+            KillSwitchEvent::Resumed { at, paused_since, held_back } => write!(
+                f,
+                "Kill switch reset: episode rate back to normal after {}s paused ({} episode starts held back); \
+                 notifications and execution resumed",
+                (*at - *paused_since).num_seconds(),
+                held_back
+            ),
+        }
+    }
+}
+
+struct State {
+    // Episode start times within the last minute, oldest first
+    starts: VecDeque<DateTime<Utc>>,
+    tripped_at: Option<DateTime<Utc>>,
+    // Since when the rate has been at or below the resume level while tripped
+    calm_since: Option<DateTime<Utc>>,
+    held_back: usize,
+}
+
+/// Trips when episode starts across all strategies exceed `max_starts_per_min`, which
+/// usually means bad data (a feed glitch or stale mark prices) rather than a real market
+/// event. While tripped, external notifiers and order entry are paused; it resets once
+/// the rate has stayed at or below `resume_below_per_min` for `resume_after_secs`.
+pub struct KillSwitch {
+    config: KillSwitchConfig,
+    tripped: AtomicBool,
+    state: Mutex<State>,
+}
+
+impl KillSwitch {
+    pub fn new(config: KillSwitchConfig) -> Self {
+        Self {
+            config,
+            tripped: AtomicBool::new(false),
+            state: Mutex::new(State {
+                starts: VecDeque::new(),
+                tripped_at: None,
+                calm_since: None,
+                held_back: 0,
+            }),
+        }
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.tripped.load(Ordering::Relaxed)
+    }
+
+    /// Counts an episode start; returns the trip event when this start pushes the rate
+    /// over the limit
+    pub fn record_start(&self, at: DateTime<Utc>) -> Option<KillSwitchEvent> {
+        let mut state = self.state.lock().unwrap();
+        state.starts.push_back(at);
+        prune(&mut state.starts, at);
+
+        if state.tripped_at.is_some() {
+            state.held_back += 1;
+            if state.starts.len() > self.config.resume_below_per_min {
+                state.calm_since = None;
+            }
+            return None;
+        }
+        if state.starts.len() <= self.config.max_starts_per_min {
+            return None;
+        }
+
+        state.tripped_at = Some(at);
+        state.calm_since = None;
+        state.held_back = 1;
+        self.tripped.store(true, Ordering::Relaxed);
+        Some(KillSwitchEvent::Tripped {
+            at,
+            starts_per_min: state.starts.len(),
+            threshold: self.config.max_starts_per_min,
+        })
+    }
+
+    /// Re-evaluates a tripped switch at `now`; returns the reset event once the rate has
+    /// been calm for long enough
+    pub fn poll(&self, now: DateTime<Utc>) -> Option<KillSwitchEvent> {
+        let mut state = self.state.lock().unwrap();
+        let paused_since = state.tripped_at?;
+        prune(&mut state.starts, now);
+
+        if state.starts.len() > self.config.resume_below_per_min {
+            state.calm_since = None;
+            return None;
+        }
+        let calm_since = *state.calm_since.get_or_insert(now);
+        if now - calm_since < Duration::seconds(self.config.resume_after_secs as i64) {
+            return None;
+        }
+
+        let held_back = state.held_back;
+        state.tripped_at = None;
+        state.calm_since = None;
+        state.held_back = 0;
+        self.tripped.store(false, Ordering::Relaxed);
+        Some(KillSwitchEvent::Resumed {
+            at: now,
+            paused_since,
+            held_back,
+        })
+    }
+}
+
+/// Drops starts older than a minute before `now`
+fn prune(starts: &mut VecDeque<DateTime<Utc>>, now: DateTime<Utc>) {
+    let cutoff = now - Duration::minutes(1);
+    while starts.front().is_some_and(|t| *t <= cutoff) {
+        starts.pop_front();
+    }
+}
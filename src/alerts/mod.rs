@@ -0,0 +1,32 @@
+pub mod alert;
+pub mod digest;
+pub mod digest_mode;
+pub mod dispatcher;
+pub mod kill_switch;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+pub mod notifier;
+pub mod pacing;
+// JSON bodies shared by the MQTT and ZeroMQ notifiers
+#[cfg(any(feature = "mqtt", feature = "zmq"))]
+pub mod payload;
+pub mod suppression;
+#[cfg(feature = "telegram")]
+pub mod telegram;
+#[cfg(feature = "zmq")]
+pub mod zmq;
+
+pub use alert::*;
+pub use digest::*;
+pub use digest_mode::*;
+pub use dispatcher::*;
+pub use kill_switch::*;
+#[cfg(feature = "mqtt")]
+pub use mqtt::*;
+pub use notifier::*;
+pub use pacing::*;
+pub use suppression::*;
+#[cfg(feature = "telegram")]
+pub use telegram::*;
+#[cfg(feature = "zmq")]
+pub use zmq::*;
@@ -0,0 +1,375 @@
+use crate::alerts::payload::{alert_digest_payload, alert_payload, contract_state_payload, digest_payload, kill_switch_payload, new_listing_payload};
+use crate::alerts::{Alert, AlertDigest, KillSwitchEvent, MissedDigest, Notifier};
+use crate::config::MqttConfig;
+use crate::models::{ContractStateChange, NewListing};
+use crate::utils::TaskSupervisor;
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::timeout;
+use tracing::{info, warn};
+
+// MQTT 3.1.1 control packet types (upper nibble of the fixed header)
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const PUBACK: u8 = 0x40;
+const PUBREC: u8 = 0x50;
+const PUBREL: u8 = 0x62; // PUBREL carries the mandatory 0b0010 flags
+const PUBCOMP: u8 = 0x70;
+const PINGREQ: u8 = 0xC0;
+const PINGRESP: u8 = 0xD0;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+// Packets read while waiting for a different one, kept until someone waits for them
+const MAX_PENDING_PACKETS: usize = 16;
+
+/// One message waiting to be published
+#[derive(Debug, Clone)]
+struct MqttMessage {
+    topic: String,
+    payload: Vec<u8>,
+}
+
+trait Transport: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Transport for T {}
+
+/// Publishes alerts to an MQTT broker, so lightweight subscribers on other machines
+/// (home automation, dashboards) can react to detections.
+///
+/// Delivery happens on a supervised background task that keeps one connection open and
+/// reconnects on failure; alerts are queued while the broker is unreachable.
+pub struct MqttNotifier {
+    topic_prefix: String,
+    tx: mpsc::Sender<MqttMessage>,
+}
+
+impl MqttNotifier {
+    pub fn start(config: MqttConfig, supervisor: &TaskSupervisor) -> Self {
+        let (tx, rx) = mpsc::channel(config.queue_size.max(1));
+        let publisher = Arc::new(MqttPublisher {
+            password: std::env::var("MQTT_PASSWORD").ok().filter(|p| !p.is_empty()),
+            config: config.clone(),
+            rx: Mutex::new(rx),
+        });
+        supervisor.supervise("mqtt", move || publisher.clone().run());
+
+        Self {
+            topic_prefix: config.topic_prefix.trim_end_matches('/').to_string(),
+            tx,
+        }
+    }
+
+    fn enqueue(&self, topic: String, payload: serde_json::Value) -> Result<()> {
+        let message = MqttMessage {
+            topic,
+            payload: payload.to_string().into_bytes(),
+        };
+        self.tx
+            .try_send(message)
+            .map_err(|e| anyhow!("MQTT queue unavailable, message dropped: {}", e))
+    }
+}
+
+#[async_trait]
+impl Notifier for MqttNotifier {
+    fn name(&self) -> &str {
+        "mqtt"
+    }
+
+    async fn notify(&self, alert: &Alert) -> Result<()> {
+        self.enqueue(format!("{}/alerts/{}", self.topic_prefix, alert.strategy), alert_payload(alert))
+    }
+
+    async fn notify_digest(&self, digest: &MissedDigest) -> Result<()> {
+        self.enqueue(format!("{}/digest", self.topic_prefix), digest_payload(digest))
+    }
+
+    async fn notify_alert_digest(&self, digest: &AlertDigest) -> Result<()> {
+        self.enqueue(format!("{}/alert_digest", self.topic_prefix), alert_digest_payload(digest))
+    }
+
+    async fn notify_kill_switch(&self, event: &KillSwitchEvent) -> Result<()> {
+        self.enqueue(format!("{}/kill_switch", self.topic_prefix), kill_switch_payload(event))
+    }
+
+    async fn notify_contract_state(&self, change: &ContractStateChange) -> Result<()> {
+        self.enqueue(format!("{}/contract_state", self.topic_prefix), contract_state_payload(change))
+    }
+
+    async fn notify_new_listing(&self, listing: &NewListing) -> Result<()> {
+        self.enqueue(format!("{}/pre_listing", self.topic_prefix), new_listing_payload(listing))
+    }
+}
+
+struct MqttPublisher {
+    config: MqttConfig,
+    password: Option<String>,
+    rx: Mutex<mpsc::Receiver<MqttMessage>>,
+}
+
+impl MqttPublisher {
+    async fn run(self: Arc<Self>) {
+        let mut rx = self.rx.lock().await;
+        // Message whose delivery was cut short by a connection failure; resent first
+        let mut unacked: Option<MqttMessage> = None;
+        let mut packet_id: u16 = 0;
+
+        loop {
+            let mut session = match self.connect().await {
+                Ok(session) => session,
+                Err(e) => {
+                    warn!("[MQTT] Cannot connect to {}:{}: {:#}", self.config.host, self.config.port, e);
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                    continue;
+                }
+            };
+            info!("[MQTT] Connected to {}:{} (QoS {})", self.config.host, self.config.port, self.config.qos);
+
+            let keep_alive = Duration::from_secs(self.config.keep_alive_secs.max(2));
+            let mut ping = tokio::time::interval(keep_alive / 2);
+            ping.tick().await;
+
+            let result: Result<()> = async {
+                loop {
+                    let (message, dup) = match unacked.take() {
+                        Some(message) => (message, true),
+                        None => tokio::select! {
+                            message = rx.recv() => match message {
+                                Some(message) => (message, false),
+                                None => return Ok(()),
+                            },
+                            _ = ping.tick() => {
+                                session.ping().await?;
+                                continue;
+                            }
+                        },
+                    };
+
+                    packet_id = packet_id.wrapping_add(1).max(1);
+                    if let Err(e) = session.publish(&message, packet_id, dup).await {
+                        // QoS 0 has no delivery guarantee to keep up
+                        if self.config.qos > 0 {
+                            unacked = Some(message);
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            .await;
+
+            match result {
+                Ok(()) => return,
+                Err(e) => warn!("[MQTT] Connection lost: {:#} - reconnecting", e),
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    async fn connect(&self) -> Result<Session> {
+        let io_timeout = Duration::from_secs(self.config.keep_alive_secs.max(2));
+        let address = (self.config.host.as_str(), self.config.port);
+        let tcp = timeout(io_timeout, TcpStream::connect(address))
+            .await
+            .context("connect timed out")??;
+
+        let stream: Box<dyn Transport> = if self.config.tls {
+            let mut builder = native_tls::TlsConnector::builder();
+            if let Some(ref path) = self.config.ca_cert {
+                let pem = std::fs::read(path).with_context(|| format!("reading CA certificate {}", path))?;
+                builder.add_root_certificate(native_tls::Certificate::from_pem(&pem)?);
+            }
+            builder.danger_accept_invalid_certs(self.config.accept_invalid_certs);
+            let connector = tokio_native_tls::TlsConnector::from(builder.build()?);
+            Box::new(connector.connect(&self.config.host, tcp).await?)
+        } else {
+            Box::new(tcp)
+        };
+
+        let mut session = Session {
+            stream,
+            qos: self.config.qos.min(2),
+            retain: self.config.retain,
+            io_timeout,
+            pending: VecDeque::new(),
+        };
+        session.handshake(&self.config, self.password.as_deref()).await?;
+        Ok(session)
+    }
+}
+
+/// An established broker connection
+struct Session {
+    stream: Box<dyn Transport>,
+    qos: u8,
+    retain: bool,
+    io_timeout: Duration,
+    /// Packets that arrived while another one was awaited, e.g. a PUBACK during a ping
+    pending: VecDeque<(u8, Vec<u8>)>,
+}
+
+impl Session {
+    async fn handshake(&mut self, config: &MqttConfig, password: Option<&str>) -> Result<()> {
+        let mut flags = 0x02; // clean session
+        let mut payload = Vec::new();
+        put_string(&mut payload, &config.client_id)?;
+        if !config.username.is_empty() {
+            flags |= 0x80;
+            put_string(&mut payload, &config.username)?;
+            if let Some(password) = password {
+                flags |= 0x40;
+                put_string(&mut payload, password)?;
+            }
+        }
+
+        let mut body = Vec::new();
+        put_string(&mut body, "MQTT")?;
+        body.push(4); // protocol level 3.1.1
+        body.push(flags);
+        body.extend_from_slice(&(config.keep_alive_secs as u16).to_be_bytes());
+        body.extend_from_slice(&payload);
+        self.write_packet(CONNECT, &body).await?;
+
+        let (header, body) = self.read_packet().await?;
+        if header & 0xF0 != CONNACK || body.len() < 2 {
+            bail!("unexpected reply to CONNECT (packet type {:#04x})", header);
+        }
+        match body[1] {
+            0 => Ok(()),
+            1 => bail!("broker refused the connection: unacceptable protocol version"),
+            2 => bail!("broker refused the connection: client id rejected"),
+            3 => bail!("broker refused the connection: server unavailable"),
+            4 => bail!("broker refused the connection: bad username or password"),
+            5 => bail!("broker refused the connection: not authorized"),
+            code => bail!("broker refused the connection (code {})", code),
+        }
+    }
+
+    async fn publish(&mut self, message: &MqttMessage, packet_id: u16, dup: bool) -> Result<()> {
+        let mut header = PUBLISH | (self.qos << 1);
+        if self.retain {
+            header |= 0x01;
+        }
+        if dup {
+            header |= 0x08;
+        }
+
+        let mut body = Vec::with_capacity(message.topic.len() + message.payload.len() + 4);
+        put_string(&mut body, &message.topic)?;
+        if self.qos > 0 {
+            body.extend_from_slice(&packet_id.to_be_bytes());
+        }
+        body.extend_from_slice(&message.payload);
+        self.write_packet(header, &body).await?;
+
+        match self.qos {
+            0 => Ok(()),
+            1 => self.expect_ack(PUBACK, packet_id).await,
+            _ => {
+                self.expect_ack(PUBREC, packet_id).await?;
+                self.write_packet(PUBREL, &packet_id.to_be_bytes()).await?;
+                self.expect_ack(PUBCOMP, packet_id).await
+            }
+        }
+    }
+
+    async fn ping(&mut self) -> Result<()> {
+        self.write_packet(PINGREQ, &[]).await?;
+        self.read_matching(|header, _| header & 0xF0 == PINGRESP).await?;
+        Ok(())
+    }
+
+    async fn expect_ack(&mut self, kind: u8, packet_id: u16) -> Result<()> {
+        self.read_matching(|header, body| {
+            let acked = body.get(..2).map(|id| u16::from_be_bytes([id[0], id[1]]));
+            header & 0xF0 == kind && acked == Some(packet_id)
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the first packet `wanted` accepts, earlier pending ones first; the packets
+    /// passed over are kept for a later wait instead of being dropped
+    async fn read_matching(&mut self, wanted: impl Fn(u8, &[u8]) -> bool) -> Result<(u8, Vec<u8>)> {
+        if let Some(index) = self.pending.iter().position(|(header, body)| wanted(*header, body)) {
+            return Ok(self.pending.remove(index).expect("index is in range"));
+        }
+        loop {
+            let (header, body) = self.read_packet().await?;
+            if wanted(header, &body) {
+                return Ok((header, body));
+            }
+            if self.pending.len() == MAX_PENDING_PACKETS {
+                self.pending.pop_front();
+            }
+            self.pending.push_back((header, body));
+        }
+    }
+
+    async fn write_packet(&mut self, header: u8, body: &[u8]) -> Result<()> {
+        let mut packet = Vec::with_capacity(body.len() + 5);
+        packet.push(header);
+        put_remaining_length(&mut packet, body.len())?;
+        packet.extend_from_slice(body);
+        timeout(self.io_timeout, self.stream.write_all(&packet))
+            .await
+            .context("write timed out")??;
+        Ok(())
+    }
+
+    async fn read_packet(&mut self) -> Result<(u8, Vec<u8>)> {
+        timeout(self.io_timeout, async {
+            let header = self.stream.read_u8().await?;
+            let mut length = 0usize;
+            let mut shift = 0;
+            loop {
+                let byte = self.stream.read_u8().await?;
+                length |= ((byte & 0x7F) as usize) << shift;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+                shift += 7;
+                if shift > 21 {
+                    bail!("malformed packet length");
+                }
+            }
+            let mut body = vec![0u8; length];
+            self.stream.read_exact(&mut body).await?;
+            Ok((header, body))
+        })
+        .await
+        .context("no response from broker")?
+    }
+}
+
+fn put_string(buf: &mut Vec<u8>, value: &str) -> Result<()> {
+    let Ok(length) = u16::try_from(value.len()) else {
+        bail!("MQTT string too long ({} bytes, at most {})", value.len(), u16::MAX);
+    };
+    buf.extend_from_slice(&length.to_be_bytes());
+    buf.extend_from_slice(value.as_bytes());
+    Ok(())
+}
+
+fn put_remaining_length(buf: &mut Vec<u8>, mut length: usize) -> Result<()> {
+    if length > 268_435_455 {
+        bail!("MQTT packet too large ({} bytes)", length);
+    }
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        buf.push(byte);
+        if length == 0 {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::alerts::{Alert, AlertDigest, KillSwitchEvent, MissedDigest};
+use crate::models::{ContractStateChange, NewListing};
+use anyhow::Result;
+use async_trait::async_trait;
+use tracing::{info, warn};
+
+/// Destination for alerts (log, chat, webhook, ...)
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Internal consumers (e.g. precision scoring) see every alert regardless of routing
+    fn is_internal(&self) -> bool {
+        false
+    }
+
+    async fn notify(&self, alert: &Alert) -> Result<()>;
+
+    /// Summary of anomalies that happened while the detector was offline
+    async fn notify_digest(&self, digest: &MissedDigest) -> Result<()>;
+
+    /// Alerts collapsed into one message while digest mode is on
+    async fn notify_alert_digest(&self, digest: &AlertDigest) -> Result<()>;
+
+    /// Notifications and execution were paused or resumed by the kill switch
+    async fn notify_kill_switch(&self, event: &KillSwitchEvent) -> Result<()>;
+
+    /// A monitored contract was paused, went into settlement, was delisted or resumed
+    async fn notify_contract_state(&self, change: &ContractStateChange) -> Result<()>;
+
+    /// A contract appeared in the contract details, usually ahead of its first trades
+    async fn notify_new_listing(&self, listing: &NewListing) -> Result<()>;
+}
+
+/// Writes alerts with their trigger explanation to the application log
+pub struct LogNotifier;
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    fn name(&self) -> &str {
+        "log"
+    }
+
+    async fn notify(&self, alert: &Alert) -> Result<()> {
+        info!("[Alert] {}", alert.summary());
+        info!("[Alert]   Why: {}", alert.explanation);
+        Ok(())
+    }
+
+    async fn notify_digest(&self, digest: &MissedDigest) -> Result<()> {
+        for line in digest.summary().lines() {
+            info!("[Digest] {}", line);
+        }
+        Ok(())
+    }
+
+    async fn notify_alert_digest(&self, digest: &AlertDigest) -> Result<()> {
+        for line in digest.summary().lines() {
+            info!("[AlertDigest] {}", line);
+        }
+        Ok(())
+    }
+
+    async fn notify_kill_switch(&self, event: &KillSwitchEvent) -> Result<()> {
+        warn!("[KillSwitch] {}", event);
+        Ok(())
+    }
+
+    async fn notify_contract_state(&self, change: &ContractStateChange) -> Result<()> {
+        warn!("[ContractState] {}", change);
+        Ok(())
+    }
+
+    async fn notify_new_listing(&self, listing: &NewListing) -> Result<()> {
+        info!("[PreListing] {}", listing);
+        Ok(())
+    }
+}
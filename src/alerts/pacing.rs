@@ -0,0 +1,59 @@
+use crate::alerts::Alert;
+use crate::config::CooldownConfig;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+/// Which cooldown held an alert back, and for how much longer
+#[derive(Debug, Clone, Copy)]
+pub enum Paced {
+    /// Another strategy alerted on the symbol too recently
+    Symbol { remaining: Duration },
+    /// Any alert went out too recently
+    Global { remaining: Duration },
+}
+
+/// The cooldown scopes wider than one strategy on one symbol (which the episode trackers
+/// handle): per symbol across all strategies, and a minimum gap between any two alerts.
+/// Only delivered alerts start a cooldown.
+pub struct AlertPacer {
+    per_symbol: Duration,
+    global: Duration,
+    last_by_symbol: HashMap<String, DateTime<Utc>>,
+    last_any: Option<DateTime<Utc>>,
+}
+
+impl AlertPacer {
+    pub fn new(config: &CooldownConfig) -> Self {
+        Self {
+            per_symbol: Duration::seconds(config.per_symbol_any_strategy_seconds as i64),
+            global: Duration::seconds(config.global_seconds as i64),
+            last_by_symbol: HashMap::new(),
+            last_any: None,
+        }
+    }
+
+    /// Whether `alert` is clear of both cooldowns
+    pub fn check(&self, alert: &Alert) -> Result<(), Paced> {
+        let at = alert.timestamp;
+        let remaining = |last: DateTime<Utc>, gap: Duration| Some(last + gap - at).filter(|r| *r > Duration::zero());
+
+        if let Some(remaining) = self.last_any.and_then(|last| remaining(last, self.global)) {
+            return Err(Paced::Global { remaining });
+        }
+        if let Some(remaining) = self.last_by_symbol.get(&alert.symbol).and_then(|&last| remaining(last, self.per_symbol)) {
+            return Err(Paced::Symbol { remaining });
+        }
+        Ok(())
+    }
+
+    /// Starts both cooldowns from a delivered alert
+    pub fn record(&mut self, alert: &Alert) {
+        let at = alert.timestamp;
+        let per_symbol = self.per_symbol;
+        self.last_by_symbol.retain(|_, last| *last + per_symbol > at);
+        if per_symbol > Duration::zero() {
+            self.last_by_symbol.insert(alert.symbol.clone(), at);
+        }
+        self.last_any = Some(at);
+    }
+}
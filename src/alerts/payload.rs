@@ -0,0 +1,111 @@
+use crate::alerts::{Alert, AlertDigest, KillSwitchEvent, MissedDigest};
+use crate::models::{ContractStateChange, NewListing};
+use serde_json::json;
+
+/// JSON body of an alert, shared by the MQTT and ZeroMQ notifiers
+pub(crate) fn alert_payload(alert: &Alert) -> serde_json::Value {
+    json!({
+        "strategy": alert.strategy,
+        "episode_id": alert.episode_id,
+        "symbol": alert.symbol,
+        "ratio": alert.ratio,
+        "last_price": alert.last_price,
+        "mark_price": alert.mark_price,
+        "stats_24h": alert.stats_24h.map(|s| json!({
+            "volume_usdt": s.volume_usdt,
+            "high": s.high,
+            "low": s.low,
+            "change_pct": s.change_pct,
+        })),
+        "market_moves": alert
+            .market_moves
+            .iter()
+            .map(|m| (m.symbol.clone(), json!(m.change_pct)))
+            .collect::<serde_json::Map<_, _>>(),
+        "timestamp": alert.timestamp.to_rfc3339(),
+        "explanation": alert.explanation.to_string(),
+        "source": alert.source.name(),
+        "note": alert.source.note(),
+    })
+}
+
+pub(crate) fn digest_payload(digest: &MissedDigest) -> serde_json::Value {
+    let anomalies: Vec<_> = digest
+        .anomalies
+        .iter()
+        .map(|a| {
+            json!({
+                "symbol": a.symbol,
+                "minute": a.minute.to_rfc3339(),
+                "peak_ratio": a.peak_ratio,
+            })
+        })
+        .collect();
+    json!({
+        "offline_from": digest.offline_from.to_rfc3339(),
+        "offline_until": digest.offline_until.to_rfc3339(),
+        "truncated": digest.truncated,
+        "anomalies": anomalies,
+    })
+}
+
+pub(crate) fn alert_digest_payload(digest: &AlertDigest) -> serde_json::Value {
+    let symbols: Vec<_> = digest
+        .symbols
+        .iter()
+        .map(|s| {
+            json!({
+                "symbol": s.symbol,
+                "peak_ratio": s.peak_ratio,
+                "strategies": s.strategies,
+                "alerts": s.alerts,
+            })
+        })
+        .collect();
+    json!({
+        "from": digest.from.to_rfc3339(),
+        "until": digest.until.to_rfc3339(),
+        "alerts": digest.alerts,
+        "symbols": symbols,
+        "more_symbols": digest.more_symbols,
+        "more_alerts": digest.more_alerts,
+        "last": digest.last,
+    })
+}
+
+pub(crate) fn kill_switch_payload(event: &KillSwitchEvent) -> serde_json::Value {
+    match event {
+        KillSwitchEvent::Tripped { at, starts_per_min, threshold } => json!({
+            "state": "tripped",
+            "timestamp": at.to_rfc3339(),
+            "starts_per_min": starts_per_min,
+            "threshold": threshold,
+        }),
+        KillSwitchEvent::Resumed { at, paused_since, held_back } => json!({
+            "state": "resumed",
+            "timestamp": at.to_rfc3339(),
+            "paused_since": paused_since.to_rfc3339(),
+            "held_back": held_back,
+        }),
+    }
+}
+
+pub(crate) fn contract_state_payload(change: &ContractStateChange) -> serde_json::Value {
+    json!({
+        "symbol": change.symbol,
+        "state": change.current.to_string(),
+        "previous": change.previous.to_string(),
+        "trading": change.current.is_trading(),
+        "timestamp": change.at.to_rfc3339(),
+    })
+}
+
+pub(crate) fn new_listing_payload(listing: &NewListing) -> serde_json::Value {
+    json!({
+        "symbol": listing.symbol,
+        "state": listing.state.to_string(),
+        "opens_at": listing.opens_at.map(|at| at.to_rfc3339()),
+        "subscribed": listing.subscribed,
+        "timestamp": listing.at.to_rfc3339(),
+    })
+}
@@ -0,0 +1,63 @@
+use crate::alerts::Alert;
+use crate::config::AlertSuppressionConfig;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Spread of the alert in percent, the measure re-alerts are compared by
+pub fn alert_severity(alert: &Alert) -> f64 {
+    (alert.ratio - 1.0) * 100.0
+}
+
+/// Why an alert was held back
+#[derive(Debug, Clone, Copy)]
+pub struct Suppressed {
+    pub severity: f64,
+    /// Severity the alert would have needed
+    pub required: f64,
+}
+
+/// Remembers the last delivered alert per symbol and holds back repeats that aren't
+/// sufficiently stronger while its window lasts
+pub struct AlertSuppressor {
+    config: AlertSuppressionConfig,
+    // symbol -> (severity, time) of the last delivered alert
+    last_delivered: HashMap<String, (f64, DateTime<Utc>)>,
+}
+
+impl AlertSuppressor {
+    pub fn new(config: AlertSuppressionConfig) -> Self {
+        Self {
+            config,
+            last_delivered: HashMap::new(),
+        }
+    }
+
+    /// Decides whether `alert` goes out; a delivered alert becomes the symbol's new reference
+    pub fn check(&mut self, alert: &Alert) -> Result<(), Suppressed> {
+        let severity = alert_severity(alert);
+        if let Some(required) = self.required_severity(&alert.symbol, alert.timestamp) {
+            if severity < required {
+                return Err(Suppressed { severity, required });
+            }
+        }
+
+        self.last_delivered.insert(alert.symbol.clone(), (severity, alert.timestamp));
+        Ok(())
+    }
+
+    /// Severity a new alert at `at` needs, None once the window has passed
+    fn required_severity(&mut self, symbol: &str, at: DateTime<Utc>) -> Option<f64> {
+        let (severity, delivered_at) = *self.last_delivered.get(symbol)?;
+        let elapsed_mins = (at - delivered_at).num_milliseconds().max(0) as f64 / 60_000.0;
+        if elapsed_mins >= self.config.window_mins as f64 {
+            self.last_delivered.remove(symbol);
+            return None;
+        }
+
+        let decayed = match self.config.half_life_mins {
+            Some(half_life) if half_life > 0.0 => severity * 0.5f64.powf(elapsed_mins / half_life),
+            _ => severity,
+        };
+        Some(decayed * (1.0 + self.config.min_increase_pct / 100.0))
+    }
+}
@@ -0,0 +1,185 @@
+use crate::alerts::{Alert, AlertDigest, KillSwitchEvent, MissedDigest, Notifier, SignalSource};
+use crate::config::TelegramConfig;
+use crate::detection::format_moves;
+use crate::models::{ContractStateChange, NewListing};
+use crate::utils::TaskSupervisor;
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+use tracing::{info, warn};
+
+// Telegram rejects longer messages
+const MAX_MESSAGE_CHARS: usize = 4096;
+
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Sends alerts as chat messages through a Telegram bot.
+///
+/// Messages are delivered in order by a supervised background task, so a slow or
+/// unreachable Bot API never holds up the other notifiers; they are queued meanwhile.
+pub struct TelegramNotifier {
+    tx: mpsc::Sender<String>,
+}
+
+impl TelegramNotifier {
+    /// Fails without a bot token (`bot_token` or `TELEGRAM_BOT_TOKEN`) or chat id
+    pub fn start(config: TelegramConfig, supervisor: &TaskSupervisor) -> Result<Self> {
+        let token = std::env::var("TELEGRAM_BOT_TOKEN")
+            .ok()
+            .filter(|t| !t.is_empty())
+            .or_else(|| config.bot_token.clone().filter(|t| !t.is_empty()))
+            .ok_or_else(|| anyhow!("no bot token: set TELEGRAM_BOT_TOKEN or [alerts.telegram] bot_token"))?;
+        if config.chat_id.is_empty() {
+            bail!("[alerts.telegram] chat_id is empty");
+        }
+
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+            .build()?;
+        let (tx, rx) = mpsc::channel(config.queue_size.max(1));
+        let sender = Arc::new(TelegramSender {
+            url: format!("{}/bot{}/sendMessage", config.api_url.trim_end_matches('/'), token),
+            config,
+            http,
+            rx: Mutex::new(rx),
+        });
+        supervisor.supervise("telegram", move || sender.clone().run());
+
+        Ok(Self { tx })
+    }
+
+    fn enqueue(&self, text: String) -> Result<()> {
+        self.tx
+            .try_send(truncate(text))
+            .map_err(|e| anyhow!("Telegram queue unavailable, message dropped: {}", e))
+    }
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    async fn notify(&self, alert: &Alert) -> Result<()> {
+        let mut text = format!(
+            "🚨 {} | {}\nRatio: {:.4}\nLast: {:.8}\nFair: {:.8}",
+            alert.strategy, alert.symbol, alert.ratio, alert.last_price, alert.mark_price
+        );
+        if let Some(ref stats) = alert.stats_24h {
+            text.push_str(&format!("\n24h: {}", stats));
+        }
+        if !alert.market_moves.is_empty() {
+            text.push_str(&format!("\nMarket: {}", format_moves(&alert.market_moves)));
+        }
+        if alert.source != SignalSource::Detector {
+            text.push_str(&format!("\nSource: {}", alert.source));
+        }
+        text.push_str(&format!("\nWhy: {}", alert.explanation));
+        text.push_str(&format!(
+            "\nEpisode: {} at {}",
+            alert.episode_id,
+            alert.timestamp.format("%H:%M:%S UTC")
+        ));
+        self.enqueue(text)
+    }
+
+    async fn notify_digest(&self, digest: &MissedDigest) -> Result<()> {
+        self.enqueue(format!("📋 Missed while offline\n{}", digest.summary()))
+    }
+
+    async fn notify_alert_digest(&self, digest: &AlertDigest) -> Result<()> {
+        self.enqueue(format!("🗂 {}", digest.summary()))
+    }
+
+    async fn notify_kill_switch(&self, event: &KillSwitchEvent) -> Result<()> {
+        self.enqueue(format!("⛔ {}", event))
+    }
+
+    async fn notify_contract_state(&self, change: &ContractStateChange) -> Result<()> {
+        self.enqueue(format!("⚠️ {}", change))
+    }
+
+    async fn notify_new_listing(&self, listing: &NewListing) -> Result<()> {
+        self.enqueue(format!("🆕 {}", listing))
+    }
+}
+
+struct TelegramSender {
+    config: TelegramConfig,
+    url: String,
+    http: reqwest::Client,
+    rx: Mutex<mpsc::Receiver<String>>,
+}
+
+impl TelegramSender {
+    async fn run(self: Arc<Self>) {
+        let mut rx = self.rx.lock().await;
+        info!("[Telegram] Sending alerts to chat {}", self.config.chat_id);
+
+        while let Some(text) = rx.recv().await {
+            // Retried until delivered or rejected, which keeps the messages in order
+            loop {
+                match self.send(&text).await {
+                    Ok(()) => break,
+                    Err(Delivery::Retry(delay, reason)) => {
+                        warn!("[Telegram] {} - retrying in {}s", reason, delay.as_secs());
+                        tokio::time::sleep(delay).await;
+                    }
+                    Err(Delivery::Rejected(reason)) => {
+                        warn!("[Telegram] Message dropped: {}", reason);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn send(&self, text: &str) -> std::result::Result<(), Delivery> {
+        let body = json!({
+            "chat_id": self.config.chat_id,
+            "text": text,
+            "disable_web_page_preview": true,
+            "disable_notification": self.config.silent,
+        });
+        let response = self
+            .http
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await
+            // The error text would contain the URL and with it the bot token
+            .map_err(|e| Delivery::Retry(RETRY_DELAY, format!("Bot API unreachable: {}", e.without_url())))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let reply: serde_json::Value = response.json().await.unwrap_or_default();
+        let description = reply["description"].as_str().unwrap_or("no description").to_string();
+        if status.as_u16() == 429 {
+            let retry_after = reply["parameters"]["retry_after"].as_u64().unwrap_or(RETRY_DELAY.as_secs());
+            return Err(Delivery::Retry(Duration::from_secs(retry_after), "rate limited".to_string()));
+        }
+        if status.is_server_error() {
+            return Err(Delivery::Retry(RETRY_DELAY, format!("Bot API error {}: {}", status, description)));
+        }
+        Err(Delivery::Rejected(format!("{}: {}", status, description)))
+    }
+}
+
+enum Delivery {
+    Retry(Duration, String),
+    Rejected(String),
+}
+
+fn truncate(mut text: String) -> String {
+    if text.chars().count() > MAX_MESSAGE_CHARS {
+        text = text.chars().take(MAX_MESSAGE_CHARS - 1).collect();
+        text.push('…');
+    }
+    text
+}
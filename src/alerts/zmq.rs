@@ -0,0 +1,365 @@
+use crate::alerts::payload::{alert_digest_payload, alert_payload, contract_state_payload, digest_payload, kill_switch_payload, new_listing_payload};
+use crate::alerts::{
+    Alert, AlertDigest, KillSwitchEvent, MissedDigest, Notifier, ALERT_DIGEST_ROUTE, CONTRACT_STATE_ROUTE, DIGEST_ROUTE,
+    KILL_SWITCH_ROUTE, PRE_LISTING_ROUTE,
+};
+use crate::config::ZmqConfig;
+use crate::models::{ContractStateChange, NewListing};
+use crate::utils::TaskSupervisor;
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::os::unix::fs::FileTypeExt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, UnixListener};
+use tokio::sync::broadcast;
+use tokio::time::timeout;
+use tracing::{info, warn};
+
+// ZMTP 3.0 frame flags
+const FLAG_MORE: u8 = 0x01;
+const FLAG_LONG: u8 = 0x02;
+const FLAG_COMMAND: u8 = 0x04;
+
+const GREETING_LEN: usize = 64;
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+const REBIND_DELAY: Duration = Duration::from_secs(5);
+
+// Frames from subscribers are subscriptions and small commands; anything bigger is a broken peer
+const MAX_INBOUND_FRAME: u64 = 64 * 1024;
+
+/// One message for subscribers: a topic frame followed by a JSON frame
+#[derive(Debug, Clone)]
+struct ZmqMessage {
+    topic: Arc<str>,
+    payload: Arc<[u8]>,
+}
+
+trait Transport: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Transport for T {}
+
+/// Publishes alerts on a ZeroMQ PUB socket, for colocated processes (e.g. an execution
+/// engine) that want them over local IPC rather than HTTP.
+///
+/// The socket speaks ZMTP 3.0 with the NULL mechanism, so any libzmq SUB socket can connect
+/// to it. Alerts are published with the strategy name as topic, and subscribers filter by
+/// topic prefix as usual. Like a libzmq PUB socket, nothing is queued for absent subscribers
+/// and a subscriber more than `queue_size` messages behind loses the oldest ones.
+pub struct ZmqNotifier {
+    tx: broadcast::Sender<ZmqMessage>,
+}
+
+impl ZmqNotifier {
+    pub fn start(config: ZmqConfig, supervisor: &TaskSupervisor) -> Self {
+        let (tx, _) = broadcast::channel(config.queue_size.max(1));
+        let publisher = Arc::new(ZmqPublisher { config, tx: tx.clone() });
+        supervisor.supervise("zmq", move || publisher.clone().run());
+        Self { tx }
+    }
+
+    fn publish(&self, topic: &str, payload: serde_json::Value) -> Result<()> {
+        let message = ZmqMessage {
+            topic: topic.into(),
+            payload: payload.to_string().into_bytes().into(),
+        };
+        // Without subscribers the message is dropped, as by any PUB socket
+        let _ = self.tx.send(message);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Notifier for ZmqNotifier {
+    fn name(&self) -> &str {
+        "zmq"
+    }
+
+    async fn notify(&self, alert: &Alert) -> Result<()> {
+        self.publish(&alert.strategy, alert_payload(alert))
+    }
+
+    async fn notify_digest(&self, digest: &MissedDigest) -> Result<()> {
+        self.publish(DIGEST_ROUTE, digest_payload(digest))
+    }
+
+    async fn notify_alert_digest(&self, digest: &AlertDigest) -> Result<()> {
+        self.publish(ALERT_DIGEST_ROUTE, alert_digest_payload(digest))
+    }
+
+    async fn notify_kill_switch(&self, event: &KillSwitchEvent) -> Result<()> {
+        self.publish(KILL_SWITCH_ROUTE, kill_switch_payload(event))
+    }
+
+    async fn notify_contract_state(&self, change: &ContractStateChange) -> Result<()> {
+        self.publish(CONTRACT_STATE_ROUTE, contract_state_payload(change))
+    }
+
+    async fn notify_new_listing(&self, listing: &NewListing) -> Result<()> {
+        self.publish(PRE_LISTING_ROUTE, new_listing_payload(listing))
+    }
+}
+
+enum Listener {
+    Tcp(TcpListener),
+    Ipc(UnixListener),
+}
+
+impl Listener {
+    /// Binds `tcp://host:port` or `ipc:///path/to/socket`
+    async fn bind(endpoint: &str) -> Result<Self> {
+        if let Some(addr) = endpoint.strip_prefix("tcp://") {
+            // libzmq spells "all interfaces" as `*`
+            let addr = addr.replacen('*', "0.0.0.0", 1);
+            return Ok(Listener::Tcp(TcpListener::bind(&addr).await?));
+        }
+        if let Some(path) = endpoint.strip_prefix("ipc://") {
+            // A socket file left behind by an earlier run blocks the bind; anything else at
+            // the path is not ours to delete
+            match std::fs::symlink_metadata(path) {
+                Ok(metadata) if metadata.file_type().is_socket() => {
+                    std::fs::remove_file(path).with_context(|| format!("removing stale socket {}", path))?
+                }
+                Ok(_) => bail!("{} exists and is not a socket", path),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e).with_context(|| format!("checking {}", path)),
+            }
+            return Ok(Listener::Ipc(UnixListener::bind(path)?));
+        }
+        bail!("unsupported endpoint {:?}, expected tcp://host:port or ipc://path", endpoint)
+    }
+
+    async fn accept(&self) -> Result<(Box<dyn Transport>, String)> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, addr) = listener.accept().await?;
+                stream.set_nodelay(true)?;
+                Ok((Box::new(stream), addr.to_string()))
+            }
+            Listener::Ipc(listener) => {
+                let (stream, _) = listener.accept().await?;
+                Ok((Box::new(stream), "ipc peer".to_string()))
+            }
+        }
+    }
+}
+
+struct ZmqPublisher {
+    config: ZmqConfig,
+    tx: broadcast::Sender<ZmqMessage>,
+}
+
+impl ZmqPublisher {
+    async fn run(self: Arc<Self>) {
+        let listener = loop {
+            match Listener::bind(&self.config.endpoint).await {
+                Ok(listener) => break listener,
+                Err(e) => {
+                    warn!("[ZMQ] Cannot bind {}: {:#}", self.config.endpoint, e);
+                    tokio::time::sleep(REBIND_DELAY).await;
+                }
+            }
+        };
+        info!("[ZMQ] Publishing alerts on {}", self.config.endpoint);
+
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("[ZMQ] Accept failed: {:#}", e);
+                    continue;
+                }
+            };
+            // Subscribe before the handshake so nothing published meanwhile is missed
+            let rx = self.tx.subscribe();
+            tokio::spawn(async move {
+                match serve_subscriber(stream, rx).await {
+                    Ok(()) => info!("[ZMQ] Subscriber {} disconnected", peer),
+                    Err(e) => warn!("[ZMQ] Subscriber {} dropped: {:#}", peer, e),
+                }
+            });
+        }
+    }
+}
+
+/// Topic prefixes a subscriber asked for, counted like libzmq does for repeated subscriptions
+#[derive(Default)]
+struct Subscriptions(HashMap<Vec<u8>, usize>);
+
+impl Subscriptions {
+    fn matches(&self, topic: &[u8]) -> bool {
+        self.0.keys().any(|prefix| topic.starts_with(prefix))
+    }
+
+    fn subscribe(&mut self, prefix: &[u8]) {
+        *self.0.entry(prefix.to_vec()).or_default() += 1;
+    }
+
+    fn cancel(&mut self, prefix: &[u8]) {
+        if let Some(count) = self.0.get_mut(prefix) {
+            *count -= 1;
+            if *count == 0 {
+                self.0.remove(prefix);
+            }
+        }
+    }
+}
+
+async fn serve_subscriber(mut stream: Box<dyn Transport>, mut rx: broadcast::Receiver<ZmqMessage>) -> Result<()> {
+    timeout(HANDSHAKE_TIMEOUT, handshake(&mut stream))
+        .await
+        .context("handshake timed out")??;
+
+    let subscriptions = Mutex::new(Subscriptions::default());
+    let (mut reader, mut writer) = tokio::io::split(stream);
+
+    let read_subscriptions = async {
+        loop {
+            let Some((flags, body)) = read_frame(&mut reader).await? else {
+                return Ok(());
+            };
+            let mut subscriptions = subscriptions.lock().unwrap();
+            if flags & FLAG_COMMAND != 0 {
+                // ZMTP 3.1 peers send subscriptions as commands
+                match parse_command(&body) {
+                    Some((b"SUBSCRIBE", prefix)) => subscriptions.subscribe(prefix),
+                    Some((b"CANCEL", prefix)) => subscriptions.cancel(prefix),
+                    _ => {}
+                }
+            } else {
+                match body.split_first() {
+                    Some((1, prefix)) => subscriptions.subscribe(prefix),
+                    Some((0, prefix)) => subscriptions.cancel(prefix),
+                    _ => {}
+                }
+            }
+        }
+    };
+
+    let write_messages = async {
+        loop {
+            let message = match rx.recv().await {
+                Ok(message) => message,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!("[ZMQ] Subscriber fell behind - {} messages dropped", missed);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok::<(), anyhow::Error>(()),
+            };
+            if !subscriptions.lock().unwrap().matches(message.topic.as_bytes()) {
+                continue;
+            }
+            let mut bytes = Vec::with_capacity(message.topic.len() + message.payload.len() + 18);
+            encode_frame(&mut bytes, FLAG_MORE, message.topic.as_bytes());
+            encode_frame(&mut bytes, 0, &message.payload);
+            writer.write_all(&bytes).await?;
+        }
+    };
+
+    tokio::select! {
+        result = read_subscriptions => result,
+        result = write_messages => result,
+    }
+}
+
+/// Exchanges greetings and READY commands with a connecting SUB socket
+async fn handshake(stream: &mut Box<dyn Transport>) -> Result<()> {
+    let mut greeting = [0u8; GREETING_LEN];
+    greeting[0] = 0xFF;
+    greeting[9] = 0x7F;
+    greeting[10] = 3; // version 3.0
+    greeting[12..16].copy_from_slice(b"NULL");
+    stream.write_all(&greeting).await?;
+
+    let mut peer = [0u8; GREETING_LEN];
+    stream.read_exact(&mut peer).await?;
+    if peer[0] != 0xFF || peer[9] & 0x01 == 0 {
+        bail!("not a ZMTP peer");
+    }
+    if peer[10] < 3 {
+        bail!("ZMTP {}.{} is not supported, 3.0 or newer is needed", peer[10], peer[11]);
+    }
+    let mechanism = &peer[12..32];
+    if !mechanism.starts_with(b"NULL") || mechanism[4..].iter().any(|&b| b != 0) {
+        bail!("security mechanism {:?} is not supported", String::from_utf8_lossy(mechanism).trim_end_matches('\0'));
+    }
+
+    let mut ready = Vec::new();
+    push_short_string(&mut ready, b"READY");
+    push_short_string(&mut ready, b"Socket-Type");
+    ready.extend_from_slice(&3u32.to_be_bytes());
+    ready.extend_from_slice(b"PUB");
+    let mut frame = Vec::new();
+    encode_frame(&mut frame, FLAG_COMMAND, &ready);
+    stream.write_all(&frame).await?;
+
+    let (flags, body) = read_frame(stream).await?.ok_or_else(|| anyhow!("closed during the handshake"))?;
+    let properties = match parse_command(&body) {
+        Some((b"READY", properties)) if flags & FLAG_COMMAND != 0 => properties,
+        Some((b"ERROR", reason)) => bail!("peer refused: {}", String::from_utf8_lossy(reason)),
+        _ => bail!("expected a READY command"),
+    };
+    let socket_type = ready_property(properties, b"Socket-Type").unwrap_or_default();
+    if socket_type != b"SUB" && socket_type != b"XSUB" {
+        bail!("{} sockets cannot connect to a PUB socket", String::from_utf8_lossy(socket_type));
+    }
+    Ok(())
+}
+
+fn push_short_string(buf: &mut Vec<u8>, s: &[u8]) {
+    buf.push(s.len() as u8);
+    buf.extend_from_slice(s);
+}
+
+fn encode_frame(buf: &mut Vec<u8>, flags: u8, body: &[u8]) {
+    if body.len() <= u8::MAX as usize {
+        buf.push(flags);
+        buf.push(body.len() as u8);
+    } else {
+        buf.push(flags | FLAG_LONG);
+        buf.extend_from_slice(&(body.len() as u64).to_be_bytes());
+    }
+    buf.extend_from_slice(body);
+}
+
+/// Reads one frame; None when the peer closed the connection between frames
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Option<(u8, Vec<u8>)>> {
+    let flags = match reader.read_u8().await {
+        Ok(flags) => flags,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let len = if flags & FLAG_LONG != 0 {
+        reader.read_u64().await?
+    } else {
+        reader.read_u8().await? as u64
+    };
+    if len > MAX_INBOUND_FRAME {
+        bail!("{} byte frame from a subscriber", len);
+    }
+    let mut body = vec![0u8; len as usize];
+    reader.read_exact(&mut body).await?;
+    Ok(Some((flags, body)))
+}
+
+/// Splits a command frame body into its name and data
+fn parse_command(body: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (&len, rest) = body.split_first()?;
+    (rest.len() >= len as usize).then(|| rest.split_at(len as usize))
+}
+
+/// Value of a property in READY command data
+fn ready_property<'a>(mut properties: &'a [u8], name: &[u8]) -> Option<&'a [u8]> {
+    while let Some((&name_len, rest)) = properties.split_first() {
+        let (key, rest) = rest.split_at_checked(name_len as usize)?;
+        let (len, rest) = rest.split_at_checked(4)?;
+        let len = u32::from_be_bytes(len.try_into().ok()?) as usize;
+        let (value, rest) = rest.split_at_checked(len)?;
+        if key.eq_ignore_ascii_case(name) {
+            return Some(value);
+        }
+        properties = rest;
+    }
+    None
+}
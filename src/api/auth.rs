@@ -0,0 +1,70 @@
+use crate::config::CredentialsConfig;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use thiserror::Error;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Errors raised while authenticating against MEXC private endpoints
+#[derive(Debug, Clone, Error)]
+pub enum AuthError {
+    #[error("missing API credentials: set {0}_API_KEY and {0}_API_SECRET (or the keys in execution.toml)")]
+    MissingCredentials(String),
+    #[error("login rejected by exchange: {0}")]
+    Rejected(String),
+    #[error("no login response within {0}s")]
+    Timeout(u64),
+    #[error("connection lost during login: {0}")]
+    Connection(String),
+}
+
+/// API key pair for private endpoints, loaded from the environment or execution.toml (never
+/// from config.toml)
+#[derive(Clone)]
+pub struct ApiCredentials {
+    pub api_key: String,
+    api_secret: String,
+}
+
+impl ApiCredentials {
+    /// Key pair read from `{prefix}_API_KEY` / `{prefix}_API_SECRET`, e.g. `MEXC` for the
+    /// master account or `MEXC_SUB` for a sub-account
+    pub fn from_env_prefixed(prefix: &str) -> Result<Self, AuthError> {
+        let api_key = std::env::var(format!("{}_API_KEY", prefix)).unwrap_or_default();
+        let api_secret = std::env::var(format!("{}_API_SECRET", prefix)).unwrap_or_default();
+
+        if api_key.is_empty() || api_secret.is_empty() || api_key == "your_api_key_here" {
+            return Err(AuthError::MissingCredentials(prefix.to_string()));
+        }
+
+        Ok(Self { api_key, api_secret })
+    }
+
+    /// The environment's key pair, or else the one in execution.toml
+    pub fn from_env_or_config(prefix: &str, config: &CredentialsConfig) -> Result<Self, AuthError> {
+        Self::from_env_prefixed(prefix).or_else(|e| match (&config.api_key, &config.api_secret) {
+            (Some(api_key), Some(api_secret)) if !api_key.is_empty() && !api_secret.is_empty() => Ok(Self {
+                api_key: api_key.clone(),
+                api_secret: api_secret.clone(),
+            }),
+            _ => Err(e),
+        })
+    }
+
+    /// Hex-encoded HMAC-SHA256 of `payload` keyed with the API secret
+    pub fn sign(&self, payload: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(self.api_secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(payload.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+impl std::fmt::Debug for ApiCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiCredentials")
+            .field("api_key", &self.api_key)
+            .field("api_secret", &"<redacted>")
+            .finish()
+    }
+}
@@ -0,0 +1,240 @@
+use crate::api::SubscriptionCommand;
+use crate::config::ChannelHealthConfig;
+use crate::universe::LoadShedding;
+use crate::models::MarketEvent;
+use dashmap::DashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// A per-symbol market data channel whose coverage is tracked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataChannel {
+    Ticker,
+    FairPrice,
+    Depth,
+}
+
+impl DataChannel {
+    pub const ALL: [DataChannel; 3] = [DataChannel::Ticker, DataChannel::FairPrice, DataChannel::Depth];
+
+    /// Channel name in the exchange's `sub.*` methods
+    pub fn name(self) -> &'static str {
+        match self {
+            DataChannel::Ticker => "ticker",
+            DataChannel::FairPrice => "fair_price",
+            DataChannel::Depth => "depth",
+        }
+    }
+
+    fn of(event: &MarketEvent) -> Option<Self> {
+        match event {
+            MarketEvent::TickerUpdate { .. } => Some(DataChannel::Ticker),
+            MarketEvent::MarkPriceUpdate { .. } => Some(DataChannel::FairPrice),
+            MarketEvent::OrderbookUpdate { .. } => Some(DataChannel::Depth),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DataChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug)]
+struct SymbolChannels {
+    first_seen: Instant,
+    last_seen: [Option<Instant>; 3],
+    last_resubscribe: Option<Instant>,
+    resubscribes: u32,
+}
+
+impl SymbolChannels {
+    fn new(now: Instant) -> Self {
+        Self {
+            first_seen: now,
+            last_seen: [None; 3],
+            last_resubscribe: None,
+            resubscribes: 0,
+        }
+    }
+
+    /// A channel is missing once it has been silent (or never delivered) for `stale` while
+    /// another channel of the symbol still delivers
+    fn missing(&self, expected: &[DataChannel], now: Instant, stale: Duration) -> Vec<DataChannel> {
+        let silent = |channel: DataChannel| {
+            let since = self.last_seen[channel as usize].unwrap_or(self.first_seen);
+            now.duration_since(since) >= stale
+        };
+        if expected.iter().all(|&channel| silent(channel)) {
+            return Vec::new();
+        }
+        expected.iter().copied().filter(|&channel| silent(channel)).collect()
+    }
+
+    fn is_silent(&self, now: Instant, stale: Duration) -> bool {
+        self.last_seen
+            .iter()
+            .flatten()
+            .all(|seen| now.duration_since(*seen) >= stale)
+    }
+}
+
+/// A symbol that only some of its channels deliver data for
+#[derive(Debug, Clone)]
+pub struct PartialCoverage {
+    pub symbol: String,
+    pub missing: Vec<DataChannel>,
+    pub resubscribes: u32,
+}
+
+/// Which of the ticker, fair price and depth channels are delivering data for each
+/// symbol. A symbol that lost one of them keeps updating from the others, so the gap
+/// doesn't show anywhere else: without depth Strategy4/5 never trigger for it.
+///
+/// Symbols with partial coverage get just the missing channels resubscribed; symbols
+/// that went silent on every channel are forgotten until they deliver again.
+pub struct ChannelHealth {
+    config: ChannelHealthConfig,
+    expected: Vec<DataChannel>,
+    symbols: DashMap<String, SymbolChannels>,
+    resubscribes: AtomicU64,
+    shedding: Option<Arc<LoadShedding>>,
+}
+
+impl ChannelHealth {
+    /// `orderbooks` is false when the depth channel isn't subscribed at all
+    pub fn new(config: ChannelHealthConfig, orderbooks: bool) -> Self {
+        let expected = DataChannel::ALL
+            .into_iter()
+            .filter(|&channel| orderbooks || channel != DataChannel::Depth)
+            .collect();
+        Self {
+            config,
+            expected,
+            symbols: DashMap::new(),
+            resubscribes: AtomicU64::new(0),
+            shedding: None,
+        }
+    }
+
+    /// Symbols whose orderbook the resource budget dropped aren't expected to deliver depth
+    pub fn with_load_shedding(mut self, shedding: Arc<LoadShedding>) -> Self {
+        self.shedding = Some(shedding);
+        self
+    }
+
+    fn expected_for(&self, symbol: &str) -> Vec<DataChannel> {
+        match self.shedding {
+            Some(ref shedding) if shedding.is_depthless(symbol) => {
+                self.expected.iter().copied().filter(|&channel| channel != DataChannel::Depth).collect()
+            }
+            _ => self.expected.clone(),
+        }
+    }
+
+    /// Notes the channel a market event arrived on
+    pub fn record(&self, event: &MarketEvent) {
+        let Some(channel) = DataChannel::of(event) else {
+            return;
+        };
+        let now = Instant::now();
+        match self.symbols.get_mut(event.symbol()) {
+            Some(mut channels) => channels.last_seen[channel as usize] = Some(now),
+            None => {
+                let mut channels = SymbolChannels::new(now);
+                channels.last_seen[channel as usize] = Some(now);
+                self.symbols.insert(event.symbol().to_string(), channels);
+            }
+        }
+    }
+
+    /// Symbols with partial coverage, by symbol
+    pub fn partial(&self) -> Vec<PartialCoverage> {
+        let now = Instant::now();
+        let stale = Duration::from_secs(self.config.stale_secs);
+        let mut partial: Vec<PartialCoverage> = self
+            .symbols
+            .iter()
+            .filter_map(|entry| {
+                let missing = entry.value().missing(&self.expected_for(entry.key()), now, stale);
+                (!missing.is_empty()).then(|| PartialCoverage {
+                    symbol: entry.key().clone(),
+                    missing,
+                    resubscribes: entry.value().resubscribes,
+                })
+            })
+            .collect();
+        partial.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        partial
+    }
+
+    /// One line for the status report
+    pub fn summary(&self, partial: &[PartialCoverage]) -> String {
+        let missing = |channel: DataChannel| partial.iter().filter(|p| p.missing.contains(&channel)).count();
+        let by_channel: Vec<String> = self
+            .expected
+            .iter()
+            .map(|&channel| format!("{} {}", channel, missing(channel)))
+            .collect();
+        format!(
+            "Channel coverage: {} symbols | partial: {} (missing {}) | resubscribes: {}",
+            self.symbols.len(),
+            partial.len(),
+            by_channel.join(", "),
+            self.resubscribes.load(Ordering::Relaxed)
+        )
+    }
+
+    /// Periodically resubscribes the missing channels of symbols with partial coverage
+    pub async fn run(self: Arc<Self>, command_tx: mpsc::UnboundedSender<SubscriptionCommand>) {
+        let mut check = tokio::time::interval(Duration::from_secs(self.config.check_interval_secs.max(1)));
+        loop {
+            check.tick().await;
+            self.repair(&command_tx);
+        }
+    }
+
+    fn repair(&self, command_tx: &mpsc::UnboundedSender<SubscriptionCommand>) {
+        let now = Instant::now();
+        let stale = Duration::from_secs(self.config.stale_secs);
+        let cooldown = Duration::from_secs(self.config.resubscribe_cooldown_secs);
+
+        self.symbols.retain(|_, channels| !channels.is_silent(now, stale));
+        for mut entry in self.symbols.iter_mut() {
+            let (symbol, channels) = entry.pair_mut();
+            let missing = channels.missing(&self.expected_for(symbol), now, stale);
+            if missing.is_empty() {
+                continue;
+            }
+            if channels.last_resubscribe.is_some_and(|at| now.duration_since(at) < cooldown) {
+                continue;
+            }
+            channels.last_resubscribe = Some(now);
+            channels.resubscribes += 1;
+            self.resubscribes.fetch_add(1, Ordering::Relaxed);
+
+            let names: Vec<&str> = missing.iter().map(|channel| channel.name()).collect();
+            warn!(
+                "[ChannelHealth] {} has no {} data for {}s+ - resubscribing (attempt {})",
+                symbol,
+                names.join("/"),
+                self.config.stale_secs,
+                channels.resubscribes
+            );
+            let command = SubscriptionCommand::Resubscribe {
+                symbol: symbol.clone(),
+                channels: missing,
+            };
+            if command_tx.send(command).is_err() {
+                info!("[ChannelHealth] Market stream gone, stopping repairs");
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use crate::config::EventPriorityConfig;
+use crate::models::MarketEvent;
+use chrono::{DateTime, Utc};
+use dashmap::DashSet;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Symbols whose events go through the high-priority queue: those with a running episode
+/// or chart recording, or on the watchlist. Kept current by the event loop after each event.
+#[derive(Default)]
+pub struct PrioritySymbols {
+    symbols: DashSet<String>,
+    throttled: AtomicU64,
+}
+
+impl PrioritySymbols {
+    pub fn set(&self, symbol: &str, priority: bool) {
+        if priority {
+            if !self.symbols.contains(symbol) {
+                self.symbols.insert(symbol.to_string());
+            }
+        } else {
+            self.symbols.remove(symbol);
+        }
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.symbols.contains(symbol)
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Background updates dropped by the throttle so far
+    pub fn throttled(&self) -> u64 {
+        self.throttled.load(Ordering::Relaxed)
+    }
+}
+
+/// Splits the market stream into a high-priority queue for the symbols in
+/// [`PrioritySymbols`] and a background queue for the rest, which the event loop only
+/// drains when the priority queue is empty.
+///
+/// Priority symbols are always forwarded at full tick resolution. Background price and
+/// book updates can be thinned to one per `background_min_interval_ms` per symbol.
+pub struct EventRouter {
+    config: EventPriorityConfig,
+    priority: Arc<PrioritySymbols>,
+    // Last forwarded ticker, mark price and orderbook update per background symbol
+    last_forwarded: HashMap<String, [Option<Instant>; 3]>,
+}
+
+impl EventRouter {
+    pub fn new(config: EventPriorityConfig, priority: Arc<PrioritySymbols>) -> Self {
+        Self {
+            config,
+            priority,
+            last_forwarded: HashMap::new(),
+        }
+    }
+
+    /// Forwards events until the stream side or both queues are closed
+    pub async fn run(
+        mut self,
+        mut events: mpsc::UnboundedReceiver<MarketEvent>,
+        priority_tx: mpsc::UnboundedSender<MarketEvent>,
+        background_tx: mpsc::UnboundedSender<MarketEvent>,
+    ) {
+        while let Some(event) = events.recv().await {
+            let sent = if matches!(event, MarketEvent::ConnectionStatus(_)) {
+                // Every symbol's data is affected, so it mustn't wait behind the background queue
+                priority_tx.send(event)
+            } else if self.priority.contains(event.symbol()) {
+                self.last_forwarded.remove(event.symbol());
+                priority_tx.send(event)
+            } else if self.throttle(&event) {
+                self.priority.throttled.fetch_add(1, Ordering::Relaxed);
+                continue;
+            } else {
+                background_tx.send(event)
+            };
+            if sent.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Whether a background update follows the previous one of its kind too closely
+    fn throttle(&mut self, event: &MarketEvent) -> bool {
+        if self.config.background_min_interval_ms == 0 {
+            return false;
+        }
+        let kind = match event {
+            MarketEvent::TickerUpdate { .. } => 0,
+            MarketEvent::MarkPriceUpdate { .. } => 1,
+            MarketEvent::OrderbookUpdate { .. } => 2,
+            // Every trade adds to the candle volume, so none are thinned; funding rates are rare
+            MarketEvent::TradeUpdate { .. }
+            | MarketEvent::FundingRateUpdate { .. }
+            | MarketEvent::ContractStateChange(_)
+            | MarketEvent::SymbolRenamed(_)
+            | MarketEvent::ConnectionStatus(_) => return false,
+        };
+
+        let now = Instant::now();
+        let min_interval = Duration::from_millis(self.config.background_min_interval_ms);
+        let last = &mut self.last_forwarded.entry(event.symbol().to_string()).or_default()[kind];
+        if last.is_some_and(|at| now.duration_since(at) < min_interval) {
+            return true;
+        }
+        *last = Some(now);
+        false
+    }
+}
+
+/// The two queues the event loop reads, the priority one first.
+///
+/// A symbol that turns priority may still have older updates waiting in the background
+/// queue; those are dropped once a newer update of the symbol came through the priority
+/// queue, so its data never steps back in time.
+pub struct MarketEventQueues {
+    priority_rx: mpsc::UnboundedReceiver<MarketEvent>,
+    background_rx: mpsc::UnboundedReceiver<MarketEvent>,
+    // Newest priority event time per symbol, until the background queue catches up
+    priority_watermarks: HashMap<String, DateTime<Utc>>,
+}
+
+impl MarketEventQueues {
+    pub fn new(
+        priority_rx: mpsc::UnboundedReceiver<MarketEvent>,
+        background_rx: mpsc::UnboundedReceiver<MarketEvent>,
+    ) -> Self {
+        Self {
+            priority_rx,
+            background_rx,
+            priority_watermarks: HashMap::new(),
+        }
+    }
+
+    /// Events waiting in both queues
+    pub fn len(&self) -> usize {
+        self.priority_rx.len() + self.background_rx.len()
+    }
+
+    pub async fn recv(&mut self) -> Option<MarketEvent> {
+        loop {
+            tokio::select! {
+                biased;
+                Some(event) = self.priority_rx.recv() => {
+                    if let Some(timestamp) = event.timestamp() {
+                        self.priority_watermarks.insert(event.symbol().to_string(), timestamp);
+                    }
+                    return Some(event);
+                }
+                Some(event) = self.background_rx.recv() => {
+                    let (Some(timestamp), Some(watermark)) = (event.timestamp(), self.priority_watermarks.get(event.symbol())) else {
+                        return Some(event);
+                    };
+                    if timestamp > *watermark {
+                        self.priority_watermarks.remove(event.symbol());
+                        return Some(event);
+                    }
+                }
+                else => return None,
+            }
+        }
+    }
+}
@@ -1,5 +1,25 @@
+#[cfg(feature = "execution")]
+pub mod auth;
+pub mod channel_health;
+pub mod event_router;
+#[cfg(feature = "execution")]
+pub mod private_ws;
 pub mod rest;
+#[cfg(feature = "execution")]
+pub mod spot;
+pub mod stream;
+pub mod synthetic;
 pub mod websocket;
 
+#[cfg(feature = "execution")]
+pub use auth::*;
+pub use channel_health::*;
+pub use event_router::*;
+#[cfg(feature = "execution")]
+pub use private_ws::*;
 pub use rest::*;
+#[cfg(feature = "execution")]
+pub use spot::*;
+pub use stream::*;
+pub use synthetic::*;
 pub use websocket::*;
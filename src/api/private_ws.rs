@@ -0,0 +1,229 @@
+use crate::api::{ApiCredentials, AuthError};
+use anyhow::Result;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio::time::{interval, sleep, timeout, Duration, Instant};
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{error, info, warn};
+
+// A session that stayed logged in this long resets the reconnect backoff
+const STABLE_SESSION: Duration = Duration::from_secs(60);
+
+/// Events from the private user-data stream, consumed by the execution subsystem
+#[derive(Debug, Clone)]
+pub enum PrivateEvent {
+    Authenticated,
+    AuthFailed(AuthError),
+    Disconnected,
+    OrderUpdate(Value),
+    PositionUpdate(Value),
+}
+
+/// Authenticated WebSocket session for order and position updates.
+///
+/// Logs in on every (re)connect and repeats the login periodically so the
+/// session never expires while the connection stays open.
+pub struct PrivateWebSocketClient {
+    ws_url: String,
+    credentials: ApiCredentials,
+    relogin_interval: Duration,
+    login_timeout: Duration,
+}
+
+impl PrivateWebSocketClient {
+    pub fn new(
+        ws_url: String,
+        credentials: ApiCredentials,
+        relogin_interval_secs: u64,
+        login_timeout_secs: u64,
+    ) -> Self {
+        Self {
+            ws_url,
+            credentials,
+            relogin_interval: Duration::from_secs(relogin_interval_secs),
+            login_timeout: Duration::from_secs(login_timeout_secs),
+        }
+    }
+
+    pub async fn run(self, event_tx: mpsc::UnboundedSender<PrivateEvent>) -> Result<()> {
+        let initial_reconnect_delay = Duration::from_secs(1);
+        let max_reconnect_delay = Duration::from_secs(60);
+        let mut reconnect_delay = initial_reconnect_delay;
+
+        loop {
+            info!("[PrivateWS] Connecting to {}", self.ws_url);
+
+            let mut logged_in_at = None;
+            match self.connect_and_run(&event_tx, &mut logged_in_at).await {
+                Ok(_) => {
+                    warn!("[PrivateWS] Connection closed");
+                }
+                Err(e) => {
+                    error!("[PrivateWS] Error: {:?}", e);
+                }
+            }
+
+            if event_tx.send(PrivateEvent::Disconnected).is_err() {
+                // Nobody is listening anymore
+                return Ok(());
+            }
+
+            if logged_in_at.is_some_and(|at| at.elapsed() >= STABLE_SESSION) {
+                reconnect_delay = initial_reconnect_delay;
+            }
+
+            info!("[PrivateWS] Reconnecting in {:?}...", reconnect_delay);
+            sleep(reconnect_delay).await;
+
+            reconnect_delay = std::cmp::min(reconnect_delay * 2, max_reconnect_delay);
+        }
+    }
+
+    fn login_message(&self) -> Message {
+        let req_time = chrono::Utc::now().timestamp_millis().to_string();
+        let signature = self
+            .credentials
+            .sign(&format!("{}{}", self.credentials.api_key, req_time));
+
+        let login = json!({
+            "method": "login",
+            "param": {
+                "apiKey": self.credentials.api_key,
+                "reqTime": req_time,
+                "signature": signature
+            }
+        });
+        Message::Text(login.to_string())
+    }
+
+    async fn connect_and_run(
+        &self,
+        event_tx: &mpsc::UnboundedSender<PrivateEvent>,
+        logged_in_at: &mut Option<Instant>,
+    ) -> Result<()> {
+        let (ws_stream, _) = connect_async(&self.ws_url).await?;
+        let (mut write, mut read) = ws_stream.split();
+
+        // Initial login must be confirmed before any private data flows
+        write.send(self.login_message()).await?;
+
+        let deadline = Instant::now() + self.login_timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let msg = match timeout(remaining, read.next()).await {
+                Ok(Some(Ok(msg))) => msg,
+                Ok(Some(Err(e))) => {
+                    let err = AuthError::Connection(e.to_string());
+                    event_tx.send(PrivateEvent::AuthFailed(err.clone()))?;
+                    return Err(err.into());
+                }
+                Ok(None) => {
+                    let err = AuthError::Connection("stream ended".to_string());
+                    event_tx.send(PrivateEvent::AuthFailed(err.clone()))?;
+                    return Err(err.into());
+                }
+                Err(_) => {
+                    let err = AuthError::Timeout(self.login_timeout.as_secs());
+                    event_tx.send(PrivateEvent::AuthFailed(err.clone()))?;
+                    return Err(err.into());
+                }
+            };
+
+            if let Message::Text(text) = msg {
+                match parse_login_response(&text) {
+                    Some(Ok(())) => break,
+                    Some(Err(err)) => {
+                        event_tx.send(PrivateEvent::AuthFailed(err.clone()))?;
+                        return Err(err.into());
+                    }
+                    None => continue,
+                }
+            }
+        }
+
+        info!("[PrivateWS] Login successful");
+        *logged_in_at = Some(Instant::now());
+        event_tx.send(PrivateEvent::Authenticated)?;
+
+        let mut heartbeat = interval(Duration::from_secs(30));
+        let mut relogin = interval(self.relogin_interval);
+        // Both intervals fire immediately on first tick; skip those
+        heartbeat.tick().await;
+        relogin.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = heartbeat.tick() => {
+                    write.send(Message::Text(json!({"method": "ping"}).to_string())).await?;
+                }
+                _ = relogin.tick() => {
+                    info!("[PrivateWS] Renewing login");
+                    write.send(self.login_message()).await?;
+                }
+                msg = read.next() => {
+                    match msg {
+                        Some(Ok(Message::Text(text))) => {
+                            self.handle_message(&text, event_tx)?;
+                        }
+                        Some(Ok(Message::Close(_))) | None => {
+                            warn!("[PrivateWS] Closed by server");
+                            break;
+                        }
+                        Some(Err(e)) => {
+                            error!("[PrivateWS] Read error: {:?}", e);
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_message(&self, text: &str, event_tx: &mpsc::UnboundedSender<PrivateEvent>) -> Result<()> {
+        let value: Value = serde_json::from_str(text)?;
+        let channel = value.get("channel").and_then(|c| c.as_str()).unwrap_or("");
+
+        match channel {
+            "push.personal.order" => {
+                event_tx.send(PrivateEvent::OrderUpdate(value["data"].clone()))?;
+            }
+            "push.personal.position" => {
+                event_tx.send(PrivateEvent::PositionUpdate(value["data"].clone()))?;
+            }
+            "rs.login" => {
+                // Response to a renewal login
+                if let Some(Err(err)) = parse_login_response(text) {
+                    event_tx.send(PrivateEvent::AuthFailed(err.clone()))?;
+                    return Err(err.into());
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns `None` for messages that aren't a login response
+fn parse_login_response(text: &str) -> Option<Result<(), AuthError>> {
+    let value: Value = serde_json::from_str(text).ok()?;
+    let channel = value.get("channel").and_then(|c| c.as_str())?;
+
+    match channel {
+        "rs.login" => {
+            let data = value.get("data").and_then(|d| d.as_str()).unwrap_or("");
+            if data == "success" {
+                Some(Ok(()))
+            } else {
+                Some(Err(AuthError::Rejected(value["data"].to_string())))
+            }
+        }
+        "rs.error" => Some(Err(AuthError::Rejected(value["data"].to_string()))),
+        _ => None,
+    }
+}
@@ -1,39 +1,331 @@
-use crate::models::ContractDetailResponse;
-use anyhow::Result;
+#[cfg(feature = "execution")]
+use crate::api::{ApiCredentials, AuthError};
+use crate::config::RestConfig;
+use crate::models::{
+    Candle, ContractDetail, ContractDetailResponse, ContractTicker, ContractTickerResponse, DepthResponse, KlineResponse,
+    OrderbookData,
+};
+#[cfg(feature = "execution")]
+use crate::models::{AccountAsset, ApiResponse, ExchangeOrder, OrderRequest};
+use anyhow::{bail, Result};
+#[cfg(feature = "execution")]
+use reqwest::RequestBuilder;
 use reqwest::Client;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+#[cfg(feature = "execution")]
+use thiserror::Error;
+use tracing::warn;
+
+// Response codes of the private API for invalid keys, bad signatures and missing permissions
+#[cfg(feature = "execution")]
+const PERMISSION_ERROR_CODES: [i32; 6] = [401, 402, 403, 406, 602, 10072];
+
+/// Failure of a private (signed) REST call
+#[cfg(feature = "execution")]
+#[derive(Debug, Error)]
+pub enum RequestError {
+    /// The request may have reached the exchange (e.g. timed out waiting for the response)
+    #[error("{endpoint}: outcome unknown ({message})")]
+    Ambiguous { endpoint: String, message: String },
+    /// The request never left this machine, so it is safe to retry
+    #[error("{endpoint}: exchange unreachable ({message})")]
+    Unreachable { endpoint: String, message: String },
+    #[error("{endpoint}: rejected by exchange (code={code}, {message})")]
+    Rejected { endpoint: String, code: i32, message: String },
+    #[error("{endpoint}: unexpected response ({message})")]
+    Decode { endpoint: String, message: String },
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+}
+
+#[cfg(feature = "execution")]
+impl RequestError {
+    /// Whether the request may have taken effect despite the error
+    pub fn is_ambiguous(&self) -> bool {
+        matches!(self, RequestError::Ambiguous { .. } | RequestError::Decode { .. })
+    }
+
+    pub(crate) fn from_transport(endpoint: &str, e: reqwest::Error) -> Self {
+        if e.is_connect() {
+            RequestError::Unreachable {
+                endpoint: endpoint.to_string(),
+                message: e.to_string(),
+            }
+        } else {
+            RequestError::Ambiguous {
+                endpoint: endpoint.to_string(),
+                message: e.to_string(),
+            }
+        }
+    }
+}
 
 pub struct MexcRestClient {
     client: Client,
     base_url: String,
+    config: RestConfig,
+    #[cfg(feature = "execution")]
+    credentials: Option<ApiCredentials>,
 }
 
 impl MexcRestClient {
-    pub fn new(base_url: String) -> Self {
-        Self {
-            client: Client::new(),
+    pub fn new(base_url: String, config: RestConfig) -> Result<Self> {
+        let client = Client::builder()
+            .connect_timeout(Duration::from_millis(config.connect_timeout_ms))
+            .timeout(Duration::from_millis(config.request_timeout_ms))
+            .build()?;
+
+        Ok(Self {
+            client,
             base_url,
+            config,
+            #[cfg(feature = "execution")]
+            credentials: None,
+        })
+    }
+
+    pub async fn get_contract_details(&self) -> Result<Vec<ContractDetail>> {
+        let data: ContractDetailResponse = self.get_public("/api/v1/contract/detail", &[]).await?;
+
+        if !data.success {
+            anyhow::bail!("API returned success=false, code={}", data.code);
         }
+
+        Ok(data.data)
     }
 
-    pub async fn get_all_contracts(&self) -> Result<Vec<String>> {
-        let url = format!("{}/api/v1/contract/detail", self.base_url);
+    /// 24h statistics for every contract
+    pub async fn get_tickers(&self) -> Result<Vec<ContractTicker>> {
+        let data: ContractTickerResponse = self.get_public("/api/v1/contract/ticker", &[]).await?;
 
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?;
+        if !data.success {
+            anyhow::bail!("API returned success=false, code={}", data.code);
+        }
+
+        Ok(data.data)
+    }
 
-        let data: ContractDetailResponse = response.json().await?;
+    /// Orderbook snapshot of `symbol` with up to `limit` levels per side
+    pub async fn get_depth(&self, symbol: &str, limit: usize) -> Result<OrderbookData> {
+        let query = [("limit", limit.to_string())];
+        let data: DepthResponse = self.get_public(&format!("/api/v1/contract/depth/{}", symbol), &query).await?;
 
         if !data.success {
             anyhow::bail!("API returned success=false, code={}", data.code);
         }
 
-        let symbols: Vec<String> = data.data.iter()
-            .filter(|contract| contract.state == 0)
-            .map(|contract| contract.symbol.clone())
-            .collect();
+        Ok(data.data)
+    }
+
+    /// Official 1-minute last-price klines for `symbol` between `start` and `end` (unix seconds, inclusive)
+    pub async fn get_klines_1m(&self, symbol: &str, start: i64, end: i64) -> Result<Vec<Candle>> {
+        self.fetch_klines_1m(&format!("kline/{}", symbol), start, end).await
+    }
+
+    /// Official 1-minute fair (mark) price klines, same window semantics as `get_klines_1m`
+    pub async fn get_fair_price_klines_1m(&self, symbol: &str, start: i64, end: i64) -> Result<Vec<Candle>> {
+        self.fetch_klines_1m(&format!("kline/fair_price/{}", symbol), start, end).await
+    }
+
+    async fn fetch_klines_1m(&self, path: &str, start: i64, end: i64) -> Result<Vec<Candle>> {
+        let query = [
+            ("interval", "Min1".to_string()),
+            ("start", start.to_string()),
+            ("end", end.to_string()),
+        ];
+        let data: KlineResponse = self.get_public(&format!("/api/v1/contract/{}", path), &query).await?;
+
+        if !data.success {
+            anyhow::bail!("API returned success=false, code={}", data.code);
+        }
+
+        Ok(data.data.into_candles())
+    }
+
+    /// GETs a public endpoint, retrying timeouts, transport errors, HTTP error statuses and
+    /// unreadable bodies with exponential backoff
+    async fn get_public<T: DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> Result<T> {
+        let url = format!("{}{}", self.base_url, path);
+        let attempts = self.config.max_retries + 1;
+        let mut backoff = Duration::from_millis(self.config.retry_backoff_ms);
+        let mut attempt = 1;
+
+        loop {
+            let result = async {
+                self.client
+                    .get(&url)
+                    .query(query)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json::<T>()
+                    .await
+            }
+            .await;
+
+            match result {
+                Ok(data) => return Ok(data),
+                Err(e) if attempt < attempts => {
+                    warn!(
+                        "[Rest] GET {} failed (attempt {}/{}): {}; retrying in {}ms",
+                        path, attempt, attempts, e, backoff.as_millis()
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(Duration::from_millis(self.config.max_backoff_ms));
+                    attempt += 1;
+                }
+                Err(e) => bail!("GET {} failed after {} attempt(s): {}", path, attempt, e),
+            }
+        }
+    }
+}
+
+/// Private (signed) endpoints of order execution
+#[cfg(feature = "execution")]
+impl MexcRestClient {
+    /// Enables the private (signed) endpoints
+    pub fn with_credentials(mut self, credentials: ApiCredentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Places an order and returns the exchange order id
+    pub async fn submit_order(&self, order: &OrderRequest, timeout: Duration) -> Result<String, RequestError> {
+        let body = serde_json::to_string(order).map_err(|e| RequestError::Decode {
+            endpoint: "order/submit".to_string(),
+            message: e.to_string(),
+        })?;
+
+        let order_id: serde_json::Value = self
+            .signed_post("/api/v1/private/order/submit", body, timeout)
+            .await?
+            .ok_or_else(|| RequestError::Decode {
+                endpoint: "order/submit".to_string(),
+                message: "missing order id".to_string(),
+            })?;
+
+        Ok(match order_id {
+            serde_json::Value::String(s) => s,
+            other => other.to_string(),
+        })
+    }
+
+    /// Looks up an order by the client-assigned id; `None` if the exchange doesn't know it
+    pub async fn get_order_by_external_id(
+        &self,
+        symbol: &str,
+        external_oid: &str,
+        timeout: Duration,
+    ) -> Result<Option<ExchangeOrder>, RequestError> {
+        let path = format!("/api/v1/private/order/external/{}/{}", symbol, external_oid);
+        self.signed_get(&path, &[], timeout).await
+    }
+
+    /// Cancels an order by the client-assigned id
+    pub async fn cancel_order_by_external_id(
+        &self,
+        symbol: &str,
+        external_oid: &str,
+        timeout: Duration,
+    ) -> Result<(), RequestError> {
+        let body = serde_json::json!({ "symbol": symbol, "externalOid": external_oid }).to_string();
+        let _: Option<serde_json::Value> =
+            self.signed_post("/api/v1/private/order/cancel_with_external", body, timeout).await?;
+        Ok(())
+    }
+
+    /// Futures balance of `currency` for the account these credentials belong to
+    pub async fn get_account_asset(&self, currency: &str, timeout: Duration) -> Result<Option<AccountAsset>, RequestError> {
+        let path = format!("/api/v1/private/account/asset/{}", currency);
+        self.signed_get(&path, &[], timeout).await
+    }
+
+    /// Checks that the credentials can read the futures account and reach the order endpoints
+    /// without trading: reads the USDT balance, then cancels an order id that can't exist.
+    /// Any answer to the cancel other than an authentication or permission error passes.
+    pub async fn verify_trading_permissions(&self, timeout: Duration) -> Result<(), RequestError> {
+        self.get_account_asset("USDT", timeout).await?;
+
+        let cancel: Result<Option<serde_json::Value>, RequestError> =
+            self.signed_post("/api/v1/private/order/cancel", "[\"0\"]".to_string(), timeout).await;
+        match cancel {
+            Err(RequestError::Rejected { code, .. }) if !PERMISSION_ERROR_CODES.contains(&code) => Ok(()),
+            Err(e) => Err(e),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    async fn signed_get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+        timeout: Duration,
+    ) -> Result<Option<T>, RequestError> {
+        let mut params: Vec<(&str, String)> = query.to_vec();
+        params.sort_by(|a, b| a.0.cmp(b.0));
+        let param_string = params
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let url = format!("{}{}", self.base_url, path);
+        let request = self.client.get(&url).query(&params);
+        self.send_signed(path, request, &param_string, timeout).await
+    }
+
+    async fn signed_post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: String,
+        timeout: Duration,
+    ) -> Result<Option<T>, RequestError> {
+        let url = format!("{}{}", self.base_url, path);
+        let request = self
+            .client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .body(body.clone());
+        self.send_signed(path, request, &body, timeout).await
+    }
+
+    async fn send_signed<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        request: RequestBuilder,
+        param_string: &str,
+        timeout: Duration,
+    ) -> Result<Option<T>, RequestError> {
+        let credentials = self.credentials.as_ref().ok_or_else(|| AuthError::MissingCredentials("MEXC".to_string()))?;
+        let req_time = chrono::Utc::now().timestamp_millis().to_string();
+        let signature = credentials.sign(&format!("{}{}{}", credentials.api_key, req_time, param_string));
+
+        let response = request
+            .header("ApiKey", &credentials.api_key)
+            .header("Request-Time", &req_time)
+            .header("Signature", signature)
+            .timeout(timeout)
+            .send()
+            .await
+            .map_err(|e| RequestError::from_transport(path, e))?;
+
+        let envelope: ApiResponse<T> = response
+            .json()
+            .await
+            .map_err(|e| RequestError::Decode {
+                endpoint: path.to_string(),
+                message: e.to_string(),
+            })?;
+
+        if !envelope.success {
+            return Err(RequestError::Rejected {
+                endpoint: path.to_string(),
+                code: envelope.code,
+                message: envelope.message.unwrap_or_default(),
+            });
+        }
 
-        Ok(symbols)
+        Ok(envelope.data)
     }
 }
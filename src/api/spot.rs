@@ -0,0 +1,84 @@
+use crate::api::{ApiCredentials, RequestError};
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Client for the spot/wallet (v3) API, which hosts the sub-account transfer endpoints.
+///
+/// v3 requests are signed differently from futures ones: the HMAC covers the
+/// query string (including `timestamp`) and the key goes in `X-MEXC-APIKEY`.
+pub struct MexcSpotClient {
+    client: Client,
+    base_url: String,
+    credentials: ApiCredentials,
+}
+
+#[derive(Debug, Deserialize)]
+struct TransferResponse {
+    #[serde(rename = "tranId")]
+    tran_id: Option<serde_json::Value>,
+    code: Option<i32>,
+    msg: Option<String>,
+}
+
+impl MexcSpotClient {
+    pub fn new(base_url: String, credentials: ApiCredentials) -> Self {
+        Self {
+            client: Client::new(),
+            base_url,
+            credentials,
+        }
+    }
+
+    /// Moves `amount` of `asset` from a sub-account to the master account; returns the transfer id
+    pub async fn transfer_from_sub_account(
+        &self,
+        sub_account: &str,
+        from_account_type: &str,
+        to_account_type: &str,
+        asset: &str,
+        amount: f64,
+        timeout: Duration,
+    ) -> Result<String, RequestError> {
+        let path = "/api/v3/capital/sub-account/universalTransfer";
+        let params = [
+            ("fromAccount", sub_account.to_string()),
+            ("fromAccountType", from_account_type.to_string()),
+            ("toAccountType", to_account_type.to_string()),
+            ("asset", asset.to_string()),
+            ("amount", format!("{:.2}", amount)),
+            ("timestamp", chrono::Utc::now().timestamp_millis().to_string()),
+        ];
+        let query = params
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&");
+        let signature = self.credentials.sign(&query);
+        let url = format!("{}{}?{}&signature={}", self.base_url, path, query, signature);
+
+        let response = self
+            .client
+            .post(&url)
+            .header("X-MEXC-APIKEY", &self.credentials.api_key)
+            .header("Content-Type", "application/json")
+            .timeout(timeout)
+            .send()
+            .await
+            .map_err(|e| RequestError::from_transport(path, e))?;
+
+        let body: TransferResponse = response.json().await.map_err(|e| RequestError::Decode {
+            endpoint: path.to_string(),
+            message: e.to_string(),
+        })?;
+
+        match body.tran_id {
+            Some(id) => Ok(id.as_str().map(str::to_string).unwrap_or_else(|| id.to_string())),
+            None => Err(RequestError::Rejected {
+                endpoint: path.to_string(),
+                code: body.code.unwrap_or_default(),
+                message: body.msg.unwrap_or_default(),
+            }),
+        }
+    }
+}
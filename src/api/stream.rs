@@ -0,0 +1,19 @@
+use crate::api::SubscriptionCommand;
+use crate::models::MarketEvent;
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+/// A source of market events; the live WebSocket client is one implementation,
+/// synthetic or recorded streams can be swapped in for tests and offline runs
+#[async_trait]
+pub trait MarketStream: Send {
+    fn name(&self) -> &str;
+
+    /// Produces events until the source is exhausted or fails
+    async fn run(
+        self: Box<Self>,
+        event_tx: mpsc::UnboundedSender<MarketEvent>,
+        command_rx: mpsc::UnboundedReceiver<SubscriptionCommand>,
+    ) -> Result<()>;
+}
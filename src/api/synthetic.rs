@@ -0,0 +1,103 @@
+use crate::api::{MarketStream, SubscriptionCommand};
+use crate::config::MarketStreamConfig;
+use crate::models::{MarketEvent, Symbol};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use tokio::time::{interval, Duration};
+use tracing::info;
+
+/// Random-walk price generator with occasional injected pumps, for exercising
+/// the pipeline without an exchange connection
+pub struct SyntheticStream {
+    symbols: Vec<Symbol>,
+    tick_interval: Duration,
+    pump_probability: f64,
+    pump_ratio: f64,
+    pump_ticks: u32,
+}
+
+impl SyntheticStream {
+    pub fn new(symbols: Vec<String>, tick_interval_ms: u64, config: &MarketStreamConfig) -> Self {
+        Self {
+            symbols: symbols.iter().map(|s| Symbol::from(s.as_str())).collect(),
+            tick_interval: Duration::from_millis(tick_interval_ms),
+            pump_probability: config.synthetic_pump_probability,
+            pump_ratio: config.synthetic_pump_ratio,
+            pump_ticks: config.synthetic_pump_ticks,
+        }
+    }
+}
+
+#[async_trait]
+impl MarketStream for SyntheticStream {
+    fn name(&self) -> &str {
+        "synthetic"
+    }
+
+    async fn run(
+        mut self: Box<Self>,
+        event_tx: mpsc::UnboundedSender<MarketEvent>,
+        mut command_rx: mpsc::UnboundedReceiver<SubscriptionCommand>,
+    ) -> Result<()> {
+        let mut rng = rand::rngs::SmallRng::from_os_rng();
+        // symbol -> (mark price, remaining pump ticks)
+        let mut state: HashMap<Symbol, (f64, u32)> = HashMap::new();
+        let mut ticker = interval(self.tick_interval);
+
+        info!("[Synthetic] Generating prices for {} symbols", self.symbols.len());
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                Some(command) = command_rx.recv() => {
+                    match command {
+                        SubscriptionCommand::Subscribe(symbols) => {
+                            self.symbols.extend(symbols.iter().map(|s| Symbol::from(s.as_str())))
+                        }
+                        SubscriptionCommand::Unsubscribe(symbols) => self.symbols.retain(|s| !symbols.iter().any(|u| **u == **s)),
+                        // No orderbook is synthesized, and every channel always delivers
+                        SubscriptionCommand::SetDepth { .. }
+                        | SubscriptionCommand::Resubscribe { .. }
+                        | SubscriptionCommand::DropDepth(_)
+                        | SubscriptionCommand::RestoreDepth(_) => {}
+                    }
+                    continue;
+                }
+            }
+
+            let now = Utc::now();
+            for symbol in &self.symbols {
+                let (mark, pump_left) = state
+                    .entry(symbol.clone())
+                    .or_insert_with(|| (rng.random_range(0.01..100.0), 0));
+
+                *mark *= 1.0 + rng.random_range(-0.0005..0.0005);
+
+                if *pump_left == 0 && rng.random_bool(self.pump_probability) {
+                    *pump_left = self.pump_ticks;
+                    info!("[Synthetic] Injecting pump on {} for {} ticks", symbol, self.pump_ticks);
+                }
+
+                let last = if *pump_left > 0 {
+                    *pump_left -= 1;
+                    *mark * self.pump_ratio
+                } else {
+                    *mark * (1.0 + rng.random_range(-0.001..0.001))
+                };
+
+                event_tx.send(MarketEvent::TickerUpdate {
+                    symbol: symbol.clone(),
+                    last_price: last,
+                    mark_price: Some(*mark),
+                    stats_24h: None,
+                    quote: None,
+                    timestamp: now,
+                })?;
+            }
+        }
+    }
+}
@@ -1,40 +1,170 @@
-use crate::models::{MarketEvent, MarkPriceData, OrderbookData, ProcessedOrderbook, TickerData};
+use crate::models::{
+    ConnectionStatus, DealData, MarketEvent, MarkPriceData, OrderbookData, ProcessedOrderbook, PushMessage, Symbol, SymbolInterner,
+    TickerData,
+};
+use crate::utils::{IssueCollector, MalformedCapture, TaskSupervisor, WsRawLog};
+use crate::api::{DataChannel, MarketStream};
 use anyhow::Result;
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use futures_util::{SinkExt, StreamExt};
-use serde_json::{json, Value};
-use tokio::net::TcpStream;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use tokio::sync::mpsc;
-use tokio::time::{sleep, Duration, interval};
-use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use tokio::time::{sleep, Duration, Instant, interval, timeout};
+use tokio_tungstenite::connect_async;
 use tokio_tungstenite::tungstenite::Message;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, trace, warn};
+
+/// A connection that stayed up this long was healthy: the next reconnect starts over at
+/// the shortest delay
+const STABLE_CONNECTION: Duration = Duration::from_secs(60);
+/// No message at all for this long (pings are answered every 30s) means a dead connection
+const IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// What one connection delivered, logged when it ends
+struct ConnectionHealth {
+    number: u64,
+    connected_at: Instant,
+    messages: u64,
+}
 
-type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+impl ConnectionHealth {
+    fn new(number: u64) -> Self {
+        Self {
+            number,
+            connected_at: Instant::now(),
+            messages: 0,
+        }
+    }
+}
+
+/// Runtime changes to the set of subscribed symbols
+#[derive(Debug, Clone)]
+pub enum SubscriptionCommand {
+    Subscribe(Vec<String>),
+    Unsubscribe(Vec<String>),
+    /// Resubscribe a symbol's orderbook with a different number of levels
+    SetDepth { symbol: String, limit: usize },
+    /// Unsubscribe and subscribe again just these channels of a symbol
+    Resubscribe { symbol: String, channels: Vec<DataChannel> },
+    /// Unsubscribe the orderbooks of these symbols, keeping their other channels
+    DropDepth(Vec<String>),
+    /// Subscribe the orderbooks dropped by `DropDepth` again
+    RestoreDepth(Vec<String>),
+}
 
 pub struct MexcWebSocketClient {
     ws_url: String,
     symbols: Vec<String>,
     max_levels: usize,
+    // False in low-memory mode: no depth channel at all
+    orderbooks: bool,
+    // Deal channel, for candle volume
+    trades: bool,
+    funding_rates: bool,
+    // Per-symbol orderbook depth overrides (e.g. watchlisted symbols)
+    depth_overrides: HashMap<String, usize>,
+    // Symbols whose orderbook was dropped to save resources
+    depthless: HashSet<String>,
+    supervisor: TaskSupervisor,
+    raw_log: Option<Arc<WsRawLog>>,
+    issues: Option<Arc<IssueCollector>>,
+    // One shared allocation per symbol for the events of every push
+    interner: SymbolInterner,
+    // Strict parsing mode
+    malformed: Option<Arc<MalformedCapture>>,
 }
 
 impl MexcWebSocketClient {
-    pub fn new(ws_url: String, symbols: Vec<String>, max_levels: usize) -> Self {
+    pub fn new(ws_url: String, symbols: Vec<String>, max_levels: usize, supervisor: TaskSupervisor) -> Self {
         Self {
             ws_url,
             symbols,
             max_levels,
+            orderbooks: true,
+            trades: true,
+            funding_rates: true,
+            depth_overrides: HashMap::new(),
+            depthless: HashSet::new(),
+            supervisor,
+            raw_log: None,
+            issues: None,
+            interner: SymbolInterner::new(),
+            malformed: None,
+        }
+    }
+
+    /// Leaves out the orderbook depth channel when `enabled` is false
+    pub fn with_orderbooks(mut self, enabled: bool) -> Self {
+        self.orderbooks = enabled;
+        self
+    }
+
+    /// Leaves out the deal (trade) channel when `enabled` is false
+    pub fn with_trades(mut self, enabled: bool) -> Self {
+        self.trades = enabled;
+        self
+    }
+
+    /// Leaves out the funding rate channel when `enabled` is false
+    pub fn with_funding_rates(mut self, enabled: bool) -> Self {
+        self.funding_rates = enabled;
+        self
+    }
+
+    /// Keep acks, errors and unrecognized messages in the raw message log
+    pub fn with_raw_log(mut self, raw_log: Arc<WsRawLog>) -> Self {
+        self.raw_log = Some(raw_log);
+        self
+    }
+
+    /// Sample messages that fail to parse or have an unexpected format for issue bundles
+    pub fn with_issue_collector(mut self, issues: Arc<IssueCollector>) -> Self {
+        self.issues = Some(issues);
+        self
+    }
+
+    /// Strict parsing: keep a sample of every message that doesn't match the expected schema
+    pub fn with_malformed_capture(mut self, malformed: Arc<MalformedCapture>) -> Self {
+        self.malformed = Some(malformed);
+        self
+    }
+
+    fn record_issue(&self, kind: &'static str, text: &str, error: Option<String>) {
+        if let Some(ref issues) = self.issues {
+            issues.record(kind, text, error);
         }
     }
 
-    pub async fn run(self, event_tx: mpsc::UnboundedSender<MarketEvent>) -> Result<()> {
-        let mut reconnect_delay = Duration::from_secs(1);
+    fn capture_malformed(&self, kind: &'static str, text: &str, error: &str) {
+        if let Some(ref malformed) = self.malformed {
+            malformed.capture(kind, text, error);
+        }
+    }
+
+    fn record_raw(&self, text: &str) {
+        if let Some(ref raw_log) = self.raw_log {
+            raw_log.record(text);
+        }
+    }
+
+    async fn run_forever(
+        mut self,
+        event_tx: mpsc::UnboundedSender<MarketEvent>,
+        mut command_rx: mpsc::UnboundedReceiver<SubscriptionCommand>,
+    ) -> Result<()> {
+        let initial_reconnect_delay = Duration::from_secs(1);
         let max_reconnect_delay = Duration::from_secs(60);
+        let mut reconnect_delay = initial_reconnect_delay;
+        let mut connections = 0;
 
         loop {
             info!("Connecting to WebSocket: {}", self.ws_url);
 
-            match self.connect_and_run(&event_tx).await {
+            let mut health = None;
+            match self.connect_and_run(&event_tx, &mut command_rx, connections + 1, &mut health).await {
                 Ok(_) => {
                     warn!("WebSocket connection closed normally");
                 }
@@ -43,6 +173,19 @@ impl MexcWebSocketClient {
                 }
             }
 
+            if let Some(health) = health {
+                connections = health.number;
+                let lasted = health.connected_at.elapsed();
+                info!(
+                    "WebSocket connection #{} lasted {:?} and delivered {} messages",
+                    health.number, lasted, health.messages
+                );
+                event_tx.send(MarketEvent::ConnectionStatus(ConnectionStatus::Disconnected { timestamp: Utc::now() }))?;
+                if lasted >= STABLE_CONNECTION {
+                    reconnect_delay = initial_reconnect_delay;
+                }
+            }
+
             info!("Reconnecting in {:?}...", reconnect_delay);
             sleep(reconnect_delay).await;
 
@@ -50,9 +193,16 @@ impl MexcWebSocketClient {
         }
     }
 
-    async fn connect_and_run(&self, event_tx: &mpsc::UnboundedSender<MarketEvent>) -> Result<()> {
+    async fn connect_and_run(
+        &mut self,
+        event_tx: &mpsc::UnboundedSender<MarketEvent>,
+        command_rx: &mut mpsc::UnboundedReceiver<SubscriptionCommand>,
+        number: u64,
+        health: &mut Option<ConnectionHealth>,
+    ) -> Result<()> {
         let (ws_stream, _) = connect_async(&self.ws_url).await?;
-        info!("WebSocket connected successfully");
+        info!("WebSocket connected successfully (connection #{})", number);
+        let health = health.insert(ConnectionHealth::new(number));
 
         let (write, read) = ws_stream.split();
 
@@ -70,59 +220,70 @@ impl MexcWebSocketClient {
             }
         });
 
-        // Subscribe to ticker, mark price, and orderbook for each symbol
+        // Subscribe to ticker, mark price, orderbook and trades for each symbol
         for symbol in &self.symbols {
-            // Subscribe to ticker for this symbol
-            let ticker_sub = json!({
-                "method": "sub.ticker",
-                "param": {
-                    "symbol": symbol
-                }
-            });
-            write_tx.send(Message::Text(ticker_sub.to_string()))?;
-
-            // Subscribe to fair/mark price for this symbol
-            let mark_price_sub = json!({
-                "method": "sub.fair_price",
-                "param": {
-                    "symbol": symbol
-                }
-            });
-            write_tx.send(Message::Text(mark_price_sub.to_string()))?;
-
-            // Subscribe to orderbook depth for this symbol
-            let depth_sub = json!({
-                "method": "sub.depth",
-                "param": {
-                    "symbol": symbol,
-                    "limit": self.max_levels
-                }
-            });
-            write_tx.send(Message::Text(depth_sub.to_string()))?;
+            self.send_subscription(&write_tx, symbol, "sub")?;
         }
 
-        info!("Subscribed to ticker, fair_price, and depth for {} symbols", self.symbols.len());
+        let mut channels = vec!["ticker", "fair_price"];
+        if self.orderbooks {
+            channels.push("depth");
+        }
+        if self.trades {
+            channels.push("deal");
+        }
+        if self.funding_rates {
+            channels.push("funding.rate");
+        }
+        info!("Subscribed to {} for {} symbols", channels.join(", "), self.symbols.len());
+        event_tx.send(MarketEvent::ConnectionStatus(ConnectionStatus::Connected {
+            reconnect: number > 1,
+            timestamp: Utc::now(),
+        }))?;
 
         // Spawn heartbeat task
         let write_tx_clone = write_tx.clone();
-        tokio::spawn(async move {
-            let mut heartbeat_interval = interval(Duration::from_secs(30));
-            loop {
-                heartbeat_interval.tick().await;
-                let ping = json!({"method": "ping"});
-                if write_tx_clone.send(Message::Text(ping.to_string())).is_err() {
-                    break;
+        self.supervisor.supervise("ws-heartbeat", move || {
+            let write_tx = write_tx_clone.clone();
+            async move {
+                let mut heartbeat_interval = interval(Duration::from_secs(30));
+                loop {
+                    heartbeat_interval.tick().await;
+                    let ping = json!({"method": "ping"});
+                    if write_tx.send(Message::Text(ping.to_string())).is_err() {
+                        break;
+                    }
                 }
             }
         });
 
-        // Read messages
+        // Read messages and apply subscription changes
         let mut read = read;
-        while let Some(msg) = read.next().await {
+        // Reused by every frame for parsing (see `PushMessage::decode`)
+        let mut scratch = Vec::new();
+        loop {
+            let msg = tokio::select! {
+                msg = timeout(IDLE_TIMEOUT, read.next()) => match msg {
+                    Ok(Some(msg)) => msg,
+                    Ok(None) => break,
+                    Err(_) => {
+                        warn!("No WebSocket message for {:?}, dropping the connection", IDLE_TIMEOUT);
+                        break;
+                    }
+                },
+                Some(command) = command_rx.recv() => {
+                    self.apply_command(&write_tx, command)?;
+                    continue;
+                }
+            };
+
+            health.messages += 1;
             match msg {
                 Ok(Message::Text(text)) => {
-                    if let Err(e) = self.handle_message(&text, event_tx) {
+                    if let Err(e) = self.handle_message(&text, &mut scratch, event_tx) {
                         warn!("Failed to handle message: {:?}", e);
+                        self.capture_malformed("parse_error", &text, &format!("{:#}", e));
+                        self.record_issue("parse_error", &text, Some(format!("{:#}", e)));
                     }
                 }
                 Ok(Message::Ping(_)) => {
@@ -147,40 +308,228 @@ impl MexcWebSocketClient {
         Ok(())
     }
 
-    fn handle_message(&self, text: &str, event_tx: &mpsc::UnboundedSender<MarketEvent>) -> Result<()> {
-        let value: Value = serde_json::from_str(text)?;
-
-        // Check for pong
-        if let Some(channel) = value.get("channel").and_then(|c| c.as_str()) {
-            if channel == "pong" {
-                return Ok(());
+    fn apply_command(&mut self, write_tx: &mpsc::UnboundedSender<Message>, command: SubscriptionCommand) -> Result<()> {
+        match command {
+            SubscriptionCommand::Subscribe(symbols) => {
+                for symbol in symbols {
+                    if !self.symbols.contains(&symbol) {
+                        self.send_subscription(write_tx, &symbol, "sub")?;
+                        info!("Subscribed to {}", symbol);
+                        self.symbols.push(symbol);
+                    }
+                }
             }
-
-            match channel {
-                "push.ticker" => {
-                    if let Some(data) = value.get("data") {
-                        let ticker: TickerData = serde_json::from_value(data.clone())?;
-                        self.handle_ticker(ticker, event_tx)?;
+            SubscriptionCommand::Unsubscribe(symbols) => {
+                for symbol in symbols {
+                    if let Some(pos) = self.symbols.iter().position(|s| *s == symbol) {
+                        self.send_subscription(write_tx, &symbol, "unsub")?;
+                        info!("Unsubscribed from {}", symbol);
+                        self.symbols.swap_remove(pos);
+                        self.depth_overrides.remove(&symbol);
+                        self.depthless.remove(&symbol);
                     }
                 }
-                "push.fair_price" => {
-                    if let Some(data) = value.get("data") {
-                        let mark_price: MarkPriceData = serde_json::from_value(data.clone())?;
-                        self.handle_mark_price(mark_price, event_tx)?;
+            }
+            SubscriptionCommand::SetDepth { symbol, limit } => {
+                if !self.orderbooks {
+                    debug!("Ignoring depth change for {}: orderbooks disabled", symbol);
+                    return Ok(());
+                }
+                if !self.symbols.contains(&symbol) || self.depth_limit(&symbol) == limit {
+                    return Ok(());
+                }
+                // A dropped orderbook comes back with the new limit once it's restored
+                let subscribed = !self.depthless.contains(&symbol);
+                if subscribed {
+                    self.send_depth(write_tx, &symbol, "unsub")?;
+                }
+                if limit == self.max_levels {
+                    self.depth_overrides.remove(&symbol);
+                } else {
+                    self.depth_overrides.insert(symbol.clone(), limit);
+                }
+                if subscribed {
+                    self.send_depth(write_tx, &symbol, "sub")?;
+                }
+                info!("Orderbook depth for {} set to {} levels", symbol, limit);
+            }
+            SubscriptionCommand::Resubscribe { symbol, channels } => {
+                if !self.symbols.contains(&symbol) {
+                    return Ok(());
+                }
+                for channel in channels {
+                    if channel == DataChannel::Depth && (!self.orderbooks || self.depthless.contains(&symbol)) {
+                        continue;
                     }
+                    self.send_channel(write_tx, &symbol, channel, "unsub")?;
+                    self.send_channel(write_tx, &symbol, channel, "sub")?;
+                    info!("Resubscribed to {} for {}", channel, symbol);
+                }
+            }
+            SubscriptionCommand::DropDepth(symbols) => {
+                if !self.orderbooks {
+                    return Ok(());
                 }
-                "push.depth" => {
-                    if let Some(symbol) = value.get("symbol").and_then(|s| s.as_str()) {
-                        if let Some(data) = value.get("data") {
-                            let mut orderbook: OrderbookData = serde_json::from_value(data.clone())?;
-                            orderbook.symbol = Some(symbol.to_string());
-                            self.handle_orderbook(orderbook, event_tx)?;
-                        }
+                let mut dropped = 0;
+                for symbol in symbols {
+                    if self.symbols.contains(&symbol) && !self.depthless.contains(&symbol) {
+                        self.send_depth(write_tx, &symbol, "unsub")?;
+                        self.depthless.insert(symbol);
+                        dropped += 1;
                     }
                 }
-                _ => {
-                    // Ignore subscription confirmations (rs.sub.*) and other non-data channels
+                info!("Orderbooks dropped for {} symbols ({} without depth)", dropped, self.depthless.len());
+            }
+            SubscriptionCommand::RestoreDepth(symbols) => {
+                let mut restored = 0;
+                for symbol in symbols {
+                    if self.depthless.remove(&symbol) && self.symbols.contains(&symbol) {
+                        self.send_depth(write_tx, &symbol, "sub")?;
+                        restored += 1;
+                    }
                 }
+                info!("Orderbooks restored for {} symbols ({} without depth)", restored, self.depthless.len());
+            }
+        }
+        Ok(())
+    }
+
+    fn send_channel(
+        &self,
+        write_tx: &mpsc::UnboundedSender<Message>,
+        symbol: &str,
+        channel: DataChannel,
+        action: &str,
+    ) -> Result<()> {
+        if channel == DataChannel::Depth {
+            return self.send_depth(write_tx, symbol, action);
+        }
+        let request = json!({
+            "method": format!("{}.{}", action, channel.name()),
+            "param": {
+                "symbol": symbol
+            }
+        });
+        write_tx.send(Message::Text(request.to_string()))?;
+        Ok(())
+    }
+
+    /// Sends `sub.*` or `unsub.*` requests for the ticker, fair price, depth, deal and funding
+    /// rate channels of a symbol
+    fn send_subscription(&self, write_tx: &mpsc::UnboundedSender<Message>, symbol: &str, action: &str) -> Result<()> {
+        // Ticker for this symbol
+        let ticker_sub = json!({
+            "method": format!("{}.ticker", action),
+            "param": {
+                "symbol": symbol
+            }
+        });
+        write_tx.send(Message::Text(ticker_sub.to_string()))?;
+
+        // Fair/mark price for this symbol
+        let mark_price_sub = json!({
+            "method": format!("{}.fair_price", action),
+            "param": {
+                "symbol": symbol
+            }
+        });
+        write_tx.send(Message::Text(mark_price_sub.to_string()))?;
+
+        // Orderbook depth for this symbol, unless it was dropped
+        if self.orderbooks && !self.depthless.contains(symbol) {
+            self.send_depth(write_tx, symbol, action)?;
+        }
+
+        // Trades for this symbol
+        if self.trades {
+            let deal_sub = json!({
+                "method": format!("{}.deal", action),
+                "param": {
+                    "symbol": symbol
+                }
+            });
+            write_tx.send(Message::Text(deal_sub.to_string()))?;
+        }
+
+        // Funding rate for this symbol
+        if self.funding_rates {
+            let funding_sub = json!({
+                "method": format!("{}.funding.rate", action),
+                "param": {
+                    "symbol": symbol
+                }
+            });
+            write_tx.send(Message::Text(funding_sub.to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn send_depth(&self, write_tx: &mpsc::UnboundedSender<Message>, symbol: &str, action: &str) -> Result<()> {
+        let depth_sub = json!({
+            "method": format!("{}.depth", action),
+            "param": {
+                "symbol": symbol,
+                "limit": self.depth_limit(symbol)
+            }
+        });
+        write_tx.send(Message::Text(depth_sub.to_string()))?;
+        Ok(())
+    }
+
+    fn depth_limit(&self, symbol: &str) -> usize {
+        self.depth_overrides.get(symbol).copied().unwrap_or(self.max_levels)
+    }
+
+    fn handle_message(
+        &self,
+        text: &str,
+        scratch: &mut Vec<u8>,
+        event_tx: &mpsc::UnboundedSender<MarketEvent>,
+    ) -> Result<()> {
+        let message = match PushMessage::decode(text, scratch) {
+            Ok(message) => message,
+            Err(e) => {
+                self.record_raw(text);
+                return Err(e.into());
+            }
+        };
+        if self.malformed.is_some() {
+            if let Some(lost) = message.lost_data() {
+                self.capture_malformed("partial", text, &lost);
+            }
+        }
+        match message {
+            PushMessage::Ticker { data } => self.handle_ticker(data, event_tx)?,
+            PushMessage::FairPrice { data } => self.handle_mark_price(data, event_tx)?,
+            PushMessage::Depth { symbol, data } => self.handle_orderbook(self.interner.intern(&symbol), data, event_tx)?,
+            PushMessage::Deal { symbol, data } => self.handle_deal(self.interner.intern(&symbol), data, event_tx)?,
+            PushMessage::Kline { symbol, data } => {
+                trace!(
+                    "Kline {} {} @ {} | o={} h={} l={} c={} v={}",
+                    symbol, data.interval, data.time, data.open, data.high, data.low, data.close, data.volume
+                );
+            }
+            PushMessage::FundingRate { symbol, data } => {
+                event_tx.send(MarketEvent::FundingRateUpdate {
+                    symbol: self.interner.intern(&symbol),
+                    rate: data.rate,
+                    timestamp: Utc::now(),
+                })?;
+            }
+            PushMessage::Pong | PushMessage::Untagged => {}
+            PushMessage::SubscriptionAck { data } => {
+                self.record_raw(text);
+                debug!("Subscription acknowledged: {}", data);
+            }
+            PushMessage::Error { data } => {
+                self.record_raw(text);
+                warn!("WebSocket error message from server: {}", data);
+            }
+            PushMessage::Unknown => {
+                self.record_raw(text);
+                self.capture_malformed("unknown_message", text, "unknown channel");
+                self.record_issue("unknown_message", text, None);
+                trace!("Ignoring message on unknown channel: {}", text);
             }
         }
 
@@ -194,7 +543,9 @@ impl MexcWebSocketClient {
             .unwrap_or_else(Utc::now);
 
         let event = MarketEvent::TickerUpdate {
-            symbol: ticker.symbol,
+            stats_24h: ticker.stats_24h(),
+            quote: ticker.quote(),
+            symbol: self.interner.intern(&ticker.symbol),
             last_price,
             mark_price,
             timestamp,
@@ -210,7 +561,7 @@ impl MexcWebSocketClient {
             .unwrap_or_else(Utc::now);
 
         let event = MarketEvent::MarkPriceUpdate {
-            symbol: data.symbol,
+            symbol: self.interner.intern(&data.symbol),
             mark_price,
             timestamp,
         };
@@ -219,9 +570,29 @@ impl MexcWebSocketClient {
         Ok(())
     }
 
-    fn handle_orderbook(&self, data: OrderbookData, event_tx: &mpsc::UnboundedSender<MarketEvent>) -> Result<()> {
-        let symbol = data.symbol.clone().ok_or_else(|| anyhow::anyhow!("Missing symbol in orderbook"))?;
-        let orderbook = ProcessedOrderbook::from_raw(&data, self.max_levels);
+    fn handle_deal(&self, symbol: Symbol, data: DealData, event_tx: &mpsc::UnboundedSender<MarketEvent>) -> Result<()> {
+        trace!("Deal {} | {} @ {} (side {}) at {}", symbol, data.volume, data.price, data.side, data.timestamp);
+        let timestamp = DateTime::from_timestamp_millis(data.timestamp)
+            .unwrap_or_else(Utc::now);
+
+        let event = MarketEvent::TradeUpdate {
+            symbol,
+            price: data.price,
+            volume: data.volume,
+            timestamp,
+        };
+
+        event_tx.send(event)?;
+        Ok(())
+    }
+
+    fn handle_orderbook(
+        &self,
+        symbol: Symbol,
+        data: OrderbookData,
+        event_tx: &mpsc::UnboundedSender<MarketEvent>,
+    ) -> Result<()> {
+        let orderbook = ProcessedOrderbook::from_raw(&data, self.depth_limit(&symbol));
 
         let event = MarketEvent::OrderbookUpdate {
             symbol,
@@ -232,3 +603,18 @@ impl MexcWebSocketClient {
         Ok(())
     }
 }
+
+#[async_trait]
+impl MarketStream for MexcWebSocketClient {
+    fn name(&self) -> &str {
+        "websocket"
+    }
+
+    async fn run(
+        self: Box<Self>,
+        event_tx: mpsc::UnboundedSender<MarketEvent>,
+        command_rx: mpsc::UnboundedReceiver<SubscriptionCommand>,
+    ) -> Result<()> {
+        self.run_forever(event_tx, command_rx).await
+    }
+}
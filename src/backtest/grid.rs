@@ -0,0 +1,149 @@
+use crate::config::Config;
+use crate::models::PositionSide;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+// A grid this large is almost certainly a mistake in the grid file
+const MAX_COMBINATIONS: usize = 10_000;
+
+/// A backtest grid file (see `backtest.example.toml`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BacktestSpec {
+    /// config.toml keys and the values to try for each, e.g.
+    /// `strategy1.spread_ratio_min = [1.2, 1.5]`; a single value is used in every run
+    pub grid: toml::Table,
+    #[serde(default)]
+    pub pnl: PnlConfig,
+}
+
+/// How the hypothetical trade of each alert is priced
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct PnlConfig {
+    /// Entered when the alert fires, closed when its episode ends; both fill against the
+    /// tape's orderbook at that moment
+    pub side: PositionSide,
+    /// Fee per side in percent of the notional, paid on entry and exit
+    pub fee_pct: f64,
+    pub notional_usdt: f64,
+    /// Base units per contract by symbol, to size `notional_usdt` in the contracts the
+    /// orderbook quantities are given in; 1 for symbols not listed
+    pub contract_sizes: HashMap<String, f64>,
+}
+
+impl Default for PnlConfig {
+    fn default() -> Self {
+        Self {
+            side: PositionSide::Short,
+            fee_pct: 0.02,
+            notional_usdt: 100.0,
+            contract_sizes: HashMap::new(),
+        }
+    }
+}
+
+impl PnlConfig {
+    /// Contracts of `symbol` that `notional_usdt` buys at `price`
+    pub fn contracts(&self, symbol: &str, price: f64) -> f64 {
+        let contract_size = self.contract_sizes.get(symbol).copied().unwrap_or(1.0);
+        self.notional_usdt / (price * contract_size)
+    }
+}
+
+impl BacktestSpec {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// Every combination of the grid's values; keys are taken in sorted order, the last one
+    /// varying fastest
+    pub fn parameter_sets(&self) -> Result<Vec<ParameterSet>> {
+        let mut axes = Vec::new();
+        collect_axes(&self.grid, "", &mut axes)?;
+
+        let total = axes.iter().try_fold(1usize, |total, (_, values)| total.checked_mul(values.len()));
+        match total {
+            Some(total) if total <= MAX_COMBINATIONS => {}
+            _ => bail!("the grid has more than {} combinations", MAX_COMBINATIONS),
+        }
+
+        let mut sets = vec![ParameterSet::default()];
+        for (key, values) in &axes {
+            sets = sets
+                .into_iter()
+                .flat_map(|set| {
+                    values.iter().map(move |value| {
+                        let mut set = set.clone();
+                        set.values.push((key.clone(), value.clone()));
+                        set
+                    })
+                })
+                .collect();
+        }
+        Ok(sets)
+    }
+}
+
+/// Flattens nested grid tables into dotted keys, each with the values to try
+fn collect_axes(table: &toml::Table, prefix: &str, axes: &mut Vec<(String, Vec<toml::Value>)>) -> Result<()> {
+    for (key, value) in table {
+        let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match value {
+            toml::Value::Table(nested) => collect_axes(nested, &path, axes)?,
+            toml::Value::Array(values) if values.is_empty() => bail!("grid key {} has no values", path),
+            toml::Value::Array(values) => axes.push((path, values.clone())),
+            single => axes.push((path, vec![single.clone()])),
+        }
+    }
+    Ok(())
+}
+
+/// One combination of grid values, applied on top of config.toml
+#[derive(Debug, Clone, Default)]
+pub struct ParameterSet {
+    /// Dotted config key and its value
+    pub values: Vec<(String, toml::Value)>,
+}
+
+impl ParameterSet {
+    /// `base` (the parsed config.toml) with this set's values in place. Fails on a key
+    /// config.toml doesn't support, as a typo would otherwise sweep nothing.
+    pub fn apply(&self, base: &toml::Table) -> Result<Config> {
+        let mut table = base.clone();
+        for (key, value) in &self.values {
+            let mut section = &mut table;
+            let mut parts = key.split('.').peekable();
+            while let Some(part) = parts.next() {
+                if parts.peek().is_none() {
+                    section.insert(part.to_string(), value.clone());
+                    break;
+                }
+                let entry = section
+                    .entry(part.to_string())
+                    .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+                section = match entry {
+                    toml::Value::Table(nested) => nested,
+                    _ => bail!("grid key {}: {} is not a section", key, part),
+                };
+            }
+        }
+        Config::from_table(table).with_context(|| format!("applying {}", self))
+    }
+}
+
+impl fmt::Display for ParameterSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.values.is_empty() {
+            return write!(f, "config.toml");
+        }
+        let values: Vec<String> = self.values.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+        write!(f, "{}", values.join(" "))
+    }
+}
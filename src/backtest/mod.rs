@@ -0,0 +1,7 @@
+pub mod grid;
+pub mod report;
+pub mod runner;
+
+pub use grid::*;
+pub use report::*;
+pub use runner::*;
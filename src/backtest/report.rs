@@ -0,0 +1,160 @@
+use crate::backtest::{BacktestTrade, PnlConfig, RunOutcome};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One strategy's results under one parameter set
+#[derive(Debug, Clone)]
+pub struct StrategySummary {
+    pub strategy: String,
+    pub triggers: usize,
+    pub avg_duration_secs: f64,
+    pub max_duration_secs: f64,
+    /// Trades that ended in profit after fees
+    pub wins: usize,
+    pub total_pnl_pct: f64,
+    pub avg_pnl_pct: f64,
+    /// `total_pnl_pct` on the configured notional per trade
+    pub pnl_usdt: f64,
+}
+
+/// Per-strategy summaries of a run, including enabled strategies that never fired
+pub fn summarize(outcome: &RunOutcome, pnl: &PnlConfig) -> Vec<StrategySummary> {
+    let mut by_strategy: BTreeMap<String, Vec<&BacktestTrade>> =
+        outcome.enabled.iter().map(|name| (name.to_string(), Vec::new())).collect();
+    for trade in &outcome.trades {
+        by_strategy.entry(trade.strategy.clone()).or_default().push(trade);
+    }
+
+    by_strategy
+        .into_iter()
+        .map(|(strategy, trades)| {
+            let triggers = trades.len();
+            let total_duration = trades.iter().map(|t| t.duration_secs()).fold(0.0, |sum, d| sum + d);
+            // Folded from +0.0: an empty f64 sum is -0.0, printed as "-0.000"
+            let total_pnl_pct = trades.iter().map(|t| t.pnl_pct).fold(0.0, |sum, pnl| sum + pnl);
+            let average = |total: f64| if triggers > 0 { total / triggers as f64 } else { 0.0 };
+            StrategySummary {
+                strategy,
+                triggers,
+                avg_duration_secs: average(total_duration),
+                max_duration_secs: trades.iter().map(|t| t.duration_secs()).fold(0.0, f64::max),
+                wins: trades.iter().filter(|t| t.pnl_pct > 0.0).count(),
+                total_pnl_pct,
+                avg_pnl_pct: average(total_pnl_pct),
+                pnl_usdt: total_pnl_pct / 100.0 * pnl.notional_usdt,
+            }
+        })
+        .collect()
+}
+
+/// Writes `report.csv` (a row per parameter set and strategy) to `out_dir` and each run's
+/// `trades.csv` to its `run_<index>` directory; returns the report's path
+pub fn write_report(out_dir: &Path, outcomes: &[RunOutcome], pnl: &PnlConfig) -> Result<PathBuf> {
+    fs::create_dir_all(out_dir)?;
+    let path = out_dir.join("report.csv");
+    let mut wtr = csv::Writer::from_path(&path)?;
+    wtr.write_record([
+        "run",
+        "parameters",
+        "strategy",
+        "triggers",
+        "avg_duration_secs",
+        "max_duration_secs",
+        "wins",
+        "total_pnl_pct",
+        "avg_pnl_pct",
+        "pnl_usdt",
+    ])?;
+    for outcome in outcomes {
+        for summary in summarize(outcome, pnl) {
+            wtr.write_record([
+                outcome.index.to_string(),
+                outcome.parameters.to_string(),
+                summary.strategy,
+                summary.triggers.to_string(),
+                format!("{:.1}", summary.avg_duration_secs),
+                format!("{:.1}", summary.max_duration_secs),
+                summary.wins.to_string(),
+                format!("{:.3}", summary.total_pnl_pct),
+                format!("{:.3}", summary.avg_pnl_pct),
+                format!("{:.2}", summary.pnl_usdt),
+            ])?;
+        }
+        write_trades(&out_dir.join(format!("run_{}", outcome.index)), &outcome.trades)?;
+    }
+    wtr.flush()?;
+    Ok(path)
+}
+
+fn write_trades(run_dir: &Path, trades: &[BacktestTrade]) -> Result<()> {
+    fs::create_dir_all(run_dir)?;
+    let mut wtr = csv::Writer::from_path(run_dir.join("trades.csv"))?;
+    wtr.write_record([
+        "entry_time",
+        "exit_time",
+        "episode_id",
+        "strategy",
+        "symbol",
+        "entry_price",
+        "exit_price",
+        "duration_secs",
+        "peak_ratio",
+        "filled_pct",
+        "slippage_pct",
+        "pnl_pct",
+    ])?;
+    for trade in trades {
+        wtr.write_record([
+            trade.entry_time.to_rfc3339(),
+            trade.exit_time.to_rfc3339(),
+            trade.episode_id.clone(),
+            trade.strategy.clone(),
+            trade.symbol.clone(),
+            format!("{:.8}", trade.entry_price),
+            format!("{:.8}", trade.exit_price),
+            format!("{:.1}", trade.duration_secs()),
+            format!("{:.4}", trade.peak_ratio),
+            format!("{:.1}", trade.filled_pct),
+            format!("{:.3}", trade.slippage_pct),
+            format!("{:.3}", trade.pnl_pct),
+        ])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Plain-text table of every run, best total PnL first
+pub fn render_table(outcomes: &[RunOutcome], pnl: &PnlConfig) -> String {
+    let mut rows: Vec<(&RunOutcome, StrategySummary)> = outcomes
+        .iter()
+        .flat_map(|outcome| summarize(outcome, pnl).into_iter().map(move |summary| (outcome, summary)))
+        .collect();
+    rows.sort_by(|a, b| b.1.total_pnl_pct.total_cmp(&a.1.total_pnl_pct));
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:>4}  {:<10} {:>8} {:>9} {:>9} {:>6} {:>10} {:>9} {:>10}  parameters",
+        "run", "strategy", "triggers", "avg_dur_s", "max_dur_s", "wins", "total_pnl%", "avg_pnl%", "pnl_usdt"
+    );
+    for (outcome, summary) in rows {
+        let _ = writeln!(
+            out,
+            "{:>4}  {:<10} {:>8} {:>9.1} {:>9.1} {:>6} {:>10.3} {:>9.3} {:>10.2}  {}",
+            outcome.index,
+            summary.strategy,
+            summary.triggers,
+            summary.avg_duration_secs,
+            summary.max_duration_secs,
+            summary.wins,
+            summary.total_pnl_pct,
+            summary.avg_pnl_pct,
+            summary.pnl_usdt,
+            outcome.parameters
+        );
+    }
+    out
+}
@@ -0,0 +1,445 @@
+use crate::alerts::{AlertSender, Notification};
+use crate::backtest::{ParameterSet, PnlConfig};
+use crate::config::Config;
+use crate::detection::{
+    build_strategies, handle_market_event, FeatureParams, ReadinessBoard, StrategyDeps, StrategyRegistry, StrategyThresholds,
+    Watchlist,
+};
+use crate::execution::simulate_fill;
+use crate::models::{MarketEvent, PositionSide, Symbol, SymbolData, SymbolInterner};
+use crate::replay::{read_tape_file, TapeEvent};
+use crate::utils::{DiskWriter, EpisodeLogWriter, EpisodeLogger, EpisodeStore, SimulatedClock};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tracing::{debug, info, warn};
+
+// Completed episodes are taken out of the store after every event, so this only has to
+// hold the episodes a single event can end
+const EPISODE_STORE_CAPACITY: usize = 256;
+
+// Unreadable tape lines reported individually before only counting them
+const MAX_LOGGED_ERRORS: usize = 10;
+
+/// The hypothetical trade on one alert: entered when the alert fired, closed when the
+/// alert's episode ended, each time as a market order against the tape's orderbook
+#[derive(Debug, Clone)]
+pub struct BacktestTrade {
+    pub strategy: String,
+    pub symbol: String,
+    pub episode_id: String,
+    pub entry_time: DateTime<Utc>,
+    pub exit_time: DateTime<Utc>,
+    /// Average fill prices
+    pub entry_price: f64,
+    pub exit_price: f64,
+    pub peak_ratio: f64,
+    /// Share of the notional the book could fill on entry, in percent
+    pub filled_pct: f64,
+    /// Adverse distance of the entry and exit fills from the last price, in percent
+    pub slippage_pct: f64,
+    /// Result after fees, in percent of the notional
+    pub pnl_pct: f64,
+}
+
+impl BacktestTrade {
+    pub fn duration_secs(&self) -> f64 {
+        (self.exit_time - self.entry_time).num_milliseconds() as f64 / 1000.0
+    }
+}
+
+/// What one parameter set did on the tape
+#[derive(Debug, Clone)]
+pub struct RunOutcome {
+    /// 1-based, also the name of the run's directory (`run_<index>`)
+    pub index: usize,
+    pub parameters: ParameterSet,
+    /// Strategies enabled under this parameter set, so ones that never fired are reported too
+    pub enabled: Vec<&'static str>,
+    pub trades: Vec<BacktestTrade>,
+}
+
+/// Runs the tape through the five strategies once per parameter set, each with a fresh
+/// state and a simulated clock that follows the tape. The tape is read once and shared by
+/// the runs, which play it in parallel. Every run keeps its episode logs under
+/// `<out_dir>/run_<index>`.
+///
+/// All sets are applied to `base` before the tape is read, so a bad grid key fails at once.
+pub async fn run_backtest(
+    files: &[PathBuf],
+    sets: &[ParameterSet],
+    base: &toml::Table,
+    pnl: &PnlConfig,
+    out_dir: &Path,
+) -> Result<Vec<RunOutcome>> {
+    let configs = sets.iter().map(|set| set.apply(base)).collect::<Result<Vec<_>>>()?;
+    let tape = Arc::new(read_tape(files)?);
+    run_sets(tape, sets, configs, pnl, out_dir).await
+}
+
+/// Every event of `files` in order, skipping unreadable lines
+pub fn read_tape(files: &[PathBuf]) -> Result<Vec<TapeEvent>> {
+    let mut events = Vec::new();
+    let mut errors = 0usize;
+    for file in files {
+        read_tape_file(
+            file,
+            |event| {
+                events.push(event);
+                true
+            },
+            |line, e| {
+                errors += 1;
+                if errors <= MAX_LOGGED_ERRORS {
+                    warn!("[Backtest] {}:{} skipped: {:#}", file.display(), line, e);
+                }
+            },
+        )?;
+    }
+    info!(
+        "[Backtest] {} events read{}",
+        events.len(),
+        if errors > 0 { format!(" | {} unreadable lines skipped", errors) } else { String::new() }
+    );
+    Ok(events)
+}
+
+/// Plays `tape` once per set on the blocking pool, as many runs at a time as there are cores
+pub async fn run_sets(
+    tape: Arc<Vec<TapeEvent>>,
+    sets: &[ParameterSet],
+    configs: Vec<Config>,
+    pnl: &PnlConfig,
+    out_dir: &Path,
+) -> Result<Vec<RunOutcome>> {
+    let parallel = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut running = JoinSet::new();
+    let mut outcomes = Vec::with_capacity(sets.len());
+    for (index, (set, config)) in sets.iter().zip(configs).enumerate() {
+        if running.len() >= parallel {
+            outcomes.push(running.join_next().await.expect("runs in flight")??);
+        }
+        let index = index + 1;
+        info!("[Backtest] Run {}/{}: {}", index, sets.len(), set);
+        let run_dir = out_dir.join(format!("run_{}", index));
+        let (tape, set, pnl) = (tape.clone(), set.clone(), pnl.clone());
+        running.spawn_blocking(move || -> Result<RunOutcome> {
+            let mut run = SimulatedRun::new(config, &run_dir, pnl)?;
+            for event in tape.iter() {
+                run.handle(event);
+            }
+            let enabled = run.enabled();
+            let trades = run.finish();
+            info!("[Backtest] Run {} done: {} trigger(s)", index, trades.len());
+            Ok(RunOutcome {
+                index,
+                parameters: set,
+                enabled,
+                trades,
+            })
+        });
+    }
+    while let Some(outcome) = running.join_next().await {
+        outcomes.push(outcome??);
+    }
+    outcomes.sort_by_key(|outcome| outcome.index);
+    Ok(outcomes)
+}
+
+/// A trade waiting for its episode to end
+struct OpenTrade {
+    strategy: String,
+    symbol: String,
+    entry_time: DateTime<Utc>,
+    entry: Fill,
+}
+
+/// A market order priced against the book
+#[derive(Debug, Clone, Copy)]
+struct Fill {
+    /// Average price of the filled contracts
+    price: f64,
+    /// Price of the deepest level touched
+    worst_price: f64,
+    contracts: f64,
+    /// Share of the requested contracts that filled, from 0 to 1
+    share: f64,
+    /// Adverse distance of `price` from the last price, in percent
+    slippage_pct: f64,
+}
+
+/// One parameter set's strategies and market state while the tape plays
+struct SimulatedRun {
+    config: Config,
+    clock: Arc<SimulatedClock>,
+    symbols: DashMap<Symbol, SymbolData>,
+    interner: SymbolInterner,
+    strategies: StrategyRegistry,
+    watchlist: Watchlist,
+    readiness: ReadinessBoard,
+    feature_params: FeatureParams,
+    alert_rx: mpsc::UnboundedReceiver<Notification>,
+    store: Arc<EpisodeStore>,
+    pnl: PnlConfig,
+    /// Episode id -> trade opened on its alert
+    open: HashMap<String, OpenTrade>,
+    trades: Vec<BacktestTrade>,
+}
+
+impl SimulatedRun {
+    fn new(mut config: Config, run_dir: &Path, pnl: PnlConfig) -> Result<Self> {
+        // As in a live run, Strategy4 is nothing but orderbook checks
+        if !config.orderbook.enabled {
+            config.strategy4.enabled = false;
+        }
+
+        let clock = Arc::new(SimulatedClock::new(DateTime::UNIX_EPOCH));
+        let log_dir = run_dir.to_str().context("backtest output path is not valid UTF-8")?;
+        let disk = DiskWriter::start(config.disk_io.clone())?;
+        let writer = EpisodeLogWriter::start(config.logging.episodes.clone(), disk);
+        let store = Arc::new(EpisodeStore::new(EPISODE_STORE_CAPACITY));
+        let logger = |name: &'static str| -> Result<Arc<EpisodeLogger>> {
+            Ok(Arc::new(EpisodeLogger::new(log_dir, name, store.clone(), writer.clone())?))
+        };
+        let (alert_tx, alert_rx): (AlertSender, _) = mpsc::unbounded_channel();
+        let deps = StrategyDeps {
+            alert_tx,
+            clock: clock.clone(),
+            csv_exporter: None,
+            contracts: None,
+            executor: None,
+        };
+        let strategies = build_strategies(&config, &StrategyThresholds::from_config(&config)?, deps, logger, |_| Arc::default())?;
+        // Nothing subscribes offline, so the deeper books of watched symbols are never asked for
+        let (command_tx, _) = mpsc::unbounded_channel();
+        let watchlist = Watchlist::new(config.watchlist.clone(), command_tx, config.orderbook.max_levels);
+
+        Ok(Self {
+            feature_params: FeatureParams::from_config(&config),
+            config,
+            clock,
+            symbols: DashMap::new(),
+            interner: SymbolInterner::new(),
+            strategies,
+            watchlist,
+            readiness: ReadinessBoard::default(),
+            alert_rx,
+            store,
+            pnl,
+            open: HashMap::new(),
+            trades: Vec::new(),
+        })
+    }
+
+    fn enabled(&self) -> Vec<&'static str> {
+        let config = &self.config;
+        [
+            ("strategy1", config.strategy1.enabled),
+            ("strategy2", config.strategy2.enabled),
+            ("strategy3", config.strategy3.enabled),
+            ("strategy4", config.strategy4.enabled),
+            ("strategy5", config.strategy5.enabled),
+        ]
+        .into_iter()
+        .filter_map(|(name, enabled)| enabled.then_some(name))
+        .collect()
+    }
+
+    /// Applies one tape event through the live event loop's handler, then settles the trades
+    /// of the alerts and episodes it caused
+    fn handle(&mut self, event: &TapeEvent) {
+        self.clock.set(event.time());
+        let event = event.to_event(&self.interner);
+        // Orderbooks aren't subscribed in low-memory mode
+        if !self.config.orderbook.enabled && matches!(event, MarketEvent::OrderbookUpdate { .. }) {
+            return;
+        }
+        // Tape symbols are only known once their first event arrives
+        if let Some(symbol) = event.interned_symbol() {
+            if !self.symbols.contains_key(&**symbol) {
+                self.symbols.insert(symbol.clone(), SymbolData::new(symbol.clone(), self.clock.clone()));
+            }
+        }
+        handle_market_event(
+            event,
+            &self.symbols,
+            &mut self.strategies,
+            None,
+            &self.readiness,
+            &self.watchlist,
+            &mut None,
+            None,
+            self.feature_params,
+            None,
+            // Pipelines are extra parameter sets; a backtest sweeps those through its grid
+            &mut [],
+            None,
+            self.config.market_stream.stale_grace_secs,
+        );
+        self.settle();
+    }
+
+    /// Walks a market order for `contracts` through the symbol's current orderbook from the
+    /// tape. Without a book (orderbooks off, or none recorded yet) it fills whole at `price`.
+    fn fill(&self, symbol: &str, side: PositionSide, contracts: f64, price: f64) -> Fill {
+        let book = self.symbols.get(symbol).and_then(|data| data.orderbook.clone());
+        let Some(book) = book else {
+            return Fill { price, worst_price: price, contracts, share: 1.0, slippage_pct: 0.0 };
+        };
+        let fill = simulate_fill(&book, side, contracts);
+        debug!(
+            "[Backtest] {:?} {}: {}/{} contracts @ {:?} over {} levels",
+            side, symbol, fill.filled, fill.requested, fill.avg_price, fill.levels_consumed
+        );
+        Fill {
+            price: fill.avg_price.unwrap_or(price),
+            worst_price: fill.worst_price.unwrap_or(price),
+            contracts: fill.filled,
+            share: if fill.is_partial() { fill.filled / fill.requested } else { 1.0 },
+            slippage_pct: fill.slippage_pct(side, price).unwrap_or(0.0),
+        }
+    }
+
+    /// Opens a trade for every new alert and closes the trades of the episodes that ended
+    fn settle(&mut self) {
+        while let Ok(notification) = self.alert_rx.try_recv() {
+            if let Notification::Alert(alert) = notification {
+                let contracts = self.pnl.contracts(&alert.symbol, alert.last_price);
+                let entry = self.fill(&alert.symbol, self.pnl.side, contracts, alert.last_price);
+                self.open.insert(
+                    alert.episode_id,
+                    OpenTrade {
+                        strategy: alert.strategy,
+                        symbol: alert.symbol,
+                        entry_time: alert.timestamp,
+                        entry,
+                    },
+                );
+            }
+        }
+
+        for episode in self.store.drain() {
+            let Some(trade) = self.open.remove(&episode.id) else {
+                continue;
+            };
+            let entry = trade.entry;
+            let last_price = self
+                .symbols
+                .get(trade.symbol.as_str())
+                .and_then(|data| data.current_last_price)
+                .unwrap_or(entry.price);
+            let exit = self.fill(&trade.symbol, self.pnl.side.opposite(), entry.contracts, last_price);
+            // The position has to be closed: what the visible book can't take is assumed to
+            // go no better than its deepest level
+            let exit_price = exit.price * exit.share + exit.worst_price * (1.0 - exit.share);
+            // A close takes the other side of the book, so its slippage is the other side's
+            let slippage_pct = entry.slippage_pct + self.pnl.side.opposite().profit_pct(last_price, exit_price);
+            // Only the part of the notional that filled on entry makes or loses anything
+            let pnl_pct = (self.pnl.side.profit_pct(entry.price, exit_price) - 2.0 * self.pnl.fee_pct) * entry.share;
+            self.trades.push(BacktestTrade {
+                strategy: trade.strategy,
+                symbol: trade.symbol,
+                episode_id: episode.id,
+                entry_time: trade.entry_time,
+                exit_time: episode.end_time,
+                entry_price: entry.price,
+                exit_price,
+                peak_ratio: episode.peak_ratio,
+                filled_pct: entry.share * 100.0,
+                slippage_pct,
+                pnl_pct,
+            });
+        }
+    }
+
+    /// Ends the episodes still open when the tape runs out, closing their trades at the
+    /// last price seen
+    fn finish(mut self) -> Vec<BacktestTrade> {
+        let mut open_symbols: Vec<String> = self.open.values().map(|trade| trade.symbol.clone()).collect();
+        open_symbols.sort();
+        open_symbols.dedup();
+        for symbol in open_symbols {
+            self.strategies.force_end_episode(&symbol, "end of tape");
+        }
+        self.settle();
+        self.trades.sort_by_key(|trade| trade.entry_time);
+        self.trades
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replay::fixtures::RecordedIncident;
+
+    fn default_config() -> Config {
+        Config::load(Path::new(env!("CARGO_MANIFEST_DIR")).join("config.toml")).expect("config.toml loads")
+    }
+
+    #[tokio::test]
+    async fn fills_walk_the_tape_book() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let mut run = SimulatedRun::new(default_config(), out_dir.path(), PnlConfig::default()).unwrap();
+        run.handle(&TapeEvent::Depth {
+            time: DateTime::UNIX_EPOCH,
+            symbol: "THIN_USDT".to_string(),
+            bids: vec![(0.99, 10.0), (0.98, 5.0)],
+            asks: vec![(1.01, 10.0), (1.02, 5.0)],
+        });
+
+        // A short hits the bids; the book only holds 15 of the 20 contracts
+        let entry = run.fill("THIN_USDT", PositionSide::Short, 20.0, 1.0);
+        assert_eq!(entry.contracts, 15.0);
+        assert_eq!(entry.share, 0.75);
+        assert!((entry.price - (0.99 * 10.0 + 0.98 * 5.0) / 15.0).abs() < 1e-12);
+        assert_eq!(entry.worst_price, 0.98);
+
+        // Covering lifts the asks
+        let exit = run.fill("THIN_USDT", PositionSide::Short.opposite(), 12.0, 1.0);
+        assert_eq!(exit.share, 1.0);
+        assert!((exit.price - (1.01 * 10.0 + 1.02 * 2.0) / 12.0).abs() < 1e-12);
+
+        // No book recorded for the symbol: everything at the last price
+        let unbooked = run.fill("OTHER_USDT", PositionSide::Short, 20.0, 2.0);
+        assert_eq!((unbooked.price, unbooked.contracts, unbooked.share), (2.0, 20.0, 1.0));
+    }
+
+    #[tokio::test]
+    async fn parameter_sets_run_independently_on_one_tape() {
+        let recording = RecordedIncident::load(
+            &Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures/pumps/SYNTH_SPIKE_USDT_20240610_061320_01J00CGEHMM9S346Q3D25VT4F5"),
+        )
+        .unwrap();
+        let tape = Arc::new(recording.tape());
+        let base: toml::Table =
+            toml::from_str(&std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("config.toml")).unwrap())
+                .unwrap();
+        let unreachable = ParameterSet {
+            values: vec![("strategy1.spread_ratio_min".to_string(), toml::Value::Float(100.0))],
+        };
+        let sets = vec![ParameterSet::default(), unreachable, ParameterSet::default()];
+        let configs = sets.iter().map(|set| set.apply(&base)).collect::<Result<Vec<_>>>().unwrap();
+        let out_dir = tempfile::tempdir().unwrap();
+
+        let outcomes = run_sets(tape.clone(), &sets, configs, &PnlConfig::default(), out_dir.path()).await.unwrap();
+
+        let triggers = |outcome: &RunOutcome, strategy: &str| -> Vec<DateTime<Utc>> {
+            outcome.trades.iter().filter(|t| t.strategy == strategy).map(|t| t.entry_time).collect()
+        };
+        assert_eq!(outcomes.iter().map(|o| o.index).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert!(!triggers(&outcomes[0], "strategy1").is_empty());
+        // Raising one set's threshold leaves the other strategies of that set, and the other sets, alone
+        assert!(triggers(&outcomes[1], "strategy1").is_empty());
+        assert_eq!(triggers(&outcomes[1], "strategy2"), triggers(&outcomes[0], "strategy2"));
+        assert!(!triggers(&outcomes[1], "strategy2").is_empty());
+        // The same set on the same shared tape comes out the same
+        assert_eq!(triggers(&outcomes[2], "strategy1"), triggers(&outcomes[0], "strategy1"));
+        assert_eq!(Arc::strong_count(&tape), 1);
+    }
+}
@@ -1,10 +1,17 @@
-use serde::Deserialize;
+use crate::config_schema::{find_unknown_keys, format_unknown_keys};
+use crate::detection::StrategyThresholds;
+use crate::models::{OrderType, PositionSide, SymbolData};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::Path;
 
 #[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     pub api: ApiConfig,
+    #[serde(default)]
+    pub rest: RestConfig,
     pub general: GeneralConfig,
     pub cooldowns: CooldownConfig,
     pub orderbook: OrderbookConfig,
@@ -14,15 +21,183 @@ pub struct Config {
     pub strategy4: Strategy4Config,
     pub strategy5: Strategy5Config,
     pub csv_export: CsvExportConfig,
+    #[serde(default)]
+    pub book_heatmap: BookHeatmapConfig,
+    #[serde(default)]
+    pub web: WebConfig,
+    /// Read from the separate execution.toml by `load_execution`; detection only without it
+    #[serde(skip)]
+    pub execution: ExecutionConfig,
+    #[serde(default)]
+    pub profiling: ProfilingConfig,
+    #[serde(default)]
+    pub resource_budget: ResourceBudgetConfig,
+    #[serde(default)]
+    pub universe: UniverseConfig,
+    #[serde(default)]
+    pub kline_check: KlineCheckConfig,
+    #[serde(default)]
+    pub missed_digest: MissedDigestConfig,
+    #[serde(default)]
+    pub latency_budget: LatencyBudgetConfig,
+    #[serde(default)]
+    pub funding: FundingConfig,
+    #[serde(default)]
+    pub open_interest: OpenInterestConfig,
+    #[serde(default)]
+    pub channel_health: ChannelHealthConfig,
+    #[serde(default)]
+    pub market_stream: MarketStreamConfig,
+    #[serde(default)]
+    pub recording: RecordingConfig,
+    #[serde(default)]
+    pub event_priority: EventPriorityConfig,
+    #[serde(default)]
+    pub ratio_export: RatioExportConfig,
+    #[serde(default)]
+    pub watchlist: WatchlistConfig,
+    #[serde(default)]
+    pub ratio_baseline: RatioBaselineConfig,
+    #[serde(default)]
+    pub precision: PrecisionConfig,
+    #[serde(default)]
+    pub correlation: CorrelationConfig,
+    #[serde(default)]
+    pub throttle: ThrottleConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    #[serde(default)]
+    pub mqtt: MqttConfig,
+    #[serde(default)]
+    pub inter_contract: InterContractConfig,
+    #[serde(default)]
+    pub dormancy: DormancyConfig,
+    #[serde(default)]
+    pub tiers: TiersConfig,
+    #[serde(default)]
+    pub contract_monitor: ContractMonitorConfig,
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+    #[serde(default)]
+    pub control: ControlConfig,
+    #[serde(default)]
+    pub manual_signals: ManualSignalConfig,
+    #[serde(default)]
+    pub market_context: MarketContextConfig,
+    #[serde(default)]
+    pub disk_io: DiskIoConfig,
+    #[serde(default)]
+    pub issue_bundle: IssueBundleConfig,
+    #[serde(default)]
+    pub parsing: ParsingConfig,
+    #[serde(default)]
+    pub pipelines: BTreeMap<String, PipelineConfig>,
+    /// Strategy options that differ for one symbol, e.g.
+    /// `[symbol_overrides."BTC_USDT".strategy1] spread_ratio_min = 1.002`
+    #[serde(default)]
+    pub symbol_overrides: BTreeMap<String, toml::Table>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ApiConfig {
     pub base_rest_url: String,
     pub base_ws_url: String,
+    /// Order placement and private WebSocket; default to the market data endpoints
+    #[serde(default)]
+    pub trading_rest_url: Option<String>,
+    #[serde(default)]
+    pub trading_ws_url: Option<String>,
+    /// Prefix of the trading key variables (`{prefix}_API_KEY`); default `MEXC`
+    #[serde(default)]
+    pub credentials_prefix: Option<String>,
+    /// Named endpoint sets selectable with `--env`; the fields above are the `prod` set
+    #[serde(default)]
+    pub profiles: HashMap<String, ApiProfile>,
 }
 
+/// One environment's endpoints, e.g. `[api.profiles.testnet]`
 #[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiProfile {
+    pub base_rest_url: String,
+    pub base_ws_url: String,
+    #[serde(default)]
+    pub trading_rest_url: Option<String>,
+    #[serde(default)]
+    pub trading_ws_url: Option<String>,
+    #[serde(default)]
+    pub credentials_prefix: Option<String>,
+}
+
+/// Timeouts and retries of REST calls
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct RestConfig {
+    pub connect_timeout_ms: u64,
+    /// Whole-request limit; signed (trading) calls use their own per-call timeouts
+    pub request_timeout_ms: u64,
+    /// Retries after a failed public request; signed calls are never retried blindly
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for each further one
+    pub retry_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RestConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_ms: 5000,
+            request_timeout_ms: 10000,
+            max_retries: 3,
+            retry_backoff_ms: 500,
+            max_backoff_ms: 8000,
+        }
+    }
+}
+
+/// Environment used when no `--env` is given
+pub const DEFAULT_ENVIRONMENT: &str = "prod";
+
+impl ApiConfig {
+    /// Replaces the endpoints with the named profile's. `prod` selects the top-level
+    /// endpoints unless a profile of that name exists.
+    pub fn select_environment(&mut self, name: &str) -> anyhow::Result<()> {
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            if name == DEFAULT_ENVIRONMENT {
+                return Ok(());
+            }
+            let mut available: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            available.sort_unstable();
+            available.insert(0, DEFAULT_ENVIRONMENT);
+            anyhow::bail!("unknown environment '{}' (available: {})", name, available.join(", "));
+        };
+
+        self.base_rest_url = profile.base_rest_url;
+        self.base_ws_url = profile.base_ws_url;
+        self.trading_rest_url = profile.trading_rest_url;
+        self.trading_ws_url = profile.trading_ws_url;
+        self.credentials_prefix = profile.credentials_prefix;
+        Ok(())
+    }
+
+    pub fn trading_rest_url(&self) -> &str {
+        self.trading_rest_url.as_deref().unwrap_or(&self.base_rest_url)
+    }
+
+    pub fn trading_ws_url(&self) -> &str {
+        self.trading_ws_url.as_deref().unwrap_or(&self.base_ws_url)
+    }
+
+    #[cfg(feature = "execution")]
+    pub fn credentials_prefix(&self) -> &str {
+        self.credentials_prefix.as_deref().unwrap_or("MEXC")
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct GeneralConfig {
     pub symbols: Vec<String>,
     pub log_dir: String,
@@ -30,36 +205,162 @@ pub struct GeneralConfig {
 }
 
 #[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct CooldownConfig {
+    /// Between episodes of one strategy on one symbol, applied by the episode trackers
     pub per_symbol_seconds: u64,
+    /// Between delivered alerts for one symbol from any strategy; 0 = off
+    #[serde(default)]
+    pub per_symbol_any_strategy_seconds: u64,
+    /// Between any two delivered alerts; 0 = off
+    #[serde(default)]
+    pub global_seconds: u64,
 }
 
 #[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct OrderbookConfig {
+    /// Subscribe to and keep orderbooks; off for price-only (low-memory) operation
+    #[serde(default = "default_true")]
+    pub enabled: bool,
     pub max_levels: usize,
     pub depth_band_pct: f64,
     pub min_thick_depth_usdt: f64,
     pub max_spread_pct: f64,
+    /// Ignore last prices this far outside the best bid/ask (0 = no check)
+    #[serde(default)]
+    pub max_last_outside_quote_pct: f64,
+    /// Widen or narrow `depth_band_pct` per symbol with its recent volatility
+    #[serde(default)]
+    pub adaptive_band: AdaptiveBandConfig,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// How a symbol's volatility is turned into its depth band
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BandMapping {
+    /// Band grows in proportion to the volatility
+    Linear,
+    /// Band grows with the square root of the volatility, so very jumpy coins widen less
+    Sqrt,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct AdaptiveBandConfig {
+    pub enabled: bool,
+    /// Window the mark price volatility is measured over (at most 120s of history is kept)
+    pub lookback_secs: u64,
+    /// Volatility (stddev / mean of the mark price) at which the band equals `depth_band_pct`
+    pub reference_volatility: f64,
+    pub mapping: BandMapping,
+    pub min_band_pct: f64,
+    pub max_band_pct: f64,
+}
+
+impl Default for AdaptiveBandConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lookback_secs: 60,
+            reference_volatility: 0.002,
+            mapping: BandMapping::Linear,
+            min_band_pct: 0.002,
+            max_band_pct: 0.05,
+        }
+    }
+}
+
+impl AdaptiveBandConfig {
+    /// `base_pct` scaled by how `data`'s recent volatility compares with the reference and
+    /// clamped to the min/max band; `base_pct` itself while disabled or until the symbol has
+    /// `lookback_secs` of history
+    pub fn band_pct(&self, base_pct: f64, data: &SymbolData) -> f64 {
+        if !self.enabled || self.reference_volatility <= 0.0 {
+            return base_pct;
+        }
+        let Some(volatility) = data.mark_price_volatility(self.lookback_secs) else {
+            return base_pct;
+        };
+        let scale = volatility / self.reference_volatility;
+        let scale = match self.mapping {
+            BandMapping::Linear => scale,
+            BandMapping::Sqrt => scale.sqrt(),
+        };
+        (base_pct * scale).clamp(self.min_band_pct, self.max_band_pct.max(self.min_band_pct))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Strategy1Config {
     pub enabled: bool,
     pub spread_ratio_min: f64,
     pub min_abs_diff: f64,
     pub min_price: f64,
+    /// Minimum 24h turnover in USDT (0 = no requirement)
+    #[serde(default)]
+    pub min_volume_24h_usdt: f64,
+    /// Minimum last price * contract size in USDT (0 = no requirement)
+    #[serde(default)]
+    pub min_contract_notional_usdt: f64,
+    /// Skip symbols whose current funding rate is further from zero than this (0 = no limit)
+    #[serde(default)]
+    pub max_abs_funding_rate: f64,
+    /// Minimum open interest increase in percent over `oi_lookback_secs` (0 = no requirement)
+    #[serde(default)]
+    pub min_oi_increase_pct: f64,
+    #[serde(default = "default_oi_lookback_secs")]
+    pub oi_lookback_secs: u64,
+    /// Minimum last price in price ticks (0 = no requirement)
+    #[serde(default)]
+    pub min_price_ticks: f64,
+    /// Minimum best bid / mark price (0 = no requirement)
+    #[serde(default)]
+    pub min_tradable_ratio: f64,
+    #[serde(default)]
+    pub end: EpisodeEndConfig,
+    #[serde(default)]
+    pub recording: StrategyRecordingConfig,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Strategy2Config {
     pub enabled: bool,
     pub spread_ratio_min: f64,
     pub spike_lookback_secs: u64,
     pub spike_ratio_min: f64,
     pub min_price: f64,
+    /// Minimum 24h turnover in USDT (0 = no requirement)
+    #[serde(default)]
+    pub min_volume_24h_usdt: f64,
+    /// Minimum last price * contract size in USDT (0 = no requirement)
+    #[serde(default)]
+    pub min_contract_notional_usdt: f64,
+    /// Skip symbols whose current funding rate is further from zero than this (0 = no limit)
+    #[serde(default)]
+    pub max_abs_funding_rate: f64,
+    /// Minimum open interest increase in percent over `oi_lookback_secs` (0 = no requirement)
+    #[serde(default)]
+    pub min_oi_increase_pct: f64,
+    #[serde(default = "default_oi_lookback_secs")]
+    pub oi_lookback_secs: u64,
+    /// Minimum last price in price ticks (0 = no requirement)
+    #[serde(default)]
+    pub min_price_ticks: f64,
+    /// Minimum best bid / mark price (0 = no requirement)
+    #[serde(default)]
+    pub min_tradable_ratio: f64,
+    #[serde(default)]
+    pub end: EpisodeEndConfig,
+    #[serde(default)]
+    pub recording: StrategyRecordingConfig,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Strategy3Config {
     pub enabled: bool,
     pub spread_ratio_min: f64,
@@ -67,35 +368,1581 @@ pub struct Strategy3Config {
     pub pump_vs_baseline_min: f64,
     pub mark_stability_max: f64,
     pub min_price: f64,
+    /// Minimum 24h turnover in USDT (0 = no requirement)
+    #[serde(default)]
+    pub min_volume_24h_usdt: f64,
+    /// Minimum last price * contract size in USDT (0 = no requirement)
+    #[serde(default)]
+    pub min_contract_notional_usdt: f64,
+    /// Skip symbols whose current funding rate is further from zero than this (0 = no limit)
+    #[serde(default)]
+    pub max_abs_funding_rate: f64,
+    /// Minimum open interest increase in percent over `oi_lookback_secs` (0 = no requirement)
+    #[serde(default)]
+    pub min_oi_increase_pct: f64,
+    #[serde(default = "default_oi_lookback_secs")]
+    pub oi_lookback_secs: u64,
+    /// Minimum last price in price ticks (0 = no requirement)
+    #[serde(default)]
+    pub min_price_ticks: f64,
+    /// Minimum best bid / mark price (0 = no requirement)
+    #[serde(default)]
+    pub min_tradable_ratio: f64,
+    #[serde(default)]
+    pub end: EpisodeEndConfig,
+    #[serde(default)]
+    pub recording: StrategyRecordingConfig,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Strategy4Config {
     pub enabled: bool,
     pub spread_ratio_min: f64,
     pub min_abs_diff: f64,
     pub min_price: f64,
+    /// Minimum 24h turnover in USDT (0 = no requirement)
+    #[serde(default)]
+    pub min_volume_24h_usdt: f64,
+    /// Minimum last price * contract size in USDT (0 = no requirement)
+    #[serde(default)]
+    pub min_contract_notional_usdt: f64,
+    /// Skip symbols whose current funding rate is further from zero than this (0 = no limit)
+    #[serde(default)]
+    pub max_abs_funding_rate: f64,
+    /// Minimum open interest increase in percent over `oi_lookback_secs` (0 = no requirement)
+    #[serde(default)]
+    pub min_oi_increase_pct: f64,
+    #[serde(default = "default_oi_lookback_secs")]
+    pub oi_lookback_secs: u64,
+    /// Minimum last price in price ticks (0 = no requirement)
+    #[serde(default)]
+    pub min_price_ticks: f64,
+    #[serde(default)]
+    pub end: EpisodeEndConfig,
+    #[serde(default)]
+    pub recording: StrategyRecordingConfig,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Strategy5Config {
     pub enabled: bool,
     pub min_price: f64,
+    /// Minimum 24h turnover in USDT (0 = no requirement)
+    #[serde(default)]
+    pub min_volume_24h_usdt: f64,
+    /// Minimum last price * contract size in USDT (0 = no requirement)
+    #[serde(default)]
+    pub min_contract_notional_usdt: f64,
+    /// Skip symbols whose current funding rate is further from zero than this (0 = no limit)
+    #[serde(default)]
+    pub max_abs_funding_rate: f64,
+    /// Minimum open interest increase in percent over `oi_lookback_secs` (0 = no requirement)
+    #[serde(default)]
+    pub min_oi_increase_pct: f64,
+    #[serde(default = "default_oi_lookback_secs")]
+    pub oi_lookback_secs: u64,
+    /// Minimum last price in price ticks (0 = no requirement)
+    #[serde(default)]
+    pub min_price_ticks: f64,
+    #[serde(default)]
+    pub patterns: PatternConfig,
+    #[serde(default)]
+    pub end: EpisodeEndConfig,
+    #[serde(default)]
+    pub recording: StrategyRecordingConfig,
+}
+
+fn default_oi_lookback_secs() -> u64 {
+    60
+}
+
+/// When a strategy's episode ends; by default as soon as its start condition stops holding
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct EpisodeEndConfig {
+    /// Only end once last/mark has fallen below this level (hysteresis below the start threshold)
+    pub ratio_below: Option<f64>,
+    /// How long the end criteria must hold continuously before the episode ends
+    pub hold_secs: u64,
+}
+
+/// Optional candle-shape conditions on completed last-price candles; unset ones are not checked
+/// Per-strategy chart recording lengths; unset values fall back to `[csv_export]`
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct StrategyRecordingConfig {
+    pub pre_anomaly_buffer_secs: Option<i64>,
+    pub post_anomaly_recording_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct PatternConfig {
+    pub min_consecutive_up: Option<usize>,
+    pub min_body_ratio: Option<f64>,
+    pub max_upper_wick_ratio: Option<f64>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct CsvExportConfig {
     pub enabled: bool,
     pub charts_dir: String,
     pub pre_anomaly_buffer_secs: i64,
     pub post_anomaly_recording_secs: i64,
-    pub candle_interval_ms: i64,
+    /// Extra candle resolutions written next to the 500ms candles, rolled up from them
+    #[serde(default)]
+    pub resolutions_ms: Vec<i64>,
+    /// Write the incident CSVs gzip-compressed (`.csv.gz`)
+    #[serde(default)]
+    pub compress: bool,
+    /// Finer candles (ms) written for the seconds around the trigger; 0 = off
+    #[serde(default)]
+    pub fine_candle_ms: i64,
+    /// Length of the fine-candle window, centered on the trigger
+    #[serde(default = "default_fine_window_secs")]
+    pub fine_window_secs: i64,
+}
+
+fn default_fine_window_secs() -> i64 {
+    10
+}
+
+/// Resting liquidity per price bucket, sampled periodically while an incident is recorded
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct BookHeatmapConfig {
+    pub enabled: bool,
+    pub sample_interval_ms: u64,
+    /// Bucket width in basis points of the mid price at the first sample
+    pub bucket_bps: f64,
+    /// Levels further than this from the mid price are left out (%)
+    pub max_distance_pct: f64,
+    /// Also write the heatmap as Parquet (needs the `parquet-export` feature)
+    pub parquet: bool,
+}
+
+impl Default for BookHeatmapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sample_interval_ms: 500,
+            bucket_bps: 10.0,
+            max_distance_pct: 5.0,
+            parquet: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct WebConfig {
+    pub enabled: bool,
+    pub bind_addr: String,
+    pub feed_title: String,
+    pub feed_max_entries: usize,
+    /// How often browsers on `/ws` get a snapshot of the monitored symbols; 0 = signals only
+    pub snapshot_interval_ms: u64,
+    /// Symbols per snapshot, widest spreads first; 0 = all
+    pub snapshot_max_symbols: usize,
+}
+
+impl Default for WebConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_addr: "127.0.0.1:8080".to_string(),
+            feed_title: "MEXC Sniper Episodes".to_string(),
+            feed_max_entries: 50,
+            snapshot_interval_ms: 1000,
+            snapshot_max_symbols: 50,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct ExecutionConfig {
+    pub enabled: bool,
+    pub risk_usdt: f64,
+    pub private_ws_enabled: bool,
+    pub relogin_interval_secs: u64,
+    pub login_timeout_secs: u64,
+    pub dry_run: bool,
+    pub side: PositionSide,
+    pub order_type: OrderType,
+    /// How far beyond the signal price limit orders are placed (%)
+    pub limit_offset_pct: f64,
+    /// Sent with every order; 0 keeps the account's leverage setting
+    pub leverage: u32,
+    /// Entries are skipped while this many positions are open; 0 = no limit
+    pub max_open_positions: usize,
+    pub order_timeout_ms: u64,
+    pub max_submit_attempts: u32,
+    /// Wait before resending an order the exchange never saw, doubled per resend (ms)
+    pub resubmit_backoff_ms: u64,
+    pub reconcile_interval_secs: u64,
+    /// Fee per fill in percent of the notional, recorded with each trade (fills don't report theirs)
+    pub fee_pct: f64,
+    /// Strategy name -> scale-out exits of the positions its signals open
+    pub exits: HashMap<String, ExitLadderConfig>,
+    pub anticipate: AnticipateConfig,
+    pub ledger_export: LedgerExportConfig,
+    /// Used when the API key variables aren't set in the environment
+    pub credentials: CredentialsConfig,
+    pub sub_account: SubAccountConfig,
+}
+
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            risk_usdt: 25.0,
+            private_ws_enabled: false,
+            relogin_interval_secs: 600,
+            login_timeout_secs: 10,
+            dry_run: true,
+            side: PositionSide::Short,
+            order_type: OrderType::Market,
+            limit_offset_pct: 0.2,
+            leverage: 0,
+            max_open_positions: 0,
+            order_timeout_ms: 3000,
+            max_submit_attempts: 3,
+            resubmit_backoff_ms: 250,
+            reconcile_interval_secs: 10,
+            fee_pct: 0.02,
+            exits: HashMap::new(),
+            anticipate: AnticipateConfig::default(),
+            ledger_export: LedgerExportConfig::default(),
+            credentials: CredentialsConfig::default(),
+            sub_account: SubAccountConfig::default(),
+        }
+    }
+}
+
+/// CSV layout of a trade ledger export
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LedgerFormat {
+    /// timestamp, symbol, buy/sell, quantity, price, fee and realized PnL per fill
+    Generic,
+    /// Koinly universal import format
+    Koinly,
+    /// CoinTracking CSV import format
+    Cointracking,
+}
+
+#[cfg(feature = "execution")]
+impl LedgerFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LedgerFormat::Generic => "generic",
+            LedgerFormat::Koinly => "koinly",
+            LedgerFormat::Cointracking => "cointracking",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        [LedgerFormat::Generic, LedgerFormat::Koinly, LedgerFormat::Cointracking]
+            .into_iter()
+            .find(|format| format.name() == name)
+    }
+}
+
+/// Trade ledger copies in portfolio tracker formats, written next to trades.csv as trades happen
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct LedgerExportConfig {
+    /// Each gets its own `trades_<format>.csv`; empty writes only trades.csv
+    pub formats: Vec<LedgerFormat>,
+    /// Also export dry-run (paper) trades
+    pub include_paper: bool,
+}
+
+/// Small resting limit order placed when a watched symbol's book leans to the bid side,
+/// ahead of the signal that would confirm the entry
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct AnticipateConfig {
+    pub enabled: bool,
+    /// Share of `risk_usdt` put into the anticipation order (%)
+    pub risk_pct: f64,
+    /// How far above the last price the limit sits (%)
+    pub offset_pct: f64,
+    /// (bid - ask) / (bid + ask) notional of the kept book levels needed to place it
+    pub min_book_imbalance: f64,
+    /// Cancelled unless a strategy confirms the entry within this time
+    pub confirm_secs: u64,
+}
+
+impl Default for AnticipateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            risk_pct: 25.0,
+            offset_pct: 0.1,
+            min_book_imbalance: 0.3,
+            confirm_secs: 10,
+        }
+    }
+}
+
+/// Closes a position in steps as it moves into profit, then the remainder on a trailing
+/// stop or once the spread has normalized
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct ExitLadderConfig {
+    pub steps: Vec<ExitStep>,
+    /// Give-back from the best price since entry, armed once the first step has closed
+    pub trailing_stop_pct: Option<f64>,
+    /// Last/mark ratio below which the remainder is closed
+    pub ratio_below: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(not(feature = "execution"), allow(dead_code))]
+pub struct ExitStep {
+    /// Profit in the position's favor, in percent of the entry price
+    pub profit_pct: f64,
+    /// Share of the entry size to close, in percent
+    pub close_pct: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct SubAccountConfig {
+    pub enabled: bool,
+    pub name: String,
+    pub sweep_enabled: bool,
+    pub working_capital_usdt: f64,
+    pub min_sweep_usdt: f64,
+    pub sweep_interval_secs: u64,
+    pub to_account_type: String,
+    pub spot_base_url: String,
+    /// Used when the sub-account key variables aren't set in the environment
+    pub credentials: CredentialsConfig,
+}
+
+impl Default for SubAccountConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            name: String::new(),
+            sweep_enabled: false,
+            working_capital_usdt: 500.0,
+            min_sweep_usdt: 20.0,
+            sweep_interval_secs: 86400,
+            to_account_type: "SPOT".to_string(),
+            spot_base_url: "https://api.mexc.com".to_string(),
+            credentials: CredentialsConfig::default(),
+        }
+    }
+}
+
+/// API key pair kept in execution.toml
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct CredentialsConfig {
+    pub api_key: Option<String>,
+    pub api_secret: Option<String>,
+}
+
+impl std::fmt::Debug for CredentialsConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CredentialsConfig")
+            .field("api_key", &self.api_key)
+            .field("api_secret", &self.api_secret.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct ProfilingConfig {
+    pub enabled: bool,
+    pub frequency_hz: i32,
+    pub report_interval_secs: u64,
+    pub output_dir: String,
+    pub span_timings: bool,
+}
+
+impl Default for ProfilingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            frequency_hz: 99,
+            report_interval_secs: 300,
+            output_dir: "profiles".to_string(),
+            span_timings: false,
+        }
+    }
+}
+
+/// A step taken to bring the process back within its resource budget
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Mitigation {
+    /// Unsubscribe the orderbooks of all but the `depth_keep_symbols` highest-priority symbols
+    DropDepth,
+    /// Keep `history_retention_secs` of price history instead of the usual two minutes
+    ShortenHistory,
+    /// Unsubscribe the lowest-priority symbols, keeping `reduce_symbols_keep_pct` of them
+    ReduceSymbols,
+}
+
+impl Mitigation {
+    pub fn name(self) -> &'static str {
+        match self {
+            Mitigation::DropDepth => "drop_depth",
+            Mitigation::ShortenHistory => "shorten_history",
+            Mitigation::ReduceSymbols => "reduce_symbols",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct ResourceBudgetConfig {
+    pub enabled: bool,
+    pub check_interval_secs: u64,
+    /// Soft limit on resident memory (0 = none)
+    pub max_rss_mb: u64,
+    /// Soft limit on CPU time, in percent of one core (0 = none)
+    pub max_cpu_pct: f64,
+    /// Consecutive checks over a limit before the next mitigation is applied
+    pub escalate_after_checks: u32,
+    /// Usage below this fraction of every limit counts towards lifting a mitigation
+    pub release_ratio: f64,
+    /// Consecutive checks below `release_ratio` before the latest mitigation is lifted
+    pub release_after_checks: u32,
+    /// Applied in this order, lifted in reverse
+    pub mitigations: Vec<Mitigation>,
+    pub depth_keep_symbols: usize,
+    pub history_retention_secs: u64,
+    pub reduce_symbols_keep_pct: f64,
+}
+
+impl Default for ResourceBudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            check_interval_secs: 30,
+            max_rss_mb: 0,
+            max_cpu_pct: 0.0,
+            escalate_after_checks: 2,
+            release_ratio: 0.8,
+            release_after_checks: 5,
+            mitigations: vec![Mitigation::DropDepth, Mitigation::ShortenHistory, Mitigation::ReduceSymbols],
+            depth_keep_symbols: 20,
+            history_retention_secs: 60,
+            reduce_symbols_keep_pct: 50.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct UniverseConfig {
+    pub max_symbols: usize,
+    pub priority_symbols: Vec<String>,
+    pub volume_weight: f64,
+    pub pump_history_weight: f64,
+    pub pump_history_hours: u64,
+    pub rotation_interval_secs: u64,
+}
+
+impl Default for UniverseConfig {
+    fn default() -> Self {
+        Self {
+            max_symbols: 0,
+            priority_symbols: Vec::new(),
+            volume_weight: 1.0,
+            pump_history_weight: 2.0,
+            pump_history_hours: 24,
+            rotation_interval_secs: 900,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct KlineCheckConfig {
+    pub enabled: bool,
+    pub tolerance_pct: f64,
+    pub settle_delay_secs: u64,
+}
+
+impl Default for KlineCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tolerance_pct: 0.5,
+            settle_delay_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct MissedDigestConfig {
+    pub enabled: bool,
+    pub heartbeat_file: String,
+    pub heartbeat_interval_secs: u64,
+    pub min_downtime_secs: u64,
+    pub max_lookback_hours: u64,
+    pub ratio_threshold: f64,
+    pub max_symbols: usize,
+    pub request_delay_ms: u64,
+}
+
+impl Default for MissedDigestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            heartbeat_file: "state/heartbeat".to_string(),
+            heartbeat_interval_secs: 30,
+            min_downtime_secs: 120,
+            max_lookback_hours: 6,
+            ratio_threshold: 1.15,
+            max_symbols: 200,
+            request_delay_ms: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct LatencyBudgetConfig {
+    pub enabled: bool,
+    pub budget_us: u64,
+    pub warn_interval_secs: u64,
+}
+
+impl Default for LatencyBudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            budget_us: 200,
+            warn_interval_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct FundingConfig {
+    pub enabled: bool,
+    pub poll_interval_secs: u64,
+    pub followup_minutes: u64,
+}
+
+impl Default for FundingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval_secs: 60,
+            followup_minutes: 60,
+        }
+    }
+}
+
+/// Open interest polling for the strategies' `min_oi_increase_pct` condition
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct OpenInterestConfig {
+    pub enabled: bool,
+    pub poll_interval_secs: u64,
+}
+
+impl Default for OpenInterestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct ChannelHealthConfig {
+    pub enabled: bool,
+    /// A channel silent this long while another channel of the symbol delivers is missing
+    pub stale_secs: u64,
+    pub check_interval_secs: u64,
+    /// Minimum time between resubscribes of the same symbol
+    pub resubscribe_cooldown_secs: u64,
+}
+
+impl Default for ChannelHealthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            stale_secs: 60,
+            check_interval_secs: 30,
+            resubscribe_cooldown_secs: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarketSource {
+    Websocket,
+    Synthetic,
+    Replay,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct MarketStreamConfig {
+    pub source: MarketSource,
+    /// Subscribe to the deal channel for real candle volume (websocket source)
+    pub trades: bool,
+    /// Subscribe to the funding rate channel for each symbol's current funding (websocket source)
+    pub funding_rates: bool,
+    pub synthetic_pump_probability: f64,
+    pub synthetic_pump_ratio: f64,
+    pub synthetic_pump_ticks: u32,
+    /// Tape file, or directory of tape files, read by the replay source
+    pub replay_path: String,
+    /// Replay speed relative to the recorded timing; 0 = as fast as possible
+    pub replay_speed: f64,
+    /// After a reconnect, strategies hold off this long while fresh data replaces what was
+    /// missed (websocket source)
+    pub stale_grace_secs: u64,
+    /// Refill the orderbooks dropped during an outage from REST snapshots after a reconnect
+    pub resync_orderbooks: bool,
+    /// Strategies don't trigger on a symbol whose last or mark price is older than this; 0 = no check
+    pub max_data_age_ms: u64,
+}
+
+impl Default for MarketStreamConfig {
+    fn default() -> Self {
+        Self {
+            source: MarketSource::Websocket,
+            trades: true,
+            funding_rates: true,
+            synthetic_pump_probability: 0.0005,
+            synthetic_pump_ratio: 1.3,
+            synthetic_pump_ticks: 20,
+            replay_path: "recordings".to_string(),
+            replay_speed: 1.0,
+            stale_grace_secs: 10,
+            resync_orderbooks: true,
+            max_data_age_ms: 10_000,
+        }
+    }
+}
+
+/// Raw market event tape for replays
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct RecordingConfig {
+    pub enabled: bool,
+    pub dir: String,
+    /// Write `.jsonl.gz` instead of `.jsonl`
+    pub compress: bool,
+    /// Start a new file once the current one reaches this size
+    pub max_file_mb: u64,
+    /// Delete the oldest files once the tape takes more than this; 0 = no limit
+    pub max_disk_mb: u64,
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: "recordings".to_string(),
+            compress: true,
+            max_file_mb: 100,
+            max_disk_mb: 2048,
+        }
+    }
+}
+
+/// High-priority event queue for symbols with a running episode or on the watchlist
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct EventPriorityConfig {
+    pub enabled: bool,
+    /// Thin background (non-priority) price and book updates to one per symbol and kind
+    /// per this many ms; 0 keeps every update
+    pub background_min_interval_ms: u64,
+}
+
+impl Default for EventPriorityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            background_min_interval_ms: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct RatioExportConfig {
+    pub enabled: bool,
+    pub output_dir: String,
+    pub sample_interval_ms: u64,
+}
+
+impl Default for RatioExportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            output_dir: "ratio_series".to_string(),
+            sample_interval_ms: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct WatchlistConfig {
+    pub enabled: bool,
+    pub soft_ratio_min: f64,
+    pub watch_duration_secs: u64,
+    pub threshold_relax_pct: f64,
+    pub watch_depth_levels: usize,
+}
+
+impl Default for WatchlistConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            soft_ratio_min: 1.05,
+            watch_duration_secs: 300,
+            threshold_relax_pct: 10.0,
+            watch_depth_levels: 50,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct RatioBaselineConfig {
+    pub enabled: bool,
+    pub window_mins: u64,
+    pub sample_interval_secs: u64,
+    /// Flag symbols whose baseline is at least this far from 1.0 (%)
+    pub flag_deviation_pct: f64,
+    /// Samples needed before a symbol can be flagged (minutes)
+    pub min_coverage_mins: u64,
+    /// Stop evaluating flagged symbols until their baseline recovers
+    pub exclude_flagged: bool,
+}
+
+impl Default for RatioBaselineConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_mins: 60,
+            sample_interval_secs: 5,
+            flag_deviation_pct: 2.0,
+            min_coverage_mins: 30,
+            exclude_flagged: false,
+        }
+    }
+}
+
+/// Linking episodes of different strategies that start on the same symbol together
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct CorrelationConfig {
+    pub enabled: bool,
+    /// Starts within this many seconds of a symbol's first one are correlated with it
+    pub window_secs: u64,
+    /// Distinct strategies needed for a correlation record (at least 2)
+    pub min_strategies: usize,
+    /// Also correlate the strategies of `[pipelines.*]`
+    pub include_pipelines: bool,
+}
+
+impl Default for CorrelationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 5,
+            min_strategies: 2,
+            include_pipelines: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct PrecisionConfig {
+    pub enabled: bool,
+    pub target_rise_pct: f64,
+    pub horizon_secs: u64,
+    pub window: usize,
+    pub check_interval_ms: u64,
+}
+
+impl Default for PrecisionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            target_rise_pct: 2.0,
+            horizon_secs: 120,
+            window: 50,
+            check_interval_ms: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct ThrottleConfig {
+    pub enabled: bool,
+    /// Episode starts of one strategy over the last hour above which its thresholds go up
+    pub max_episodes_per_hour: usize,
+    /// Applied per step to each ratio threshold's distance above 1.0
+    pub threshold_multiplier: f64,
+    pub max_steps: u32,
+    /// Minimum time between two adjustments of the same strategy
+    pub hold_mins: u64,
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_episodes_per_hour: 30,
+            threshold_multiplier: 1.5,
+            max_steps: 3,
+            hold_mins: 15,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct LoggingConfig {
+    pub console: ConsoleConfig,
+    pub episodes: EpisodeLogConfig,
+    pub ws_raw: WsRawLogConfig,
+}
+
+/// Compressed, size-rotated log of WebSocket acks, errors and unrecognized messages
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct WsRawLogConfig {
+    pub enabled: bool,
+    pub dir: String,
+    /// Start a new file once the current one reaches this size (compressed)
+    pub max_file_mb: u64,
+    /// Oldest files beyond this many are deleted
+    pub max_files: usize,
+}
+
+impl Default for WsRawLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: "logs/ws_raw".to_string(),
+            max_file_mb: 10,
+            max_files: 10,
+        }
+    }
+}
+
+/// When buffered episode log lines are written out
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FlushPolicy {
+    /// As soon as possible; lines arriving together are written as one batch
+    Immediate,
+    /// Every `flush_interval_ms`
+    Interval,
+    /// Only when the process shuts down
+    Shutdown,
+}
+
+/// How episodes are written to the episode logs
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EpisodeLogFormat {
+    /// One pipe-delimited line per episode in `<strategy>_episodes.log`
+    Text,
+    /// One JSON object per episode, with what it triggered on, in `<strategy>_episodes.jsonl`
+    Json,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct EpisodeLogConfig {
+    pub format: EpisodeLogFormat,
+    pub flush: FlushPolicy,
+    pub flush_interval_ms: u64,
+    /// Sync the files to disk after every write, not just hand them to the OS
+    pub fsync: bool,
+}
+
+impl Default for EpisodeLogConfig {
+    fn default() -> Self {
+        Self {
+            format: EpisodeLogFormat::Text,
+            flush: FlushPolicy::Immediate,
+            flush_interval_ms: 1000,
+            fsync: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsoleColor {
+    /// Colored only when stdout is a terminal
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct ConsoleConfig {
+    pub color: ConsoleColor,
+    pub bell_on_strategy5: bool,
+    pub dim_diagnostics: bool,
+}
+
+impl Default for ConsoleConfig {
+    fn default() -> Self {
+        Self {
+            color: ConsoleColor::Auto,
+            bell_on_strategy5: false,
+            dim_diagnostics: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct MqttConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+    pub ca_cert: Option<String>,
+    pub accept_invalid_certs: bool,
+    pub client_id: String,
+    pub username: String,
+    pub topic_prefix: String,
+    pub qos: u8,
+    pub retain: bool,
+    pub keep_alive_secs: u64,
+    pub queue_size: usize,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: 1883,
+            tls: false,
+            ca_cert: None,
+            accept_invalid_certs: false,
+            client_id: "mexc-sniper".to_string(),
+            username: String::new(),
+            topic_prefix: "mexc-sniper".to_string(),
+            qos: 1,
+            retain: false,
+            keep_alive_secs: 30,
+            queue_size: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct InterContractConfig {
+    pub enabled: bool,
+    pub deviation_pct: f64,
+    pub baseline_window_secs: u64,
+    pub min_samples: u64,
+    pub min_price: f64,
+}
+
+impl Default for InterContractConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            deviation_pct: 3.0,
+            baseline_window_secs: 600,
+            min_samples: 30,
+            min_price: 0.01,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct DormancyConfig {
+    pub enabled: bool,
+    pub idle_hours: u64,
+    pub check_interval_secs: u64,
+    pub recheck_interval_hours: u64,
+    pub revive_min_turnover_usdt: f64,
+    pub state_file: String,
+}
+
+impl Default for DormancyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_hours: 6,
+            check_interval_secs: 600,
+            recheck_interval_hours: 24,
+            revive_min_turnover_usdt: 1000.0,
+            state_file: "state/dormant.json".to_string(),
+        }
+    }
+}
+
+/// Volume tiers that alert routing and execution can be restricted by
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct TiersConfig {
+    pub enabled: bool,
+    /// 24h turnover (USDT) needed for tier1, tier2, ...; symbols below the last are one tier lower
+    pub min_volume_usdt: Vec<f64>,
+    pub refresh_interval_mins: u64,
+    /// Tier name (e.g. "tier1") -> names of the notifiers that receive its alerts
+    pub routes: HashMap<String, Vec<String>>,
+    /// Tiers whose signals may be executed; empty allows all
+    pub execute: Vec<String>,
+    /// Names of tier1, tier2, ... in order, used instead of `tierN` in routes, execute and
+    /// thresholds; empty keeps the numbered names
+    pub names: Vec<String>,
+    /// Tier name -> strategy options that differ for its symbols, e.g.
+    /// `[tiers.thresholds.tier3.strategy1] spread_ratio_min = 1.3`
+    pub thresholds: BTreeMap<String, toml::Table>,
+}
+
+impl TiersConfig {
+    /// Number of tiers: one per `min_volume_usdt` entry plus the one below the last
+    pub fn tier_count(&self) -> usize {
+        self.min_volume_usdt.len() + 1
+    }
+
+    /// Name of a tier numbered from 1
+    pub fn tier_name(&self, tier: usize) -> String {
+        match self.names.get(tier.wrapping_sub(1)) {
+            Some(name) => name.clone(),
+            None => format!("tier{}", tier),
+        }
+    }
+}
+
+impl Default for TiersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_volume_usdt: vec![50_000_000.0, 5_000_000.0],
+            refresh_interval_mins: 60,
+            routes: HashMap::new(),
+            execute: Vec::new(),
+            names: Vec::new(),
+            thresholds: BTreeMap::new(),
+        }
+    }
+}
+
+/// An extra strategy set fed by the same market data, e.g. `[pipelines.aggressive]`.
+/// Its strategy sections only need the options that differ from the top-level ones.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineConfig {
+    /// Episode logs; default `<general.log_dir>/<pipeline>`
+    #[serde(default)]
+    pub log_dir: Option<String>,
+    /// Chart recordings; default `<csv_export.charts_dir>/<pipeline>`
+    #[serde(default)]
+    pub charts_dir: Option<String>,
+    pub strategy1: Strategy1Config,
+    pub strategy2: Strategy2Config,
+    pub strategy3: Strategy3Config,
+    pub strategy4: Strategy4Config,
+    pub strategy5: Strategy5Config,
+}
+
+/// Periodic re-check of the contract details for paused, settling and delisted contracts
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct ContractMonitorConfig {
+    pub enabled: bool,
+    pub check_interval_secs: u64,
+    /// Alert on contracts that appear in the details (route key `pre_listing`)
+    pub new_listings: bool,
+    /// Subscribe new contracts right away so no trades of their first minutes are missed
+    pub subscribe_new_listings: bool,
+    /// Unsubscribe contracts that stop trading until they are enabled again
+    pub unsubscribe_untradable: bool,
+    /// Carry the history and open episodes of a renamed contract over to its new symbol
+    /// instead of treating it as a delisting and a new listing
+    pub migrate_renames: bool,
+}
+
+impl Default for ContractMonitorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            check_interval_secs: 300,
+            new_listings: true,
+            subscribe_new_listings: true,
+            unsubscribe_untradable: true,
+            migrate_renames: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct AlertsConfig {
+    /// Strategy name (or "digest") -> names of the notifiers that receive its alerts
+    pub routes: HashMap<String, Vec<String>>,
+    pub suppression: AlertSuppressionConfig,
+    pub kill_switch: KillSwitchConfig,
+    pub digest_mode: DigestModeConfig,
+    pub telegram: TelegramConfig,
+    pub zmq: ZmqConfig,
+}
+
+/// Chat messages through a Telegram bot
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct TelegramConfig {
+    pub enabled: bool,
+    /// Overridden by TELEGRAM_BOT_TOKEN in the environment
+    pub bot_token: Option<String>,
+    /// Numeric chat id, or `@channelname` for public channels
+    pub chat_id: String,
+    pub api_url: String,
+    /// Deliver without a notification sound
+    pub silent: bool,
+    pub timeout_secs: u64,
+    pub queue_size: usize,
+}
+
+impl Default for TelegramConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bot_token: None,
+            chat_id: String::new(),
+            api_url: "https://api.telegram.org".to_string(),
+            silent: false,
+            timeout_secs: 10,
+            queue_size: 1000,
+        }
+    }
+}
+
+/// ZeroMQ PUB socket for colocated subscribers
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct ZmqConfig {
+    pub enabled: bool,
+    /// `tcp://host:port` or `ipc:///path/to/socket`
+    pub endpoint: String,
+    /// Messages a slow subscriber may fall behind before it loses the oldest
+    pub queue_size: usize,
+}
+
+impl Default for ZmqConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "tcp://127.0.0.1:5556".to_string(),
+            queue_size: 1000,
+        }
+    }
+}
+
+/// Per-symbol re-alert suppression; severity is the spread in percent (`(ratio - 1) * 100`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct AlertSuppressionConfig {
+    pub enabled: bool,
+    /// How long after a delivered alert further alerts for the symbol need a higher severity
+    pub window_mins: u64,
+    /// Required severity increase over the last delivered alert, in percent of its severity
+    pub min_increase_pct: f64,
+    /// Halves the remembered severity every this many minutes, lowering the bar over time
+    pub half_life_mins: Option<f64>,
+}
+
+impl Default for AlertSuppressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_mins: 15,
+            min_increase_pct: 25.0,
+            half_life_mins: None,
+        }
+    }
+}
+
+/// Pauses notifications and execution while episode starts arrive at an abnormal rate
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct KillSwitchConfig {
+    pub enabled: bool,
+    /// Episode starts over the last minute, across all strategies, that trip the switch
+    pub max_starts_per_min: usize,
+    /// Rate the starts must stay at or below before resuming
+    pub resume_below_per_min: usize,
+    /// How long the rate must stay at or below `resume_below_per_min`
+    pub resume_after_secs: u64,
+}
+
+impl Default for KillSwitchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_starts_per_min: 30,
+            resume_below_per_min: 5,
+            resume_after_secs: 300,
+        }
+    }
+}
+
+/// One summarized message per interval instead of one per alert while alerts pile up
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct DigestModeConfig {
+    pub enabled: bool,
+    /// Delivered alerts within a minute above which digest mode starts
+    pub enter_alerts_per_min: usize,
+    /// Digest mode ends once the last minute had at most this many alerts
+    pub exit_alerts_per_min: usize,
+    pub interval_secs: u64,
+    /// Symbols listed per digest; the rest are only counted
+    pub max_symbols: usize,
+}
+
+impl Default for DigestModeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            enter_alerts_per_min: 10,
+            exit_alerts_per_min: 3,
+            interval_secs: 60,
+            max_symbols: 15,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct ControlConfig {
+    pub enabled: bool,
+    pub socket_path: String,
+}
+
+impl Default for ControlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            socket_path: "state/control.sock".to_string(),
+        }
+    }
+}
+
+/// Signals injected through the control socket, e.g. `mexc-sniper signal SYMBOL`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct ManualSignalConfig {
+    pub enabled: bool,
+    /// How long an injected episode stays open; another signal for the symbol extends it
+    pub duration_secs: u64,
+    /// Let signals sent with `--execute` enter a position while execution is enabled
+    pub allow_execution: bool,
+    pub recording: StrategyRecordingConfig,
+}
+
+impl Default for ManualSignalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            duration_secs: 60,
+            allow_execution: false,
+            recording: StrategyRecordingConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct MarketContextConfig {
+    pub enabled: bool,
+    pub reference_symbols: Vec<String>,
+    /// Window before a trigger the reference move is measured over
+    pub lookback_secs: u64,
+    /// How long reference prices are kept, bounding the episodes that can be compared
+    pub history_mins: u64,
+    /// Withhold alerts while a reference symbol moved at least this much (%); None = never
+    pub suppress_above_pct: Option<f64>,
+}
+
+impl Default for MarketContextConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            reference_symbols: vec!["BTC_USDT".to_string(), "ETH_USDT".to_string()],
+            lookback_secs: 60,
+            history_mins: 60,
+            suppress_above_pct: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct DiskIoConfig {
+    /// Writes that can wait for the disk before new ones are refused
+    pub queue_capacity: usize,
+    /// Writes taking longer than this are logged
+    pub slow_write_ms: u64,
+}
+
+impl Default for DiskIoConfig {
+    fn default() -> Self {
+        Self {
+            queue_capacity: 1024,
+            slow_write_ms: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct IssueBundleConfig {
+    /// Sample bad messages and allow writing bundles
+    pub enabled: bool,
+    pub dir: String,
+    /// Bad messages kept for the next bundle (the newest ones)
+    pub max_samples: usize,
+    /// Application log lines kept for the next bundle
+    pub log_lines: usize,
+    /// Write a bundle when this many bad messages arrive within a minute; 0 disables
+    pub auto_errors_per_min: usize,
+    /// At most one automatic bundle per this many minutes
+    pub auto_min_interval_mins: u64,
+}
+
+impl Default for IssueBundleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: "bundles".to_string(),
+            max_samples: 50,
+            log_lines: 500,
+            auto_errors_per_min: 20,
+            auto_min_interval_mins: 60,
+        }
+    }
+}
+
+/// How market WebSocket messages that don't match the expected schema are treated
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ParsingMode {
+    /// Skip them with a warning; optional fields and orderbook levels that don't parse are
+    /// left out silently
+    Lenient,
+    /// Also keep each one, and each message that lost fields or levels, as a sample file
+    Strict,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct ParsingConfig {
+    pub mode: ParsingMode,
+    /// Where strict mode writes the samples
+    pub samples_dir: String,
+    /// Samples written per minute at most; the rest are only counted
+    pub max_samples_per_min: usize,
+    /// Only the newest this many samples are kept
+    pub max_files: usize,
+}
+
+impl Default for ParsingConfig {
+    fn default() -> Self {
+        Self {
+            mode: ParsingMode::Lenient,
+            samples_dir: "logs/malformed".to_string(),
+            max_samples_per_min: 10,
+            max_files: 500,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
 }
 
 impl Config {
     pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
         let contents = fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&contents)?;
+        Self::from_table(toml::from_str(&contents)?)
+    }
+
+    /// The config a parsed config.toml describes, e.g. one with keys changed by a backtest
+    pub fn from_table(table: toml::Table) -> anyhow::Result<Self> {
+        // Report every unknown key at once rather than serde's first one
+        if table.contains_key("execution") {
+            anyhow::bail!(
+                "config.toml contains an [execution] section; execution settings now live in their own \
+                 file: move the section's contents to execution.toml (see execution.example.toml)"
+            );
+        }
+        let unknown = find_unknown_keys(&table);
+        if !unknown.is_empty() {
+            anyhow::bail!(format_unknown_keys(&unknown));
+        }
+
+        let mut table = table;
+        if table.contains_key("pipelines") {
+            inherit_pipeline_sections(&mut table);
+        }
+        let config: Config = toml::Value::Table(table).try_into()?;
+        let tiers = &config.tiers;
+        if !tiers.names.is_empty() && tiers.names.len() != tiers.tier_count() {
+            anyhow::bail!(
+                "tiers.names has {} names but min_volume_usdt makes {} tiers",
+                tiers.names.len(),
+                tiers.tier_count()
+            );
+        }
+        StrategyThresholds::from_config(&config)?;
         Ok(config)
     }
+
+    /// Reads the execution settings from `path`, whose top level is the execution section.
+    /// Returns false, leaving execution disabled, when the file doesn't exist.
+    pub fn load_execution<P: AsRef<Path>>(&mut self, path: P) -> anyhow::Result<bool> {
+        let path = path.as_ref();
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(anyhow::anyhow!("cannot read {}: {}", path.display(), e)),
+        };
+        self.execution =
+            toml::from_str(&contents).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+        Ok(true)
+    }
+
+    /// Whether an enabled strategy, top-level or in a pipeline, needs open interest samples
+    pub fn requires_open_interest(&self) -> bool {
+        let pipelines = self.pipelines.values().map(|p| (&p.strategy1, &p.strategy2, &p.strategy3, &p.strategy4, &p.strategy5));
+        std::iter::once((&self.strategy1, &self.strategy2, &self.strategy3, &self.strategy4, &self.strategy5))
+            .chain(pipelines)
+            .any(|(s1, s2, s3, s4, s5)| {
+                (s1.enabled && s1.min_oi_increase_pct > 0.0)
+                    || (s2.enabled && s2.min_oi_increase_pct > 0.0)
+                    || (s3.enabled && s3.min_oi_increase_pct > 0.0)
+                    || (s4.enabled && s4.min_oi_increase_pct > 0.0)
+                    || (s5.enabled && s5.min_oi_increase_pct > 0.0)
+            })
+    }
+}
+
+/// Sections a pipeline can override; it inherits every option it doesn't set
+const PIPELINE_SECTIONS: [&str; 5] = ["strategy1", "strategy2", "strategy3", "strategy4", "strategy5"];
+
+/// Completes each pipeline's strategy sections with the top-level ones
+fn inherit_pipeline_sections(table: &mut toml::Table) {
+    let base: Vec<(&str, toml::Value)> = PIPELINE_SECTIONS
+        .iter()
+        .filter_map(|section| table.get(*section).map(|value| (*section, value.clone())))
+        .collect();
+    let Some(toml::Value::Table(pipelines)) = table.get_mut("pipelines") else {
+        return;
+    };
+    for (_, pipeline) in pipelines.iter_mut() {
+        let toml::Value::Table(pipeline) = pipeline else { continue };
+        for (section, base) in &base {
+            let mut merged = base.clone();
+            if let Some(overrides) = pipeline.remove(*section) {
+                merge_toml(&mut merged, overrides);
+            }
+            pipeline.insert(section.to_string(), merged);
+        }
+    }
+}
+
+/// Overlays `overrides` on `base`, table by table
+pub(crate) fn merge_toml(base: &mut toml::Value, overrides: toml::Value) {
+    match (base, overrides) {
+        (toml::Value::Table(base), toml::Value::Table(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
 }
@@ -0,0 +1,479 @@
+use crate::config::Config;
+use serde::de::{self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The annotated default config, used as the source of comments for the template
+const ANNOTATED_CONFIG: &str = include_str!("../config.toml");
+
+/// Shape of one config option, as declared by the config structs
+#[derive(Debug, Clone)]
+pub enum SchemaNode {
+    /// A `[section]`, with its keys in declaration order
+    Table(Vec<(&'static str, SchemaNode)>),
+    Value(ValueKind),
+}
+
+#[derive(Debug, Clone)]
+pub enum ValueKind {
+    Bool,
+    Integer,
+    Float,
+    String,
+    Array,
+    /// Free-form table whose keys are data, not options
+    Map,
+    OneOf(&'static [&'static str]),
+    Optional(Box<ValueKind>),
+}
+
+impl ValueKind {
+    /// Placeholder shown for options that have no example in the annotated config
+    fn placeholder(&self) -> String {
+        match self {
+            ValueKind::Bool => "false".to_string(),
+            ValueKind::Integer => "0".to_string(),
+            ValueKind::Float => "0.0".to_string(),
+            ValueKind::String => "\"\"".to_string(),
+            ValueKind::Array => "[]".to_string(),
+            ValueKind::Map => "{}".to_string(),
+            ValueKind::OneOf(variants) => format!("\"{}\"", variants.first().copied().unwrap_or_default()),
+            ValueKind::Optional(inner) => inner.placeholder(),
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            ValueKind::Bool => "boolean".to_string(),
+            ValueKind::Integer => "integer".to_string(),
+            ValueKind::Float => "number".to_string(),
+            ValueKind::String => "string".to_string(),
+            ValueKind::Array => "array".to_string(),
+            ValueKind::Map => "table".to_string(),
+            ValueKind::OneOf(variants) => format!(
+                "one of {}",
+                variants.iter().map(|v| format!("\"{}\"", v)).collect::<Vec<_>>().join(", ")
+            ),
+            ValueKind::Optional(inner) => format!("optional {}, unset by default", inner.description()),
+        }
+    }
+}
+
+/// Every option `Config` accepts, read from the serde derive itself so it can't drift
+pub fn config_schema() -> SchemaNode {
+    let mut node = SchemaNode::Table(Vec::new());
+    Config::deserialize(Probe(&mut node)).expect("config structs only use probe-able types");
+    node
+}
+
+/// A key present in the config file that no config struct declares
+#[derive(Debug, Clone)]
+pub struct UnknownKey {
+    pub path: String,
+    pub suggestion: Option<String>,
+}
+
+/// Lists every unknown key in `table`, with the closest known key where one is similar
+pub fn find_unknown_keys(table: &toml::Table) -> Vec<UnknownKey> {
+    let mut unknown = Vec::new();
+    if let SchemaNode::Table(entries) = config_schema() {
+        collect_unknown(table, &entries, "", &mut unknown);
+    }
+    unknown
+}
+
+/// Diff-style listing: `-` is what the file says, `+` the option it most likely meant
+pub fn format_unknown_keys(unknown: &[UnknownKey]) -> String {
+    let mut lines = vec![format!(
+        "config.toml contains {} unknown key{} (run `mexc-sniper config-schema` for every supported option):",
+        unknown.len(),
+        if unknown.len() == 1 { "" } else { "s" }
+    )];
+    for key in unknown {
+        lines.push(format!("  - {}", key.path));
+        match &key.suggestion {
+            Some(suggestion) => lines.push(format!("  + {}", suggestion)),
+            None => lines.push("    (no similar option)".to_string()),
+        }
+    }
+    lines.join("\n")
+}
+
+fn collect_unknown(table: &toml::Table, entries: &[(&'static str, SchemaNode)], prefix: &str, out: &mut Vec<UnknownKey>) {
+    for (key, value) in table {
+        let path = format!("{}{}", prefix, key);
+        match entries.iter().find(|(name, _)| name == key) {
+            Some((_, SchemaNode::Table(children))) => {
+                if let toml::Value::Table(inner) = value {
+                    collect_unknown(inner, children, &format!("{}.", path), out);
+                }
+            }
+            Some(_) => {}
+            None => out.push(UnknownKey {
+                suggestion: closest_key(key, entries).map(|name| format!("{}{}", prefix, name)),
+                path,
+            }),
+        }
+    }
+}
+
+fn closest_key(key: &str, entries: &[(&'static str, SchemaNode)]) -> Option<&'static str> {
+    let max_distance = (key.len() / 3).max(2);
+    entries
+        .iter()
+        .map(|(name, _)| (*name, edit_distance(key, name)))
+        .filter(|(_, distance)| *distance <= max_distance)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(name, _)| name)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Comments and example lines of the annotated config, keyed by section and option
+#[derive(Default)]
+struct Annotations {
+    /// Comment block directly above each `[section]` header
+    headers: HashMap<String, Vec<String>>,
+    /// Comment block above each option plus the option line itself (possibly commented out)
+    options: HashMap<(String, String), Vec<String>>,
+    /// Comments at the end of a section that don't belong to any option
+    footers: HashMap<String, Vec<String>>,
+}
+
+impl Annotations {
+    /// Assumes one option per line, which holds for the shipped config.toml
+    fn parse(text: &str) -> Self {
+        let mut annotations = Annotations::default();
+        let mut section = String::new();
+        let mut block: Vec<String> = Vec::new();
+
+        for line in text.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                if !block.is_empty() {
+                    annotations.footers.entry(section.clone()).or_default().append(&mut block);
+                }
+            } else if let Some(header) = trimmed.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+                section = header.trim().to_string();
+                annotations.headers.insert(section.clone(), std::mem::take(&mut block));
+            } else if let Some(key) = option_key(trimmed) {
+                block.push(line.to_string());
+                annotations.options.insert((section.clone(), key), std::mem::take(&mut block));
+            } else {
+                block.push(line.to_string());
+            }
+        }
+        if !block.is_empty() {
+            annotations.footers.entry(section).or_default().append(&mut block);
+        }
+        annotations
+    }
+}
+
+/// Key of an option line, including options that are commented out as examples
+fn option_key(line: &str) -> Option<String> {
+    let line = line.trim_start_matches('#').trim();
+    let (key, _) = line.split_once('=')?;
+    let key = key.trim();
+    let is_key = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    is_key.then(|| key.to_string())
+}
+
+/// Commented template with every supported option, in declaration order.
+///
+/// Comments and example values come from the annotated config.toml; options it
+/// doesn't mention are added commented out with their type.
+pub fn render_template() -> String {
+    let annotations = Annotations::parse(ANNOTATED_CONFIG);
+    let mut out = String::from("# mexc-sniper configuration - every supported option\n");
+    if let SchemaNode::Table(entries) = config_schema() {
+        for (name, node) in &entries {
+            if let SchemaNode::Table(children) = node {
+                render_section(&mut out, &annotations, name, children);
+            }
+        }
+    }
+    out
+}
+
+fn render_section(out: &mut String, annotations: &Annotations, path: &str, entries: &[(&'static str, SchemaNode)]) {
+    out.push('\n');
+    for line in annotations.headers.get(path).into_iter().flatten() {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.push_str(&format!("[{}]\n", path));
+
+    for (key, node) in entries {
+        let SchemaNode::Value(kind) = node else { continue };
+        match annotations.options.get(&(path.to_string(), key.to_string())) {
+            Some(lines) => {
+                for line in lines {
+                    out.push_str(line);
+                    out.push('\n');
+                }
+            }
+            None => {
+                out.push_str(&format!("# ({})\n", kind.description()));
+                let assignment = format!("{} = {}", key, kind.placeholder());
+                match kind {
+                    ValueKind::Optional(_) => out.push_str(&format!("# {}\n", assignment)),
+                    _ => out.push_str(&format!("{}\n", assignment)),
+                }
+            }
+        }
+    }
+    for line in annotations.footers.get(path).into_iter().flatten() {
+        out.push_str(line);
+        out.push('\n');
+    }
+
+    for (key, node) in entries {
+        if let SchemaNode::Table(children) = node {
+            render_section(out, annotations, &format!("{}.{}", path, key), children);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ProbeError(String);
+
+impl fmt::Display for ProbeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ProbeError {}
+
+impl de::Error for ProbeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        ProbeError(msg.to_string())
+    }
+}
+
+/// Deserializer that records the shape requested by a `Deserialize` impl,
+/// answering every request with a zero value
+struct Probe<'a>(&'a mut SchemaNode);
+
+impl Probe<'_> {
+    fn value(self, kind: ValueKind) {
+        *self.0 = SchemaNode::Value(kind);
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Probe<'_> {
+    type Error = ProbeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("config schema probe needs a concrete type"))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.value(ValueKind::Bool);
+        visitor.visit_bool(false)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.value(ValueKind::Integer);
+        visitor.visit_u64(0)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.value(ValueKind::Float);
+        visitor.visit_f64(0.0)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.value(ValueKind::String);
+        visitor.visit_str("")
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let node = self.0;
+        let value = visitor.visit_some(Probe(&mut *node))?;
+        if let SchemaNode::Value(kind) = node {
+            *kind = ValueKind::Optional(Box::new(kind.clone()));
+        }
+        Ok(value)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.value(ValueKind::Array);
+        visitor.visit_seq(Empty)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.value(ValueKind::Map);
+        visitor.visit_map(Empty)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let mut entries = Vec::new();
+        let value = visitor.visit_map(ProbeFields {
+            fields,
+            index: 0,
+            entries: &mut entries,
+        })?;
+        *self.0 = SchemaNode::Table(entries);
+        Ok(value)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value(ValueKind::OneOf(variants));
+        let variant = variants.first().copied().unwrap_or_default();
+        visitor.visit_enum(UnitVariant(variant))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bytes byte_buf unit unit_struct newtype_struct tuple tuple_struct identifier ignored_any
+    }
+}
+
+struct Empty;
+
+impl<'de> SeqAccess<'de> for Empty {
+    type Error = ProbeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, _seed: T) -> Result<Option<T::Value>, Self::Error> {
+        Ok(None)
+    }
+}
+
+impl<'de> MapAccess<'de> for Empty {
+    type Error = ProbeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, _seed: K) -> Result<Option<K::Value>, Self::Error> {
+        Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, _seed: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("empty map has no values"))
+    }
+}
+
+/// Presents every declared field once, probing each value's type
+struct ProbeFields<'a> {
+    fields: &'static [&'static str],
+    index: usize,
+    entries: &'a mut Vec<(&'static str, SchemaNode)>,
+}
+
+impl<'de> MapAccess<'de> for ProbeFields<'_> {
+    type Error = ProbeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        match self.fields.get(self.index) {
+            Some(field) => seed.deserialize((*field).into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        let field = self.fields[self.index];
+        self.index += 1;
+        let mut node = SchemaNode::Table(Vec::new());
+        let value = seed.deserialize(Probe(&mut node))?;
+        self.entries.push((field, node));
+        Ok(value)
+    }
+}
+
+struct UnitVariant(&'static str);
+
+impl<'de> EnumAccess<'de> for UnitVariant {
+    type Error = ProbeError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Self::Error> {
+        let variant = seed.deserialize(self.0.into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for UnitVariant {
+    type Error = ProbeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, _seed: T) -> Result<T::Value, Self::Error> {
+        Err(de::Error::custom("config enums are unit-only"))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("config enums are unit-only"))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("config enums are unit-only"))
+    }
+}
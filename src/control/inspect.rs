@@ -0,0 +1,264 @@
+use crate::detection::{ConditionCheck, Explanation, Readiness};
+use crate::models::{history_retention_secs, Candle, PriceSnapshot, SymbolData};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+// Longer windows are downsampled to keep the report readable
+const MAX_HISTORY_ROWS: usize = 60;
+// Completed 500ms candles kept by the candle buffer
+const CANDLE_BUFFER_SECS: u64 = 20;
+// Resolution of the candles built from price history for windows the candle buffer doesn't cover
+const HISTORY_CANDLE_MS: i64 = 5000;
+
+/// A strategy's view of the symbol at the time of the report
+pub struct StrategyState {
+    pub name: &'static str,
+    pub enabled: bool,
+    /// Outcome of the strategy's last check of the symbol, None if it never ran
+    pub readiness: Option<Readiness>,
+    /// None until the symbol has both a last and a mark price
+    pub explanation: Option<Explanation>,
+}
+
+/// Plain-text report of a symbol's recent history, candles, derived features and the
+/// strategy thresholds it came closest to, built from what is held in memory.
+///
+/// History retention is limited (see `SymbolData`), so windows longer than that report
+/// whatever is still available and say so.
+pub fn inspect_report(
+    data: &SymbolData,
+    window_secs: u64,
+    spike_lookback_secs: u64,
+    strategies: &[StrategyState],
+) -> String {
+    let now = data.now();
+    let cutoff = now - Duration::seconds(window_secs as i64);
+    let mut out = String::new();
+
+    let _ = writeln!(out, "INSPECT {} | window {}s | {}", data.symbol, window_secs, now.to_rfc3339());
+
+    match (data.current_last_price, data.current_mark_price) {
+        (Some(last), Some(mark)) => {
+            let _ = writeln!(
+                out,
+                "Current: last {:.6} | mark {:.6} | ratio {:.6} | abs diff {:.6}",
+                last,
+                mark,
+                last / mark,
+                last - mark
+            );
+        }
+        _ => {
+            let _ = writeln!(out, "Current: no complete last/mark price yet");
+        }
+    }
+    match data.quote {
+        Some(quote) => {
+            let tradable = data
+                .tradable_ratio()
+                .map(|ratio| format!("{:.6}", ratio))
+                .unwrap_or_else(|| "n/a".to_string());
+            let _ = writeln!(
+                out,
+                "Quote: bid {:.6} | ask {:.6} | spread {:.4}% | bid/mark {} | age {:.1}s",
+                quote.best_bid,
+                quote.best_ask,
+                quote.spread_pct() * 100.0,
+                tradable,
+                (now - quote.timestamp).num_milliseconds() as f64 / 1000.0
+            );
+        }
+        None => {
+            let _ = writeln!(out, "Quote: no best bid/ask yet");
+        }
+    }
+    if let Some(stats) = data.stats_24h {
+        let _ = writeln!(out, "24h: {}", stats);
+    }
+    if let Some(ref watch) = data.watch {
+        let _ = writeln!(
+            out,
+            "Watchlist: since {} until {} (thresholds relaxed {:.0}%)",
+            format_time(watch.since),
+            format_time(watch.until),
+            watch.relax_fraction * 100.0
+        );
+    }
+    match data.orderbook.as_ref() {
+        Some(orderbook) => {
+            let spread = orderbook.calculate_spread_pct().map(|s| format!("{:.4}%", s * 100.0));
+            let _ = writeln!(
+                out,
+                "Orderbook: {} bids / {} asks | spread {}",
+                orderbook.bids.len(),
+                orderbook.asks.len(),
+                spread.unwrap_or_else(|| "n/a".to_string())
+            );
+        }
+        None => {
+            let _ = writeln!(out, "Orderbook: none");
+        }
+    }
+
+    // Price history with per-sample features
+    let samples: Vec<&PriceSnapshot> = data.price_history.iter().filter(|s| s.timestamp >= cutoff).collect();
+    let _ = writeln!(out);
+    match (samples.first(), samples.last()) {
+        (Some(first), Some(last)) => {
+            let covered = (last.timestamp - first.timestamp).num_seconds();
+            let _ = write!(out, "Price history: {} samples covering {}s", samples.len(), covered);
+            let retention = history_retention_secs();
+            if window_secs as i64 > retention {
+                let _ = write!(out, " (only the last {}s are kept in memory)", retention);
+            }
+            let _ = writeln!(out);
+        }
+        _ => {
+            let _ = writeln!(out, "Price history: no samples in the window");
+        }
+    }
+    if !samples.is_empty() {
+        let _ = writeln!(
+            out,
+            "  {:<12}  {:>14}  {:>14}  {:>10}  {:>12}  {:>10}",
+            "time",
+            "last",
+            "mark",
+            "ratio",
+            "abs_diff",
+            format!("spike_{}s", spike_lookback_secs)
+        );
+        let step = samples.len().div_ceil(MAX_HISTORY_ROWS);
+        for (i, sample) in samples.iter().enumerate() {
+            // Always include the latest sample
+            if i % step != 0 && i != samples.len() - 1 {
+                continue;
+            }
+            let spike = price_before(&data.price_history, sample.timestamp - Duration::seconds(spike_lookback_secs as i64))
+                .map(|old| format!("{:.4}", sample.last_price / old))
+                .unwrap_or_else(|| "n/a".to_string());
+            let _ = writeln!(
+                out,
+                "  {:<12}  {:>14.6}  {:>14.6}  {:>10.6}  {:>12.6}  {:>10}",
+                format_time(sample.timestamp),
+                sample.last_price,
+                sample.mark_price,
+                sample.last_price / sample.mark_price,
+                sample.last_price - sample.mark_price,
+                spike
+            );
+        }
+
+        let peak = samples
+            .iter()
+            .max_by(|a, b| (a.last_price / a.mark_price).total_cmp(&(b.last_price / b.mark_price)));
+        if let Some(peak) = peak {
+            let _ = writeln!(
+                out,
+                "Peak ratio: {:.6} at {}",
+                peak.last_price / peak.mark_price,
+                format_time(peak.timestamp)
+            );
+        }
+    }
+
+    // Completed candles
+    let (last_candles, mark_candles) = data.candle_buffer.get_recent_candles(window_secs as i64);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Candles: {} completed", last_candles.len());
+    write_candles(&mut out, &last_candles, &mark_candles);
+
+    // Coarser candles over the longer price history
+    if window_secs > CANDLE_BUFFER_SECS {
+        let cutoff_ms = cutoff.timestamp_millis();
+        let (last_candles, mark_candles) = data.candles_from_history(HISTORY_CANDLE_MS);
+        let last_candles: Vec<Candle> = last_candles.into_iter().filter(|c| c.timestamp_ms >= cutoff_ms).collect();
+        let mark_candles: Vec<Candle> = mark_candles.into_iter().filter(|c| c.timestamp_ms >= cutoff_ms).collect();
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "{}s candles from price history: {}",
+            HISTORY_CANDLE_MS / 1000,
+            last_candles.len()
+        );
+        write_candles(&mut out, &last_candles, &mark_candles);
+    }
+
+    // How close each strategy is to firing right now
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Closest to firing:");
+    for strategy in strategies {
+        let state = match (strategy.enabled, strategy.readiness) {
+            (false, _) => "off".to_string(),
+            (true, Some(readiness)) => format!("on, {}", readiness),
+            (true, None) => "on, not checked yet".to_string(),
+        };
+        let Some(ref explanation) = strategy.explanation else {
+            let _ = writeln!(out, "  {} [{}]: no prices yet", strategy.name, state);
+            continue;
+        };
+
+        let passed = explanation.checks.iter().filter(|c| c.passed).count();
+        let nearest_miss = explanation
+            .checks
+            .iter()
+            .filter(|c| !c.passed)
+            .min_by(|a, b| a.shortfall_pct().total_cmp(&b.shortfall_pct()));
+        let _ = write!(out, "  {} [{}]: {}/{} passed", strategy.name, state, passed, explanation.checks.len());
+        match nearest_miss {
+            Some(check) => {
+                let _ = write!(out, " | nearest miss: {} ({:.2}% short)", check_summary(check), check.shortfall_pct());
+            }
+            None if explanation.unavailable.is_empty() => {
+                let _ = write!(out, " | all conditions met");
+            }
+            None => {}
+        }
+        if !explanation.unavailable.is_empty() {
+            let _ = write!(out, " | n/a: {}", explanation.unavailable.join(", "));
+        }
+        let _ = writeln!(out);
+    }
+
+    out
+}
+
+fn write_candles(out: &mut String, last_candles: &[Candle], mark_candles: &[Candle]) {
+    if last_candles.is_empty() {
+        return;
+    }
+    let _ = writeln!(
+        out,
+        "  {:<12}  {:>14}  {:>14}  {:>14}  {:>14}  {:>14}",
+        "time", "open", "high", "low", "close", "mark_close"
+    );
+    for (candle, mark) in last_candles.iter().zip(mark_candles.iter()) {
+        let time = DateTime::from_timestamp_millis(candle.timestamp_ms).unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "  {:<12}  {:>14.6}  {:>14.6}  {:>14.6}  {:>14.6}  {:>14.6}",
+            format_time(time),
+            candle.open,
+            candle.high,
+            candle.low,
+            candle.close,
+            mark.close
+        );
+    }
+}
+
+/// Last price of the newest sample at or before `at`
+fn price_before(history: &VecDeque<PriceSnapshot>, at: DateTime<Utc>) -> Option<f64> {
+    history.iter().rev().find(|s| s.timestamp <= at).map(|s| s.last_price)
+}
+
+fn check_summary(check: &ConditionCheck) -> String {
+    // Strip the pass/fail marker; the report states that separately
+    let text = check.to_string();
+    text.split_once(' ').map(|(_, rest)| rest.to_string()).unwrap_or(text)
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%H:%M:%S%.3f").to_string()
+}
@@ -0,0 +1,5 @@
+pub mod inspect;
+pub mod server;
+
+pub use inspect::*;
+pub use server::*;
@@ -0,0 +1,195 @@
+use crate::config::ControlConfig;
+use anyhow::{bail, Result};
+use std::path::Path;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{info, warn};
+
+/// A command received on the control socket
+#[derive(Debug, Clone)]
+pub enum ControlCommand {
+    /// Report on a symbol's recent in-memory state
+    Inspect { symbol: String, window_secs: u64 },
+    /// Show the settings that can be changed at runtime
+    Settings,
+    /// Change a runtime setting, e.g. `csv_export.enabled`
+    Set { key: String, value: String },
+    /// Write an issue bundle for a bug report
+    Bundle,
+    /// Open a manual episode on a symbol, optionally entering a position
+    Signal {
+        symbol: String,
+        execute: bool,
+        note: Option<String>,
+    },
+}
+
+impl ControlCommand {
+    /// Parses one request line, e.g. `inspect BTC_USDT 600`, `set csv_export.enabled false` or
+    /// `signal BTC_USDT --execute listing tweet`
+    pub fn parse(line: &str) -> Result<Self> {
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("inspect") => {
+                let Some(symbol) = parts.next() else {
+                    bail!("usage: inspect SYMBOL [WINDOW_SECS]");
+                };
+                let window_secs = match parts.next() {
+                    Some(secs) => secs.parse()?,
+                    None => DEFAULT_INSPECT_WINDOW_SECS,
+                };
+                Ok(Self::Inspect {
+                    symbol: symbol.to_uppercase(),
+                    window_secs,
+                })
+            }
+            Some("settings") => Ok(Self::Settings),
+            Some("set") => {
+                let (Some(key), Some(value)) = (parts.next(), parts.next()) else {
+                    bail!("usage: set KEY VALUE");
+                };
+                Ok(Self::Set {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                })
+            }
+            Some("bundle") => Ok(Self::Bundle),
+            Some("signal") => {
+                let Some(symbol) = parts.next() else {
+                    bail!("usage: signal SYMBOL [--execute] [NOTE]");
+                };
+                let mut rest = parts.peekable();
+                let execute = rest.next_if_eq(&"--execute").is_some();
+                let note = rest.collect::<Vec<_>>().join(" ");
+                Ok(Self::Signal {
+                    symbol: symbol.to_uppercase(),
+                    execute,
+                    note: (!note.is_empty()).then_some(note),
+                })
+            }
+            Some(other) => bail!("unknown command '{}'", other),
+            None => bail!("empty command"),
+        }
+    }
+
+    /// Request line understood by `parse`
+    pub fn to_line(&self) -> String {
+        match self {
+            Self::Inspect { symbol, window_secs } => format!("inspect {} {}", symbol, window_secs),
+            Self::Settings => "settings".to_string(),
+            Self::Set { key, value } => format!("set {} {}", key, value),
+            Self::Bundle => "bundle".to_string(),
+            Self::Signal { symbol, execute, note } => {
+                let mut line = format!("signal {}", symbol);
+                if *execute {
+                    line.push_str(" --execute");
+                }
+                if let Some(note) = note {
+                    // One request per line
+                    line.push(' ');
+                    line.push_str(&note.replace(['\r', '\n'], " "));
+                }
+                line
+            }
+        }
+    }
+}
+
+pub const DEFAULT_INSPECT_WINDOW_SECS: u64 = 600;
+
+/// A command together with the channel its plain-text response goes back on
+pub struct ControlRequest {
+    pub command: ControlCommand,
+    pub reply: oneshot::Sender<String>,
+}
+
+/// Local Unix socket for on-demand queries against the running detector.
+///
+/// Commands are handed to the main event loop, which owns the strategies, so responses
+/// see exactly the state the strategies evaluate.
+pub struct ControlServer {
+    config: ControlConfig,
+    request_tx: mpsc::UnboundedSender<ControlRequest>,
+}
+
+impl ControlServer {
+    pub fn new(config: ControlConfig, request_tx: mpsc::UnboundedSender<ControlRequest>) -> Self {
+        Self { config, request_tx }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let path = Path::new(&self.config.socket_path);
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        // A socket file left behind by a previous run would make bind fail
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+
+        let listener = UnixListener::bind(path)?;
+        info!("[Control] Listening on {}", self.config.socket_path);
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let request_tx = self.request_tx.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_connection(stream, request_tx).await {
+                    warn!("[Control] Failed to serve request: {:?}", e);
+                }
+            });
+        }
+    }
+}
+
+async fn handle_connection(stream: UnixStream, request_tx: mpsc::UnboundedSender<ControlRequest>) -> Result<()> {
+    let (read, mut write) = stream.into_split();
+    let mut line = String::new();
+    BufReader::new(read).read_line(&mut line).await?;
+
+    let response = match ControlCommand::parse(&line) {
+        Ok(command) => {
+            let (reply_tx, reply_rx) = oneshot::channel();
+            request_tx.send(ControlRequest { command, reply: reply_tx })?;
+            reply_rx.await?
+        }
+        Err(e) => format!("error: {}\n", e),
+    };
+
+    write.write_all(response.as_bytes()).await?;
+    write.shutdown().await?;
+    Ok(())
+}
+
+/// Sends `command` to a running instance and returns its response
+pub async fn send_control_command(socket_path: &str, command: &ControlCommand) -> Result<String> {
+    let mut stream = match UnixStream::connect(socket_path).await {
+        Ok(stream) => stream,
+        Err(e) => bail!(
+            "cannot reach the control socket at {} ({}); is mexc-sniper running with [control] enabled?",
+            socket_path,
+            e
+        ),
+    };
+    stream.write_all(format!("{}\n", command.to_line()).as_bytes()).await?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    Ok(response)
+}
+
+/// Parses a window such as `600`, `90s`, `10m` or `1h` into seconds
+pub fn parse_window_secs(text: &str) -> Result<u64> {
+    let (number, unit) = match text.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((i, _)) => text.split_at(i),
+        None => (text, "s"),
+    };
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        _ => bail!("invalid window '{}' (expected e.g. 90s, 10m or 1h)", text),
+    };
+    Ok(number.parse::<u64>()? * multiplier)
+}
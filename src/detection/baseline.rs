@@ -0,0 +1,156 @@
+use crate::config::RatioBaselineConfig;
+use crate::models::{Symbol, SymbolData};
+use dashmap::{DashMap, DashSet};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+// Flagged symbols named in the status line
+const MAX_LISTED: usize = 5;
+
+/// Time-weighted average last/mark ratio of a symbol over the baseline window
+#[derive(Debug, Clone, Copy)]
+pub struct RatioBaseline {
+    pub ratio: f64,
+    /// How much of the window the samples cover
+    pub covered: Duration,
+}
+
+impl RatioBaseline {
+    /// Distance of the baseline from 1.0 in percent
+    pub fn deviation_pct(&self) -> f64 {
+        (self.ratio - 1.0) * 100.0
+    }
+}
+
+/// Per-symbol average last/mark ratio over the past `window_mins`, sampled at a fixed
+/// interval so every sample stands for the same amount of time.
+///
+/// A symbol whose baseline stays `flag_deviation_pct` or more away from 1.0 usually has
+/// broken mark pricing rather than a pump; it is flagged in the status report and, with
+/// `exclude_flagged`, no longer evaluated by the strategies until its baseline recovers.
+pub struct RatioBaselines {
+    config: RatioBaselineConfig,
+    samples: DashMap<Symbol, VecDeque<f64>>,
+    flagged: DashSet<String>,
+}
+
+impl RatioBaselines {
+    pub fn new(config: RatioBaselineConfig) -> Self {
+        Self {
+            config,
+            samples: DashMap::new(),
+            flagged: DashSet::new(),
+        }
+    }
+
+    pub async fn run(self: Arc<Self>, symbol_data: Arc<DashMap<Symbol, SymbolData>>) {
+        let mut interval = tokio::time::interval(self.sample_interval());
+        loop {
+            interval.tick().await;
+            self.sample(&symbol_data);
+        }
+    }
+
+    fn sample_interval(&self) -> Duration {
+        Duration::from_secs(self.config.sample_interval_secs.max(1))
+    }
+
+    fn window_samples(&self) -> usize {
+        ((self.config.window_mins * 60) / self.config.sample_interval_secs.max(1)).max(1) as usize
+    }
+
+    fn sample(&self, symbol_data: &DashMap<Symbol, SymbolData>) {
+        let capacity = self.window_samples();
+        for entry in symbol_data.iter() {
+            let (Some(last), Some(mark)) = (entry.current_last_price, entry.current_mark_price) else {
+                continue;
+            };
+            if mark <= 0.0 {
+                continue;
+            }
+            let mut samples = self.samples.entry(entry.key().clone()).or_default();
+            if samples.len() == capacity {
+                samples.pop_front();
+            }
+            samples.push_back(last / mark);
+        }
+        // Symbols rotated out or delisted
+        self.samples.retain(|symbol, _| symbol_data.contains_key(symbol));
+        self.flagged.retain(|symbol| symbol_data.contains_key(symbol.as_str()));
+
+        for entry in self.samples.iter() {
+            self.update_flag(entry.key(), self.baseline_of(&entry));
+        }
+    }
+
+    fn baseline_of(&self, samples: &VecDeque<f64>) -> Option<RatioBaseline> {
+        if samples.is_empty() {
+            return None;
+        }
+        Some(RatioBaseline {
+            ratio: samples.iter().sum::<f64>() / samples.len() as f64,
+            covered: self.sample_interval() * samples.len() as u32,
+        })
+    }
+
+    fn update_flag(&self, symbol: &str, baseline: Option<RatioBaseline>) {
+        let Some(baseline) = baseline else {
+            return;
+        };
+        let min_covered = Duration::from_secs(self.config.min_coverage_mins * 60);
+        let off = baseline.deviation_pct().abs() >= self.config.flag_deviation_pct;
+        if off && baseline.covered >= min_covered {
+            if self.flagged.insert(symbol.to_string()) {
+                warn!(
+                    "[Baseline] {} ratio baseline is {:.6} ({:+.2}%) over the last {}m - mark price looks broken{}",
+                    symbol,
+                    baseline.ratio,
+                    baseline.deviation_pct(),
+                    baseline.covered.as_secs() / 60,
+                    if self.config.exclude_flagged { "; excluded from the strategies" } else { "" }
+                );
+            }
+        } else if !off && self.flagged.remove(symbol).is_some() {
+            info!("[Baseline] {} ratio baseline is back at {:.6}", symbol, baseline.ratio);
+        }
+    }
+
+    pub fn baseline(&self, symbol: &str) -> Option<RatioBaseline> {
+        self.samples.get(symbol).and_then(|samples| self.baseline_of(&samples))
+    }
+
+    pub fn is_flagged(&self, symbol: &str) -> bool {
+        self.flagged.contains(symbol)
+    }
+
+    /// Whether the strategies should skip the symbol
+    pub fn is_excluded(&self, symbol: &str) -> bool {
+        self.config.exclude_flagged && self.is_flagged(symbol)
+    }
+
+    /// Status report line: tracked symbols and the flagged ones, furthest from 1.0 first
+    pub fn summary(&self) -> String {
+        let mut flagged: Vec<(String, RatioBaseline)> = self
+            .flagged
+            .iter()
+            .filter_map(|symbol| self.baseline(&symbol).map(|baseline| (symbol.clone(), baseline)))
+            .collect();
+        flagged.sort_by(|a, b| b.1.deviation_pct().abs().total_cmp(&a.1.deviation_pct().abs()));
+
+        let mut line = format!(
+            "Ratio baselines ({}m): {} symbols | flagged: {}",
+            self.config.window_mins,
+            self.samples.len(),
+            flagged.len()
+        );
+        for (symbol, baseline) in flagged.iter().take(MAX_LISTED) {
+            line.push_str(&format!(" | {} {:.6} ({:+.2}%)", symbol, baseline.ratio, baseline.deviation_pct()));
+        }
+        if flagged.len() > MAX_LISTED {
+            line.push_str(&format!(" | +{} more", flagged.len() - MAX_LISTED));
+        }
+        line
+    }
+}
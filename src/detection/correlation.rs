@@ -0,0 +1,281 @@
+use crate::alerts::{Alert, AlertDigest, KillSwitchEvent, MissedDigest, Notifier};
+use crate::config::CorrelationConfig;
+use crate::detection::new_episode_id;
+use crate::models::{ContractStateChange, NewListing};
+use crate::utils::{DiskWriter, SharedClock};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::info;
+
+/// Columns of correlations.csv, in order
+const CORRELATION_COLUMNS: [&str; 8] = [
+    "correlation_id",
+    "symbol",
+    "strategies",
+    "episode_ids",
+    "first_start",
+    "last_start",
+    "spread_secs",
+    "peak_ratio",
+];
+
+/// One episode start taking part in a correlation
+#[derive(Debug, Clone)]
+pub struct CorrelatedEpisode {
+    pub strategy: String,
+    pub episode_id: String,
+    pub started_at: DateTime<Utc>,
+    pub ratio: f64,
+}
+
+/// Episodes of different strategies that started on the same symbol within the window
+#[derive(Debug, Clone)]
+pub struct CorrelationRecord {
+    /// Time-sortable like episode ids, from the first start
+    pub id: String,
+    pub symbol: String,
+    /// In start order, one per strategy
+    pub episodes: Vec<CorrelatedEpisode>,
+}
+
+impl CorrelationRecord {
+    /// `strategy1+strategy2+strategy4`, in start order
+    pub fn strategies(&self) -> String {
+        self.episodes.iter().map(|e| e.strategy.as_str()).collect::<Vec<_>>().join("+")
+    }
+
+    /// Time between the first and the last start
+    pub fn spread(&self) -> chrono::Duration {
+        match (self.episodes.first(), self.episodes.last()) {
+            (Some(first), Some(last)) => last.started_at - first.started_at,
+            _ => chrono::Duration::zero(),
+        }
+    }
+
+    fn peak_ratio(&self) -> f64 {
+        self.episodes.iter().map(|e| e.ratio).fold(f64::MIN, f64::max)
+    }
+
+    fn row(&self) -> Vec<String> {
+        let (first, last) = (&self.episodes[0], &self.episodes[self.episodes.len() - 1]);
+        vec![
+            self.id.clone(),
+            self.symbol.clone(),
+            self.strategies(),
+            self.episodes.iter().map(|e| e.episode_id.as_str()).collect::<Vec<_>>().join("+"),
+            first.started_at.to_rfc3339(),
+            last.started_at.to_rfc3339(),
+            format!("{:.3}", self.spread().num_milliseconds() as f64 / 1000.0),
+            format!("{:.6}", self.peak_ratio()),
+        ]
+    }
+}
+
+impl fmt::Display for CorrelationRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ids: Vec<&str> = self.episodes.iter().map(|e| e.episode_id.as_str()).collect();
+        write!(
+            f,
+            "{}: {} agreed within {:.1}s | Peak ratio: {:.4} | Correlation: {} | Episodes: {}",
+            self.symbol,
+            self.strategies(),
+            self.spread().num_milliseconds() as f64 / 1000.0,
+            self.peak_ratio(),
+            self.id,
+            ids.join(", ")
+        )
+    }
+}
+
+#[derive(Default)]
+struct CorrelatorState {
+    // Symbol -> episode starts since the first one, while its window is open
+    open: HashMap<String, Vec<CorrelatedEpisode>>,
+    // Strategy combination -> correlations recorded since startup
+    recorded: BTreeMap<String, u64>,
+}
+
+/// Links episodes that different strategies start on the same symbol within
+/// `window_secs` of the first one, so confluence can be analyzed after the fact rather than
+/// only through strategy5's fixed combination.
+///
+/// Receives alerts like any other notifier (every alert opens an episode). Once a window
+/// closes with `min_strategies` or more strategies in it, the correlation is logged and
+/// appended to `correlations.csv` in the log directory, with the episode ids that tie it to
+/// the episode logs and recordings.
+#[derive(Clone)]
+pub struct EpisodeCorrelator {
+    config: CorrelationConfig,
+    file_path: PathBuf,
+    disk: Arc<DiskWriter>,
+    state: Arc<Mutex<CorrelatorState>>,
+    // Closes windows in market time, so replays correlate like the live run
+    clock: SharedClock,
+}
+
+impl EpisodeCorrelator {
+    pub fn new(config: CorrelationConfig, log_dir: &str, disk: Arc<DiskWriter>, clock: SharedClock) -> Result<Self> {
+        fs::create_dir_all(log_dir)?;
+        Ok(Self {
+            config,
+            file_path: PathBuf::from(log_dir).join("correlations.csv"),
+            disk,
+            state: Arc::default(),
+            clock,
+        })
+    }
+
+    fn window(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.config.window_secs as i64)
+    }
+
+    pub fn record_start(&self, episode: CorrelatedEpisode, symbol: &str) -> Option<CorrelationRecord> {
+        let mut state = self.state.lock().unwrap();
+        let expired = state
+            .open
+            .get(symbol)
+            .and_then(|episodes| episodes.first())
+            .is_some_and(|first| episode.started_at - first.started_at > self.window());
+        let closed = if expired { state.open.remove(symbol) } else { None };
+
+        let episodes = state.open.entry(symbol.to_string()).or_default();
+        // A strategy's repeat episode within the window adds nothing to the agreement
+        if !episodes.iter().any(|e| e.strategy == episode.strategy) {
+            episodes.push(episode);
+        }
+        closed.and_then(|episodes| self.close(&mut state, symbol, episodes))
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            for record in self.close_expired(self.clock.now()) {
+                self.write(record).await;
+            }
+        }
+    }
+
+    fn close_expired(&self, now: DateTime<Utc>) -> Vec<CorrelationRecord> {
+        let mut state = self.state.lock().unwrap();
+        let expired: Vec<String> = state
+            .open
+            .iter()
+            .filter(|(_, episodes)| episodes.first().is_some_and(|first| now - first.started_at > self.window()))
+            .map(|(symbol, _)| symbol.clone())
+            .collect();
+        expired
+            .into_iter()
+            .filter_map(|symbol| {
+                let episodes = state.open.remove(&symbol)?;
+                self.close(&mut state, &symbol, episodes)
+            })
+            .collect()
+    }
+
+    fn close(&self, state: &mut CorrelatorState, symbol: &str, episodes: Vec<CorrelatedEpisode>) -> Option<CorrelationRecord> {
+        if episodes.len() < self.config.min_strategies.max(2) {
+            return None;
+        }
+        let record = CorrelationRecord {
+            id: new_episode_id(episodes[0].started_at),
+            symbol: symbol.to_string(),
+            episodes,
+        };
+        *state.recorded.entry(record.strategies()).or_default() += 1;
+        Some(record)
+    }
+
+    async fn write(&self, record: CorrelationRecord) {
+        info!("[Correlation] {}", record);
+        let file_path = self.file_path.clone();
+        let name = format!("correlation {}", record.id);
+        self.disk
+            .write(name, move || {
+                let write_header = !file_path.exists();
+                let file = OpenOptions::new().create(true).append(true).open(&file_path)?;
+                let mut wtr = csv::Writer::from_writer(file);
+                if write_header {
+                    wtr.write_record(CORRELATION_COLUMNS)?;
+                }
+                wtr.write_record(record.row())?;
+                wtr.flush()?;
+                Ok(())
+            })
+            .await;
+    }
+
+    /// One line for the status report, with the most frequent strategy combinations
+    pub fn summary(&self) -> String {
+        let state = self.state.lock().unwrap();
+        let total: u64 = state.recorded.values().sum();
+        let mut combinations: Vec<(&String, &u64)> = state.recorded.iter().collect();
+        combinations.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        let top: Vec<String> = combinations
+            .iter()
+            .take(5)
+            .map(|(strategies, count)| format!("{} x{}", strategies, count))
+            .collect();
+        format!(
+            "Correlations: {} recorded (window {}s){}",
+            total,
+            self.config.window_secs,
+            if top.is_empty() { String::new() } else { format!(" | {}", top.join(", ")) }
+        )
+    }
+}
+
+#[async_trait]
+impl Notifier for EpisodeCorrelator {
+    fn name(&self) -> &str {
+        "correlation"
+    }
+
+    fn is_internal(&self) -> bool {
+        true
+    }
+
+    async fn notify(&self, alert: &Alert) -> Result<()> {
+        // Pipelines mostly repeat the top-level strategies with other thresholds
+        if !self.config.include_pipelines && alert.strategy.contains('/') {
+            return Ok(());
+        }
+        let episode = CorrelatedEpisode {
+            strategy: alert.strategy.clone(),
+            episode_id: alert.episode_id.clone(),
+            started_at: alert.timestamp,
+            ratio: alert.ratio,
+        };
+        if let Some(record) = self.record_start(episode, &alert.symbol) {
+            self.write(record).await;
+        }
+        Ok(())
+    }
+
+    async fn notify_digest(&self, _digest: &MissedDigest) -> Result<()> {
+        Ok(())
+    }
+
+    async fn notify_alert_digest(&self, _digest: &AlertDigest) -> Result<()> {
+        Ok(())
+    }
+
+    async fn notify_kill_switch(&self, _event: &KillSwitchEvent) -> Result<()> {
+        Ok(())
+    }
+
+    async fn notify_contract_state(&self, _change: &ContractStateChange) -> Result<()> {
+        Ok(())
+    }
+
+    async fn notify_new_listing(&self, _listing: &NewListing) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,279 @@
+use crate::alerts::{Alert, AlertSender, SignalSource};
+use crate::detection::{
+    EpisodeTracker, Evaluation, Explanation, FeatureCache, PriceFloor, Readiness, Spread, Strategy, StrategySection,
+    StrategyThrottle, ThresholdResolver, Verdict, WarmupGap,
+};
+use crate::execution::OrderExecutor;
+use crate::export::CsvExporter;
+use crate::funding::format_rate;
+use crate::models::{ContractCatalog, SymbolData, SymbolRename};
+use crate::utils::{EpisodeLogger, SharedClock};
+use std::sync::Arc;
+use tracing::{info, instrument};
+
+/// The conditions that set one strategy apart. Episodes, logging, alerts, recordings and
+/// entries are the same for every strategy and handled by `Detector`.
+pub trait StrategyRules: Send + 'static {
+    /// The config.toml section with the strategy's thresholds
+    type Config: StrategySection;
+
+    /// Prefix of the strategy's log lines, e.g. `Strategy1`
+    const LABEL: &'static str;
+
+    /// Whether every orderbook update is a reason to re-check, not only price updates
+    const USES_ORDERBOOK: bool = false;
+
+    /// Start of the log line of a new episode
+    const HEADLINE: &'static str = "🚨 ANOMALY DETECTED";
+
+    /// Adds the strategy's conditions, and anything it needs before it is armed, to
+    /// `evaluation` for the symbol's settings `config`
+    fn evaluate(
+        &self,
+        config: &Self::Config,
+        evaluation: &mut Evaluation,
+        data: &SymbolData,
+        features: &FeatureCache,
+        spread: Spread,
+        throttle: &StrategyThrottle,
+    );
+
+    /// What the trigger log line shows besides the symbol, ratio and funding
+    fn describe_trigger(&self, evaluation: &Evaluation, features: &FeatureCache, spread: Spread) -> String;
+}
+
+/// A strategy: its rules plus the episode tracking, logging, alerting and recording every
+/// strategy shares
+pub struct Detector<R: StrategyRules> {
+    rules: R,
+    thresholds: ThresholdResolver<R::Config>,
+    price_floors: ThresholdResolver<PriceFloor>,
+    tracker: EpisodeTracker,
+    logger: Arc<EpisodeLogger>,
+    csv_exporter: Option<Arc<CsvExporter>>,
+    alert_tx: AlertSender,
+    throttle: Arc<StrategyThrottle>,
+    executor: Option<Arc<OrderExecutor>>,
+}
+
+impl<R: StrategyRules> Detector<R> {
+    pub fn new(
+        rules: R,
+        config: impl Into<ThresholdResolver<R::Config>>,
+        cooldown_seconds: u64,
+        logger: Arc<EpisodeLogger>,
+        csv_exporter: Option<Arc<CsvExporter>>,
+        alert_tx: AlertSender,
+    ) -> Self {
+        let thresholds: ThresholdResolver<R::Config> = config.into();
+        Self {
+            rules,
+            tracker: EpisodeTracker::new(cooldown_seconds).with_end_condition(thresholds.map(|config| config.end())),
+            price_floors: thresholds.map(|config| config.price_floor()),
+            thresholds,
+            logger,
+            csv_exporter,
+            alert_tx,
+            throttle: Arc::default(),
+            executor: None,
+        }
+    }
+
+    /// Runs episode timing on `clock` instead of the wall clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.tracker.set_clock(clock);
+        self
+    }
+
+    /// Resolves the contract-relative price floors from exchange metadata
+    pub fn with_contracts(mut self, contracts: Arc<ContractCatalog>) -> Self {
+        for price_floor in self.price_floors.values_mut() {
+            price_floor.set_contracts(contracts.clone());
+        }
+        self
+    }
+
+    /// Raises the ratio thresholds while the strategy fires more often than allowed
+    pub fn with_throttle(mut self, throttle: Arc<StrategyThrottle>) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// Enters a position on every new episode
+    pub fn with_executor(mut self, executor: Option<Arc<OrderExecutor>>) -> Self {
+        self.executor = executor;
+        self
+    }
+
+    /// Evaluates the gates and every condition at the symbol's current prices
+    fn evaluate(&self, data: &SymbolData, features: &FeatureCache, spread: Spread) -> Evaluation {
+        let mut evaluation = Evaluation::new(features.gates(self.price_floors.get(&data.symbol)));
+        let config = self.thresholds.get(&data.symbol);
+        self.rules.evaluate(config, &mut evaluation, data, features, spread, &self.throttle);
+        evaluation
+    }
+
+    fn started(&self, data: &SymbolData, features: &FeatureCache, spread: Spread, evaluation: &Evaluation) {
+        let name = R::Config::NAME;
+        let Spread { last_price, mark_price, ratio, .. } = spread;
+        self.throttle.record_start(self.tracker.now());
+        let episode_id = self.tracker.episode_id(&data.symbol).unwrap_or_default();
+        info!(
+            "[{}] {}: {} | Ratio: {:.4} | {} | Funding: {} | Episode: {}",
+            R::LABEL,
+            R::HEADLINE,
+            data.symbol,
+            ratio,
+            self.rules.describe_trigger(evaluation, features, spread),
+            format_rate(data.funding_rate),
+            episode_id
+        );
+
+        let alert = Alert {
+            strategy: name.to_string(),
+            episode_id: episode_id.clone(),
+            symbol: data.symbol.to_string(),
+            ratio,
+            last_price,
+            mark_price,
+            stats_24h: data.stats_24h,
+            explanation: evaluation.explanation(),
+            market_moves: Vec::new(),
+            timestamp: self.tracker.now(),
+            source: SignalSource::Detector,
+        };
+        self.logger.record_trigger(&alert, data.orderbook.as_ref());
+        if let Err(e) = self.alert_tx.send(alert.into()) {
+            tracing::error!("Failed to queue alert: {:?}", e);
+        }
+
+        if let Some(ref exporter) = self.csv_exporter {
+            let recording = self.thresholds.get(&data.symbol).recording();
+            let pre_buffer_candles = data.candle_buffer.get_pre_buffer_candles(exporter.pre_buffer_secs(&recording));
+            exporter.start_recording(&data.symbol, name, &episode_id, pre_buffer_candles, recording);
+        }
+
+        if let Some(ref executor) = self.executor {
+            executor.enter_position(&data.symbol, name, last_price, data.orderbook.as_ref(), &episode_id);
+        }
+    }
+}
+
+impl<R: StrategyRules> Strategy for Detector<R> {
+    fn name(&self) -> &'static str {
+        R::Config::NAME
+    }
+
+    fn enabled(&self) -> bool {
+        self.thresholds.base().enabled()
+    }
+
+    fn uses_orderbook(&self) -> bool {
+        R::USES_ORDERBOOK
+    }
+
+    #[instrument(level = "trace", name = "strategy.check", skip_all, fields(strategy = R::Config::NAME, symbol = %data.symbol))]
+    fn check(&mut self, data: &SymbolData, features: &FeatureCache) -> Readiness {
+        if !self.thresholds.get(&data.symbol).enabled() {
+            return Readiness::Disabled;
+        }
+
+        let Some(spread) = features.spread() else {
+            return Readiness::Warming(WarmupGap::Prices);
+        };
+        let evaluation = self.evaluate(data, features, spread);
+        let condition_met = match evaluation.verdict() {
+            Verdict::Met => true,
+            Verdict::NotMet => false,
+            Verdict::Hold => return evaluation.readiness,
+        };
+
+        let (ended, started) = self.tracker.check_condition(
+            &data.symbol,
+            condition_met,
+            spread.ratio,
+            spread.last_price,
+            spread.mark_price,
+        );
+        if started {
+            self.started(data, features, spread, &evaluation);
+        }
+
+        if let Some(episode) = ended {
+            if let Err(e) = self.logger.log_episode(
+                &episode.id,
+                &episode.symbol,
+                episode.start_time,
+                self.tracker.now(),
+                episode.peak_ratio,
+                episode.peak_last_price,
+                episode.peak_mark_price,
+                None,
+            ) {
+                tracing::error!("Failed to log episode: {:?}", e);
+            } else {
+                info!(
+                    "[{}] ✅ Episode ended: {} | Peak Ratio: {:.4} | Duration: {:?} | Episode: {}",
+                    R::LABEL,
+                    episode.symbol,
+                    episode.peak_ratio,
+                    self.tracker.now().signed_duration_since(episode.start_time),
+                    episode.id
+                );
+
+                if let Some(ref exporter) = self.csv_exporter {
+                    exporter.mark_anomaly_ended(&episode.symbol, R::Config::NAME);
+                }
+            }
+        }
+
+        Readiness::Armed
+    }
+
+    fn explain(&self, data: &SymbolData, features: &FeatureCache) -> Option<Explanation> {
+        Some(self.evaluate(data, features, features.spread()?).explanation())
+    }
+
+    fn force_end_episode(&mut self, symbol: &str, reason: &str) {
+        let Some(episode) = self.tracker.force_end(symbol) else {
+            return;
+        };
+
+        if let Err(e) = self.logger.log_episode(
+            &episode.id,
+            &episode.symbol,
+            episode.start_time,
+            self.tracker.now(),
+            episode.peak_ratio,
+            episode.peak_last_price,
+            episode.peak_mark_price,
+            Some(reason),
+        ) {
+            tracing::error!("Failed to log episode: {:?}", e);
+        }
+        info!("[{}] Episode closed: {} ({}) | Episode: {}", R::LABEL, symbol, reason, episode.id);
+
+        if let Some(ref exporter) = self.csv_exporter {
+            exporter.mark_anomaly_ended(symbol, R::Config::NAME);
+        }
+    }
+
+    fn rename_symbol(&mut self, rename: &SymbolRename) {
+        if let Some(episode_id) = self.tracker.rename(&rename.from, &rename.to, rename.price_factor) {
+            info!("[{}] Episode carried over: {} -> {} | Episode: {}", R::LABEL, rename.from, rename.to, episode_id);
+            if let Some(ref exporter) = self.csv_exporter {
+                exporter.rename_recording(&rename.from, &rename.to, rename.price_factor);
+            }
+        }
+        // Left over only if the new name already had an episode of its own
+        self.force_end_episode(&rename.from, &format!("renamed to {}", rename.to));
+    }
+
+    fn active_episodes(&self) -> usize {
+        self.tracker.active_count()
+    }
+
+    fn has_active_episode(&self, symbol: &str) -> bool {
+        self.tracker.is_active(symbol)
+    }
+}
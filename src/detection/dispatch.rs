@@ -0,0 +1,233 @@
+use crate::detection::{
+    FeatureCache, FeatureParams, InterContractMonitor, LatencyBudget, MarketContext, Pipeline, RatioBaselines,
+    ReadinessBoard, StrategyRegistry, Watchlist,
+};
+use crate::execution::OrderExecutor;
+use crate::models::{ConnectionStatus, MarketEvent, Symbol, SymbolData};
+use dashmap::DashMap;
+use std::sync::Arc;
+use tracing::{info, instrument, warn};
+
+/// Applies one market event to the symbol's data and runs the strategies, pipelines and
+/// executor on it. The live event loop, replays and backtests all go through here, so a
+/// recorded event is evaluated exactly like a live one.
+///
+/// `latency` is checked against the strategies' budgets; offline runs pass None.
+#[instrument(level = "trace", skip_all)]
+#[allow(clippy::too_many_arguments)]
+pub fn handle_market_event(
+    event: MarketEvent,
+    symbol_data: &DashMap<Symbol, SymbolData>,
+    strategies: &mut StrategyRegistry,
+    latency: Option<&LatencyBudget>,
+    readiness: &ReadinessBoard,
+    watchlist: &Watchlist,
+    inter_contract: &mut Option<InterContractMonitor>,
+    market_context: Option<&MarketContext>,
+    feature_params: FeatureParams,
+    executor: Option<&Arc<OrderExecutor>>,
+    pipelines: &mut [Pipeline],
+    baselines: Option<&RatioBaselines>,
+    stale_grace_secs: u64,
+) {
+    match event {
+        MarketEvent::TickerUpdate {
+            symbol,
+            last_price,
+            mark_price,
+            stats_24h,
+            quote,
+            timestamp,
+        } => {
+            if let Some(context) = market_context {
+                context.record(&symbol, last_price, timestamp);
+            }
+
+            // Strategies only read the symbol, so they run on a snapshot taken once the update
+            // is applied and never hold the shard lock
+            let snapshot = symbol_data.get_mut(&*symbol).map(|mut data| {
+                data.update_last_price(last_price, timestamp);
+                if stats_24h.is_some() {
+                    data.stats_24h = stats_24h;
+                }
+                if let Some(quote) = quote {
+                    data.update_quote(quote);
+                }
+
+                if let Some(mark) = mark_price {
+                    data.update_mark_price(mark, timestamp);
+                }
+                watchlist.observe(&mut data);
+                data.clone()
+            });
+
+            if let Some(data) = snapshot {
+                // Halted contracts and excluded symbols keep their data current but are not evaluated
+                if !data.contract_state.is_trading() || baseline_excluded(&symbol, baselines, strategies, pipelines) {
+                    return;
+                }
+
+                // Run all strategies
+                let features = FeatureCache::new(&data, feature_params);
+                readiness.record(&symbol, &strategies.check_prices(&data, &features, latency));
+                for pipeline in pipelines.iter_mut() {
+                    pipeline.check_prices(&data);
+                }
+                manage_exits(executor, &data, &features);
+                anticipate(executor, &data);
+            }
+
+            // Reads the other leg of each pair from the map
+            if let Some(monitor) = inter_contract {
+                monitor.check(&symbol, symbol_data);
+            }
+        }
+        MarketEvent::MarkPriceUpdate {
+            symbol,
+            mark_price,
+            timestamp,
+        } => {
+            let snapshot = symbol_data.get_mut(&*symbol).map(|mut data| {
+                data.update_mark_price(mark_price, timestamp);
+                watchlist.observe(&mut data);
+                data.clone()
+            });
+
+            if let Some(data) = snapshot {
+                // Halted contracts and excluded symbols keep their data current but are not evaluated
+                if !data.contract_state.is_trading() || baseline_excluded(&symbol, baselines, strategies, pipelines) {
+                    return;
+                }
+
+                // Run all strategies
+                let features = FeatureCache::new(&data, feature_params);
+                readiness.record(&symbol, &strategies.check_prices(&data, &features, latency));
+                for pipeline in pipelines.iter_mut() {
+                    pipeline.check_prices(&data);
+                }
+                manage_exits(executor, &data, &features);
+                anticipate(executor, &data);
+            }
+        }
+        MarketEvent::OrderbookUpdate { symbol, orderbook } => {
+            let snapshot = symbol_data.get_mut(&*symbol).map(|mut data| {
+                data.update_orderbook(orderbook);
+                data.clone()
+            });
+
+            if let Some(data) = snapshot {
+                if !data.contract_state.is_trading() || baseline_excluded(&symbol, baselines, strategies, pipelines) {
+                    return;
+                }
+
+                // Run strategies that use orderbook data, and all of them on watched symbols
+                let features = FeatureCache::new(&data, feature_params);
+                readiness.record(&symbol, &strategies.check_orderbook(&data, &features, latency));
+                for pipeline in pipelines.iter_mut() {
+                    pipeline.check_orderbook(&data);
+                }
+                anticipate(executor, &data);
+            }
+        }
+        MarketEvent::TradeUpdate {
+            symbol,
+            volume,
+            timestamp,
+            ..
+        } => {
+            // Trades only feed candle volume; prices keep coming from the ticker
+            if let Some(mut data) = symbol_data.get_mut(&*symbol) {
+                data.update_trade(volume, timestamp);
+            }
+        }
+        MarketEvent::FundingRateUpdate { symbol, rate, .. } => {
+            if let Some(mut data) = symbol_data.get_mut(&*symbol) {
+                data.funding_rate = Some(rate);
+            }
+        }
+        MarketEvent::ConnectionStatus(status) => mark_connection_status(status, symbol_data, stale_grace_secs),
+        MarketEvent::ContractStateChange(change) => {
+            if let Some(mut data) = symbol_data.get_mut(change.symbol.as_str()) {
+                data.contract_state = change.current;
+            }
+            if change.current.is_trading() {
+                return;
+            }
+
+            let reason = format!("contract {}", change.current);
+            strategies.force_end_episode(&change.symbol, &reason);
+            if let Some(monitor) = inter_contract {
+                monitor.force_end_episode(&change.symbol, &reason);
+            }
+            for pipeline in pipelines.iter_mut() {
+                pipeline.force_end_episode(&change.symbol, &reason);
+            }
+        }
+        // The monitor already moved the market data to the new symbol
+        MarketEvent::SymbolRenamed(rename) => {
+            strategies.rename_symbol(&rename);
+            for pipeline in pipelines.iter_mut() {
+                pipeline.rename_symbol(&rename);
+            }
+            // Pairs were matched by symbol at startup and don't follow the new one
+            if let Some(monitor) = inter_contract {
+                monitor.force_end_episode(&rename.from, &format!("renamed to {}", rename.to));
+            }
+        }
+    }
+}
+
+/// Marks every symbol stale while the market stream is down, and keeps it stale for
+/// `stale_grace_secs` after a reconnect while fresh data replaces what was missed
+pub fn mark_connection_status(status: ConnectionStatus, symbol_data: &DashMap<Symbol, SymbolData>, stale_grace_secs: u64) {
+    match status {
+        ConnectionStatus::Disconnected { .. } => {
+            for mut entry in symbol_data.iter_mut() {
+                entry.value_mut().mark_stale();
+            }
+            warn!("Market stream disconnected: {} symbols are stale until it's back", symbol_data.len());
+        }
+        ConnectionStatus::Connected { reconnect: false, .. } => {}
+        ConnectionStatus::Connected { reconnect: true, .. } => {
+            let grace = chrono::Duration::seconds(stale_grace_secs as i64);
+            for mut entry in symbol_data.iter_mut() {
+                entry.value_mut().resume_after(grace);
+            }
+            info!("Market stream reconnected: strategies resume in {}s", stale_grace_secs);
+        }
+    }
+}
+
+/// Steps the exit ladders of the symbol's open positions on a new last or mark price
+fn manage_exits(executor: Option<&Arc<OrderExecutor>>, data: &SymbolData, features: &FeatureCache) {
+    if let (Some(executor), Some(spread)) = (executor, features.spread()) {
+        executor.manage_exits(&data.symbol, spread.last_price, spread.ratio, data.orderbook.as_ref());
+    }
+}
+
+/// Lets the executor place an anticipation order on a watched symbol
+fn anticipate(executor: Option<&Arc<OrderExecutor>>, data: &SymbolData) {
+    if let (Some(executor), Some(watch), Some(last_price)) = (executor, &data.watch, data.current_last_price) {
+        executor.anticipate(&data.symbol, watch.since, last_price, data.orderbook.as_ref());
+    }
+}
+
+/// Whether the symbol's ratio baseline keeps it from the strategies; ends its open episodes
+fn baseline_excluded(
+    symbol: &str,
+    baselines: Option<&RatioBaselines>,
+    strategies: &mut StrategyRegistry,
+    pipelines: &mut [Pipeline],
+) -> bool {
+    if !baselines.is_some_and(|baselines| baselines.is_excluded(symbol)) {
+        return false;
+    }
+    let reason = "ratio baseline off";
+    if strategies.has_active_episode(symbol) {
+        strategies.force_end_episode(symbol, reason);
+    }
+    for pipeline in pipelines.iter_mut().filter(|pipeline| pipeline.has_active_episode(symbol)) {
+        pipeline.force_end_episode(symbol, reason);
+    }
+    true
+}
@@ -1,25 +1,39 @@
+use crate::config::EpisodeEndConfig;
+use crate::detection::ThresholdResolver;
+use crate::utils::{system_clock, SharedClock};
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
+use ulid::Ulid;
+
+/// Unique, time-sortable id (ULID) shared by an episode's logs, recordings, alerts and orders
+pub fn new_episode_id(start_time: DateTime<Utc>) -> String {
+    Ulid::from_datetime(start_time.into()).to_string()
+}
 
 #[derive(Debug, Clone)]
 pub struct Episode {
+    pub id: String,
     pub symbol: String,
     pub start_time: DateTime<Utc>,
     pub peak_ratio: f64,
     pub peak_last_price: f64,
     pub peak_mark_price: f64,
     pub last_cooldown_end: Option<DateTime<Utc>>,
+    /// Since when the end criteria have held without interruption
+    pub ending_since: Option<DateTime<Utc>>,
 }
 
 impl Episode {
-    pub fn new(symbol: String, ratio: f64, last_price: f64, mark_price: f64) -> Self {
+    pub fn new(symbol: String, start_time: DateTime<Utc>, ratio: f64, last_price: f64, mark_price: f64) -> Self {
         Self {
+            id: new_episode_id(start_time),
             symbol,
-            start_time: Utc::now(),
+            start_time,
             peak_ratio: ratio,
             peak_last_price: last_price,
             peak_mark_price: mark_price,
             last_cooldown_end: None,
+            ending_since: None,
         }
     }
 
@@ -34,15 +48,89 @@ impl Episode {
 
 pub struct EpisodeTracker {
     active_episodes: HashMap<String, Episode>,
+    // When each symbol's cooldown after its last episode ends
+    cooldowns: HashMap<String, DateTime<Utc>>,
     cooldown_seconds: u64,
+    end: ThresholdResolver<EpisodeEndConfig>,
+    clock: SharedClock,
 }
 
 impl EpisodeTracker {
     pub fn new(cooldown_seconds: u64) -> Self {
+        Self::with_clock(cooldown_seconds, system_clock())
+    }
+
+    pub fn with_clock(cooldown_seconds: u64, clock: SharedClock) -> Self {
         Self {
             active_episodes: HashMap::new(),
+            cooldowns: HashMap::new(),
             cooldown_seconds,
+            end: ThresholdResolver::new(EpisodeEndConfig::default()),
+            clock,
+        }
+    }
+
+    /// Ends episodes by `end` (per symbol) instead of as soon as the start condition stops holding
+    pub fn with_end_condition(mut self, end: impl Into<ThresholdResolver<EpisodeEndConfig>>) -> Self {
+        self.end = end.into();
+        self
+    }
+
+    pub fn set_clock(&mut self, clock: SharedClock) {
+        self.clock = clock;
+    }
+
+    /// Current time according to the tracker's clock
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    /// Number of episodes currently open
+    pub fn active_count(&self) -> usize {
+        self.active_episodes.len()
+    }
+
+    pub fn is_active(&self, symbol: &str) -> bool {
+        self.active_episodes.contains_key(symbol)
+    }
+
+    /// Id of the symbol's running episode
+    pub fn episode_id(&self, symbol: &str) -> Option<String> {
+        self.active_episodes.get(symbol).map(|e| e.id.clone())
+    }
+
+    /// Ends the symbol's episode regardless of its end criteria (e.g. the contract stopped
+    /// trading); the cooldown applies as usual
+    pub fn force_end(&mut self, symbol: &str) -> Option<Episode> {
+        let episode = self.active_episodes.remove(symbol)?;
+        Some(self.start_cooldown(episode, self.clock.now()))
+    }
+
+    /// Moves the symbol's running episode and cooldown to its new name, with the episode's
+    /// peak prices multiplied by `price_factor`. Returns the moved episode's id; an episode
+    /// already running under the new name is kept and the old one is left in place.
+    pub fn rename(&mut self, from: &str, to: &str, price_factor: f64) -> Option<String> {
+        if let Some(cooldown_end) = self.cooldowns.remove(from) {
+            let until = self.cooldowns.entry(to.to_string()).or_insert(cooldown_end);
+            *until = (*until).max(cooldown_end);
         }
+        if self.active_episodes.contains_key(to) {
+            return None;
+        }
+        let mut episode = self.active_episodes.remove(from)?;
+        episode.symbol = to.to_string();
+        episode.peak_last_price *= price_factor;
+        episode.peak_mark_price *= price_factor;
+        let id = episode.id.clone();
+        self.active_episodes.insert(to.to_string(), episode);
+        Some(id)
+    }
+
+    fn start_cooldown(&mut self, mut episode: Episode, now: DateTime<Utc>) -> Episode {
+        let cooldown_end = now + chrono::Duration::seconds(self.cooldown_seconds as i64);
+        episode.last_cooldown_end = Some(cooldown_end);
+        self.cooldowns.insert(episode.symbol.clone(), cooldown_end);
+        episode
     }
 
     pub fn check_condition(
@@ -56,34 +144,47 @@ impl EpisodeTracker {
         if condition_met {
             if let Some(episode) = self.active_episodes.get_mut(symbol) {
                 // Update existing episode
+                episode.ending_since = None;
                 episode.update_peak(ratio, last_price, mark_price);
                 (None, false)
             } else {
                 // Check if still in cooldown
-                let now = Utc::now();
-                if let Some(last_cooldown) = self.active_episodes
-                    .get(symbol)
-                    .and_then(|e| e.last_cooldown_end)
-                {
-                    if now < last_cooldown {
+                let now = self.clock.now();
+                if let Some(&cooldown_end) = self.cooldowns.get(symbol) {
+                    if now < cooldown_end {
                         return (None, false);
                     }
+                    self.cooldowns.remove(symbol);
                 }
 
                 // Start new episode
-                let episode = Episode::new(symbol.to_string(), ratio, last_price, mark_price);
+                let episode = Episode::new(symbol.to_string(), now, ratio, last_price, mark_price);
                 self.active_episodes.insert(symbol.to_string(), episode);
                 (None, true) // Return true to indicate episode started
             }
         } else {
             // Condition no longer met
-            if let Some(mut episode) = self.active_episodes.remove(symbol) {
-                // End episode and apply cooldown
-                episode.last_cooldown_end = Some(Utc::now() + chrono::Duration::seconds(self.cooldown_seconds as i64));
-                (Some(episode), false)
-            } else {
-                (None, false)
+            let now = self.clock.now();
+            let Some(episode) = self.active_episodes.get_mut(symbol) else {
+                return (None, false);
+            };
+
+            let end = *self.end.get(symbol);
+            let below_end_level = end.ratio_below.is_none_or(|level| ratio < level);
+            if !below_end_level {
+                // Between the end level and the start threshold: the episode keeps running
+                episode.ending_since = None;
+                return (None, false);
             }
+
+            let ending_since = *episode.ending_since.get_or_insert(now);
+            if now - ending_since < chrono::Duration::seconds(end.hold_secs as i64) {
+                return (None, false);
+            }
+
+            // End episode and apply cooldown
+            let episode = self.active_episodes.remove(symbol).expect("episode checked above");
+            (Some(self.start_cooldown(episode, now)), false)
         }
     }
 }
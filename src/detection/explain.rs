@@ -0,0 +1,216 @@
+use crate::detection::Readiness;
+use crate::models::Stats24h;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    AtLeast,
+    AtMost,
+}
+
+/// Outcome of a single threshold comparison
+#[derive(Debug, Clone)]
+pub struct ConditionCheck {
+    pub name: &'static str,
+    pub actual: f64,
+    pub threshold: f64,
+    pub comparison: Comparison,
+    pub passed: bool,
+}
+
+impl ConditionCheck {
+    pub fn at_least(name: &'static str, actual: f64, threshold: f64) -> Self {
+        Self {
+            name,
+            actual,
+            threshold,
+            comparison: Comparison::AtLeast,
+            passed: actual >= threshold,
+        }
+    }
+
+    pub fn at_most(name: &'static str, actual: f64, threshold: f64) -> Self {
+        Self {
+            name,
+            actual,
+            threshold,
+            comparison: Comparison::AtMost,
+            passed: actual <= threshold,
+        }
+    }
+
+    /// How far the actual value is from passing, relative to the threshold (0 when passed)
+    pub fn shortfall_pct(&self) -> f64 {
+        if self.passed || self.threshold == 0.0 {
+            return 0.0;
+        }
+        let gap = match self.comparison {
+            Comparison::AtLeast => self.threshold - self.actual,
+            Comparison::AtMost => self.actual - self.threshold,
+        };
+        gap / self.threshold.abs() * 100.0
+    }
+}
+
+impl fmt::Display for ConditionCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self.comparison {
+            Comparison::AtLeast => ">=",
+            Comparison::AtMost => "<=",
+        };
+        write!(
+            f,
+            "{} {} {} {} {}",
+            if self.passed { "✓" } else { "✗" },
+            self.name,
+            format_value(self.actual),
+            op,
+            format_value(self.threshold)
+        )
+    }
+}
+
+/// Human-readable breakdown of the conditions a strategy evaluated for a symbol
+#[derive(Debug, Clone, Default)]
+pub struct Explanation {
+    pub checks: Vec<ConditionCheck>,
+    /// Conditions that could not be evaluated (e.g. missing history or orderbook)
+    pub unavailable: Vec<&'static str>,
+}
+
+impl Explanation {
+    pub fn push(&mut self, check: ConditionCheck) {
+        self.checks.push(check);
+    }
+
+    pub fn mark_unavailable(&mut self, name: &'static str) {
+        self.unavailable.push(name);
+    }
+
+    /// Whether every condition could be evaluated and passed
+    pub fn passed(&self) -> bool {
+        self.unavailable.is_empty() && self.checks.iter().all(|check| check.passed)
+    }
+
+    /// The actual value of the named condition, if it was evaluated
+    pub fn actual(&self, name: &str) -> Option<f64> {
+        self.checks.iter().find(|check| check.name == name).map(|check| check.actual)
+    }
+
+    /// Adds the minimum 24h volume requirement, if one is configured
+    pub fn push_volume_24h(&mut self, stats: Option<Stats24h>, min_usdt: f64) {
+        if min_usdt <= 0.0 {
+            return;
+        }
+        match stats {
+            Some(stats) => self.push(ConditionCheck::at_least("volume_24h_usdt", stats.volume_usdt, min_usdt)),
+            None => self.mark_unavailable("volume_24h_usdt"),
+        }
+    }
+
+    /// Adds the funding rate limit, if one is configured
+    pub fn push_funding_rate(&mut self, rate: Option<f64>, max_abs_rate: f64) {
+        if max_abs_rate <= 0.0 {
+            return;
+        }
+        match rate {
+            Some(rate) => self.push(ConditionCheck::at_most("abs_funding_rate", rate.abs(), max_abs_rate)),
+            None => self.mark_unavailable("funding_rate"),
+        }
+    }
+
+    /// Adds the minimum open interest increase, if one is configured
+    pub fn push_oi_increase(&mut self, change_pct: Option<f64>, min_pct: f64) {
+        if min_pct <= 0.0 {
+            return;
+        }
+        match change_pct {
+            Some(change_pct) => self.push(ConditionCheck::at_least("oi_increase_pct", change_pct, min_pct)),
+            None => self.mark_unavailable("oi_increase_pct"),
+        }
+    }
+
+    /// Adds the minimum best bid / mark requirement, if one is configured
+    pub fn push_tradable_ratio(&mut self, ratio: Option<f64>, min_ratio: f64) {
+        if min_ratio <= 0.0 {
+            return;
+        }
+        match ratio {
+            Some(ratio) => self.push(ConditionCheck::at_least("tradable_ratio", ratio, min_ratio)),
+            None => self.mark_unavailable("tradable_ratio"),
+        }
+    }
+}
+
+/// What an evaluation means for the symbol's episode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Every condition passed: an episode starts or continues
+    Met,
+    /// A condition failed: a running episode may end
+    NotMet,
+    /// A gate failed or the strategy isn't armed yet: the episode is left as it is
+    Hold,
+}
+
+/// A strategy's evaluation of a symbol at its current prices: the single source both
+/// for acting on the symbol and for explaining why (or why not)
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    pub readiness: Readiness,
+    /// Price floors and data quality, checked before the strategy's own conditions
+    pub gates: Explanation,
+    pub conditions: Explanation,
+}
+
+impl Evaluation {
+    pub fn new(gates: Explanation) -> Self {
+        Self {
+            readiness: Readiness::Armed,
+            gates,
+            conditions: Explanation::default(),
+        }
+    }
+
+    /// Downgrades an armed evaluation to `readiness`, e.g. while a lookback isn't filled yet;
+    /// the first gap reported is kept
+    pub fn require(&mut self, readiness: Readiness) {
+        if self.readiness == Readiness::Armed {
+            self.readiness = readiness;
+        }
+    }
+
+    pub fn verdict(&self) -> Verdict {
+        if self.readiness != Readiness::Armed || !self.gates.passed() {
+            Verdict::Hold
+        } else if self.conditions.passed() {
+            Verdict::Met
+        } else {
+            Verdict::NotMet
+        }
+    }
+
+    /// Gates and conditions together, as shown in alerts and `inspect`
+    pub fn explanation(&self) -> Explanation {
+        let mut explanation = self.gates.clone();
+        explanation.checks.extend(self.conditions.checks.iter().cloned());
+        explanation.unavailable.extend(self.conditions.unavailable.iter().copied());
+        explanation
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = self.checks.iter().map(|c| c.to_string()).collect();
+        parts.extend(self.unavailable.iter().map(|name| format!("? {} n/a", name)));
+        write!(f, "{}", parts.join(" | "))
+    }
+}
+
+fn format_value(value: f64) -> String {
+    if value.abs() >= 1000.0 {
+        format!("{:.0}", value)
+    } else {
+        format!("{:.4}", value)
+    }
+}
@@ -0,0 +1,144 @@
+use crate::config::{AdaptiveBandConfig, Config};
+use crate::detection::{ConditionCheck, Explanation, PriceFloor};
+use crate::models::SymbolData;
+use std::cell::OnceCell;
+
+/// Lookbacks the shared features are computed with, taken from the strategy configs
+#[derive(Debug, Clone, Copy)]
+pub struct FeatureParams {
+    pub spike_lookback_secs: u64,
+    pub baseline_window_secs: u64,
+    pub depth_band_pct: f64,
+    pub adaptive_band: AdaptiveBandConfig,
+    pub max_last_outside_quote_pct: f64,
+    pub max_data_age_ms: u64,
+}
+
+impl FeatureParams {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            spike_lookback_secs: config.strategy2.spike_lookback_secs,
+            baseline_window_secs: config.strategy3.baseline_window_secs,
+            depth_band_pct: config.orderbook.depth_band_pct,
+            adaptive_band: config.orderbook.adaptive_band,
+            max_last_outside_quote_pct: config.orderbook.max_last_outside_quote_pct,
+            max_data_age_ms: config.market_stream.max_data_age_ms,
+        }
+    }
+}
+
+/// Current last/mark prices and the spread between them
+#[derive(Debug, Clone, Copy)]
+pub struct Spread {
+    pub last_price: f64,
+    pub mark_price: f64,
+    pub ratio: f64,
+    pub abs_diff: f64,
+}
+
+/// Orderbook shape around the mid price
+#[derive(Debug, Clone, Copy)]
+pub struct BookFeatures {
+    pub spread_pct: f64,
+    /// Band around mid the depth was measured in, adapted to the symbol's volatility if configured
+    pub band_pct: f64,
+    /// Notional depth within `band_pct` around mid
+    pub depth: f64,
+}
+
+/// Values several strategies derive from the same tick, built once per event by the
+/// dispatcher and shared by every strategy check.
+///
+/// History and orderbook features are computed on first use and then shared, so each scan
+/// runs at most once per tick however many strategies need it.
+pub struct FeatureCache<'a> {
+    data: &'a SymbolData,
+    params: FeatureParams,
+    spread: Option<Spread>,
+    price_at_lookback: OnceCell<Option<f64>>,
+    baseline: OnceCell<Option<(f64, f64)>>,
+    book: OnceCell<Option<BookFeatures>>,
+}
+
+impl<'a> FeatureCache<'a> {
+    pub fn new(data: &'a SymbolData, params: FeatureParams) -> Self {
+        let spread = match (data.current_last_price, data.current_mark_price) {
+            (Some(last_price), Some(mark_price)) => Some(Spread {
+                last_price,
+                mark_price,
+                ratio: last_price / mark_price,
+                abs_diff: last_price - mark_price,
+            }),
+            _ => None,
+        };
+
+        Self {
+            data,
+            params,
+            spread,
+            price_at_lookback: OnceCell::new(),
+            baseline: OnceCell::new(),
+            book: OnceCell::new(),
+        }
+    }
+
+    /// None until both a last and a mark price have been seen
+    pub fn spread(&self) -> Option<Spread> {
+        self.spread
+    }
+
+    /// The checks every strategy passes before its own conditions count: `price_floor`, the
+    /// last price near the best bid/ask (a bad print isn't a real trade) and both prices still
+    /// updating (a frozen ticker makes the ratio meaningless). The data checks are off at 0.
+    pub fn gates(&self, price_floor: &PriceFloor) -> Explanation {
+        let mut gates = Explanation::default();
+        if let Some(spread) = self.spread {
+            price_floor.explain(&self.data.symbol, spread.last_price, &mut gates);
+        }
+
+        let max_outside_pct = self.params.max_last_outside_quote_pct;
+        // Without a fresh quote there's nothing to compare the last price with
+        if let (true, Some(outside_pct)) = (max_outside_pct > 0.0, self.data.last_outside_quote_pct()) {
+            gates.push(ConditionCheck::at_most("last_outside_quote", outside_pct, max_outside_pct));
+        }
+
+        let max_age_ms = self.params.max_data_age_ms;
+        if max_age_ms > 0 {
+            match self.data.price_age_ms() {
+                Some(age_ms) => gates.push(ConditionCheck::at_most("price_age_ms", age_ms as f64, max_age_ms as f64)),
+                None => gates.mark_unavailable("price_age_ms"),
+            }
+        }
+        gates
+    }
+
+    /// Last price `lookback_secs` ago; cached for the configured spike lookback
+    pub fn price_at(&self, lookback_secs: u64) -> Option<f64> {
+        if lookback_secs != self.params.spike_lookback_secs {
+            return self.data.get_price_at(lookback_secs);
+        }
+        *self.price_at_lookback.get_or_init(|| self.data.get_price_at(lookback_secs))
+    }
+
+    /// Average (last, mark) over `window_secs`; cached for the configured baseline window
+    pub fn baseline_prices(&self, window_secs: u64) -> Option<(f64, f64)> {
+        if window_secs != self.params.baseline_window_secs {
+            return self.data.get_baseline_prices(window_secs);
+        }
+        *self.baseline.get_or_init(|| self.data.get_baseline_prices(window_secs))
+    }
+
+    /// None without an orderbook or while one side of it is empty
+    pub fn book(&self) -> Option<BookFeatures> {
+        *self.book.get_or_init(|| {
+            let orderbook = self.data.orderbook.as_ref()?;
+            let mid_price = orderbook.calculate_mid_price()?;
+            let band_pct = self.params.adaptive_band.band_pct(self.params.depth_band_pct, self.data);
+            Some(BookFeatures {
+                spread_pct: orderbook.calculate_spread_pct()?,
+                band_pct,
+                depth: orderbook.calculate_depth_in_band(mid_price, band_pct),
+            })
+        })
+    }
+}
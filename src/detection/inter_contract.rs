@@ -0,0 +1,188 @@
+use crate::alerts::{Alert, AlertSender, SignalSource};
+use crate::config::InterContractConfig;
+use crate::detection::{ConditionCheck, EpisodeTracker, Explanation};
+use crate::models::{ContractPair, PairRegistry, Stats24h, Symbol, SymbolData};
+use crate::utils::{EpisodeLogger, SharedClock};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{error, info};
+
+pub const INTER_CONTRACT_SIGNAL: &str = "inter_contract";
+
+/// Rolling state of one pair's dated/perpetual price ratio
+#[derive(Debug, Clone)]
+struct BasisState {
+    baseline: f64,
+    samples: u64,
+    updated_at: DateTime<Utc>,
+    in_episode: bool,
+}
+
+/// Flags sudden moves in the spread between a perpetual and a dated future on the
+/// same underlying - a distinct signal from the last/mark spread of a single contract.
+///
+/// Dated futures carry a normal basis (contango/backwardation), so the ratio is compared
+/// to its own slow-moving baseline rather than to 1.0.
+pub struct InterContractMonitor {
+    config: InterContractConfig,
+    registry: PairRegistry,
+    basis: HashMap<String, BasisState>,
+    tracker: EpisodeTracker,
+    logger: Arc<EpisodeLogger>,
+    alert_tx: AlertSender,
+}
+
+impl InterContractMonitor {
+    pub fn new(
+        config: InterContractConfig,
+        registry: PairRegistry,
+        cooldown_seconds: u64,
+        logger: Arc<EpisodeLogger>,
+        alert_tx: AlertSender,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            config,
+            registry,
+            basis: HashMap::new(),
+            tracker: EpisodeTracker::with_clock(cooldown_seconds, clock),
+            logger,
+            alert_tx,
+        }
+    }
+
+    /// Re-evaluates every pair `symbol` belongs to. Must not be called while holding a
+    /// guard into `symbol_data`, since the other leg is read from the same map.
+    pub fn check(&mut self, symbol: &str, symbol_data: &DashMap<Symbol, SymbolData>) {
+        let pairs: Vec<ContractPair> = self.registry.pairs_for(symbol).cloned().collect();
+        for pair in pairs {
+            // A halted leg's last price no longer moves with the market
+            let trading = |symbol: &str| symbol_data.get(symbol).and_then(|d| d.contract_state.is_trading().then_some(d));
+            let perpetual = trading(&pair.perpetual).and_then(|d| d.current_last_price);
+            let dated = trading(&pair.dated).and_then(|d| Some((d.current_last_price?, d.stats_24h)));
+            if let (Some(perpetual), Some((dated, dated_stats))) = (perpetual, dated) {
+                if perpetual >= self.config.min_price && dated >= self.config.min_price {
+                    self.check_pair(&pair, perpetual, dated, dated_stats);
+                }
+            }
+        }
+    }
+
+    /// Closes the open episodes of every pair `symbol` belongs to, e.g. when it is halted
+    pub fn force_end_episode(&mut self, symbol: &str, reason: &str) {
+        let pairs: Vec<ContractPair> = self.registry.pairs_for(symbol).cloned().collect();
+        for pair in pairs {
+            let label = pair.label();
+            let Some(episode) = self.tracker.force_end(&label) else {
+                continue;
+            };
+            if let Some(state) = self.basis.get_mut(&label) {
+                state.in_episode = false;
+            }
+
+            if let Err(e) = self.logger.log_episode(
+                &episode.id,
+                &pair.dated,
+                episode.start_time,
+                self.tracker.now(),
+                episode.peak_ratio,
+                episode.peak_last_price,
+                episode.peak_mark_price,
+                Some(reason),
+            ) {
+                error!("Failed to log episode: {:?}", e);
+            }
+            info!(
+                "[InterContract] Basis episode closed: {} vs {} ({}) | Episode: {}",
+                pair.dated, pair.perpetual, reason, episode.id
+            );
+        }
+    }
+
+    fn check_pair(&mut self, pair: &ContractPair, perpetual: f64, dated: f64, dated_stats: Option<Stats24h>) {
+        let now = self.tracker.now();
+        let ratio = dated / perpetual;
+        let label = pair.label();
+
+        let state = self.basis.entry(label.clone()).or_insert(BasisState {
+            baseline: ratio,
+            samples: 0,
+            updated_at: now,
+            in_episode: false,
+        });
+
+        let deviation_pct = (ratio / state.baseline - 1.0).abs() * 100.0;
+        let warmed_up = state.samples >= self.config.min_samples;
+        let condition_met = warmed_up && deviation_pct >= self.config.deviation_pct;
+
+        // The baseline only follows the normal basis, not the anomaly itself
+        if !state.in_episode && !condition_met {
+            let elapsed = (now - state.updated_at).num_milliseconds().max(0) as f64 / 1000.0;
+            let alpha = 1.0 - (-elapsed / self.config.baseline_window_secs.max(1) as f64).exp();
+            state.baseline += alpha * (ratio - state.baseline);
+            state.samples += 1;
+            state.updated_at = now;
+        }
+        let baseline = state.baseline;
+
+        let (episode, started) = self.tracker.check_condition(&label, condition_met, ratio, dated, perpetual);
+        if started {
+            state.in_episode = true;
+            let episode_id = self.tracker.episode_id(&label).unwrap_or_default();
+            info!(
+                "[InterContract] 🚨 BASIS ANOMALY: {} ({}) | Ratio: {:.4} vs baseline {:.4} ({:+.2}%) | Dated: {:.4} | Perp: {:.4} | Episode: {}",
+                pair.dated,
+                pair.underlying,
+                ratio,
+                baseline,
+                (ratio / baseline - 1.0) * 100.0,
+                dated,
+                perpetual,
+                episode_id
+            );
+
+            let mut explanation = Explanation::default();
+            explanation.push(ConditionCheck::at_least("basis_deviation_pct", deviation_pct, self.config.deviation_pct));
+            let alert = Alert {
+                strategy: INTER_CONTRACT_SIGNAL.to_string(),
+                episode_id,
+                symbol: pair.dated.clone(),
+                ratio,
+                last_price: dated,
+                mark_price: perpetual,
+                stats_24h: dated_stats,
+                explanation,
+                market_moves: Vec::new(),
+                timestamp: now,
+                source: SignalSource::Detector,
+            };
+            self.logger.record_trigger(&alert, None);
+            if let Err(e) = self.alert_tx.send(alert.into()) {
+                error!("Failed to queue alert: {:?}", e);
+            }
+        }
+
+        if let Some(episode) = episode {
+            state.in_episode = false;
+            if let Err(e) = self.logger.log_episode(
+                &episode.id,
+                &pair.dated,
+                episode.start_time,
+                now,
+                episode.peak_ratio,
+                episode.peak_last_price,
+                episode.peak_mark_price,
+                None,
+            ) {
+                error!("Failed to log episode: {:?}", e);
+            } else {
+                info!(
+                    "[InterContract] ✅ Basis episode ended: {} vs {} | Peak Ratio: {:.4} | Episode: {}",
+                    pair.dated, pair.perpetual, episode.peak_ratio, episode.id
+                );
+            }
+        }
+    }
+}
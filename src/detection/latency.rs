@@ -0,0 +1,82 @@
+use crate::config::LatencyBudgetConfig;
+use dashmap::DashMap;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Call statistics for one strategy's `check`
+#[derive(Debug, Clone, Default)]
+pub struct CheckLatencyStats {
+    pub calls: u64,
+    pub slow_calls: u64,
+    pub max: Duration,
+    // Slow calls not logged because of the per-strategy warning rate limit
+    suppressed_warnings: u64,
+    last_warning: Option<Instant>,
+}
+
+/// Times strategy checks against a per-call budget and warns about slow ones,
+/// so a pathological symbol can't quietly stall the whole event loop.
+pub struct LatencyBudget {
+    config: LatencyBudgetConfig,
+    stats: DashMap<&'static str, CheckLatencyStats>,
+}
+
+impl LatencyBudget {
+    pub fn new(config: LatencyBudgetConfig) -> Self {
+        Self {
+            config,
+            stats: DashMap::new(),
+        }
+    }
+
+    /// Runs `check`, recording its duration under `strategy`
+    pub fn measure<R, F: FnOnce() -> R>(&self, strategy: &'static str, symbol: &str, check: F) -> R {
+        if !self.config.enabled {
+            return check();
+        }
+
+        let started = Instant::now();
+        let outcome = check();
+        let elapsed = started.elapsed();
+
+        let budget = Duration::from_micros(self.config.budget_us);
+        let mut stats = self.stats.entry(strategy).or_default();
+        stats.calls += 1;
+        stats.max = stats.max.max(elapsed);
+
+        if elapsed <= budget {
+            return outcome;
+        }
+
+        stats.slow_calls += 1;
+        let warn_interval = Duration::from_secs(self.config.warn_interval_secs);
+        let can_warn = stats.last_warning.is_none_or(|last| last.elapsed() >= warn_interval);
+
+        if can_warn {
+            warn!(
+                "[Latency] {} check for {} took {}µs (budget {}µs){}",
+                strategy,
+                symbol,
+                elapsed.as_micros(),
+                self.config.budget_us,
+                if stats.suppressed_warnings > 0 {
+                    format!(" | {} more slow checks since last warning", stats.suppressed_warnings)
+                } else {
+                    String::new()
+                }
+            );
+            stats.last_warning = Some(Instant::now());
+            stats.suppressed_warnings = 0;
+        } else {
+            stats.suppressed_warnings += 1;
+        }
+        outcome
+    }
+
+    /// Snapshot of per-strategy statistics, sorted by strategy name
+    pub fn snapshot(&self) -> Vec<(&'static str, CheckLatencyStats)> {
+        let mut stats: Vec<_> = self.stats.iter().map(|e| (*e.key(), e.value().clone())).collect();
+        stats.sort_by_key(|(name, _)| *name);
+        stats
+    }
+}
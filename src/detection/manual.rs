@@ -0,0 +1,197 @@
+use crate::alerts::{Alert, AlertSender, SignalSource};
+use crate::config::ManualSignalConfig;
+use crate::detection::{Episode, EpisodeTracker, Explanation};
+use crate::execution::OrderExecutor;
+use crate::export::CsvExporter;
+use crate::models::{Symbol, SymbolData};
+use crate::utils::{EpisodeLogger, SharedClock};
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{error, info};
+
+pub const MANUAL_SIGNAL: &str = "manual";
+
+/// Episodes opened by hand through the control socket, e.g. by someone reacting to news
+/// the detector can't see. A signal starts a `manual` episode on the symbol at its current
+/// prices and from there it is handled like a detection: alert (tagged `source=manual`),
+/// episode log, chart recording and, when asked for and allowed, an entry.
+///
+/// The episode stays open for `duration_secs`, following the symbol's peak ratio, and a
+/// further signal for the same symbol extends it instead of starting another one.
+pub struct ManualSignals {
+    config: ManualSignalConfig,
+    tracker: EpisodeTracker,
+    // Symbol -> when its episode ends
+    expiries: HashMap<String, DateTime<Utc>>,
+    logger: Arc<EpisodeLogger>,
+    csv_exporter: Option<Arc<CsvExporter>>,
+    alert_tx: AlertSender,
+    executor: Option<Arc<OrderExecutor>>,
+}
+
+impl ManualSignals {
+    pub fn new(
+        config: ManualSignalConfig,
+        logger: Arc<EpisodeLogger>,
+        csv_exporter: Option<Arc<CsvExporter>>,
+        alert_tx: AlertSender,
+        executor: Option<Arc<OrderExecutor>>,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            config,
+            // Every signal is deliberate, so none is held back by a cooldown
+            tracker: EpisodeTracker::with_clock(0, clock),
+            expiries: HashMap::new(),
+            logger,
+            csv_exporter,
+            alert_tx,
+            executor,
+        }
+    }
+
+    pub fn active_episodes(&self) -> usize {
+        self.tracker.active_count()
+    }
+
+    /// Opens a manual episode on the symbol, or extends its open one; the response for the
+    /// control socket
+    pub fn inject(&mut self, data: &SymbolData, note: Option<String>, execute: bool) -> Result<String> {
+        let (Some(last_price), Some(mark_price)) = (data.current_last_price, data.current_mark_price) else {
+            bail!("{} has no last and mark price yet", data.symbol);
+        };
+        if !data.contract_state.is_trading() {
+            bail!("{} is not trading ({})", data.symbol, data.contract_state);
+        }
+        let executor = match (execute, &self.executor) {
+            (false, _) => None,
+            (true, _) if !self.config.allow_execution => {
+                bail!("--execute needs [manual_signals] allow_execution = true")
+            }
+            (true, None) => bail!("--execute needs [execution] enabled"),
+            (true, Some(executor)) => Some(executor.clone()),
+        };
+
+        let ratio = last_price / mark_price;
+        let now = self.tracker.now();
+        let until = now + Duration::seconds(self.config.duration_secs.max(1) as i64);
+        self.expiries.insert(data.symbol.to_string(), until);
+        let (_, started) = self.tracker.check_condition(&data.symbol, true, ratio, last_price, mark_price);
+        let episode_id = self.tracker.episode_id(&data.symbol).unwrap_or_default();
+        if !started {
+            info!(
+                "[Manual] Signal for {} extends its open episode until {} | Episode: {}",
+                data.symbol,
+                until.format("%H:%M:%S"),
+                episode_id
+            );
+            return Ok(format!(
+                "{}: manual episode extended until {} | Episode: {}\n",
+                data.symbol,
+                until.format("%H:%M:%S UTC"),
+                episode_id
+            ));
+        }
+
+        let source = SignalSource::Manual { note };
+        info!(
+            "[Manual] 🚨 MANUAL SIGNAL: {} | Ratio: {:.4} | Last: {:.4} | Mark: {:.4} | Source: {} | Episode: {}",
+            data.symbol, ratio, last_price, mark_price, source, episode_id
+        );
+        let alert = Alert {
+            strategy: MANUAL_SIGNAL.to_string(),
+            episode_id: episode_id.clone(),
+            symbol: data.symbol.to_string(),
+            ratio,
+            last_price,
+            mark_price,
+            stats_24h: data.stats_24h,
+            explanation: Explanation::default(),
+            market_moves: Vec::new(),
+            timestamp: now,
+            source,
+        };
+        self.logger.record_trigger(&alert, data.orderbook.as_ref());
+        if let Err(e) = self.alert_tx.send(alert.into()) {
+            error!("Failed to queue alert: {:?}", e);
+        }
+
+        if let Some(ref exporter) = self.csv_exporter {
+            let pre_buffer_candles = data.candle_buffer.get_pre_buffer_candles(exporter.pre_buffer_secs(&self.config.recording));
+            exporter.start_recording(&data.symbol, MANUAL_SIGNAL, &episode_id, pre_buffer_candles, self.config.recording);
+        }
+
+        if let Some(ref executor) = executor {
+            executor.enter_position(&data.symbol, MANUAL_SIGNAL, last_price, data.orderbook.as_ref(), &episode_id);
+        }
+
+        Ok(format!(
+            "{}: manual episode started | Ratio: {:.4} | Last: {:.8} | Mark: {:.8} | Open until {}{} | Episode: {}\n",
+            data.symbol,
+            ratio,
+            last_price,
+            mark_price,
+            until.format("%H:%M:%S UTC"),
+            if executor.is_some() { " | Entry submitted" } else { "" },
+            episode_id
+        ))
+    }
+
+    /// Follows the open episodes' peak ratio and ends those whose time is up, or whose
+    /// symbol stopped trading or is no longer monitored
+    pub fn poll(&mut self, symbol_data: &DashMap<Symbol, SymbolData>) {
+        let now = self.tracker.now();
+        let symbols: Vec<String> = self.expiries.keys().cloned().collect();
+        for symbol in symbols {
+            let ended = match symbol_data.get(symbol.as_str()) {
+                None => self.tracker.force_end(&symbol).map(|episode| (episode, Some("symbol no longer monitored".to_string()))),
+                Some(data) if !data.contract_state.is_trading() => {
+                    let reason = format!("contract {}", data.contract_state);
+                    self.tracker.force_end(&symbol).map(|episode| (episode, Some(reason)))
+                }
+                Some(data) => {
+                    let (Some(last_price), Some(mark_price)) = (data.current_last_price, data.current_mark_price) else {
+                        continue;
+                    };
+                    let open = self.expiries.get(&symbol).is_some_and(|until| now < *until);
+                    let (episode, _) = self.tracker.check_condition(&symbol, open, last_price / mark_price, last_price, mark_price);
+                    episode.map(|episode| (episode, None))
+                }
+            };
+            if !self.tracker.is_active(&symbol) {
+                self.expiries.remove(&symbol);
+            }
+            if let Some((episode, reason)) = ended {
+                self.end(episode, reason.as_deref());
+            }
+        }
+    }
+
+    fn end(&self, episode: Episode, reason: Option<&str>) {
+        if let Err(e) = self.logger.log_episode(
+            &episode.id,
+            &episode.symbol,
+            episode.start_time,
+            self.tracker.now(),
+            episode.peak_ratio,
+            episode.peak_last_price,
+            episode.peak_mark_price,
+            reason,
+        ) {
+            error!("Failed to log episode: {:?}", e);
+        }
+        info!(
+            "[Manual] ✅ Episode ended: {}{} | Peak Ratio: {:.4} | Episode: {}",
+            episode.symbol,
+            reason.map(|reason| format!(" ({})", reason)).unwrap_or_default(),
+            episode.peak_ratio,
+            episode.id
+        );
+        if let Some(ref exporter) = self.csv_exporter {
+            exporter.mark_anomaly_ended(&episode.symbol, MANUAL_SIGNAL);
+        }
+    }
+}
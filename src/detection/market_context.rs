@@ -0,0 +1,104 @@
+use crate::config::MarketContextConfig;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Mutex;
+
+/// Price change of a reference symbol (e.g. BTC_USDT) over an episode's window
+#[derive(Debug, Clone)]
+pub struct ReferenceMove {
+    pub symbol: String,
+    pub change_pct: f64,
+}
+
+impl fmt::Display for ReferenceMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:+.2}%", self.symbol, self.change_pct)
+    }
+}
+
+/// Formats moves as `BTC_USDT +0.52% ETH_USDT -0.10%`
+pub fn format_moves(moves: &[ReferenceMove]) -> String {
+    moves.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+// (sample time, last price), oldest first
+type PriceSeries = VecDeque<(DateTime<Utc>, f64)>;
+
+/// Price series of the reference symbols, used to tell isolated pumps from market-wide moves.
+///
+/// Sampled once per second for `history_mins`, independently of the shorter per-symbol
+/// history, so whole episodes can be compared against the market.
+pub struct MarketContext {
+    config: MarketContextConfig,
+    prices: Mutex<HashMap<String, PriceSeries>>,
+}
+
+impl MarketContext {
+    pub fn new(config: MarketContextConfig) -> Self {
+        Self {
+            config,
+            prices: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn reference_symbols(&self) -> &[String] {
+        &self.config.reference_symbols
+    }
+
+    /// Records a last price if `symbol` is a reference symbol
+    pub fn record(&self, symbol: &str, price: f64, timestamp: DateTime<Utc>) {
+        if !self.config.reference_symbols.iter().any(|s| s == symbol) {
+            return;
+        }
+
+        let mut prices = self.prices.lock().unwrap();
+        let series = prices.entry(symbol.to_string()).or_default();
+        if series.back().is_some_and(|(t, _)| timestamp - *t < Duration::seconds(1)) {
+            return;
+        }
+        series.push_back((timestamp, price));
+
+        let cutoff = timestamp - Duration::minutes(self.config.history_mins as i64);
+        while series.front().is_some_and(|(t, _)| *t < cutoff) {
+            series.pop_front();
+        }
+    }
+
+    /// Change of each reference symbol between `from` and `to`; symbols without a price
+    /// at or before `from` are left out
+    pub fn moves(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<ReferenceMove> {
+        let prices = self.prices.lock().unwrap();
+        self.config
+            .reference_symbols
+            .iter()
+            .filter_map(|symbol| {
+                let series = prices.get(symbol)?;
+                let before = price_at(series, from)?;
+                let after = price_at(series, to)?;
+                Some(ReferenceMove {
+                    symbol: symbol.clone(),
+                    change_pct: (after / before - 1.0) * 100.0,
+                })
+            })
+            .collect()
+    }
+
+    /// Moves over the `lookback_secs` leading up to a trigger at `at`
+    pub fn moves_before(&self, at: DateTime<Utc>) -> Vec<ReferenceMove> {
+        self.moves(at - Duration::seconds(self.config.lookback_secs as i64), at)
+    }
+
+    /// Whether any reference symbol moved at least `suppress_above_pct` (either direction)
+    pub fn is_market_wide(&self, moves: &[ReferenceMove]) -> bool {
+        match self.config.suppress_above_pct {
+            Some(threshold) => moves.iter().any(|m| m.change_pct.abs() >= threshold),
+            None => false,
+        }
+    }
+}
+
+/// Price of the newest sample at or before `at`
+fn price_at(series: &PriceSeries, at: DateTime<Utc>) -> Option<f64> {
+    series.iter().rev().find(|(t, _)| *t <= at).map(|(_, price)| *price)
+}
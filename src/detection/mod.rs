@@ -1,13 +1,51 @@
+pub mod baseline;
+pub mod correlation;
+pub mod detector;
+pub mod dispatch;
 pub mod episode;
+pub mod explain;
+pub mod features;
+pub mod inter_contract;
+pub mod latency;
+pub mod manual;
+pub mod market_context;
+pub mod patterns;
+pub mod pipeline;
+pub mod precision;
+pub mod price_floor;
+pub mod registry;
 pub mod strategy1;
 pub mod strategy2;
 pub mod strategy3;
 pub mod strategy4;
 pub mod strategy5;
+pub mod thresholds;
+pub mod throttle;
+pub mod warmup;
+pub mod watchlist;
 
+pub use baseline::*;
+pub use correlation::*;
+pub use detector::*;
+pub use dispatch::*;
 pub use episode::*;
+pub use explain::*;
+pub use features::*;
+pub use inter_contract::*;
+pub use latency::*;
+pub use manual::*;
+pub use market_context::*;
+pub use patterns::*;
+pub use pipeline::*;
+pub use precision::*;
+pub use price_floor::*;
+pub use registry::*;
 pub use strategy1::*;
 pub use strategy2::*;
 pub use strategy3::*;
 pub use strategy4::*;
 pub use strategy5::*;
+pub use thresholds::*;
+pub use throttle::*;
+pub use warmup::*;
+pub use watchlist::*;
@@ -0,0 +1,71 @@
+use crate::config::PatternConfig;
+use crate::detection::{ConditionCheck, Explanation};
+use crate::models::Candle;
+
+/// Number of consecutive up candles (close > open) ending at the most recent candle
+pub fn consecutive_up_candles<'a>(candles: impl DoubleEndedIterator<Item = &'a Candle>) -> usize {
+    candles.rev().take_while(|c| c.close > c.open).count()
+}
+
+/// Body size relative to the full high-low range (1.0 = no wicks)
+pub fn body_ratio(candle: &Candle) -> Option<f64> {
+    let range = candle.high - candle.low;
+    (range > 0.0).then(|| (candle.close - candle.open).abs() / range)
+}
+
+/// Upper wick relative to the full range; a long upper wick means the highs were rejected
+pub fn upper_wick_ratio(candle: &Candle) -> Option<f64> {
+    let range = candle.high - candle.low;
+    (range > 0.0).then(|| (candle.high - candle.open.max(candle.close)) / range)
+}
+
+/// Evaluates the configured candle-shape conditions on completed last-price candles.
+///
+/// Conditions left unset in config are skipped; returns whether every configured one passed.
+pub fn check_patterns<'a, I>(config: &PatternConfig, candles: I, explanation: &mut Explanation) -> bool
+where
+    I: DoubleEndedIterator<Item = &'a Candle> + Clone,
+{
+    let mut passed = true;
+    let latest = candles.clone().next_back();
+
+    if let Some(min_up) = config.min_consecutive_up {
+        let check = ConditionCheck::at_least(
+            "pattern.consecutive_up",
+            consecutive_up_candles(candles) as f64,
+            min_up as f64,
+        );
+        passed &= check.passed;
+        explanation.push(check);
+    }
+
+    if let Some(min_body) = config.min_body_ratio {
+        match latest.and_then(body_ratio) {
+            Some(ratio) => {
+                let check = ConditionCheck::at_least("pattern.body_ratio", ratio, min_body);
+                passed &= check.passed;
+                explanation.push(check);
+            }
+            None => {
+                passed = false;
+                explanation.mark_unavailable("pattern.body_ratio");
+            }
+        }
+    }
+
+    if let Some(max_wick) = config.max_upper_wick_ratio {
+        match latest.and_then(upper_wick_ratio) {
+            Some(ratio) => {
+                let check = ConditionCheck::at_most("pattern.upper_wick", ratio, max_wick);
+                passed &= check.passed;
+                explanation.push(check);
+            }
+            None => {
+                passed = false;
+                explanation.mark_unavailable("pattern.upper_wick");
+            }
+        }
+    }
+
+    passed
+}
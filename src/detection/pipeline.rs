@@ -0,0 +1,106 @@
+use crate::alerts::{AlertSender, Notification};
+use crate::config::{Config, PipelineConfig};
+use crate::detection::{build_strategies, FeatureCache, FeatureParams, StrategyDeps, StrategyRegistry, StrategyThresholds};
+use crate::export::CsvExporter;
+use crate::models::{ContractCatalog, SymbolData, SymbolRename};
+use crate::utils::{EpisodeLogger, SharedClock};
+use anyhow::Result;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// A named set of strategies with its own thresholds, episode logs and recordings, run
+/// next to the top-level strategies on the same market data (`[pipelines.<name>]`).
+///
+/// Its alerts go through the shared dispatcher with the strategy named `<pipeline>/<strategy>`.
+pub struct Pipeline {
+    feature_params: FeatureParams,
+    strategies: StrategyRegistry,
+}
+
+impl Pipeline {
+    /// `logger` opens the episode log of each of the pipeline's strategies
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        name: &str,
+        pipeline: &PipelineConfig,
+        base: &Config,
+        logger: impl FnMut(&'static str) -> Result<Arc<EpisodeLogger>>,
+        csv_exporter: Option<Arc<CsvExporter>>,
+        alert_tx: AlertSender,
+        clock: SharedClock,
+        contracts: Arc<ContractCatalog>,
+    ) -> Result<Self> {
+        let deps = StrategyDeps {
+            alert_tx: pipeline_alert_sender(name, alert_tx),
+            clock,
+            csv_exporter,
+            contracts: Some(contracts),
+            executor: None,
+        };
+        let strategies = build_strategies(
+            base,
+            &StrategyThresholds::of_pipeline(pipeline),
+            deps,
+            logger,
+            |_| Arc::default(),
+        )?;
+
+        // Lookback features follow this pipeline's strategy2/3 windows
+        let feature_params = FeatureParams {
+            spike_lookback_secs: pipeline.strategy2.spike_lookback_secs,
+            baseline_window_secs: pipeline.strategy3.baseline_window_secs,
+            ..FeatureParams::from_config(base)
+        };
+
+        Ok(Self {
+            feature_params,
+            strategies,
+        })
+    }
+
+    /// Runs every strategy on a new last or mark price
+    pub fn check_prices(&mut self, data: &SymbolData) {
+        let features = FeatureCache::new(data, self.feature_params);
+        self.strategies.check_prices(data, &features, None);
+    }
+
+    /// Runs the orderbook strategies on a book update, and all of them on watched symbols
+    pub fn check_orderbook(&mut self, data: &SymbolData) {
+        let features = FeatureCache::new(data, self.feature_params);
+        self.strategies.check_orderbook(data, &features, None);
+    }
+
+    pub fn force_end_episode(&mut self, symbol: &str, reason: &str) {
+        self.strategies.force_end_episode(symbol, reason);
+    }
+
+    pub fn rename_symbol(&mut self, rename: &SymbolRename) {
+        self.strategies.rename_symbol(rename);
+    }
+
+    pub fn active_episodes(&self) -> usize {
+        self.strategies.active_episodes()
+    }
+
+    pub fn has_active_episode(&self, symbol: &str) -> bool {
+        self.strategies.has_active_episode(symbol)
+    }
+}
+
+/// Forwards the pipeline's notifications to `alert_tx`, prefixing alert strategy names
+/// with the pipeline
+fn pipeline_alert_sender(name: &str, alert_tx: AlertSender) -> AlertSender {
+    let (tx, mut rx) = mpsc::unbounded_channel::<Notification>();
+    let prefix = name.to_string();
+    tokio::spawn(async move {
+        while let Some(mut notification) = rx.recv().await {
+            if let Notification::Alert(ref mut alert) = notification {
+                alert.strategy = format!("{}/{}", prefix, alert.strategy);
+            }
+            if alert_tx.send(notification).is_err() {
+                return;
+            }
+        }
+    });
+    tx
+}
@@ -0,0 +1,215 @@
+use crate::alerts::{Alert, AlertDigest, KillSwitchEvent, MissedDigest, Notifier};
+use crate::config::PrecisionConfig;
+use crate::models::{ContractStateChange, NewListing, Symbol, SymbolData};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::debug;
+
+/// A trigger whose outcome is not known yet
+#[derive(Debug, Clone)]
+struct PendingTrigger {
+    strategy: String,
+    symbol: String,
+    triggered_at: DateTime<Utc>,
+    trigger_price: f64,
+    max_price: f64,
+}
+
+/// Rolling hit rate of one strategy's most recent resolved triggers
+#[derive(Debug, Clone, Default)]
+pub struct PrecisionStats {
+    /// Resolved triggers in the window (at most `window`)
+    pub resolved: usize,
+    pub successes: usize,
+    /// Triggers still inside their follow-up horizon
+    pub pending: usize,
+    /// Triggers resolved since start, including those that fell out of the window
+    pub total_resolved: u64,
+}
+
+impl PrecisionStats {
+    pub fn precision(&self) -> Option<f64> {
+        (self.resolved > 0).then(|| self.successes as f64 / self.resolved as f64)
+    }
+}
+
+#[derive(Default)]
+struct TrackerState {
+    pending: Vec<PendingTrigger>,
+    outcomes: BTreeMap<String, VecDeque<bool>>,
+    total_resolved: BTreeMap<String, u64>,
+}
+
+/// Scores strategies automatically from what the price did after they fired.
+///
+/// Receives alerts like any other notifier; a trigger counts as successful when the
+/// last price rises a further `target_rise_pct` within `horizon_secs`.
+#[derive(Clone)]
+pub struct PrecisionTracker {
+    config: PrecisionConfig,
+    symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+    state: Arc<Mutex<TrackerState>>,
+}
+
+impl PrecisionTracker {
+    pub fn new(config: PrecisionConfig, symbol_data: Arc<DashMap<Symbol, SymbolData>>) -> Self {
+        Self {
+            config,
+            symbol_data,
+            state: Arc::new(Mutex::new(TrackerState::default())),
+        }
+    }
+
+    pub fn record_trigger(&self, strategy: &str, symbol: &str, triggered_at: DateTime<Utc>, trigger_price: f64) {
+        self.state.lock().unwrap().pending.push(PendingTrigger {
+            strategy: strategy.to_string(),
+            symbol: symbol.to_string(),
+            triggered_at,
+            trigger_price,
+            max_price: trigger_price,
+        });
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Duration::from_millis(self.config.check_interval_ms));
+        loop {
+            interval.tick().await;
+            self.resolve(Utc::now());
+        }
+    }
+
+    /// Updates the running maximum of each pending trigger and resolves those that
+    /// hit the target or ran out of time
+    fn resolve(&self, now: DateTime<Utc>) {
+        let horizon = chrono::Duration::seconds(self.config.horizon_secs as i64);
+        let target = 1.0 + self.config.target_rise_pct / 100.0;
+
+        let mut state = self.state.lock().unwrap();
+        let mut resolved = Vec::new();
+
+        state.pending.retain_mut(|trigger| {
+            let deadline = trigger.triggered_at + horizon;
+            if let Some(data) = self.symbol_data.get(trigger.symbol.as_str()) {
+                // Completed candle highs catch spikes between two checks
+                let since_ms = trigger.triggered_at.timestamp_millis();
+                let deadline_ms = deadline.timestamp_millis();
+                let candle_high = data
+                    .candle_buffer
+                    .completed_last_price_candles()
+                    .rev()
+                    .take_while(|c| c.timestamp_ms >= since_ms)
+                    .filter(|c| c.timestamp_ms <= deadline_ms)
+                    .map(|c| c.high)
+                    .fold(f64::MIN, f64::max);
+                let current = if now <= deadline { data.current_last_price } else { None };
+                trigger.max_price = trigger.max_price.max(candle_high).max(current.unwrap_or(f64::MIN));
+            }
+
+            let success = trigger.max_price >= trigger.trigger_price * target;
+            if success || now > deadline {
+                resolved.push((trigger.strategy.clone(), trigger.symbol.clone(), success));
+                false
+            } else {
+                true
+            }
+        });
+
+        for (strategy, symbol, success) in resolved {
+            debug!(
+                "[Precision] {} trigger on {} resolved as {}",
+                strategy,
+                symbol,
+                if success { "success" } else { "miss" }
+            );
+            *state.total_resolved.entry(strategy.clone()).or_default() += 1;
+            let outcomes = state.outcomes.entry(strategy).or_default();
+            outcomes.push_back(success);
+            while outcomes.len() > self.config.window {
+                outcomes.pop_front();
+            }
+        }
+    }
+
+    /// Per-strategy rolling precision, sorted by strategy name
+    pub fn snapshot(&self) -> Vec<(String, PrecisionStats)> {
+        let state = self.state.lock().unwrap();
+        let mut stats: BTreeMap<String, PrecisionStats> = BTreeMap::new();
+
+        for (strategy, outcomes) in &state.outcomes {
+            let entry = stats.entry(strategy.clone()).or_default();
+            entry.resolved = outcomes.len();
+            entry.successes = outcomes.iter().filter(|s| **s).count();
+            entry.total_resolved = state.total_resolved.get(strategy).copied().unwrap_or_default();
+        }
+        for trigger in &state.pending {
+            stats.entry(trigger.strategy.clone()).or_default().pending += 1;
+        }
+
+        stats.into_iter().collect()
+    }
+
+    /// One line per strategy, as shown in status logs and the web report
+    pub fn summary_lines(&self) -> Vec<String> {
+        self.snapshot()
+            .into_iter()
+            .map(|(strategy, stats)| {
+                format!(
+                    "{} precision: {} ({}/{} of last {} triggers rose {}% within {}s) | resolved total: {} | pending: {}",
+                    strategy,
+                    stats
+                        .precision()
+                        .map(|p| format!("{:.1}%", p * 100.0))
+                        .unwrap_or_else(|| "n/a".to_string()),
+                    stats.successes,
+                    stats.resolved,
+                    self.config.window,
+                    self.config.target_rise_pct,
+                    self.config.horizon_secs,
+                    stats.total_resolved,
+                    stats.pending
+                )
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Notifier for PrecisionTracker {
+    fn name(&self) -> &str {
+        "precision"
+    }
+
+    fn is_internal(&self) -> bool {
+        true
+    }
+
+    async fn notify(&self, alert: &Alert) -> Result<()> {
+        self.record_trigger(&alert.strategy, &alert.symbol, alert.timestamp, alert.last_price);
+        Ok(())
+    }
+
+    async fn notify_digest(&self, _digest: &MissedDigest) -> Result<()> {
+        Ok(())
+    }
+
+    async fn notify_alert_digest(&self, _digest: &AlertDigest) -> Result<()> {
+        Ok(())
+    }
+
+    async fn notify_kill_switch(&self, _event: &KillSwitchEvent) -> Result<()> {
+        Ok(())
+    }
+
+    async fn notify_contract_state(&self, _change: &ContractStateChange) -> Result<()> {
+        Ok(())
+    }
+
+    async fn notify_new_listing(&self, _listing: &NewListing) -> Result<()> {
+        Ok(())
+    }
+}
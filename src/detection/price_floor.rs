@@ -0,0 +1,59 @@
+use crate::detection::{ConditionCheck, Explanation};
+use crate::models::{ContractCatalog, ContractMeta};
+use std::sync::Arc;
+
+/// Minimum price a strategy considers, per symbol.
+///
+/// An absolute `min_price` means different things for a coin at $0.00001 and one at
+/// $50,000, so floors relative to the contract can be set as well: a minimum notional per
+/// contract and a minimum price in ticks (coarse ticks distort the last/mark ratio).
+/// A floor of 0 is off. Symbols without metadata don't qualify while a relative floor is set.
+#[derive(Clone)]
+pub struct PriceFloor {
+    min_price: f64,
+    min_contract_notional_usdt: f64,
+    min_price_ticks: f64,
+    contracts: Option<Arc<ContractCatalog>>,
+}
+
+impl PriceFloor {
+    pub fn new(min_price: f64, min_contract_notional_usdt: f64, min_price_ticks: f64) -> Self {
+        Self {
+            min_price,
+            min_contract_notional_usdt,
+            min_price_ticks,
+            contracts: None,
+        }
+    }
+
+    pub fn set_contracts(&mut self, contracts: Arc<ContractCatalog>) {
+        self.contracts = Some(contracts);
+    }
+
+    fn meta(&self, symbol: &str) -> Option<&ContractMeta> {
+        self.contracts.as_ref()?.get(symbol)
+    }
+
+    /// Adds a check per configured floor; `last_price` clears the floor when they all pass
+    pub fn explain(&self, symbol: &str, last_price: f64, explanation: &mut Explanation) {
+        explanation.push(ConditionCheck::at_least("price", last_price, self.min_price));
+
+        let meta = self.meta(symbol);
+        if self.min_contract_notional_usdt > 0.0 {
+            match meta.and_then(|m| m.contract_notional(last_price)) {
+                Some(notional) => explanation.push(ConditionCheck::at_least(
+                    "contract_notional_usdt",
+                    notional,
+                    self.min_contract_notional_usdt,
+                )),
+                None => explanation.mark_unavailable("contract_notional_usdt"),
+            }
+        }
+        if self.min_price_ticks > 0.0 {
+            match meta.and_then(|m| m.price_in_ticks(last_price)) {
+                Some(ticks) => explanation.push(ConditionCheck::at_least("price_ticks", ticks, self.min_price_ticks)),
+                None => explanation.mark_unavailable("price_ticks"),
+            }
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use crate::alerts::AlertSender;
+use crate::config::Config;
+use crate::detection::{
+    Detector, Explanation, FeatureCache, LatencyBudget, Readiness, Strategy1, Strategy2, Strategy3, Strategy4, Strategy5,
+    StrategyRules, StrategySection, StrategyThresholds, StrategyThrottle, ThresholdResolver, WarmupGap,
+};
+use crate::execution::OrderExecutor;
+use crate::export::CsvExporter;
+use crate::models::{ContractCatalog, SymbolData, SymbolRename};
+use crate::utils::{EpisodeLogger, SharedClock};
+use anyhow::Result;
+use std::sync::Arc;
+
+/// A detection strategy driven by the event loop.
+///
+/// Implementations keep their own episode tracking, logging, recording and alerting;
+/// the loop only decides which strategies to run on an event. The built-in strategies
+/// are `Detector`s over their `StrategyRules`.
+pub trait Strategy: Send {
+    /// Name used in alerts, logs, routes and the status report, e.g. `strategy1`
+    fn name(&self) -> &'static str;
+
+    fn enabled(&self) -> bool;
+
+    /// Whether every orderbook update is a reason to re-check, not only price updates
+    fn uses_orderbook(&self) -> bool {
+        false
+    }
+
+    fn check(&mut self, data: &SymbolData, features: &FeatureCache) -> Readiness;
+
+    /// Each gate and condition of the strategy against the symbol's current prices, as
+    /// evaluated by `check`
+    fn explain(&self, data: &SymbolData, features: &FeatureCache) -> Option<Explanation>;
+
+    /// Closes the symbol's open episode early, e.g. because the contract stopped trading
+    fn force_end_episode(&mut self, symbol: &str, reason: &str);
+
+    /// Carries the symbol's open episode over to the name its contract was renamed to
+    fn rename_symbol(&mut self, rename: &SymbolRename);
+
+    fn active_episodes(&self) -> usize;
+
+    fn has_active_episode(&self, symbol: &str) -> bool;
+}
+
+/// The strategies the event loop runs, in registration order
+#[derive(Default)]
+pub struct StrategyRegistry {
+    strategies: Vec<Box<dyn Strategy>>,
+}
+
+impl StrategyRegistry {
+    /// Adds a strategy; it runs after the ones registered before it
+    pub fn register(mut self, strategy: impl Strategy + 'static) -> Self {
+        self.strategies.push(Box::new(strategy));
+        self
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.strategies.iter().map(|s| s.name()).collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Strategy> {
+        self.strategies.iter().map(|s| s.as_ref())
+    }
+
+    /// Runs every strategy on a new last or mark price, timed against `latency` when given
+    pub fn check_prices(
+        &mut self,
+        data: &SymbolData,
+        features: &FeatureCache,
+        latency: Option<&LatencyBudget>,
+    ) -> Vec<(&'static str, Readiness)> {
+        self.check_where(data, features, latency, |_| true)
+    }
+
+    /// Runs the orderbook strategies on a book update; watched symbols are re-checked by
+    /// every strategy
+    pub fn check_orderbook(
+        &mut self,
+        data: &SymbolData,
+        features: &FeatureCache,
+        latency: Option<&LatencyBudget>,
+    ) -> Vec<(&'static str, Readiness)> {
+        let watched = data.watch.is_some();
+        self.check_where(data, features, latency, |strategy| watched || strategy.uses_orderbook())
+    }
+
+    fn check_where(
+        &mut self,
+        data: &SymbolData,
+        features: &FeatureCache,
+        latency: Option<&LatencyBudget>,
+        select: impl Fn(&dyn Strategy) -> bool,
+    ) -> Vec<(&'static str, Readiness)> {
+        // Prices missed while the stream was down would look like sudden moves
+        if data.is_stale() {
+            return self
+                .strategies
+                .iter()
+                .filter(|strategy| select(strategy.as_ref()))
+                .map(|strategy| (strategy.name(), Readiness::Warming(WarmupGap::Resync)))
+                .collect();
+        }
+        self.strategies
+            .iter_mut()
+            .filter(|strategy| select(strategy.as_ref()))
+            .map(|strategy| {
+                let name = strategy.name();
+                let readiness = match latency {
+                    Some(latency) => latency.measure(name, &data.symbol, || strategy.check(data, features)),
+                    None => strategy.check(data, features),
+                };
+                (name, readiness)
+            })
+            .collect()
+    }
+
+    pub fn force_end_episode(&mut self, symbol: &str, reason: &str) {
+        for strategy in &mut self.strategies {
+            strategy.force_end_episode(symbol, reason);
+        }
+    }
+
+    pub fn rename_symbol(&mut self, rename: &SymbolRename) {
+        for strategy in &mut self.strategies {
+            strategy.rename_symbol(rename);
+        }
+    }
+
+    pub fn active_episodes(&self) -> usize {
+        self.strategies.iter().map(|s| s.active_episodes()).sum()
+    }
+
+    /// Whether any strategy has an episode running on `symbol`
+    pub fn has_active_episode(&self, symbol: &str) -> bool {
+        self.strategies.iter().any(|s| s.has_active_episode(symbol))
+    }
+}
+
+/// What the strategies of one run share besides their thresholds
+pub struct StrategyDeps {
+    pub alert_tx: AlertSender,
+    pub clock: SharedClock,
+    pub csv_exporter: Option<Arc<CsvExporter>>,
+    /// Resolves the contract-relative price floors; without it only `min_price` applies
+    pub contracts: Option<Arc<ContractCatalog>>,
+    /// Strategy5 enters a position on every new episode when set
+    pub executor: Option<Arc<OrderExecutor>>,
+}
+
+impl StrategyDeps {
+    fn detector<R: StrategyRules>(
+        &self,
+        rules: R,
+        thresholds: ThresholdResolver<R::Config>,
+        cooldown_seconds: u64,
+        logger: &mut dyn FnMut(&'static str) -> Result<Arc<EpisodeLogger>>,
+        throttle: &mut dyn FnMut(&'static str) -> Arc<StrategyThrottle>,
+    ) -> Result<Detector<R>> {
+        let name = R::Config::NAME;
+        let detector = Detector::new(
+            rules,
+            thresholds,
+            cooldown_seconds,
+            logger(name)?,
+            self.csv_exporter.clone(),
+            self.alert_tx.clone(),
+        )
+        .with_clock(self.clock.clone())
+        .with_throttle(throttle(name));
+        Ok(match self.contracts {
+            Some(ref contracts) => detector.with_contracts(contracts.clone()),
+            None => detector,
+        })
+    }
+}
+
+/// Strategies 1-5 in the order the event loop runs them, with `config`'s cooldown and
+/// orderbook settings. `logger` opens each strategy's episode log and `throttle` hands out
+/// its trigger throttle.
+pub fn build_strategies(
+    config: &Config,
+    thresholds: &StrategyThresholds,
+    deps: StrategyDeps,
+    mut logger: impl FnMut(&'static str) -> Result<Arc<EpisodeLogger>>,
+    mut throttle: impl FnMut(&'static str) -> Arc<StrategyThrottle>,
+) -> Result<StrategyRegistry> {
+    let cooldown = config.cooldowns.per_symbol_seconds;
+    let strategy4 = Strategy4::new(config.orderbook.clone());
+    let strategy5 = Strategy5::new(
+        thresholds.strategy1.clone(),
+        thresholds.strategy2.clone(),
+        thresholds.strategy3.clone(),
+        thresholds.strategy4.clone(),
+        config.orderbook.clone(),
+    );
+
+    Ok(StrategyRegistry::default()
+        .register(deps.detector(Strategy1, thresholds.strategy1.clone(), cooldown, &mut logger, &mut throttle)?)
+        .register(deps.detector(Strategy2, thresholds.strategy2.clone(), cooldown, &mut logger, &mut throttle)?)
+        .register(deps.detector(Strategy3, thresholds.strategy3.clone(), cooldown, &mut logger, &mut throttle)?)
+        .register(deps.detector(strategy4, thresholds.strategy4.clone(), cooldown, &mut logger, &mut throttle)?)
+        .register(
+            deps.detector(strategy5, thresholds.strategy5.clone(), cooldown, &mut logger, &mut throttle)?
+                .with_executor(deps.executor.clone()),
+        ))
+}
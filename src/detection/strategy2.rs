@@ -1,111 +1,43 @@
 use crate::config::Strategy2Config;
-use crate::detection::EpisodeTracker;
-use crate::export::CsvExporter;
+use crate::detection::{history_readiness, ConditionCheck, Evaluation, FeatureCache, Spread, StrategyRules, StrategyThrottle};
 use crate::models::SymbolData;
-use crate::utils::EpisodeLogger;
-use std::sync::Arc;
-use tracing::info;
 
-pub struct Strategy2 {
-    config: Strategy2Config,
-    tracker: EpisodeTracker,
-    logger: Arc<EpisodeLogger>,
-    csv_exporter: Option<Arc<CsvExporter>>,
-    pre_buffer_secs: i64,
-}
-
-impl Strategy2 {
-    pub fn new(
-        config: Strategy2Config,
-        cooldown_seconds: u64,
-        logger: Arc<EpisodeLogger>,
-        csv_exporter: Option<Arc<CsvExporter>>,
-        pre_buffer_secs: i64,
-    ) -> Self {
-        Self {
-            config,
-            tracker: EpisodeTracker::new(cooldown_seconds),
-            logger,
-            csv_exporter,
-            pre_buffer_secs,
+/// A spread together with a sudden rise of the last price over a short lookback
+pub struct Strategy2;
+
+impl StrategyRules for Strategy2 {
+    type Config = Strategy2Config;
+    const LABEL: &'static str = "Strategy2";
+
+    fn evaluate(
+        &self,
+        config: &Strategy2Config,
+        evaluation: &mut Evaluation,
+        data: &SymbolData,
+        features: &FeatureCache,
+        spread: Spread,
+        throttle: &StrategyThrottle,
+    ) {
+        // Spike lookback must be fully covered before the strategy is armed
+        evaluation.require(history_readiness(data, config.spike_lookback_secs));
+
+        let conditions = &mut evaluation.conditions;
+        conditions.push(ConditionCheck::at_least("ratio", spread.ratio, data.ratio_threshold(throttle.ratio_threshold(config.spread_ratio_min))));
+        match features.price_at(config.spike_lookback_secs) {
+            Some(old_price) => conditions.push(ConditionCheck::at_least(
+                "spike",
+                spread.last_price / old_price,
+                throttle.ratio_threshold(config.spike_ratio_min),
+            )),
+            None => conditions.mark_unavailable("spike"),
         }
+        conditions.push_volume_24h(data.stats_24h, config.min_volume_24h_usdt);
+        conditions.push_funding_rate(data.funding_rate, config.max_abs_funding_rate);
+        conditions.push_oi_increase(data.open_interest_change_pct(config.oi_lookback_secs), config.min_oi_increase_pct);
+        conditions.push_tradable_ratio(data.tradable_ratio(), config.min_tradable_ratio);
     }
 
-    pub fn check(&mut self, data: &SymbolData) {
-        if !self.config.enabled {
-            return;
-        }
-
-        let (last_price, mark_price) = match (data.current_last_price, data.current_mark_price) {
-            (Some(l), Some(m)) => (l, m),
-            _ => return,
-        };
-
-        if last_price < self.config.min_price {
-            return;
-        }
-
-        let ratio = last_price / mark_price;
-
-        // Check base spread condition
-        if ratio < self.config.spread_ratio_min {
-            // Condition not met, check for episode end
-            self.tracker.check_condition(&data.symbol, false, ratio, last_price, mark_price);
-            return;
-        }
-
-        // Check spike condition
-        let historical_price = data.get_price_at(self.config.spike_lookback_secs);
-        let spike_ratio = match historical_price {
-            Some(old_price) => last_price / old_price,
-            None => {
-                // Not enough history yet
-                return;
-            }
-        };
-
-        let condition_met = spike_ratio >= self.config.spike_ratio_min;
-
-        let (episode_opt, started) = self.tracker.check_condition(
-            &data.symbol,
-            condition_met,
-            ratio,
-            last_price,
-            mark_price,
-        );
-
-        if started {
-            info!(
-                "[Strategy2] 🚨 ANOMALY DETECTED: {} | Ratio: {:.4} | Spike: {:.4}x",
-                data.symbol, ratio, spike_ratio
-            );
-
-            if let Some(ref exporter) = self.csv_exporter {
-                let pre_buffer_candles = data.candle_buffer.get_pre_buffer_candles(self.pre_buffer_secs);
-                exporter.start_recording(&data.symbol, "strategy2", pre_buffer_candles);
-            }
-        }
-
-        if let Some(episode) = episode_opt {
-            if let Err(e) = self.logger.log_episode(
-                &episode.symbol,
-                episode.start_time,
-                chrono::Utc::now(),
-                episode.peak_ratio,
-                episode.peak_last_price,
-                episode.peak_mark_price,
-            ) {
-                tracing::error!("Failed to log episode: {:?}", e);
-            } else {
-                info!(
-                    "[Strategy2] ✅ Episode ended: {} | Peak Ratio: {:.4}",
-                    episode.symbol, episode.peak_ratio
-                );
-
-                if let Some(ref exporter) = self.csv_exporter {
-                    exporter.mark_anomaly_ended(&episode.symbol, "strategy2");
-                }
-            }
-        }
+    fn describe_trigger(&self, evaluation: &Evaluation, _features: &FeatureCache, _spread: Spread) -> String {
+        format!("Spike: {:.4}x", evaluation.conditions.actual("spike").unwrap_or_default())
     }
 }
@@ -1,118 +1,50 @@
 use crate::config::Strategy3Config;
-use crate::detection::EpisodeTracker;
-use crate::export::CsvExporter;
+use crate::detection::{history_readiness, ConditionCheck, Evaluation, FeatureCache, Spread, StrategyRules, StrategyThrottle};
 use crate::models::SymbolData;
-use crate::utils::EpisodeLogger;
-use std::sync::Arc;
-use tracing::info;
 
-pub struct Strategy3 {
-    config: Strategy3Config,
-    tracker: EpisodeTracker,
-    logger: Arc<EpisodeLogger>,
-    csv_exporter: Option<Arc<CsvExporter>>,
-    pre_buffer_secs: i64,
-}
-
-impl Strategy3 {
-    pub fn new(
-        config: Strategy3Config,
-        cooldown_seconds: u64,
-        logger: Arc<EpisodeLogger>,
-        csv_exporter: Option<Arc<CsvExporter>>,
-        pre_buffer_secs: i64,
-    ) -> Self {
-        Self {
-            config,
-            tracker: EpisodeTracker::new(cooldown_seconds),
-            logger,
-            csv_exporter,
-            pre_buffer_secs,
-        }
-    }
-
-    pub fn check(&mut self, data: &SymbolData) {
-        if !self.config.enabled {
-            return;
-        }
-
-        let (last_price, mark_price) = match (data.current_last_price, data.current_mark_price) {
-            (Some(l), Some(m)) => (l, m),
-            _ => return,
-        };
-
-        if last_price < self.config.min_price {
-            return;
-        }
-
-        let ratio = last_price / mark_price;
-
-        // Check base spread condition
-        if ratio < self.config.spread_ratio_min {
-            self.tracker.check_condition(&data.symbol, false, ratio, last_price, mark_price);
-            return;
-        }
-
-        // Get baseline averages
-        let (baseline_last, baseline_mark) = match data.get_baseline_prices(self.config.baseline_window_secs) {
-            Some(prices) => prices,
-            None => {
-                // Not enough history yet
-                return;
-            }
-        };
-
-        // Check pump vs baseline
-        let pump_ratio = last_price / baseline_last;
-        if pump_ratio < self.config.pump_vs_baseline_min {
-            self.tracker.check_condition(&data.symbol, false, ratio, last_price, mark_price);
-            return;
-        }
-
-        // Check mark stability
-        let mark_deviation = (mark_price / baseline_mark - 1.0).abs();
-        let condition_met = mark_deviation <= self.config.mark_stability_max;
-
-        let (episode_opt, started) = self.tracker.check_condition(
-            &data.symbol,
-            condition_met,
-            ratio,
-            last_price,
-            mark_price,
-        );
-
-        if started {
-            info!(
-                "[Strategy3] 🚨 ANOMALY DETECTED: {} | Ratio: {:.4} | Pump: {:.2}x baseline",
-                data.symbol, ratio, last_price / baseline_last
-            );
-
-            if let Some(ref exporter) = self.csv_exporter {
-                let pre_buffer_candles = data.candle_buffer.get_pre_buffer_candles(self.pre_buffer_secs);
-                exporter.start_recording(&data.symbol, "strategy3", pre_buffer_candles);
+/// A last price pumped above its baseline while the mark price stays near its own
+pub struct Strategy3;
+
+impl StrategyRules for Strategy3 {
+    type Config = Strategy3Config;
+    const LABEL: &'static str = "Strategy3";
+
+    fn evaluate(
+        &self,
+        config: &Strategy3Config,
+        evaluation: &mut Evaluation,
+        data: &SymbolData,
+        features: &FeatureCache,
+        spread: Spread,
+        throttle: &StrategyThrottle,
+    ) {
+        // The baseline is only meaningful once the whole window is filled
+        evaluation.require(history_readiness(data, config.baseline_window_secs));
+
+        let conditions = &mut evaluation.conditions;
+        conditions.push(ConditionCheck::at_least("ratio", spread.ratio, data.ratio_threshold(throttle.ratio_threshold(config.spread_ratio_min))));
+        match features.baseline_prices(config.baseline_window_secs) {
+            Some((baseline_last, baseline_mark)) => {
+                conditions.push(ConditionCheck::at_least(
+                    "pump_vs_baseline",
+                    spread.last_price / baseline_last,
+                    throttle.ratio_threshold(config.pump_vs_baseline_min),
+                ));
+                conditions.push(ConditionCheck::at_most(
+                    "mark_deviation",
+                    (spread.mark_price / baseline_mark - 1.0).abs(),
+                    config.mark_stability_max,
+                ));
             }
+            None => conditions.mark_unavailable("baseline"),
         }
+        conditions.push_volume_24h(data.stats_24h, config.min_volume_24h_usdt);
+        conditions.push_funding_rate(data.funding_rate, config.max_abs_funding_rate);
+        conditions.push_oi_increase(data.open_interest_change_pct(config.oi_lookback_secs), config.min_oi_increase_pct);
+        conditions.push_tradable_ratio(data.tradable_ratio(), config.min_tradable_ratio);
+    }
 
-        if let Some(episode) = episode_opt {
-            if let Err(e) = self.logger.log_episode(
-                &episode.symbol,
-                episode.start_time,
-                chrono::Utc::now(),
-                episode.peak_ratio,
-                episode.peak_last_price,
-                episode.peak_mark_price,
-            ) {
-                tracing::error!("Failed to log episode: {:?}", e);
-            } else {
-                info!(
-                    "[Strategy3] ✅ Episode ended: {} | Peak Ratio: {:.4}",
-                    episode.symbol, episode.peak_ratio
-                );
-
-                if let Some(ref exporter) = self.csv_exporter {
-                    exporter.mark_anomaly_ended(&episode.symbol, "strategy3");
-                }
-            }
-        }
+    fn describe_trigger(&self, evaluation: &Evaluation, _features: &FeatureCache, _spread: Spread) -> String {
+        format!("Pump: {:.2}x baseline", evaluation.conditions.actual("pump_vs_baseline").unwrap_or_default())
     }
 }
@@ -1,136 +1,56 @@
 use crate::config::{OrderbookConfig, Strategy4Config};
-use crate::detection::EpisodeTracker;
-use crate::export::CsvExporter;
+use crate::detection::{ConditionCheck, Evaluation, FeatureCache, Readiness, Spread, StrategyRules, StrategyThrottle, WarmupGap};
 use crate::models::SymbolData;
-use crate::utils::EpisodeLogger;
-use std::sync::Arc;
-use tracing::info;
 
+/// A spread while the orderbook is tight and thick around the price
 pub struct Strategy4 {
-    config: Strategy4Config,
     orderbook_config: OrderbookConfig,
-    tracker: EpisodeTracker,
-    logger: Arc<EpisodeLogger>,
-    csv_exporter: Option<Arc<CsvExporter>>,
-    pre_buffer_secs: i64,
 }
 
 impl Strategy4 {
-    pub fn new(
-        config: Strategy4Config,
-        orderbook_config: OrderbookConfig,
-        cooldown_seconds: u64,
-        logger: Arc<EpisodeLogger>,
-        csv_exporter: Option<Arc<CsvExporter>>,
-        pre_buffer_secs: i64,
-    ) -> Self {
-        Self {
-            config,
-            orderbook_config,
-            tracker: EpisodeTracker::new(cooldown_seconds),
-            logger,
-            csv_exporter,
-            pre_buffer_secs,
-        }
+    pub fn new(orderbook_config: OrderbookConfig) -> Self {
+        Self { orderbook_config }
     }
+}
 
-    pub fn check(&mut self, data: &SymbolData) {
-        if !self.config.enabled {
-            return;
-        }
-
-        let (last_price, mark_price) = match (data.current_last_price, data.current_mark_price) {
-            (Some(l), Some(m)) => (l, m),
-            _ => return,
-        };
-
-        if last_price < self.config.min_price {
-            return;
-        }
-
-        let ratio = last_price / mark_price;
-        let abs_diff = last_price - mark_price;
-
-        // Check base spread conditions (like Strategy1)
-        if ratio < self.config.spread_ratio_min || abs_diff < self.config.min_abs_diff {
-            self.tracker.check_condition(&data.symbol, false, ratio, last_price, mark_price);
-            return;
-        }
-
-        // Check orderbook conditions
-        let orderbook = match &data.orderbook {
-            Some(ob) => ob,
-            None => {
-                // No orderbook data yet
-                return;
+impl StrategyRules for Strategy4 {
+    type Config = Strategy4Config;
+    const LABEL: &'static str = "Strategy4";
+    const USES_ORDERBOOK: bool = true;
+
+    fn evaluate(
+        &self,
+        config: &Strategy4Config,
+        evaluation: &mut Evaluation,
+        data: &SymbolData,
+        features: &FeatureCache,
+        spread: Spread,
+        throttle: &StrategyThrottle,
+    ) {
+        let conditions = &mut evaluation.conditions;
+        conditions.push(ConditionCheck::at_least("ratio", spread.ratio, data.ratio_threshold(throttle.ratio_threshold(config.spread_ratio_min))));
+        conditions.push(ConditionCheck::at_least("abs_diff", spread.abs_diff, config.min_abs_diff));
+        match features.book() {
+            Some(book) => {
+                conditions.push(ConditionCheck::at_most("book_spread", book.spread_pct, self.orderbook_config.max_spread_pct));
+                conditions.push(ConditionCheck::at_least("depth_usdt", book.depth, self.orderbook_config.min_thick_depth_usdt));
             }
-        };
-
-        // Calculate mid price
-        let mid_price = match orderbook.calculate_mid_price() {
-            Some(mid) => mid,
-            None => return,
-        };
-
-        // Check spread
-        let spread_pct = match orderbook.calculate_spread_pct() {
-            Some(spread) => spread,
-            None => return,
-        };
-
-        if spread_pct > self.orderbook_config.max_spread_pct {
-            self.tracker.check_condition(&data.symbol, false, ratio, last_price, mark_price);
-            return;
-        }
-
-        // Check depth in band
-        let depth = orderbook.calculate_depth_in_band(
-            mid_price,
-            self.orderbook_config.depth_band_pct,
-        );
-
-        let condition_met = depth >= self.orderbook_config.min_thick_depth_usdt;
-
-        let (episode_opt, started) = self.tracker.check_condition(
-            &data.symbol,
-            condition_met,
-            ratio,
-            last_price,
-            mark_price,
-        );
-
-        if started {
-            info!(
-                "[Strategy4] 🚨 ANOMALY DETECTED: {} | Ratio: {:.4} | Thick Book: ${:.0}",
-                data.symbol, ratio, depth
-            );
-
-            if let Some(ref exporter) = self.csv_exporter {
-                let pre_buffer_candles = data.candle_buffer.get_pre_buffer_candles(self.pre_buffer_secs);
-                exporter.start_recording(&data.symbol, "strategy4", pre_buffer_candles);
+            None => {
+                conditions.mark_unavailable("orderbook");
+                // No usable orderbook yet
+                evaluation.require(Readiness::Warming(WarmupGap::Orderbook));
             }
         }
 
-        if let Some(episode) = episode_opt {
-            if let Err(e) = self.logger.log_episode(
-                &episode.symbol,
-                episode.start_time,
-                chrono::Utc::now(),
-                episode.peak_ratio,
-                episode.peak_last_price,
-                episode.peak_mark_price,
-            ) {
-                tracing::error!("Failed to log episode: {:?}", e);
-            } else {
-                info!(
-                    "[Strategy4] ✅ Episode ended: {} | Peak Ratio: {:.4}",
-                    episode.symbol, episode.peak_ratio
-                );
+        let conditions = &mut evaluation.conditions;
+        conditions.push_volume_24h(data.stats_24h, config.min_volume_24h_usdt);
+        conditions.push_funding_rate(data.funding_rate, config.max_abs_funding_rate);
+        conditions.push_oi_increase(data.open_interest_change_pct(config.oi_lookback_secs), config.min_oi_increase_pct);
+    }
 
-                if let Some(ref exporter) = self.csv_exporter {
-                    exporter.mark_anomaly_ended(&episode.symbol, "strategy4");
-                }
-            }
-        }
+    fn describe_trigger(&self, _evaluation: &Evaluation, features: &FeatureCache, _spread: Spread) -> String {
+        // Conditions are only met with a usable book
+        let (depth, band_pct) = features.book().map_or((0.0, 0.0), |book| (book.depth, book.band_pct));
+        format!("Thick Book: ${:.0} (±{:.2}%)", depth, band_pct * 100.0)
     }
 }
@@ -1,195 +1,128 @@
 use crate::config::{OrderbookConfig, Strategy1Config, Strategy2Config, Strategy3Config, Strategy4Config, Strategy5Config};
-use crate::detection::EpisodeTracker;
-use crate::export::CsvExporter;
+use crate::detection::{
+    check_patterns, history_readiness, ConditionCheck, Evaluation, FeatureCache, Readiness, Spread, StrategyRules, StrategyThrottle,
+    ThresholdResolver, WarmupGap,
+};
 use crate::models::SymbolData;
-use crate::utils::EpisodeLogger;
-use std::sync::Arc;
-use tracing::info;
 
+/// The conditions of strategies 1-4 at once, plus optional candle patterns
 pub struct Strategy5 {
-    config: Strategy5Config,
-    strategy1_config: Strategy1Config,
-    strategy2_config: Strategy2Config,
-    strategy3_config: Strategy3Config,
-    strategy4_config: Strategy4Config,
+    strategy1: ThresholdResolver<Strategy1Config>,
+    strategy2: ThresholdResolver<Strategy2Config>,
+    strategy3: ThresholdResolver<Strategy3Config>,
+    strategy4: ThresholdResolver<Strategy4Config>,
     orderbook_config: OrderbookConfig,
-    tracker: EpisodeTracker,
-    logger: Arc<EpisodeLogger>,
-    csv_exporter: Option<Arc<CsvExporter>>,
-    pre_buffer_secs: i64,
 }
 
 impl Strategy5 {
     pub fn new(
-        config: Strategy5Config,
-        strategy1_config: Strategy1Config,
-        strategy2_config: Strategy2Config,
-        strategy3_config: Strategy3Config,
-        strategy4_config: Strategy4Config,
+        strategy1: impl Into<ThresholdResolver<Strategy1Config>>,
+        strategy2: impl Into<ThresholdResolver<Strategy2Config>>,
+        strategy3: impl Into<ThresholdResolver<Strategy3Config>>,
+        strategy4: impl Into<ThresholdResolver<Strategy4Config>>,
         orderbook_config: OrderbookConfig,
-        cooldown_seconds: u64,
-        logger: Arc<EpisodeLogger>,
-        csv_exporter: Option<Arc<CsvExporter>>,
-        pre_buffer_secs: i64,
     ) -> Self {
         Self {
-            config,
-            strategy1_config,
-            strategy2_config,
-            strategy3_config,
-            strategy4_config,
+            strategy1: strategy1.into(),
+            strategy2: strategy2.into(),
+            strategy3: strategy3.into(),
+            strategy4: strategy4.into(),
             orderbook_config,
-            tracker: EpisodeTracker::new(cooldown_seconds),
-            logger,
-            csv_exporter,
-            pre_buffer_secs,
         }
     }
+}
 
-    pub fn check(&mut self, data: &SymbolData) {
-        if !self.config.enabled {
-            return;
-        }
-
-        let (last_price, mark_price) = match (data.current_last_price, data.current_mark_price) {
-            (Some(l), Some(m)) => (l, m),
-            _ => return,
-        };
-
-        if last_price < self.config.min_price {
-            return;
-        }
-
-        let ratio = last_price / mark_price;
-
-        // Check all 4 strategy conditions
+impl StrategyRules for Strategy5 {
+    type Config = Strategy5Config;
+    const LABEL: &'static str = "Strategy5";
+    const USES_ORDERBOOK: bool = true;
+    const HEADLINE: &'static str = "🔥 CRITICAL ANOMALY";
+
+    /// Every condition of strategies 1-4 at the symbol's thresholds for them
+    fn evaluate(
+        &self,
+        config: &Strategy5Config,
+        evaluation: &mut Evaluation,
+        data: &SymbolData,
+        features: &FeatureCache,
+        spread: Spread,
+        throttle: &StrategyThrottle,
+    ) {
+        let strategy1 = self.strategy1.get(&data.symbol);
+        let strategy2 = self.strategy2.get(&data.symbol);
+        let strategy3 = self.strategy3.get(&data.symbol);
+        let strategy4 = self.strategy4.get(&data.symbol);
+        let Spread { last_price, mark_price, ratio, abs_diff } = spread;
+
+        // Needs the strategy2 lookback and strategy3 baseline filled, and a book unless orderbooks are off
+        evaluation.require(history_readiness(data, strategy2.spike_lookback_secs.max(strategy3.baseline_window_secs)));
+        let conditions = &mut evaluation.conditions;
 
         // Condition 1: Basic spread (Strategy 1)
-        let abs_diff = last_price - mark_price;
-        let condition1 = ratio >= self.strategy1_config.spread_ratio_min
-            && abs_diff >= self.strategy1_config.min_abs_diff;
-
-        if !condition1 {
-            self.tracker.check_condition(&data.symbol, false, ratio, last_price, mark_price);
-            return;
-        }
+        conditions.push(ConditionCheck::at_least("s1.ratio", ratio, data.ratio_threshold(throttle.ratio_threshold(strategy1.spread_ratio_min))));
+        conditions.push(ConditionCheck::at_least("s1.abs_diff", abs_diff, strategy1.min_abs_diff));
 
         // Condition 2: Spike detection (Strategy 2)
-        let historical_price = data.get_price_at(self.strategy2_config.spike_lookback_secs);
-        let spike_ratio = match historical_price {
-            Some(old_price) => last_price / old_price,
-            None => {
-                // Not enough history yet
-                return;
-            }
-        };
-
-        let condition2 = ratio >= self.strategy2_config.spread_ratio_min
-            && spike_ratio >= self.strategy2_config.spike_ratio_min;
-
-        if !condition2 {
-            self.tracker.check_condition(&data.symbol, false, ratio, last_price, mark_price);
-            return;
+        conditions.push(ConditionCheck::at_least("s2.ratio", ratio, data.ratio_threshold(throttle.ratio_threshold(strategy2.spread_ratio_min))));
+        match features.price_at(strategy2.spike_lookback_secs) {
+            Some(old_price) => conditions.push(ConditionCheck::at_least(
+                "s2.spike",
+                last_price / old_price,
+                throttle.ratio_threshold(strategy2.spike_ratio_min),
+            )),
+            None => conditions.mark_unavailable("s2.spike"),
         }
 
         // Condition 3: Baseline stability (Strategy 3)
-        let (baseline_last, baseline_mark) = match data.get_baseline_prices(self.strategy3_config.baseline_window_secs) {
-            Some(prices) => prices,
-            None => {
-                // Not enough history yet
-                return;
-            }
-        };
-
-        let pump_ratio = last_price / baseline_last;
-        let mark_deviation = (mark_price / baseline_mark - 1.0).abs();
-
-        let condition3 = ratio >= self.strategy3_config.spread_ratio_min
-            && pump_ratio >= self.strategy3_config.pump_vs_baseline_min
-            && mark_deviation <= self.strategy3_config.mark_stability_max;
-
-        if !condition3 {
-            self.tracker.check_condition(&data.symbol, false, ratio, last_price, mark_price);
-            return;
-        }
-
-        // Condition 4: Thick orderbook (Strategy 4)
-        let orderbook = match &data.orderbook {
-            Some(ob) => ob,
-            None => {
-                // No orderbook data yet
-                return;
+        conditions.push(ConditionCheck::at_least("s3.ratio", ratio, data.ratio_threshold(throttle.ratio_threshold(strategy3.spread_ratio_min))));
+        match features.baseline_prices(strategy3.baseline_window_secs) {
+            Some((baseline_last, baseline_mark)) => {
+                conditions.push(ConditionCheck::at_least(
+                    "s3.pump_vs_baseline",
+                    last_price / baseline_last,
+                    throttle.ratio_threshold(strategy3.pump_vs_baseline_min),
+                ));
+                conditions.push(ConditionCheck::at_most(
+                    "s3.mark_deviation",
+                    (mark_price / baseline_mark - 1.0).abs(),
+                    strategy3.mark_stability_max,
+                ));
             }
-        };
-
-        let mid_price = match orderbook.calculate_mid_price() {
-            Some(mid) => mid,
-            None => return,
-        };
-
-        let spread_pct = match orderbook.calculate_spread_pct() {
-            Some(spread) => spread,
-            None => return,
-        };
-
-        if spread_pct > self.orderbook_config.max_spread_pct {
-            self.tracker.check_condition(&data.symbol, false, ratio, last_price, mark_price);
-            return;
+            None => conditions.mark_unavailable("s3.baseline"),
         }
 
-        let depth = orderbook.calculate_depth_in_band(
-            mid_price,
-            self.orderbook_config.depth_band_pct,
-        );
-
-        let condition4 = ratio >= self.strategy4_config.spread_ratio_min
-            && abs_diff >= self.strategy4_config.min_abs_diff
-            && depth >= self.orderbook_config.min_thick_depth_usdt;
-
-        // ALL 4 conditions must be met
-        let all_conditions_met = condition1 && condition2 && condition3 && condition4;
-
-        let (episode_opt, started) = self.tracker.check_condition(
-            &data.symbol,
-            all_conditions_met,
-            ratio,
-            last_price,
-            mark_price,
-        );
-
-        if started {
-            info!(
-                "[Strategy5] 🔥 CRITICAL ANOMALY: {} | Ratio: {:.4} | ALL 4 CONDITIONS MET | Spike: {:.2}x | Pump: {:.2}x | Depth: ${:.0}",
-                data.symbol, ratio, spike_ratio, pump_ratio, depth
-            );
-
-            if let Some(ref exporter) = self.csv_exporter {
-                let pre_buffer_candles = data.candle_buffer.get_pre_buffer_candles(self.pre_buffer_secs);
-                exporter.start_recording(&data.symbol, "strategy5", pre_buffer_candles);
+        // Condition 4: Thick orderbook (Strategy 4); only its spread thresholds when orderbooks are disabled
+        conditions.push(ConditionCheck::at_least("s4.ratio", ratio, data.ratio_threshold(throttle.ratio_threshold(strategy4.spread_ratio_min))));
+        conditions.push(ConditionCheck::at_least("s4.abs_diff", abs_diff, strategy4.min_abs_diff));
+        if self.orderbook_config.enabled {
+            match features.book() {
+                Some(book) => {
+                    conditions.push(ConditionCheck::at_most("s4.book_spread", book.spread_pct, self.orderbook_config.max_spread_pct));
+                    conditions.push(ConditionCheck::at_least("s4.depth_usdt", book.depth, self.orderbook_config.min_thick_depth_usdt));
+                }
+                None => {
+                    conditions.mark_unavailable("s4.orderbook");
+                    // No usable orderbook yet
+                    evaluation.require(Readiness::Warming(WarmupGap::Orderbook));
+                }
             }
         }
 
-        if let Some(episode) = episode_opt {
-            if let Err(e) = self.logger.log_episode(
-                &episode.symbol,
-                episode.start_time,
-                chrono::Utc::now(),
-                episode.peak_ratio,
-                episode.peak_last_price,
-                episode.peak_mark_price,
-            ) {
-                tracing::error!("Failed to log episode: {:?}", e);
-            } else {
-                info!(
-                    "[Strategy5] ✅ Critical episode ended: {} | Peak Ratio: {:.4} | Duration: {:?}",
-                    episode.symbol, episode.peak_ratio,
-                    chrono::Utc::now().signed_duration_since(episode.start_time)
-                );
+        // Optional candle-shape conditions, then the market filters
+        let conditions = &mut evaluation.conditions;
+        check_patterns(&config.patterns, data.candle_buffer.completed_last_price_candles(), conditions);
+        conditions.push_volume_24h(data.stats_24h, config.min_volume_24h_usdt);
+        conditions.push_funding_rate(data.funding_rate, config.max_abs_funding_rate);
+        conditions.push_oi_increase(data.open_interest_change_pct(config.oi_lookback_secs), config.min_oi_increase_pct);
+    }
 
-                if let Some(ref exporter) = self.csv_exporter {
-                    exporter.mark_anomaly_ended(&episode.symbol, "strategy5");
-                }
-            }
-        }
+    fn describe_trigger(&self, evaluation: &Evaluation, _features: &FeatureCache, _spread: Spread) -> String {
+        let conditions = &evaluation.conditions;
+        format!(
+            "ALL 4 CONDITIONS MET | Spike: {:.2}x | Pump: {:.2}x | Depth: {}",
+            conditions.actual("s2.spike").unwrap_or_default(),
+            conditions.actual("s3.pump_vs_baseline").unwrap_or_default(),
+            conditions.actual("s4.depth_usdt").map_or_else(|| "n/a (orderbooks disabled)".to_string(), |depth| format!("${:.0}", depth)),
+        )
     }
 }
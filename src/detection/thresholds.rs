@@ -0,0 +1,252 @@
+use crate::config::{
+    merge_toml, Config, EpisodeEndConfig, PipelineConfig, Strategy1Config, Strategy2Config, Strategy3Config, Strategy4Config,
+    Strategy5Config, StrategyRecordingConfig,
+};
+use crate::detection::PriceFloor;
+use crate::universe::VolumeTiers;
+use anyhow::{anyhow, bail, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Sections `[symbol_overrides."SYMBOL"]` and `[tiers.thresholds.<tier>]` can override
+pub const OVERRIDABLE_SECTIONS: [&str; 5] = ["strategy1", "strategy2", "strategy3", "strategy4", "strategy5"];
+
+/// A strategy section of config.toml that symbol overrides apply to, with the settings
+/// every strategy has
+pub trait StrategySection: Clone + Serialize + DeserializeOwned + Send + Sync + 'static {
+    const NAME: &'static str;
+
+    fn of(config: &Config) -> &Self;
+
+    fn enabled(&self) -> bool;
+
+    fn end(&self) -> EpisodeEndConfig;
+
+    fn recording(&self) -> StrategyRecordingConfig;
+
+    fn price_floor(&self) -> PriceFloor;
+}
+
+macro_rules! strategy_section {
+    ($config:ty, $name:ident) => {
+        impl StrategySection for $config {
+            const NAME: &'static str = stringify!($name);
+
+            fn of(config: &Config) -> &Self {
+                &config.$name
+            }
+
+            fn enabled(&self) -> bool {
+                self.enabled
+            }
+
+            fn end(&self) -> EpisodeEndConfig {
+                self.end
+            }
+
+            fn recording(&self) -> StrategyRecordingConfig {
+                self.recording
+            }
+
+            fn price_floor(&self) -> PriceFloor {
+                PriceFloor::new(self.min_price, self.min_contract_notional_usdt, self.min_price_ticks)
+            }
+        }
+    };
+}
+
+strategy_section!(Strategy1Config, strategy1);
+strategy_section!(Strategy2Config, strategy2);
+strategy_section!(Strategy3Config, strategy3);
+strategy_section!(Strategy4Config, strategy4);
+strategy_section!(Strategy5Config, strategy5);
+
+/// A strategy's settings per symbol: the configured section, replaced for the symbols of a
+/// tier with `[tiers.thresholds]` and for symbols with their own `[symbol_overrides]`, in
+/// that order. Strategies look every threshold up here for the symbol they check.
+#[derive(Clone)]
+pub struct ThresholdResolver<C> {
+    /// The settings without a tier, then those of tier1, tier2, ... when tiers have
+    /// thresholds
+    base: Vec<C>,
+    /// Symbol -> its settings, laid out like `base`
+    overrides: HashMap<String, Vec<C>>,
+    tiers: Option<Arc<VolumeTiers>>,
+}
+
+impl<C> ThresholdResolver<C> {
+    /// The same settings for every symbol
+    pub fn new(base: C) -> Self {
+        Self {
+            base: vec![base],
+            overrides: HashMap::new(),
+            tiers: None,
+        }
+    }
+
+    /// Resolves tier thresholds by the symbol's current tier; without it symbols get the
+    /// settings without a tier
+    pub fn with_tiers(mut self, tiers: Option<Arc<VolumeTiers>>) -> Self {
+        self.tiers = tiers;
+        self
+    }
+
+    pub fn get(&self, symbol: &str) -> &C {
+        let layers = self.overrides.get(symbol).unwrap_or(&self.base);
+        let tier = match self.tiers {
+            Some(ref tiers) if layers.len() > 1 => tiers.tier_of(symbol).min(layers.len() - 1),
+            _ => 0,
+        };
+        &layers[tier]
+    }
+
+    /// Settings of symbols without overrides or tier thresholds
+    pub fn base(&self) -> &C {
+        &self.base[0]
+    }
+
+    /// Something derived from the settings, resolved for the same symbols
+    pub fn map<T>(&self, f: impl Fn(&C) -> T) -> ThresholdResolver<T> {
+        ThresholdResolver {
+            base: self.base.iter().map(&f).collect(),
+            overrides: self
+                .overrides
+                .iter()
+                .map(|(symbol, layers)| (symbol.clone(), layers.iter().map(&f).collect()))
+                .collect(),
+            tiers: self.tiers.clone(),
+        }
+    }
+
+    /// Every resolved variant of the settings
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut C> {
+        self.base.iter_mut().chain(self.overrides.values_mut().flatten())
+    }
+}
+
+impl<C> From<C> for ThresholdResolver<C> {
+    fn from(base: C) -> Self {
+        Self::new(base)
+    }
+}
+
+impl<C: StrategySection> ThresholdResolver<C> {
+    /// The section of `config`, with the options of each tier's
+    /// `[tiers.thresholds.<tier>.<section>]` and each symbol's
+    /// `[symbol_overrides."SYMBOL".<section>]` on top of it
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let base_value = toml::Value::try_from(C::of(config))?;
+
+        // No tier first, then every tier; only kept when some tier has thresholds
+        let mut tier_layers: Vec<Option<(String, &toml::Table)>> = vec![None];
+        for tier in 1..=config.tiers.tier_count() {
+            let name = config.tiers.tier_name(tier);
+            let section = config.tiers.thresholds.get(&name).and_then(|sections| sections.get(C::NAME));
+            tier_layers.push(section.and_then(toml::Value::as_table).map(|section| {
+                (format!("tiers.thresholds.{}.{}", name, C::NAME), section)
+            }));
+        }
+        if tier_layers.iter().all(Option::is_none) {
+            tier_layers.truncate(1);
+        }
+
+        let resolved = |symbol_layer: Option<(String, &toml::Table)>| -> Result<Vec<C>> {
+            tier_layers
+                .iter()
+                .map(|tier_layer| {
+                    let mut merged = base_value.clone();
+                    for (path, section) in tier_layer.iter().chain(symbol_layer.iter()) {
+                        if section.get("enabled").is_some() {
+                            bail!("{}.enabled: strategies are switched on and off for every symbol at once", path);
+                        }
+                        merge_toml(&mut merged, toml::Value::Table((*section).clone()));
+                    }
+                    let path = symbol_layer.as_ref().or(tier_layer.as_ref()).map_or(C::NAME.to_string(), |(path, _)| path.clone());
+                    merged.try_into().map_err(|e| anyhow!("{}: {}", path, e))
+                })
+                .collect()
+        };
+
+        let base = resolved(None)?;
+        let mut overrides = HashMap::new();
+        for (symbol, sections) in &config.symbol_overrides {
+            let Some(section) = sections.get(C::NAME).and_then(toml::Value::as_table) else {
+                continue;
+            };
+            let path = format!("symbol_overrides.{}.{}", symbol, C::NAME);
+            overrides.insert(symbol.clone(), resolved(Some((path, section)))?);
+        }
+        Ok(Self {
+            base,
+            overrides,
+            tiers: None,
+        })
+    }
+}
+
+/// Every strategy's thresholds, with `[tiers.thresholds]` and `[symbol_overrides]` applied
+#[derive(Clone)]
+pub struct StrategyThresholds {
+    pub strategy1: ThresholdResolver<Strategy1Config>,
+    pub strategy2: ThresholdResolver<Strategy2Config>,
+    pub strategy3: ThresholdResolver<Strategy3Config>,
+    pub strategy4: ThresholdResolver<Strategy4Config>,
+    pub strategy5: ThresholdResolver<Strategy5Config>,
+}
+
+impl StrategyThresholds {
+    /// Fails on an unknown tier, a section other than the strategy ones, or an option a
+    /// strategy doesn't have
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let tier_names: Vec<String> = (1..=config.tiers.tier_count()).map(|tier| config.tiers.tier_name(tier)).collect();
+        for name in config.tiers.thresholds.keys() {
+            if !tier_names.contains(name) {
+                bail!("tiers.thresholds.{} is not a tier (tiers are {})", name, tier_names.join(", "));
+            }
+        }
+        let overridden = config
+            .tiers
+            .thresholds
+            .iter()
+            .map(|(tier, sections)| (format!("tiers.thresholds.{}", tier), sections))
+            .chain(config.symbol_overrides.iter().map(|(symbol, sections)| (format!("symbol_overrides.{}", symbol), sections)));
+        for (path, sections) in overridden {
+            for (name, value) in sections {
+                if !OVERRIDABLE_SECTIONS.contains(&name.as_str()) || !value.is_table() {
+                    bail!("{}.{} is not a strategy section (one of {})", path, name, OVERRIDABLE_SECTIONS.join(", "));
+                }
+            }
+        }
+        Ok(Self {
+            strategy1: ThresholdResolver::from_config(config)?,
+            strategy2: ThresholdResolver::from_config(config)?,
+            strategy3: ThresholdResolver::from_config(config)?,
+            strategy4: ThresholdResolver::from_config(config)?,
+            strategy5: ThresholdResolver::from_config(config)?,
+        })
+    }
+
+    /// A pipeline's sections, the same for every symbol
+    pub fn of_pipeline(pipeline: &PipelineConfig) -> Self {
+        Self {
+            strategy1: pipeline.strategy1.clone().into(),
+            strategy2: pipeline.strategy2.clone().into(),
+            strategy3: pipeline.strategy3.clone().into(),
+            strategy4: pipeline.strategy4.clone().into(),
+            strategy5: pipeline.strategy5.clone().into(),
+        }
+    }
+
+    /// Resolves tier thresholds by each symbol's current tier
+    pub fn with_tiers(self, tiers: Option<Arc<VolumeTiers>>) -> Self {
+        Self {
+            strategy1: self.strategy1.with_tiers(tiers.clone()),
+            strategy2: self.strategy2.with_tiers(tiers.clone()),
+            strategy3: self.strategy3.with_tiers(tiers.clone()),
+            strategy4: self.strategy4.with_tiers(tiers.clone()),
+            strategy5: self.strategy5.with_tiers(tiers),
+        }
+    }
+}
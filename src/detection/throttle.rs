@@ -0,0 +1,141 @@
+use crate::config::ThrottleConfig;
+use crate::utils::SharedClock;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{info, warn};
+
+// How often rates are re-evaluated without new episodes, so raised thresholds come back down
+const EVALUATE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+struct ThrottleState {
+    /// Episode starts within the last hour, oldest first
+    starts: VecDeque<DateTime<Utc>>,
+    level: u32,
+    last_change: Option<DateTime<Utc>>,
+}
+
+/// Threshold scaling of one strategy: raised one step at a time while the strategy starts
+/// more than `max_episodes_per_hour` episodes, lowered again once the rate has normalized.
+///
+/// The default throttle never raises anything, for strategies built without one.
+#[derive(Default)]
+pub struct StrategyThrottle {
+    strategy: &'static str,
+    settings: Option<(ThrottleConfig, SharedClock)>,
+    state: Mutex<ThrottleState>,
+    /// Current factor as `f64` bits, read on every check without locking
+    factor: AtomicU64,
+}
+
+impl StrategyThrottle {
+    fn new(strategy: &'static str, config: ThrottleConfig, clock: SharedClock) -> Self {
+        Self {
+            strategy,
+            settings: Some((config, clock)),
+            state: Mutex::new(ThrottleState::default()),
+            factor: AtomicU64::new(1.0f64.to_bits()),
+        }
+    }
+
+    /// Multiplier applied to the distance of ratio thresholds above 1.0
+    pub fn factor(&self) -> f64 {
+        match self.settings {
+            Some(_) => f64::from_bits(self.factor.load(Ordering::Relaxed)),
+            None => 1.0,
+        }
+    }
+
+    /// A `>= base` ratio threshold raised by the current factor, e.g. 1.10 -> 1.15 at 1.5x
+    pub fn ratio_threshold(&self, base: f64) -> f64 {
+        1.0 + (base - 1.0) * self.factor()
+    }
+
+    pub fn record_start(&self, at: DateTime<Utc>) {
+        if self.settings.is_none() {
+            return;
+        }
+        self.state.lock().unwrap().starts.push_back(at);
+        self.evaluate();
+    }
+
+    /// Raises or lowers the thresholds by one step when the last hour's rate calls for it
+    fn evaluate(&self) {
+        let Some((ref config, ref clock)) = self.settings else {
+            return;
+        };
+        let now = clock.now();
+        let mut state = self.state.lock().unwrap();
+        while state.starts.front().is_some_and(|&at| now - at > ChronoDuration::hours(1)) {
+            state.starts.pop_front();
+        }
+
+        let rate = state.starts.len();
+        let settled = state
+            .last_change
+            .is_none_or(|at| now - at >= ChronoDuration::minutes(config.hold_mins as i64));
+        if !settled {
+            return;
+        }
+
+        if rate > config.max_episodes_per_hour && state.level < config.max_steps {
+            state.level += 1;
+            let factor = config.threshold_multiplier.powi(state.level as i32);
+            warn!(
+                "[Throttle] {} started {} episodes in the last hour (max {}) - ratio thresholds raised to {:.2}x (step {}/{})",
+                self.strategy, rate, config.max_episodes_per_hour, factor, state.level, config.max_steps
+            );
+            self.set_level(&mut state, now, factor);
+        } else if state.level > 0 && rate <= config.max_episodes_per_hour / 2 {
+            state.level -= 1;
+            let factor = config.threshold_multiplier.powi(state.level as i32);
+            info!(
+                "[Throttle] {} down to {} episodes in the last hour - ratio thresholds lowered to {:.2}x (step {}/{})",
+                self.strategy, rate, factor, state.level, config.max_steps
+            );
+            self.set_level(&mut state, now, factor);
+        }
+    }
+
+    fn set_level(&self, state: &mut ThrottleState, now: DateTime<Utc>, factor: f64) {
+        state.last_change = Some(now);
+        self.factor.store(factor.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Hands out a `StrategyThrottle` per strategy and re-evaluates them periodically
+#[derive(Clone)]
+pub struct TriggerThrottle {
+    config: ThrottleConfig,
+    clock: SharedClock,
+    throttles: Vec<Arc<StrategyThrottle>>,
+}
+
+impl TriggerThrottle {
+    pub fn new(config: ThrottleConfig, clock: SharedClock) -> Self {
+        Self {
+            config,
+            clock,
+            throttles: Vec::new(),
+        }
+    }
+
+    pub fn for_strategy(&mut self, strategy: &'static str) -> Arc<StrategyThrottle> {
+        let throttle = Arc::new(StrategyThrottle::new(strategy, self.config.clone(), self.clock.clone()));
+        self.throttles.push(throttle.clone());
+        throttle
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(EVALUATE_INTERVAL);
+        loop {
+            interval.tick().await;
+            for throttle in &self.throttles {
+                throttle.evaluate();
+            }
+        }
+    }
+}
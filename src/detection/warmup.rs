@@ -0,0 +1,150 @@
+use crate::models::{Symbol, SymbolData};
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// What a strategy still needs before it can evaluate a symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WarmupGap {
+    /// No last and mark price seen yet
+    Prices,
+    /// Price history doesn't reach back `secs` yet
+    History { secs: u64 },
+    /// No usable orderbook yet
+    Orderbook,
+    /// Data is stale after a market stream outage
+    Resync,
+}
+
+impl fmt::Display for WarmupGap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarmupGap::Prices => write!(f, "prices"),
+            WarmupGap::History { secs } => write!(f, "{}s history", secs),
+            WarmupGap::Orderbook => write!(f, "orderbook"),
+            WarmupGap::Resync => write!(f, "fresh data after reconnect"),
+        }
+    }
+}
+
+/// Outcome of a strategy check: "armed" means its conditions were actually evaluated,
+/// whatever their result, as opposed to skipped while data is still accumulating
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Readiness {
+    Armed,
+    Warming(WarmupGap),
+    Disabled,
+}
+
+impl fmt::Display for Readiness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Readiness::Armed => write!(f, "armed"),
+            Readiness::Warming(gap) => write!(f, "warming up (needs {})", gap),
+            Readiness::Disabled => write!(f, "disabled"),
+        }
+    }
+}
+
+/// Armed when the symbol's history reaches back `secs`, so a lookback or baseline
+/// window of that length is completely filled
+pub fn history_readiness(data: &SymbolData, secs: u64) -> Readiness {
+    if data.history_covers(secs) {
+        Readiness::Armed
+    } else {
+        Readiness::Warming(WarmupGap::History { secs })
+    }
+}
+
+/// Per-strategy readiness counts across all monitored symbols
+#[derive(Debug, Clone, Default)]
+pub struct ReadinessSummary {
+    pub armed: usize,
+    pub disabled: usize,
+    pub warming: HashMap<WarmupGap, usize>,
+}
+
+impl ReadinessSummary {
+    pub fn add(&mut self, readiness: Readiness) {
+        match readiness {
+            Readiness::Armed => self.armed += 1,
+            Readiness::Disabled => self.disabled += 1,
+            Readiness::Warming(gap) => *self.warming.entry(gap).or_insert(0) += 1,
+        }
+    }
+
+    pub fn warming_total(&self) -> usize {
+        self.warming.values().sum()
+    }
+}
+
+impl fmt::Display for ReadinessSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.armed + self.disabled + self.warming_total();
+        if self.disabled == total {
+            return write!(f, "disabled");
+        }
+        write!(f, "armed {}/{}", self.armed, total)?;
+        if !self.warming.is_empty() {
+            let mut gaps: Vec<_> = self.warming.iter().collect();
+            gaps.sort_by(|a, b| b.1.cmp(a.1));
+            let gaps: Vec<String> = gaps.iter().map(|(gap, count)| format!("{} {}", count, gap)).collect();
+            write!(f, " | warming: {}", gaps.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Latest readiness of every strategy for every symbol, as reported by their checks
+#[derive(Default)]
+pub struct ReadinessBoard {
+    entries: DashMap<String, HashMap<&'static str, Readiness>>,
+    // Strategy checks recorded since startup
+    checks: AtomicU64,
+}
+
+impl ReadinessBoard {
+    pub fn record(&self, symbol: &str, outcomes: &[(&'static str, Readiness)]) {
+        self.checks.fetch_add(outcomes.len() as u64, Ordering::Relaxed);
+        match self.entries.get_mut(symbol) {
+            Some(mut entry) => entry.extend(outcomes.iter().copied()),
+            None => {
+                self.entries.insert(symbol.to_string(), outcomes.iter().copied().collect());
+            }
+        }
+    }
+
+    /// Number of strategy checks recorded since startup
+    pub fn checks_recorded(&self) -> u64 {
+        self.checks.load(Ordering::Relaxed)
+    }
+
+    pub fn get(&self, symbol: &str, strategy: &str) -> Option<Readiness> {
+        self.entries.get(symbol)?.get(strategy).copied()
+    }
+
+    /// Counts per strategy over the monitored symbols; symbols no strategy has checked
+    /// yet count as waiting for prices. Entries of symbols no longer monitored are dropped.
+    pub fn summarize(
+        &self,
+        strategies: &[&'static str],
+        symbol_data: &DashMap<Symbol, SymbolData>,
+    ) -> Vec<(&'static str, ReadinessSummary)> {
+        self.entries.retain(|symbol, _| symbol_data.contains_key(symbol.as_str()));
+
+        let mut summaries: Vec<(&'static str, ReadinessSummary)> =
+            strategies.iter().map(|s| (*s, ReadinessSummary::default())).collect();
+        for entry in symbol_data.iter() {
+            let checked = self.entries.get(&**entry.key());
+            for (strategy, summary) in summaries.iter_mut() {
+                let readiness = checked
+                    .as_ref()
+                    .and_then(|c| c.get(strategy).copied())
+                    .unwrap_or(Readiness::Warming(WarmupGap::Prices));
+                summary.add(readiness);
+            }
+        }
+        summaries
+    }
+}
@@ -0,0 +1,81 @@
+use crate::api::SubscriptionCommand;
+use crate::config::WatchlistConfig;
+use crate::models::{SymbolData, WatchState};
+use chrono::Duration;
+use tokio::sync::mpsc;
+use tracing::info;
+
+/// Promotes symbols whose spread crosses a soft threshold to a time-limited watchlist.
+///
+/// Watched symbols get a deeper orderbook subscription, are re-checked on every
+/// orderbook update, and have their ratio thresholds relaxed by `threshold_relax_pct`.
+pub struct Watchlist {
+    config: WatchlistConfig,
+    command_tx: mpsc::UnboundedSender<SubscriptionCommand>,
+    default_depth: usize,
+}
+
+impl Watchlist {
+    pub fn new(
+        config: WatchlistConfig,
+        command_tx: mpsc::UnboundedSender<SubscriptionCommand>,
+        default_depth: usize,
+    ) -> Self {
+        Self {
+            config,
+            command_tx,
+            default_depth,
+        }
+    }
+
+    /// Promotes, extends or expires the watch on `data` based on its current ratio
+    pub fn observe(&self, data: &mut SymbolData) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let now = data.now();
+        let ratio = match (data.current_last_price, data.current_mark_price) {
+            (Some(last), Some(mark)) if mark > 0.0 => last / mark,
+            _ => return,
+        };
+
+        if ratio >= self.config.soft_ratio_min {
+            let until = now + Duration::seconds(self.config.watch_duration_secs as i64);
+            match data.watch {
+                Some(ref mut watch) => watch.until = until,
+                None => {
+                    info!(
+                        "[Watchlist] 👀 {} promoted (ratio {:.4} >= {:.4}) for {}s",
+                        data.symbol, ratio, self.config.soft_ratio_min, self.config.watch_duration_secs
+                    );
+                    data.watch = Some(WatchState {
+                        since: now,
+                        until,
+                        relax_fraction: self.config.threshold_relax_pct / 100.0,
+                    });
+                    self.set_depth(&data.symbol, self.config.watch_depth_levels);
+                }
+            }
+        } else if data.watch.as_ref().is_some_and(|watch| watch.until <= now) {
+            if let Some(watch) = data.watch.take() {
+                info!(
+                    "[Watchlist] {} dropped after {}s without escalation",
+                    data.symbol,
+                    now.signed_duration_since(watch.since).num_seconds()
+                );
+            }
+            self.set_depth(&data.symbol, self.default_depth);
+        }
+    }
+
+    fn set_depth(&self, symbol: &str, limit: usize) {
+        if limit == self.default_depth && self.config.watch_depth_levels == self.default_depth {
+            return;
+        }
+        let _ = self.command_tx.send(SubscriptionCommand::SetDepth {
+            symbol: symbol.to_string(),
+            limit,
+        });
+    }
+}
@@ -0,0 +1,36 @@
+use crate::models::ProcessedOrderbook;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+/// Stands in for the order executor in binaries built without the `execution` feature.
+///
+/// It has no values, so the `Option<Arc<OrderExecutor>>` that detection passes around is
+/// always `None` and the calls below are never made.
+pub enum OrderExecutor {}
+
+impl OrderExecutor {
+    pub fn enter_position(
+        self: &Arc<Self>,
+        _symbol: &str,
+        _strategy: &str,
+        _price: f64,
+        _orderbook: Option<&ProcessedOrderbook>,
+        _episode_id: &str,
+    ) {
+        match **self {}
+    }
+
+    pub fn anticipate(
+        self: &Arc<Self>,
+        _symbol: &str,
+        _watched_since: DateTime<Utc>,
+        _last_price: f64,
+        _orderbook: Option<&ProcessedOrderbook>,
+    ) {
+        match **self {}
+    }
+
+    pub fn manage_exits(self: &Arc<Self>, _symbol: &str, _last_price: f64, _ratio: f64, _orderbook: Option<&ProcessedOrderbook>) {
+        match **self {}
+    }
+}
@@ -0,0 +1,729 @@
+use crate::alerts::KillSwitch;
+use crate::api::{AuthError, MexcRestClient, PrivateEvent, RequestError};
+use crate::config::ExecutionConfig;
+use crate::execution::{
+    simulate_fill, size_for_risk, ContractSpec, ExitOrder, OrderState, OrderTracker, Position, PositionBook, TradeLedger,
+    TradeRecord,
+};
+use crate::models::{OrderRequest, OrderType, PositionSide, ProcessedOrderbook};
+use crate::universe::VolumeTiers;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+// MEXC margin mode code
+const OPEN_TYPE_CROSS: u8 = 2;
+
+// Strategy and episode of anticipation orders that filled without a confirming signal
+const ANTICIPATE: &str = "anticipate";
+
+/// A resting anticipation order waiting for a strategy to confirm the entry
+#[derive(Debug, Clone)]
+struct Anticipation {
+    /// Client order id; None in dry run
+    order: Option<String>,
+    contracts: f64,
+    price: f64,
+    notional_usdt: f64,
+    /// Whether the limit crossed the book when placed, i.e. a paper order would have filled
+    marketable: bool,
+    placed_at: DateTime<Utc>,
+}
+
+/// Turns confirmed signals into position entries, and closes them again along the
+/// strategy's exit ladder (`exits` in execution.toml).
+///
+/// In dry-run mode entries and exits are only sized, filled against the current orderbook
+/// snapshot on paper and logged. Live orders carry an
+/// idempotent client order id so ambiguous submissions can be reconciled
+/// against the exchange instead of being blindly resent.
+///
+/// With `anticipate` enabled, a watched symbol whose book leans to the bid side gets a small
+/// limit order ahead of the signal, which a confirming entry takes over.
+pub struct OrderExecutor {
+    config: ExecutionConfig,
+    specs: DashMap<String, ContractSpec>,
+    rest: Option<Arc<MexcRestClient>>,
+    orders: OrderTracker,
+    authenticated: AtomicBool,
+    last_auth_error: Mutex<Option<AuthError>>,
+    kill_switch: Option<Arc<KillSwitch>>,
+    tiers: Option<Arc<VolumeTiers>>,
+    positions: PositionBook,
+    ledger: Option<Arc<TradeLedger>>,
+    anticipations: DashMap<String, Anticipation>,
+    /// Start of the watch each symbol last anticipated, so a watch anticipates at most once
+    anticipated_watches: DashMap<String, DateTime<Utc>>,
+}
+
+impl OrderExecutor {
+    pub fn new(config: ExecutionConfig, specs: Vec<ContractSpec>, rest: Option<Arc<MexcRestClient>>) -> Self {
+        let map = DashMap::new();
+        for spec in specs {
+            map.insert(spec.symbol.clone(), spec);
+        }
+
+        Self {
+            config,
+            specs: map,
+            rest,
+            orders: OrderTracker::default(),
+            authenticated: AtomicBool::new(false),
+            last_auth_error: Mutex::new(None),
+            kill_switch: None,
+            tiers: None,
+            positions: PositionBook::default(),
+            ledger: None,
+            anticipations: DashMap::new(),
+            anticipated_watches: DashMap::new(),
+        }
+    }
+
+    /// Skip entries while the kill switch is tripped
+    pub fn with_kill_switch(mut self, kill_switch: Arc<KillSwitch>) -> Self {
+        self.kill_switch = Some(kill_switch);
+        self
+    }
+
+    /// Only enter positions on symbols whose volume tier is listed in `[tiers] execute`
+    pub fn with_tiers(mut self, tiers: Arc<VolumeTiers>) -> Self {
+        self.tiers = Some(tiers);
+        self
+    }
+
+    /// Record every entry and close in the trade ledger
+    pub fn with_ledger(mut self, ledger: Arc<TradeLedger>) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    /// Whether the private user-data stream is currently logged in
+    pub fn is_authenticated(&self) -> bool {
+        self.authenticated.load(Ordering::Relaxed)
+    }
+
+    pub fn handle_private_event(&self, event: PrivateEvent) {
+        match event {
+            PrivateEvent::Authenticated => {
+                self.authenticated.store(true, Ordering::Relaxed);
+                *self.last_auth_error.lock().unwrap() = None;
+                info!("[Execution] Private stream authenticated");
+            }
+            PrivateEvent::AuthFailed(e) => {
+                self.authenticated.store(false, Ordering::Relaxed);
+                error!("[Execution] Private stream authentication failed: {}", e);
+                *self.last_auth_error.lock().unwrap() = Some(e);
+            }
+            PrivateEvent::Disconnected => {
+                self.authenticated.store(false, Ordering::Relaxed);
+                warn!("[Execution] Private stream disconnected - awaiting re-authentication");
+            }
+            PrivateEvent::OrderUpdate(data) => {
+                debug!("[Execution] Order update: {}", data);
+                let client_order_id = data.get("externalOid").and_then(|v| v.as_str());
+                let state = data.get("state").and_then(|v| v.as_i64()).and_then(OrderState::from_mexc);
+                if let (Some(client_order_id), Some(state)) = (client_order_id, state) {
+                    let exchange_order_id = data.get("orderId").map(|v| match v.as_str() {
+                        Some(s) => s.to_string(),
+                        None => v.to_string(),
+                    });
+                    self.orders.transition(client_order_id, state, exchange_order_id);
+                }
+            }
+            PrivateEvent::PositionUpdate(data) => {
+                debug!("[Execution] Position update: {}", data);
+            }
+        }
+    }
+
+    /// `orderbook` is the symbol's latest snapshot; a dry-run entry opens with the contracts it
+    /// would fill, and is skipped when nothing rests on the book
+    pub fn enter_position(
+        self: &Arc<Self>,
+        symbol: &str,
+        strategy: &str,
+        price: f64,
+        orderbook: Option<&ProcessedOrderbook>,
+        episode_id: &str,
+    ) {
+        if !self.may_open("entry", symbol) {
+            return;
+        }
+
+        let spec = match self.specs.get(symbol) {
+            Some(spec) => spec.clone(),
+            None => {
+                warn!("[Execution] No contract spec for {} - skipping entry", symbol);
+                return;
+            }
+        };
+
+        // An anticipation order on the symbol becomes part of the entry; the rest of the
+        // risk is entered as usual. A dry-run limit that never crossed the book would not
+        // have filled, so the whole risk goes to the entry instead.
+        let mut risk_usdt = self.config.risk_usdt;
+        if let Some((_, anticipation)) = self.anticipations.remove(symbol) {
+            if anticipation.order.is_none() && !anticipation.marketable {
+                info!(
+                    "[Execution] DRY RUN anticipation on {} dropped on {}'s signal - limit {:.8} would not have filled | Episode: {}",
+                    symbol, strategy, anticipation.price, episode_id
+                );
+            } else {
+                info!(
+                    "[Execution] Anticipation on {} confirmed by {} | {} contracts @ {:.8} | Episode: {}",
+                    symbol, strategy, anticipation.contracts, anticipation.price, episode_id
+                );
+                risk_usdt -= anticipation.notional_usdt;
+                self.open(Position::new(
+                    symbol,
+                    strategy,
+                    episode_id,
+                    self.config.side,
+                    anticipation.order,
+                    anticipation.price,
+                    anticipation.contracts,
+                    spec.vol_unit,
+                    spec.contract_size,
+                ));
+                if risk_usdt <= 0.0 {
+                    return;
+                }
+            }
+        }
+
+        let size = match size_for_risk(&spec, risk_usdt, price) {
+            Ok(size) => size,
+            Err(e) => {
+                warn!("[Execution] Cannot size entry for {}: {}", symbol, e);
+                return;
+            }
+        };
+
+        if size.capped {
+            warn!(
+                "[Execution] {} quantity capped at max volume {}",
+                symbol, spec.max_vol
+            );
+        }
+
+        if self.config.dry_run {
+            info!(
+                "[Execution] DRY RUN entry: {} | {:?} {} contracts @ {:.8} | Notional: {:.2} USDT (risk {:.2} USDT) | Episode: {}",
+                symbol, self.config.side, size.contracts, price, size.notional_usdt, risk_usdt, episode_id
+            );
+            let mut entry_price = price;
+            let mut contracts = size.contracts;
+            match orderbook {
+                Some(book) => {
+                    let fill = simulate_fill(book, self.config.side, size.contracts);
+                    let (Some(avg_price), Some(slippage)) = (fill.avg_price, fill.slippage_pct(self.config.side, price))
+                    else {
+                        warn!("[Execution] DRY RUN fill: {} | no resting liquidity on the book - skipping entry", symbol);
+                        return;
+                    };
+                    info!(
+                        "[Execution] DRY RUN fill: {} | {}/{} contracts @ avg {:.8} (worst {:.8}, {} levels) | Slippage: {:.3}%{}",
+                        symbol,
+                        fill.filled,
+                        fill.requested,
+                        avg_price,
+                        fill.worst_price.unwrap_or(avg_price),
+                        fill.levels_consumed,
+                        slippage,
+                        if fill.is_partial() { " | PARTIAL - book too thin" } else { "" }
+                    );
+                    entry_price = avg_price;
+                    contracts = fill.filled;
+                }
+                None => debug!("[Execution] DRY RUN fill for {} not simulated - no orderbook yet", symbol),
+            }
+            let position = Position::new(
+                symbol,
+                strategy,
+                episode_id,
+                self.config.side,
+                None,
+                entry_price,
+                contracts,
+                spec.vol_unit,
+                spec.contract_size,
+            );
+            self.open(position);
+            return;
+        }
+
+        let side = self.config.side.open_code();
+        let order = self.orders.create(
+            symbol,
+            side,
+            size.contracts,
+            self.order_price(&spec, side, price),
+            self.config.order_type,
+            episode_id,
+        );
+        info!(
+            "[Execution] Submitting {} | {:?} {} contracts {} (client id {}, episode {})",
+            symbol, self.config.side, size.contracts, self.describe_price(order.price), order.client_order_id, episode_id
+        );
+        let position = Position::new(
+            symbol,
+            strategy,
+            episode_id,
+            self.config.side,
+            Some(order.client_order_id.clone()),
+            price,
+            size.contracts,
+            spec.vol_unit,
+            spec.contract_size,
+        );
+        self.open(position);
+
+        let executor = self.clone();
+        tokio::spawn(async move {
+            executor.submit_with_retry(&order.client_order_id).await;
+        });
+    }
+
+    /// Whether a new position may be opened on `symbol` now; logs why not. `what` names the
+    /// order being skipped
+    fn may_open(&self, what: &str, symbol: &str) -> bool {
+        if self.kill_switch.as_ref().is_some_and(|k| k.is_tripped()) {
+            warn!("[Execution] Skipping {} for {} - kill switch tripped", what, symbol);
+            return false;
+        }
+
+        if let Some(ref tiers) = self.tiers {
+            if !tiers.may_execute(symbol) {
+                info!("[Execution] Skipping {} for {} - {} is not executed", what, symbol, tiers.tier_name_of(symbol));
+                return false;
+            }
+        }
+
+        if self.config.private_ws_enabled && !self.is_authenticated() {
+            let reason = self
+                .last_auth_error
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map(|e| e.to_string())
+                .unwrap_or_else(|| "not logged in yet".to_string());
+            warn!("[Execution] Skipping {} for {} - private stream unavailable ({})", what, symbol, reason);
+            return false;
+        }
+
+        let max_open = self.config.max_open_positions;
+        if max_open > 0 && self.positions.len() >= max_open {
+            warn!("[Execution] Skipping {} for {} - {} positions already open", what, symbol, max_open);
+            return false;
+        }
+        true
+    }
+
+    /// Places a small limit order `offset_pct` above the last price on a watched symbol once
+    /// its book leans to the bid side, at most once per watch. A strategy entering the symbol
+    /// within `confirm_secs` takes the order over; otherwise it is cancelled.
+    pub fn anticipate(
+        self: &Arc<Self>,
+        symbol: &str,
+        watched_since: DateTime<Utc>,
+        last_price: f64,
+        orderbook: Option<&ProcessedOrderbook>,
+    ) {
+        let config = &self.config.anticipate;
+        if !config.enabled
+            || self.anticipated_watches.get(symbol).is_some_and(|since| *since == watched_since)
+            || self.anticipations.contains_key(symbol)
+            || self.positions.contains(symbol)
+        {
+            return;
+        }
+        let Some(book) = orderbook else {
+            return;
+        };
+        let imbalance = book.imbalance().unwrap_or(0.0);
+        if imbalance < config.min_book_imbalance {
+            return;
+        }
+        self.anticipated_watches.insert(symbol.to_string(), watched_since);
+
+        if !self.may_open("anticipation", symbol) {
+            return;
+        }
+        let Some(spec) = self.specs.get(symbol).map(|spec| spec.clone()) else {
+            return;
+        };
+        let size = match size_for_risk(&spec, self.config.risk_usdt * config.risk_pct / 100.0, last_price) {
+            Ok(size) => size,
+            Err(e) => {
+                warn!("[Execution] Cannot size anticipation for {}: {}", symbol, e);
+                return;
+            }
+        };
+        let price = spec.round_price(last_price * (1.0 + config.offset_pct / 100.0), false);
+        let marketable = book.best_quote().is_some_and(|quote| match self.config.side {
+            PositionSide::Long => quote.best_ask <= price,
+            PositionSide::Short => quote.best_bid >= price,
+        });
+
+        let order = if self.config.dry_run {
+            info!(
+                "[Execution] DRY RUN anticipation: {} | {:?} {} contracts limit {:.8} | Book imbalance: {:+.2} | Confirm within {}s",
+                symbol, self.config.side, size.contracts, price, imbalance, config.confirm_secs
+            );
+            None
+        } else {
+            let order = self.orders.create(symbol, self.config.side.open_code(), size.contracts, price, OrderType::Limit, ANTICIPATE);
+            info!(
+                "[Execution] Submitting anticipation {} | {:?} {} contracts limit {:.8} | Book imbalance: {:+.2} (client id {}, confirm within {}s)",
+                symbol, self.config.side, size.contracts, price, imbalance, order.client_order_id, config.confirm_secs
+            );
+            let executor = self.clone();
+            let client_order_id = order.client_order_id.clone();
+            tokio::spawn(async move {
+                executor.submit_with_retry(&client_order_id).await;
+            });
+            Some(order.client_order_id)
+        };
+
+        let placed_at = Utc::now();
+        self.anticipations.insert(
+            symbol.to_string(),
+            Anticipation {
+                order,
+                contracts: size.contracts,
+                price,
+                notional_usdt: size.notional_usdt,
+                marketable,
+                placed_at,
+            },
+        );
+
+        let executor = self.clone();
+        let symbol = symbol.to_string();
+        let confirm = Duration::from_secs(config.confirm_secs);
+        tokio::spawn(async move {
+            tokio::time::sleep(confirm).await;
+            if let Some((_, anticipation)) = executor.anticipations.remove_if(&symbol, |_, a| a.placed_at == placed_at) {
+                executor.cancel_anticipation(&symbol, anticipation).await;
+            }
+        });
+    }
+
+    /// Cancels an unconfirmed anticipation order. One that filled anyway is kept as a position
+    /// of the `anticipate` strategy, closed along `exits.anticipate` when configured.
+    async fn cancel_anticipation(&self, symbol: &str, anticipation: Anticipation) {
+        let confirm_secs = self.config.anticipate.confirm_secs;
+        let filled = match anticipation.order {
+            None => {
+                info!("[Execution] DRY RUN anticipation on {} cancelled - no signal within {}s", symbol, confirm_secs);
+                anticipation.marketable
+            }
+            Some(ref client_order_id) => {
+                let open = self.orders.get(client_order_id).is_some_and(|order| !order.state.is_terminal());
+                if open {
+                    if let Some(ref rest) = self.rest {
+                        let timeout = Duration::from_millis(self.config.order_timeout_ms);
+                        match rest.cancel_order_by_external_id(symbol, client_order_id, timeout).await {
+                            Ok(()) => info!(
+                                "[Execution] Anticipation {} on {} cancelled - no signal within {}s",
+                                client_order_id, symbol, confirm_secs
+                            ),
+                            Err(e) => warn!("[Execution] Cancelling anticipation {} failed: {}", client_order_id, e),
+                        }
+                    }
+                }
+                // The order may have filled before the cancel reached the exchange
+                if let Err(e) = self.reconcile_order(client_order_id).await {
+                    warn!("[Execution] Reconciliation of {} failed: {}", client_order_id, e);
+                }
+                self.orders.get(client_order_id).is_some_and(|order| order.state == OrderState::Filled)
+            }
+        };
+        if !filled {
+            return;
+        }
+
+        warn!(
+            "[Execution] Anticipation on {} filled without a confirming signal - holding {} contracts as '{}'",
+            symbol, anticipation.contracts, ANTICIPATE
+        );
+        let Some(spec) = self.specs.get(symbol).map(|spec| spec.clone()) else {
+            return;
+        };
+        self.open(Position::new(
+            symbol,
+            ANTICIPATE,
+            ANTICIPATE,
+            self.config.side,
+            anticipation.order,
+            anticipation.price,
+            anticipation.contracts,
+            spec.vol_unit,
+            spec.contract_size,
+        ));
+    }
+
+    /// Records the entry and keeps the position open; it is closed along the strategy's exit
+    /// ladder, or held when there is none
+    fn open(&self, position: Position) {
+        self.record_trade(&position, "entry", position.contracts, position.entry_price, None);
+        self.positions.open(position);
+    }
+
+    /// The signal price for market orders. Limit orders go `limit_offset_pct` beyond it,
+    /// rounded to the tick grid towards the signal price so the offset is never exceeded.
+    fn order_price(&self, spec: &ContractSpec, side: u8, price: f64) -> f64 {
+        if self.config.order_type == OrderType::Market {
+            return price;
+        }
+        let offset = self.config.limit_offset_pct / 100.0;
+        if PositionSide::code_sells(side) {
+            spec.round_price(price * (1.0 - offset), true)
+        } else {
+            spec.round_price(price * (1.0 + offset), false)
+        }
+    }
+
+    fn describe_price(&self, price: f64) -> String {
+        match self.config.order_type {
+            OrderType::Market => format!("@ ~{:.8}", price),
+            OrderType::Limit => format!("limit {:.8}", price),
+        }
+    }
+
+    /// Applies the exit ladders of the symbol's open positions to its latest prices.
+    /// Live positions are only closed once their entry has filled.
+    pub fn manage_exits(
+        self: &Arc<Self>,
+        symbol: &str,
+        last_price: f64,
+        ratio: f64,
+        orderbook: Option<&ProcessedOrderbook>,
+    ) {
+        let mut exits = Vec::new();
+        self.positions.update(symbol, |position| {
+            if let Some(ref entry_order) = position.entry_order {
+                match self.orders.get(entry_order).map(|order| order.state) {
+                    Some(OrderState::Filled) => {}
+                    Some(OrderState::Pending | OrderState::Acked) => return true,
+                    _ => {
+                        info!(
+                            "[Execution] No longer managing {} (episode {}) - entry {} did not fill",
+                            symbol, position.episode_id, entry_order
+                        );
+                        return false;
+                    }
+                }
+            }
+            let Some(ladder) = self.config.exits.get(&position.strategy) else {
+                return true;
+            };
+            for exit in position.evaluate(ladder, last_price, ratio) {
+                exits.push((position.clone(), exit));
+            }
+            true
+        });
+
+        for (position, exit) in exits {
+            self.close(&position, exit, last_price, orderbook);
+        }
+    }
+
+    fn close(self: &Arc<Self>, position: &Position, exit: ExitOrder, price: f64, orderbook: Option<&ProcessedOrderbook>) {
+        let symbol = &position.symbol;
+        if self.config.dry_run {
+            // A close takes the other side of the book than the entry did
+            let fill_price = orderbook
+                .and_then(|book| simulate_fill(book, position.side.opposite(), exit.contracts).avg_price)
+                .unwrap_or(price);
+            info!(
+                "[Execution] DRY RUN exit: {} | {} - {} contracts @ {:.8} ({:+.2}%) | {} left | Episode: {}",
+                symbol,
+                exit.reason,
+                exit.contracts,
+                fill_price,
+                position.profit_pct(fill_price),
+                exit.remaining,
+                position.episode_id
+            );
+            self.record_trade(position, &exit.reason.action(), exit.contracts, fill_price, Some(exit.remaining));
+            return;
+        }
+
+        let side = position.side.close_code();
+        let order_price = match self.specs.get(symbol) {
+            Some(spec) => self.order_price(&spec, side, price),
+            None => price,
+        };
+        let order = self.orders.create(symbol, side, exit.contracts, order_price, self.config.order_type, &position.episode_id);
+        info!(
+            "[Execution] Submitting exit {} | {} - {} contracts {} ({:+.2}%) | {} left (client id {}, episode {})",
+            symbol,
+            exit.reason,
+            exit.contracts,
+            self.describe_price(order.price),
+            position.profit_pct(price),
+            exit.remaining,
+            order.client_order_id,
+            position.episode_id
+        );
+        let mut record = self.trade_record(position, &exit.reason.action(), exit.contracts, price, Some(exit.remaining));
+        record.client_order_id = Some(order.client_order_id.clone());
+        if let Some(ref ledger) = self.ledger {
+            ledger.record(record);
+        }
+
+        let executor = self.clone();
+        tokio::spawn(async move {
+            executor.submit_with_retry(&order.client_order_id).await;
+        });
+    }
+
+    /// `remaining` is None for entries
+    fn trade_record(&self, position: &Position, action: &str, contracts: f64, price: f64, remaining: Option<f64>) -> TradeRecord {
+        let is_close = remaining.is_some();
+        TradeRecord {
+            timestamp: chrono::Utc::now(),
+            episode_id: position.episode_id.clone(),
+            strategy: position.strategy.clone(),
+            symbol: position.symbol.clone(),
+            side: position.side,
+            action: action.to_string(),
+            contracts,
+            quantity: contracts * position.contract_size,
+            price,
+            fee_usdt: contracts * position.contract_size * price * self.config.fee_pct / 100.0,
+            remaining: remaining.unwrap_or(position.contracts),
+            pnl_pct: is_close.then(|| position.profit_pct(price)),
+            pnl_usdt: is_close.then(|| position.pnl_usdt(contracts, price)),
+            dry_run: self.config.dry_run,
+            client_order_id: if is_close { None } else { position.entry_order.clone() },
+        }
+    }
+
+    fn record_trade(&self, position: &Position, action: &str, contracts: f64, price: f64, remaining: Option<f64>) {
+        if let Some(ref ledger) = self.ledger {
+            ledger.record(self.trade_record(position, action, contracts, price, remaining));
+        }
+    }
+
+    async fn submit_with_retry(&self, client_order_id: &str) {
+        let rest = match self.rest {
+            Some(ref rest) => rest,
+            None => {
+                error!("[Execution] Live order without a REST client - marking {} rejected", client_order_id);
+                self.orders.transition(client_order_id, OrderState::Rejected, None);
+                return;
+            }
+        };
+
+        let order = match self.orders.get(client_order_id) {
+            Some(order) => order,
+            None => return,
+        };
+
+        let request = OrderRequest {
+            symbol: order.symbol.clone(),
+            price: order.price,
+            vol: order.contracts,
+            side: order.side,
+            order_type: order.order_type.code(),
+            open_type: OPEN_TYPE_CROSS,
+            external_oid: order.client_order_id.clone(),
+            leverage: (self.config.leverage > 0).then_some(self.config.leverage),
+        };
+        let timeout = Duration::from_millis(self.config.order_timeout_ms);
+        let mut resubmit_backoff = Duration::from_millis(self.config.resubmit_backoff_ms);
+
+        while self.orders.record_attempt(client_order_id) <= self.config.max_submit_attempts {
+            match rest.submit_order(&request, timeout).await {
+                Ok(exchange_order_id) => {
+                    self.orders.transition(client_order_id, OrderState::Acked, Some(exchange_order_id));
+                    return;
+                }
+                Err(e) if e.is_ambiguous() => {
+                    warn!("[Execution] Submission of {} is ambiguous ({}) - reconciling", client_order_id, e);
+                    match self.reconcile_order(client_order_id).await {
+                        Ok(true) => return,
+                        // The exchange never saw it, resubmitting with the same id is safe
+                        Ok(false) => {
+                            tokio::time::sleep(resubmit_backoff).await;
+                            resubmit_backoff = (resubmit_backoff * 2).min(timeout);
+                        }
+                        Err(e) => {
+                            // Can't tell; leave it pending for the reconciliation loop
+                            warn!("[Execution] Reconciliation of {} failed: {}", client_order_id, e);
+                            return;
+                        }
+                    }
+                }
+                Err(RequestError::Unreachable { message, .. }) => {
+                    warn!("[Execution] Exchange unreachable submitting {}: {}", client_order_id, message);
+                    tokio::time::sleep(timeout).await;
+                }
+                Err(e) => {
+                    error!("[Execution] Order {} rejected: {}", client_order_id, e);
+                    self.orders.transition(client_order_id, OrderState::Rejected, None);
+                    return;
+                }
+            }
+        }
+
+        warn!(
+            "[Execution] Gave up submitting {} after {} attempts - left for reconciliation",
+            client_order_id, self.config.max_submit_attempts
+        );
+    }
+
+    /// Queries the exchange for an order by client id; returns whether the exchange knows it
+    async fn reconcile_order(&self, client_order_id: &str) -> Result<bool, RequestError> {
+        let (rest, order) = match (&self.rest, self.orders.get(client_order_id)) {
+            (Some(rest), Some(order)) => (rest, order),
+            _ => return Ok(false),
+        };
+
+        let timeout = Duration::from_millis(self.config.order_timeout_ms);
+        match rest.get_order_by_external_id(&order.symbol, client_order_id, timeout).await? {
+            Some(exchange_order) => {
+                self.orders.apply_exchange_order(client_order_id, &exchange_order);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Periodically re-checks orders whose state hasn't been confirmed
+    pub async fn run_reconciliation(self: Arc<Self>) {
+        let period = Duration::from_secs(self.config.reconcile_interval_secs);
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            let stale = self.orders.stale_open_orders(chrono::Duration::seconds(self.config.reconcile_interval_secs as i64));
+            for order in stale {
+                match self.reconcile_order(&order.client_order_id).await {
+                    Ok(true) => {}
+                    Ok(false) if order.state == OrderState::Pending
+                        && order.submit_attempts >= self.config.max_submit_attempts =>
+                    {
+                        warn!(
+                            "[Execution] {} ({}) never reached the exchange after {}s - marking rejected",
+                            order.client_order_id,
+                            order.symbol,
+                            chrono::Utc::now().signed_duration_since(order.created_at).num_seconds()
+                        );
+                        self.orders.transition(&order.client_order_id, OrderState::Rejected, None);
+                    }
+                    Ok(false) => {}
+                    Err(e) => {
+                        warn!("[Execution] Reconciliation of {} failed: {}", order.client_order_id, e);
+                    }
+                }
+            }
+        }
+    }
+}
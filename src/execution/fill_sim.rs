@@ -0,0 +1,69 @@
+use crate::models::{PositionSide, ProcessedOrderbook};
+
+/// Outcome of walking a market order through an orderbook snapshot
+#[derive(Debug, Clone)]
+pub struct SimulatedFill {
+    pub requested: f64,
+    /// Contracts that found resting liquidity in the snapshot
+    pub filled: f64,
+    /// Volume-weighted fill price; None when nothing filled
+    pub avg_price: Option<f64>,
+    /// Price of the deepest level touched
+    pub worst_price: Option<f64>,
+    pub levels_consumed: usize,
+}
+
+impl SimulatedFill {
+    pub fn is_partial(&self) -> bool {
+        self.filled < self.requested
+    }
+
+    /// Adverse distance of the average fill from `reference_price`, in percent
+    pub fn slippage_pct(&self, side: PositionSide, reference_price: f64) -> Option<f64> {
+        let avg = self.avg_price?;
+        let signed = match side {
+            PositionSide::Long => avg / reference_price - 1.0,
+            PositionSide::Short => 1.0 - avg / reference_price,
+        };
+        Some(signed * 100.0)
+    }
+}
+
+/// Fills `contracts` against the side of the book an entry on `side` would take:
+/// a long lifts the asks, a short hits the bids.
+///
+/// Levels are consumed best price first (price priority; the snapshot carries no queue
+/// position, so each level's whole resting size is available). Whatever the visible
+/// book can't absorb is left unfilled rather than assumed to fill at the last level.
+pub fn simulate_fill(book: &ProcessedOrderbook, side: PositionSide, contracts: f64) -> SimulatedFill {
+    let levels = match side {
+        PositionSide::Long => &book.asks,
+        PositionSide::Short => &book.bids,
+    };
+
+    let mut remaining = contracts;
+    let mut filled = 0.0;
+    let mut cost = 0.0;
+    let mut worst_price = None;
+    let mut levels_consumed = 0;
+
+    for level in levels.iter().filter(|l| l.quantity > 0.0) {
+        if remaining <= 0.0 {
+            break;
+        }
+        let take = remaining.min(level.quantity);
+        filled += take;
+        cost += take * level.price;
+        remaining -= take;
+        worst_price = Some(level.price);
+        levels_consumed += 1;
+    }
+
+    SimulatedFill {
+        requested: contracts,
+        filled,
+        avg_price: (filled > 0.0).then(|| cost / filled),
+        worst_price,
+        levels_consumed,
+    }
+}
@@ -0,0 +1,233 @@
+use crate::config::{LedgerExportConfig, LedgerFormat};
+use crate::execution::{export_file_name, export_header, export_rows};
+use crate::models::PositionSide;
+use crate::utils::{set_aside, DiskWriter};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::info;
+
+/// Columns of trades.csv, in order
+const TRADE_COLUMNS: [&str; 15] = [
+    "timestamp",
+    "episode_id",
+    "strategy",
+    "symbol",
+    "side",
+    "action",
+    "contracts",
+    "quantity",
+    "price",
+    "fee_usdt",
+    "remaining",
+    "pnl_pct",
+    "pnl_usdt",
+    "mode",
+    "client_order_id",
+];
+
+/// One entry or (partial) close of a position
+#[derive(Debug, Clone)]
+pub struct TradeRecord {
+    pub timestamp: DateTime<Utc>,
+    pub episode_id: String,
+    pub strategy: String,
+    pub symbol: String,
+    pub side: PositionSide,
+    /// `entry`, `ladder_1`, `ladder_2`, ..., `trailing_stop` or `ratio_normalized`
+    pub action: String,
+    pub contracts: f64,
+    /// Base asset amount: contracts * contract size
+    pub quantity: f64,
+    pub price: f64,
+    /// Estimated from `fee_pct`, as fills don't report their fee
+    pub fee_usdt: f64,
+    /// Contracts still open after this trade
+    pub remaining: f64,
+    /// Result of a close relative to the entry price; None for entries
+    pub pnl_pct: Option<f64>,
+    pub pnl_usdt: Option<f64>,
+    pub dry_run: bool,
+    /// Client order id of live orders
+    pub client_order_id: Option<String>,
+}
+
+impl TradeRecord {
+    pub fn is_entry(&self) -> bool {
+        self.action == "entry"
+    }
+
+    /// Whether the fill bought: a long entry or a short close
+    pub fn is_buy(&self) -> bool {
+        self.is_entry() == (self.side == PositionSide::Long)
+    }
+
+    /// Quote currency of the contract (`PEPE_USDT` -> `USDT`), which fees and PnL are in
+    pub fn quote_currency(&self) -> &str {
+        self.symbol.rsplit_once('_').map_or("USDT", |(_, quote)| quote)
+    }
+}
+
+/// Appends every entry and close of the executor to `trades.csv`, so partial closes of a
+/// position can be reconciled and its realized result added up, and to a
+/// `trades_<format>.csv` per configured portfolio tracker format
+pub struct TradeLedger {
+    dir: PathBuf,
+    file_path: PathBuf,
+    export: LedgerExportConfig,
+    // Appends go through the disk writer thread, which also keeps them in order
+    disk: Arc<DiskWriter>,
+}
+
+impl TradeLedger {
+    pub fn new(log_dir: &str, disk: Arc<DiskWriter>) -> Result<Self> {
+        fs::create_dir_all(log_dir)?;
+
+        let file_path = PathBuf::from(log_dir).join("trades.csv");
+        // Rows with the current columns can't be appended under an older header
+        if read_header(&file_path).is_some_and(|header| header != TRADE_COLUMNS.join(",")) {
+            let kept = set_aside(&file_path, "old-columns")?;
+            info!("[Ledger] {} had older columns, kept as {}", file_path.display(), kept.display());
+        }
+
+        Ok(Self {
+            dir: PathBuf::from(log_dir),
+            file_path,
+            export: LedgerExportConfig::default(),
+            disk,
+        })
+    }
+
+    pub fn with_export(mut self, export: LedgerExportConfig) -> Self {
+        self.export = export;
+        self
+    }
+
+    pub fn record(&self, record: TradeRecord) {
+        let file_path = self.file_path.clone();
+        let exports: Vec<(PathBuf, LedgerFormat)> = if record.dry_run && !self.export.include_paper {
+            Vec::new()
+        } else {
+            self.export.formats.iter().map(|&format| (self.dir.join(export_file_name(format)), format)).collect()
+        };
+        let disk = self.disk.clone();
+        tokio::spawn(async move {
+            let name = format!("trade row for {} ({})", record.episode_id, record.action);
+            disk.write(name, move || {
+                append(&file_path, &TRADE_COLUMNS, &[trade_row(&record)])?;
+                for (path, format) in &exports {
+                    append(path, export_header(*format), &export_rows(*format, &record))?;
+                }
+                Ok(())
+            })
+            .await;
+        });
+    }
+}
+
+fn read_header(path: &Path) -> Option<String> {
+    let mut line = String::new();
+    BufReader::new(File::open(path).ok()?).read_line(&mut line).ok()?;
+    Some(line.trim_end().to_string())
+}
+
+/// Appends rows, writing the header first if the file is new
+fn append(file_path: &Path, header: &[&str], rows: &[Vec<String>]) -> Result<()> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+    let write_header = !file_path.exists();
+
+    let file = OpenOptions::new().create(true).append(true).open(file_path)?;
+    let mut wtr = csv::Writer::from_writer(file);
+
+    if write_header {
+        wtr.write_record(header)?;
+    }
+    for row in rows {
+        wtr.write_record(row)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+fn trade_row(record: &TradeRecord) -> Vec<String> {
+    let optional = |value: Option<f64>, precision: usize| {
+        value.map(|v| format!("{:.*}", precision, v)).unwrap_or_default()
+    };
+    vec![
+        record.timestamp.to_rfc3339(),
+        record.episode_id.clone(),
+        record.strategy.clone(),
+        record.symbol.clone(),
+        format!("{:?}", record.side).to_lowercase(),
+        record.action.clone(),
+        record.contracts.to_string(),
+        record.quantity.to_string(),
+        format!("{:.8}", record.price),
+        format!("{:.6}", record.fee_usdt),
+        record.remaining.to_string(),
+        optional(record.pnl_pct, 3),
+        optional(record.pnl_usdt, 4),
+        if record.dry_run { "dry_run" } else { "live" }.to_string(),
+        record.client_order_id.clone().unwrap_or_default(),
+    ]
+}
+
+/// A trades.csv row; `quantity` and `fee_usdt` are missing from files written before they
+/// were added
+#[derive(Debug, Deserialize)]
+struct TradeRow {
+    timestamp: DateTime<Utc>,
+    episode_id: String,
+    strategy: String,
+    symbol: String,
+    side: PositionSide,
+    action: String,
+    contracts: f64,
+    #[serde(default)]
+    quantity: Option<f64>,
+    price: f64,
+    #[serde(default)]
+    fee_usdt: Option<f64>,
+    remaining: f64,
+    pnl_pct: Option<f64>,
+    pnl_usdt: Option<f64>,
+    mode: String,
+    client_order_id: Option<String>,
+}
+
+/// Reads back a trades.csv. Rows from before quantities and fees were recorded count each
+/// contract as one unit of the base asset, with no fee.
+pub fn read_trades(path: &Path) -> Result<Vec<TradeRecord>> {
+    let mut reader = csv::Reader::from_path(path).with_context(|| format!("opening {}", path.display()))?;
+    reader
+        .deserialize::<TradeRow>()
+        .enumerate()
+        .map(|(index, row)| {
+            // Line numbers count the header
+            let row = row.map_err(|e| anyhow!("{}:{}: {}", path.display(), index + 2, e))?;
+            Ok(TradeRecord {
+                timestamp: row.timestamp,
+                episode_id: row.episode_id,
+                strategy: row.strategy,
+                symbol: row.symbol,
+                side: row.side,
+                action: row.action,
+                contracts: row.contracts,
+                quantity: row.quantity.unwrap_or(row.contracts),
+                price: row.price,
+                fee_usdt: row.fee_usdt.unwrap_or(0.0),
+                remaining: row.remaining,
+                pnl_pct: row.pnl_pct,
+                pnl_usdt: row.pnl_usdt,
+                dry_run: row.mode == "dry_run",
+                client_order_id: row.client_order_id.filter(|id| !id.is_empty()),
+            })
+        })
+        .collect()
+}
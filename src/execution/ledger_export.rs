@@ -0,0 +1,158 @@
+use crate::config::LedgerFormat;
+use crate::execution::TradeRecord;
+use anyhow::Result;
+
+const GENERIC_COLUMNS: [&str; 11] = [
+    "timestamp",
+    "symbol",
+    "side",
+    "quantity",
+    "price",
+    "fee",
+    "fee_currency",
+    "realized_pnl",
+    "pnl_currency",
+    "trade_id",
+    "note",
+];
+
+const KOINLY_COLUMNS: [&str; 12] = [
+    "Date",
+    "Sent Amount",
+    "Sent Currency",
+    "Received Amount",
+    "Received Currency",
+    "Fee Amount",
+    "Fee Currency",
+    "Net Worth Amount",
+    "Net Worth Currency",
+    "Label",
+    "Description",
+    "TxHash",
+];
+
+const COINTRACKING_COLUMNS: [&str; 11] = [
+    "Type",
+    "Buy Amount",
+    "Buy Currency",
+    "Sell Amount",
+    "Sell Currency",
+    "Fee",
+    "Fee Currency",
+    "Exchange",
+    "Trade-Group",
+    "Comment",
+    "Date",
+];
+
+/// Name of the continuously written export next to trades.csv
+pub fn export_file_name(format: LedgerFormat) -> String {
+    format!("trades_{}.csv", format.name())
+}
+
+pub fn export_header(format: LedgerFormat) -> &'static [&'static str] {
+    match format {
+        LedgerFormat::Generic => &GENERIC_COLUMNS,
+        LedgerFormat::Koinly => &KOINLY_COLUMNS,
+        LedgerFormat::Cointracking => &COINTRACKING_COLUMNS,
+    }
+}
+
+/// The rows a trade becomes in `format`.
+///
+/// Trackers that import futures as realized results rather than as buys and sells (Koinly,
+/// CoinTracking) get a gain or loss per close with its fee, and a fee-only row per entry;
+/// an entry without a fee adds nothing there.
+pub fn export_rows(format: LedgerFormat, record: &TradeRecord) -> Vec<Vec<String>> {
+    let currency = record.quote_currency().to_string();
+    let trade_id = record
+        .client_order_id
+        .clone()
+        .unwrap_or_else(|| format!("{}-{}", record.episode_id, record.action));
+    let note = format!(
+        "{} {} {} {}{}",
+        record.strategy,
+        record.symbol,
+        format!("{:?}", record.side).to_lowercase(),
+        record.action,
+        if record.dry_run { " (paper)" } else { "" }
+    );
+    let fee = (record.fee_usdt > 0.0).then_some(record.fee_usdt);
+    let amount = |value: f64| format!("{:.8}", value);
+
+    match format {
+        LedgerFormat::Generic => vec![vec![
+            record.timestamp.to_rfc3339(),
+            record.symbol.clone(),
+            if record.is_buy() { "buy" } else { "sell" }.to_string(),
+            record.quantity.to_string(),
+            amount(record.price),
+            amount(record.fee_usdt),
+            currency.clone(),
+            record.pnl_usdt.map(amount).unwrap_or_default(),
+            currency,
+            trade_id,
+            note,
+        ]],
+        LedgerFormat::Koinly => {
+            let date = record.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string();
+            let row = |sent: Option<f64>, received: Option<f64>, fee: Option<f64>, label: &str| {
+                vec![
+                    date.clone(),
+                    sent.map(amount).unwrap_or_default(),
+                    if sent.is_some() { currency.clone() } else { String::new() },
+                    received.map(amount).unwrap_or_default(),
+                    if received.is_some() { currency.clone() } else { String::new() },
+                    fee.map(amount).unwrap_or_default(),
+                    if fee.is_some() { currency.clone() } else { String::new() },
+                    String::new(),
+                    String::new(),
+                    label.to_string(),
+                    note.clone(),
+                    trade_id.clone(),
+                ]
+            };
+            match record.pnl_usdt {
+                // Losses are withdrawals labelled "realized gain"
+                Some(pnl) if pnl >= 0.0 => vec![row(None, Some(pnl), fee, "realized gain")],
+                Some(pnl) => vec![row(Some(-pnl), None, fee, "realized gain")],
+                None => fee.map(|fee| row(Some(fee), None, None, "cost")).into_iter().collect(),
+            }
+        }
+        LedgerFormat::Cointracking => {
+            let date = record.timestamp.format("%Y-%m-%d %H:%M:%S").to_string();
+            let row = |kind: &str, buy: Option<f64>, sell: Option<f64>, fee: Option<f64>| {
+                vec![
+                    kind.to_string(),
+                    buy.map(amount).unwrap_or_default(),
+                    if buy.is_some() { currency.clone() } else { String::new() },
+                    sell.map(amount).unwrap_or_default(),
+                    if sell.is_some() { currency.clone() } else { String::new() },
+                    fee.map(amount).unwrap_or_default(),
+                    if fee.is_some() { currency.clone() } else { String::new() },
+                    "MEXC".to_string(),
+                    record.strategy.clone(),
+                    format!("{} {}", note, trade_id),
+                    date.clone(),
+                ]
+            };
+            match record.pnl_usdt {
+                Some(pnl) if pnl >= 0.0 => vec![row("Derivatives / Futures Profit", Some(pnl), None, fee)],
+                Some(pnl) => vec![row("Derivatives / Futures Loss", None, Some(-pnl), fee)],
+                None => fee.map(|fee| row("Other Fee", None, Some(fee), None)).into_iter().collect(),
+            }
+        }
+    }
+}
+
+/// `records` as a complete CSV in `format`, header included
+pub fn render_export(format: LedgerFormat, records: &[TradeRecord]) -> Result<String> {
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    wtr.write_record(export_header(format))?;
+    for record in records {
+        for row in export_rows(format, record) {
+            wtr.write_record(&row)?;
+        }
+    }
+    Ok(String::from_utf8(wtr.into_inner()?)?)
+}
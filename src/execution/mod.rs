@@ -0,0 +1,35 @@
+#[cfg(not(feature = "execution"))]
+pub mod disabled;
+#[cfg(feature = "execution")]
+pub mod executor;
+pub mod fill_sim;
+#[cfg(feature = "execution")]
+pub mod ledger;
+#[cfg(feature = "execution")]
+pub mod ledger_export;
+#[cfg(feature = "execution")]
+pub mod orders;
+#[cfg(feature = "execution")]
+pub mod positions;
+#[cfg(feature = "execution")]
+pub mod sizing;
+#[cfg(feature = "execution")]
+pub mod sweep;
+
+#[cfg(not(feature = "execution"))]
+pub use disabled::*;
+#[cfg(feature = "execution")]
+pub use executor::*;
+pub use fill_sim::*;
+#[cfg(feature = "execution")]
+pub use ledger::*;
+#[cfg(feature = "execution")]
+pub use ledger_export::*;
+#[cfg(feature = "execution")]
+pub use orders::*;
+#[cfg(feature = "execution")]
+pub use positions::*;
+#[cfg(feature = "execution")]
+pub use sizing::*;
+#[cfg(feature = "execution")]
+pub use sweep::*;
@@ -0,0 +1,170 @@
+use crate::models::{ExchangeOrder, OrderType};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{info, warn};
+
+/// Lifecycle of an order placed by the executor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderState {
+    /// Submitted (or about to be) but not yet confirmed by the exchange
+    Pending,
+    /// Accepted by the exchange and resting/working
+    Acked,
+    Filled,
+    Cancelled,
+    Rejected,
+}
+
+impl OrderState {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, OrderState::Filled | OrderState::Cancelled | OrderState::Rejected)
+    }
+
+    /// States only move forward: Pending → Acked → Filled/Cancelled
+    pub fn can_transition_to(&self, next: OrderState) -> bool {
+        match self {
+            OrderState::Pending => next != OrderState::Pending,
+            OrderState::Acked => matches!(next, OrderState::Filled | OrderState::Cancelled),
+            _ => false,
+        }
+    }
+
+    /// Maps MEXC order state codes (1 uninformed, 2 uncompleted, 3 completed, 4 cancelled, 5 invalid)
+    pub fn from_mexc(code: i64) -> Option<Self> {
+        match code {
+            1 => Some(OrderState::Pending),
+            2 => Some(OrderState::Acked),
+            3 => Some(OrderState::Filled),
+            4 => Some(OrderState::Cancelled),
+            5 => Some(OrderState::Rejected),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackedOrder {
+    /// Idempotency key sent as `externalOid`; reused on every resubmission
+    pub client_order_id: String,
+    pub exchange_order_id: Option<String>,
+    /// Episode whose signal opened this order
+    pub episode_id: String,
+    pub symbol: String,
+    /// MEXC `side` code: opens on entries, closes on exits
+    pub side: u8,
+    pub contracts: f64,
+    pub price: f64,
+    pub order_type: OrderType,
+    pub state: OrderState,
+    pub submit_attempts: u32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// In-memory registry of the executor's orders keyed by client order id
+#[derive(Default)]
+pub struct OrderTracker {
+    orders: DashMap<String, TrackedOrder>,
+    sequence: AtomicU64,
+}
+
+impl OrderTracker {
+    /// Registers a new pending order with a fresh client order id
+    pub fn create(
+        &self,
+        symbol: &str,
+        side: u8,
+        contracts: f64,
+        price: f64,
+        order_type: OrderType,
+        episode_id: &str,
+    ) -> TrackedOrder {
+        let seq = self.sequence.fetch_add(1, Ordering::Relaxed);
+        let now = Utc::now();
+        let order = TrackedOrder {
+            client_order_id: format!("snp{}_{:04}", now.timestamp_millis(), seq % 10_000),
+            exchange_order_id: None,
+            episode_id: episode_id.to_string(),
+            symbol: symbol.to_string(),
+            side,
+            contracts,
+            price,
+            order_type,
+            state: OrderState::Pending,
+            submit_attempts: 0,
+            created_at: now,
+            updated_at: now,
+        };
+        self.orders.insert(order.client_order_id.clone(), order.clone());
+        order
+    }
+
+    pub fn get(&self, client_order_id: &str) -> Option<TrackedOrder> {
+        self.orders.get(client_order_id).map(|o| o.clone())
+    }
+
+    pub fn record_attempt(&self, client_order_id: &str) -> u32 {
+        match self.orders.get_mut(client_order_id) {
+            Some(mut order) => {
+                order.submit_attempts += 1;
+                order.updated_at = Utc::now();
+                order.submit_attempts
+            }
+            None => 0,
+        }
+    }
+
+    /// Applies a state change if it is a valid forward transition; returns whether it was applied
+    pub fn transition(&self, client_order_id: &str, next: OrderState, exchange_order_id: Option<String>) -> bool {
+        let mut order = match self.orders.get_mut(client_order_id) {
+            Some(order) => order,
+            None => return false,
+        };
+
+        if exchange_order_id.is_some() {
+            order.exchange_order_id = exchange_order_id;
+        }
+
+        if order.state == next {
+            return false;
+        }
+
+        if !order.state.can_transition_to(next) {
+            warn!(
+                "[Orders] Ignoring invalid transition {:?} -> {:?} for {} ({})",
+                order.state, next, client_order_id, order.symbol
+            );
+            return false;
+        }
+
+        info!(
+            "[Orders] {} ({}, episode {}) {:?} -> {:?}",
+            client_order_id, order.symbol, order.episode_id, order.state, next
+        );
+        order.state = next;
+        order.updated_at = Utc::now();
+        true
+    }
+
+    /// Applies the state reported by the exchange for one of our orders
+    pub fn apply_exchange_order(&self, client_order_id: &str, exchange: &ExchangeOrder) -> bool {
+        match OrderState::from_mexc(exchange.state as i64) {
+            Some(state) => self.transition(client_order_id, state, Some(exchange.order_id.clone())),
+            None => {
+                warn!("[Orders] Unknown exchange state {} for {}", exchange.state, client_order_id);
+                false
+            }
+        }
+    }
+
+    /// Non-terminal orders that have not changed for at least `min_age`
+    pub fn stale_open_orders(&self, min_age: chrono::Duration) -> Vec<TrackedOrder> {
+        let cutoff = Utc::now() - min_age;
+        self.orders
+            .iter()
+            .filter(|o| !o.state.is_terminal() && o.updated_at <= cutoff)
+            .map(|o| o.clone())
+            .collect()
+    }
+}
@@ -0,0 +1,212 @@
+use crate::config::ExitLadderConfig;
+use crate::models::PositionSide;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+// Quantities below this are treated as nothing left to close
+const QTY_EPSILON: f64 = 1e-9;
+
+/// Why (part of) a position was closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    /// Ladder step, numbered from 1
+    Ladder(usize),
+    TrailingStop,
+    RatioNormalized,
+}
+
+impl ExitReason {
+    /// Action name in the trade ledger
+    pub fn action(&self) -> String {
+        match self {
+            ExitReason::Ladder(step) => format!("ladder_{}", step),
+            ExitReason::TrailingStop => "trailing_stop".to_string(),
+            ExitReason::RatioNormalized => "ratio_normalized".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ExitReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExitReason::Ladder(step) => write!(f, "ladder step {}", step),
+            ExitReason::TrailingStop => write!(f, "trailing stop"),
+            ExitReason::RatioNormalized => write!(f, "ratio normalized"),
+        }
+    }
+}
+
+/// A close the exit ladder asks for
+#[derive(Debug, Clone, Copy)]
+pub struct ExitOrder {
+    pub contracts: f64,
+    pub reason: ExitReason,
+    /// Contracts left open after this close
+    pub remaining: f64,
+}
+
+/// An open position the executor manages exits for
+#[derive(Debug, Clone)]
+pub struct Position {
+    pub symbol: String,
+    pub strategy: String,
+    pub episode_id: String,
+    pub side: PositionSide,
+    /// Client order id of a live entry; None for dry-run positions
+    pub entry_order: Option<String>,
+    pub entry_price: f64,
+    pub contracts: f64,
+    pub remaining: f64,
+    /// Volume step closes are rounded down to
+    pub vol_unit: f64,
+    pub contract_size: f64,
+    /// Most favorable price since entry
+    best_price: f64,
+    // Ladder steps already taken
+    steps_taken: usize,
+}
+
+impl Position {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        symbol: &str,
+        strategy: &str,
+        episode_id: &str,
+        side: PositionSide,
+        entry_order: Option<String>,
+        entry_price: f64,
+        contracts: f64,
+        vol_unit: f64,
+        contract_size: f64,
+    ) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            strategy: strategy.to_string(),
+            episode_id: episode_id.to_string(),
+            side,
+            entry_order,
+            entry_price,
+            contracts,
+            remaining: contracts,
+            vol_unit,
+            contract_size,
+            best_price: entry_price,
+            steps_taken: 0,
+        }
+    }
+
+    /// Move in the position's favor from the entry, in percent
+    pub fn profit_pct(&self, price: f64) -> f64 {
+        self.side.profit_pct(self.entry_price, price)
+    }
+
+    /// Result in USDT of closing `contracts` at `price`
+    pub fn pnl_usdt(&self, contracts: f64, price: f64) -> f64 {
+        let diff = match self.side {
+            PositionSide::Long => price - self.entry_price,
+            PositionSide::Short => self.entry_price - price,
+        };
+        diff * contracts * self.contract_size
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.remaining <= QTY_EPSILON
+    }
+
+    /// Give-back from the best price since entry, in percent
+    fn drawdown_pct(&self, price: f64) -> f64 {
+        match self.side {
+            PositionSide::Long => (1.0 - price / self.best_price) * 100.0,
+            PositionSide::Short => (price / self.best_price - 1.0) * 100.0,
+        }
+    }
+
+    fn round_to_step(&self, contracts: f64) -> f64 {
+        if self.vol_unit <= 0.0 {
+            return contracts;
+        }
+        (contracts / self.vol_unit + QTY_EPSILON).floor() * self.vol_unit
+    }
+
+    /// Closes due at `price` and last/mark `ratio`; reduces `remaining` by them
+    pub fn evaluate(&mut self, ladder: &ExitLadderConfig, price: f64, ratio: f64) -> Vec<ExitOrder> {
+        let favorable = match self.side {
+            PositionSide::Long => price > self.best_price,
+            PositionSide::Short => price < self.best_price,
+        };
+        if favorable {
+            self.best_price = price;
+        }
+
+        let mut exits = Vec::new();
+        let profit = self.profit_pct(price);
+        while let Some(step) = ladder.steps.get(self.steps_taken).filter(|step| profit >= step.profit_pct) {
+            self.steps_taken += 1;
+            let contracts = self.round_to_step(self.contracts * step.close_pct / 100.0).min(self.remaining);
+            if contracts > QTY_EPSILON {
+                self.remaining -= contracts;
+                exits.push(ExitOrder {
+                    contracts,
+                    reason: ExitReason::Ladder(self.steps_taken),
+                    remaining: self.remaining,
+                });
+            }
+        }
+        if self.is_closed() {
+            return exits;
+        }
+
+        let armed = ladder.steps.is_empty() || self.steps_taken > 0;
+        let reason = if ladder.trailing_stop_pct.is_some_and(|trail| armed && self.drawdown_pct(price) >= trail) {
+            Some(ExitReason::TrailingStop)
+        } else if ladder.ratio_below.is_some_and(|level| ratio < level) {
+            Some(ExitReason::RatioNormalized)
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            exits.push(ExitOrder {
+                contracts: self.remaining,
+                reason,
+                remaining: 0.0,
+            });
+            self.remaining = 0.0;
+        }
+        exits
+    }
+}
+
+/// Open positions per symbol
+#[derive(Default)]
+pub struct PositionBook {
+    positions: Mutex<HashMap<String, Vec<Position>>>,
+}
+
+impl PositionBook {
+    pub fn open(&self, position: Position) {
+        self.positions.lock().unwrap().entry(position.symbol.clone()).or_default().push(position);
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.positions.lock().unwrap().contains_key(symbol)
+    }
+
+    /// Open positions across all symbols
+    pub fn len(&self) -> usize {
+        self.positions.lock().unwrap().values().map(Vec::len).sum()
+    }
+
+    /// Runs `f` on every open position of `symbol`, dropping those it leaves closed or
+    /// returns false for
+    pub fn update(&self, symbol: &str, mut f: impl FnMut(&mut Position) -> bool) {
+        let mut positions = self.positions.lock().unwrap();
+        let Some(open) = positions.get_mut(symbol) else {
+            return;
+        };
+        open.retain_mut(|position| f(position) && !position.is_closed());
+        if open.is_empty() {
+            positions.remove(symbol);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::models::ContractDetail;
+use thiserror::Error;
+
+/// Trading parameters of a futures contract needed to size orders
+#[derive(Debug, Clone)]
+pub struct ContractSpec {
+    pub symbol: String,
+    /// Amount of the base asset represented by one contract
+    pub contract_size: f64,
+    /// Minimum order volume in contracts
+    pub min_vol: f64,
+    /// Maximum order volume in contracts
+    pub max_vol: f64,
+    /// Volume step in contracts
+    pub vol_unit: f64,
+    /// Price tick size
+    pub price_unit: Option<f64>,
+}
+
+impl ContractSpec {
+    pub fn from_detail(detail: &ContractDetail) -> Option<Self> {
+        Some(Self {
+            symbol: detail.symbol.clone(),
+            contract_size: detail.contract_size?,
+            min_vol: detail.min_vol.unwrap_or(1.0),
+            max_vol: detail.max_vol.unwrap_or(f64::MAX),
+            vol_unit: detail.vol_unit.unwrap_or(1.0),
+            price_unit: detail.price_unit.filter(|unit| *unit > 0.0),
+        })
+    }
+
+    /// `price` on the tick grid, rounded up or down
+    pub fn round_price(&self, price: f64, up: bool) -> f64 {
+        match self.price_unit {
+            // Small epsilon so prices already on the grid stay where they are
+            Some(unit) if up => ((price / unit) - 1e-9).ceil() * unit,
+            Some(unit) => ((price / unit) + 1e-9).floor() * unit,
+            None => price,
+        }
+    }
+}
+
+/// Result of converting a USDT risk amount into a contract quantity
+#[derive(Debug, Clone)]
+pub struct OrderSize {
+    /// Number of contracts, rounded down to the volume step
+    pub contracts: f64,
+    /// Notional value of the rounded quantity in USDT
+    pub notional_usdt: f64,
+    /// True when the quantity was capped at the contract's maximum volume
+    pub capped: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum SizingError {
+    #[error("invalid price {0}")]
+    InvalidPrice(f64),
+    #[error("invalid contract spec for {0}")]
+    InvalidSpec(String),
+    #[error("{symbol}: {contracts} contracts is below the minimum order of {min_vol}")]
+    BelowMinimum {
+        symbol: String,
+        contracts: f64,
+        min_vol: f64,
+    },
+}
+
+/// Converts a USDT risk amount into a contract quantity at the given price.
+///
+/// The raw quantity is rounded down to the contract's volume step so the
+/// order never exceeds the configured risk, and capped at the maximum volume.
+pub fn size_for_risk(spec: &ContractSpec, risk_usdt: f64, price: f64) -> Result<OrderSize, SizingError> {
+    if !price.is_finite() || price <= 0.0 {
+        return Err(SizingError::InvalidPrice(price));
+    }
+    if spec.contract_size <= 0.0 || spec.vol_unit <= 0.0 {
+        return Err(SizingError::InvalidSpec(spec.symbol.clone()));
+    }
+
+    let contract_value = price * spec.contract_size;
+    let raw_contracts = risk_usdt / contract_value;
+
+    // Small epsilon so values like 2.9999999 round to the intended step
+    let steps = (raw_contracts / spec.vol_unit + 1e-9).floor();
+    let mut contracts = steps * spec.vol_unit;
+
+    let capped = contracts > spec.max_vol;
+    if capped {
+        contracts = (spec.max_vol / spec.vol_unit).floor() * spec.vol_unit;
+    }
+
+    if contracts < spec.min_vol || contracts <= 0.0 {
+        return Err(SizingError::BelowMinimum {
+            symbol: spec.symbol.clone(),
+            contracts,
+            min_vol: spec.min_vol,
+        });
+    }
+
+    Ok(OrderSize {
+        contracts,
+        notional_usdt: contracts * contract_value,
+        capped,
+    })
+}
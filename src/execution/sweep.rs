@@ -0,0 +1,79 @@
+use crate::api::{MexcRestClient, MexcSpotClient};
+use crate::config::SubAccountConfig;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const SWEEP_ASSET: &str = "USDT";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Periodically moves realized profit out of the trading sub-account.
+///
+/// Everything above `working_capital_usdt` in the sub-account's futures wallet
+/// is transferred to the master account, so a compromised or misbehaving
+/// trading key can never lose more than the working capital.
+pub struct ProfitSweeper {
+    config: SubAccountConfig,
+    sub_rest: Arc<MexcRestClient>,
+    master: MexcSpotClient,
+}
+
+impl ProfitSweeper {
+    pub fn new(config: SubAccountConfig, sub_rest: Arc<MexcRestClient>, master: MexcSpotClient) -> Self {
+        Self { config, sub_rest, master }
+    }
+
+    pub async fn run(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.sweep_interval_secs));
+        loop {
+            interval.tick().await;
+            self.sweep_once().await;
+        }
+    }
+
+    async fn sweep_once(&self) {
+        let asset = match self.sub_rest.get_account_asset(SWEEP_ASSET, REQUEST_TIMEOUT).await {
+            Ok(Some(asset)) => asset,
+            Ok(None) => {
+                warn!("[Sweep] No {} asset reported for sub-account {}", SWEEP_ASSET, self.config.name);
+                return;
+            }
+            Err(e) => {
+                warn!("[Sweep] Failed to read sub-account balance: {}", e);
+                return;
+            }
+        };
+
+        let excess = asset.available_balance - self.config.working_capital_usdt;
+        if excess < self.config.min_sweep_usdt {
+            info!(
+                "[Sweep] Sub-account {} holds {:.2} {} (equity {:.2}) - nothing to sweep",
+                self.config.name, asset.available_balance, asset.currency, asset.equity
+            );
+            return;
+        }
+
+        // Round down to cents so the transfer never exceeds what is available
+        let amount = (excess * 100.0).floor() / 100.0;
+        match self
+            .master
+            .transfer_from_sub_account(
+                &self.config.name,
+                "FUTURES",
+                &self.config.to_account_type,
+                SWEEP_ASSET,
+                amount,
+                REQUEST_TIMEOUT,
+            )
+            .await
+        {
+            Ok(transfer_id) => info!(
+                "[Sweep] Moved {:.2} {} from {} to master {} wallet (transfer {})",
+                amount, SWEEP_ASSET, self.config.name, self.config.to_account_type, transfer_id
+            ),
+            // Not retried: an ambiguous failure may have gone through, and the next
+            // sweep recomputes the excess from the actual balance anyway
+            Err(e) => warn!("[Sweep] Transfer of {:.2} {} failed: {}", amount, SWEEP_ASSET, e),
+        }
+    }
+}
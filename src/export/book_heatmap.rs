@@ -0,0 +1,157 @@
+use crate::config::BookHeatmapConfig;
+use crate::export::{csv_file_name, CsvFile};
+use crate::models::ProcessedOrderbook;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Resting liquidity of one price bucket at one sample
+#[derive(Debug, Clone, Copy)]
+pub struct HeatmapCell {
+    pub timestamp_ms: i64,
+    /// Lower bound of the bucket
+    pub price_level: f64,
+    /// Notional (USDT) of the bid and ask levels in the bucket
+    pub bid_usdt: f64,
+    pub ask_usdt: f64,
+}
+
+/// Time x price-level matrix of resting liquidity over an incident, for reconstructing
+/// book animations.
+///
+/// Prices are bucketed on a fixed grid whose width is `bucket_bps` of the mid price at the
+/// first sample, so buckets line up across the whole incident. Only non-empty buckets are
+/// kept (one row per sample and bucket).
+#[derive(Debug, Clone, Default)]
+pub struct BookHeatmap {
+    bucket_width: Option<f64>,
+    // Book timestamp of the last sample, so an unchanged book isn't sampled twice
+    last_book_ms: Option<i64>,
+    cells: Vec<HeatmapCell>,
+}
+
+impl BookHeatmap {
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn bucket_width(&self) -> Option<f64> {
+        self.bucket_width
+    }
+
+    pub fn sample_count(&self) -> usize {
+        let mut timestamps: Vec<i64> = self.cells.iter().map(|c| c.timestamp_ms).collect();
+        timestamps.dedup();
+        timestamps.len()
+    }
+
+    /// Adds the levels of `book` within `max_distance_pct` of its mid price
+    pub fn sample(&mut self, book: &ProcessedOrderbook, config: &BookHeatmapConfig) {
+        let timestamp_ms = book.timestamp.timestamp_millis();
+        if self.last_book_ms == Some(timestamp_ms) {
+            return;
+        }
+        let Some(mid) = book.calculate_mid_price().filter(|mid| *mid > 0.0) else {
+            return;
+        };
+        let width = *self.bucket_width.get_or_insert(mid * config.bucket_bps / 10_000.0);
+        if width <= 0.0 {
+            return;
+        }
+        self.last_book_ms = Some(timestamp_ms);
+
+        let (lower, upper) = (mid * (1.0 - config.max_distance_pct / 100.0), mid * (1.0 + config.max_distance_pct / 100.0));
+        let mut buckets: BTreeMap<i64, (f64, f64)> = BTreeMap::new();
+        for level in book.bids.iter().filter(|l| l.price >= lower) {
+            buckets.entry((level.price / width).floor() as i64).or_default().0 += level.price * level.quantity;
+        }
+        for level in book.asks.iter().filter(|l| l.price <= upper) {
+            buckets.entry((level.price / width).floor() as i64).or_default().1 += level.price * level.quantity;
+        }
+
+        self.cells.extend(buckets.into_iter().map(|(bucket, (bid_usdt, ask_usdt))| HeatmapCell {
+            timestamp_ms,
+            price_level: bucket as f64 * width,
+            bid_usdt,
+            ask_usdt,
+        }));
+    }
+}
+
+/// Writes `<prefix>_heatmap.csv` (`.csv.gz` with `compress`), and `<prefix>_heatmap.parquet`
+/// too when `parquet` is set and the binary has the `parquet-export` feature; returns the
+/// file names
+pub fn write_heatmap_files(
+    charts_dir: &Path,
+    prefix: &str,
+    heatmap: &BookHeatmap,
+    parquet: bool,
+    compress: bool,
+) -> Result<Vec<String>> {
+    let csv_filename = csv_file_name(&format!("{}_heatmap", prefix), compress);
+    write_heatmap_to_csv(&charts_dir.join(&csv_filename), heatmap, compress)?;
+
+    #[cfg(feature = "parquet-export")]
+    if parquet {
+        let parquet_filename = format!("{}_heatmap.parquet", prefix);
+        parquet_export::write(&charts_dir.join(&parquet_filename), heatmap)?;
+        return Ok(vec![csv_filename, parquet_filename]);
+    }
+    #[cfg(not(feature = "parquet-export"))]
+    let _ = parquet;
+
+    Ok(vec![csv_filename])
+}
+
+fn write_heatmap_to_csv(path: &Path, heatmap: &BookHeatmap, compress: bool) -> Result<()> {
+    let mut wtr = CsvFile::create(path, compress)?;
+    wtr.write_record(["timestamp_ms", "price_level", "bid_usdt", "ask_usdt"])?;
+    for cell in &heatmap.cells {
+        wtr.write_record([
+            cell.timestamp_ms.to_string(),
+            cell.price_level.to_string(),
+            format!("{:.2}", cell.bid_usdt),
+            format!("{:.2}", cell.ask_usdt),
+        ])?;
+    }
+    wtr.finish()?;
+    Ok(())
+}
+
+#[cfg(feature = "parquet-export")]
+mod parquet_export {
+    use super::*;
+    use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch};
+    use arrow_schema::{DataType, Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::properties::WriterProperties;
+    use std::fs;
+    use std::sync::Arc;
+
+    pub fn write(path: &Path, heatmap: &BookHeatmap) -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("timestamp_ms", DataType::Int64, false),
+            Field::new("price_level", DataType::Float64, false),
+            Field::new("bid_usdt", DataType::Float64, false),
+            Field::new("ask_usdt", DataType::Float64, false),
+        ]));
+
+        let cells = &heatmap.cells;
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from_iter_values(cells.iter().map(|c| c.timestamp_ms))),
+            Arc::new(Float64Array::from_iter_values(cells.iter().map(|c| c.price_level))),
+            Arc::new(Float64Array::from_iter_values(cells.iter().map(|c| c.bid_usdt))),
+            Arc::new(Float64Array::from_iter_values(cells.iter().map(|c| c.ask_usdt))),
+        ];
+        let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let mut writer = ArrowWriter::try_new(fs::File::create(path)?, schema, Some(properties))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+}
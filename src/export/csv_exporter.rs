@@ -1,91 +1,279 @@
-use crate::models::market_data::{Candle, SymbolData};
+use crate::api::MexcRestClient;
+use crate::config::{BookHeatmapConfig, KlineCheckConfig, StrategyRecordingConfig};
+use crate::export::{compare_klines, csv_file_name, write_heatmap_files, BookHeatmap, CsvFile, RecordingSettings};
+use crate::models::Symbol;
+use crate::models::market_data::{aggregate_candles, Candle, CandleSeries, DepthSample, SymbolData, CANDLE_WINDOW_MS};
+use crate::utils::{DiskWriter, TaskSupervisor};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::time::{sleep, Duration};
-use tracing::{error, info};
+use tokio::time::{sleep, sleep_until, Duration, Instant};
+use tracing::{error, info, warn};
+
+/// A strategy that joined an incident recording
+#[derive(Debug, Clone, Serialize)]
+struct StrategyTrigger {
+    strategy: String,
+    episode_id: String,
+    triggered_at: DateTime<Utc>,
+    /// The strategy's own post-anomaly tail, if it overrides `[csv_export]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_anomaly_recording_secs: Option<i64>,
+}
 
+/// One recording per symbol incident, shared by every strategy that triggers while it is open
 #[derive(Debug, Clone)]
 struct RecordingSession {
     symbol: String,
-    strategy_name: String,
+    // Episode that opened the incident; names the recording files
+    incident_id: String,
     start_time: DateTime<Utc>,
     anomaly_ended: Option<DateTime<Utc>>,
-    last_price_candles: Vec<Candle>,
-    mark_price_candles: Vec<Candle>,
+    triggers: Vec<StrategyTrigger>,
+    // Strategies whose episode is still running; the incident ends when this is empty
+    active_strategies: HashSet<String>,
+    // Bumped whenever the incident ends, so a stale finalize task can tell it was superseded
+    end_generation: u64,
+    candles: CandleSeries,
+    heatmap: BookHeatmap,
+    // Start (ms) of the fine candle open at the trigger, if fine candles are kept
+    fine_trigger_ms: Option<i64>,
 }
 
 impl RecordingSession {
-    fn new(symbol: String, strategy_name: String, pre_buffer_candles: (Vec<Candle>, Vec<Candle>)) -> Self {
-        Self {
+    fn new(
+        symbol: String,
+        strategy_name: String,
+        episode_id: String,
+        pre_buffer_candles: CandleSeries,
+        durations: StrategyRecordingConfig,
+    ) -> Self {
+        let fine_trigger_ms = pre_buffer_candles
+            .fine_last_price
+            .last()
+            .or(pre_buffer_candles.fine_mark_price.last())
+            .map(|c| c.timestamp_ms);
+        let mut session = Self {
             symbol,
-            strategy_name,
+            incident_id: episode_id.clone(),
             start_time: Utc::now(),
             anomaly_ended: None,
-            last_price_candles: pre_buffer_candles.0,
-            mark_price_candles: pre_buffer_candles.1,
+            triggers: Vec::new(),
+            active_strategies: HashSet::new(),
+            end_generation: 0,
+            candles: pre_buffer_candles,
+            heatmap: BookHeatmap::default(),
+            fine_trigger_ms,
+        };
+        session.add_strategy(strategy_name, episode_id, durations);
+        session
+    }
+
+    fn add_strategy(&mut self, strategy_name: String, episode_id: String, durations: StrategyRecordingConfig) {
+        if !self.triggers.iter().any(|t| t.strategy == strategy_name) {
+            self.triggers.push(StrategyTrigger {
+                strategy: strategy_name.clone(),
+                episode_id,
+                triggered_at: Utc::now(),
+                post_anomaly_recording_secs: durations.post_anomaly_recording_secs,
+            });
         }
+        self.active_strategies.insert(strategy_name);
+        // A new trigger during the post-anomaly tail keeps the incident open
+        self.anomaly_ended = None;
+    }
+
+    /// Common prefix of every file written for this incident
+    fn file_prefix(&self) -> String {
+        format!("{}_{}_{}", self.symbol, self.start_time.format("%Y%m%d_%H%M%S"), self.incident_id)
+    }
+
+    /// Longest post-anomaly tail wanted by any strategy in the incident
+    fn post_anomaly_recording_secs(&self, default_secs: i64) -> i64 {
+        self.triggers
+            .iter()
+            .map(|t| t.post_anomaly_recording_secs.unwrap_or(default_secs))
+            .max()
+            .unwrap_or(default_secs)
+    }
+
+    fn strategy_names(&self) -> Vec<&str> {
+        self.triggers.iter().map(|t| t.strategy.as_str()).collect()
     }
 
-    fn add_candles(&mut self, candles: (Vec<Candle>, Vec<Candle>)) {
-        self.last_price_candles.extend(candles.0);
-        self.mark_price_candles.extend(candles.1);
+    fn add_candles(&mut self, candles: CandleSeries) {
+        self.candles.extend(candles);
     }
 }
 
+/// Summary written next to the CSV files of an incident
+#[derive(Debug, Serialize)]
+struct IncidentManifest<'a> {
+    incident_id: &'a str,
+    symbol: &'a str,
+    strategies: Vec<&'a str>,
+    triggers: &'a [StrategyTrigger],
+    start_time: DateTime<Utc>,
+    anomaly_ended: Option<DateTime<Utc>>,
+    candle_count: usize,
+    /// Price bucket width of the heatmap file, if one was written
+    #[serde(skip_serializing_if = "Option::is_none")]
+    heatmap_bucket_width: Option<f64>,
+    /// Span written at the fine resolution, if fine candles were kept
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fine_candles: Option<FineSpan>,
+    files: Vec<String>,
+}
+
+/// The part of an incident's candle CSVs at the fine resolution
+#[derive(Debug, Clone, Copy, Serialize)]
+struct FineSpan {
+    candle_ms: i64,
+    start_ms: i64,
+    end_ms: i64,
+}
+
 #[derive(Clone)]
 pub struct CsvExporter {
     charts_dir: PathBuf,
-    post_anomaly_recording_secs: i64,
+    settings: Arc<RecordingSettings>,
     active_recordings: Arc<DashMap<String, RecordingSession>>,
-    symbol_data: Arc<DashMap<String, SymbolData>>,
+    symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+    kline_check: Option<(Arc<MexcRestClient>, KlineCheckConfig)>,
+    book_heatmap: Option<BookHeatmapConfig>,
+    // Extra candle resolutions (ms) rolled up from the 500ms candles
+    resolutions_ms: Arc<Vec<i64>>,
+    // Write `.csv.gz` instead of `.csv`
+    compress: bool,
+    // Fine candle width (ms) and the seconds around the trigger written with it, if fine
+    // candles are kept
+    fine_candles: Option<(i64, i64)>,
+    supervisor: TaskSupervisor,
+    disk: Arc<DiskWriter>,
 }
 
 impl CsvExporter {
     pub fn new(
         charts_dir: &str,
-        post_anomaly_recording_secs: i64,
-        symbol_data: Arc<DashMap<String, SymbolData>>,
+        settings: Arc<RecordingSettings>,
+        symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+        supervisor: TaskSupervisor,
+        disk: Arc<DiskWriter>,
     ) -> Result<Self> {
         // Create charts directory if it doesn't exist
         fs::create_dir_all(charts_dir)?;
 
         Ok(Self {
             charts_dir: PathBuf::from(charts_dir),
-            post_anomaly_recording_secs,
+            settings,
             active_recordings: Arc::new(DashMap::new()),
             symbol_data,
+            kline_check: None,
+            book_heatmap: None,
+            resolutions_ms: Arc::new(Vec::new()),
+            compress: false,
+            fine_candles: None,
+            supervisor,
+            disk,
         })
     }
 
-    pub fn start_recording(&self, symbol: &str, strategy_name: &str, pre_buffer_candles: (Vec<Candle>, Vec<Candle>)) {
-        info!("[CsvExporter] start_recording() called for {} ({})", symbol, strategy_name);
+    /// Cross-check every finalized incident against the exchange's official 1m klines
+    pub fn with_kline_check(mut self, rest: Arc<MexcRestClient>, config: KlineCheckConfig) -> Self {
+        self.kline_check = Some((rest, config));
+        self
+    }
+
+    /// Sample the orderbook of every recorded incident into a liquidity heatmap
+    pub fn with_book_heatmap(mut self, config: BookHeatmapConfig) -> Self {
+        self.book_heatmap = Some(config);
+        self
+    }
+
+    /// Also write each incident's candles at these resolutions (ms), e.g. 5000 for 5s candles
+    pub fn with_resolutions(mut self, resolutions_ms: &[i64]) -> Self {
+        self.resolutions_ms = Arc::new(resolutions_ms.iter().copied().filter(|ms| *ms > 0).collect());
+        self
+    }
+
+    /// Write the incident CSVs gzip-compressed, as `.csv.gz`
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Write the `window_secs` around each trigger with the symbols' `candle_ms` candles
+    /// instead of the 500ms ones
+    pub fn with_fine_candles(mut self, candle_ms: i64, window_secs: i64) -> Self {
+        self.fine_candles = Some((candle_ms, window_secs.max(1)));
+        self
+    }
+
+    /// Incidents being recorded, including those in their post-anomaly tail
+    pub fn active_recording_count(&self) -> usize {
+        self.active_recordings.len()
+    }
 
-        let recording_key = format!("{}_{}", symbol, strategy_name);
+    /// Seconds of candles before the trigger that a new recording of a strategy with
+    /// `durations` starts with
+    pub fn pre_buffer_secs(&self, durations: &StrategyRecordingConfig) -> i64 {
+        durations
+            .pre_anomaly_buffer_secs
+            .unwrap_or_else(|| self.settings.pre_anomaly_buffer_secs())
+    }
+
+    pub fn start_recording(
+        &self,
+        symbol: &str,
+        strategy_name: &str,
+        episode_id: &str,
+        pre_buffer_candles: CandleSeries,
+        durations: StrategyRecordingConfig,
+    ) {
+        info!("[CsvExporter] start_recording() called for {} ({}, episode {})", symbol, strategy_name, episode_id);
+
+        // Join the incident already being recorded for this symbol, if any
+        if let Some(mut session) = self.active_recordings.get_mut(symbol) {
+            session.add_strategy(strategy_name.to_string(), episode_id.to_string(), durations);
+            info!(
+                "[CsvExporter] {} (episode {}) joined incident {} for {} - strategies: {:?}",
+                strategy_name, episode_id, session.incident_id, symbol, session.strategy_names()
+            );
+            return;
+        }
 
-        // Check if already recording for this symbol+strategy combination
-        if self.active_recordings.contains_key(&recording_key) {
-            info!("[CsvExporter] Already recording for {} ({})", symbol, strategy_name);
+        if !self.settings.enabled() {
+            info!("[CsvExporter] CSV export is disabled - not recording {} ({})", symbol, strategy_name);
             return;
         }
 
         info!(
-            "[CsvExporter] Received {} last_price candles and {} mark_price candles as pre-buffer",
-            pre_buffer_candles.0.len(), pre_buffer_candles.1.len()
+            "[CsvExporter] Received {} last_price candles, {} mark_price candles and {} depth samples as pre-buffer",
+            pre_buffer_candles.last_price.len(), pre_buffer_candles.mark_price.len(), pre_buffer_candles.depth.len()
         );
 
-        info!("[CsvExporter] Creating recording session for {}", recording_key);
+        info!("[CsvExporter] Creating incident recording session for {}", symbol);
 
         let session = RecordingSession::new(
             symbol.to_string(),
             strategy_name.to_string(),
+            episode_id.to_string(),
             pre_buffer_candles,
+            durations,
         );
 
-        self.active_recordings.insert(recording_key.clone(), session);
+        self.active_recordings.insert(symbol.to_string(), session);
+        if let Some(config) = &self.book_heatmap {
+            self.sample_book_heatmap(symbol, episode_id, config.clone());
+        }
+        if let Some((_, window_secs)) = self.fine_candles {
+            self.collect_fine_candles(symbol, episode_id, window_secs);
+        }
 
         info!(
             "[CsvExporter] ✅ Recording session started for {} ({}) - Total active recordings: {}",
@@ -93,70 +281,147 @@ impl CsvExporter {
         );
     }
 
-    pub fn update_recording(&self, symbol: &str) {
-        // Update all active recordings for this symbol
-        let recordings: Vec<String> = self
-            .active_recordings
-            .iter()
-            .filter(|entry| entry.value().symbol == symbol && entry.value().anomaly_ended.is_none())
-            .map(|entry| entry.key().clone())
-            .collect();
-
-        for recording_key in recordings {
-            if let Some(data) = self.symbol_data.get(symbol) {
-                // Get the latest completed candles
-                let new_candles = data.candle_buffer.get_all_completed_candles();
+    /// Samples the symbol's orderbook into the incident's heatmap until the incident is written
+    fn sample_book_heatmap(&self, symbol: &str, incident_id: &str, config: BookHeatmapConfig) {
+        let exporter = self.clone();
+        let symbol = symbol.to_string();
+        let incident_id = incident_id.to_string();
+        self.supervisor.supervise(format!("book-heatmap-{}", symbol), move || {
+            let exporter = exporter.clone();
+            let symbol = symbol.clone();
+            let incident_id = incident_id.clone();
+            let config = config.clone();
+            async move {
+                let mut interval = tokio::time::interval(Duration::from_millis(config.sample_interval_ms.max(50)));
+                loop {
+                    interval.tick().await;
+                    let Some(data) = exporter.symbol_data.get(symbol.as_str()) else {
+                        return;
+                    };
+                    // Stops once the incident is finalized (or replaced by a newer one)
+                    let Some(mut session) = exporter.active_recordings.get_mut(&symbol) else {
+                        return;
+                    };
+                    if session.incident_id != incident_id {
+                        return;
+                    }
+                    if let Some(book) = &data.orderbook {
+                        session.heatmap.sample(book, &config);
+                    }
+                }
+            }
+        });
+    }
 
-                if let Some(mut session) = self.active_recordings.get_mut(&recording_key) {
-                    session.add_candles(new_candles);
+    /// Picks up the fine candles after the trigger once the fine window has passed, as the
+    /// symbol's fine buffer only holds a few seconds
+    fn collect_fine_candles(&self, symbol: &str, incident_id: &str, window_secs: i64) {
+        let exporter = self.clone();
+        let symbol = symbol.to_string();
+        let incident_id = incident_id.to_string();
+        self.supervisor.supervise(format!("fine-candles-{}", symbol), move || {
+            let exporter = exporter.clone();
+            let symbol = symbol.clone();
+            let incident_id = incident_id.clone();
+            async move {
+                sleep(Duration::from_millis((window_secs * 1000 / 2 + 1000) as u64)).await;
+                let Some(data) = exporter.symbol_data.get(symbol.as_str()) else {
+                    return;
+                };
+                let candles = data.candle_buffer.get_all_completed_candles();
+                let Some(mut session) = exporter.active_recordings.get_mut(&symbol) else {
+                    return;
+                };
+                if session.incident_id == incident_id {
+                    session.candles.fine_last_price.extend(candles.fine_last_price);
+                    session.candles.fine_mark_price.extend(candles.fine_mark_price);
                 }
             }
+        });
+    }
+
+    /// Moves the symbol's open incident to the name its contract was renamed to, with the
+    /// candles recorded so far multiplied by `price_factor`. An incident already in its
+    /// post-anomaly tail is finished under the old name.
+    pub fn rename_recording(&self, from: &str, to: &str, price_factor: f64) {
+        if self.active_recordings.contains_key(to) {
+            return;
         }
+        let Some((_, mut session)) = self.active_recordings.remove_if(from, |_, session| session.anomaly_ended.is_none()) else {
+            return;
+        };
+        session.symbol = to.to_string();
+        session.candles.scale_prices(price_factor);
+        info!("[CsvExporter] Incident {} continues as {} (was {})", session.incident_id, to, from);
+        self.active_recordings.insert(to.to_string(), session);
     }
 
     pub fn mark_anomaly_ended(&self, symbol: &str, strategy_name: &str) {
         info!("[CsvExporter] mark_anomaly_ended() called for {} ({})", symbol, strategy_name);
 
-        let recording_key = format!("{}_{}", symbol, strategy_name);
+        let (generation, post_secs) = if let Some(mut session) = self.active_recordings.get_mut(symbol) {
+            session.active_strategies.remove(strategy_name);
+
+            if !session.active_strategies.is_empty() {
+                info!(
+                    "[CsvExporter] {} ended for {}, incident still open for {:?}",
+                    strategy_name, symbol, session.active_strategies
+                );
+                return;
+            }
 
-        if let Some(mut session) = self.active_recordings.get_mut(&recording_key) {
             session.anomaly_ended = Some(Utc::now());
+            session.end_generation += 1;
+            let post_secs = session.post_anomaly_recording_secs(self.settings.post_anomaly_recording_secs());
             info!(
-                "[CsvExporter] ✅ Marked anomaly ended for {} ({}), will continue recording for {} more seconds",
-                symbol, strategy_name, self.post_anomaly_recording_secs
+                "[CsvExporter] ✅ Marked incident ended for {} (strategies: {:?}), will continue recording for {} more seconds",
+                symbol, session.strategy_names(), post_secs
             );
+            (session.end_generation, post_secs)
         } else {
-            info!("[CsvExporter] WARNING: No active recording found for {}", recording_key);
+            info!("[CsvExporter] WARNING: No active recording found for {}", symbol);
             return;
-        }
+        };
 
         // Spawn background task to finalize after delay
-        info!("[CsvExporter] Spawning background task to finalize recording after {} seconds", self.post_anomaly_recording_secs);
+        info!("[CsvExporter] Spawning background task to finalize recording after {} seconds", post_secs);
 
         let exporter = self.clone();
         let symbol_owned = symbol.to_string();
-        let strategy_owned = strategy_name.to_string();
-        let post_secs = self.post_anomaly_recording_secs;
-
-        tokio::spawn(async move {
-            info!("[CsvExporter] Background task started - waiting {} seconds before finalizing {}", post_secs, symbol_owned);
-            sleep(Duration::from_secs(post_secs as u64)).await;
-            info!("[CsvExporter] Wait complete - now finalizing recording for {}", symbol_owned);
-
-            if let Err(e) = exporter.finalize_recording(&symbol_owned, &strategy_owned).await {
-                error!("[CsvExporter] Failed to finalize recording for {} ({}): {}", symbol_owned, strategy_owned, e);
-            } else {
-                info!("[CsvExporter] Successfully finalized recording for {} ({})", symbol_owned, strategy_owned);
+        // A restarted task resumes waiting for the original deadline rather than starting over
+        let deadline = Instant::now() + Duration::from_secs(post_secs as u64);
+
+        self.supervisor.supervise(format!("finalize-{}", symbol), move || {
+            let exporter = exporter.clone();
+            let symbol_owned = symbol_owned.clone();
+            async move {
+                info!("[CsvExporter] Background task started - waiting {} seconds before finalizing {}", post_secs, symbol_owned);
+                sleep_until(deadline).await;
+                info!("[CsvExporter] Wait complete - now finalizing recording for {}", symbol_owned);
+
+                if let Err(e) = exporter.finalize_recording(&symbol_owned, generation).await {
+                    error!("[CsvExporter] Failed to finalize recording for {}: {}", symbol_owned, e);
+                }
             }
         });
 
-        info!("[CsvExporter] Background task spawned for {} ({})", symbol, strategy_name);
+        info!("[CsvExporter] Background task spawned for {}", symbol);
     }
 
-    async fn finalize_recording(&self, symbol: &str, strategy_name: &str) -> Result<()> {
-        info!("[CsvExporter] finalize_recording() called for {} ({})", symbol, strategy_name);
+    async fn finalize_recording(&self, symbol: &str, generation: u64) -> Result<()> {
+        info!("[CsvExporter] finalize_recording() called for {}", symbol);
+
+        // The incident may have been re-opened by another trigger during the tail
+        let still_current = self
+            .active_recordings
+            .get(symbol)
+            .map(|session| session.anomaly_ended.is_some() && session.end_generation == generation)
+            .unwrap_or(false);
 
-        let recording_key = format!("{}_{}", symbol, strategy_name);
+        if !still_current {
+            info!("[CsvExporter] Incident for {} was extended by a new trigger - skipping this finalize", symbol);
+            return Ok(());
+        }
 
         // Get the final candles from the buffer
         info!("[CsvExporter] Getting final candles from buffer...");
@@ -164,18 +429,18 @@ impl CsvExporter {
             let final_candles = data.candle_buffer.get_all_completed_candles();
             info!(
                 "[CsvExporter] Retrieved {} final last_price candles and {} mark_price candles",
-                final_candles.0.len(), final_candles.1.len()
+                final_candles.last_price.len(), final_candles.mark_price.len()
             );
 
-            if let Some(mut session) = self.active_recordings.get_mut(&recording_key) {
-                let before_count = session.last_price_candles.len();
+            if let Some(mut session) = self.active_recordings.get_mut(symbol) {
+                let before_count = session.candles.last_price.len();
                 session.add_candles(final_candles);
                 info!(
                     "[CsvExporter] Added final candles - session now has {} candles (was {})",
-                    session.last_price_candles.len(), before_count
+                    session.candles.last_price.len(), before_count
                 );
             } else {
-                info!("[CsvExporter] WARNING: Could not find recording session {}", recording_key);
+                info!("[CsvExporter] WARNING: Could not find recording session {}", symbol);
             }
         } else {
             info!("[CsvExporter] WARNING: Could not find symbol data for {}", symbol);
@@ -183,103 +448,296 @@ impl CsvExporter {
 
         // Remove the session and write CSV files
         info!("[CsvExporter] Removing recording session and writing CSV files...");
-        if let Some((_, session)) = self.active_recordings.remove(&recording_key) {
+        if let Some((_, session)) = self.active_recordings.remove(symbol) {
             info!(
                 "[CsvExporter] Writing CSV files with {} last_price candles and {} mark_price candles",
-                session.last_price_candles.len(),
-                session.mark_price_candles.len()
+                session.candles.last_price.len(),
+                session.candles.mark_price.len()
             );
 
-            self.write_csv_files(&session)?;
+            let session = Arc::new(session);
+            let charts_dir = self.charts_dir.clone();
+            let resolutions_ms = self.resolutions_ms.clone();
+            let fine_candles = self.fine_candles;
+            let job_session = session.clone();
+            let heatmap_parquet = self.book_heatmap.as_ref().is_some_and(|config| config.parquet);
+            let compress = self.compress;
+            self.disk
+                .write(format!("chart recording {}", session.file_prefix()), move || {
+                    write_csv_files(
+                        &charts_dir,
+                        &job_session,
+                        &resolutions_ms,
+                        fine_candles,
+                        heatmap_parquet,
+                        compress,
+                    )
+                })
+                .await;
 
             info!(
-                "[CsvExporter] ✅ Finalized recording for {} ({}) - wrote {} candles to CSV files",
+                "[CsvExporter] ✅ Finalized incident recording for {} ({:?}) - queued {} candles for CSV files",
                 symbol,
-                strategy_name,
-                session.last_price_candles.len()
+                session.strategy_names(),
+                session.candles.last_price.len()
             );
+
+            if self.kline_check.is_some() {
+                let exporter = self.clone();
+                self.supervisor.supervise(format!("kline-check-{}", symbol), move || {
+                    let exporter = exporter.clone();
+                    let session = session.clone();
+                    async move {
+                        if let Err(e) = exporter.cross_check_klines(&session).await {
+                            warn!("[CsvExporter] Kline cross-check failed for {}: {:?}", session.symbol, e);
+                        }
+                    }
+                });
+            }
         } else {
-            info!("[CsvExporter] WARNING: No recording session found to remove for {}", recording_key);
+            info!("[CsvExporter] WARNING: No recording session found to remove for {}", symbol);
         }
 
         Ok(())
     }
 
-    fn write_csv_files(&self, session: &RecordingSession) -> Result<()> {
-        info!("[CsvExporter] write_csv_files() called for {} ({})", session.symbol, session.strategy_name);
+    /// Fetches the official 1m klines covering the incident and stores them next to the
+    /// local candles, together with a report of any minutes that disagree
+    async fn cross_check_klines(&self, session: &RecordingSession) -> Result<()> {
+        let Some((rest, config)) = &self.kline_check else {
+            return Ok(());
+        };
+        let (Some(first), Some(last)) = (session.candles.last_price.first(), session.candles.last_price.last()) else {
+            return Ok(());
+        };
+
+        // Klines are only final once the last recorded minute has closed
+        let last_minute_close_ms = last.timestamp_ms - last.timestamp_ms.rem_euclid(60_000) + 60_000;
+        let wait_ms = (last_minute_close_ms - Utc::now().timestamp_millis()).max(0) as u64
+            + config.settle_delay_secs * 1000;
+        sleep(Duration::from_millis(wait_ms)).await;
+
+        let exchange = rest
+            .get_klines_1m(&session.symbol, first.timestamp_ms / 1000, last.timestamp_ms / 1000)
+            .await?;
+        let report = compare_klines(&session.symbol, &session.candles.last_price, &exchange, config.tolerance_pct);
+
+        let prefix = session.file_prefix();
+        let klines_path = self.charts_dir.join(csv_file_name(&format!("{}_exchange_klines", prefix), self.compress));
+        let report_path = self.charts_dir.join(format!("{}_kline_check.json", prefix));
+        let report_json = serde_json::to_string_pretty(&report)?;
+        let compress = self.compress;
+        self.disk
+            .write(format!("kline check {}", prefix), move || {
+                write_candles_to_csv(&klines_path, &exchange, compress)?;
+                fs::write(report_path, report_json)?;
+                Ok(())
+            })
+            .await;
+
+        if report.is_consistent() {
+            info!(
+                "[CsvExporter] Kline cross-check OK for {} - {} minutes match the exchange within {}%",
+                session.symbol, report.minutes_compared, config.tolerance_pct
+            );
+        } else {
+            warn!(
+                "[CsvExporter] ⚠️ Kline cross-check for {}: {} discrepancies, {} minutes missing locally, {} missing on exchange",
+                session.symbol,
+                report.discrepancies.len(),
+                report.missing_local.len(),
+                report.missing_exchange.len()
+            );
+        }
 
-        // Generate filename with datetime
-        let datetime_str = session.start_time.format("%Y%m%d_%H%M%S").to_string();
-        let last_price_filename = format!(
-            "{}_{}_{}_{}.csv",
-            session.symbol, session.strategy_name, datetime_str, "lastprice"
-        );
-        let mark_price_filename = format!(
-            "{}_{}_{}_{}.csv",
-            session.symbol, session.strategy_name, datetime_str, "fairprice"
-        );
+        Ok(())
+    }
 
-        info!("[CsvExporter] Generated filenames: {} and {}", last_price_filename, mark_price_filename);
+    /// Whether any recording of `symbol` is still running
+    pub fn has_recording(&self, symbol: &str) -> bool {
+        self.active_recordings.contains_key(symbol)
+    }
 
-        // Write last_price CSV
-        let last_price_path = self.charts_dir.join(&last_price_filename);
-        info!("[CsvExporter] Writing last_price CSV to: {}", last_price_path.display());
-        self.write_candles_to_csv(&last_price_path, &session.last_price_candles)?;
-        info!("[CsvExporter] ✅ Successfully wrote last_price CSV");
+}
 
-        // Write mark_price (fair_price) CSV
-        let mark_price_path = self.charts_dir.join(&mark_price_filename);
-        info!("[CsvExporter] Writing mark_price CSV to: {}", mark_price_path.display());
-        self.write_candles_to_csv(&mark_price_path, &session.mark_price_candles)?;
-        info!("[CsvExporter] ✅ Successfully wrote mark_price CSV");
+/// Writes an incident's candle CSVs and manifest into `charts_dir`
+fn write_csv_files(
+    charts_dir: &Path,
+    session: &RecordingSession,
+    resolutions_ms: &[i64],
+    fine_candles: Option<(i64, i64)>,
+    heatmap_parquet: bool,
+    compress: bool,
+) -> Result<()> {
+    info!("[CsvExporter] write_csv_files() called for {} ({:?})", session.symbol, session.strategy_names());
+
+    // Generate filenames from the datetime and the id of the episode that opened the incident
+    let prefix = session.file_prefix();
+    let last_price_filename = csv_file_name(&format!("{}_{}", prefix, "lastprice"), compress);
+    let mark_price_filename = csv_file_name(&format!("{}_{}", prefix, "fairprice"), compress);
+    let manifest_filename = format!("{}_{}.json", prefix, "manifest");
+
+    info!("[CsvExporter] Generated filenames: {} and {}", last_price_filename, mark_price_filename);
+
+    // Fine candles replace the 500ms ones around the trigger
+    let fine_span = fine_candles.and_then(|(candle_ms, window_secs)| fine_span(session, candle_ms, window_secs));
+    let (last_price, mark_price) = match fine_span {
+        Some(span) => (
+            with_fine_span(&session.candles.last_price, &session.candles.fine_last_price, span),
+            with_fine_span(&session.candles.mark_price, &session.candles.fine_mark_price, span),
+        ),
+        None => (session.candles.last_price.clone(), session.candles.mark_price.clone()),
+    };
+
+    // Write last_price CSV
+    let last_price_path = charts_dir.join(&last_price_filename);
+    info!("[CsvExporter] Writing last_price CSV to: {}", last_price_path.display());
+    write_candles_to_csv(&last_price_path, &last_price, compress)?;
+    info!("[CsvExporter] ✅ Successfully wrote last_price CSV");
+
+    // Write mark_price (fair_price) CSV
+    let mark_price_path = charts_dir.join(&mark_price_filename);
+    info!("[CsvExporter] Writing mark_price CSV to: {}", mark_price_path.display());
+    write_candles_to_csv(&mark_price_path, &mark_price, compress)?;
+    info!("[CsvExporter] ✅ Successfully wrote mark_price CSV");
+
+    let mut files = vec![last_price_filename, mark_price_filename];
+
+    // Coarser candles rolled up from the 500ms (and fine) ones
+    for &window_ms in resolutions_ms {
+        let label = resolution_label(window_ms);
+        for (series, candles) in [("lastprice", &last_price), ("fairprice", &mark_price)] {
+            let filename = csv_file_name(&format!("{}_{}_{}", prefix, series, label), compress);
+            write_candles_to_csv(&charts_dir.join(&filename), &aggregate_candles(candles, window_ms), compress)?;
+            files.push(filename);
+        }
+    }
 
-        info!(
-            "[CsvExporter] ✅✅ Wrote both CSV files for {} ({}):\n  - {}\n  - {}",
-            session.symbol,
-            session.strategy_name,
-            last_price_path.display(),
-            mark_price_path.display()
-        );
+    // Book depth at each candle close; absent while orderbooks are disabled
+    if !session.candles.depth.is_empty() {
+        let depth_filename = csv_file_name(&format!("{}_{}", prefix, "depth"), compress);
+        write_depth_to_csv(&charts_dir.join(&depth_filename), &session.candles.depth, compress)?;
+        info!("[CsvExporter] ✅ Successfully wrote depth CSV ({} samples)", session.candles.depth.len());
+        files.push(depth_filename);
+    }
 
-        Ok(())
+    // Resting liquidity per price bucket; only sampled with [book_heatmap] enabled
+    if !session.heatmap.is_empty() {
+        files.extend(write_heatmap_files(charts_dir, &prefix, &session.heatmap, heatmap_parquet, compress)?);
+        info!("[CsvExporter] ✅ Successfully wrote heatmap ({} samples)", session.heatmap.sample_count());
     }
 
-    fn write_candles_to_csv(&self, path: &PathBuf, candles: &[Candle]) -> Result<()> {
-        info!("[CsvExporter] write_candles_to_csv() - Writing {} candles to {}", candles.len(), path.display());
+    // Write manifest listing every strategy that contributed to the incident
+    let manifest = IncidentManifest {
+        incident_id: &session.incident_id,
+        symbol: &session.symbol,
+        strategies: session.strategy_names(),
+        triggers: &session.triggers,
+        start_time: session.start_time,
+        anomaly_ended: session.anomaly_ended,
+        candle_count: session.candles.last_price.len(),
+        heatmap_bucket_width: session.heatmap.bucket_width(),
+        fine_candles: fine_span,
+        files,
+    };
+    let manifest_path = charts_dir.join(&manifest_filename);
+    fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+
+    info!(
+        "[CsvExporter] ✅✅ Wrote incident files for {} ({:?}):\n  - {}\n  - {}\n  - {}",
+        session.symbol,
+        session.strategy_names(),
+        last_price_path.display(),
+        mark_price_path.display(),
+        manifest_path.display()
+    );
+
+    Ok(())
+}
 
-        let mut wtr = csv::Writer::from_path(path)?;
-        info!("[CsvExporter] CSV writer created successfully");
+/// The `window_secs` centered on the trigger, widened to whole 500ms candles; None when
+/// the incident has no fine candles
+fn fine_span(session: &RecordingSession, candle_ms: i64, window_secs: i64) -> Option<FineSpan> {
+    let trigger_ms = session.fine_trigger_ms?;
+    let half_ms = window_secs * 1000 / 2;
+    let start_ms = (trigger_ms - half_ms).div_euclid(CANDLE_WINDOW_MS) * CANDLE_WINDOW_MS;
+    let end_ms = (trigger_ms + half_ms + CANDLE_WINDOW_MS - 1).div_euclid(CANDLE_WINDOW_MS) * CANDLE_WINDOW_MS;
+    Some(FineSpan {
+        candle_ms,
+        start_ms,
+        end_ms,
+    })
+}
 
-        // Write header
-        wtr.write_record(&["timestamp_ms", "open", "high", "low", "close", "volume"])?;
-        info!("[CsvExporter] CSV header written");
+/// The coarse candles outside `span` and the fine ones inside it, in time order. Fine
+/// candles collected more than once keep their latest state.
+fn with_fine_span(coarse: &[Candle], fine: &[Candle], span: FineSpan) -> Vec<Candle> {
+    let inside = |c: &Candle| c.timestamp_ms >= span.start_ms && c.timestamp_ms < span.end_ms;
+    let fine: BTreeMap<i64, &Candle> = fine.iter().filter(|c| inside(c)).map(|c| (c.timestamp_ms, c)).collect();
 
-        // Write candle data
-        for (i, candle) in candles.iter().enumerate() {
-            wtr.write_record(&[
-                candle.timestamp_ms.to_string(),
-                candle.open.to_string(),
-                candle.high.to_string(),
-                candle.low.to_string(),
-                candle.close.to_string(),
-                candle.volume.to_string(),
-            ])?;
+    let mut candles: Vec<Candle> = coarse.iter().filter(|c| !inside(c)).cloned().collect();
+    candles.extend(fine.into_values().cloned());
+    candles.sort_by_key(|c| c.timestamp_ms);
+    candles
+}
 
-            if i < 3 || i == candles.len() - 1 {
-                info!(
-                    "[CsvExporter] Row {}: ts={}, o={:.4}, h={:.4}, l={:.4}, c={:.4}",
-                    i, candle.timestamp_ms, candle.open, candle.high, candle.low, candle.close
-                );
-            }
-        }
+/// File name suffix for a candle resolution, e.g. `5s`, `1m` or `250ms`
+fn resolution_label(window_ms: i64) -> String {
+    if window_ms % 60_000 == 0 {
+        format!("{}m", window_ms / 60_000)
+    } else if window_ms % 1000 == 0 {
+        format!("{}s", window_ms / 1000)
+    } else {
+        format!("{}ms", window_ms)
+    }
+}
 
-        wtr.flush()?;
-        info!("[CsvExporter] ✅ CSV file flushed and closed successfully");
-        Ok(())
+fn write_candles_to_csv(path: &Path, candles: &[Candle], compress: bool) -> Result<()> {
+    info!("[CsvExporter] write_candles_to_csv() - Writing {} candles to {}", candles.len(), path.display());
+
+    let mut wtr = CsvFile::create(path, compress)?;
+    info!("[CsvExporter] CSV writer created successfully");
+
+    // Write header
+    wtr.write_record(["timestamp_ms", "open", "high", "low", "close", "volume", "tick_count"])?;
+    info!("[CsvExporter] CSV header written");
+
+    // Write candle data
+    for (i, candle) in candles.iter().enumerate() {
+        wtr.write_record([
+            candle.timestamp_ms.to_string(),
+            candle.open.to_string(),
+            candle.high.to_string(),
+            candle.low.to_string(),
+            candle.close.to_string(),
+            candle.volume.to_string(),
+            candle.tick_count.to_string(),
+        ])?;
+
+        if i < 3 || i == candles.len() - 1 {
+            info!(
+                "[CsvExporter] Row {}: ts={}, o={:.4}, h={:.4}, l={:.4}, c={:.4}, ticks={}",
+                i, candle.timestamp_ms, candle.open, candle.high, candle.low, candle.close, candle.tick_count
+            );
+        }
     }
 
-    pub fn is_recording(&self, symbol: &str, strategy_name: &str) -> bool {
-        let recording_key = format!("{}_{}", symbol, strategy_name);
-        self.active_recordings.contains_key(&recording_key)
+    wtr.finish()?;
+    info!("[CsvExporter] ✅ CSV file flushed and closed successfully");
+    Ok(())
+}
+
+fn write_depth_to_csv(path: &Path, samples: &[DepthSample], compress: bool) -> Result<()> {
+    let mut wtr = CsvFile::create(path, compress)?;
+    wtr.write_record(["timestamp_ms", "bid_depth_usdt", "ask_depth_usdt", "imbalance"])?;
+    for sample in samples {
+        wtr.write_record([
+            sample.timestamp_ms.to_string(),
+            sample.bid_depth.to_string(),
+            sample.ask_depth.to_string(),
+            sample.imbalance.to_string(),
+        ])?;
     }
+    wtr.finish()?;
+    Ok(())
 }
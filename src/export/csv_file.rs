@@ -0,0 +1,75 @@
+use anyhow::{anyhow, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// `<stem>.csv`, or `<stem>.csv.gz` for compressed files
+pub fn csv_file_name(stem: &str, compress: bool) -> String {
+    if compress {
+        format!("{}.csv.gz", stem)
+    } else {
+        format!("{}.csv", stem)
+    }
+}
+
+enum Sink {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Sink::Plain(file) => file.write(buf),
+            Sink::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Sink::Plain(file) => file.flush(),
+            Sink::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// A recording CSV, written plain or gzip-compressed; `finish` must be called to complete
+/// the file
+pub struct CsvFile {
+    writer: csv::Writer<Sink>,
+}
+
+impl CsvFile {
+    pub fn create(path: &Path, compress: bool) -> Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        let sink = if compress {
+            Sink::Gzip(GzEncoder::new(file, Compression::default()))
+        } else {
+            Sink::Plain(file)
+        };
+        Ok(Self {
+            writer: csv::Writer::from_writer(sink),
+        })
+    }
+
+    pub fn write_record<I, T>(&mut self, record: I) -> Result<()>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        self.writer.write_record(record)?;
+        Ok(())
+    }
+
+    /// Flushes the rows and, for compressed files, writes the gzip trailer
+    pub fn finish(self) -> Result<()> {
+        let sink = self.writer.into_inner().map_err(|e| anyhow!("flushing CSV: {}", e.error()))?;
+        match sink {
+            Sink::Plain(mut file) => file.flush()?,
+            Sink::Gzip(encoder) => encoder.finish()?.flush()?,
+        }
+        Ok(())
+    }
+}
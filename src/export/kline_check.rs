@@ -0,0 +1,107 @@
+use crate::models::{aggregate_candles, Candle};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+const MINUTE_MS: i64 = 60_000;
+
+/// A minute where the local candles disagree with the exchange kline
+#[derive(Debug, Clone, Serialize)]
+pub struct KlineDiscrepancy {
+    pub timestamp_ms: i64,
+    pub field: &'static str,
+    pub local: f64,
+    pub exchange: f64,
+    pub deviation_pct: f64,
+}
+
+/// Result of comparing locally built candles with the official 1m klines
+#[derive(Debug, Clone, Serialize)]
+pub struct KlineCheckReport {
+    pub symbol: String,
+    pub tolerance_pct: f64,
+    pub minutes_compared: usize,
+    /// Fully covered minutes the exchange has a kline for but no local candle fell into
+    pub missing_local: Vec<i64>,
+    /// Minutes with local candles but no exchange kline
+    pub missing_exchange: Vec<i64>,
+    pub discrepancies: Vec<KlineDiscrepancy>,
+}
+
+impl KlineCheckReport {
+    pub fn is_consistent(&self) -> bool {
+        self.missing_local.is_empty() && self.missing_exchange.is_empty() && self.discrepancies.is_empty()
+    }
+}
+
+/// Rolls sub-minute candles up into 1-minute candles keyed by minute start
+pub fn aggregate_minutes(candles: &[Candle]) -> BTreeMap<i64, Candle> {
+    aggregate_candles(candles, MINUTE_MS)
+        .into_iter()
+        .map(|minute| (minute.timestamp_ms, minute))
+        .collect()
+}
+
+/// Compares local candles against exchange 1m klines.
+///
+/// The first and last local minutes are only partially recorded, so their
+/// OHLC values are not expected to match and are skipped.
+pub fn compare_klines(symbol: &str, local: &[Candle], exchange: &[Candle], tolerance_pct: f64) -> KlineCheckReport {
+    let local_minutes = aggregate_minutes(local);
+    let exchange_minutes: BTreeMap<i64, &Candle> = exchange.iter().map(|k| (k.timestamp_ms, k)).collect();
+
+    let first = local_minutes.keys().next().copied();
+    let last = local_minutes.keys().next_back().copied();
+    let is_full_minute = |minute: i64| Some(minute) != first && Some(minute) != last;
+
+    let mut report = KlineCheckReport {
+        symbol: symbol.to_string(),
+        tolerance_pct,
+        minutes_compared: 0,
+        missing_local: Vec::new(),
+        missing_exchange: Vec::new(),
+        discrepancies: Vec::new(),
+    };
+
+    for (&minute, local_candle) in &local_minutes {
+        let Some(kline) = exchange_minutes.get(&minute) else {
+            report.missing_exchange.push(minute);
+            continue;
+        };
+
+        if !is_full_minute(minute) {
+            continue;
+        }
+
+        report.minutes_compared += 1;
+        for (field, local_value, exchange_value) in [
+            ("open", local_candle.open, kline.open),
+            ("high", local_candle.high, kline.high),
+            ("low", local_candle.low, kline.low),
+            ("close", local_candle.close, kline.close),
+        ] {
+            if exchange_value <= 0.0 {
+                continue;
+            }
+            let deviation_pct = (local_value - exchange_value).abs() / exchange_value * 100.0;
+            if deviation_pct > tolerance_pct {
+                report.discrepancies.push(KlineDiscrepancy {
+                    timestamp_ms: minute,
+                    field,
+                    local: local_value,
+                    exchange: exchange_value,
+                    deviation_pct,
+                });
+            }
+        }
+    }
+
+    if let (Some(first), Some(last)) = (first, last) {
+        report.missing_local = exchange_minutes
+            .keys()
+            .copied()
+            .filter(|&minute| minute > first && minute < last && !local_minutes.contains_key(&minute))
+            .collect();
+    }
+
+    report
+}
@@ -1,2 +1,13 @@
+pub mod book_heatmap;
 pub mod csv_exporter;
+pub mod csv_file;
+pub mod kline_check;
+pub mod ratio_series;
+pub mod recording;
+
+pub use book_heatmap::*;
 pub use csv_exporter::*;
+pub use csv_file::*;
+pub use kline_check::*;
+pub use ratio_series::*;
+pub use recording::*;
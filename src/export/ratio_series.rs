@@ -0,0 +1,219 @@
+use crate::config::RatioExportConfig;
+use crate::models::{Symbol, SymbolData};
+use crate::utils::{DiskWriter, TaskSupervisor};
+use dashmap::DashMap;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+use tracing::warn;
+
+/// Asks the running ratio-series export to write the hour in progress, e.g. on shutdown
+#[derive(Clone)]
+pub struct RatioExportFlush(mpsc::UnboundedSender<oneshot::Sender<()>>);
+
+impl RatioExportFlush {
+    /// Returns once the rows sampled so far are queued on the disk writer; sampling stops
+    pub async fn flush(&self) {
+        let (done_tx, done_rx) = oneshot::channel();
+        if self.0.send(done_tx).is_ok() {
+            let _ = done_rx.await;
+        }
+    }
+}
+
+/// Starts the hourly ratio-series export if it is enabled in config and compiled in.
+///
+/// Every monitored symbol's last/mark/ratio is sampled at `sample_interval_ms` regardless
+/// of whether any strategy triggers, and each completed hour is written as one Parquet file.
+/// The hour in progress is written when the returned handle is flushed.
+pub fn start_ratio_export(
+    config: &RatioExportConfig,
+    symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+    disk: Arc<DiskWriter>,
+    supervisor: &TaskSupervisor,
+) -> Option<RatioExportFlush> {
+    if !config.enabled {
+        return None;
+    }
+
+    #[cfg(feature = "parquet-export")]
+    {
+        let config = config.clone();
+        let (flush_tx, flush_rx) = mpsc::unbounded_channel();
+        let flush_rx = Arc::new(tokio::sync::Mutex::new(flush_rx));
+        supervisor.supervise("ratio-export", move || {
+            parquet_export::run(config.clone(), symbol_data.clone(), disk.clone(), flush_rx.clone())
+        });
+        tracing::info!("[RatioExport] Hourly ratio series export started");
+        Some(RatioExportFlush(flush_tx))
+    }
+
+    #[cfg(not(feature = "parquet-export"))]
+    {
+        let _ = (symbol_data, disk, supervisor);
+        warn!("[RatioExport] [ratio_export] is enabled but this binary was built without the `parquet-export` feature");
+        None
+    }
+}
+
+#[cfg(feature = "parquet-export")]
+mod parquet_export {
+    use super::*;
+    use anyhow::Result;
+    use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use chrono::{DateTime, Utc};
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::properties::WriterProperties;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+    use tokio::sync::Mutex;
+    use tracing::info;
+
+    const HOUR_MS: i64 = 3_600_000;
+
+    /// Column buffers for one hour of samples; symbols are interned to keep memory flat
+    #[derive(Default)]
+    struct HourBuffer {
+        symbols: Vec<String>,
+        symbol_index: HashMap<String, u32>,
+        timestamp_ms: Vec<i64>,
+        symbol: Vec<u32>,
+        last_price: Vec<f64>,
+        mark_price: Vec<f64>,
+    }
+
+    impl HourBuffer {
+        fn push(&mut self, timestamp_ms: i64, symbol: &str, last_price: f64, mark_price: f64) {
+            let index = match self.symbol_index.get(symbol) {
+                Some(index) => *index,
+                None => {
+                    let index = self.symbols.len() as u32;
+                    self.symbols.push(symbol.to_string());
+                    self.symbol_index.insert(symbol.to_string(), index);
+                    index
+                }
+            };
+            self.timestamp_ms.push(timestamp_ms);
+            self.symbol.push(index);
+            self.last_price.push(last_price);
+            self.mark_price.push(mark_price);
+        }
+
+        fn len(&self) -> usize {
+            self.timestamp_ms.len()
+        }
+    }
+
+    pub async fn run(
+        config: RatioExportConfig,
+        symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+        disk: Arc<DiskWriter>,
+        flush_rx: Arc<Mutex<mpsc::UnboundedReceiver<oneshot::Sender<()>>>>,
+    ) {
+        if let Err(e) = fs::create_dir_all(&config.output_dir) {
+            warn!("[RatioExport] Cannot create {}: {:?}", config.output_dir, e);
+            return;
+        }
+
+        let mut flush_rx = flush_rx.lock().await;
+        let mut interval = tokio::time::interval(Duration::from_millis(config.sample_interval_ms));
+        let mut buffer = HourBuffer::default();
+        let mut current_hour = Utc::now().timestamp_millis() / HOUR_MS;
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                Some(done) = flush_rx.recv() => {
+                    write_hour(&config, &disk, current_hour, std::mem::take(&mut buffer)).await;
+                    let _ = done.send(());
+                    return;
+                }
+            }
+            let now_ms = Utc::now().timestamp_millis();
+            let hour = now_ms / HOUR_MS;
+
+            if hour != current_hour {
+                write_hour(&config, &disk, current_hour, std::mem::take(&mut buffer)).await;
+                current_hour = hour;
+            }
+
+            for entry in symbol_data.iter() {
+                let data = entry.value();
+                if let (Some(last), Some(mark)) = (data.current_last_price, data.current_mark_price) {
+                    buffer.push(now_ms, entry.key(), last, mark);
+                }
+            }
+        }
+    }
+
+    /// Queues the hour's rows as one Parquet file, if there are any
+    async fn write_hour(config: &RatioExportConfig, disk: &DiskWriter, hour: i64, buffer: HourBuffer) {
+        if buffer.len() == 0 {
+            return;
+        }
+        let output_dir = PathBuf::from(&config.output_dir);
+        let name = format!("ratio series {}", file_name(hour, 0));
+        disk.write(name, move || {
+            let rows = buffer.len();
+            let path = free_path(&output_dir, hour);
+            write_parquet(&path, buffer)?;
+            info!("[RatioExport] Wrote {} rows to {}", rows, path.display());
+            Ok(())
+        })
+        .await;
+    }
+
+    /// The hour's file name, numbered when an earlier run already wrote part of that hour
+    fn free_path(output_dir: &Path, hour: i64) -> PathBuf {
+        (0..)
+            .map(|part| output_dir.join(file_name(hour, part)))
+            .find(|path| !path.exists())
+            .expect("unbounded range")
+    }
+
+    fn file_name(hour: i64, part: u32) -> String {
+        let start = DateTime::from_timestamp_millis(hour * HOUR_MS).unwrap_or_else(Utc::now);
+        match part {
+            0 => format!("ratios_{}.parquet", start.format("%Y%m%d_%H")),
+            part => format!("ratios_{}.{}.parquet", start.format("%Y%m%d_%H"), part),
+        }
+    }
+
+    fn write_parquet(path: &PathBuf, buffer: HourBuffer) -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("timestamp_ms", DataType::Int64, false),
+            Field::new("symbol", DataType::Utf8, false),
+            Field::new("last_price", DataType::Float64, false),
+            Field::new("mark_price", DataType::Float64, false),
+            Field::new("ratio", DataType::Float64, false),
+        ]));
+
+        let ratio: Vec<f64> = buffer
+            .last_price
+            .iter()
+            .zip(&buffer.mark_price)
+            .map(|(last, mark)| last / mark)
+            .collect();
+        let symbols = StringArray::from_iter_values(buffer.symbol.iter().map(|i| buffer.symbols[*i as usize].as_str()));
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from(buffer.timestamp_ms)),
+            Arc::new(symbols),
+            Arc::new(Float64Array::from(buffer.last_price)),
+            Arc::new(Float64Array::from(buffer.mark_price)),
+            Arc::new(Float64Array::from(ratio)),
+        ];
+        let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let mut writer = ArrowWriter::try_new(fs::File::create(path)?, schema, Some(properties))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use crate::config::CsvExportConfig;
+use anyhow::{bail, Result};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+
+/// Recording options that can be changed on the running process via the control socket.
+///
+/// Starts from `[csv_export]`; changes apply to incidents recorded from then on and are
+/// not written back to config.toml.
+#[derive(Debug)]
+pub struct RecordingSettings {
+    enabled: AtomicBool,
+    pre_anomaly_buffer_secs: AtomicI64,
+    post_anomaly_recording_secs: AtomicI64,
+}
+
+impl RecordingSettings {
+    /// Keys accepted by `set`
+    pub const KEYS: [&'static str; 3] = [
+        "csv_export.enabled",
+        "csv_export.pre_anomaly_buffer_secs",
+        "csv_export.post_anomaly_recording_secs",
+    ];
+
+    pub fn from_config(config: &CsvExportConfig) -> Self {
+        Self {
+            enabled: AtomicBool::new(config.enabled),
+            pre_anomaly_buffer_secs: AtomicI64::new(config.pre_anomaly_buffer_secs),
+            post_anomaly_recording_secs: AtomicI64::new(config.post_anomaly_recording_secs),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn pre_anomaly_buffer_secs(&self) -> i64 {
+        self.pre_anomaly_buffer_secs.load(Ordering::Relaxed)
+    }
+
+    pub fn post_anomaly_recording_secs(&self) -> i64 {
+        self.post_anomaly_recording_secs.load(Ordering::Relaxed)
+    }
+
+    /// Applies `key = value`, e.g. `csv_export.enabled = false`
+    pub fn set(&self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "csv_export.enabled" => {
+                let Ok(enabled) = value.parse::<bool>() else {
+                    bail!("{} must be true or false, got '{}'", key, value);
+                };
+                self.enabled.store(enabled, Ordering::Relaxed);
+            }
+            "csv_export.pre_anomaly_buffer_secs" => {
+                self.pre_anomaly_buffer_secs.store(parse_secs(key, value)?, Ordering::Relaxed);
+            }
+            "csv_export.post_anomaly_recording_secs" => {
+                self.post_anomaly_recording_secs.store(parse_secs(key, value)?, Ordering::Relaxed);
+            }
+            _ => bail!("unknown setting '{}' (settable: {})", key, Self::KEYS.join(", ")),
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for RecordingSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "csv_export.enabled = {}", self.enabled())?;
+        writeln!(f, "csv_export.pre_anomaly_buffer_secs = {}", self.pre_anomaly_buffer_secs())?;
+        writeln!(f, "csv_export.post_anomaly_recording_secs = {}", self.post_anomaly_recording_secs())
+    }
+}
+
+fn parse_secs(key: &str, value: &str) -> Result<i64> {
+    match value.parse::<i64>() {
+        Ok(secs) if secs >= 0 => Ok(secs),
+        _ => bail!("{} must be a whole number of seconds >= 0, got '{}'", key, value),
+    }
+}
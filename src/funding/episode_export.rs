@@ -0,0 +1,149 @@
+use crate::funding::FundingHistory;
+use crate::utils::{DiskWriter, EpisodeStore};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::info;
+
+/// Appends the funding rate at trigger time and its change over the following
+/// window to `funding_episodes.csv`, once that window has elapsed
+pub struct FundingEpisodeExporter {
+    history: Arc<FundingHistory>,
+    store: Arc<EpisodeStore>,
+    file_path: PathBuf,
+    followup: Duration,
+    // Appends go through the disk writer thread, which also keeps them in order
+    disk: Arc<DiskWriter>,
+}
+
+impl FundingEpisodeExporter {
+    pub fn new(
+        history: Arc<FundingHistory>,
+        store: Arc<EpisodeStore>,
+        log_dir: &str,
+        followup_minutes: u64,
+        disk: Arc<DiskWriter>,
+    ) -> Result<Self> {
+        fs::create_dir_all(log_dir)?;
+
+        Ok(Self {
+            history,
+            store,
+            file_path: PathBuf::from(log_dir).join("funding_episodes.csv"),
+            followup: Duration::minutes(followup_minutes as i64),
+            disk,
+        })
+    }
+
+    pub fn rate_at(&self, symbol: &str, at: DateTime<Utc>) -> Option<f64> {
+        self.history.rate_at(symbol, at)
+    }
+
+    /// Waits until the follow-up window after `start_time` has passed, then records the change
+    pub fn schedule_followup(
+        self: &Arc<Self>,
+        episode_id: &str,
+        strategy: &str,
+        symbol: &str,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        rate_at_trigger: Option<f64>,
+    ) {
+        let exporter = self.clone();
+        let episode_id = episode_id.to_string();
+        let strategy = strategy.to_string();
+        let symbol = symbol.to_string();
+
+        tokio::spawn(async move {
+            let followup_at = start_time + exporter.followup;
+            let wait = followup_at.signed_duration_since(Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+
+            let rate_after = exporter.history.rate_at(&symbol, followup_at);
+            let change = rate_at_trigger.zip(rate_after).map(|(before, after)| after - before);
+
+            if let Some(change) = change {
+                exporter.store.set_funding_change(&episode_id, change);
+            }
+
+            let file_path = exporter.file_path.clone();
+            let followup_minutes = exporter.followup.num_minutes();
+            exporter
+                .disk
+                .write(format!("funding row for {}", episode_id), move || {
+                    let row = FundingRow {
+                        episode_id: &episode_id,
+                        strategy: &strategy,
+                        symbol: &symbol,
+                        start_time,
+                        end_time,
+                        rate_at_trigger,
+                        rate_after,
+                        change,
+                    };
+                    append(&file_path, &row)?;
+                    info!(
+                        "[Funding] {} ({}) funding {} -> {} over {}m",
+                        symbol,
+                        strategy,
+                        format_rate(rate_at_trigger),
+                        format_rate(rate_after),
+                        followup_minutes
+                    );
+                    Ok(())
+                })
+                .await;
+        });
+    }
+}
+
+/// Appends one row, writing the header first if the file is new
+fn append(file_path: &Path, row: &FundingRow) -> Result<()> {
+    let write_header = !file_path.exists();
+
+    let file = OpenOptions::new().create(true).append(true).open(file_path)?;
+    let mut wtr = csv::Writer::from_writer(file);
+
+    if write_header {
+        wtr.write_record([
+            "episode_id",
+            "strategy",
+            "symbol",
+            "start_time",
+            "end_time",
+            "funding_at_trigger",
+            "funding_after",
+            "funding_change",
+        ])?;
+    }
+
+    wtr.write_record([
+        row.episode_id.to_string(),
+        row.strategy.to_string(),
+        row.symbol.to_string(),
+        row.start_time.to_rfc3339(),
+        row.end_time.to_rfc3339(),
+        format_rate(row.rate_at_trigger),
+        format_rate(row.rate_after),
+        format_rate(row.change),
+    ])?;
+    wtr.flush()?;
+    Ok(())
+}
+
+struct FundingRow<'a> {
+    episode_id: &'a str,
+    strategy: &'a str,
+    symbol: &'a str,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    rate_at_trigger: Option<f64>,
+    rate_after: Option<f64>,
+    change: Option<f64>,
+}
+
+pub fn format_rate(rate: Option<f64>) -> String {
+    rate.map(|r| format!("{:.6}", r)).unwrap_or_else(|| "n/a".to_string())
+}
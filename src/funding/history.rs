@@ -0,0 +1,61 @@
+use crate::api::MexcRestClient;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Rolling per-symbol funding rate samples, ingested by polling the REST ticker endpoint
+pub struct FundingHistory {
+    retention: Duration,
+    samples: DashMap<String, VecDeque<(DateTime<Utc>, f64)>>,
+}
+
+impl FundingHistory {
+    pub fn new(retention_hours: u64) -> Self {
+        Self {
+            retention: Duration::hours(retention_hours as i64),
+            samples: DashMap::new(),
+        }
+    }
+
+    pub fn record(&self, symbol: &str, at: DateTime<Utc>, rate: f64) {
+        let mut samples = self.samples.entry(symbol.to_string()).or_default();
+        samples.push_back((at, rate));
+
+        let cutoff = at - self.retention;
+        while samples.front().is_some_and(|(t, _)| *t < cutoff) {
+            samples.pop_front();
+        }
+    }
+
+    /// Latest known funding rate at or before `at`
+    pub fn rate_at(&self, symbol: &str, at: DateTime<Utc>) -> Option<f64> {
+        let samples = self.samples.get(symbol)?;
+        samples.iter().rev().find(|(t, _)| *t <= at).map(|(_, rate)| *rate)
+    }
+
+    /// Polls funding rates for every contract until the process exits
+    pub async fn run_ingestion(self: Arc<Self>, rest: Arc<MexcRestClient>, poll_interval_secs: u64) {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(poll_interval_secs));
+
+        loop {
+            interval.tick().await;
+
+            match rest.get_tickers().await {
+                Ok(tickers) => {
+                    let now = Utc::now();
+                    let mut count = 0;
+                    for ticker in tickers {
+                        if let Some(rate) = ticker.funding_rate {
+                            self.record(&ticker.symbol, now, rate);
+                            count += 1;
+                        }
+                    }
+                    debug!("[Funding] Recorded funding rates for {} contracts", count);
+                }
+                Err(e) => warn!("[Funding] Failed to poll funding rates: {:?}", e),
+            }
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod episode_export;
+pub mod history;
+
+pub use episode_export::*;
+pub use history::*;
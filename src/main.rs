@@ -1,184 +1,1178 @@
+mod alerts;
 mod api;
+mod backtest;
 mod config;
+mod config_schema;
+mod control;
 mod detection;
+mod execution;
 mod export;
+mod funding;
 mod models;
+mod replay;
+mod universe;
 mod utils;
+#[cfg(feature = "dashboard")]
+mod web;
 
-use crate::api::{MexcRestClient, MexcWebSocketClient};
-use crate::config::Config;
-use crate::detection::{Strategy1, Strategy2, Strategy3, Strategy4, Strategy5};
-use crate::export::CsvExporter;
-use crate::models::{MarketEvent, SymbolData};
-use crate::utils::EpisodeLogger;
+use crate::alerts::{build_missed_digest, AlertDispatcher, AlertPacer, AlertSuppressor, DigestMode, KillSwitch, LogNotifier, Notification, Notifier};
+#[cfg(feature = "mqtt")]
+use crate::alerts::MqttNotifier;
+#[cfg(feature = "telegram")]
+use crate::alerts::TelegramNotifier;
+#[cfg(feature = "zmq")]
+use crate::alerts::ZmqNotifier;
+use crate::api::{
+    ChannelHealth, EventRouter, MarketEventQueues, MarketStream, MexcRestClient, MexcWebSocketClient, PrioritySymbols,
+    SubscriptionCommand, SyntheticStream,
+};
+#[cfg(feature = "execution")]
+use crate::api::{ApiCredentials, MexcSpotClient, PrivateEvent, PrivateWebSocketClient};
+use crate::backtest::{render_table, run_backtest, write_report, BacktestSpec};
+use crate::config::{Config, MarketSource, Mitigation, ParsingMode, DEFAULT_ENVIRONMENT};
+#[cfg(feature = "execution")]
+use crate::config::LedgerFormat;
+use crate::control::{
+    inspect_report, parse_window_secs, send_control_command, ControlCommand, ControlRequest, ControlServer, StrategyState,
+    DEFAULT_INSPECT_WINDOW_SECS,
+};
+use crate::detection::{build_strategies, handle_market_event, mark_connection_status, EpisodeCorrelator, FeatureCache, RatioBaselines, FeatureParams, InterContractMonitor, LatencyBudget, ManualSignals, MarketContext, INTER_CONTRACT_SIGNAL, MANUAL_SIGNAL, Pipeline, PrecisionTracker, ReadinessBoard, StrategyDeps, StrategyRegistry, StrategyThresholds, TriggerThrottle, Watchlist};
+#[cfg(feature = "execution")]
+use crate::execution::{read_trades, render_export, ContractSpec, OrderExecutor, ProfitSweeper, TradeLedger, TradeRecord};
+use crate::export::{start_ratio_export, CsvExporter, RecordingSettings};
+use crate::funding::{FundingEpisodeExporter, FundingHistory};
+use crate::models::{
+    ConnectionStatus, ContractCatalog, FineCandles, MarketEvent, PairRegistry, ProcessedOrderbook, Symbol, SymbolData,
+    CANDLE_WINDOW_MS, PRICE_HISTORY_SECS,
+};
+#[cfg(feature = "execution")]
+use crate::models::ContractDetail;
+use crate::replay::{check_consistency, switch_off_for_replay, tape_files, EventRecorder, ReplayStream};
+use crate::universe::{
+    ContractStateMonitor, DormancyMonitor, DormantList, LoadShedding, OpenInterestPoller, ResourceGovernor, SuspendedContracts,
+    SymbolSelector, UniverseRotator, VolumeTiers,
+};
+use crate::utils::{
+    format_bytes, format_uptime, process_rss_bytes, record_strategy_config, start_profiler, system_clock, ConsoleFormat,
+    DiskWriter, EpisodeLogWriter, EpisodeLogger, EpisodeStore, Heartbeat, IssueCollector, MalformedCapture, RecentLogs,
+    SessionStats, SharedClock, SimulatedClock, TaskSupervisor, WsRawLog, CONFIG_HISTORY_FILE,
+};
+#[cfg(feature = "dashboard")]
+use crate::web::{LiveFeed, WebServer};
 use dashmap::DashMap;
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use rand::{seq::IteratorRandom, SeedableRng};
-use tracing::{debug, error, info};
-use tracing_subscriber;
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize tracing with debug level for more visibility
+    // Load environment variables
+    dotenv::dotenv().ok();
+
+    // `mexc-sniper config-schema` prints every supported option and exits
+    if std::env::args().nth(1).as_deref() == Some("config-schema") {
+        print!("{}", config_schema::render_template());
+        return Ok(());
+    }
+
+    // Load configuration, switching endpoints to the environment given by `--env <name>`
+    let mut config = Config::load("config.toml")?;
+    let environment = cli_option("--env").unwrap_or_else(|| DEFAULT_ENVIRONMENT.to_string());
+    config.api.select_environment(&environment)?;
+
+    // `--replay <tape>` plays a recorded tape instead of connecting to the exchange
+    if let Some(path) = cli_option("--replay") {
+        config.market_stream.source = MarketSource::Replay;
+        config.market_stream.replay_path = path;
+    }
+    if let Some(speed) = cli_option("--replay-speed") {
+        config.market_stream.replay_speed = speed
+            .parse()
+            .map_err(|_| anyhow::anyhow!("--replay-speed must be a number, got {:?}", speed))?;
+    }
+    let replay = config.market_stream.source == MarketSource::Replay;
+
+    // `mexc-sniper inspect SYMBOL [--window 10m]` queries a running instance and exits
+    if std::env::args().nth(1).as_deref() == Some("inspect") {
+        let Some(symbol) = std::env::args().nth(2) else {
+            anyhow::bail!("usage: mexc-sniper inspect SYMBOL [--window 10m]");
+        };
+        let window_secs = match cli_option("--window") {
+            Some(window) => parse_window_secs(&window)?,
+            None => DEFAULT_INSPECT_WINDOW_SECS,
+        };
+        let command = ControlCommand::Inspect {
+            symbol: symbol.to_uppercase(),
+            window_secs,
+        };
+        print!("{}", send_control_command(&config.control.socket_path, &command).await?);
+        return Ok(());
+    }
+
+    // `mexc-sniper settings` and `mexc-sniper set KEY VALUE` show or change runtime settings
+    match std::env::args().nth(1).as_deref() {
+        Some("settings") => {
+            print!("{}", send_control_command(&config.control.socket_path, &ControlCommand::Settings).await?);
+            return Ok(());
+        }
+        Some("set") => {
+            let (Some(key), Some(value)) = (std::env::args().nth(2), std::env::args().nth(3)) else {
+                anyhow::bail!("usage: mexc-sniper set KEY VALUE (keys: {})", RecordingSettings::KEYS.join(", "));
+            };
+            let command = ControlCommand::Set { key, value };
+            print!("{}", send_control_command(&config.control.socket_path, &command).await?);
+            return Ok(());
+        }
+        // `mexc-sniper bundle` has the running instance write an issue bundle
+        Some("bundle") => {
+            print!("{}", send_control_command(&config.control.socket_path, &ControlCommand::Bundle).await?);
+            return Ok(());
+        }
+        // `mexc-sniper signal SYMBOL [--note TEXT] [--execute]` opens a manual episode
+        Some("signal") => {
+            let Some(symbol) = std::env::args().nth(2).filter(|arg| !arg.starts_with("--")) else {
+                anyhow::bail!("usage: mexc-sniper signal SYMBOL [--note TEXT] [--execute]");
+            };
+            let command = ControlCommand::Signal {
+                symbol: symbol.to_uppercase(),
+                execute: std::env::args().any(|arg| arg == "--execute"),
+                note: cli_option("--note"),
+            };
+            print!("{}", send_control_command(&config.control.socket_path, &command).await?);
+            return Ok(());
+        }
+        // `mexc-sniper ledger-export FORMAT` converts trades.csv for a portfolio tracker
+        Some("ledger-export") => {
+            #[cfg(not(feature = "execution"))]
+            anyhow::bail!("ledger-export needs a binary built with the `execution` feature");
+            #[cfg(feature = "execution")]
+            {
+                print!("{}", ledger_export_command(&config)?);
+                return Ok(());
+            }
+        }
+        _ => {}
+    }
+
+    // Initialize tracing with debug level for more visibility (RUST_LOG overrides)
+    let span_events = if config.profiling.span_timings {
+        FmtSpan::CLOSE
+    } else {
+        FmtSpan::NONE
+    };
+    // The recent log lines also go into issue bundles
+    let recent_logs = RecentLogs::new(if config.issue_bundle.enabled { config.issue_bundle.log_lines } else { 0 });
+    // A backtest or consistency check runs the strategies many times over; only its own
+    // progress is logged
+    let command = std::env::args().nth(1);
+    let backtest = command.as_deref() == Some("backtest");
+    let consistency = command.as_deref() == Some("consistency");
+    let default_filter = match (backtest, consistency) {
+        (true, _) => "mexc_sniper::backtest=info",
+        (_, true) => "mexc_sniper::replay=info",
+        _ => "mexc_sniper=debug",
+    };
     tracing_subscriber::fmt()
-        .with_env_filter("mexc_sniper=debug")
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_filter)),
+        )
+        .with_span_events(span_events)
+        .event_format(ConsoleFormat::new(&config.logging.console))
+        .finish()
+        .with(recent_logs.clone())
         .init();
 
+    // `mexc-sniper backtest TAPE [--grid backtest.toml] [--out DIR]` sweeps strategy
+    // parameters over a recorded tape and exits
+    if backtest {
+        return backtest_command(&config).await;
+    }
+    // `mexc-sniper consistency TAPE [--episodes DIR] [--tolerance-ms N] [--out DIR]` replays
+    // a tape a live run recorded and diffs the episodes against that run's
+    if consistency {
+        return consistency_command(&config).await;
+    }
+
     info!("Starting MEXC Futures Pump Anomaly Detector");
+    info!("Configuration loaded successfully");
+    info!(
+        "[Config] Environment '{}': REST {} | WS {} | trading REST {} | trading WS {}",
+        environment,
+        config.api.base_rest_url,
+        config.api.base_ws_url,
+        config.api.trading_rest_url(),
+        config.api.trading_ws_url()
+    );
 
-    // Load environment variables
-    dotenv::dotenv().ok();
+    // Execution settings and trading keys live apart from config.toml, so the detector can
+    // run from a config that grants no trading access
+    let execution_path = cli_option("--execution-config").unwrap_or_else(|| "execution.toml".to_string());
+    if config.load_execution(&execution_path)? {
+        info!("[Config] Execution settings loaded from {}", execution_path);
+        let execution = &config.execution;
+        if execution.credentials.api_secret.is_some() || execution.sub_account.credentials.api_secret.is_some() {
+            warn_if_readable_by_others(&execution_path);
+        }
+    } else {
+        info!("[Config] No {} - running detection only", execution_path);
+    }
 
-    // Load configuration
-    let config = Config::load("config.toml")?;
-    info!("Configuration loaded successfully");
+    // Low-memory mode: Strategy4 is nothing but orderbook checks
+    if !config.orderbook.enabled {
+        if config.strategy4.enabled {
+            warn!("[Config] Orderbooks disabled: Strategy4 is switched off");
+            config.strategy4.enabled = false;
+        }
+        if config.strategy5.enabled {
+            info!("[Config] Orderbooks disabled: Strategy5 runs without its depth and book-spread checks");
+        }
+        if config.book_heatmap.enabled {
+            warn!("[Config] Orderbooks disabled: no book heatmap is recorded");
+            config.book_heatmap.enabled = false;
+        }
+        config.resource_budget.mitigations.retain(|&mitigation| mitigation != Mitigation::DropDepth);
+    }
+
+    if replay {
+        switch_off_for_replay(&mut config);
+    }
+
+    start_profiler(&config.profiling);
+
+    // Restarts background tasks that die from a panic
+    let supervisor = TaskSupervisor::default();
 
     // Initialize REST client and fetch symbols
-    let rest_client = MexcRestClient::new(config.api.base_rest_url.clone());
+    let rest_client = Arc::new(MexcRestClient::new(config.api.base_rest_url.clone(), config.rest.clone())?);
     info!("Fetching contract list from exchange...");
 
-    let all_symbols = rest_client.get_all_contracts().await?;
+    // Retries can take a while against an unreachable exchange; let Ctrl+C cut them short
+    let contract_details = if replay {
+        info!("[Replay] Contract list not fetched; symbols are taken from the tape");
+        Vec::new()
+    } else {
+        tokio::select! {
+            result = rest_client.get_contract_details() => result?,
+            _ = tokio::signal::ctrl_c() => {
+                info!("Received shutdown signal while fetching contracts");
+                return Ok(());
+            }
+        }
+    };
+    let all_symbols: Vec<String> = contract_details
+        .iter()
+        .filter(|contract| contract.state == 0)
+        .map(|contract| contract.symbol.clone())
+        .collect();
     info!("Found {} active contracts", all_symbols.len());
 
+    // Contract sizes and tick sizes for the contract-relative price floors
+    let contracts = Arc::new(ContractCatalog::from_details(&contract_details));
+
     // Determine which symbols to monitor
-    let symbols_to_monitor = if config.general.symbols.is_empty() {
+    let candidate_symbols = if config.general.symbols.is_empty() {
         all_symbols
     } else {
         config.general.symbols.clone()
     };
 
-    info!("Monitoring {} symbols", symbols_to_monitor.len());
+    // Wall clock for live runs; replays substitute a simulated one that the event loop
+    // moves to the time of each replayed event
+    let replay_clock = replay.then(|| Arc::new(SimulatedClock::new(chrono::DateTime::UNIX_EPOCH)));
+    let clock: SharedClock = match replay_clock {
+        Some(ref replay_clock) => replay_clock.clone(),
+        None => system_clock(),
+    };
 
     // Initialize shared symbol data storage
-    let symbol_data: Arc<DashMap<String, SymbolData>> = Arc::new(DashMap::new());
+    let symbol_data: Arc<DashMap<Symbol, SymbolData>> = Arc::new(DashMap::new());
+
+    // Short fine-resolution candle buffer per symbol, for the trigger window of chart recordings
+    let fine_candles = match config.csv_export.fine_candle_ms {
+        ms if ms <= 0 => None,
+        ms if CANDLE_WINDOW_MS % ms != 0 => {
+            warn!(
+                "[csv_export] fine_candle_ms = {} doesn't divide the {}ms candles - fine candles disabled",
+                ms, CANDLE_WINDOW_MS
+            );
+            None
+        }
+        ms => Some(FineCandles {
+            window_ms: ms,
+            // Covers the post-trigger half of the window when the recording collects it
+            keep_secs: config.csv_export.fine_window_secs.max(1) + 2,
+        }),
+    };
+
+    // Shared history of recently completed episodes (backs the feed endpoint and universe scoring)
+    let episode_store = Arc::new(
+        EpisodeStore::new(config.web.feed_max_entries)
+            .with_symbol_history(chrono::Duration::hours(config.universe.pump_history_hours as i64)),
+    );
+
+    // Episode logs, chart recordings and other exports are written off the runtime threads
+    let disk = DiskWriter::start(config.disk_io.clone())?;
+
+    // Samples of unparsable exchange messages for issue bundles
+    let issue_collector = config.issue_bundle.enabled.then(|| {
+        IssueCollector::new(config.issue_bundle.clone(), "config.toml", &environment, recent_logs, disk.clone())
+    });
+
+    // Channel for adding/removing WebSocket subscriptions at runtime
+    let (command_tx, command_rx) = mpsc::unbounded_channel::<SubscriptionCommand>();
+
+    // Contracts archived for inactivity by a previous run stay unsubscribed until they revive
+    let dormant = config
+        .dormancy
+        .enabled
+        .then(|| Arc::new(DormantList::load(&config.dormancy.state_file)));
+    if let Some(ref dormant) = dormant {
+        info!("[Dormancy] {} contracts dormant, skipped until they revive", dormant.len());
+    }
+
+    // Reference symbols (BTC/ETH) that episodes are compared against
+    let market_context = config
+        .market_context
+        .enabled
+        .then(|| Arc::new(MarketContext::new(config.market_context.clone())));
+    let reference_symbols = market_context
+        .as_ref()
+        .map(|context| context.reference_symbols().to_vec())
+        .unwrap_or_default();
+
+    // Contracts the contract monitor unsubscribed because they stopped trading
+    let suspended = (config.contract_monitor.enabled && config.contract_monitor.unsubscribe_untradable)
+        .then(|| Arc::new(SuspendedContracts::default()));
+
+    // Mitigations the resource budget applies while the process is over its soft limits
+    let load_shedding = config.resource_budget.enabled.then(|| Arc::new(LoadShedding::default()));
+
+    // Symbols with a running episode, a running recording or on the watchlist are handled
+    // ahead of the rest, and neither shed by the resource budget nor rotated out
+    let priority_symbols = Arc::new(PrioritySymbols::default());
+
+    // Cap the monitored universe by priority score if configured
+    let selector = SymbolSelector::new(config.universe.clone());
+    let mut symbols_to_monitor = if selector.is_limited() {
+        let rotator = UniverseRotator::new(
+            selector,
+            rest_client.clone(),
+            candidate_symbols.clone(),
+            episode_store.clone(),
+            symbol_data.clone(),
+            command_tx.clone(),
+            config.universe.pump_history_hours,
+            config.universe.rotation_interval_secs,
+        )
+        .with_clock(clock.clone())
+        .with_fine_candles(fine_candles)
+        .with_pinned(reference_symbols.clone())
+        .with_busy(priority_symbols.clone());
+        let rotator = match dormant {
+            Some(ref dormant) => rotator.with_dormant(dormant.clone()),
+            None => rotator,
+        };
+        let rotator = match suspended {
+            Some(ref suspended) => rotator.with_suspended(suspended.clone()),
+            None => rotator,
+        };
+        let rotator = match load_shedding {
+            Some(ref shedding) => rotator.with_load_shedding(shedding.clone()),
+            None => rotator,
+        };
+        let selected = rotator.initial_selection().await;
+        info!(
+            "[Universe] Selected {} of {} candidate symbols (max_symbols = {})",
+            selected.len(),
+            candidate_symbols.len(),
+            config.universe.max_symbols
+        );
+        let rotator = Arc::new(rotator);
+        supervisor.supervise("universe-rotation", move || rotator.clone().run());
+        selected
+    } else {
+        match dormant {
+            Some(ref dormant) => candidate_symbols.into_iter().filter(|s| !dormant.contains(s)).collect(),
+            None => candidate_symbols,
+        }
+    };
+
+    for symbol in &reference_symbols {
+        if !symbols_to_monitor.contains(symbol) {
+            info!("[MarketContext] Also monitoring reference symbol {}", symbol);
+            symbols_to_monitor.push(symbol.clone());
+        }
+    }
+
+    // Escalates symbols showing early signs (also adjusts their orderbook subscription)
+    let watchlist = Watchlist::new(config.watchlist.clone(), command_tx.clone(), config.orderbook.max_levels);
+
+    info!("Monitoring {} symbols", symbols_to_monitor.len());
 
     for symbol in &symbols_to_monitor {
-        symbol_data.insert(symbol.clone(), SymbolData::new(symbol.clone()));
+        let symbol = Symbol::from(symbol.as_str());
+        symbol_data.insert(symbol.clone(), SymbolData::new(symbol, clock.clone()).with_fine_candles(fine_candles));
+    }
+
+    // Archive contracts that stopped trading and resubscribe them once they revive
+    if let Some(dormant) = dormant {
+        let monitor = Arc::new(DormancyMonitor::new(
+            config.dormancy.clone(),
+            rest_client.clone(),
+            symbol_data.clone(),
+            command_tx.clone(),
+            dormant,
+            clock.clone(),
+        )
+        .with_fine_candles(fine_candles));
+        supervisor.supervise("dormancy", move || monitor.clone().run());
     }
 
     // Initialize episode loggers
-    let logger1 = Arc::new(EpisodeLogger::new(&config.general.log_dir, "strategy1")?);
-    let logger2 = Arc::new(EpisodeLogger::new(&config.general.log_dir, "strategy2")?);
-    let logger3 = Arc::new(EpisodeLogger::new(&config.general.log_dir, "strategy3")?);
-    let logger4 = Arc::new(EpisodeLogger::new(&config.general.log_dir, "strategy4")?);
-    let logger5 = Arc::new(EpisodeLogger::new(&config.general.log_dir, "strategy5")?);
+    let log_dir = &config.general.log_dir;
 
-    info!("Episode loggers initialized");
+    // Every episode line references the strategy parameters version it fired under
+    let config_version = record_strategy_config(&config, log_dir)?;
+    if config_version.is_new {
+        info!(
+            "Strategy parameters recorded as version {} in {}/{}",
+            config_version.version, log_dir, CONFIG_HISTORY_FILE
+        );
+    } else {
+        info!("Strategy parameters unchanged (version {})", config_version.version);
+    }
 
-    // Initialize CSV exporter if enabled
-    let csv_exporter = if config.csv_export.enabled {
+    // Optional funding rate ingestion, attached to every episode record
+    let funding = if config.funding.enabled {
+        // Keep enough history to look up the rate at the end of the follow-up window
+        let history = Arc::new(FundingHistory::new(config.funding.followup_minutes / 60 + 2));
+        let rest = rest_client.clone();
+        let poll_secs = config.funding.poll_interval_secs;
+        let history_for_ingestion = history.clone();
+        supervisor.supervise("funding-ingestion", move || {
+            history_for_ingestion.clone().run_ingestion(rest.clone(), poll_secs)
+        });
+        info!("Funding rate ingestion enabled (poll every {}s)", poll_secs);
+        Some(Arc::new(FundingEpisodeExporter::new(
+            history,
+            episode_store.clone(),
+            log_dir,
+            config.funding.followup_minutes,
+            disk.clone(),
+        )?))
+    } else {
+        None
+    };
+    // Open interest history for the strategies' min_oi_increase_pct condition
+    if config.open_interest.enabled {
+        let poller = Arc::new(OpenInterestPoller::new(
+            config.open_interest.clone(),
+            rest_client.clone(),
+            symbol_data.clone(),
+        ));
+        supervisor.supervise("open-interest", move || poller.clone().run());
+        info!("Open interest polling enabled (every {}s)", config.open_interest.poll_interval_secs);
+    } else if config.requires_open_interest() {
+        warn!("A strategy sets min_oi_increase_pct but [open_interest] is disabled - it will never trigger");
+    }
+    let episode_log = EpisodeLogWriter::start(config.logging.episodes.clone(), disk.clone());
+    let new_logger_in = |dir: &str, pipeline: Option<&str>, strategy: &str| -> anyhow::Result<Arc<EpisodeLogger>> {
+        let mut logger = EpisodeLogger::new(dir, strategy, episode_store.clone(), episode_log.clone())?
+            .with_config_version(config_version.version);
+        if let Some(pipeline) = pipeline {
+            logger = logger.with_pipeline(pipeline);
+        }
+        if let Some(ref funding) = funding {
+            logger = logger.with_funding(funding.clone());
+        }
+        if let Some(ref market_context) = market_context {
+            logger = logger.with_market_context(market_context.clone());
+        }
+        Ok(Arc::new(logger))
+    };
+    let new_logger = |strategy: &str| new_logger_in(log_dir, None, strategy);
+    // Automatic strategy scoring from post-trigger price action
+    let precision = if config.precision.enabled {
+        let tracker = PrecisionTracker::new(config.precision.clone(), symbol_data.clone());
+        let runner = tracker.clone();
+        supervisor.supervise("precision", move || runner.clone().run());
+        Some(tracker)
+    } else {
+        None
+    };
+
+    // Cross-strategy episode correlation, written to the log dir
+    let correlator = if config.correlation.enabled {
+        let correlator = EpisodeCorrelator::new(config.correlation.clone(), log_dir, disk.clone(), clock.clone())?;
+        let runner = correlator.clone();
+        supervisor.supervise("correlation", move || runner.clone().run());
+        Some(correlator)
+    } else {
+        None
+    };
+
+    // Start the optional HTTP server for episode feeds and live browser push
+    #[cfg(feature = "dashboard")]
+    let live_feed = start_web_server(&config, symbol_data.clone(), episode_store.clone(), precision.as_ref(), &supervisor);
+    #[cfg(not(feature = "dashboard"))]
+    if config.web.enabled {
+        warn!("[Config] [web] is enabled but this binary was built without the `dashboard` feature");
+    }
+
+    // Commands from the control socket are answered by the event loop below
+    let (control_tx, mut control_rx) = mpsc::unbounded_channel::<ControlRequest>();
+    if config.control.enabled {
+        let control_server = ControlServer::new(config.control.clone(), control_tx.clone());
+        tokio::spawn(async move {
+            if let Err(e) = control_server.run().await {
+                error!("Control socket failed: {:?}", e);
+            }
+        });
+    }
+
+    // Initialize the CSV exporter; it is created even when disabled so recording can be
+    // switched on at runtime through the control socket
+    let recording_settings = Arc::new(RecordingSettings::from_config(&config.csv_export));
+    let new_exporter = |charts_dir: &str| -> anyhow::Result<Arc<CsvExporter>> {
         let exporter = CsvExporter::new(
-            &config.csv_export.charts_dir,
-            config.csv_export.post_anomaly_recording_secs,
+            charts_dir,
+            recording_settings.clone(),
             symbol_data.clone(),
-        )?;
+            supervisor.clone(),
+            disk.clone(),
+        )?
+        .with_resolutions(&config.csv_export.resolutions_ms)
+        .with_compression(config.csv_export.compress);
+        let exporter = match fine_candles {
+            Some(fine) => exporter.with_fine_candles(fine.window_ms, config.csv_export.fine_window_secs),
+            None => exporter,
+        };
+        let exporter = if config.book_heatmap.enabled {
+            exporter.with_book_heatmap(config.book_heatmap.clone())
+        } else {
+            exporter
+        };
+        Ok(Arc::new(if config.kline_check.enabled {
+            exporter.with_kline_check(rest_client.clone(), config.kline_check.clone())
+        } else {
+            exporter
+        }))
+    };
+    let exporter = new_exporter(&config.csv_export.charts_dir)?;
+    if config.book_heatmap.enabled {
+        info!(
+            "Book heatmap sampling enabled ({}ms, {} bps buckets)",
+            config.book_heatmap.sample_interval_ms, config.book_heatmap.bucket_bps
+        );
+        if config.book_heatmap.parquet && !cfg!(feature = "parquet-export") {
+            warn!("[Config] book_heatmap.parquet is set but this binary was built without the `parquet-export` feature; writing CSV only");
+        }
+    }
+    if config.kline_check.enabled {
+        info!("Kline cross-check enabled (tolerance {}%)", config.kline_check.tolerance_pct);
+    }
+    if config.csv_export.enabled {
         info!("CSV exporter initialized - charts will be saved to: {}", config.csv_export.charts_dir);
-        Some(Arc::new(exporter))
     } else {
         info!("CSV export is disabled");
+    }
+    let csv_exporter = Some(exporter);
+
+    let ratio_export = start_ratio_export(&config.ratio_export, symbol_data.clone(), disk.clone(), &supervisor);
+
+    // Shared notification channel for all strategies
+    let (alert_tx, alert_rx) = mpsc::unbounded_channel::<Notification>();
+    let mut notifiers: Vec<Box<dyn Notifier>> = vec![Box::new(LogNotifier)];
+    if let Some(ref precision) = precision {
+        notifiers.push(Box::new(precision.clone()));
+    }
+    if let Some(ref correlator) = correlator {
+        notifiers.push(Box::new(correlator.clone()));
+    }
+    #[cfg(feature = "dashboard")]
+    if let Some(live) = live_feed {
+        notifiers.push(Box::new(live));
+    }
+    if config.mqtt.enabled {
+        #[cfg(feature = "mqtt")]
+        notifiers.push(Box::new(MqttNotifier::start(config.mqtt.clone(), &supervisor)));
+        #[cfg(not(feature = "mqtt"))]
+        warn!("[Config] [mqtt] is enabled but this binary was built without the `mqtt` feature");
+    }
+    if config.alerts.telegram.enabled {
+        #[cfg(feature = "telegram")]
+        match TelegramNotifier::start(config.alerts.telegram.clone(), &supervisor) {
+            Ok(telegram) => notifiers.push(Box::new(telegram)),
+            Err(e) => error!("[Telegram] Alerts disabled: {:#}", e),
+        }
+        #[cfg(not(feature = "telegram"))]
+        warn!("[Config] [alerts.telegram] is enabled but this binary was built without the `telegram` feature");
+    }
+    if config.alerts.zmq.enabled {
+        #[cfg(feature = "zmq")]
+        notifiers.push(Box::new(ZmqNotifier::start(config.alerts.zmq.clone(), &supervisor)));
+        #[cfg(not(feature = "zmq"))]
+        warn!("[Config] [alerts.zmq] is enabled but this binary was built without the `zmq` feature");
+    }
+    // Volume tiers for per-tier alert routing and execution
+    let tiers = if config.tiers.enabled {
+        let tiers = Arc::new(VolumeTiers::new(config.tiers.clone(), rest_client.clone()));
+        let refresher = tiers.clone();
+        supervisor.supervise("tiers", move || refresher.clone().run());
+        info!(
+            "Volume tiers enabled: {:?} USDT, refreshed every {} min",
+            config.tiers.min_volume_usdt, config.tiers.refresh_interval_mins
+        );
+        Some(tiers)
+    } else {
+        None
+    };
+
+    let mut dispatcher = AlertDispatcher::new(notifiers).with_routes(config.alerts.routes.clone());
+    if let Some(ref tiers) = tiers {
+        dispatcher = dispatcher.with_tiers(tiers.clone(), config.tiers.routes.clone());
+    }
+    if let Some(ref market_context) = market_context {
+        dispatcher = dispatcher.with_market_context(market_context.clone());
+    }
+    if config.alerts.suppression.enabled {
+        dispatcher = dispatcher.with_suppression(AlertSuppressor::new(config.alerts.suppression.clone()));
+    }
+    if config.cooldowns.per_symbol_any_strategy_seconds > 0 || config.cooldowns.global_seconds > 0 {
+        dispatcher = dispatcher.with_pacing(AlertPacer::new(&config.cooldowns));
+        info!(
+            "Alert cooldowns: {}s per symbol across strategies, {}s between any two alerts",
+            config.cooldowns.per_symbol_any_strategy_seconds, config.cooldowns.global_seconds
+        );
+    }
+    #[cfg_attr(not(feature = "execution"), allow(unused_variables))]
+    let kill_switch = if config.alerts.kill_switch.enabled {
+        info!(
+            "Kill switch enabled: pauses notifications and execution above {} episode starts/min",
+            config.alerts.kill_switch.max_starts_per_min
+        );
+        let kill_switch = Arc::new(KillSwitch::new(config.alerts.kill_switch.clone()));
+        dispatcher = dispatcher.with_kill_switch(kill_switch.clone());
+        Some(kill_switch)
+    } else {
+        None
+    };
+    if config.alerts.digest_mode.enabled {
+        info!(
+            "Alert digest mode enabled: above {} alerts/min, one digest every {}s",
+            config.alerts.digest_mode.enter_alerts_per_min, config.alerts.digest_mode.interval_secs
+        );
+        dispatcher = dispatcher.with_digest_mode(DigestMode::new(config.alerts.digest_mode.clone()));
+    }
+    tokio::spawn(dispatcher.run(alert_rx));
+
+    // Report what happened on the exchange while this process was down
+    if config.missed_digest.enabled {
+        let digest_config = config.missed_digest.clone();
+        let heartbeat = Heartbeat::new(&digest_config.heartbeat_file, digest_config.heartbeat_interval_secs);
+        let now = chrono::Utc::now();
+
+        match heartbeat.last_seen() {
+            Some(last_seen)
+                if now.signed_duration_since(last_seen).num_seconds()
+                    >= (digest_config.min_downtime_secs + digest_config.heartbeat_interval_secs) as i64 =>
+            {
+                info!("[Digest] Detector was offline since {} - replaying the gap", last_seen);
+                let rest = rest_client.clone();
+                let symbols = symbols_to_monitor.clone();
+                let alert_tx = alert_tx.clone();
+                tokio::spawn(async move {
+                    let digest = build_missed_digest(&rest, &symbols, last_seen, now, &digest_config).await;
+                    let _ = alert_tx.send(digest.into());
+                });
+            }
+            Some(_) => info!("[Digest] Restarted within the downtime threshold - no digest needed"),
+            None => info!("[Digest] No previous heartbeat found - skipping missed digest"),
+        }
+
+        supervisor.supervise("heartbeat", move || heartbeat.clone().run());
+    }
+
+    // Initialize order executor if enabled
+    let executor = if config.execution.enabled {
+        #[cfg(feature = "execution")]
+        let executor = Some(
+            start_executor(&config, &contract_details, &disk, kill_switch.as_ref(), tiers.as_ref(), &supervisor).await?,
+        );
+        #[cfg(not(feature = "execution"))]
+        let executor = {
+            warn!("[Config] Execution is enabled but this binary was built without the `execution` feature; detecting only");
+            None
+        };
+        executor
+    } else {
+        info!("Execution is disabled");
         None
     };
 
-    let pre_buffer_secs = config.csv_export.pre_anomaly_buffer_secs;
+    // Per-strategy threshold throttling; strategies keep their configured thresholds without it
+    let mut throttle = TriggerThrottle::new(config.throttle.clone(), clock.clone());
+    let mut throttle_for = |strategy: &'static str| {
+        if config.throttle.enabled {
+            throttle.for_strategy(strategy)
+        } else {
+            Arc::default()
+        }
+    };
 
     // Initialize strategies
-    let mut strategy1 = Strategy1::new(
-        config.strategy1.clone(),
-        config.cooldowns.per_symbol_seconds,
-        logger1,
-        csv_exporter.clone(),
-        pre_buffer_secs,
-    );
+    let thresholds = StrategyThresholds::from_config(&config)?.with_tiers(tiers.clone());
+    if !config.tiers.thresholds.is_empty() {
+        if tiers.is_some() {
+            let tiers: Vec<String> = config
+                .tiers
+                .thresholds
+                .iter()
+                .map(|(tier, sections)| format!("{} ({})", tier, sections.keys().cloned().collect::<Vec<_>>().join(", ")))
+                .collect();
+            info!("[Config] Tier thresholds: {}", tiers.join(", "));
+        } else {
+            warn!("[Config] tiers.thresholds is set but [tiers] is disabled; no tier thresholds apply");
+        }
+    }
+    if !config.symbol_overrides.is_empty() {
+        let symbols: Vec<&str> = config.symbol_overrides.keys().map(String::as_str).collect();
+        info!("[Config] Threshold overrides for {} symbols: {}", symbols.len(), symbols.join(", "));
+    }
 
-    let mut strategy2 = Strategy2::new(
-        config.strategy2.clone(),
-        config.cooldowns.per_symbol_seconds,
-        logger2,
-        csv_exporter.clone(),
-        pre_buffer_secs,
-    );
+    // The event loop runs these in order; new strategies only need adding to build_strategies
+    let mut strategies = build_strategies(
+        &config,
+        &thresholds,
+        StrategyDeps {
+            alert_tx: alert_tx.clone(),
+            clock: clock.clone(),
+            csv_exporter: csv_exporter.clone(),
+            contracts: Some(contracts.clone()),
+            executor: executor.clone(),
+        },
+        &new_logger,
+        &mut throttle_for,
+    )?;
 
-    let mut strategy3 = Strategy3::new(
-        config.strategy3.clone(),
-        config.cooldowns.per_symbol_seconds,
-        logger3,
-        csv_exporter.clone(),
-        pre_buffer_secs,
-    );
+    if config.throttle.enabled {
+        info!(
+            "Trigger throttling enabled: above {} episodes/hour a strategy's ratio thresholds go up {}x per step",
+            config.throttle.max_episodes_per_hour, config.throttle.threshold_multiplier
+        );
+        supervisor.supervise("throttle", move || throttle.clone().run());
+    }
 
-    let mut strategy4 = Strategy4::new(
-        config.strategy4.clone(),
-        config.orderbook.clone(),
-        config.cooldowns.per_symbol_seconds,
-        logger4,
-        csv_exporter.clone(),
-        pre_buffer_secs,
-    );
+    // Perpetual vs dated future basis monitoring
+    let mut inter_contract = if config.inter_contract.enabled {
+        let registry = PairRegistry::from_details(&contract_details);
+        if registry.is_empty() {
+            warn!("[InterContract] No dated futures with a matching perpetual are listed");
+        } else {
+            info!("[InterContract] Monitoring {} perpetual/dated pairs", registry.len());
+        }
+        Some(InterContractMonitor::new(
+            config.inter_contract.clone(),
+            registry,
+            config.cooldowns.per_symbol_seconds,
+            new_logger(INTER_CONTRACT_SIGNAL)?,
+            alert_tx.clone(),
+            clock.clone(),
+        ))
+    } else {
+        None
+    };
 
-    let mut strategy5 = Strategy5::new(
-        config.strategy5.clone(),
-        config.strategy1.clone(),
-        config.strategy2.clone(),
-        config.strategy3.clone(),
-        config.strategy4.clone(),
-        config.orderbook.clone(),
-        config.cooldowns.per_symbol_seconds,
-        logger5,
-        csv_exporter.clone(),
-        pre_buffer_secs,
-    );
+    // Episodes opened by hand through the control socket
+    let mut manual_signals = if config.manual_signals.enabled {
+        if !config.control.enabled {
+            warn!("[Manual] [manual_signals] is enabled but [control] is not - no signal can reach it");
+        }
+        info!(
+            "[Manual] Accepting manual signals (episodes open for {}s, execution {})",
+            config.manual_signals.duration_secs,
+            if config.manual_signals.allow_execution { "allowed" } else { "not allowed" }
+        );
+        Some(ManualSignals::new(
+            config.manual_signals.clone(),
+            new_logger(MANUAL_SIGNAL)?,
+            csv_exporter.clone(),
+            alert_tx.clone(),
+            executor.clone(),
+            clock.clone(),
+        ))
+    } else {
+        None
+    };
 
     info!("Detection strategies initialized (including Strategy5: Ultra-Strict)");
 
-    // Create WebSocket client
-    let ws_client = MexcWebSocketClient::new(
-        config.api.base_ws_url.clone(),
-        symbols_to_monitor.clone(),
-        config.orderbook.max_levels,
-    );
+    // Extra strategy sets on the same market data, each with its own logs and recordings
+    let mut pipelines = Vec::new();
+    for (name, pipeline_config) in &config.pipelines {
+        let pipeline_log_dir = pipeline_config.log_dir.clone().unwrap_or_else(|| format!("{}/{}", log_dir, name));
+        let charts_dir = pipeline_config
+            .charts_dir
+            .clone()
+            .unwrap_or_else(|| format!("{}/{}", config.csv_export.charts_dir, name));
+        pipelines.push(Pipeline::new(
+            name,
+            pipeline_config,
+            &config,
+            |strategy| new_logger_in(&pipeline_log_dir, Some(name), strategy),
+            Some(new_exporter(&charts_dir)?),
+            alert_tx.clone(),
+            clock.clone(),
+            contracts.clone(),
+        )?);
+        info!("[Pipeline] '{}' initialized - episodes in {}, charts in {}", name, pipeline_log_dir, charts_dir);
+    }
+
+    // Price history is only kept for PRICE_HISTORY_SECS, so longer windows never fill
+    for (option, secs) in [
+        ("strategy2.spike_lookback_secs", config.strategy2.spike_lookback_secs),
+        ("strategy3.baseline_window_secs", config.strategy3.baseline_window_secs),
+    ] {
+        if secs as i64 >= PRICE_HISTORY_SECS {
+            warn!(
+                "[Warmup] {} = {}s but only {}s of price history is kept; the strategy will never be armed",
+                option, secs, PRICE_HISTORY_SECS
+            );
+        }
+    }
+
+    let latency_budget = Arc::new(LatencyBudget::new(config.latency_budget.clone()));
+    let readiness = Arc::new(ReadinessBoard::default());
+
+    // Create the market data source selected in config
+    let market_stream: Box<dyn MarketStream> = match config.market_stream.source {
+        MarketSource::Websocket => {
+            let mut client = MexcWebSocketClient::new(
+                config.api.base_ws_url.clone(),
+                symbols_to_monitor.clone(),
+                config.orderbook.max_levels,
+                supervisor.clone(),
+            )
+            .with_orderbooks(config.orderbook.enabled)
+            .with_trades(config.market_stream.trades)
+            .with_funding_rates(config.market_stream.funding_rates);
+            if config.logging.ws_raw.enabled {
+                client = client.with_raw_log(WsRawLog::start(config.logging.ws_raw.clone(), disk.clone())?);
+                info!("WebSocket acks and errors are kept in {}", config.logging.ws_raw.dir);
+            }
+            if let Some(ref issues) = issue_collector {
+                client = client.with_issue_collector(issues.clone());
+            }
+            if config.parsing.mode == ParsingMode::Strict {
+                client = client.with_malformed_capture(MalformedCapture::start(config.parsing.clone(), disk.clone())?);
+                info!("Strict parsing: malformed market messages are kept in {}", config.parsing.samples_dir);
+            }
+            Box::new(client)
+        }
+        MarketSource::Synthetic => Box::new(SyntheticStream::new(
+            symbols_to_monitor.clone(),
+            config.general.poll_interval_ms,
+            &config.market_stream,
+        )),
+        MarketSource::Replay => Box::new(ReplayStream::new(
+            &config.market_stream.replay_path,
+            config.market_stream.replay_speed,
+            &config.general.symbols,
+        )),
+    };
+    let stream_name = market_stream.name().to_string();
+
+    // Per-symbol channel coverage; only the websocket stream can resubscribe channels
+    let channel_health = (config.channel_health.enabled && config.market_stream.source == MarketSource::Websocket).then(|| {
+        let health = ChannelHealth::new(config.channel_health.clone(), config.orderbook.enabled);
+        let health = Arc::new(match load_shedding {
+            Some(ref shedding) => health.with_load_shedding(shedding.clone()),
+            None => health,
+        });
+        let (repairer, command_tx) = (health.clone(), command_tx.clone());
+        supervisor.supervise("channel-health", move || repairer.clone().run(command_tx.clone()));
+        info!(
+            "Channel health enabled: channels silent for {}s are resubscribed",
+            config.channel_health.stale_secs
+        );
+        health
+    });
+
+    // Tape of the market events the strategies see, for replaying later
+    let recorder = if config.recording.enabled {
+        Some(EventRecorder::start(&config.recording, disk.clone())?)
+    } else {
+        None
+    };
 
     // Create channel for market events
-    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<MarketEvent>();
+    let (event_tx, event_rx) = mpsc::unbounded_channel::<MarketEvent>();
+
+    // Orderbooks dropped during an outage are refilled from REST snapshots on reconnect
+    let orderbook_resync = (config.market_stream.source == MarketSource::Websocket
+        && config.orderbook.enabled
+        && config.market_stream.resync_orderbooks)
+        .then(|| OrderbookResync {
+            rest: rest_client.clone(),
+            event_tx: event_tx.clone(),
+            levels: config.orderbook.max_levels,
+            shedding: load_shedding.clone(),
+        });
+
+    let (priority_tx, priority_rx) = mpsc::unbounded_channel::<MarketEvent>();
+    let mut market_events = if config.event_priority.enabled {
+        let (background_tx, background_rx) = mpsc::unbounded_channel::<MarketEvent>();
+        let router = EventRouter::new(config.event_priority.clone(), priority_symbols.clone());
+        tokio::spawn(router.run(event_rx, priority_tx, background_tx));
+        info!(
+            "Event priority enabled (background updates thinned to every {}ms)",
+            config.event_priority.background_min_interval_ms
+        );
+        MarketEventQueues::new(priority_rx, background_rx)
+    } else {
+        drop(priority_tx);
+        MarketEventQueues::new(priority_rx, event_rx)
+    };
+
+    // Sheds load while memory or CPU usage is over its soft limits
+    if let Some(ref shedding) = load_shedding {
+        let pinned = config.universe.priority_symbols.iter().chain(&reference_symbols).cloned().collect();
+        let governor = Arc::new(
+            ResourceGovernor::new(
+                config.resource_budget.clone(),
+                shedding.clone(),
+                symbol_data.clone(),
+                command_tx.clone(),
+                priority_symbols.clone(),
+                clock.clone(),
+            )
+            .with_pinned(pinned)
+            .with_fine_candles(fine_candles),
+        );
+        supervisor.supervise("resource-budget", move || governor.clone().run());
+        info!(
+            "Resource budget enabled: RSS limit {} MB, CPU limit {}% (0 = none), mitigations {:?}",
+            config.resource_budget.max_rss_mb,
+            config.resource_budget.max_cpu_pct,
+            config.resource_budget.mitigations.iter().map(|m| m.name()).collect::<Vec<_>>()
+        );
+        if config.resource_budget.max_rss_mb == 0 && config.resource_budget.max_cpu_pct <= 0.0 {
+            warn!("[Config] resource_budget is enabled without max_rss_mb or max_cpu_pct: usage is only reported");
+        }
+    }
+
+    // Contract state re-checks, fed into the same event loop as the market data
+    if config.contract_monitor.enabled {
+        let monitor = ContractStateMonitor::new(
+            config.contract_monitor.clone(),
+            rest_client.clone(),
+            symbol_data.clone(),
+            &contract_details,
+            event_tx.clone(),
+            alert_tx.clone(),
+        );
+        // With an explicit symbol list, new listings are only reported
+        let monitor = if config.contract_monitor.subscribe_new_listings && config.general.symbols.is_empty() {
+            monitor
+                .with_new_listing_subscriptions(command_tx.clone(), clock.clone())
+                .with_fine_candles(fine_candles)
+        } else {
+            monitor
+        };
+        let monitor = match suspended {
+            Some(ref suspended) => monitor.with_suspensions(suspended.clone(), command_tx.clone(), clock.clone()),
+            None => monitor,
+        };
+        let monitor = if config.contract_monitor.migrate_renames {
+            monitor.with_rename_migration(command_tx.clone())
+        } else {
+            monitor
+        };
+        let monitor = Arc::new(monitor);
+        supervisor.supervise("contract_state", move || monitor.clone().run());
+        info!(
+            "Contract state monitor enabled: re-checking every {}s",
+            config.contract_monitor.check_interval_secs
+        );
+    }
 
-    // Spawn WebSocket task
+    // Spawn market stream task
     let ws_handle = tokio::spawn(async move {
-        if let Err(e) = ws_client.run(event_tx).await {
-            error!("WebSocket task failed: {:?}", e);
+        if let Err(e) = market_stream.run(event_tx, command_rx).await {
+            error!("Market stream ({}) failed: {:?}", stream_name, e);
         }
     });
 
-    info!("WebSocket connection established");
+    info!("Market stream started ({:?})", config.market_stream.source);
     info!("System running - monitoring for pump anomalies...");
 
+    // Hourly ratio baselines for spotting symbols with broken mark pricing
+    let ratio_baselines = config.ratio_baseline.enabled.then(|| {
+        let baselines = Arc::new(RatioBaselines::new(config.ratio_baseline.clone()));
+        let (sampler, symbol_data) = (baselines.clone(), symbol_data.clone());
+        supervisor.supervise("ratio-baselines", move || sampler.clone().run(symbol_data.clone()));
+        baselines
+    });
+
     // Create periodic status logger
+    let session_stats = Arc::new(SessionStats::new());
+    let session_for_status = session_stats.clone();
+    let recordings_for_status = csv_exporter.clone();
+    let priority_for_status = config.event_priority.enabled.then(|| priority_symbols.clone());
     let symbol_data_clone = symbol_data.clone();
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
-        loop {
-            interval.tick().await;
-            let symbols_with_data: Vec<_> = symbol_data_clone
-                .iter()
-                .filter(|entry| entry.value().current_last_price.is_some())
-                .map(|entry| entry.key().clone())
-                .collect();
+    let supervisor_for_status = supervisor.clone();
+    let latency_for_status = latency_budget.clone();
+    let readiness_for_status = readiness.clone();
+    let strategy_names = strategies.names();
+    let disk_for_status = disk.clone();
+    let precision_for_status = precision.clone();
+    let correlator_for_status = correlator.clone();
+    let baselines_for_status = ratio_baselines.clone();
+    let channels_for_status = channel_health.clone();
+    let shedding_for_status = load_shedding.clone();
+    let suspended_for_status = suspended.clone();
+    supervisor.supervise("status", move || {
+        let symbol_data_clone = symbol_data_clone.clone();
+        let supervisor_for_status = supervisor_for_status.clone();
+        let latency_for_status = latency_for_status.clone();
+        let readiness_for_status = readiness_for_status.clone();
+        let strategy_names = strategy_names.clone();
+        let disk_for_status = disk_for_status.clone();
+        let precision_for_status = precision_for_status.clone();
+        let correlator_for_status = correlator_for_status.clone();
+        let session_for_status = session_for_status.clone();
+        let recordings_for_status = recordings_for_status.clone();
+        let priority_for_status = priority_for_status.clone();
+        let baselines_for_status = baselines_for_status.clone();
+        let channels_for_status = channels_for_status.clone();
+        let suspended_for_status = suspended_for_status.clone();
+        let shedding_for_status = shedding_for_status.clone();
+        async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+            let mut previous_counts = session_for_status.counts(readiness_for_status.checks_recorded());
+            loop {
+                interval.tick().await;
+                let symbols_with_data: Vec<_> = symbol_data_clone
+                    .iter()
+                    .filter(|entry| entry.value().current_last_price.is_some())
+                    .map(|entry| entry.key().clone())
+                    .collect();
 
-            info!(
-                "Status: Monitoring {} symbols | Active data streams: {} | Uptime: OK",
-                symbol_data_clone.len(),
-                symbols_with_data.len()
-            );
+                info!(
+                    "Status: Monitoring {} symbols | Active data streams: {} | Task restarts: {} | Uptime: {}",
+                    symbol_data_clone.len(),
+                    symbols_with_data.len(),
+                    supervisor_for_status.total_restarts(),
+                    format_uptime(session_for_status.uptime())
+                );
 
-            // Log a few price samples
-            if !symbols_with_data.is_empty() {
-                for symbol in symbols_with_data.iter().take(3) {
-                    if let Some(data) = symbol_data_clone.get(symbol) {
-                        if let (Some(last), Some(mark)) = (data.current_last_price, data.current_mark_price) {
-                            let ratio = last / mark;
-                            info!(
-                                "  {} | Last: {:.4} | Mark: {:.4} | Ratio: {:.6}",
-                                symbol, last, mark, ratio
-                            );
+                let counts = session_for_status.counts(readiness_for_status.checks_recorded());
+                let rates = counts.rates_since(&previous_counts);
+                previous_counts = counts;
+                let (queued_events, max_queued_events) = session_for_status.take_event_queue_depth();
+                info!(
+                    "  Events/s: ticker {:.1} | mark price {:.1} | orderbook {:.1} | trades {:.1} | queued {} (max {})",
+                    rates.ticker_events,
+                    rates.mark_price_events,
+                    rates.orderbook_events,
+                    rates.trade_events,
+                    queued_events,
+                    max_queued_events
+                );
+                if let Some(priority) = &priority_for_status {
+                    info!(
+                        "  Priority symbols: {} | background updates thinned: {}",
+                        priority.len(),
+                        priority.throttled()
+                    );
+                }
+                let recordings = recordings_for_status.as_ref().map(|e| e.active_recording_count()).unwrap_or(0);
+                info!(
+                    "  Strategy checks/s: {:.1} | active episodes: {} | recordings in flight: {}",
+                    rates.strategy_checks,
+                    session_for_status.active_episodes(),
+                    recordings
+                );
+                let symbol_bytes: usize = symbol_data_clone.iter().map(|entry| entry.value().approx_bytes()).sum();
+                info!(
+                    "  Memory: RSS {} | symbol data ~{}",
+                    process_rss_bytes().map(format_bytes).unwrap_or_else(|| "n/a".to_string()),
+                    format_bytes(symbol_bytes as u64)
+                );
+                if let Some(ref shedding) = shedding_for_status {
+                    info!("  {}", shedding.summary());
+                }
+                for (task, restarts) in supervisor_for_status.restart_counts() {
+                    info!("  Task '{}' restarted {} times", task, restarts);
+                }
+                for (strategy, stats) in latency_for_status.snapshot() {
+                    info!(
+                        "  {} checks: {} | over budget: {} | max: {}µs",
+                        strategy, stats.calls, stats.slow_calls, stats.max.as_micros()
+                    );
+                }
+                for (strategy, summary) in readiness_for_status.summarize(&strategy_names, &symbol_data_clone) {
+                    info!("  {} readiness: {}", strategy, summary);
+                }
+                let disk_stats = disk_for_status.stats();
+                info!(
+                    "  Disk writes: queued {} (max {}) | done: {} | failed: {} | slow: {} | max: {}ms",
+                    disk_stats.queued,
+                    disk_stats.max_queued,
+                    disk_stats.completed,
+                    disk_stats.failed,
+                    disk_stats.slow,
+                    disk_stats.max_duration.as_millis()
+                );
+                if let Some(ref precision) = precision_for_status {
+                    for line in precision.summary_lines() {
+                        info!("  {}", line);
+                    }
+                }
+                if let Some(ref correlator) = correlator_for_status {
+                    info!("  {}", correlator.summary());
+                }
+                if let Some(ref baselines) = baselines_for_status {
+                    info!("  {}", baselines.summary());
+                }
+                if let Some(ref channels) = channels_for_status {
+                    let partial = channels.partial();
+                    info!("  {}", channels.summary(&partial));
+                    for coverage in partial.iter().take(10) {
+                        let missing: Vec<&str> = coverage.missing.iter().map(|channel| channel.name()).collect();
+                        info!(
+                            "    {} missing {} (resubscribed {}x)",
+                            coverage.symbol,
+                            missing.join(", "),
+                            coverage.resubscribes
+                        );
+                    }
+                    if partial.len() > 10 {
+                        info!("    ... and {} more", partial.len() - 10);
+                    }
+                }
+                if let Some(ref suspended) = suspended_for_status {
+                    if !suspended.is_empty() {
+                        info!("  Suspended contracts (not trading, unsubscribed): {}", suspended.len());
+                    }
+                }
+
+                // Log a few price samples
+                if !symbols_with_data.is_empty() {
+                    for symbol in symbols_with_data.iter().take(3) {
+                        if let Some(data) = symbol_data_clone.get(symbol) {
+                            if let (Some(last), Some(mark)) = (data.current_last_price, data.current_mark_price) {
+                                let ratio = last / mark;
+                                info!(
+                                    "  {} | Last: {:.4} | Mark: {:.4} | Ratio: {:.6}",
+                                    symbol, last, mark, ratio
+                                );
+                            }
                         }
                     }
                 }
@@ -186,128 +1180,123 @@ async fn main() -> anyhow::Result<()> {
         }
     });
 
-    // Create periodic detailed trace logger (every 10 seconds, random symbol)
-    let symbol_data_for_trace = symbol_data.clone();
-    let config_for_trace = config.clone();
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(10));
-        let mut rng = rand::rngs::SmallRng::from_os_rng();
+    // Per-tick features shared by the strategies are computed with these lookbacks
+    let feature_params = FeatureParams::from_config(&config);
 
-        loop {
-            interval.tick().await;
+    // Ends manual episodes whose time is up
+    let mut manual_poll = tokio::time::interval(std::time::Duration::from_secs(1));
 
-            // Get symbols that have both prices available
-            let symbols_with_data: Vec<_> = symbol_data_for_trace
-                .iter()
-                .filter(|entry| {
-                    entry.value().current_last_price.is_some()
-                        && entry.value().current_mark_price.is_some()
-                })
-                .map(|entry| entry.key().clone())
-                .collect();
-
-            if symbols_with_data.is_empty() {
-                continue;
-            }
-
-            // Pick a random symbol
-            let random_symbol = symbols_with_data.iter().choose(&mut rng);
-
-            if let Some(symbol) = random_symbol {
-                if let Some(data) = symbol_data_for_trace.get(symbol) {
-                    let last_price = data.current_last_price.unwrap();
-                    let mark_price = data.current_mark_price.unwrap();
-                    let ratio = last_price / mark_price;
-                    let abs_diff = last_price - mark_price;
-
-                    // Strategy thresholds from config
-                    let s1 = &config_for_trace.strategy1;
-                    let s2 = &config_for_trace.strategy2;
-                    let s3 = &config_for_trace.strategy3;
-                    let s4 = &config_for_trace.strategy4;
-
-                    // Check strategy conditions
-                    let s1_ratio_ok = ratio >= s1.spread_ratio_min;
-                    let s1_diff_ok = abs_diff >= s1.min_abs_diff;
-                    let s1_price_ok = last_price >= s1.min_price;
-                    let s1_triggered = s1.enabled && s1_ratio_ok && s1_diff_ok && s1_price_ok;
-
-                    let s2_ratio_ok = ratio >= s2.spread_ratio_min;
-                    let s2_price_ok = last_price >= s2.min_price;
-
-                    let s3_ratio_ok = ratio >= s3.spread_ratio_min;
-                    let s3_price_ok = last_price >= s3.min_price;
-
-                    let s4_ratio_ok = ratio >= s4.spread_ratio_min;
-                    let s4_diff_ok = abs_diff >= s4.min_abs_diff;
-                    let s4_price_ok = last_price >= s4.min_price;
-
-                    // Check orderbook data availability
-                    let has_orderbook = data.orderbook.is_some();
-
-                    info!("══════════════════════════════════════════════════════════════");
-                    info!("[TRACE] Random Symbol Check: {}", symbol);
-                    info!("├─ Last Price:    {:.6}", last_price);
-                    info!("├─ Mark Price:    {:.6}", mark_price);
-                    info!("├─ Ratio:         {:.6} (last/mark)", ratio);
-                    info!("├─ Abs Diff:      {:.6} (last - mark)", abs_diff);
-                    info!("├─ Orderbook:     {}", if has_orderbook { "Available" } else { "Not available" });
-                    info!("├─ Strategy1 [{}]:", if s1.enabled { "ON" } else { "OFF" });
-                    info!("│  ├─ Ratio >= {:.4}?  {} (actual: {:.6})",
-                        s1.spread_ratio_min,
-                        if s1_ratio_ok { "YES" } else { "NO" },
-                        ratio
-                    );
-                    info!("│  ├─ Diff >= {:.4}?   {} (actual: {:.6})",
-                        s1.min_abs_diff,
-                        if s1_diff_ok { "YES" } else { "NO" },
-                        abs_diff
-                    );
-                    info!("│  ├─ Price >= {:.4}? {} (actual: {:.6})",
-                        s1.min_price,
-                        if s1_price_ok { "YES" } else { "NO" },
-                        last_price
-                    );
-                    info!("│  └─ TRIGGERED:    {}", if s1_triggered { "YES" } else { "NO" });
-                    info!("├─ Strategy2 [{}]: Ratio {} | Price {}",
-                        if s2.enabled { "ON" } else { "OFF" },
-                        if s2_ratio_ok { "OK" } else { "NO" },
-                        if s2_price_ok { "OK" } else { "NO" }
-                    );
-                    info!("├─ Strategy3 [{}]: Ratio {} | Price {}",
-                        if s3.enabled { "ON" } else { "OFF" },
-                        if s3_ratio_ok { "OK" } else { "NO" },
-                        if s3_price_ok { "OK" } else { "NO" }
-                    );
-                    info!("├─ Strategy4 [{}]: Ratio {} | Diff {} | Price {}",
-                        if s4.enabled { "ON" } else { "OFF" },
-                        if s4_ratio_ok { "OK" } else { "NO" },
-                        if s4_diff_ok { "OK" } else { "NO" },
-                        if s4_price_ok { "OK" } else { "NO" }
-                    );
-                    info!("└─ Strategy5 [{}]: Combines all above conditions",
-                        if config_for_trace.strategy5.enabled { "ON" } else { "OFF" }
-                    );
-                    info!("══════════════════════════════════════════════════════════════");
-                }
-            }
-        }
-    });
+    // Detailed trace of a random symbol every 10 seconds, as the strategies evaluate it
+    let mut trace_tick = tokio::time::interval(std::time::Duration::from_secs(10));
+    let mut trace_rng = rand::rngs::SmallRng::from_os_rng();
 
     // Main event loop
+    let mut stream_open = true;
     loop {
         tokio::select! {
-            Some(event) = event_rx.recv() => {
+            event = market_events.recv(), if stream_open => {
+                let Some(event) = event else {
+                    // Every sender is gone: the stream ended (a finished replay) or failed
+                    stream_open = false;
+                    if replay {
+                        info!("[Replay] Every replayed event was handled");
+                        break;
+                    }
+                    continue;
+                };
+                session_stats.record_event(&event, market_events.len());
+                if let Some(ref channel_health) = channel_health {
+                    channel_health.record(&event);
+                }
+                if let Some(ref recorder) = recorder {
+                    recorder.record(&event);
+                }
+                if let (Some(replay_clock), Some(timestamp)) = (&replay_clock, event.timestamp()) {
+                    replay_clock.set(timestamp);
+                }
+                if let MarketEvent::ConnectionStatus(status) = event {
+                    handle_connection_status(
+                        status,
+                        &symbol_data,
+                        config.market_stream.stale_grace_secs,
+                        orderbook_resync.as_ref(),
+                    );
+                    continue;
+                }
+                // Only state changes and renames, both rare, need a symbol allocated
+                let symbol = match event.interned_symbol() {
+                    Some(symbol) => symbol.clone(),
+                    None => Symbol::from(event.symbol()),
+                };
+                // Replayed symbols are only known once their first event arrives
+                if replay_clock.is_some() && !symbol_data.contains_key(&*symbol) {
+                    let data = SymbolData::new(symbol.clone(), clock.clone()).with_fine_candles(fine_candles);
+                    symbol_data.insert(symbol.clone(), data);
+                }
                 handle_market_event(
                     event,
                     &symbol_data,
-                    &mut strategy1,
-                    &mut strategy2,
-                    &mut strategy3,
-                    &mut strategy4,
-                    &mut strategy5,
+                    &mut strategies,
+                    Some(&latency_budget),
+                    &readiness,
+                    &watchlist,
+                    &mut inter_contract,
+                    market_context.as_deref(),
+                    feature_params,
+                    executor.as_ref(),
+                    &mut pipelines,
+                    ratio_baselines.as_deref(),
+                    config.market_stream.stale_grace_secs,
+                );
+                session_stats.set_active_episodes(
+                    strategies.active_episodes()
+                        + pipelines.iter().map(Pipeline::active_episodes).sum::<usize>()
+                        + manual_signals.as_ref().map_or(0, ManualSignals::active_episodes),
                 );
+                // Also keeps the resource budget from shedding and rotation from dropping these symbols
+                if config.event_priority.enabled || config.resource_budget.enabled || config.universe.max_symbols > 0 {
+                    let watched = symbol_data.get(&*symbol).is_some_and(|data| data.watch.is_some());
+                    priority_symbols.set(
+                        &symbol,
+                        watched
+                            || strategies.has_active_episode(&symbol)
+                            || pipelines.iter().any(|pipeline| pipeline.has_active_episode(&symbol))
+                            || csv_exporter.as_ref().is_some_and(|exporter| exporter.has_recording(&symbol)),
+                    );
+                }
             }
+            _ = trace_tick.tick() => {
+                log_trace(&symbol_data, feature_params, &strategies, config.orderbook.enabled, &mut trace_rng);
+            }
+            _ = manual_poll.tick(), if manual_signals.is_some() => {
+                if let Some(ref mut manual_signals) = manual_signals {
+                    manual_signals.poll(&symbol_data);
+                }
+            }
+            Some(request) = control_rx.recv() => match request.command {
+                ControlCommand::Bundle => write_issue_bundle(issue_collector.clone(), request.reply),
+                ControlCommand::Signal { symbol, execute, note } => {
+                    let response = match (manual_signals.as_mut(), symbol_data.get(symbol.as_str())) {
+                        (None, _) => "error: manual signals are disabled ([manual_signals] enabled = false)\n".to_string(),
+                        (Some(_), None) => format!("error: {} is not monitored\n", symbol),
+                        (Some(manual_signals), Some(data)) => manual_signals
+                            .inject(&data, note, execute)
+                            .unwrap_or_else(|e| format!("error: {}\n", e)),
+                    };
+                    let _ = request.reply.send(response);
+                }
+                command => {
+                    let response = answer_control_command(
+                        command,
+                        &symbol_data,
+                        feature_params,
+                        &readiness,
+                        &recording_settings,
+                        &strategies,
+                    );
+                    let _ = request.reply.send(response);
+                }
+            },
             _ = tokio::signal::ctrl_c() => {
                 info!("Received shutdown signal");
                 break;
@@ -318,64 +1307,479 @@ async fn main() -> anyhow::Result<()> {
     info!("Shutting down gracefully...");
     ws_handle.abort();
 
+    // Buffered episode lines and queued writes would be lost on exit; a stalled disk
+    // shouldn't keep the process from exiting though
+    info!("Flushing episode logs and pending disk writes...");
+    let flush = async {
+        episode_log.flush().await;
+        if let Some(ref ratio_export) = ratio_export {
+            ratio_export.flush().await;
+        }
+        disk.flush().await;
+    };
+    if tokio::time::timeout(tokio::time::Duration::from_secs(10), flush).await.is_err() {
+        warn!("Gave up waiting for pending disk writes after 10s");
+    }
+
     Ok(())
 }
 
-fn handle_market_event(
-    event: MarketEvent,
-    symbol_data: &Arc<DashMap<String, SymbolData>>,
-    strategy1: &mut Strategy1,
-    strategy2: &mut Strategy2,
-    strategy3: &mut Strategy3,
-    strategy4: &mut Strategy4,
-    strategy5: &mut Strategy5,
-) {
-    match event {
-        MarketEvent::TickerUpdate {
-            symbol,
-            last_price,
-            mark_price,
-            timestamp,
-        } => {
-            if let Some(mut data) = symbol_data.get_mut(&symbol) {
-                data.update_last_price(last_price, timestamp);
-
-                if let Some(mark) = mark_price {
-                    data.update_mark_price(mark, timestamp);
-                }
+/// Starts the HTTP server for episode feeds and live browser push if `[web]` is enabled;
+/// the live feed it returns also gets the alerts
+#[cfg(feature = "dashboard")]
+fn start_web_server(
+    config: &Config,
+    symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+    episode_store: Arc<EpisodeStore>,
+    precision: Option<&PrecisionTracker>,
+    supervisor: &TaskSupervisor,
+) -> Option<LiveFeed> {
+    if !config.web.enabled {
+        info!("Web server is disabled");
+        return None;
+    }
+    let live = LiveFeed::new(&config.web, symbol_data);
+    if config.web.snapshot_interval_ms > 0 {
+        let runner = live.clone();
+        supervisor.supervise("live_feed", move || runner.clone().run());
+    }
+    let mut web_server = WebServer::new(config.web.clone(), episode_store).with_live_feed(live.clone());
+    if let Some(precision) = precision {
+        web_server = web_server.with_precision(precision.clone());
+    }
+    tokio::spawn(async move {
+        if let Err(e) = web_server.run().await {
+            error!("Web server failed: {:?}", e);
+        }
+    });
+    Some(live)
+}
 
-                // Run all strategies
-                strategy1.check(&data);
-                strategy2.check(&data);
-                strategy3.check(&data);
-                strategy4.check(&data);
-                strategy5.check(&data);
+/// Sets up the order executor from execution.toml: checks the trading key of live runs,
+/// starts the profit sweep, order reconciliation and private WebSocket it relies on
+#[cfg(feature = "execution")]
+async fn start_executor(
+    config: &Config,
+    contract_details: &[ContractDetail],
+    disk: &Arc<DiskWriter>,
+    kill_switch: Option<&Arc<KillSwitch>>,
+    tiers: Option<&Arc<VolumeTiers>>,
+    supervisor: &TaskSupervisor,
+) -> anyhow::Result<Arc<OrderExecutor>> {
+    let specs: Vec<ContractSpec> = contract_details
+        .iter()
+        .filter_map(ContractSpec::from_detail)
+        .collect();
+    info!("Order executor initialized with {} contract specs (risk per entry: {} USDT)",
+        specs.len(), config.execution.risk_usdt);
+    // With a sub-account, trading keys never touch the master account's funds
+    let sub_account = &config.execution.sub_account;
+    let execution_credentials = || {
+        if sub_account.enabled {
+            ApiCredentials::from_env_or_config(
+                &format!("{}_SUB", config.api.credentials_prefix()),
+                &sub_account.credentials,
+            )
+        } else {
+            ApiCredentials::from_env_or_config(config.api.credentials_prefix(), &config.execution.credentials)
+        }
+    };
+    if sub_account.enabled {
+        info!("Execution runs in sub-account '{}'", sub_account.name);
+    }
+    let anticipate = &config.execution.anticipate;
+    if anticipate.enabled {
+        info!(
+            "Anticipation orders enabled: {}% of the risk, {}% above the last price, cancelled after {}s without a signal",
+            anticipate.risk_pct, anticipate.offset_pct, anticipate.confirm_secs
+        );
+        if !config.watchlist.enabled {
+            warn!("[Config] execution.anticipate is enabled but [watchlist] is not; no anticipation orders will be placed");
+        }
+    }
+
+    let rest = if config.execution.dry_run {
+        None
+    } else {
+        let credentials = execution_credentials()?;
+        let rest =
+            MexcRestClient::new(config.api.trading_rest_url().to_string(), config.rest.clone())?.with_credentials(credentials);
+        // Fail now rather than on the first signal
+        let timeout = std::time::Duration::from_millis(config.execution.order_timeout_ms.max(5000));
+        if let Err(e) = rest.verify_trading_permissions(timeout).await {
+            anyhow::bail!("Refusing to start execution - the trading key failed the permission check: {}", e);
+        }
+        info!("Trading key verified: futures account readable, order endpoints accessible");
+        Some(Arc::new(rest))
+    };
+
+    if let Some(sub_rest) = rest.as_ref().filter(|_| sub_account.enabled && sub_account.sweep_enabled) {
+        let master = MexcSpotClient::new(
+            sub_account.spot_base_url.clone(),
+            ApiCredentials::from_env_or_config(config.api.credentials_prefix(), &config.execution.credentials)?,
+        );
+        let sweeper = Arc::new(ProfitSweeper::new(sub_account.clone(), sub_rest.clone(), master));
+        supervisor.supervise("profit-sweep", move || sweeper.clone().run());
+        info!(
+            "Profit sweep enabled: balance above {} USDT moves to the master account every {}s",
+            sub_account.working_capital_usdt, sub_account.sweep_interval_secs
+        );
+    }
+
+    let export = &config.execution.ledger_export;
+    if config.execution.dry_run && !export.formats.is_empty() && !export.include_paper {
+        info!("[Ledger] Dry run: paper trades are not exported (ledger_export.include_paper = false)");
+    }
+    let mut executor = OrderExecutor::new(config.execution.clone(), specs, rest)
+        .with_ledger(Arc::new(
+            TradeLedger::new(&config.general.log_dir, disk.clone())?.with_export(config.execution.ledger_export.clone()),
+        ));
+    if let Some(kill_switch) = kill_switch {
+        executor = executor.with_kill_switch(kill_switch.clone());
+    }
+    if let Some(tiers) = tiers {
+        executor = executor.with_tiers(tiers.clone());
+    }
+    let executor = Arc::new(executor);
+
+    if !config.execution.dry_run {
+        let executor = executor.clone();
+        supervisor.supervise("order-reconciliation", move || executor.clone().run_reconciliation());
+    }
+
+    if config.execution.private_ws_enabled {
+        let (private_tx, mut private_rx) = mpsc::unbounded_channel::<PrivateEvent>();
+
+        match execution_credentials() {
+            Ok(credentials) => {
+                let private_client = PrivateWebSocketClient::new(
+                    config.api.trading_ws_url().to_string(),
+                    credentials,
+                    config.execution.relogin_interval_secs,
+                    config.execution.login_timeout_secs,
+                );
+                tokio::spawn(async move {
+                    if let Err(e) = private_client.run(private_tx).await {
+                        error!("Private WebSocket task failed: {:?}", e);
+                    }
+                });
+            }
+            Err(e) => {
+                private_tx.send(PrivateEvent::AuthFailed(e))?;
             }
         }
-        MarketEvent::MarkPriceUpdate {
-            symbol,
-            mark_price,
-            timestamp,
-        } => {
-            if let Some(mut data) = symbol_data.get_mut(&symbol) {
-                data.update_mark_price(mark_price, timestamp);
-
-                // Run all strategies
-                strategy1.check(&data);
-                strategy2.check(&data);
-                strategy3.check(&data);
-                strategy4.check(&data);
-                strategy5.check(&data);
+
+        let executor_for_events = executor.clone();
+        tokio::spawn(async move {
+            while let Some(event) = private_rx.recv().await {
+                executor_for_events.handle_private_event(event);
+            }
+        });
+    }
+
+    Ok(executor)
+}
+
+/// Warns when a file holding API secrets can be read by other users
+fn warn_if_readable_by_others(path: &str) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(metadata) = std::fs::metadata(path) {
+            let mode = metadata.permissions().mode();
+            if mode & 0o077 != 0 {
+                warn!("[Config] {} holds API secrets but has mode {:o}; restrict it with `chmod 600 {}`", path, mode & 0o777, path);
             }
         }
-        MarketEvent::OrderbookUpdate { symbol, orderbook } => {
-            if let Some(mut data) = symbol_data.get_mut(&symbol) {
-                data.update_orderbook(orderbook);
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+/// Runs every parameter set of the grid file over the tape, prints the results and writes
+/// them to the output directory
+async fn backtest_command(config: &Config) -> anyhow::Result<()> {
+    let Some(tape) = std::env::args().nth(2) else {
+        anyhow::bail!("usage: mexc-sniper backtest TAPE [--grid backtest.toml] [--out DIR]");
+    };
+    let grid_path = cli_option("--grid").unwrap_or_else(|| "backtest.toml".to_string());
+    let spec = BacktestSpec::load(std::path::Path::new(&grid_path))?;
+    let sets = spec.parameter_sets()?;
+    let files = tape_files(std::path::Path::new(&tape))?;
+    // Grid values are applied to config.toml as written
+    let base: toml::Table = toml::from_str(&std::fs::read_to_string("config.toml")?)?;
+    let out_dir = cli_option("--out").map(std::path::PathBuf::from).unwrap_or_else(|| {
+        std::path::Path::new(&config.general.log_dir)
+            .join(format!("backtest_{}", chrono::Utc::now().format("%Y%m%d_%H%M%S")))
+    });
+
+    info!(
+        "[Backtest] {} parameter set(s) from {} over {} tape file(s) from {}",
+        sets.len(),
+        grid_path,
+        files.len(),
+        tape
+    );
+    let outcomes = run_backtest(&files, &sets, &base, &spec.pnl, &out_dir).await?;
+    let report = write_report(&out_dir, &outcomes, &spec.pnl)?;
+    print!("{}", render_table(&outcomes, &spec.pnl));
+    info!("[Backtest] Report written to {}", report.display());
+    Ok(())
+}
+
+// Live episodes start on the wall clock when an event arrives, replayed ones at its exchange
+// time; text episode logs also only give whole seconds
+const CONSISTENCY_TOLERANCE_MS: i64 = 2000;
+
+/// Replays the tape twice and compares the episodes with each other and with the live run's
+/// episode logs (`--episodes DIR`, default the log directory); fails when they disagree
+async fn consistency_command(config: &Config) -> anyhow::Result<()> {
+    let Some(tape) = std::env::args().nth(2) else {
+        anyhow::bail!("usage: mexc-sniper consistency TAPE [--episodes DIR] [--tolerance-ms N] [--out DIR]");
+    };
+    let live_log_dir = cli_option("--episodes").unwrap_or_else(|| config.general.log_dir.clone());
+    let tolerance_ms = match cli_option("--tolerance-ms") {
+        Some(ms) => ms
+            .parse::<i64>()
+            .map_err(|_| anyhow::anyhow!("--tolerance-ms must be a whole number, got {:?}", ms))?,
+        None => CONSISTENCY_TOLERANCE_MS,
+    };
+    let out_dir = cli_option("--out").map(std::path::PathBuf::from).unwrap_or_else(|| {
+        std::path::Path::new(&config.general.log_dir)
+            .join(format!("consistency_{}", chrono::Utc::now().format("%Y%m%d_%H%M%S")))
+    });
+
+    // Replayed with the config the live run uses, less what a replay switches off
+    let report = check_consistency(
+        std::path::Path::new(&tape),
+        config,
+        std::path::Path::new(&live_log_dir),
+        chrono::Duration::milliseconds(tolerance_ms),
+        &out_dir,
+    )
+    .await?;
+    print!("{}", report.render());
+    if !report.is_consistent() {
+        anyhow::bail!("replayed episodes differ (replay episode logs in {})", out_dir.display());
+    }
+    Ok(())
+}
+
+/// trades.csv (`--trades PATH`, default the one in the log directory) in the format given
+/// on the command line, live trades only unless `--include-paper` is passed. Written to
+/// `--out PATH` if given, otherwise returned for stdout.
+#[cfg(feature = "execution")]
+fn ledger_export_command(config: &Config) -> anyhow::Result<String> {
+    let usage = "usage: mexc-sniper ledger-export generic|koinly|cointracking [--trades PATH] [--out PATH] [--include-paper]";
+    let Some(format) = std::env::args().nth(2).and_then(|name| LedgerFormat::parse(&name)) else {
+        anyhow::bail!(usage);
+    };
+    let trades_path = cli_option("--trades")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| std::path::Path::new(&config.general.log_dir).join("trades.csv"));
+    let include_paper = std::env::args().any(|arg| arg == "--include-paper");
+
+    let records: Vec<TradeRecord> = read_trades(&trades_path)?
+        .into_iter()
+        .filter(|record| include_paper || !record.dry_run)
+        .collect();
+    let csv = render_export(format, &records)?;
+    match cli_option("--out") {
+        Some(out) => {
+            std::fs::write(&out, csv)?;
+            Ok(format!("{} trades from {} written to {}\n", records.len(), trades_path.display(), out))
+        }
+        None => Ok(csv),
+    }
+}
+
+/// Value of a `--flag value` or `--flag=value` command-line option
+fn cli_option(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix(flag).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Logs a random symbol's prices and every strategy's gates and conditions for it, with
+/// the thresholds the strategy currently applies to the symbol
+fn log_trace(
+    symbol_data: &DashMap<Symbol, SymbolData>,
+    feature_params: FeatureParams,
+    strategies: &StrategyRegistry,
+    orderbook_enabled: bool,
+    rng: &mut rand::rngs::SmallRng,
+) {
+    // Only symbols that have both prices can be evaluated
+    let Some(entry) = symbol_data
+        .iter()
+        .filter(|entry| entry.current_last_price.is_some() && entry.current_mark_price.is_some())
+        .choose(rng)
+    else {
+        return;
+    };
+    let data = entry.value();
+    let features = FeatureCache::new(data, feature_params);
+    let Some(spread) = features.spread() else {
+        return;
+    };
+
+    info!("══════════════════════════════════════════════════════════════");
+    info!("[TRACE] Random Symbol Check: {}", data.symbol);
+    info!("├─ Last Price:    {:.6}", spread.last_price);
+    info!("├─ Mark Price:    {:.6}", spread.mark_price);
+    info!("├─ Ratio:         {:.6} (last/mark)", spread.ratio);
+    info!("├─ Abs Diff:      {:.6} (last - mark)", spread.abs_diff);
+    let orderbook_status = if !orderbook_enabled {
+        "Disabled"
+    } else if data.orderbook.is_some() {
+        "Available"
+    } else {
+        "Not available"
+    };
+    info!("├─ Orderbook:     {}", orderbook_status);
+    let count = strategies.iter().count();
+    for (index, strategy) in strategies.iter().enumerate() {
+        let branch = if index + 1 == count { "└─" } else { "├─" };
+        let Some(explanation) = strategy.explain(data, &features) else {
+            continue;
+        };
+        info!(
+            "{} {} [{}]: {} | {}",
+            branch,
+            strategy.name(),
+            if strategy.enabled() { "ON" } else { "OFF" },
+            if explanation.passed() { "MET" } else { "not met" },
+            explanation
+        );
+    }
+    info!("══════════════════════════════════════════════════════════════");
+}
+
+/// Plain-text response to a control socket command
+fn answer_control_command(
+    command: ControlCommand,
+    symbol_data: &DashMap<Symbol, SymbolData>,
+    feature_params: FeatureParams,
+    readiness: &ReadinessBoard,
+    recording_settings: &RecordingSettings,
+    strategies: &StrategyRegistry,
+) -> String {
+    match command {
+        ControlCommand::Inspect { symbol, window_secs } => {
+            let Some(data) = symbol_data.get(symbol.as_str()) else {
+                return format!("error: {} is not monitored\n", symbol);
+            };
+            let features = FeatureCache::new(&data, feature_params);
+            let states: Vec<StrategyState> = strategies
+                .iter()
+                .map(|strategy| StrategyState {
+                    name: strategy.name(),
+                    enabled: strategy.enabled(),
+                    readiness: readiness.get(&symbol, strategy.name()),
+                    explanation: strategy.explain(&data, &features),
+                })
+                .collect();
+            inspect_report(&data, window_secs, feature_params.spike_lookback_secs, &states)
+        }
+        ControlCommand::Settings => recording_settings.to_string(),
+        ControlCommand::Set { key, value } => match recording_settings.set(&key, &value) {
+            Ok(()) => {
+                info!("[Control] Set {} = {}", key, value);
+                recording_settings.to_string()
+            }
+            Err(e) => format!("error: {}\n", e),
+        },
+        // Written off the event loop by write_issue_bundle
+        ControlCommand::Bundle => unreachable!("bundle requests are not answered here"),
+        // Handed to the manual signals, which this doesn't see
+        ControlCommand::Signal { .. } => unreachable!("signal requests are not answered here"),
+    }
+}
+
+/// Writes an issue bundle in the background and replies with its path
+fn write_issue_bundle(issue_collector: Option<Arc<IssueCollector>>, reply: oneshot::Sender<String>) {
+    let Some(issue_collector) = issue_collector else {
+        let _ = reply.send("error: issue bundles are disabled ([issue_bundle] enabled = false)\n".to_string());
+        return;
+    };
+    tokio::spawn(async move {
+        let response = match issue_collector.create_bundle("requested with `mexc-sniper bundle`").await {
+            Ok(path) => format!("Issue bundle written to {}\n", path.display()),
+            Err(e) => format!("error: {:#}\n", e),
+        };
+        let _ = reply.send(response);
+    });
+}
 
-                // Run strategies that use orderbook data
-                strategy4.check(&data);
-                strategy5.check(&data);
+/// Pause between REST orderbook snapshots after a reconnect, to stay within rate limits
+const ORDERBOOK_RESYNC_DELAY: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// What refilling the orderbooks after a reconnect needs
+struct OrderbookResync {
+    rest: Arc<MexcRestClient>,
+    event_tx: mpsc::UnboundedSender<MarketEvent>,
+    levels: usize,
+    // Symbols whose orderbook the resource budget dropped get no snapshot
+    shedding: Option<Arc<LoadShedding>>,
+}
+
+/// Marks every symbol stale while the market stream is down, and after a reconnect also
+/// refetches the orderbooks the outage dropped
+fn handle_connection_status(
+    status: ConnectionStatus,
+    symbol_data: &Arc<DashMap<Symbol, SymbolData>>,
+    stale_grace_secs: u64,
+    resync: Option<&OrderbookResync>,
+) {
+    mark_connection_status(status, symbol_data, stale_grace_secs);
+    if let (ConnectionStatus::Connected { reconnect: true, .. }, Some(resync)) = (status, resync) {
+        let symbols = symbol_data
+            .iter()
+            .map(|entry| entry.key().to_string())
+            .filter(|symbol| !resync.shedding.as_ref().is_some_and(|shedding| shedding.is_depthless(symbol)))
+            .collect();
+        tokio::spawn(resync_orderbooks(
+            resync.rest.clone(),
+            symbols,
+            resync.levels,
+            symbol_data.clone(),
+            resync.event_tx.clone(),
+        ));
+    }
+}
+
+/// Fetches a REST snapshot for each symbol whose orderbook wasn't refilled by a depth push
+/// yet, fed through the event loop like any other book update
+async fn resync_orderbooks(
+    rest: Arc<MexcRestClient>,
+    symbols: Vec<String>,
+    levels: usize,
+    symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+    event_tx: mpsc::UnboundedSender<MarketEvent>,
+) {
+    let mut refilled = 0;
+    for symbol in &symbols {
+        if symbol_data.get(symbol.as_str()).is_none_or(|data| data.orderbook.is_some()) {
+            continue;
+        }
+        match rest.get_depth(symbol, levels).await {
+            Ok(depth) => {
+                let orderbook = ProcessedOrderbook::from_raw(&depth, levels);
+                if event_tx.send(MarketEvent::OrderbookUpdate { symbol: Symbol::from(symbol.as_str()), orderbook }).is_err() {
+                    return;
+                }
+                refilled += 1;
             }
+            Err(e) => debug!("[Resync] No orderbook snapshot for {}: {:?}", symbol, e),
         }
+        tokio::time::sleep(ORDERBOOK_RESYNC_DELAY).await;
     }
+    info!("[Resync] Refilled {} orderbooks from REST snapshots", refilled);
 }
@@ -0,0 +1,225 @@
+use crate::models::ContractDetail;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Contract metadata that price floors are scaled by
+#[derive(Debug, Clone, Copy)]
+pub struct ContractMeta {
+    /// Base currency per contract
+    pub contract_size: Option<f64>,
+    /// Tick size of the price
+    pub price_unit: Option<f64>,
+}
+
+impl ContractMeta {
+    /// Quote value of one contract at `price`
+    pub fn contract_notional(&self, price: f64) -> Option<f64> {
+        self.contract_size.map(|size| price * size)
+    }
+
+    /// How many ticks `price` is
+    pub fn price_in_ticks(&self, price: f64) -> Option<f64> {
+        self.price_unit.filter(|unit| *unit > 0.0).map(|unit| price / unit)
+    }
+}
+
+/// Metadata of every listed contract, fetched once at startup
+#[derive(Debug, Default)]
+pub struct ContractCatalog {
+    contracts: HashMap<String, ContractMeta>,
+}
+
+impl ContractCatalog {
+    pub fn from_details(details: &[ContractDetail]) -> Self {
+        let contracts = details
+            .iter()
+            .map(|d| {
+                let meta = ContractMeta {
+                    contract_size: d.contract_size,
+                    price_unit: d.price_unit,
+                };
+                (d.symbol.clone(), meta)
+            })
+            .collect();
+        Self { contracts }
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&ContractMeta> {
+        self.contracts.get(symbol)
+    }
+}
+
+/// Trading state of a contract, from the `state` field of the contract details
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractState {
+    Enabled,
+    /// Delivery (settlement) in progress
+    Settling,
+    Settled,
+    Delisted,
+    Paused,
+    Unknown(i32),
+}
+
+impl ContractState {
+    pub fn from_code(code: i32) -> Self {
+        match code {
+            0 => ContractState::Enabled,
+            1 => ContractState::Settling,
+            2 => ContractState::Settled,
+            3 => ContractState::Delisted,
+            4 => ContractState::Paused,
+            other => ContractState::Unknown(other),
+        }
+    }
+
+    /// Whether prices of the contract still mean anything to the strategies
+    pub fn is_trading(&self) -> bool {
+        *self == ContractState::Enabled
+    }
+}
+
+impl fmt::Display for ContractState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractState::Enabled => write!(f, "enabled"),
+            ContractState::Settling => write!(f, "settling"),
+            ContractState::Settled => write!(f, "settled"),
+            ContractState::Delisted => write!(f, "delisted"),
+            ContractState::Paused => write!(f, "paused"),
+            ContractState::Unknown(code) => write!(f, "unknown state {}", code),
+        }
+    }
+}
+
+/// A monitored contract moved to another trading state
+#[derive(Debug, Clone)]
+pub struct ContractStateChange {
+    pub symbol: String,
+    pub previous: ContractState,
+    pub current: ContractState,
+    #[cfg_attr(not(any(feature = "dashboard", feature = "mqtt", feature = "zmq")), allow(dead_code))]
+    pub at: DateTime<Utc>,
+}
+
+impl fmt::Display for ContractStateChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is now {} (was {})", self.symbol, self.current, self.previous)?;
+        if !self.current.is_trading() {
+            write!(f, "; strategies stopped and open episodes closed")?;
+        } else if !self.previous.is_trading() {
+            write!(f, "; strategies resumed")?;
+        }
+        Ok(())
+    }
+}
+
+/// A monitored contract that continues under another symbol, e.g. after a redenomination
+/// (`PEPE_USDT` -> `1000PEPE_USDT`)
+#[derive(Debug, Clone)]
+pub struct SymbolRename {
+    pub from: String,
+    pub to: String,
+    /// Price of the new contract per price of the old one: 1000 when `PEPE` became `1000PEPE`
+    pub price_factor: f64,
+}
+
+impl SymbolRename {
+    /// `to` continues `from` if both settle in the same quote coin, have the same future
+    /// type and denominate the same base coin, up to a power-of-ten multiplier in front
+    pub fn between(from: &ContractDetail, to: &ContractDetail) -> Option<Self> {
+        if from.symbol == to.symbol || from.quote_coin != to.quote_coin || from.future_type != to.future_type {
+            return None;
+        }
+        let (from_multiplier, from_coin) = split_multiplier(base_coin(from));
+        let (to_multiplier, to_coin) = split_multiplier(base_coin(to));
+        (!from_coin.is_empty() && from_coin == to_coin).then(|| Self {
+            from: from.symbol.clone(),
+            to: to.symbol.clone(),
+            price_factor: to_multiplier / from_multiplier,
+        })
+    }
+}
+
+impl fmt::Display for SymbolRename {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} renamed to {}", self.from, self.to)?;
+        if self.price_factor != 1.0 {
+            write!(f, " (prices x{})", self.price_factor)?;
+        }
+        write!(f, "; history and open episodes carried over")
+    }
+}
+
+/// The details' base coin, or the symbol's part before the quote coin if it's missing
+fn base_coin(detail: &ContractDetail) -> &str {
+    if detail.base_coin.is_empty() {
+        detail.symbol.split('_').next().unwrap_or_default()
+    } else {
+        &detail.base_coin
+    }
+}
+
+/// Splits a leading power-of-ten multiplier off a base coin: `1000PEPE` -> (1000, `PEPE`)
+fn split_multiplier(coin: &str) -> (f64, &str) {
+    let digits = coin.len() - coin.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let (prefix, rest) = coin.split_at(digits);
+    let power_of_ten = prefix.len() > 1 && prefix.starts_with('1') && prefix[1..].bytes().all(|b| b == b'0');
+    match prefix.parse::<f64>() {
+        Ok(multiplier) if power_of_ten && !rest.is_empty() => (multiplier, rest),
+        _ => (1.0, coin),
+    }
+}
+
+/// A contract that appeared in the contract details since the last check, usually before
+/// its first trades
+#[derive(Debug, Clone)]
+pub struct NewListing {
+    pub symbol: String,
+    pub state: ContractState,
+    /// Announced start of trading, when the details carry one
+    pub opens_at: Option<DateTime<Utc>>,
+    /// Whether market data of the contract was subscribed in advance
+    pub subscribed: bool,
+    pub at: DateTime<Utc>,
+}
+
+impl NewListing {
+    pub fn from_detail(detail: &ContractDetail, subscribed: bool, at: DateTime<Utc>) -> Self {
+        Self {
+            symbol: detail.symbol.clone(),
+            state: ContractState::from_code(detail.state),
+            opens_at: detail
+                .opening_time
+                .filter(|ms| *ms > 0)
+                .and_then(DateTime::from_timestamp_millis),
+            subscribed,
+            at,
+        }
+    }
+}
+
+impl fmt::Display for NewListing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} listed ({})", self.symbol, self.state)?;
+        match self.opens_at {
+            Some(opens_at) if opens_at > self.at => {
+                let wait = opens_at - self.at;
+                write!(
+                    f,
+                    "; trading opens at {} (in {}h{:02}m)",
+                    opens_at.format("%Y-%m-%d %H:%M UTC"),
+                    wait.num_hours(),
+                    wait.num_minutes() % 60
+                )?;
+            }
+            Some(opens_at) => write!(f, "; trading opened at {}", opens_at.format("%Y-%m-%d %H:%M UTC"))?,
+            None => write!(f, "; opening time not announced")?,
+        }
+        if self.subscribed {
+            write!(f, "; subscribed in advance")?;
+        }
+        Ok(())
+    }
+}
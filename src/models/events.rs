@@ -3,18 +3,93 @@ use chrono::{DateTime, Utc};
 #[derive(Debug, Clone)]
 pub enum MarketEvent {
     TickerUpdate {
-        symbol: String,
+        symbol: super::Symbol,
         last_price: f64,
         mark_price: Option<f64>,
+        stats_24h: Option<super::Stats24h>,
+        /// Best bid/ask from the ticker's bid1/ask1
+        quote: Option<super::Quote>,
         timestamp: DateTime<Utc>,
     },
     MarkPriceUpdate {
-        symbol: String,
+        symbol: super::Symbol,
         mark_price: f64,
         timestamp: DateTime<Utc>,
     },
     OrderbookUpdate {
-        symbol: String,
+        symbol: super::Symbol,
         orderbook: super::ProcessedOrderbook,
     },
+    /// A trade from the deal stream; `volume` is in contracts
+    TradeUpdate {
+        symbol: super::Symbol,
+        price: f64,
+        volume: f64,
+        timestamp: DateTime<Utc>,
+    },
+    FundingRateUpdate {
+        symbol: super::Symbol,
+        rate: f64,
+        timestamp: DateTime<Utc>,
+    },
+    /// From the periodic contract detail re-check, not the market stream
+    ContractStateChange(super::ContractStateChange),
+    /// A monitored contract continues under another symbol; its market data was already
+    /// moved to the new symbol, which is subscribed once this event is queued
+    SymbolRenamed(super::SymbolRename),
+    /// The market stream's connection came up or went down; not tied to a symbol
+    ConnectionStatus(ConnectionStatus),
+}
+
+/// Connection changes of a market stream, so data missed while it was down isn't
+/// mistaken for current
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    /// Connected and every channel subscribed; `reconnect` is false for the first connection
+    Connected { reconnect: bool, timestamp: DateTime<Utc> },
+    /// The connection was lost; no data arrives until the next `Connected`
+    Disconnected { timestamp: DateTime<Utc> },
+}
+
+impl MarketEvent {
+    pub fn symbol(&self) -> &str {
+        match self {
+            MarketEvent::TickerUpdate { symbol, .. }
+            | MarketEvent::MarkPriceUpdate { symbol, .. }
+            | MarketEvent::OrderbookUpdate { symbol, .. }
+            | MarketEvent::TradeUpdate { symbol, .. }
+            | MarketEvent::FundingRateUpdate { symbol, .. } => symbol,
+            MarketEvent::ContractStateChange(change) => &change.symbol,
+            MarketEvent::SymbolRenamed(rename) => &rename.from,
+            MarketEvent::ConnectionStatus(_) => "",
+        }
+    }
+
+    /// The interned symbol of a market data update; state changes and renames carry theirs
+    /// as plain strings
+    pub fn interned_symbol(&self) -> Option<&super::Symbol> {
+        match self {
+            MarketEvent::TickerUpdate { symbol, .. }
+            | MarketEvent::MarkPriceUpdate { symbol, .. }
+            | MarketEvent::OrderbookUpdate { symbol, .. }
+            | MarketEvent::TradeUpdate { symbol, .. }
+            | MarketEvent::FundingRateUpdate { symbol, .. } => Some(symbol),
+            MarketEvent::ContractStateChange(_) | MarketEvent::SymbolRenamed(_) | MarketEvent::ConnectionStatus(_) => None,
+        }
+    }
+
+    /// Exchange time of a market data update, or when the connection changed
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        match self {
+            MarketEvent::TickerUpdate { timestamp, .. }
+            | MarketEvent::MarkPriceUpdate { timestamp, .. }
+            | MarketEvent::TradeUpdate { timestamp, .. }
+            | MarketEvent::FundingRateUpdate { timestamp, .. }
+            | MarketEvent::ConnectionStatus(
+                ConnectionStatus::Connected { timestamp, .. } | ConnectionStatus::Disconnected { timestamp },
+            ) => Some(*timestamp),
+            MarketEvent::OrderbookUpdate { orderbook, .. } => Some(orderbook.timestamp),
+            MarketEvent::ContractStateChange(_) | MarketEvent::SymbolRenamed(_) => None,
+        }
+    }
 }
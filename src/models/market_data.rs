@@ -1,9 +1,14 @@
+use crate::utils::SharedClock;
+use crate::models::ContractState;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Deserializer, Serialize};
-use std::collections::VecDeque;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
 
 // Helper function to deserialize string or number as string
-fn string_or_number<'de, D>(deserializer: D) -> Result<String, D::Error>
+pub(crate) fn string_or_number<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -71,8 +76,10 @@ where
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TickerData {
-    pub symbol: String,
+pub struct TickerData<'a> {
+    /// Borrowed from the frame, to be interned rather than allocated per push
+    #[serde(borrow)]
+    pub symbol: Cow<'a, str>,
     #[serde(rename = "lastPrice", deserialize_with = "string_or_number")]
     pub last_price: String,
     #[serde(rename = "fairPrice", default, deserialize_with = "option_string_or_number")]
@@ -81,12 +88,96 @@ pub struct TickerData {
     pub bid1: Option<String>,
     #[serde(rename = "ask1", default, deserialize_with = "option_string_or_number")]
     pub ask1: Option<String>,
+    /// 24h turnover in USDT
+    #[serde(rename = "amount24", default, deserialize_with = "option_string_or_number")]
+    pub amount24: Option<String>,
+    #[serde(rename = "high24Price", default, deserialize_with = "option_string_or_number")]
+    pub high24_price: Option<String>,
+    #[serde(rename = "lower24Price", default, deserialize_with = "option_string_or_number")]
+    pub lower24_price: Option<String>,
+    /// 24h change as a fraction (0.05 = +5%)
+    #[serde(rename = "riseFallRate", default, deserialize_with = "option_string_or_number")]
+    pub rise_fall_rate: Option<String>,
     pub timestamp: i64,
 }
 
+impl TickerData<'_> {
+    /// 24h statistics carried by the ticker push, if all of them are present
+    pub fn stats_24h(&self) -> Option<Stats24h> {
+        let parse = |value: &Option<String>| value.as_ref().and_then(|v| v.parse::<f64>().ok());
+        Some(Stats24h {
+            volume_usdt: parse(&self.amount24)?,
+            high: parse(&self.high24_price)?,
+            low: parse(&self.lower24_price)?,
+            change_pct: parse(&self.rise_fall_rate)? * 100.0,
+        })
+    }
+
+    /// Optional fields present in the push that don't hold a number, which the lenient
+    /// parsing leaves out
+    pub fn unparsable_fields(&self) -> Vec<&'static str> {
+        let fields = [
+            ("fairPrice", &self.fair_price),
+            ("bid1", &self.bid1),
+            ("ask1", &self.ask1),
+            ("amount24", &self.amount24),
+            ("high24Price", &self.high24_price),
+            ("lower24Price", &self.lower24_price),
+            ("riseFallRate", &self.rise_fall_rate),
+        ];
+        fields
+            .into_iter()
+            .filter(|(_, value)| value.as_ref().is_some_and(|v| v.parse::<f64>().is_err()))
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Best bid/ask carried by the ticker push, if both are present
+    pub fn quote(&self) -> Option<Quote> {
+        let parse = |value: &Option<String>| value.as_ref().and_then(|v| v.parse::<f64>().ok());
+        let timestamp = DateTime::from_timestamp_millis(self.timestamp)?;
+        Quote::new(parse(&self.bid1)?, parse(&self.ask1)?, timestamp)
+    }
+}
+
+/// Rolling 24h statistics of a contract, as reported by the exchange ticker
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Stats24h {
+    /// 24h turnover in USDT
+    pub volume_usdt: f64,
+    pub high: f64,
+    pub low: f64,
+    /// Change of the last price over 24h in percent
+    pub change_pct: f64,
+}
+
+impl std::fmt::Display for Stats24h {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "24h Vol: {} USDT | 24h High/Low: {:.8}/{:.8} | 24h Change: {:+.2}%",
+            format_usdt(self.volume_usdt),
+            self.high,
+            self.low,
+            self.change_pct
+        )
+    }
+}
+
+/// Compact USDT amount, e.g. 52.3k or 48.1M
+fn format_usdt(amount: f64) -> String {
+    match amount {
+        a if a >= 1e9 => format!("{:.1}B", a / 1e9),
+        a if a >= 1e6 => format!("{:.1}M", a / 1e6),
+        a if a >= 1e3 => format!("{:.1}k", a / 1e3),
+        a => format!("{:.0}", a),
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MarkPriceData {
-    pub symbol: String,
+pub struct MarkPriceData<'a> {
+    #[serde(borrow)]
+    pub symbol: Cow<'a, str>,
     #[serde(rename = "fairPrice", deserialize_with = "string_or_number")]
     pub fair_price: String,
     pub timestamp: i64,
@@ -103,6 +194,17 @@ pub struct OrderbookData {
     pub timestamp: i64,
 }
 
+impl OrderbookData {
+    /// Levels of either side that aren't a parsable `[price, quantity, ...]`, which
+    /// `ProcessedOrderbook::from_raw` leaves out
+    pub fn unparsable_levels(&self) -> usize {
+        let parses = |level: &Vec<String>| {
+            level.len() >= 2 && level[0].parse::<f64>().is_ok() && level[1].parse::<f64>().is_ok()
+        };
+        self.bids.iter().chain(&self.asks).filter(|level| !parses(level)).count()
+    }
+}
+
 fn default_timestamp() -> i64 {
     chrono::Utc::now().timestamp_millis()
 }
@@ -187,8 +289,84 @@ impl ProcessedOrderbook {
 
         bid_depth + ask_depth
     }
+
+    /// Notional (price * quantity) of all kept bid levels and all kept ask levels
+    pub fn side_depths(&self) -> (f64, f64) {
+        let notional = |levels: &[OrderbookLevel]| levels.iter().map(|l| l.price * l.quantity).sum::<f64>();
+        (notional(&self.bids), notional(&self.asks))
+    }
+
+    /// (bid - ask) / (bid + ask) notional of the kept levels: +1 all bids, -1 all asks;
+    /// None for an empty book
+    #[cfg(feature = "execution")]
+    pub fn imbalance(&self) -> Option<f64> {
+        let (bid_depth, ask_depth) = self.side_depths();
+        let total = bid_depth + ask_depth;
+        (total > 0.0).then(|| (bid_depth - ask_depth) / total)
+    }
+
+    /// Top of the book; None while one side is empty
+    pub fn best_quote(&self) -> Option<Quote> {
+        Quote::new(self.bids.first()?.price, self.asks.first()?.price, self.timestamp)
+    }
+}
+
+/// Quotes older than this are not used for spread or plausibility checks
+pub const QUOTE_MAX_AGE_SECS: i64 = 5;
+
+/// Best bid and ask, from the top of each depth push or the ticker's bid1/ask1
+#[derive(Debug, Clone, Copy)]
+pub struct Quote {
+    pub best_bid: f64,
+    pub best_ask: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Quote {
+    /// None for empty or crossed prices
+    pub fn new(best_bid: f64, best_ask: f64, timestamp: DateTime<Utc>) -> Option<Self> {
+        (best_bid > 0.0 && best_ask >= best_bid).then_some(Self { best_bid, best_ask, timestamp })
+    }
+
+    /// Bid-ask spread relative to mid
+    pub fn spread_pct(&self) -> f64 {
+        let mid = (self.best_bid + self.best_ask) / 2.0;
+        (self.best_ask - self.best_bid) / mid
+    }
+
+    /// How far `price` lies outside the bid-ask range, relative to the nearer side; 0 inside it
+    pub fn outside_pct(&self, price: f64) -> f64 {
+        if price > self.best_ask {
+            (price - self.best_ask) / self.best_ask
+        } else if price < self.best_bid {
+            (self.best_bid - price) / self.best_bid
+        } else {
+            0.0
+        }
+    }
+}
+
+/// How long price snapshots are kept for lookbacks and baselines
+pub const PRICE_HISTORY_SECS: i64 = 120;
+
+// Lowered by the resource budget's shorten_history mitigation
+static HISTORY_RETENTION_SECS: AtomicI64 = AtomicI64::new(PRICE_HISTORY_SECS);
+
+/// How long price snapshots are currently kept: `PRICE_HISTORY_SECS` unless shortened
+pub fn history_retention_secs() -> i64 {
+    HISTORY_RETENTION_SECS.load(Ordering::Relaxed)
+}
+
+/// Shortens the price history of every symbol to `secs` (at most `PRICE_HISTORY_SECS`),
+/// or restores it with None; older snapshots go with each symbol's next update
+pub fn set_history_retention_secs(secs: Option<i64>) {
+    let secs = secs.map_or(PRICE_HISTORY_SECS, |secs| secs.clamp(1, PRICE_HISTORY_SECS));
+    HISTORY_RETENTION_SECS.store(secs, Ordering::Relaxed);
 }
 
+/// How long open interest samples are kept, the longest `oi_lookback_secs` that works
+pub const OPEN_INTEREST_HISTORY_SECS: i64 = 900;
+
 #[derive(Debug, Clone)]
 pub struct PriceSnapshot {
     pub last_price: f64,
@@ -204,22 +382,43 @@ pub struct Candle {
     pub high: f64,
     pub low: f64,
     pub close: f64,
-    pub volume: f64,  // Note: Currently set to 0.0 as volume not available in WebSocket data
+    /// Traded contracts from the deal stream; 0 without trades (or with trades disabled)
+    pub volume: f64,
+    /// Price updates that went into the candle; 0 for forward-filled candles and exchange klines
+    pub tick_count: u32,
 }
 
 impl Candle {
     pub fn from_single_price(timestamp: DateTime<Utc>, price: f64) -> Self {
         Self {
-            timestamp_ms: timestamp.timestamp_millis(),
+            tick_count: 1,
+            ..Self::flat(timestamp.timestamp_millis(), price)
+        }
+    }
+
+    /// Candle with all four prices equal to `price` and no ticks
+    pub fn flat(timestamp_ms: i64, price: f64) -> Self {
+        Self {
+            timestamp_ms,
             open: price,
             high: price,
             low: price,
             close: price,
             volume: 0.0,
+            tick_count: 0,
         }
     }
 
+    /// Multiplies the prices by `factor`, e.g. after a redenomination
+    pub fn scale_prices(&mut self, factor: f64) {
+        self.open *= factor;
+        self.high *= factor;
+        self.low *= factor;
+        self.close *= factor;
+    }
+
     pub fn update_price(&mut self, price: f64) {
+        self.tick_count += 1;
         if price > self.high {
             self.high = price;
         }
@@ -230,10 +429,139 @@ impl Candle {
     }
 }
 
+/// Rolls candles up into candles of `window_ms`, keyed by window start.
+///
+/// `window_ms` should be a multiple of the source interval; a source candle is assigned
+/// to the window its open falls in.
+pub fn aggregate_candles(candles: &[Candle], window_ms: i64) -> Vec<Candle> {
+    let mut windows: BTreeMap<i64, Candle> = BTreeMap::new();
+
+    for candle in candles {
+        let start = window_start(candle.timestamp_ms, window_ms);
+        windows
+            .entry(start)
+            .and_modify(|w| {
+                w.high = w.high.max(candle.high);
+                w.low = w.low.min(candle.low);
+                w.close = candle.close;
+                w.volume += candle.volume;
+                w.tick_count += candle.tick_count;
+            })
+            .or_insert_with(|| Candle {
+                timestamp_ms: start,
+                ..candle.clone()
+            });
+    }
+
+    windows.into_values().collect()
+}
+
+/// Builds candles of `window_ms` straight from raw price snapshots, using `price` to pick
+/// the series (e.g. `|s| s.last_price`).
+///
+/// Windows without a snapshot are skipped rather than forward-filled.
+pub fn candles_from_snapshots<'a>(
+    snapshots: impl IntoIterator<Item = &'a PriceSnapshot>,
+    window_ms: i64,
+    price: impl Fn(&PriceSnapshot) -> f64,
+) -> Vec<Candle> {
+    let mut windows: BTreeMap<i64, Candle> = BTreeMap::new();
+
+    for snapshot in snapshots {
+        let start = window_start(snapshot.timestamp.timestamp_millis(), window_ms);
+        let value = price(snapshot);
+        windows
+            .entry(start)
+            .and_modify(|c| c.update_price(value))
+            .or_insert_with(|| Candle {
+                tick_count: 1,
+                ..Candle::flat(start, value)
+            });
+    }
+
+    windows.into_values().collect()
+}
+
+fn window_start(timestamp_ms: i64, window_ms: i64) -> i64 {
+    timestamp_ms - timestamp_ms.rem_euclid(window_ms.max(1))
+}
+
+/// Orderbook depth at a candle's close, aligned with the candle by `timestamp_ms`
+#[derive(Debug, Clone)]
+pub struct DepthSample {
+    pub timestamp_ms: i64,
+    /// Notional of the kept bid levels (USDT)
+    pub bid_depth: f64,
+    pub ask_depth: f64,
+    /// (bid - ask) / (bid + ask): +1 all bids, -1 all asks
+    pub imbalance: f64,
+}
+
+impl DepthSample {
+    fn new(timestamp_ms: i64, bid_depth: f64, ask_depth: f64) -> Self {
+        let total = bid_depth + ask_depth;
+        Self {
+            timestamp_ms,
+            bid_depth,
+            ask_depth,
+            imbalance: if total > 0.0 { (bid_depth - ask_depth) / total } else { 0.0 },
+        }
+    }
+}
+
+/// Completed candles of both price series and the book depth at each close
+#[derive(Debug, Clone, Default)]
+pub struct CandleSeries {
+    pub last_price: Vec<Candle>,
+    pub mark_price: Vec<Candle>,
+    /// Empty while orderbooks are disabled
+    pub depth: Vec<DepthSample>,
+    /// Candles of the short fine-resolution buffer, including the one still open; empty
+    /// unless fine candles are kept
+    pub fine_last_price: Vec<Candle>,
+    pub fine_mark_price: Vec<Candle>,
+}
+
+impl CandleSeries {
+    pub fn extend(&mut self, other: CandleSeries) {
+        self.last_price.extend(other.last_price);
+        self.mark_price.extend(other.mark_price);
+        self.depth.extend(other.depth);
+        self.fine_last_price.extend(other.fine_last_price);
+        self.fine_mark_price.extend(other.fine_mark_price);
+    }
+
+    /// Multiplies every candle's prices by `factor`; depth is in USDT and stays as it is
+    pub fn scale_prices(&mut self, factor: f64) {
+        let candles = self.last_price.iter_mut().chain(&mut self.mark_price);
+        for candle in candles.chain(&mut self.fine_last_price).chain(&mut self.fine_mark_price) {
+            candle.scale_prices(factor);
+        }
+    }
+}
+
+/// Width of the candles every symbol keeps
+pub const CANDLE_WINDOW_MS: i64 = 500;
+
+// How much completed candle history a buffer keeps by default
+const COMPLETED_HISTORY_MS: i64 = 20_000;
+
+/// A short buffer of candles finer than `CANDLE_WINDOW_MS`, kept next to the regular ones
+#[derive(Debug, Clone, Copy)]
+pub struct FineCandles {
+    pub window_ms: i64,
+    pub keep_secs: i64,
+}
+
+// Book updates kept while waiting for the candle they close; bounds quiet symbols
+const MAX_PENDING_BOOK_UPDATES: usize = 64;
+
 /// Accumulates price updates into 500ms candles
 #[derive(Debug, Clone)]
 pub struct CandleBuffer {
     window_ms: i64,
+    // Completed candles (and depth samples) kept per series
+    max_completed: usize,
     current_window_start: Option<i64>,
     current_last_price_candle: Option<Candle>,
     current_mark_price_candle: Option<Candle>,
@@ -241,12 +569,20 @@ pub struct CandleBuffer {
     completed_mark_price_candles: VecDeque<Candle>,
     last_known_last_price: Option<f64>,
     last_known_mark_price: Option<f64>,
+    // Traded contracts in the current window, put on its last price candle when it closes
+    current_volume: f64,
+    // (timestamp_ms, bid depth, ask depth), oldest first
+    book_updates: VecDeque<(i64, f64, f64)>,
+    completed_depth_samples: VecDeque<DepthSample>,
+    // Same prices and trades at a finer resolution, without depth samples
+    fine: Option<Box<CandleBuffer>>,
 }
 
 impl CandleBuffer {
     pub fn new(window_ms: i64) -> Self {
         Self {
             window_ms,
+            max_completed: (COMPLETED_HISTORY_MS / window_ms.max(1)) as usize,
             current_window_start: None,
             current_last_price_candle: None,
             current_mark_price_candle: None,
@@ -254,31 +590,77 @@ impl CandleBuffer {
             completed_mark_price_candles: VecDeque::new(),
             last_known_last_price: None,
             last_known_mark_price: None,
+            current_volume: 0.0,
+            book_updates: VecDeque::new(),
+            completed_depth_samples: VecDeque::new(),
+            fine: None,
         }
     }
 
-    pub fn add_price_update(&mut self, last_price: Option<f64>, mark_price: Option<f64>, timestamp: DateTime<Utc>) {
+    /// Also keeps the last `fine.keep_secs` of candles at `fine.window_ms`
+    pub fn with_fine_candles(mut self, fine: FineCandles) -> Self {
+        let mut buffer = CandleBuffer::new(fine.window_ms);
+        buffer.max_completed = (fine.keep_secs * 1000 / fine.window_ms.max(1)).max(1) as usize;
+        self.fine = Some(Box::new(buffer));
+        self
+    }
+
+    /// Records the book depth; it is sampled when the candle it falls into closes
+    pub fn add_book_update(&mut self, bid_depth: f64, ask_depth: f64, timestamp: DateTime<Utc>) {
+        self.book_updates.push_back((timestamp.timestamp_millis(), bid_depth, ask_depth));
+        while self.book_updates.len() > MAX_PENDING_BOOK_UPDATES {
+            self.book_updates.pop_front();
+        }
+    }
+
+    /// Stores the depth of the newest book update before the close of the candle at `window_start`
+    fn sample_depth(&mut self, window_start: i64) {
+        let close = window_start + self.window_ms;
+        while self.book_updates.get(1).is_some_and(|(ts, _, _)| *ts < close) {
+            self.book_updates.pop_front();
+        }
+        if let Some(&(ts, bid_depth, ask_depth)) = self.book_updates.front() {
+            if ts < close {
+                self.completed_depth_samples.push_back(DepthSample::new(window_start, bid_depth, ask_depth));
+            }
+        }
+    }
+
+    /// Adds a trade's volume to the candle it falls into
+    pub fn add_trade(&mut self, volume: f64, timestamp: DateTime<Utc>) {
         let ts_ms = timestamp.timestamp_millis();
         let window_start = (ts_ms / self.window_ms) * self.window_ms;
 
-        // Check if we've moved to a new window
-        if let Some(current_start) = self.current_window_start {
-            if window_start > current_start {
-                // Complete the current candles and start new ones
-                self.complete_current_candles(current_start);
-
-                // Forward-fill any gaps with last known prices
-                let mut gap_start = current_start + self.window_ms;
-                let mut gap_count = 0;
-                while gap_start < window_start {
-                    self.forward_fill_candle(gap_start);
-                    gap_start += self.window_ms;
-                    gap_count += 1;
-                }
+        // A trade that arrives after its window closed goes onto the completed candle
+        if self.current_window_start.is_some_and(|current_start| window_start < current_start) {
+            if let Some(candle) = self
+                .completed_last_price_candles
+                .iter_mut()
+                .rev()
+                .find(|candle| candle.timestamp_ms == window_start)
+            {
+                candle.volume += volume;
             }
+            return;
         }
 
-        self.current_window_start = Some(window_start);
+        self.advance_window(window_start);
+        self.current_volume += volume;
+
+        if let Some(fine) = &mut self.fine {
+            fine.add_trade(volume, timestamp);
+        }
+    }
+
+    #[tracing::instrument(level = "trace", name = "candle_update", skip_all)]
+    pub fn add_price_update(&mut self, last_price: Option<f64>, mark_price: Option<f64>, timestamp: DateTime<Utc>) {
+        let ts_ms = timestamp.timestamp_millis();
+        let window_start = (ts_ms / self.window_ms) * self.window_ms;
+        self.advance_window(window_start);
+
+        if let Some(fine) = &mut self.fine {
+            fine.add_price_update(last_price, mark_price, timestamp);
+        }
 
         // Update last_price candle
         if let Some(price) = last_price {
@@ -309,32 +691,69 @@ impl CandleBuffer {
         }
     }
 
-    fn complete_current_candles(&mut self, _window_start: i64) {
-        if let Some(candle) = self.current_last_price_candle.take() {
+    /// Completes the current candles once `window_start` lies past their window
+    fn advance_window(&mut self, window_start: i64) {
+        if let Some(current_start) = self.current_window_start {
+            if window_start > current_start {
+                // Complete the current candles and start new ones
+                self.complete_current_candles(current_start);
+
+                // Forward-fill any gaps with last known prices, skipping windows that
+                // wouldn't be kept anyway
+                let oldest_kept = window_start - self.max_completed as i64 * self.window_ms;
+                let mut gap_start = (current_start + self.window_ms).max(oldest_kept);
+                while gap_start < window_start {
+                    self.forward_fill_candle(gap_start);
+                    gap_start += self.window_ms;
+                }
+                self.trim_completed();
+            }
+        }
+
+        self.current_window_start = Some(window_start);
+    }
+
+    fn complete_current_candles(&mut self, window_start: i64) {
+        let volume = std::mem::take(&mut self.current_volume);
+        // A window with trades but no last price update still gets a candle for its volume
+        let mut candle = self.current_last_price_candle.take();
+        if candle.is_none() && volume > 0.0 {
+            candle = self.last_known_last_price.map(|price| Candle::flat(window_start, price));
+        }
+        if let Some(mut candle) = candle {
+            candle.volume = volume;
             self.completed_last_price_candles.push_back(candle);
         }
         if let Some(candle) = self.current_mark_price_candle.take() {
             self.completed_mark_price_candles.push_back(candle);
         }
+        self.sample_depth(window_start);
+    }
 
-        // Keep only last 20 seconds of completed candles (40 candles at 500ms each)
-        while self.completed_last_price_candles.len() > 40 {
+    // Keep only the last 20 seconds of completed candles (40 candles at 500ms each), or
+    // `keep_secs` of a fine buffer
+    fn trim_completed(&mut self) {
+        while self.completed_last_price_candles.len() > self.max_completed {
             self.completed_last_price_candles.pop_front();
         }
-        while self.completed_mark_price_candles.len() > 40 {
+        while self.completed_mark_price_candles.len() > self.max_completed {
             self.completed_mark_price_candles.pop_front();
         }
+        while self.completed_depth_samples.len() > self.max_completed {
+            self.completed_depth_samples.pop_front();
+        }
     }
 
+    // Candle times come only from update timestamps, never from a clock, so replays
+    // produce the same candles as the live run
     fn forward_fill_candle(&mut self, window_start: i64) {
-        let timestamp = DateTime::from_timestamp_millis(window_start).unwrap_or_else(Utc::now);
-
         if let Some(price) = self.last_known_last_price {
-            self.completed_last_price_candles.push_back(Candle::from_single_price(timestamp, price));
+            self.completed_last_price_candles.push_back(Candle::flat(window_start, price));
         }
         if let Some(price) = self.last_known_mark_price {
-            self.completed_mark_price_candles.push_back(Candle::from_single_price(timestamp, price));
+            self.completed_mark_price_candles.push_back(Candle::flat(window_start, price));
         }
+        self.sample_depth(window_start);
     }
 
     pub fn get_recent_candles(&self, seconds: i64) -> (Vec<Candle>, Vec<Candle>) {
@@ -359,83 +778,325 @@ impl CandleBuffer {
         (last_price_candles, mark_price_candles)
     }
 
-    pub fn get_all_completed_candles(&self) -> (Vec<Candle>, Vec<Candle>) {
-        (
-            self.completed_last_price_candles.iter().cloned().collect(),
-            self.completed_mark_price_candles.iter().cloned().collect()
-        )
+    /// Completed last-price candles, oldest first, without copying them
+    pub fn completed_last_price_candles(&self) -> impl DoubleEndedIterator<Item = &Candle> + Clone {
+        self.completed_last_price_candles.iter()
     }
 
-    pub fn get_pre_buffer_candles(&self, seconds: i64) -> (Vec<Candle>, Vec<Candle>) {
-        let requested_count = (seconds * 1000 / self.window_ms) as usize;
-        let all_candles = self.get_all_completed_candles();
+    pub fn get_all_completed_candles(&self) -> CandleSeries {
+        let mut series = CandleSeries {
+            last_price: self.completed_last_price_candles.iter().cloned().collect(),
+            mark_price: self.completed_mark_price_candles.iter().cloned().collect(),
+            depth: self.completed_depth_samples.iter().cloned().collect(),
+            ..CandleSeries::default()
+        };
+        if let Some(fine) = &self.fine {
+            let mut fine_series = fine.get_all_completed_candles();
+            fine_series.last_price.extend(fine.current_last_price_candle.clone());
+            fine_series.mark_price.extend(fine.current_mark_price_candle.clone());
+            series.fine_last_price = fine_series.last_price;
+            series.fine_mark_price = fine_series.mark_price;
+        }
+        series
+    }
 
-        let last_len = all_candles.0.len();
-        let mark_len = all_candles.1.len();
+    /// Multiplies the prices of every candle, open or completed, by `factor`
+    pub fn scale_prices(&mut self, factor: f64) {
+        let open = self.current_last_price_candle.iter_mut().chain(&mut self.current_mark_price_candle);
+        for candle in open.chain(&mut self.completed_last_price_candles).chain(&mut self.completed_mark_price_candles) {
+            candle.scale_prices(factor);
+        }
+        for price in self.last_known_last_price.iter_mut().chain(&mut self.last_known_mark_price) {
+            *price *= factor;
+        }
+        if let Some(fine) = &mut self.fine {
+            fine.scale_prices(factor);
+        }
+    }
 
-        let last_price_candles = if last_len > requested_count {
-            all_candles.0.into_iter().skip(last_len - requested_count).collect()
-        } else {
-            all_candles.0
-        };
+    /// Rough heap usage of the buffered candles, samples and book updates
+    pub fn approx_heap_bytes(&self) -> usize {
+        (self.completed_last_price_candles.capacity() + self.completed_mark_price_candles.capacity())
+            * std::mem::size_of::<Candle>()
+            + self.completed_depth_samples.capacity() * std::mem::size_of::<DepthSample>()
+            + self.book_updates.capacity() * std::mem::size_of::<(i64, f64, f64)>()
+            + self.fine.as_ref().map_or(0, |fine| fine.approx_heap_bytes())
+    }
 
-        let mark_price_candles = if mark_len > requested_count {
-            all_candles.1.into_iter().skip(mark_len - requested_count).collect()
-        } else {
-            all_candles.1
-        };
+    pub fn get_pre_buffer_candles(&self, seconds: i64) -> CandleSeries {
+        let requested_count = (seconds * 1000 / self.window_ms) as usize;
+        let mut all_candles = self.get_all_completed_candles();
 
-        (last_price_candles, mark_price_candles)
+        all_candles.last_price = keep_last(all_candles.last_price, requested_count);
+        all_candles.mark_price = keep_last(all_candles.mark_price, requested_count);
+        // Depth samples from the oldest candle kept onwards
+        let since_ms = all_candles.last_price.first().map(|c| c.timestamp_ms).unwrap_or(i64::MAX);
+        all_candles.depth.retain(|s| s.timestamp_ms >= since_ms);
+
+        all_candles
+    }
+}
+
+fn keep_last<T>(mut items: Vec<T>, count: usize) -> Vec<T> {
+    if items.len() > count {
+        items.drain(..items.len() - count);
     }
+    items
 }
 
+/// Everything known about one symbol. The price and candle histories sit behind `Arc`s so
+/// a clone is a cheap snapshot for the strategies; an update copies them only while such a
+/// snapshot is still alive.
 #[derive(Debug, Clone)]
 pub struct SymbolData {
-    pub symbol: String,
+    pub symbol: super::Symbol,
     pub current_last_price: Option<f64>,
     pub current_mark_price: Option<f64>,
     pub orderbook: Option<ProcessedOrderbook>,
     pub last_update: DateTime<Utc>,
 
     // Historical data for strategies
-    pub price_history: VecDeque<PriceSnapshot>,
+    pub price_history: Arc<VecDeque<PriceSnapshot>>,
 
     // Candle buffer for CSV export
-    pub candle_buffer: CandleBuffer,
+    pub candle_buffer: Arc<CandleBuffer>,
+
+    // Set while the symbol is on the watchlist
+    pub watch: Option<WatchState>,
+
+    // Newest trade from the deal stream, or when the last price last changed
+    // (i.e. the contract actually traded)
+    pub last_trade_at: Option<DateTime<Utc>>,
+
+    // Exchange time of the latest last price and mark price updates, changed or not
+    pub last_price_at: Option<DateTime<Utc>>,
+    pub mark_price_at: Option<DateTime<Utc>>,
+
+    // 24h volume, high/low and change from the latest ticker
+    pub stats_24h: Option<Stats24h>,
+
+    // Current funding rate from the funding rate channel
+    pub funding_rate: Option<f64>,
+
+    // Open interest (contracts) polled from the REST ticker, oldest first
+    pub open_interest: VecDeque<(DateTime<Utc>, f64)>,
+
+    // Best bid/ask from the latest depth push or ticker, whichever is newer
+    pub quote: Option<Quote>,
+
+    // Strategies skip the symbol while its contract isn't trading
+    pub contract_state: ContractState,
+
+    // Set while the market stream is down and for a grace period after it's back, so
+    // prices missed during the outage don't look like sudden moves
+    pub stale_until: Option<DateTime<Utc>>,
+
+    // Source of "now" for history pruning and lookbacks
+    clock: SharedClock,
+}
+
+/// Escalated monitoring of a symbol that showed early signs of a pump
+#[derive(Debug, Clone)]
+pub struct WatchState {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// Fraction of each ratio threshold's distance above 1.0 that is waived while watched
+    pub relax_fraction: f64,
 }
 
 impl SymbolData {
-    pub fn new(symbol: String) -> Self {
+    pub fn new(symbol: impl Into<super::Symbol>, clock: SharedClock) -> Self {
         Self {
-            symbol,
+            symbol: symbol.into(),
             current_last_price: None,
             current_mark_price: None,
             orderbook: None,
-            last_update: Utc::now(),
-            price_history: VecDeque::new(),
-            candle_buffer: CandleBuffer::new(500), // 500ms candles
+            last_update: clock.now(),
+            price_history: Arc::new(VecDeque::new()),
+            candle_buffer: Arc::new(CandleBuffer::new(CANDLE_WINDOW_MS)),
+            watch: None,
+            last_trade_at: None,
+            last_price_at: None,
+            mark_price_at: None,
+            stats_24h: None,
+            funding_rate: None,
+            open_interest: VecDeque::new(),
+            quote: None,
+            contract_state: ContractState::Enabled,
+            stale_until: None,
+            clock,
+        }
+    }
+
+    /// Rough memory held for this symbol (history, candles and orderbook), for the status report
+    pub fn approx_bytes(&self) -> usize {
+        let orderbook = self
+            .orderbook
+            .as_ref()
+            .map(|book| (book.bids.capacity() + book.asks.capacity()) * std::mem::size_of::<OrderbookLevel>())
+            .unwrap_or(0);
+        std::mem::size_of::<Self>()
+            + self.symbol.len()
+            + self.price_history.capacity() * std::mem::size_of::<PriceSnapshot>()
+            + self.open_interest.capacity() * std::mem::size_of::<(DateTime<Utc>, f64)>()
+            + self.candle_buffer.approx_heap_bytes()
+            + orderbook
+    }
+
+    /// Current time according to this symbol's clock
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    /// Spread ratio threshold to apply, relaxed while the symbol is on the watchlist
+    pub fn ratio_threshold(&self, base: f64) -> f64 {
+        match self.watch {
+            Some(ref watch) => 1.0 + (base - 1.0) * (1.0 - watch.relax_fraction),
+            None => base,
         }
     }
 
+    /// Keeps a short buffer of finer candles for chart recordings, if configured
+    pub fn with_fine_candles(mut self, fine: Option<FineCandles>) -> Self {
+        if let Some(fine) = fine {
+            self.candle_buffer = Arc::new(Arc::unwrap_or_clone(self.candle_buffer).with_fine_candles(fine));
+        }
+        self
+    }
+
     pub fn update_last_price(&mut self, price: f64, timestamp: DateTime<Utc>) {
+        if self.current_last_price != Some(price) {
+            self.last_trade_at = Some(timestamp);
+        }
         self.current_last_price = Some(price);
+        self.last_price_at = Some(timestamp);
         self.last_update = timestamp;
         self.add_to_history();
         // Update candle buffer
-        self.candle_buffer.add_price_update(Some(price), self.current_mark_price, timestamp);
+        Arc::make_mut(&mut self.candle_buffer).add_price_update(Some(price), self.current_mark_price, timestamp);
     }
 
     pub fn update_mark_price(&mut self, price: f64, timestamp: DateTime<Utc>) {
         self.current_mark_price = Some(price);
+        self.mark_price_at = Some(timestamp);
         self.last_update = timestamp;
         self.add_to_history();
         // Update candle buffer
-        self.candle_buffer.add_price_update(self.current_last_price, Some(price), timestamp);
+        Arc::make_mut(&mut self.candle_buffer).add_price_update(self.current_last_price, Some(price), timestamp);
+    }
+
+    pub fn update_trade(&mut self, volume: f64, timestamp: DateTime<Utc>) {
+        if self.last_trade_at.is_none_or(|at| at < timestamp) {
+            self.last_trade_at = Some(timestamp);
+        }
+        Arc::make_mut(&mut self.candle_buffer).add_trade(volume, timestamp);
     }
 
     pub fn update_orderbook(&mut self, orderbook: ProcessedOrderbook) {
+        let (bid_depth, ask_depth) = orderbook.side_depths();
+        Arc::make_mut(&mut self.candle_buffer).add_book_update(bid_depth, ask_depth, orderbook.timestamp);
+        if let Some(quote) = orderbook.best_quote() {
+            self.update_quote(quote);
+        }
         self.orderbook = Some(orderbook);
-        self.last_update = Utc::now();
+        self.last_update = self.clock.now();
+    }
+
+    /// The market stream went down: the orderbook no longer follows the exchange and is
+    /// dropped, and the symbol stays stale until `resume_after`
+    pub fn mark_stale(&mut self) {
+        self.stale_until = Some(DateTime::<Utc>::MAX_UTC);
+        self.orderbook = None;
+    }
+
+    /// The market stream is back; the symbol stays stale for `grace` while fresh data
+    /// replaces what was missed
+    pub fn resume_after(&mut self, grace: chrono::Duration) {
+        if self.stale_until.is_some() {
+            self.stale_until = Some(self.clock.now() + grace);
+        }
+    }
+
+    pub fn is_stale(&self) -> bool {
+        self.stale_until.is_some_and(|until| self.clock.now() < until)
+    }
+
+    /// Continues the symbol under a new name (a renamed contract), with its history
+    /// multiplied by `price_factor`. The orderbook is dropped until the new symbol's first
+    /// depth push, and open interest until its next poll, as contract counts don't carry over.
+    pub fn rename(&mut self, to: &str, price_factor: f64) {
+        self.symbol = to.into();
+        self.orderbook = None;
+        self.open_interest.clear();
+        if price_factor == 1.0 {
+            return;
+        }
+        for price in self.current_last_price.iter_mut().chain(&mut self.current_mark_price) {
+            *price *= price_factor;
+        }
+        for snapshot in Arc::make_mut(&mut self.price_history).iter_mut() {
+            snapshot.last_price *= price_factor;
+            snapshot.mark_price *= price_factor;
+        }
+        Arc::make_mut(&mut self.candle_buffer).scale_prices(price_factor);
+        if let Some(stats) = &mut self.stats_24h {
+            stats.high *= price_factor;
+            stats.low *= price_factor;
+        }
+        if let Some(quote) = &mut self.quote {
+            quote.best_bid *= price_factor;
+            quote.best_ask *= price_factor;
+        }
+    }
+
+    /// Keeps the newer of the current and the given quote
+    pub fn update_quote(&mut self, quote: Quote) {
+        if self.quote.is_none_or(|current| current.timestamp <= quote.timestamp) {
+            self.quote = Some(quote);
+        }
+    }
+
+    /// The best bid/ask, unless older than `QUOTE_MAX_AGE_SECS`
+    pub fn fresh_quote(&self) -> Option<Quote> {
+        let quote = self.quote?;
+        (self.clock.now() - quote.timestamp <= chrono::Duration::seconds(QUOTE_MAX_AGE_SECS)).then_some(quote)
+    }
+
+    /// Best bid / mark price: the ratio a short entry would actually get, unlike last / mark
+    pub fn tradable_ratio(&self) -> Option<f64> {
+        Some(self.fresh_quote()?.best_bid / self.current_mark_price?)
+    }
+
+    pub fn update_open_interest(&mut self, contracts: f64, timestamp: DateTime<Utc>) {
+        self.open_interest.push_back((timestamp, contracts));
+        let cutoff = timestamp - chrono::Duration::seconds(OPEN_INTEREST_HISTORY_SECS);
+        while self.open_interest.front().is_some_and(|(at, _)| *at < cutoff) {
+            self.open_interest.pop_front();
+        }
+    }
+
+    /// Change of the open interest over the last `secs` in percent, from the newest sample
+    /// at or before then to the latest one; None until the samples reach back that far
+    pub fn open_interest_change_pct(&self, secs: u64) -> Option<f64> {
+        let since = self.clock.now() - chrono::Duration::seconds(secs as i64);
+        let (_, then) = self.open_interest.iter().rev().find(|(at, _)| *at <= since)?;
+        let (_, latest) = self.open_interest.back()?;
+        (*then > 0.0).then(|| (latest / then - 1.0) * 100.0)
+    }
+
+    /// How far the last price lies outside a fresh bid-ask range, relative to the nearer side
+    /// (0 inside it); a large value points at a bad print rather than a tradable price.
+    /// None without a last price or a fresh quote.
+    pub fn last_outside_quote_pct(&self) -> Option<f64> {
+        Some(self.fresh_quote()?.outside_pct(self.current_last_price?))
+    }
+
+    /// Time since the older of the last and mark price was updated, e.g. a frozen ticker next
+    /// to a moving mark price; None until both have been seen
+    pub fn price_age_ms(&self) -> Option<i64> {
+        let now = self.clock.now();
+        let age_ms = |at: DateTime<Utc>| (now - at).num_milliseconds();
+        Some(age_ms(self.last_price_at?).max(age_ms(self.mark_price_at?)))
     }
 
     fn add_to_history(&mut self) {
@@ -446,13 +1107,14 @@ impl SymbolData {
                 timestamp: self.last_update,
             };
 
-            self.price_history.push_back(snapshot);
+            let history = Arc::make_mut(&mut self.price_history);
+            history.push_back(snapshot);
 
-            // Keep only last 2 minutes of history
-            let cutoff = Utc::now() - chrono::Duration::seconds(120);
-            while let Some(front) = self.price_history.front() {
+            // Keep only last 2 minutes of history (less while the resource budget shortens it)
+            let cutoff = self.clock.now() - chrono::Duration::seconds(history_retention_secs());
+            while let Some(front) = history.front() {
                 if front.timestamp < cutoff {
-                    self.price_history.pop_front();
+                    history.pop_front();
                 } else {
                     break;
                 }
@@ -460,17 +1122,56 @@ impl SymbolData {
         }
     }
 
+    /// Whether the price history reaches back at least `secs`
+    pub fn history_covers(&self, secs: u64) -> bool {
+        let since = self.clock.now() - chrono::Duration::seconds(secs as i64);
+        self.price_history.front().is_some_and(|s| s.timestamp <= since)
+    }
+
+    /// Standard deviation of the mark price over the last `secs`, relative to its mean.
+    /// The mark price is used so a pump in the last price doesn't widen its own depth band.
+    /// None until the history reaches back that far.
+    pub fn mark_price_volatility(&self, secs: u64) -> Option<f64> {
+        if !self.history_covers(secs) {
+            return None;
+        }
+        let cutoff = self.clock.now() - chrono::Duration::seconds(secs as i64);
+        let prices: Vec<f64> = self.price_history.iter()
+            .filter(|s| s.timestamp >= cutoff)
+            .map(|s| s.mark_price)
+            .collect();
+        if prices.len() < 2 {
+            return None;
+        }
+
+        let mean = prices.iter().sum::<f64>() / prices.len() as f64;
+        if mean <= 0.0 {
+            return None;
+        }
+        let variance = prices.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / prices.len() as f64;
+        Some(variance.sqrt() / mean)
+    }
+
     pub fn get_price_at(&self, seconds_ago: u64) -> Option<f64> {
-        let target_time = Utc::now() - chrono::Duration::seconds(seconds_ago as i64);
+        let target_time = self.clock.now() - chrono::Duration::seconds(seconds_ago as i64);
 
         self.price_history.iter()
-            .filter(|s| s.timestamp <= target_time)
-            .last()
+            .rev()
+            .find(|s| s.timestamp <= target_time)
             .map(|s| s.last_price)
     }
 
+    /// Last and mark price candles of `window_ms` built from the retained price history
+    /// (up to `PRICE_HISTORY_SECS`), for resolutions the 500ms candle buffer doesn't cover
+    pub fn candles_from_history(&self, window_ms: i64) -> (Vec<Candle>, Vec<Candle>) {
+        (
+            candles_from_snapshots(self.price_history.iter(), window_ms, |s| s.last_price),
+            candles_from_snapshots(self.price_history.iter(), window_ms, |s| s.mark_price),
+        )
+    }
+
     pub fn get_baseline_prices(&self, window_secs: u64) -> Option<(f64, f64)> {
-        let cutoff = Utc::now() - chrono::Duration::seconds(window_secs as i64);
+        let cutoff = self.clock.now() - chrono::Duration::seconds(window_secs as i64);
 
         let relevant: Vec<_> = self.price_history.iter()
             .filter(|s| s.timestamp >= cutoff)
@@ -490,9 +1191,32 @@ impl SymbolData {
 #[derive(Debug, Clone, Deserialize)]
 pub struct ContractDetail {
     pub symbol: String,
-    #[serde(rename = "displayName")]
-    pub display_name: String,
     pub state: i32,
+    #[serde(rename = "contractSize", default)]
+    pub contract_size: Option<f64>,
+    /// Price tick size
+    #[serde(rename = "priceUnit", default)]
+    pub price_unit: Option<f64>,
+    // Order size limits, read by the executor
+    #[cfg_attr(not(feature = "execution"), allow(dead_code))]
+    #[serde(rename = "minVol", default)]
+    pub min_vol: Option<f64>,
+    #[cfg_attr(not(feature = "execution"), allow(dead_code))]
+    #[serde(rename = "maxVol", default)]
+    pub max_vol: Option<f64>,
+    #[cfg_attr(not(feature = "execution"), allow(dead_code))]
+    #[serde(rename = "volUnit", default)]
+    pub vol_unit: Option<f64>,
+    #[serde(rename = "baseCoin", default)]
+    pub base_coin: String,
+    #[serde(rename = "quoteCoin", default)]
+    pub quote_coin: String,
+    /// 1 = perpetual, 2 = dated (delivery) future
+    #[serde(rename = "futureType", default)]
+    pub future_type: Option<i32>,
+    /// Announced start of trading (ms since epoch); 0 or missing when not announced
+    #[serde(rename = "openingTime", default)]
+    pub opening_time: Option<i64>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -501,3 +1225,68 @@ pub struct ContractDetailResponse {
     pub code: i32,
     pub data: Vec<ContractDetail>,
 }
+
+/// 24h statistics of a contract from the REST ticker endpoint
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContractTicker {
+    pub symbol: String,
+    /// 24h turnover in the quote currency (USDT)
+    #[serde(rename = "amount24", default)]
+    pub amount_24h: f64,
+    #[serde(rename = "fundingRate", default)]
+    pub funding_rate: Option<f64>,
+    /// Open interest in contracts
+    #[serde(rename = "holdVol", default)]
+    pub hold_vol: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DepthResponse {
+    pub success: bool,
+    pub code: i32,
+    pub data: OrderbookData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContractTickerResponse {
+    pub success: bool,
+    pub code: i32,
+    pub data: Vec<ContractTicker>,
+}
+
+/// Column-oriented kline series as returned by the REST kline endpoint (times in seconds)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KlineSeries {
+    pub time: Vec<i64>,
+    pub open: Vec<f64>,
+    pub close: Vec<f64>,
+    pub high: Vec<f64>,
+    pub low: Vec<f64>,
+    pub vol: Vec<f64>,
+}
+
+impl KlineSeries {
+    pub fn into_candles(self) -> Vec<Candle> {
+        (0..self.time.len())
+            .filter_map(|i| {
+                Some(Candle {
+                    timestamp_ms: self.time[i] * 1000,
+                    open: *self.open.get(i)?,
+                    high: *self.high.get(i)?,
+                    low: *self.low.get(i)?,
+                    close: *self.close.get(i)?,
+                    volume: self.vol.get(i).copied().unwrap_or(0.0),
+                    tick_count: 0,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KlineResponse {
+    pub success: bool,
+    pub code: i32,
+    #[serde(default)]
+    pub data: KlineSeries,
+}
@@ -1,5 +1,15 @@
 pub mod market_data;
+pub mod contracts;
 pub mod events;
+pub mod pairing;
+pub mod symbol;
+pub mod trading;
+pub mod ws_messages;
 
 pub use market_data::*;
+pub use contracts::*;
 pub use events::*;
+pub use pairing::*;
+pub use symbol::*;
+pub use trading::*;
+pub use ws_messages::*;
@@ -0,0 +1,88 @@
+use crate::models::ContractDetail;
+use std::collections::HashMap;
+
+const FUTURE_TYPE_DATED: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractKind {
+    Perpetual,
+    Dated,
+}
+
+impl ContractDetail {
+    pub fn kind(&self) -> ContractKind {
+        if self.future_type == Some(FUTURE_TYPE_DATED) {
+            ContractKind::Dated
+        } else {
+            ContractKind::Perpetual
+        }
+    }
+}
+
+/// A dated future and the perpetual on the same underlying
+#[derive(Debug, Clone)]
+pub struct ContractPair {
+    /// e.g. `BTC/USDT`
+    pub underlying: String,
+    pub perpetual: String,
+    pub dated: String,
+}
+
+impl ContractPair {
+    /// Stable key identifying the pair in episode tracking and logs
+    pub fn label(&self) -> String {
+        format!("{}~{}", self.perpetual, self.dated)
+    }
+}
+
+/// Every perpetual/dated pair listed on the exchange, indexed by both legs
+#[derive(Debug, Default)]
+pub struct PairRegistry {
+    pairs: Vec<ContractPair>,
+    by_symbol: HashMap<String, Vec<usize>>,
+}
+
+impl PairRegistry {
+    /// Pairs active contracts that share base and quote coin
+    pub fn from_details(details: &[ContractDetail]) -> Self {
+        let mut perpetuals: HashMap<(&str, &str), &str> = HashMap::new();
+        for detail in details.iter().filter(|d| d.state == 0 && d.kind() == ContractKind::Perpetual) {
+            if !detail.base_coin.is_empty() {
+                perpetuals.insert((&detail.base_coin, &detail.quote_coin), &detail.symbol);
+            }
+        }
+
+        let mut registry = PairRegistry::default();
+        for dated in details.iter().filter(|d| d.state == 0 && d.kind() == ContractKind::Dated) {
+            let Some(perpetual) = perpetuals.get(&(dated.base_coin.as_str(), dated.quote_coin.as_str())) else {
+                continue;
+            };
+            let index = registry.pairs.len();
+            registry.pairs.push(ContractPair {
+                underlying: format!("{}/{}", dated.base_coin, dated.quote_coin),
+                perpetual: perpetual.to_string(),
+                dated: dated.symbol.clone(),
+            });
+            registry.by_symbol.entry(perpetual.to_string()).or_default().push(index);
+            registry.by_symbol.entry(dated.symbol.clone()).or_default().push(index);
+        }
+        registry
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Pairs in which `symbol` is either leg
+    pub fn pairs_for<'a>(&'a self, symbol: &str) -> impl Iterator<Item = &'a ContractPair> + 'a {
+        self.by_symbol
+            .get(symbol)
+            .into_iter()
+            .flatten()
+            .map(move |index| &self.pairs[*index])
+    }
+}
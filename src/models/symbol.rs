@@ -0,0 +1,29 @@
+use dashmap::DashSet;
+use std::sync::Arc;
+
+/// A contract symbol shared by reference: cloning one bumps a count instead of copying
+/// the name
+pub type Symbol = Arc<str>;
+
+/// Hands out one shared [`Symbol`] per contract name, so a market stream allocates each
+/// name once instead of once per message. The set only grows with the contracts seen,
+/// a few hundred at most.
+#[derive(Debug, Default)]
+pub struct SymbolInterner {
+    symbols: DashSet<Symbol>,
+}
+
+impl SymbolInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intern(&self, name: &str) -> Symbol {
+        if let Some(symbol) = self.symbols.get(name) {
+            return symbol.key().clone();
+        }
+        let symbol: Symbol = Arc::from(name);
+        self.symbols.insert(symbol.clone());
+        symbol
+    }
+}
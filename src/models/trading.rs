@@ -0,0 +1,120 @@
+#[cfg(feature = "execution")]
+use crate::models::market_data::string_or_number;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PositionSide {
+    Long,
+    Short,
+}
+
+impl PositionSide {
+        /// MEXC `side` code for opening a position on this side
+    #[cfg(feature = "execution")]
+    pub fn open_code(&self) -> u8 {
+        match self {
+            PositionSide::Long => 1,
+            PositionSide::Short => 3,
+        }
+    }
+
+        /// MEXC `side` code for closing a position on this side
+    #[cfg(feature = "execution")]
+    pub fn close_code(&self) -> u8 {
+        match self {
+            PositionSide::Long => 4,
+            PositionSide::Short => 2,
+        }
+    }
+
+        /// Whether orders with the MEXC `side` code sell (open short or close long)
+    #[cfg(feature = "execution")]
+    pub fn code_sells(code: u8) -> bool {
+        matches!(code, 3 | 4)
+    }
+
+    /// Move in favor of a position on this side from `entry_price` to `price`, in percent
+    pub fn profit_pct(&self, entry_price: f64, price: f64) -> f64 {
+        match self {
+            PositionSide::Long => (price / entry_price - 1.0) * 100.0,
+            PositionSide::Short => (1.0 - price / entry_price) * 100.0,
+        }
+    }
+
+        /// Side whose orders take the liquidity a close on this side needs
+    pub fn opposite(&self) -> Self {
+        match self {
+            PositionSide::Long => PositionSide::Short,
+            PositionSide::Short => PositionSide::Long,
+        }
+    }
+}
+
+/// Body of `POST /api/v1/private/order/submit`
+#[cfg(feature = "execution")]
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderRequest {
+    pub symbol: String,
+    pub price: f64,
+    pub vol: f64,
+    pub side: u8,
+    #[serde(rename = "type")]
+    pub order_type: u8,
+    #[serde(rename = "openType")]
+    pub open_type: u8,
+    #[serde(rename = "externalOid")]
+    pub external_oid: String,
+    /// Leaves the account's leverage setting in place when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leverage: Option<u32>,
+}
+
+/// How entry and exit orders are placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderType {
+    Market,
+    /// Limit at `limit_offset_pct` beyond the signal price
+    Limit,
+}
+
+impl OrderType {
+    /// MEXC order `type` code
+    #[cfg(feature = "execution")]
+    pub fn code(&self) -> u8 {
+        match self {
+            OrderType::Market => 5,
+            OrderType::Limit => 1,
+        }
+    }
+}
+
+/// Order as reported by the private REST API
+#[cfg(feature = "execution")]
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExchangeOrder {
+    #[serde(rename = "orderId", deserialize_with = "string_or_number")]
+    pub order_id: String,
+    pub state: i32,
+}
+
+/// Futures account balance for one currency
+#[cfg(feature = "execution")]
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccountAsset {
+    pub currency: String,
+    #[serde(rename = "availableBalance")]
+    pub available_balance: f64,
+    pub equity: f64,
+}
+
+/// Generic envelope of MEXC private REST responses
+#[cfg(feature = "execution")]
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiResponse<T> {
+    pub success: bool,
+    pub code: i32,
+    pub data: Option<T>,
+    pub message: Option<String>,
+}
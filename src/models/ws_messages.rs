@@ -0,0 +1,161 @@
+use crate::models::{MarkPriceData, OrderbookData, TickerData};
+use serde::Deserialize;
+use std::borrow::Cow;
+
+/// Every message the public futures WebSocket pushes, tagged by its `channel` field.
+///
+/// Decoding straight into this enum parses each frame once, instead of building a
+/// `serde_json::Value` tree and then converting its `data` a second time. Symbols are
+/// borrowed from the frame, for the client to intern.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "channel")]
+pub enum PushMessage<'a> {
+    #[serde(rename = "push.ticker")]
+    Ticker {
+        #[serde(borrow)]
+        data: TickerData<'a>,
+    },
+    #[serde(rename = "push.fair_price")]
+    FairPrice {
+        #[serde(borrow)]
+        data: MarkPriceData<'a>,
+    },
+    #[serde(rename = "push.depth")]
+    Depth {
+        #[serde(borrow)]
+        symbol: Cow<'a, str>,
+        data: OrderbookData,
+    },
+    #[serde(rename = "push.deal")]
+    Deal {
+        #[serde(borrow)]
+        symbol: Cow<'a, str>,
+        data: DealData,
+    },
+    #[serde(rename = "push.kline")]
+    Kline {
+        #[serde(borrow)]
+        symbol: Cow<'a, str>,
+        data: KlineData,
+    },
+    #[serde(rename = "push.funding.rate")]
+    FundingRate {
+        #[serde(borrow)]
+        symbol: Cow<'a, str>,
+        data: FundingRateData,
+    },
+    #[serde(rename = "pong")]
+    Pong,
+    /// Confirmation of a `sub.*` / `unsub.*` request
+    #[serde(
+        rename = "rs.sub.ticker",
+        alias = "rs.sub.fair_price",
+        alias = "rs.sub.depth",
+        alias = "rs.sub.deal",
+        alias = "rs.sub.kline",
+        alias = "rs.sub.funding.rate",
+        alias = "rs.unsub.ticker",
+        alias = "rs.unsub.fair_price",
+        alias = "rs.unsub.depth",
+        alias = "rs.unsub.deal",
+        alias = "rs.unsub.kline",
+        alias = "rs.unsub.funding.rate"
+    )]
+    SubscriptionAck {
+        #[serde(default)]
+        data: serde_json::Value,
+    },
+    #[serde(rename = "rs.error")]
+    Error {
+        #[serde(default)]
+        data: serde_json::Value,
+    },
+    /// A frame without any channel, such as some pongs and acks; nothing to act on
+    #[serde(skip)]
+    Untagged,
+    /// A channel this client doesn't know about yet
+    #[serde(other)]
+    Unknown,
+}
+
+/// Just enough of a frame to tell whether it names a channel at all
+#[derive(Deserialize)]
+struct ChannelProbe<'a> {
+    #[serde(borrow, default)]
+    channel: Option<Cow<'a, str>>,
+}
+
+impl<'a> PushMessage<'a> {
+    /// Parses one frame. With the `simd-json` feature the frame is copied into `scratch`,
+    /// which simd-json parses in place, and frames it rejects are parsed again with
+    /// serde_json; without it `scratch` is unused.
+    pub fn decode(text: &'a str, scratch: &'a mut Vec<u8>) -> serde_json::Result<Self> {
+        #[cfg(feature = "simd-json")]
+        {
+            scratch.clear();
+            scratch.extend_from_slice(text.as_bytes());
+            if let Ok(message) = simd_json::serde::from_slice(scratch) {
+                return Ok(message);
+            }
+        }
+        #[cfg(not(feature = "simd-json"))]
+        let _ = scratch;
+        serde_json::from_str(text).or_else(|e| match serde_json::from_str::<ChannelProbe>(text) {
+            Ok(ChannelProbe { channel: None }) => Ok(PushMessage::Untagged),
+            _ => Err(e),
+        })
+    }
+
+    /// What the lenient parsing leaves out of this message, if anything
+    pub fn lost_data(&self) -> Option<String> {
+        match self {
+            PushMessage::Ticker { data } => {
+                let fields = data.unparsable_fields();
+                (!fields.is_empty()).then(|| format!("unparsable {}", fields.join(", ")))
+            }
+            PushMessage::Depth { data, .. } => {
+                let levels = data.unparsable_levels();
+                (levels > 0).then(|| format!("{} unparsable orderbook levels", levels))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A single public trade
+#[derive(Debug, Clone, Deserialize)]
+pub struct DealData {
+    #[serde(rename = "p")]
+    pub price: f64,
+    #[serde(rename = "v")]
+    pub volume: f64,
+    /// 1 = buy, 2 = sell
+    #[serde(rename = "T")]
+    pub side: i32,
+    #[serde(rename = "t")]
+    pub timestamp: i64,
+}
+
+/// The current funding rate of a contract, pushed when it changes
+#[derive(Debug, Clone, Deserialize)]
+pub struct FundingRateData {
+    pub rate: f64,
+}
+
+/// A kline update for the subscribed interval (`t` is the window start in seconds)
+#[derive(Debug, Clone, Deserialize)]
+pub struct KlineData {
+    pub interval: String,
+    #[serde(rename = "t")]
+    pub time: i64,
+    #[serde(rename = "o")]
+    pub open: f64,
+    #[serde(rename = "h")]
+    pub high: f64,
+    #[serde(rename = "l")]
+    pub low: f64,
+    #[serde(rename = "c")]
+    pub close: f64,
+    #[serde(rename = "q", default)]
+    pub volume: f64,
+}
@@ -0,0 +1,465 @@
+use crate::alerts::AlertSender;
+use crate::api::MarketStream;
+use crate::config::Config;
+use crate::detection::{
+    build_strategies, handle_market_event, FeatureParams, MarketContext, ReadinessBoard, StrategyDeps, StrategyThresholds,
+    Watchlist,
+};
+use crate::models::{Symbol, SymbolData};
+use crate::replay::{switch_off_for_replay, ReplayStream};
+use crate::utils::{DiskWriter, EpisodeLogWriter, EpisodeLogger, EpisodeRecord, EpisodeStore, SimulatedClock};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use dashmap::DashMap;
+use serde::Deserialize;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::info;
+
+// Unmatched episodes listed per side before only counting them
+const MAX_LISTED: usize = 20;
+
+// Completed episodes are taken out of the store after every event, so this only has to
+// hold the episodes a single event can end
+const EPISODE_STORE_CAPACITY: usize = 256;
+
+/// An episode as far as the consistency check compares them: ids differ between runs, so
+/// episodes are matched by strategy, symbol and start time
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpisodeSpan {
+    pub strategy: String,
+    pub symbol: String,
+    pub id: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl From<&EpisodeRecord> for EpisodeSpan {
+    fn from(episode: &EpisodeRecord) -> Self {
+        Self {
+            strategy: episode.strategy.clone(),
+            symbol: episode.symbol.clone(),
+            id: episode.id.clone(),
+            start: episode.start_time,
+            end: episode.end_time,
+        }
+    }
+}
+
+/// How two runs' episodes over the same market data line up
+#[derive(Debug, Default)]
+pub struct EpisodeDiff {
+    /// Pairs of the expected and the actual episode
+    pub matched: Vec<(EpisodeSpan, EpisodeSpan)>,
+    /// Expected episodes the other run didn't have
+    pub missing: Vec<EpisodeSpan>,
+    /// Episodes only the other run had
+    pub extra: Vec<EpisodeSpan>,
+}
+
+impl EpisodeDiff {
+    pub fn is_consistent(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty()
+    }
+
+    /// Largest distance between the start times of matched episodes
+    pub fn max_start_offset(&self) -> Duration {
+        self.matched.iter().map(|(a, b)| (a.start - b.start).abs()).max().unwrap_or_default()
+    }
+
+    /// Largest distance between the end times of matched episodes
+    pub fn max_end_offset(&self) -> Duration {
+        self.matched.iter().map(|(a, b)| (a.end - b.end).abs()).max().unwrap_or_default()
+    }
+
+    /// Summary line plus the unmatched episodes of both sides, `expected` and `actual`
+    /// naming the two runs
+    pub fn render(&self, expected: &str, actual: &str) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{} vs {}: {} matched | {} only in {} | {} only in {} | max start offset {}ms | max end offset {}ms",
+            expected,
+            actual,
+            self.matched.len(),
+            self.missing.len(),
+            expected,
+            self.extra.len(),
+            actual,
+            self.max_start_offset().num_milliseconds(),
+            self.max_end_offset().num_milliseconds()
+        );
+        for (label, episodes) in [(expected, &self.missing), (actual, &self.extra)] {
+            for episode in episodes.iter().take(MAX_LISTED) {
+                let _ = writeln!(
+                    out,
+                    "  only in {}: {} {} {} -> {} ({})",
+                    label,
+                    episode.strategy,
+                    episode.symbol,
+                    episode.start.to_rfc3339(),
+                    episode.end.to_rfc3339(),
+                    episode.id
+                );
+            }
+            if episodes.len() > MAX_LISTED {
+                let _ = writeln!(out, "  only in {}: +{} more", label, episodes.len() - MAX_LISTED);
+            }
+        }
+        out
+    }
+}
+
+/// Pairs every expected episode with the first unmatched actual one of the same strategy
+/// and symbol that started within `tolerance` of it
+pub fn diff_episodes(expected: &[EpisodeSpan], actual: &[EpisodeSpan], tolerance: Duration) -> EpisodeDiff {
+    let mut expected = expected.to_vec();
+    expected.sort_by_key(|episode| episode.start);
+    let mut actual: Vec<Option<EpisodeSpan>> = actual.iter().cloned().map(Some).collect();
+    actual.sort_by_key(|episode| episode.as_ref().map(|e| e.start));
+
+    let mut diff = EpisodeDiff::default();
+    for episode in expected {
+        let found = actual.iter_mut().find(|candidate| {
+            candidate.as_ref().is_some_and(|c| {
+                c.strategy == episode.strategy
+                    && c.symbol == episode.symbol
+                    && (c.start - episode.start).abs() <= tolerance
+            })
+        });
+        match found.and_then(Option::take) {
+            Some(other) => diff.matched.push((episode, other)),
+            None => diff.missing.push(episode),
+        }
+    }
+    diff.extra = actual.into_iter().flatten().collect();
+    diff
+}
+
+/// A line of `<strategy>_episodes.jsonl`, as far as it is compared
+#[derive(Debug, Deserialize)]
+struct JsonEpisode {
+    id: String,
+    symbol: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+/// A line of `<strategy>_episodes.log`: `<end> | <symbol> | ID=... | ... | DURATION=<n>s | ...`.
+/// The start is only given to the second, so it is taken as the end less the duration.
+fn parse_text_episode(strategy: &str, line: &str) -> Option<EpisodeSpan> {
+    let mut fields = line.split(" | ");
+    let end = NaiveDateTime::parse_from_str(fields.next()?, "%Y-%m-%dT%H:%M:%SZ").ok()?.and_utc();
+    let symbol = fields.next()?.to_string();
+    let (mut id, mut duration) = (None, None);
+    for field in fields {
+        if let Some(value) = field.strip_prefix("ID=") {
+            id = Some(value.to_string());
+        } else if let Some(value) = field.strip_prefix("DURATION=") {
+            duration = value.strip_suffix('s')?.parse::<i64>().ok();
+        }
+    }
+    Some(EpisodeSpan {
+        strategy: strategy.to_string(),
+        symbol,
+        id: id?,
+        start: end - Duration::seconds(duration?),
+        end,
+    })
+}
+
+/// The episodes `strategies` logged to `log_dir`, in either episode log format
+pub fn read_episode_logs(log_dir: &Path, strategies: &[&str]) -> Result<Vec<EpisodeSpan>> {
+    let mut episodes = Vec::new();
+    for strategy in strategies {
+        let json = log_dir.join(format!("{}_episodes.jsonl", strategy));
+        if json.exists() {
+            let contents = fs::read_to_string(&json).with_context(|| format!("reading {}", json.display()))?;
+            for (index, line) in contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+                let episode: JsonEpisode = serde_json::from_str(line)
+                    .with_context(|| format!("{}:{} is not an episode", json.display(), index + 1))?;
+                episodes.push(EpisodeSpan {
+                    strategy: strategy.to_string(),
+                    symbol: episode.symbol,
+                    id: episode.id,
+                    start: episode.start,
+                    end: episode.end,
+                });
+            }
+        }
+        let text = log_dir.join(format!("{}_episodes.log", strategy));
+        if text.exists() {
+            let contents = fs::read_to_string(&text).with_context(|| format!("reading {}", text.display()))?;
+            for (index, line) in contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+                let episode = parse_text_episode(strategy, line)
+                    .with_context(|| format!("{}:{} is not an episode", text.display(), index + 1))?;
+                episodes.push(episode);
+            }
+        }
+    }
+    Ok(episodes)
+}
+
+/// Outcome of a consistency check
+pub struct ConsistencyReport {
+    /// The tape replayed twice over
+    pub replays: EpisodeDiff,
+    /// The live run's episodes against the first replay's
+    pub live: EpisodeDiff,
+}
+
+impl ConsistencyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.replays.is_consistent() && self.live.is_consistent()
+    }
+
+    pub fn render(&self) -> String {
+        format!("{}{}", self.replays.render("replay 1", "replay 2"), self.live.render("live", "replay 1"))
+    }
+}
+
+/// Replays a tape the live detector recorded twice, in parallel and with the live config,
+/// and diffs the episodes of the two replays with each other and with the ones the live run
+/// logged to `live_log_dir` while the tape was recorded.
+///
+/// Each replay feeds the tape through a [`ReplayStream`] and the event loop's
+/// `handle_market_event` on a clock that follows the tape, as `--replay` does. Replays that
+/// disagree with each other depend on something besides the tape (the wall clock, hash
+/// order); a replay that disagrees with the live run shows what the live event loop does
+/// that replaying doesn't. Episode start times of the live run follow its wall clock, so
+/// they are matched within `tolerance`.
+pub async fn check_consistency(
+    tape: &Path,
+    config: &Config,
+    live_log_dir: &Path,
+    tolerance: Duration,
+    out_dir: &Path,
+) -> Result<ConsistencyReport> {
+    let replays = [1, 2].map(|index| {
+        let (tape, config, log_dir) = (tape.to_path_buf(), config.clone(), out_dir.join(format!("replay_{}", index)));
+        tokio::spawn(async move { replay_episodes(&tape, config, &log_dir).await })
+    });
+    let [first_replay, second_replay] = replays;
+    let replayed = first_replay.await??;
+    let second = second_replay.await??;
+    let (Some(first), Some(last)) = (replayed.first_event, replayed.last_event) else {
+        bail!("the tape holds no events");
+    };
+
+    // Only what the live run started while it was recording can be on the tape
+    let live: Vec<EpisodeSpan> = read_episode_logs(live_log_dir, &replayed.strategies)?
+        .into_iter()
+        .filter(|episode| episode.start >= first - tolerance && episode.start <= last)
+        .collect();
+    info!(
+        "[Consistency] {} live and {} replayed episodes between {} and {}",
+        live.len(),
+        replayed.episodes.len(),
+        first.to_rfc3339(),
+        last.to_rfc3339()
+    );
+
+    Ok(ConsistencyReport {
+        replays: diff_episodes(&replayed.episodes, &second.episodes, Duration::zero()),
+        live: diff_episodes(&live, &replayed.episodes, tolerance),
+    })
+}
+
+/// What one replay of the tape produced
+struct Replayed {
+    /// Strategies the replay ran
+    strategies: Vec<&'static str>,
+    episodes: Vec<EpisodeSpan>,
+    first_event: Option<DateTime<Utc>>,
+    last_event: Option<DateTime<Utc>>,
+}
+
+/// Plays the tape at full speed through the live event handling, with the subsystems a
+/// `--replay` switches off left off, and collects every episode the strategies complete.
+/// Episodes still open when the tape ends are ended there. Episode logs go to `log_dir`.
+async fn replay_episodes(tape: &Path, mut config: Config, log_dir: &Path) -> Result<Replayed> {
+    switch_off_for_replay(&mut config);
+    // As in a live run, Strategy4 is nothing but orderbook checks
+    if !config.orderbook.enabled {
+        config.strategy4.enabled = false;
+    }
+
+    let clock = Arc::new(SimulatedClock::new(DateTime::UNIX_EPOCH));
+    let log_dir = log_dir.to_str().context("consistency output path is not valid UTF-8")?;
+    let disk = DiskWriter::start(config.disk_io.clone())?;
+    let writer = EpisodeLogWriter::start(config.logging.episodes.clone(), disk);
+    let store = Arc::new(EpisodeStore::new(EPISODE_STORE_CAPACITY));
+    let logger = |name: &'static str| -> Result<Arc<EpisodeLogger>> {
+        Ok(Arc::new(EpisodeLogger::new(log_dir, name, store.clone(), writer.clone())?))
+    };
+    let (alert_tx, mut alert_rx): (AlertSender, _) = mpsc::unbounded_channel();
+    let deps = StrategyDeps {
+        alert_tx,
+        clock: clock.clone(),
+        csv_exporter: None,
+        contracts: None,
+        executor: None,
+    };
+    let mut strategies =
+        build_strategies(&config, &StrategyThresholds::from_config(&config)?, deps, logger, |_| Arc::default())?;
+    // Nothing subscribes offline, so the deeper books of watched symbols are never asked for
+    let (command_tx, _) = mpsc::unbounded_channel();
+    let watchlist = Watchlist::new(config.watchlist.clone(), command_tx, config.orderbook.max_levels);
+    let market_context = config.market_context.enabled.then(|| MarketContext::new(config.market_context.clone()));
+    let readiness = ReadinessBoard::default();
+    let feature_params = FeatureParams::from_config(&config);
+    let symbol_data: DashMap<Symbol, SymbolData> = DashMap::new();
+
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    let (_subscriptions, subscription_rx) = mpsc::unbounded_channel();
+    let stream = Box::new(ReplayStream::new(tape, 0.0, &config.general.symbols));
+    let replay = tokio::spawn(stream.run(event_tx, subscription_rx));
+
+    let mut replayed = Replayed {
+        strategies: strategies.names(),
+        episodes: Vec::new(),
+        first_event: None,
+        last_event: None,
+    };
+    while let Some(event) = event_rx.recv().await {
+        if let Some(timestamp) = event.timestamp() {
+            clock.set(timestamp);
+            replayed.first_event.get_or_insert(timestamp);
+            replayed.last_event = Some(timestamp);
+        }
+        // Replayed symbols are only known once their first event arrives
+        if let Some(symbol) = event.interned_symbol() {
+            if !symbol_data.contains_key(&**symbol) {
+                symbol_data.insert(symbol.clone(), SymbolData::new(symbol.clone(), clock.clone()));
+            }
+        }
+        // Ratio baselines sample on the wall clock, and pipelines log apart from the
+        // strategies compared here, so neither takes part
+        handle_market_event(
+            event,
+            &symbol_data,
+            &mut strategies,
+            None,
+            &readiness,
+            &watchlist,
+            &mut None,
+            market_context.as_ref(),
+            feature_params,
+            None,
+            &mut [],
+            None,
+            config.market_stream.stale_grace_secs,
+        );
+        while alert_rx.try_recv().is_ok() {}
+        replayed.episodes.extend(store.drain().iter().map(EpisodeSpan::from));
+    }
+    replay.await.map_err(|e| anyhow!("replay task panicked: {}", e))??;
+
+    for entry in symbol_data.iter() {
+        if strategies.has_active_episode(entry.key()) {
+            strategies.force_end_episode(entry.key(), "end of tape");
+        }
+    }
+    replayed.episodes.extend(store.drain().iter().map(EpisodeSpan::from));
+    Ok(replayed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(strategy: &str, symbol: &str, start_ms: i64) -> EpisodeSpan {
+        let start = DateTime::from_timestamp_millis(start_ms).unwrap();
+        EpisodeSpan {
+            strategy: strategy.to_string(),
+            symbol: symbol.to_string(),
+            id: format!("{}-{}", symbol, start_ms),
+            start,
+            end: start + Duration::seconds(10),
+        }
+    }
+
+    #[test]
+    fn episodes_match_by_strategy_symbol_and_start() {
+        let live = vec![
+            span("strategy1", "PUMP_USDT", 1_000),
+            span("strategy1", "PUMP_USDT", 60_000),
+            span("strategy2", "PUMP_USDT", 1_000),
+        ];
+        let replayed = vec![
+            // Matched within the tolerance, in any order
+            span("strategy1", "PUMP_USDT", 60_400),
+            span("strategy1", "PUMP_USDT", 1_300),
+            // Same start, other symbol
+            span("strategy2", "OTHER_USDT", 1_000),
+        ];
+
+        let diff = diff_episodes(&live, &replayed, Duration::milliseconds(500));
+
+        assert_eq!(diff.matched.len(), 2);
+        assert_eq!(diff.max_start_offset(), Duration::milliseconds(400));
+        assert_eq!(diff.missing, vec![span("strategy2", "PUMP_USDT", 1_000)]);
+        assert_eq!(diff.extra, vec![span("strategy2", "OTHER_USDT", 1_000)]);
+        assert!(!diff.is_consistent());
+        assert!(diff_episodes(&live, &live, Duration::zero()).is_consistent());
+    }
+
+    #[test]
+    fn text_episode_lines_parse() {
+        let line = "2024-06-10T06:14:12Z | SPIKE_USDT | ID=01J00CGEHMM9S346Q3D25VT4F5 | START=06:14:00 \
+                    | END=06:14:12 | DURATION=12s | PEAK_RATIO=1.3400 | PEAK_LAST=0.64000000 \
+                    | PEAK_MARK=0.48000000 | END_REASON=end of tape";
+        let episode = parse_text_episode("strategy1", line).unwrap();
+        assert_eq!(episode.symbol, "SPIKE_USDT");
+        assert_eq!(episode.id, "01J00CGEHMM9S346Q3D25VT4F5");
+        assert_eq!(episode.start.to_rfc3339(), "2024-06-10T06:14:00+00:00");
+        assert_eq!(episode.end - episode.start, Duration::seconds(12));
+    }
+
+    #[tokio::test]
+    async fn replay_is_checked_against_the_live_episode_logs() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let recording = crate::replay::fixtures::RecordedIncident::load(
+            &root.join("tests/fixtures/pumps/SYNTH_SPIKE_USDT_20240610_061320_01J00CGEHMM9S346Q3D25VT4F5"),
+        )
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let tape = dir.path().join("events.jsonl");
+        let mut lines = vec![serde_json::to_string(&crate::replay::TapeHeader::current()).unwrap()];
+        lines.extend(recording.tape().iter().map(|event| serde_json::to_string(event).unwrap()));
+        fs::write(&tape, lines.join("\n")).unwrap();
+        let config = Config::load(root.join("config.toml")).unwrap();
+        let live_dir = dir.path().join("live");
+        fs::create_dir_all(&live_dir).unwrap();
+        let tolerance = Duration::seconds(1);
+
+        // Nothing logged live: the replays agree, but every replayed episode is extra
+        let first = dir.path().join("first");
+        let report = check_consistency(&tape, &config, &live_dir, tolerance, &first).await.unwrap();
+        assert!(report.replays.is_consistent());
+        assert!(!report.replays.matched.is_empty());
+        assert!(report.live.missing.is_empty());
+        assert_eq!(report.live.extra.len(), report.replays.matched.len());
+        assert!(!report.is_consistent());
+
+        // The same episodes logged live, started a little later on the wall clock
+        for episode in &report.live.extra {
+            let line = serde_json::json!({
+                "id": format!("live-{}", episode.id),
+                "symbol": episode.symbol,
+                "start": (episode.start + Duration::milliseconds(300)).to_rfc3339(),
+                "end": (episode.end + Duration::milliseconds(300)).to_rfc3339(),
+            });
+            let path = live_dir.join(format!("{}_episodes.jsonl", episode.strategy));
+            let mut contents = fs::read_to_string(&path).unwrap_or_default();
+            contents.push_str(&format!("{}\n", line));
+            fs::write(&path, contents).unwrap();
+        }
+        let report = check_consistency(&tape, &config, &live_dir, tolerance, &dir.path().join("second")).await.unwrap();
+        assert!(report.is_consistent(), "{}", report.render());
+        assert_eq!(report.live.max_start_offset(), Duration::milliseconds(300));
+    }
+}
@@ -0,0 +1,313 @@
+use crate::models::SymbolData;
+use crate::replay::TapeEvent;
+use crate::utils::SimulatedClock;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// One row of a chart recording's candle CSV. `volume` and `tick_count` are missing from
+/// recordings made before they were added.
+#[derive(Debug, Deserialize)]
+struct CandleRow {
+    timestamp_ms: i64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    #[serde(default)]
+    tick_count: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    symbol: String,
+}
+
+/// A last and/or mark price at one instant of a recording
+#[derive(Debug, Clone, Copy)]
+pub struct RecordedUpdate {
+    pub timestamp: DateTime<Utc>,
+    pub last_price: Option<f64>,
+    pub mark_price: Option<f64>,
+}
+
+/// An incident's chart recording (`<prefix>_lastprice.csv`, `<prefix>_fairprice.csv` and
+/// `<prefix>_manifest.json`, plain or `.csv.gz`) turned back into the price updates that
+/// built it, so strategies can be run on it again.
+///
+/// Each candle becomes its open, low and high (in the order the candle moved) and close,
+/// spread over the candle's window. Forward-filled candles had no updates and add none.
+pub struct RecordedIncident {
+    pub symbol: String,
+    pub updates: Vec<RecordedUpdate>,
+}
+
+impl RecordedIncident {
+    /// `prefix` is the recording's path without the `_lastprice.csv` suffix
+    pub fn load(prefix: &Path) -> Result<Self> {
+        let manifest: Manifest = serde_json::from_str(
+            &fs::read_to_string(with_suffix(prefix, "_manifest.json"))
+                .with_context(|| format!("reading the manifest of {}", prefix.display()))?,
+        )?;
+        let last = candle_ticks(&read_candles(&csv_path(prefix, "lastprice")?)?);
+        let mark = candle_ticks(&read_candles(&csv_path(prefix, "fairprice")?)?);
+
+        let mut updates: Vec<RecordedUpdate> = last
+            .into_iter()
+            .map(|(ms, price)| (ms, Some(price), None))
+            .chain(mark.into_iter().map(|(ms, price)| (ms, None, Some(price))))
+            .map(|(ms, last_price, mark_price)| {
+                let timestamp =
+                    DateTime::from_timestamp_millis(ms).ok_or_else(|| anyhow!("timestamp {} is out of range", ms))?;
+                Ok(RecordedUpdate {
+                    timestamp,
+                    last_price,
+                    mark_price,
+                })
+            })
+            .collect::<Result<_>>()?;
+        updates.sort_by_key(|u| u.timestamp);
+        if updates.is_empty() {
+            bail!("{} has no price updates", prefix.display());
+        }
+
+        Ok(Self {
+            symbol: manifest.symbol,
+            updates,
+        })
+    }
+
+    /// Applies the updates in order to a fresh `SymbolData`, moving `clock` along and
+    /// calling `on_update` after each one
+    pub fn play(&self, clock: Arc<SimulatedClock>, mut on_update: impl FnMut(&SymbolData)) {
+        let mut data = SymbolData::new(self.symbol.clone(), clock.clone());
+        for update in &self.updates {
+            clock.set(update.timestamp);
+            if let Some(price) = update.last_price {
+                data.update_last_price(price, update.timestamp);
+            }
+            if let Some(price) = update.mark_price {
+                data.update_mark_price(price, update.timestamp);
+            }
+            on_update(&data);
+        }
+    }
+
+    /// The updates as a tape, for runs that read one
+    pub fn tape(&self) -> Vec<TapeEvent> {
+        self.updates
+            .iter()
+            .filter_map(|update| match (update.last_price, update.mark_price) {
+                (Some(last), mark) => Some(TapeEvent::Ticker {
+                    time: update.timestamp,
+                    symbol: self.symbol.clone(),
+                    last,
+                    mark,
+                    bid: None,
+                    ask: None,
+                    stats_24h: None,
+                }),
+                (None, Some(mark)) => Some(TapeEvent::MarkPrice {
+                    time: update.timestamp,
+                    symbol: self.symbol.clone(),
+                    mark,
+                }),
+                (None, None) => None,
+            })
+            .collect()
+    }
+}
+
+fn with_suffix(prefix: &Path, suffix: &str) -> PathBuf {
+    let mut path = prefix.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// `<prefix>_<series>.csv`, or the compressed `.csv.gz` if only that exists
+fn csv_path(prefix: &Path, series: &str) -> Result<PathBuf> {
+    let plain = with_suffix(prefix, &format!("_{}.csv", series));
+    let compressed = with_suffix(prefix, &format!("_{}.csv.gz", series));
+    [plain, compressed]
+        .into_iter()
+        .find(|path| path.is_file())
+        .ok_or_else(|| anyhow!("no {} CSV for {}", series, prefix.display()))
+}
+
+fn read_candles(path: &Path) -> Result<Vec<CandleRow>> {
+    let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    let reader: Box<dyn Read> = if path.extension().is_some_and(|ext| ext == "gz") {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    let mut candles: Vec<CandleRow> = csv::Reader::from_reader(BufReader::new(reader))
+        .deserialize()
+        .collect::<Result<_, _>>()
+        .with_context(|| format!("reading {}", path.display()))?;
+    // Recordings can repeat candles where the pre-buffer and the final candles overlap
+    candles.sort_by_key(|c| c.timestamp_ms);
+    candles.dedup_by_key(|c| c.timestamp_ms);
+    Ok(candles)
+}
+
+/// Price ticks (ms, price) reproducing each candle within its window; a candle's window
+/// ends where the next one starts, as fine and 500ms candles can be mixed
+fn candle_ticks(candles: &[CandleRow]) -> Vec<(i64, f64)> {
+    let mut ticks = Vec::new();
+    for (index, candle) in candles.iter().enumerate() {
+        if candle.tick_count == Some(0) {
+            continue;
+        }
+        let window_ms = candles
+            .get(index + 1)
+            .map(|next| next.timestamp_ms - candle.timestamp_ms)
+            .unwrap_or(500)
+            .max(4);
+        let (first, second) = if candle.close >= candle.open {
+            (candle.low, candle.high)
+        } else {
+            (candle.high, candle.low)
+        };
+        let step = window_ms / 4;
+        for (offset, price) in [candle.open, first, second, candle.close].into_iter().enumerate() {
+            ticks.push((candle.timestamp_ms + step * offset as i64, price));
+        }
+    }
+    ticks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::Notification;
+    use crate::config::Config;
+    use crate::detection::{build_strategies, FeatureCache, FeatureParams, StrategyDeps, StrategyThresholds};
+    use crate::utils::{DiskWriter, EpisodeLogWriter, EpisodeLogger, EpisodeStore};
+    use std::collections::BTreeSet;
+    use tokio::sync::mpsc;
+
+    /// `tests/fixtures/incidents.toml`: the recordings and what the default config must do with them
+    #[derive(Debug, Deserialize)]
+    struct Expectations {
+        incident: Vec<Expectation>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Expectation {
+        /// Recording prefix relative to `tests/fixtures`
+        recording: String,
+        /// Strategies that must trigger; empty for a false positive none may trigger on
+        detect: Vec<String>,
+    }
+
+    fn fixtures_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures")
+    }
+
+    fn default_config() -> Config {
+        Config::load(Path::new(env!("CARGO_MANIFEST_DIR")).join("config.toml")).expect("config.toml loads")
+    }
+
+    fn expectations() -> Vec<Expectation> {
+        let contents = fs::read_to_string(fixtures_dir().join("incidents.toml")).expect("incidents.toml");
+        toml::from_str::<Expectations>(&contents).expect("incidents.toml parses").incident
+    }
+
+    /// Plays a recording through the five strategies of `config` and returns the ones that alerted
+    async fn triggered_strategies(config: &Config, recording: &RecordedIncident) -> BTreeSet<String> {
+        let clock = Arc::new(SimulatedClock::new(recording.updates[0].timestamp));
+        let log_dir = tempfile::tempdir().unwrap();
+        let log_dir = log_dir.path().to_str().unwrap();
+        let disk = DiskWriter::start(config.disk_io.clone()).unwrap();
+        let writer = EpisodeLogWriter::start(config.logging.episodes.clone(), disk);
+        let store = Arc::new(EpisodeStore::new(16));
+        let logger = |name: &'static str| Ok(Arc::new(EpisodeLogger::new(log_dir, name, store.clone(), writer.clone())?));
+        let (alert_tx, mut alert_rx) = mpsc::unbounded_channel();
+        let deps = StrategyDeps {
+            alert_tx,
+            clock: clock.clone(),
+            csv_exporter: None,
+            contracts: None,
+            executor: None,
+        };
+        let thresholds = StrategyThresholds::from_config(config).unwrap();
+        let mut strategies = build_strategies(config, &thresholds, deps, logger, |_| Arc::default()).unwrap();
+
+        let params = FeatureParams::from_config(config);
+        recording.play(clock, |data| {
+            let features = FeatureCache::new(data, params);
+            strategies.check_prices(data, &features, None);
+        });
+
+        let mut triggered = BTreeSet::new();
+        while let Ok(notification) = alert_rx.try_recv() {
+            if let Notification::Alert(alert) = notification {
+                triggered.insert(alert.strategy);
+            }
+        }
+        triggered
+    }
+
+    #[tokio::test]
+    async fn default_config_detects_recorded_pumps() {
+        let config = default_config();
+        for expectation in expectations().iter().filter(|e| !e.detect.is_empty()) {
+            let recording = RecordedIncident::load(&fixtures_dir().join(&expectation.recording)).unwrap();
+            let triggered = triggered_strategies(&config, &recording).await;
+            for strategy in &expectation.detect {
+                assert!(
+                    triggered.contains(strategy),
+                    "{} no longer triggers on {} (triggered: {:?})",
+                    strategy,
+                    expectation.recording,
+                    triggered
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn default_config_ignores_recorded_false_positives() {
+        let config = default_config();
+        for expectation in expectations().iter().filter(|e| e.detect.is_empty()) {
+            let recording = RecordedIncident::load(&fixtures_dir().join(&expectation.recording)).unwrap();
+            let triggered = triggered_strategies(&config, &recording).await;
+            assert!(triggered.is_empty(), "{:?} triggered on false positive {}", triggered, expectation.recording);
+        }
+    }
+
+    #[test]
+    fn candles_become_ticks_in_the_order_they_moved() {
+        let candles = vec![
+            CandleRow { timestamp_ms: 0, open: 1.0, high: 1.3, low: 0.9, close: 1.2, tick_count: Some(5) },
+            CandleRow { timestamp_ms: 500, open: 1.2, high: 1.2, low: 1.2, close: 1.2, tick_count: Some(0) },
+            CandleRow { timestamp_ms: 1000, open: 1.2, high: 1.25, low: 1.0, close: 1.05, tick_count: None },
+            CandleRow { timestamp_ms: 1100, open: 1.05, high: 1.05, low: 1.05, close: 1.05, tick_count: Some(1) },
+        ];
+        let ticks = candle_ticks(&candles);
+        assert_eq!(
+            ticks,
+            vec![
+                (0, 1.0),
+                (125, 0.9),
+                (250, 1.3),
+                (375, 1.2),
+                // The forward-filled candle adds nothing; the falling one visits its high first
+                (1000, 1.2),
+                (1025, 1.25),
+                (1050, 1.0),
+                (1075, 1.05),
+                (1100, 1.05),
+                (1225, 1.05),
+                (1350, 1.05),
+                (1475, 1.05),
+            ]
+        );
+    }
+}
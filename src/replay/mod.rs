@@ -0,0 +1,13 @@
+pub mod consistency;
+#[cfg(test)]
+pub mod fixtures;
+pub mod offline;
+pub mod recorder;
+pub mod stream;
+pub mod tape;
+
+pub use consistency::*;
+pub use offline::*;
+pub use recorder::*;
+pub use stream::*;
+pub use tape::*;
@@ -0,0 +1,71 @@
+use crate::config::Config;
+use tracing::info;
+
+/// Replays run offline on recorded data: nothing may reach the exchange, place orders or
+/// alert real channels, and the replayed events must not be recorded again
+pub fn switch_off_for_replay(config: &mut Config) {
+    for (feature, enabled) in [
+        ("execution", &mut config.execution.enabled),
+        ("contract_monitor", &mut config.contract_monitor.enabled),
+        ("dormancy", &mut config.dormancy.enabled),
+        ("funding", &mut config.funding.enabled),
+        ("open_interest", &mut config.open_interest.enabled),
+        ("kline_check", &mut config.kline_check.enabled),
+        ("missed_digest", &mut config.missed_digest.enabled),
+        ("tiers", &mut config.tiers.enabled),
+        ("mqtt", &mut config.mqtt.enabled),
+        ("alerts.telegram", &mut config.alerts.telegram.enabled),
+        ("alerts.zmq", &mut config.alerts.zmq.enabled),
+        ("recording", &mut config.recording.enabled),
+        // Replays run as fast as they can, and the tape can't shed symbols
+        ("resource_budget", &mut config.resource_budget.enabled),
+    ] {
+        if *enabled {
+            info!("[Replay] {} is switched off for the replay", feature);
+            *enabled = false;
+        }
+    }
+    // Symbols come from the tape rather than a volume ranking
+    config.universe.max_symbols = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MarketSource;
+    use std::path::Path;
+
+    #[test]
+    fn replay_config_reaches_no_rest_endpoint() {
+        let mut config =
+            Config::load(Path::new(env!("CARGO_MANIFEST_DIR")).join("config.toml")).expect("config.toml loads");
+        config.market_stream.source = MarketSource::Replay;
+        config.execution.enabled = true;
+        config.contract_monitor.enabled = true;
+        config.dormancy.enabled = true;
+        config.funding.enabled = true;
+        config.open_interest.enabled = true;
+        config.kline_check.enabled = true;
+        config.missed_digest.enabled = true;
+        config.tiers.enabled = true;
+        config.universe.max_symbols = 50;
+
+        switch_off_for_replay(&mut config);
+
+        let rest_backed = [
+            ("execution", config.execution.enabled),
+            ("contract_monitor", config.contract_monitor.enabled),
+            ("dormancy", config.dormancy.enabled),
+            ("funding", config.funding.enabled),
+            ("open_interest", config.open_interest.enabled),
+            ("kline_check", config.kline_check.enabled),
+            ("missed_digest", config.missed_digest.enabled),
+            ("tiers", config.tiers.enabled),
+            ("universe rotation", config.universe.max_symbols > 0),
+            // Orderbook snapshots are only fetched after a websocket reconnect
+            ("orderbook resync", config.market_stream.source == MarketSource::Websocket),
+        ];
+        let enabled: Vec<&str> = rest_backed.iter().filter(|(_, on)| *on).map(|(name, _)| *name).collect();
+        assert!(enabled.is_empty(), "replay still calls the REST API for {:?}", enabled);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::config::RecordingConfig;
+use crate::models::MarketEvent;
+use crate::replay::{TapeEvent, TapeHeader};
+use crate::utils::DiskWriter;
+use anyhow::Result;
+use chrono::Utc;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+// Events appended to the tape in one write
+const MAX_BATCH_EVENTS: usize = 1024;
+
+const FILE_PREFIX: &str = "events_";
+
+/// Writes every market event the event loop handles to JSONL tape files under `dir`, for
+/// replaying later with `market_stream.source = "replay"`.
+///
+/// Each file starts with a [`TapeHeader`] line. Files are rotated at `max_file_mb` and the oldest ones deleted once the tape takes more
+/// than `max_disk_mb`. Compressed files get each batch appended as a complete gzip member,
+/// so they stay readable (`zcat`) up to the last write, even after a crash.
+pub struct EventRecorder {
+    tx: mpsc::UnboundedSender<String>,
+}
+
+impl EventRecorder {
+    pub fn start(config: &RecordingConfig, disk: Arc<DiskWriter>) -> Result<Arc<Self>> {
+        fs::create_dir_all(&config.dir)?;
+        info!(
+            "[Recorder] Recording market events to {} ({} MB files, {} MB at most{})",
+            config.dir,
+            config.max_file_mb,
+            config.max_disk_mb,
+            if config.compress { ", gzip" } else { "" }
+        );
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(run_writer(config.clone(), disk, rx));
+        Ok(Arc::new(Self { tx }))
+    }
+
+    pub fn record(&self, event: &MarketEvent) {
+        let Some(event) = TapeEvent::from_event(event) else {
+            return;
+        };
+        match serde_json::to_string(&event) {
+            Ok(mut line) => {
+                line.push('\n');
+                if self.tx.send(line).is_err() {
+                    warn!("[Recorder] Writer task is gone, event dropped");
+                }
+            }
+            Err(e) => warn!("[Recorder] Could not serialize {} event: {}", event.symbol(), e),
+        }
+    }
+}
+
+async fn run_writer(config: RecordingConfig, disk: Arc<DiskWriter>, mut rx: mpsc::UnboundedReceiver<String>) {
+    let dir = PathBuf::from(&config.dir);
+    let max_file_bytes = config.max_file_mb.max(1) * 1024 * 1024;
+    let max_disk_bytes = config.max_disk_mb * 1024 * 1024;
+    let mut current = new_file_path(&dir, config.compress);
+    let mut lines = Vec::with_capacity(MAX_BATCH_EVENTS);
+
+    while rx.recv_many(&mut lines, MAX_BATCH_EVENTS).await > 0 {
+        let mut text: String = lines.drain(..).collect();
+
+        if fs::metadata(&current).is_ok_and(|m| m.len() >= max_file_bytes) {
+            current = new_file_path(&dir, config.compress);
+        }
+        let path = current.clone();
+        let (dir, compress) = (dir.clone(), config.compress);
+        disk.write(format!("event tape {}", path.display()), move || {
+            let is_new = !path.exists();
+            if is_new {
+                text.insert_str(0, &format!("{}\n", serde_json::to_string(&TapeHeader::current())?));
+            }
+            let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+            if compress {
+                let mut encoder = GzEncoder::new(file, Compression::default());
+                encoder.write_all(text.as_bytes())?;
+                encoder.finish()?;
+            } else {
+                file.write_all(text.as_bytes())?;
+            }
+            if is_new && max_disk_bytes > 0 {
+                prune_oldest_files(&dir, &path, max_disk_bytes)?;
+            }
+            Ok(())
+        })
+        .await;
+    }
+}
+
+/// Named by creation time, so names sort oldest first
+fn new_file_path(dir: &Path, compress: bool) -> PathBuf {
+    let suffix = if compress { ".jsonl.gz" } else { ".jsonl" };
+    dir.join(format!("{}{}{}", FILE_PREFIX, Utc::now().format("%Y%m%d_%H%M%S%.3f"), suffix))
+}
+
+/// Deletes the oldest tape files until the tape fits in `max_bytes`; the file being written
+/// is never deleted
+fn prune_oldest_files(dir: &Path, current: &Path, max_bytes: u64) -> Result<()> {
+    let mut files: Vec<(PathBuf, u64)> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry.file_name().to_str().is_some_and(|name| {
+                name.starts_with(FILE_PREFIX) && (name.ends_with(".jsonl") || name.ends_with(".jsonl.gz"))
+            })
+        })
+        .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?.len())))
+        .collect();
+    files.sort();
+
+    let mut total: u64 = files.iter().map(|(_, len)| len).sum();
+    for (path, len) in &files {
+        if total <= max_bytes {
+            break;
+        }
+        if path == current {
+            continue;
+        }
+        fs::remove_file(path)?;
+        info!("[Recorder] Deleted {} to stay within the tape disk cap", path.display());
+        total -= len;
+    }
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use crate::api::{MarketStream, SubscriptionCommand};
+use crate::models::{MarketEvent, SymbolInterner};
+use crate::replay::{read_tape_file, tape_files, TapeEvent};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tokio::sync::mpsc;
+use tokio::time::{sleep_until, Duration, Instant};
+use tracing::{info, warn};
+
+// Events read ahead of the replay position
+const READ_AHEAD_EVENTS: usize = 4096;
+
+// Unreadable lines reported individually before only counting them
+const MAX_LOGGED_ERRORS: usize = 10;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Feeds a recorded tape (see `EventRecorder`) or a CSV of prices through the event loop
+/// as if it came from the exchange, keeping the recorded spacing between events divided
+/// by `speed`; a speed of 0 sends events as fast as they are read.
+///
+/// Subscription changes are ignored: the tape holds whatever was recorded.
+pub struct ReplayStream {
+    path: PathBuf,
+    speed: f64,
+    /// Only these symbols are replayed; empty replays all
+    symbols: HashSet<String>,
+}
+
+impl ReplayStream {
+    pub fn new(path: impl Into<PathBuf>, speed: f64, symbols: &[String]) -> Self {
+        Self {
+            path: path.into(),
+            speed,
+            symbols: symbols.iter().cloned().collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl MarketStream for ReplayStream {
+    fn name(&self) -> &str {
+        "replay"
+    }
+
+    async fn run(
+        self: Box<Self>,
+        event_tx: mpsc::UnboundedSender<MarketEvent>,
+        mut command_rx: mpsc::UnboundedReceiver<SubscriptionCommand>,
+    ) -> Result<()> {
+        let files = tape_files(&self.path)?;
+        info!(
+            "[Replay] Replaying {} file(s) from {} at {}",
+            files.len(),
+            self.path.display(),
+            if self.speed > 0.0 { format!("{}x speed", self.speed) } else { "full speed".to_string() }
+        );
+
+        // Files are parsed on a blocking thread, a bounded channel ahead of the replay
+        let (tape_tx, mut tape_rx) = mpsc::channel::<TapeEvent>(READ_AHEAD_EVENTS);
+        let symbols = self.symbols.clone();
+        let reader = tokio::task::spawn_blocking(move || -> Result<usize> {
+            let mut errors = 0;
+            for file in &files {
+                read_tape_file(
+                    file,
+                    |event| {
+                        // Connection changes concern every symbol
+                        let symbol = event.symbol();
+                        if !symbols.is_empty() && !symbol.is_empty() && !symbols.contains(symbol) {
+                            return true;
+                        }
+                        // The replay is gone once the receiver is dropped
+                        tape_tx.blocking_send(event).is_ok()
+                    },
+                    |line, e| {
+                        errors += 1;
+                        if errors <= MAX_LOGGED_ERRORS {
+                            warn!("[Replay] {}:{} skipped: {:#}", file.display(), line, e);
+                        }
+                    },
+                )?;
+            }
+            Ok(errors)
+        });
+
+        let started = Instant::now();
+        let mut first: Option<DateTime<Utc>> = None;
+        let mut last: Option<DateTime<Utc>> = None;
+        let mut sent = 0u64;
+        let mut next_progress = started + PROGRESS_INTERVAL;
+        let interner = SymbolInterner::new();
+
+        loop {
+            let event = tokio::select! {
+                event = tape_rx.recv() => match event {
+                    Some(event) => event,
+                    None => break,
+                },
+                // The tape holds whatever was recorded; keep the channel drained
+                Some(_) = command_rx.recv() => continue,
+            };
+
+            let time = event.time();
+            let tape_start = *first.get_or_insert(time);
+            if self.speed > 0.0 {
+                let offset = (time - tape_start).to_std().unwrap_or_default().div_f64(self.speed);
+                sleep_until(started + offset).await;
+            } else if sent.is_multiple_of(READ_AHEAD_EVENTS as u64) {
+                tokio::task::yield_now().await;
+            }
+
+            event_tx.send(event.to_event(&interner))?;
+            sent += 1;
+            last = Some(time);
+
+            if Instant::now() >= next_progress {
+                next_progress += PROGRESS_INTERVAL;
+                info!("[Replay] {} events sent | tape time {}", sent, time.format("%Y-%m-%d %H:%M:%S"));
+            }
+        }
+
+        let errors = reader.await.map_err(|e| anyhow!("replay reader panicked: {}", e))??;
+        let span = first.zip(last).map(|(first, last)| last - first).unwrap_or_default();
+        info!(
+            "[Replay] Finished: {} events covering {}s of tape in {:.1}s{}",
+            sent,
+            span.num_seconds(),
+            started.elapsed().as_secs_f64(),
+            if errors > 0 { format!(" | {} unreadable lines skipped", errors) } else { String::new() }
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,361 @@
+use crate::models::{ConnectionStatus, MarketEvent, OrderbookLevel, ProcessedOrderbook, Quote, Stats24h, SymbolInterner};
+use crate::utils::{newer_format_error, StoredFormat};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::read::MultiGzDecoder;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// Version of the JSONL tape format. Recorded tapes start with a [`TapeHeader`] line;
+/// tapes without one are version 0, whose events are read the same way.
+pub const TAPE_FORMAT: u32 = 1;
+
+/// The first line of a recorded tape file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TapeHeader {
+    pub format_version: u32,
+}
+
+impl TapeHeader {
+    pub fn current() -> Self {
+        Self {
+            format_version: TAPE_FORMAT,
+        }
+    }
+}
+
+/// One market event as stored on a tape: a line of JSON tagged with its `type`.
+///
+/// Trades feed only candle volume. Connection changes are kept so a replay marks symbols
+/// stale where the live run did. Contract state changes come from the REST re-checks rather than the market stream and
+/// are not recorded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TapeEvent {
+    Ticker {
+        time: DateTime<Utc>,
+        symbol: String,
+        last: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mark: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bid: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ask: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        stats_24h: Option<Stats24h>,
+    },
+    MarkPrice {
+        time: DateTime<Utc>,
+        symbol: String,
+        mark: f64,
+    },
+    /// Levels as `[price, quantity]`
+    Depth {
+        time: DateTime<Utc>,
+        symbol: String,
+        bids: Vec<(f64, f64)>,
+        asks: Vec<(f64, f64)>,
+    },
+    /// `volume` in contracts
+    Trade {
+        time: DateTime<Utc>,
+        symbol: String,
+        price: f64,
+        volume: f64,
+    },
+    Funding {
+        time: DateTime<Utc>,
+        symbol: String,
+        rate: f64,
+    },
+    /// The market stream went down (`connected: false`) or came back
+    Connection {
+        time: DateTime<Utc>,
+        connected: bool,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        reconnect: bool,
+    },
+}
+
+impl TapeEvent {
+    pub fn from_event(event: &MarketEvent) -> Option<Self> {
+        let levels = |levels: &[OrderbookLevel]| levels.iter().map(|l| (l.price, l.quantity)).collect();
+        match event {
+            MarketEvent::TickerUpdate {
+                symbol,
+                last_price,
+                mark_price,
+                stats_24h,
+                quote,
+                timestamp,
+            } => Some(TapeEvent::Ticker {
+                time: *timestamp,
+                symbol: symbol.to_string(),
+                last: *last_price,
+                mark: *mark_price,
+                bid: quote.map(|q| q.best_bid),
+                ask: quote.map(|q| q.best_ask),
+                stats_24h: *stats_24h,
+            }),
+            MarketEvent::MarkPriceUpdate {
+                symbol,
+                mark_price,
+                timestamp,
+            } => Some(TapeEvent::MarkPrice {
+                time: *timestamp,
+                symbol: symbol.to_string(),
+                mark: *mark_price,
+            }),
+            MarketEvent::OrderbookUpdate { symbol, orderbook } => Some(TapeEvent::Depth {
+                time: orderbook.timestamp,
+                symbol: symbol.to_string(),
+                bids: levels(&orderbook.bids),
+                asks: levels(&orderbook.asks),
+            }),
+            MarketEvent::TradeUpdate {
+                symbol,
+                price,
+                volume,
+                timestamp,
+            } => Some(TapeEvent::Trade {
+                time: *timestamp,
+                symbol: symbol.to_string(),
+                price: *price,
+                volume: *volume,
+            }),
+            MarketEvent::FundingRateUpdate { symbol, rate, timestamp } => Some(TapeEvent::Funding {
+                time: *timestamp,
+                symbol: symbol.to_string(),
+                rate: *rate,
+            }),
+            MarketEvent::ConnectionStatus(ConnectionStatus::Connected { reconnect, timestamp }) => Some(TapeEvent::Connection {
+                time: *timestamp,
+                connected: true,
+                reconnect: *reconnect,
+            }),
+            MarketEvent::ConnectionStatus(ConnectionStatus::Disconnected { timestamp }) => Some(TapeEvent::Connection {
+                time: *timestamp,
+                connected: false,
+                reconnect: false,
+            }),
+            MarketEvent::ContractStateChange(_) | MarketEvent::SymbolRenamed(_) => None,
+        }
+    }
+
+    pub fn time(&self) -> DateTime<Utc> {
+        match self {
+            TapeEvent::Ticker { time, .. }
+            | TapeEvent::MarkPrice { time, .. }
+            | TapeEvent::Depth { time, .. }
+            | TapeEvent::Trade { time, .. }
+            | TapeEvent::Funding { time, .. }
+            | TapeEvent::Connection { time, .. } => *time,
+        }
+    }
+
+    /// The event's symbol; empty for connection changes, which concern every symbol
+    pub fn symbol(&self) -> &str {
+        match self {
+            TapeEvent::Ticker { symbol, .. }
+            | TapeEvent::MarkPrice { symbol, .. }
+            | TapeEvent::Depth { symbol, .. }
+            | TapeEvent::Trade { symbol, .. }
+            | TapeEvent::Funding { symbol, .. } => symbol,
+            TapeEvent::Connection { .. } => "",
+        }
+    }
+
+    /// The market event, with its symbol from `symbols`
+    pub fn to_event(&self, symbols: &SymbolInterner) -> MarketEvent {
+        let levels = |levels: &[(f64, f64)]| {
+            levels
+                .iter()
+                .map(|&(price, quantity)| OrderbookLevel { price, quantity })
+                .collect()
+        };
+        match self {
+            TapeEvent::Ticker {
+                time,
+                symbol,
+                last,
+                mark,
+                bid,
+                ask,
+                stats_24h,
+            } => MarketEvent::TickerUpdate {
+                symbol: symbols.intern(symbol),
+                last_price: *last,
+                mark_price: *mark,
+                stats_24h: *stats_24h,
+                quote: bid.zip(*ask).and_then(|(bid, ask)| Quote::new(bid, ask, *time)),
+                timestamp: *time,
+            },
+            TapeEvent::MarkPrice { time, symbol, mark } => MarketEvent::MarkPriceUpdate {
+                symbol: symbols.intern(symbol),
+                mark_price: *mark,
+                timestamp: *time,
+            },
+            TapeEvent::Depth {
+                time,
+                symbol,
+                bids,
+                asks,
+            } => MarketEvent::OrderbookUpdate {
+                symbol: symbols.intern(symbol),
+                orderbook: ProcessedOrderbook {
+                    bids: levels(bids),
+                    asks: levels(asks),
+                    timestamp: *time,
+                },
+            },
+            TapeEvent::Trade {
+                time,
+                symbol,
+                price,
+                volume,
+            } => MarketEvent::TradeUpdate {
+                symbol: symbols.intern(symbol),
+                price: *price,
+                volume: *volume,
+                timestamp: *time,
+            },
+            TapeEvent::Funding { time, symbol, rate } => MarketEvent::FundingRateUpdate {
+                symbol: symbols.intern(symbol),
+                rate: *rate,
+                timestamp: *time,
+            },
+            TapeEvent::Connection {
+                time,
+                connected: true,
+                reconnect,
+            } => MarketEvent::ConnectionStatus(ConnectionStatus::Connected {
+                reconnect: *reconnect,
+                timestamp: *time,
+            }),
+            TapeEvent::Connection { time, connected: false, .. } => {
+                MarketEvent::ConnectionStatus(ConnectionStatus::Disconnected { timestamp: *time })
+            }
+        }
+    }
+}
+
+/// A row of a CSV tape: `time,symbol,last_price,mark_price`, where `time` is RFC 3339 or
+/// epoch milliseconds and either price may be empty
+#[derive(Debug, Deserialize)]
+struct CsvTapeRow {
+    time: String,
+    symbol: String,
+    last_price: Option<f64>,
+    mark_price: Option<f64>,
+}
+
+impl CsvTapeRow {
+    fn into_event(self) -> Result<TapeEvent> {
+        let time = match self.time.parse::<i64>() {
+            Ok(ms) => DateTime::from_timestamp_millis(ms).ok_or_else(|| anyhow!("time {} is out of range", ms))?,
+            Err(_) => DateTime::parse_from_rfc3339(&self.time)
+                .with_context(|| format!("time {:?}", self.time))?
+                .with_timezone(&Utc),
+        };
+        match (self.last_price, self.mark_price) {
+            (Some(last), mark) => Ok(TapeEvent::Ticker {
+                time,
+                symbol: self.symbol,
+                last,
+                mark,
+                bid: None,
+                ask: None,
+                stats_24h: None,
+            }),
+            (None, Some(mark)) => Ok(TapeEvent::MarkPrice {
+                time,
+                symbol: self.symbol,
+                mark,
+            }),
+            (None, None) => bail!("neither last_price nor mark_price is set"),
+        }
+    }
+}
+
+const TAPE_SUFFIXES: [&str; 3] = [".jsonl", ".jsonl.gz", ".csv"];
+
+/// The `.jsonl`, `.jsonl.gz` and `.csv` files of a tape: a single file, or every one in a
+/// directory in name order (recordings are named by their start time)
+pub fn tape_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(path)
+        .with_context(|| format!("reading replay tape {}", path.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| TAPE_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)))
+        })
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        bail!("no .jsonl, .jsonl.gz or .csv files in {}", path.display());
+    }
+    Ok(files)
+}
+
+/// Reads the events of one tape file in order, calling `on_event` for each; lines that
+/// don't parse are passed to `on_error` and skipped. Stops early when `on_event` returns
+/// false. Fails on a file recorded in a newer format than this build reads.
+pub fn read_tape_file(
+    path: &Path,
+    mut on_event: impl FnMut(TapeEvent) -> bool,
+    mut on_error: impl FnMut(usize, anyhow::Error),
+) -> Result<()> {
+    let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    if path.extension().is_some_and(|ext| ext == "csv") {
+        let mut reader = csv::Reader::from_reader(BufReader::new(file));
+        for (index, row) in reader.deserialize::<CsvTapeRow>().enumerate() {
+            // Line numbers count the header
+            match row.map_err(anyhow::Error::from).and_then(CsvTapeRow::into_event) {
+                Ok(event) => {
+                    if !on_event(event) {
+                        break;
+                    }
+                }
+                Err(e) => on_error(index + 2, e),
+            }
+        }
+    } else {
+        // Compressed tapes are a series of gzip members, one per recorder write
+        let file: Box<dyn Read> = if path.extension().is_some_and(|ext| ext == "gz") {
+            Box::new(MultiGzDecoder::new(file))
+        } else {
+            Box::new(file)
+        };
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.with_context(|| format!("reading {}", path.display()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if index == 0 {
+                if let Ok(header) = serde_json::from_str::<TapeHeader>(&line) {
+                    if let StoredFormat::Newer(found) = StoredFormat::of(header.format_version, TAPE_FORMAT) {
+                        return Err(newer_format_error(path, found, TAPE_FORMAT));
+                    }
+                    continue;
+                }
+            }
+            match serde_json::from_str::<TapeEvent>(&line) {
+                Ok(event) => {
+                    if !on_event(event) {
+                        break;
+                    }
+                }
+                Err(e) => on_error(index + 1, e.into()),
+            }
+        }
+    }
+    Ok(())
+}
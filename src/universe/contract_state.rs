@@ -0,0 +1,338 @@
+use crate::alerts::Notification;
+use crate::api::{MexcRestClient, SubscriptionCommand};
+use crate::config::ContractMonitorConfig;
+use crate::models::{ContractDetail, ContractState, ContractStateChange, FineCandles, MarketEvent, NewListing, Symbol, SymbolData, SymbolRename};
+use crate::utils::SharedClock;
+use chrono::Utc;
+use dashmap::DashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// Contracts unsubscribed because they stopped trading, shared with the universe rotation
+/// so it doesn't subscribe them again while they are suspended
+#[derive(Debug, Default)]
+pub struct SuspendedContracts {
+    symbols: Mutex<HashSet<String>>,
+}
+
+impl SuspendedContracts {
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.symbols.lock().unwrap().contains(symbol)
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.lock().unwrap().is_empty()
+    }
+
+    fn insert(&self, symbol: &str) -> bool {
+        self.symbols.lock().unwrap().insert(symbol.to_string())
+    }
+
+    fn remove(&self, symbol: &str) -> bool {
+        self.symbols.lock().unwrap().remove(symbol)
+    }
+
+    fn symbols(&self) -> Vec<String> {
+        self.symbols.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+/// Re-fetches the contract details periodically and reports monitored contracts whose
+/// `state` changed, both to the event loop (which stops their strategies and closes open
+/// episodes) and to the notifiers.
+///
+/// A monitored contract missing from the details is treated as delisted.
+///
+/// With `with_suspensions`, a contract that stops trading is also unsubscribed and
+/// dropped from the monitored set, after the event loop was told to close its episodes.
+/// Its state keeps being re-checked, and it is subscribed again once it is enabled.
+///
+/// Contracts that appear in the details are reported as new listings; they are usually
+/// added ahead of their first trades. With `with_new_listing_subscriptions` their market
+/// data is subscribed right away.
+///
+/// With `with_rename_migration`, a monitored contract that stops trading in the same check
+/// in which a contract for the same coin appears (e.g. `PEPE_USDT` and `1000PEPE_USDT`
+/// after a redenomination) is taken as renamed rather than delisted: its market data moves
+/// to the new symbol with prices rescaled, the event loop carries its open episodes over,
+/// and the subscription follows. Neither a state change nor a new listing is reported.
+pub struct ContractStateMonitor {
+    config: ContractMonitorConfig,
+    rest: Arc<MexcRestClient>,
+    symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+    // Last known state per contract, seeded from the startup fetch
+    states: Mutex<HashMap<String, ContractState>>,
+    // Details of the last fetch, to match renamed contracts by
+    details: Mutex<HashMap<String, ContractDetail>>,
+    event_tx: mpsc::UnboundedSender<MarketEvent>,
+    alert_tx: mpsc::UnboundedSender<Notification>,
+    // Where subscription changes go, if the monitor makes any
+    subscriptions: Option<(mpsc::UnboundedSender<SubscriptionCommand>, SharedClock)>,
+    subscribe_new_listings: bool,
+    // Contracts unsubscribed for not trading, if they are
+    suspended: Option<Arc<SuspendedContracts>>,
+    fine_candles: Option<FineCandles>,
+    // Where renamed contracts are resubscribed, if renames are migrated
+    renames: Option<mpsc::UnboundedSender<SubscriptionCommand>>,
+}
+
+impl ContractStateMonitor {
+    pub fn new(
+        config: ContractMonitorConfig,
+        rest: Arc<MexcRestClient>,
+        symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+        details: &[ContractDetail],
+        event_tx: mpsc::UnboundedSender<MarketEvent>,
+        alert_tx: mpsc::UnboundedSender<Notification>,
+    ) -> Self {
+        let states = details
+            .iter()
+            .map(|d| (d.symbol.clone(), ContractState::from_code(d.state)))
+            .collect();
+        Self {
+            config,
+            rest,
+            symbol_data,
+            states: Mutex::new(states),
+            details: Mutex::new(details.iter().map(|d| (d.symbol.clone(), d.clone())).collect()),
+            event_tx,
+            alert_tx,
+            subscriptions: None,
+            subscribe_new_listings: false,
+            suspended: None,
+            fine_candles: None,
+            renames: None,
+        }
+    }
+
+    /// Subscribe the market data of new listings as soon as they appear
+    pub fn with_new_listing_subscriptions(
+        mut self,
+        command_tx: mpsc::UnboundedSender<SubscriptionCommand>,
+        clock: SharedClock,
+    ) -> Self {
+        self.subscriptions = Some((command_tx, clock));
+        self.subscribe_new_listings = true;
+        self
+    }
+
+    /// Unsubscribe contracts that stop trading, and subscribe them again once they resume
+    pub fn with_suspensions(
+        mut self,
+        suspended: Arc<SuspendedContracts>,
+        command_tx: mpsc::UnboundedSender<SubscriptionCommand>,
+        clock: SharedClock,
+    ) -> Self {
+        self.subscriptions = Some((command_tx, clock));
+        self.suspended = Some(suspended);
+        self
+    }
+
+    /// Fine candle buffer given to the `SymbolData` of subscribed new listings
+    pub fn with_fine_candles(mut self, fine_candles: Option<FineCandles>) -> Self {
+        self.fine_candles = fine_candles;
+        self
+    }
+
+    /// Move the market data and episodes of renamed contracts to their new symbol
+    pub fn with_rename_migration(mut self, command_tx: mpsc::UnboundedSender<SubscriptionCommand>) -> Self {
+        self.renames = Some(command_tx);
+        self
+    }
+
+    pub async fn run(self: Arc<Self>) {
+        let mut check = tokio::time::interval(Duration::from_secs(self.config.check_interval_secs.max(1)));
+        // The first tick completes immediately and the startup fetch is fresh
+        check.tick().await;
+        loop {
+            check.tick().await;
+            self.check().await;
+        }
+    }
+
+    /// Compares the current details with the last known states; keeps them if the fetch fails
+    pub async fn check(&self) {
+        let details = match self.rest.get_contract_details().await {
+            Ok(details) if !details.is_empty() => details,
+            Ok(_) => {
+                warn!("[ContractState] Exchange returned no contracts, keeping the current states");
+                return;
+            }
+            Err(e) => {
+                warn!("[ContractState] Failed to fetch contract details, keeping the current states: {:?}", e);
+                return;
+            }
+        };
+
+        let mut current: HashMap<String, ContractState> = details
+            .iter()
+            .map(|d| (d.symbol.clone(), ContractState::from_code(d.state)))
+            .collect();
+        let mut monitored: Vec<String> = self.symbol_data.iter().map(|entry| entry.key().to_string()).collect();
+        if let Some(ref suspended) = self.suspended {
+            monitored.extend(suspended.symbols());
+        }
+
+        let at = Utc::now();
+        let mut changes = Vec::new();
+        let mut new_listings: Vec<&ContractDetail>;
+        let mut renames = Vec::new();
+        {
+            let mut states = self.states.lock().unwrap();
+            new_listings = details.iter().filter(|d| !states.contains_key(&d.symbol)).collect();
+            for symbol in monitored {
+                let state = *current.entry(symbol.clone()).or_insert(ContractState::Delisted);
+                let previous = states.get(&symbol).copied().unwrap_or(ContractState::Enabled);
+                if state != previous {
+                    changes.push(ContractStateChange {
+                        symbol,
+                        previous,
+                        current: state,
+                        at,
+                    });
+                }
+            }
+            *states = current;
+
+            let mut previous = self.details.lock().unwrap();
+            if self.renames.is_some() {
+                renames = find_renames(&previous, &changes, &new_listings);
+                changes.retain(|change| !renames.iter().any(|rename| rename.from == change.symbol));
+                new_listings.retain(|detail| !renames.iter().any(|rename| rename.to == detail.symbol));
+            }
+            *previous = details.iter().map(|d| (d.symbol.clone(), d.clone())).collect();
+        }
+
+        for rename in &renames {
+            self.migrate(rename);
+        }
+
+        for change in &changes {
+            let _ = self.event_tx.send(MarketEvent::ContractStateChange(change.clone()));
+            let _ = self.alert_tx.send(change.clone().into());
+        }
+        if let Some(ref suspended) = self.suspended {
+            self.update_suspensions(suspended, &changes);
+        }
+
+        if self.config.new_listings && !new_listings.is_empty() {
+            self.report_new_listings(&new_listings);
+        }
+    }
+
+    /// Moves a renamed contract's market data to the new symbol and tells the event loop,
+    /// then resubscribes; the event is queued ahead of any data of the new symbol
+    fn migrate(&self, rename: &SymbolRename) {
+        let Some(ref command_tx) = self.renames else {
+            return;
+        };
+        warn!("[ContractState] {}", rename);
+        let Some((_, mut data)) = self.symbol_data.remove(rename.from.as_str()) else {
+            return;
+        };
+        data.rename(&rename.to, rename.price_factor);
+        self.symbol_data.insert(data.symbol.clone(), data);
+        let _ = self.event_tx.send(MarketEvent::SymbolRenamed(rename.clone()));
+        let _ = command_tx.send(SubscriptionCommand::Unsubscribe(vec![rename.from.clone()]));
+        let _ = command_tx.send(SubscriptionCommand::Subscribe(vec![rename.to.clone()]));
+    }
+
+    /// Unsubscribes the contracts that stopped trading and subscribes the suspended ones
+    /// that resumed. The state changes are already queued for the event loop, ahead of
+    /// anything the removed symbols could still receive.
+    fn update_suspensions(&self, suspended: &SuspendedContracts, changes: &[ContractStateChange]) {
+        let Some((ref command_tx, ref clock)) = self.subscriptions else {
+            return;
+        };
+        let mut stopped = Vec::new();
+        let mut resumed = Vec::new();
+        for change in changes {
+            if !change.current.is_trading() {
+                if suspended.insert(&change.symbol) {
+                    self.symbol_data.remove(change.symbol.as_str());
+                    stopped.push(change.symbol.clone());
+                }
+            } else if suspended.remove(&change.symbol) {
+                let data = SymbolData::new(change.symbol.as_str(), clock.clone()).with_fine_candles(self.fine_candles);
+                self.symbol_data.insert(data.symbol.clone(), data);
+                resumed.push(change.symbol.clone());
+            }
+        }
+
+        if !stopped.is_empty() {
+            info!(
+                "[ContractState] Unsubscribed {} contracts that stopped trading ({} suspended): {:?}",
+                stopped.len(),
+                suspended.len(),
+                stopped
+            );
+            let _ = command_tx.send(SubscriptionCommand::Unsubscribe(stopped));
+        }
+        if !resumed.is_empty() {
+            info!("[ContractState] {} suspended contracts trade again, resubscribed: {:?}", resumed.len(), resumed);
+            let _ = command_tx.send(SubscriptionCommand::Subscribe(resumed));
+        }
+    }
+
+    fn report_new_listings(&self, details: &[&ContractDetail]) {
+        let at = Utc::now();
+        let subscribed: Vec<String> = match self.subscriptions {
+            Some((ref command_tx, ref clock)) if self.subscribe_new_listings => {
+                let symbols: Vec<String> = details
+                    .iter()
+                    .filter(|d| !self.symbol_data.contains_key(d.symbol.as_str()))
+                    .map(|d| d.symbol.clone())
+                    .collect();
+                for symbol in &symbols {
+                    let data = SymbolData::new(symbol.as_str(), clock.clone()).with_fine_candles(self.fine_candles);
+                    self.symbol_data.insert(data.symbol.clone(), data);
+                }
+                if !symbols.is_empty() {
+                    info!("[ContractState] Subscribing {} new listings in advance: {:?}", symbols.len(), symbols);
+                    let _ = command_tx.send(SubscriptionCommand::Subscribe(symbols.clone()));
+                }
+                symbols
+            }
+            _ => Vec::new(),
+        };
+
+        for detail in details {
+            let listing = NewListing::from_detail(detail, subscribed.contains(&detail.symbol), at);
+            let _ = self.alert_tx.send(listing.into());
+        }
+    }
+}
+
+/// Pairs each monitored contract that just stopped trading with the trading contract that
+/// appeared for the same coin; contracts matching more than one counterpart are left alone
+fn find_renames(
+    previous: &HashMap<String, ContractDetail>,
+    changes: &[ContractStateChange],
+    new_listings: &[&ContractDetail],
+) -> Vec<SymbolRename> {
+    let candidates: Vec<SymbolRename> = changes
+        .iter()
+        .filter(|change| change.previous.is_trading() && !change.current.is_trading())
+        .filter_map(|change| previous.get(&change.symbol))
+        .flat_map(|old| {
+            new_listings
+                .iter()
+                .filter(|new| ContractState::from_code(new.state).is_trading())
+                .filter_map(move |new| SymbolRename::between(old, new))
+        })
+        .collect();
+    candidates
+        .iter()
+        .filter(|rename| {
+            candidates.iter().filter(|other| other.from == rename.from || other.to == rename.to).count() == 1
+        })
+        .cloned()
+        .collect()
+}
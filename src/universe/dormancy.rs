@@ -0,0 +1,243 @@
+use crate::api::{MexcRestClient, SubscriptionCommand};
+use crate::config::DormancyConfig;
+use crate::models::{FineCandles, Symbol, SymbolData};
+use crate::utils::{format_version, set_aside, SharedClock, StoredFormat, FORMAT_VERSION_KEY};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// Version of the dormant list file; version 0 is the bare `{symbol: since}` map written
+/// before the file was versioned
+const DORMANT_LIST_FORMAT: u32 = 1;
+
+/// Contracts archived for inactivity, persisted so a restart doesn't subscribe to them again
+pub struct DormantList {
+    path: PathBuf,
+    symbols: Mutex<BTreeMap<String, DateTime<Utc>>>,
+    /// Set when the file is from a newer build, which this one leaves as it is
+    read_only: bool,
+}
+
+impl DormantList {
+    /// Loads the list from `path`; a missing file starts an empty list. An unreadable file
+    /// is set aside before starting empty, and one from a newer build is left untouched.
+    pub fn load(path: &str) -> Self {
+        let path = PathBuf::from(path);
+        let mut read_only = false;
+        let symbols = match fs::read_to_string(&path) {
+            Ok(contents) => match parse_dormant_list(&contents) {
+                Ok(Some(symbols)) => symbols,
+                Ok(None) => {
+                    warn!(
+                        "[Dormancy] {} is from a newer build - not using or updating it",
+                        path.display()
+                    );
+                    read_only = true;
+                    BTreeMap::new()
+                }
+                Err(e) => {
+                    match set_aside(&path, "unreadable") {
+                        Ok(kept) => warn!("[Dormancy] Unreadable {} ({}), kept as {}", path.display(), e, kept.display()),
+                        Err(move_error) => {
+                            warn!("[Dormancy] Unreadable {} ({}) and {:?} - not updating it", path.display(), e, move_error);
+                            read_only = true;
+                        }
+                    }
+                    BTreeMap::new()
+                }
+            },
+            Err(_) => BTreeMap::new(),
+        };
+
+        Self {
+            path,
+            symbols: Mutex::new(symbols),
+            read_only,
+        }
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.symbols.lock().unwrap().contains_key(symbol)
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.lock().unwrap().is_empty()
+    }
+
+    fn insert(&self, symbols: &[String], since: DateTime<Utc>) {
+        let mut list = self.symbols.lock().unwrap();
+        for symbol in symbols {
+            list.insert(symbol.clone(), since);
+        }
+        self.save(&list);
+    }
+
+    fn remove(&self, symbols: &[String]) {
+        let mut list = self.symbols.lock().unwrap();
+        for symbol in symbols {
+            list.remove(symbol);
+        }
+        self.save(&list);
+    }
+
+    fn save(&self, list: &BTreeMap<String, DateTime<Utc>>) {
+        if self.read_only {
+            return;
+        }
+        let write = || -> Result<()> {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let tmp = self.path.with_extension("tmp");
+            let file = json!({ FORMAT_VERSION_KEY: DORMANT_LIST_FORMAT, "symbols": list });
+            fs::write(&tmp, serde_json::to_string_pretty(&file)?)?;
+            fs::rename(&tmp, &self.path)?;
+            Ok(())
+        };
+        if let Err(e) = write() {
+            warn!("[Dormancy] Failed to write {}: {:?}", self.path.display(), e);
+        }
+    }
+}
+
+/// The symbols of a dormant list file, migrated from older formats; `None` when the file
+/// is from a newer build
+fn parse_dormant_list(contents: &str) -> Result<Option<BTreeMap<String, DateTime<Utc>>>> {
+    let mut value: serde_json::Value = serde_json::from_str(contents)?;
+    let symbols = match StoredFormat::of(format_version(&value), DORMANT_LIST_FORMAT) {
+        StoredFormat::Newer(_) => return Ok(None),
+        StoredFormat::Older(_) => value,
+        StoredFormat::Current => value
+            .get_mut("symbols")
+            .map(serde_json::Value::take)
+            .ok_or_else(|| anyhow!("no symbols"))?,
+    };
+    Ok(Some(serde_json::from_value(symbols)?))
+}
+
+/// Unsubscribes contracts that stopped trading and brings them back once they revive,
+/// freeing bandwidth and memory for active markets.
+pub struct DormancyMonitor {
+    config: DormancyConfig,
+    rest: Arc<MexcRestClient>,
+    symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+    command_tx: mpsc::UnboundedSender<SubscriptionCommand>,
+    dormant: Arc<DormantList>,
+    clock: SharedClock,
+    fine_candles: Option<FineCandles>,
+    started_at: DateTime<Utc>,
+}
+
+impl DormancyMonitor {
+    pub fn new(
+        config: DormancyConfig,
+        rest: Arc<MexcRestClient>,
+        symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+        command_tx: mpsc::UnboundedSender<SubscriptionCommand>,
+        dormant: Arc<DormantList>,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            started_at: clock.now(),
+            config,
+            rest,
+            symbol_data,
+            command_tx,
+            dormant,
+            clock,
+            fine_candles: None,
+        }
+    }
+
+    /// Fine candle buffer given to the `SymbolData` of revived contracts
+    pub fn with_fine_candles(mut self, fine_candles: Option<FineCandles>) -> Self {
+        self.fine_candles = fine_candles;
+        self
+    }
+
+    pub async fn run(self: Arc<Self>) {
+        let mut idle_check = tokio::time::interval(std::time::Duration::from_secs(self.config.check_interval_secs));
+        let mut recheck = tokio::time::interval(std::time::Duration::from_secs(self.config.recheck_interval_hours * 3600));
+
+        loop {
+            tokio::select! {
+                _ = idle_check.tick() => self.archive_idle(),
+                _ = recheck.tick() => self.recheck_dormant().await,
+            }
+        }
+    }
+
+    /// Moves symbols without a trade for `idle_hours` to the dormant list
+    fn archive_idle(&self) {
+        let now = self.clock.now();
+        let cutoff = now - Duration::hours(self.config.idle_hours as i64);
+
+        let idle: Vec<String> = self
+            .symbol_data
+            .iter()
+            // Symbols that never traded since startup are judged from the start time
+            .filter(|entry| entry.value().last_trade_at.unwrap_or(self.started_at) < cutoff)
+            .map(|entry| entry.key().to_string())
+            .collect();
+        if idle.is_empty() {
+            return;
+        }
+
+        for symbol in &idle {
+            self.symbol_data.remove(symbol.as_str());
+        }
+        self.dormant.insert(&idle, now);
+        let _ = self.command_tx.send(SubscriptionCommand::Unsubscribe(idle.clone()));
+        info!(
+            "[Dormancy] Archived {} contracts with no trades for {}h ({} dormant): {:?}",
+            idle.len(),
+            self.config.idle_hours,
+            self.dormant.len(),
+            idle
+        );
+    }
+
+    /// Resubscribes dormant symbols whose 24h turnover shows they are trading again
+    async fn recheck_dormant(&self) {
+        if self.dormant.is_empty() {
+            return;
+        }
+
+        let tickers = match self.rest.get_tickers().await {
+            Ok(tickers) => tickers,
+            Err(e) => {
+                warn!("[Dormancy] Failed to fetch tickers for the dormant re-check: {:?}", e);
+                return;
+            }
+        };
+
+        let revived: Vec<String> = tickers
+            .into_iter()
+            .filter(|t| t.amount_24h >= self.config.revive_min_turnover_usdt && self.dormant.contains(&t.symbol))
+            .map(|t| t.symbol)
+            .collect();
+        if revived.is_empty() {
+            info!("[Dormancy] Re-checked {} dormant contracts - none revived", self.dormant.len());
+            return;
+        }
+
+        self.dormant.remove(&revived);
+        for symbol in &revived {
+            let data = SymbolData::new(symbol.as_str(), self.clock.clone()).with_fine_candles(self.fine_candles);
+            self.symbol_data.insert(data.symbol.clone(), data);
+        }
+        let _ = self.command_tx.send(SubscriptionCommand::Subscribe(revived.clone()));
+        info!("[Dormancy] {} contracts revived and resubscribed: {:?}", revived.len(), revived);
+    }
+}
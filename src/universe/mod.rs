@@ -0,0 +1,15 @@
+pub mod contract_state;
+pub mod dormancy;
+pub mod open_interest;
+pub mod resource_budget;
+pub mod rotation;
+pub mod selector;
+pub mod tiers;
+
+pub use contract_state::*;
+pub use dormancy::*;
+pub use open_interest::*;
+pub use resource_budget::*;
+pub use rotation::*;
+pub use selector::*;
+pub use tiers::*;
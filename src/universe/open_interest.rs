@@ -0,0 +1,56 @@
+use crate::api::MexcRestClient;
+use crate::config::OpenInterestConfig;
+use crate::models::{Symbol, SymbolData};
+use dashmap::DashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Polls the open interest of every monitored contract from the REST ticker into
+/// `SymbolData`, for the `min_oi_increase_pct` strategy condition.
+pub struct OpenInterestPoller {
+    config: OpenInterestConfig,
+    rest: Arc<MexcRestClient>,
+    symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+}
+
+impl OpenInterestPoller {
+    pub fn new(config: OpenInterestConfig, rest: Arc<MexcRestClient>, symbol_data: Arc<DashMap<Symbol, SymbolData>>) -> Self {
+        Self {
+            config,
+            rest,
+            symbol_data,
+        }
+    }
+
+    pub async fn run(self: Arc<Self>) {
+        let mut poll = tokio::time::interval(Duration::from_secs(self.config.poll_interval_secs.max(1)));
+        loop {
+            poll.tick().await;
+            self.poll().await;
+        }
+    }
+
+    async fn poll(&self) {
+        let tickers = match self.rest.get_tickers().await {
+            Ok(tickers) => tickers,
+            Err(e) => {
+                warn!("[OpenInterest] Failed to poll open interest: {:?}", e);
+                return;
+            }
+        };
+
+        let mut count = 0;
+        for ticker in tickers {
+            let Some(hold_vol) = ticker.hold_vol else {
+                continue;
+            };
+            if let Some(mut data) = self.symbol_data.get_mut(ticker.symbol.as_str()) {
+                let now = data.now();
+                data.update_open_interest(hold_vol, now);
+                count += 1;
+            }
+        }
+        debug!("[OpenInterest] Recorded open interest for {} contracts", count);
+    }
+}
@@ -0,0 +1,320 @@
+use crate::api::{PrioritySymbols, SubscriptionCommand};
+use crate::config::{Mitigation, ResourceBudgetConfig};
+use crate::models::{set_history_retention_secs, FineCandles, Symbol, SymbolData};
+use crate::utils::{format_bytes, process_cpu_secs, process_rss_bytes, SharedClock};
+use dashmap::DashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+const MB: u64 = 1024 * 1024;
+
+/// One measurement of the process's resource usage
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ResourceUsage {
+    pub rss_bytes: Option<u64>,
+    /// Over the last check interval, in percent of one core
+    pub cpu_pct: Option<f64>,
+}
+
+impl fmt::Display for ResourceUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.rss_bytes {
+            Some(rss) => write!(f, "RSS {}", format_bytes(rss))?,
+            None => write!(f, "RSS n/a")?,
+        }
+        match self.cpu_pct {
+            Some(cpu) => write!(f, " | CPU {:.0}%", cpu),
+            None => write!(f, " | CPU n/a"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct SheddingState {
+    usage: ResourceUsage,
+    /// Applied mitigations in order, each with what it did
+    active: Vec<(Mitigation, String)>,
+    /// Symbols whose orderbook was dropped
+    depthless: HashSet<String>,
+    /// Symbols unsubscribed by `reduce_symbols`, highest priority first
+    shed: Vec<String>,
+}
+
+/// Mitigations the resource budget currently applies, shared with the universe rotation
+/// (paused while symbols are shed) and the channel health check (which doesn't expect
+/// depth from symbols whose orderbook was dropped)
+#[derive(Debug, Default)]
+pub struct LoadShedding {
+    state: Mutex<SheddingState>,
+}
+
+impl LoadShedding {
+    pub fn is_depthless(&self, symbol: &str) -> bool {
+        self.state.lock().unwrap().depthless.contains(symbol)
+    }
+
+    pub fn symbols_shed(&self) -> usize {
+        self.state.lock().unwrap().shed.len()
+    }
+
+    pub fn active(&self) -> Vec<Mitigation> {
+        self.state.lock().unwrap().active.iter().map(|(mitigation, _)| *mitigation).collect()
+    }
+
+    /// One line for the status report
+    pub fn summary(&self) -> String {
+        let state = self.state.lock().unwrap();
+        let active: Vec<String> = state
+            .active
+            .iter()
+            .map(|(mitigation, detail)| format!("{} ({})", mitigation.name(), detail))
+            .collect();
+        format!(
+            "Resources: {} | mitigations: {}",
+            state.usage,
+            if active.is_empty() { "none".to_string() } else { active.join(", ") }
+        )
+    }
+}
+
+/// Measures the process's memory and CPU usage every `check_interval_secs` and sheds load
+/// while it's over its soft limits: the configured mitigations are applied one at a time,
+/// in order, and lifted in reverse once usage has been well below the limits for a while.
+///
+/// Which symbols lose their orderbook or subscription follows a fixed priority: the
+/// universe's priority symbols and the pinned reference symbols first, then 24h turnover,
+/// then name. Symbols with a running episode or on the watchlist are always kept.
+pub struct ResourceGovernor {
+    config: ResourceBudgetConfig,
+    shedding: Arc<LoadShedding>,
+    symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+    command_tx: mpsc::UnboundedSender<SubscriptionCommand>,
+    // Symbols with a running episode or on the watchlist
+    busy: Arc<PrioritySymbols>,
+    pinned: Vec<String>,
+    clock: SharedClock,
+    fine_candles: Option<FineCandles>,
+}
+
+impl ResourceGovernor {
+    pub fn new(
+        config: ResourceBudgetConfig,
+        shedding: Arc<LoadShedding>,
+        symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+        command_tx: mpsc::UnboundedSender<SubscriptionCommand>,
+        busy: Arc<PrioritySymbols>,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            config,
+            shedding,
+            symbol_data,
+            command_tx,
+            busy,
+            pinned: Vec::new(),
+            clock,
+            fine_candles: None,
+        }
+    }
+
+    /// Symbols ranked first, in this order, when choosing what to shed
+    pub fn with_pinned(mut self, pinned: Vec<String>) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    /// Fine candle buffer given to the `SymbolData` of symbols subscribed again
+    pub fn with_fine_candles(mut self, fine_candles: Option<FineCandles>) -> Self {
+        self.fine_candles = fine_candles;
+        self
+    }
+
+    pub async fn run(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.check_interval_secs.max(1)));
+        let mut previous_cpu: Option<(Instant, f64)> = None;
+        let (mut over, mut under) = (0u32, 0u32);
+
+        loop {
+            interval.tick().await;
+            let now = Instant::now();
+            let cpu_secs = process_cpu_secs();
+            let usage = ResourceUsage {
+                rss_bytes: process_rss_bytes(),
+                cpu_pct: match (previous_cpu, cpu_secs) {
+                    (Some((at, before)), Some(after)) => {
+                        let elapsed = now.duration_since(at).as_secs_f64();
+                        (elapsed > 0.0).then(|| (after - before) / elapsed * 100.0)
+                    }
+                    _ => None,
+                },
+            };
+            previous_cpu = cpu_secs.map(|secs| (now, secs));
+            self.shedding.state.lock().unwrap().usage = usage;
+
+            if self.over_budget(&usage) {
+                under = 0;
+                over += 1;
+                if over >= self.config.escalate_after_checks.max(1) {
+                    over = 0;
+                    self.escalate(&usage);
+                }
+            } else if self.below_release(&usage) {
+                over = 0;
+                under += 1;
+                if under >= self.config.release_after_checks.max(1) {
+                    under = 0;
+                    self.release(&usage);
+                }
+            } else {
+                over = 0;
+                under = 0;
+            }
+        }
+    }
+
+    fn over_budget(&self, usage: &ResourceUsage) -> bool {
+        let rss_over = self.config.max_rss_mb > 0 && usage.rss_bytes.is_some_and(|rss| rss > self.config.max_rss_mb * MB);
+        let cpu_over = self.config.max_cpu_pct > 0.0 && usage.cpu_pct.is_some_and(|cpu| cpu > self.config.max_cpu_pct);
+        rss_over || cpu_over
+    }
+
+    /// Whether every measured value is below `release_ratio` of its limit
+    fn below_release(&self, usage: &ResourceUsage) -> bool {
+        let ratio = self.config.release_ratio;
+        let rss_low = self.config.max_rss_mb == 0
+            || usage.rss_bytes.is_some_and(|rss| (rss as f64) < ratio * (self.config.max_rss_mb * MB) as f64);
+        let cpu_low = self.config.max_cpu_pct <= 0.0 || usage.cpu_pct.is_some_and(|cpu| cpu < ratio * self.config.max_cpu_pct);
+        rss_low && cpu_low
+    }
+
+    /// Applies the first configured mitigation that isn't active yet
+    fn escalate(&self, usage: &ResourceUsage) {
+        let active = self.shedding.active();
+        let Some(&mitigation) = self.config.mitigations.iter().find(|m| !active.contains(m)) else {
+            warn!("[ResourceBudget] Over budget ({}) with every mitigation already applied", usage);
+            return;
+        };
+        let detail = match mitigation {
+            Mitigation::DropDepth => self.drop_depth(),
+            Mitigation::ShortenHistory => {
+                set_history_retention_secs(Some(self.config.history_retention_secs as i64));
+                format!("{}s of price history", self.config.history_retention_secs)
+            }
+            Mitigation::ReduceSymbols => self.reduce_symbols(),
+        };
+        warn!("[ResourceBudget] Over budget ({}) - applying {}: {}", usage, mitigation.name(), detail);
+        self.shedding.state.lock().unwrap().active.push((mitigation, detail));
+    }
+
+    /// Lifts the mitigation applied last
+    fn release(&self, usage: &ResourceUsage) {
+        let Some((mitigation, _)) = self.shedding.state.lock().unwrap().active.pop() else {
+            return;
+        };
+        match mitigation {
+            Mitigation::DropDepth => self.restore_depth(),
+            Mitigation::ShortenHistory => set_history_retention_secs(None),
+            Mitigation::ReduceSymbols => self.restore_symbols(),
+        }
+        info!("[ResourceBudget] Back within budget ({}) - lifted {}", usage, mitigation.name());
+    }
+
+    /// Monitored symbols, highest priority first
+    fn ranked(&self) -> Vec<String> {
+        let mut others: Vec<(String, f64)> = self
+            .symbol_data
+            .iter()
+            .filter(|entry| !self.pinned.iter().any(|symbol| **symbol == **entry.key()))
+            .map(|entry| {
+                let turnover = entry.value().stats_24h.as_ref().map_or(0.0, |stats| stats.volume_usdt);
+                (entry.key().to_string(), turnover)
+            })
+            .collect();
+        others.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        self.pinned
+            .iter()
+            .filter(|symbol| self.symbol_data.contains_key(symbol.as_str()))
+            .cloned()
+            .chain(others.into_iter().map(|(symbol, _)| symbol))
+            .collect()
+    }
+
+    fn drop_depth(&self) -> String {
+        let dropped: Vec<String> = self
+            .ranked()
+            .into_iter()
+            .skip(self.config.depth_keep_symbols)
+            .filter(|symbol| !self.busy.contains(symbol))
+            .collect();
+        // A book that stops updating must not be evaluated any more
+        for symbol in &dropped {
+            if let Some(mut data) = self.symbol_data.get_mut(symbol.as_str()) {
+                data.orderbook = None;
+            }
+        }
+        let detail = format!("{} symbols without orderbook", dropped.len());
+        self.shedding.state.lock().unwrap().depthless.extend(dropped.iter().cloned());
+        if !dropped.is_empty() {
+            let _ = self.command_tx.send(SubscriptionCommand::DropDepth(dropped));
+        }
+        detail
+    }
+
+    fn restore_depth(&self) {
+        let mut restored: Vec<String> = self.shedding.state.lock().unwrap().depthless.drain().collect();
+        restored.sort();
+        if !restored.is_empty() {
+            let _ = self.command_tx.send(SubscriptionCommand::RestoreDepth(restored));
+        }
+    }
+
+    fn reduce_symbols(&self) -> String {
+        let ranked = self.ranked();
+        let keep = (ranked.len() as f64 * self.config.reduce_symbols_keep_pct / 100.0).ceil() as usize;
+        let shed: Vec<String> = ranked
+            .into_iter()
+            .skip(keep)
+            .filter(|symbol| !self.pinned.contains(symbol) && !self.busy.contains(symbol))
+            .collect();
+        for symbol in &shed {
+            self.symbol_data.remove(symbol.as_str());
+        }
+        let detail = format!("{} symbols unsubscribed", shed.len());
+        self.shedding.state.lock().unwrap().shed.extend(shed.iter().cloned());
+        if !shed.is_empty() {
+            let _ = self.command_tx.send(SubscriptionCommand::Unsubscribe(shed));
+        }
+        detail
+    }
+
+    fn restore_symbols(&self) {
+        let (restored, depthless): (Vec<String>, Vec<String>) = {
+            let mut state = self.shedding.state.lock().unwrap();
+            let restored: Vec<String> = state
+                .shed
+                .drain(..)
+                .filter(|symbol| !self.symbol_data.contains_key(symbol.as_str()))
+                .collect();
+            // Symbols that had lost their orderbook before being shed come back without it
+            let depthless = restored.iter().filter(|symbol| state.depthless.contains(*symbol)).cloned().collect();
+            (restored, depthless)
+        };
+        if restored.is_empty() {
+            return;
+        }
+        for symbol in &restored {
+            let data = SymbolData::new(symbol.as_str(), self.clock.clone()).with_fine_candles(self.fine_candles);
+            self.symbol_data.insert(data.symbol.clone(), data);
+        }
+        info!("[ResourceBudget] Subscribing {} shed symbols again", restored.len());
+        let _ = self.command_tx.send(SubscriptionCommand::Subscribe(restored));
+        if !depthless.is_empty() {
+            let _ = self.command_tx.send(SubscriptionCommand::DropDepth(depthless));
+        }
+    }
+}
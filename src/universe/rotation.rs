@@ -0,0 +1,203 @@
+use crate::api::{MexcRestClient, PrioritySymbols, SubscriptionCommand};
+use crate::models::{FineCandles, Symbol, SymbolData};
+use crate::universe::{DormantList, LoadShedding, SuspendedContracts, SymbolSelector};
+use crate::utils::{system_clock, EpisodeStore, SharedClock};
+use dashmap::DashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// Keeps the monitored set within `max_symbols`, periodically swapping
+/// low-priority symbols for higher-scoring candidates
+pub struct UniverseRotator {
+    selector: SymbolSelector,
+    rest: Arc<MexcRestClient>,
+    candidates: Vec<String>,
+    episode_store: Arc<EpisodeStore>,
+    symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+    command_tx: mpsc::UnboundedSender<SubscriptionCommand>,
+    pump_history_hours: u64,
+    rotation_interval_secs: u64,
+    clock: SharedClock,
+    fine_candles: Option<FineCandles>,
+    dormant: Option<Arc<DormantList>>,
+    suspended: Option<Arc<SuspendedContracts>>,
+    pinned: Vec<String>,
+    shedding: Option<Arc<LoadShedding>>,
+    busy: Option<Arc<PrioritySymbols>>,
+}
+
+impl UniverseRotator {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        selector: SymbolSelector,
+        rest: Arc<MexcRestClient>,
+        candidates: Vec<String>,
+        episode_store: Arc<EpisodeStore>,
+        symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+        command_tx: mpsc::UnboundedSender<SubscriptionCommand>,
+        pump_history_hours: u64,
+        rotation_interval_secs: u64,
+    ) -> Self {
+        Self {
+            selector,
+            rest,
+            candidates,
+            episode_store,
+            symbol_data,
+            command_tx,
+            pump_history_hours,
+            rotation_interval_secs,
+            clock: system_clock(),
+            fine_candles: None,
+            dormant: None,
+            suspended: None,
+            pinned: Vec::new(),
+            shedding: None,
+            busy: None,
+        }
+    }
+
+    /// Clock handed to the `SymbolData` of symbols rotated in and used to window pump history
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Fine candle buffer given to the `SymbolData` of symbols rotated in
+    pub fn with_fine_candles(mut self, fine_candles: Option<FineCandles>) -> Self {
+        self.fine_candles = fine_candles;
+        self
+    }
+
+    /// Archived contracts are left out of the selection until they revive
+    pub fn with_dormant(mut self, dormant: Arc<DormantList>) -> Self {
+        self.dormant = Some(dormant);
+        self
+    }
+
+    /// Contracts unsubscribed for not trading are left out of the selection until they resume
+    pub fn with_suspended(mut self, suspended: Arc<SuspendedContracts>) -> Self {
+        self.suspended = Some(suspended);
+        self
+    }
+
+    /// Symbols that stay monitored regardless of their score (e.g. market reference symbols)
+    pub fn with_pinned(mut self, pinned: Vec<String>) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    /// Rotation pauses while the resource budget has symbols unsubscribed, so it doesn't
+    /// fill their places again
+    pub fn with_load_shedding(mut self, shedding: Arc<LoadShedding>) -> Self {
+        self.shedding = Some(shedding);
+        self
+    }
+
+    /// Symbols with a running episode or chart recording aren't rotated out until it's over,
+    /// so their episodes end and their recordings complete normally
+    pub fn with_busy(mut self, busy: Arc<PrioritySymbols>) -> Self {
+        self.busy = Some(busy);
+        self
+    }
+
+    /// Scores candidates and returns the initial selection
+    pub async fn initial_selection(&self) -> Vec<String> {
+        let turnover = fetch_turnover(&self.rest).await;
+        let mut selected = self.selector.select(&self.active_candidates(), &turnover, &HashMap::new());
+        for symbol in &self.pinned {
+            if !selected.contains(symbol) {
+                selected.push(symbol.clone());
+            }
+        }
+        selected
+    }
+
+    fn active_candidates(&self) -> Vec<String> {
+        self.candidates
+            .iter()
+            .filter(|s| self.dormant.as_ref().is_none_or(|dormant| !dormant.contains(s)))
+            .filter(|s| self.suspended.as_ref().is_none_or(|suspended| !suspended.contains(s)))
+            .cloned()
+            .collect()
+    }
+
+    pub async fn run(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.rotation_interval_secs));
+        // The first tick completes immediately; the initial selection was already made
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            self.rotate().await;
+        }
+    }
+
+    async fn rotate(&self) {
+        if let Some(shed) = self.shedding.as_ref().map(|shedding| shedding.symbols_shed()).filter(|&shed| shed > 0) {
+            info!("[Universe] Rotation paused: {} symbols shed by the resource budget", shed);
+            return;
+        }
+        let turnover = fetch_turnover(&self.rest).await;
+        let since = self.clock.now() - chrono::Duration::hours(self.pump_history_hours as i64);
+        let episodes = self.episode_store.count_by_symbol(since);
+
+        let selected: HashSet<String> = self
+            .selector
+            .select(&self.active_candidates(), &turnover, &episodes)
+            .into_iter()
+            .chain(self.pinned.iter().cloned())
+            .collect();
+        let current: HashSet<String> = self.symbol_data.iter().map(|e| e.key().to_string()).collect();
+
+        let added: Vec<String> = selected.difference(&current).cloned().collect();
+        let (kept, removed): (Vec<String>, Vec<String>) = current
+            .difference(&selected)
+            .cloned()
+            .partition(|symbol| self.busy.as_ref().is_some_and(|busy| busy.contains(symbol)));
+        if !kept.is_empty() {
+            info!("[Universe] Rotation: keeping {:?} until their episodes and recordings are over", kept);
+        }
+
+        if added.is_empty() && removed.is_empty() {
+            info!("[Universe] Rotation: monitored set unchanged ({} symbols)", current.len());
+            return;
+        }
+
+        for symbol in &removed {
+            self.symbol_data.remove(symbol.as_str());
+        }
+        for symbol in &added {
+            let data = SymbolData::new(symbol.as_str(), self.clock.clone()).with_fine_candles(self.fine_candles);
+            self.symbol_data.insert(data.symbol.clone(), data);
+        }
+
+        info!(
+            "[Universe] Rotation: +{} -{} | Added: {:?} | Removed: {:?}",
+            added.len(),
+            removed.len(),
+            added,
+            removed
+        );
+
+        if !removed.is_empty() {
+            let _ = self.command_tx.send(SubscriptionCommand::Unsubscribe(removed));
+        }
+        if !added.is_empty() {
+            let _ = self.command_tx.send(SubscriptionCommand::Subscribe(added));
+        }
+    }
+}
+
+async fn fetch_turnover(rest: &MexcRestClient) -> HashMap<String, f64> {
+    match rest.get_tickers().await {
+        Ok(tickers) => tickers.into_iter().map(|t| (t.symbol, t.amount_24h)).collect(),
+        Err(e) => {
+            warn!("[Universe] Failed to fetch tickers, scoring without volume: {:?}", e);
+            HashMap::new()
+        }
+    }
+}
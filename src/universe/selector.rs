@@ -0,0 +1,95 @@
+use crate::config::UniverseConfig;
+use std::collections::{HashMap, HashSet};
+use tracing::debug;
+
+/// Priority score of a candidate symbol
+#[derive(Debug, Clone)]
+pub struct SymbolScore {
+    pub symbol: String,
+    pub score: f64,
+    pub turnover_24h: f64,
+    pub recent_episodes: usize,
+    pub manual: bool,
+}
+
+/// Picks the most promising subset of symbols when `max_symbols` limits capacity.
+///
+/// Manual priority symbols always come first; the rest are ranked by
+/// `volume_weight * log10(24h turnover) + pump_history_weight * recent episodes`.
+pub struct SymbolSelector {
+    config: UniverseConfig,
+}
+
+impl SymbolSelector {
+    pub fn new(config: UniverseConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn is_limited(&self) -> bool {
+        self.config.max_symbols > 0
+    }
+
+    pub fn rank(
+        &self,
+        candidates: &[String],
+        turnover_24h: &HashMap<String, f64>,
+        recent_episodes: &HashMap<String, usize>,
+    ) -> Vec<SymbolScore> {
+        let manual: HashSet<&String> = self.config.priority_symbols.iter().collect();
+
+        let mut scores: Vec<SymbolScore> = candidates
+            .iter()
+            .map(|symbol| {
+                let turnover = turnover_24h.get(symbol).copied().unwrap_or(0.0);
+                let episodes = recent_episodes.get(symbol).copied().unwrap_or(0);
+                let is_manual = manual.contains(symbol);
+
+                let score = if is_manual {
+                    f64::INFINITY
+                } else {
+                    self.config.volume_weight * (turnover + 1.0).log10()
+                        + self.config.pump_history_weight * episodes as f64
+                };
+
+                SymbolScore {
+                    symbol: symbol.clone(),
+                    score,
+                    turnover_24h: turnover,
+                    recent_episodes: episodes,
+                    manual: is_manual,
+                }
+            })
+            .collect();
+
+        scores.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.symbol.cmp(&b.symbol)));
+        scores
+    }
+
+    /// Returns the symbols to monitor, highest priority first
+    pub fn select(
+        &self,
+        candidates: &[String],
+        turnover_24h: &HashMap<String, f64>,
+        recent_episodes: &HashMap<String, usize>,
+    ) -> Vec<String> {
+        if !self.is_limited() {
+            return candidates.to_vec();
+        }
+
+        self.rank(candidates, turnover_24h, recent_episodes)
+            .into_iter()
+            .take(self.config.max_symbols)
+            .map(|s| {
+                debug!(
+                    "[Universe] {} | score {:.2} | 24h turnover {:.0} USDT | {} recent episodes{}",
+                    s.symbol,
+                    s.score,
+                    s.turnover_24h,
+                    s.recent_episodes,
+                    if s.manual { " | manual priority" } else { "" }
+                );
+                s.symbol
+            })
+            .collect()
+    }
+}
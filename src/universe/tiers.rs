@@ -0,0 +1,99 @@
+use crate::api::MexcRestClient;
+use crate::config::TiersConfig;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Symbols classified by 24h turnover, so alerts and execution can be routed per tier
+/// (e.g. tier1 auto-traded, tier3 only logged) and strategies can use per-tier thresholds.
+///
+/// Tiers are numbered from 1 (most liquid); symbols without turnover data yet are put in
+/// the lowest tier until the next refresh.
+pub struct VolumeTiers {
+    config: TiersConfig,
+    rest: Arc<MexcRestClient>,
+    tiers: RwLock<HashMap<String, usize>>,
+}
+
+impl VolumeTiers {
+    pub fn new(config: TiersConfig, rest: Arc<MexcRestClient>) -> Self {
+        Self {
+            config,
+            rest,
+            tiers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Name used in `routes`, `execute` and `thresholds`, e.g. `tier2`
+    pub fn tier_name(&self, tier: usize) -> String {
+        self.config.tier_name(tier)
+    }
+
+    pub fn lowest_tier(&self) -> usize {
+        self.config.tier_count()
+    }
+
+    /// Tier for a 24h turnover in USDT
+    pub fn classify(&self, volume_usdt: f64) -> usize {
+        self.config
+            .min_volume_usdt
+            .iter()
+            .position(|min| volume_usdt >= *min)
+            .map(|index| index + 1)
+            .unwrap_or_else(|| self.lowest_tier())
+    }
+
+    pub fn tier_of(&self, symbol: &str) -> usize {
+        self.tiers.read().unwrap().get(symbol).copied().unwrap_or_else(|| self.lowest_tier())
+    }
+
+    pub fn tier_name_of(&self, symbol: &str) -> String {
+        self.tier_name(self.tier_of(symbol))
+    }
+
+    /// Whether signals on `symbol` may be executed under the `execute` list
+    #[cfg(feature = "execution")]
+    pub fn may_execute(&self, symbol: &str) -> bool {
+        self.config.execute.is_empty() || self.config.execute.contains(&self.tier_name_of(symbol))
+    }
+
+    pub async fn run(self: Arc<Self>) {
+        let mut refresh = tokio::time::interval(Duration::from_secs(self.config.refresh_interval_mins.max(1) * 60));
+        loop {
+            refresh.tick().await;
+            self.refresh().await;
+        }
+    }
+
+    /// Re-classifies every contract from the REST ticker; keeps the old tiers if it fails
+    pub async fn refresh(&self) {
+        let tickers = match self.rest.get_tickers().await {
+            Ok(tickers) => tickers,
+            Err(e) => {
+                warn!("[Tiers] Failed to fetch tickers, keeping the current tiers: {:?}", e);
+                return;
+            }
+        };
+
+        let tiers: HashMap<String, usize> =
+            tickers.into_iter().map(|t| (t.symbol, self.classify(t.amount_24h))).collect();
+
+        let mut counts = vec![0usize; self.lowest_tier()];
+        for tier in tiers.values() {
+            counts[tier - 1] += 1;
+        }
+        let changed = {
+            let previous = self.tiers.read().unwrap();
+            tiers.iter().filter(|(symbol, tier)| previous.get(*symbol).is_some_and(|p| p != *tier)).count()
+        };
+        *self.tiers.write().unwrap() = tiers;
+
+        let summary: Vec<String> = counts
+            .iter()
+            .enumerate()
+            .map(|(index, count)| format!("{} {}", self.tier_name(index + 1), count))
+            .collect();
+        info!("[Tiers] Refreshed: {} | {} symbols changed tier", summary.join(", "), changed);
+    }
+}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+/// Source of "now" for time-dependent state (episodes, price history, candles),
+/// so replays and backtests can run on simulated time instead of the wall clock.
+pub trait Clock: Send + Sync + fmt::Debug {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The wall clock
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+pub fn system_clock() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+/// Manually driven clock: only moves when `set` or `advance` is called
+#[derive(Debug)]
+pub struct SimulatedClock {
+    now_ms: AtomicI64,
+}
+
+impl SimulatedClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            now_ms: AtomicI64::new(start.timestamp_millis()),
+        }
+    }
+
+    /// Moves the clock to `time`; simulated time never runs backwards
+    pub fn set(&self, time: DateTime<Utc>) {
+        self.now_ms.fetch_max(time.timestamp_millis(), Ordering::Relaxed);
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(self.now_ms.load(Ordering::Relaxed)).unwrap_or_default()
+    }
+}
+
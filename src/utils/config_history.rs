@@ -0,0 +1,134 @@
+use crate::config::Config;
+use crate::utils::{format_version, newer_format_error, StoredFormat};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use tracing::warn;
+
+pub const CONFIG_HISTORY_FILE: &str = "strategy_config_history.jsonl";
+
+/// Version of the lines of the config history; lines without `format_version` are
+/// version 0, which had the same fields
+const CONFIG_HISTORY_FORMAT: u32 = 1;
+
+/// One version of the strategy parameters, a line of `strategy_config_history.jsonl`
+#[derive(Debug, Serialize, Deserialize)]
+struct ConfigSnapshot {
+    #[serde(default)]
+    format_version: u32,
+    version: u32,
+    recorded_at: DateTime<Utc>,
+    /// SHA-256 of `strategies` as written, to recognize unchanged parameters
+    hash: String,
+    strategies: serde_json::Value,
+}
+
+/// The strategy parameters version episodes of this run are tagged with
+#[derive(Debug, Clone)]
+pub struct StrategyConfigVersion {
+    pub version: u32,
+    /// False when the parameters match the newest recorded version
+    pub is_new: bool,
+}
+
+/// Strategy sections with their defaults filled in, top-level and per pipeline
+fn strategy_parameters(config: &Config) -> serde_json::Value {
+    let pipelines: serde_json::Map<String, serde_json::Value> = config
+        .pipelines
+        .iter()
+        .map(|(name, pipeline)| {
+            let sections = json!({
+                "strategy1": pipeline.strategy1,
+                "strategy2": pipeline.strategy2,
+                "strategy3": pipeline.strategy3,
+                "strategy4": pipeline.strategy4,
+                "strategy5": pipeline.strategy5,
+            });
+            (name.clone(), sections)
+        })
+        .collect();
+
+    json!({
+        "strategy1": config.strategy1,
+        "strategy2": config.strategy2,
+        "strategy3": config.strategy3,
+        "strategy4": config.strategy4,
+        "strategy5": config.strategy5,
+        "pipelines": pipelines,
+    })
+}
+
+/// Appends the current strategy parameters to `<log_dir>/strategy_config_history.jsonl`
+/// as a new version, unless they equal the newest version there, which is reused.
+pub fn record_strategy_config(config: &Config, log_dir: &str) -> Result<StrategyConfigVersion> {
+    let strategies = strategy_parameters(config);
+    let hash = hex::encode(Sha256::digest(strategies.to_string().as_bytes()));
+
+    let path = Path::new(log_dir).join(CONFIG_HISTORY_FILE);
+    let latest = latest_snapshot(&path)?;
+    if let Some(ref latest) = latest {
+        if latest.hash == hash {
+            return Ok(StrategyConfigVersion {
+                version: latest.version,
+                is_new: false,
+            });
+        }
+    }
+
+    let snapshot = ConfigSnapshot {
+        format_version: CONFIG_HISTORY_FORMAT,
+        version: latest.map_or(1, |latest| latest.version + 1),
+        recorded_at: Utc::now(),
+        hash,
+        strategies,
+    };
+    fs::create_dir_all(log_dir)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("cannot open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(&snapshot)?)?;
+
+    Ok(StrategyConfigVersion {
+        version: snapshot.version,
+        is_new: true,
+    })
+}
+
+fn latest_snapshot(path: &Path) -> Result<Option<ConfigSnapshot>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(anyhow::anyhow!("cannot read {}: {}", path.display(), e)),
+    };
+
+    let mut latest: Option<ConfigSnapshot> = None;
+    for (index, line) in contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        let value = match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("{}:{} skipped: {}", path.display(), index + 1, e);
+                continue;
+            }
+        };
+        // Appending after a line this build can't interpret could reuse its version number
+        if let StoredFormat::Newer(found) = StoredFormat::of(format_version(&value), CONFIG_HISTORY_FORMAT) {
+            return Err(newer_format_error(path, found, CONFIG_HISTORY_FORMAT));
+        }
+        match serde_json::from_value::<ConfigSnapshot>(value) {
+            Ok(snapshot) => {
+                if latest.as_ref().is_none_or(|latest| snapshot.version > latest.version) {
+                    latest = Some(snapshot);
+                }
+            }
+            Err(e) => warn!("{}:{} skipped: {}", path.display(), index + 1, e),
+        }
+    }
+    Ok(latest)
+}
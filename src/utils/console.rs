@@ -0,0 +1,147 @@
+use crate::config::{ConsoleColor, ConsoleConfig};
+use std::fmt;
+use std::io::IsTerminal;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::format::{Format, Writer};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const BELL: &str = "\x07";
+
+/// How much attention a log line deserves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tier {
+    /// A strategy fired
+    Detection,
+    /// A strategy's episode ended or an alert was delivered
+    Outcome,
+    Error,
+    Warning,
+    /// Debug output and per-strategy bookkeeping
+    Diagnostic,
+    Normal,
+}
+
+/// Console event format that makes detections stand out from diagnostic noise:
+/// colored per strategy and severity, with an optional bell on Strategy5.
+pub struct ConsoleFormat {
+    inner: Format,
+    color: bool,
+    bell_on_strategy5: bool,
+    dim_diagnostics: bool,
+}
+
+impl ConsoleFormat {
+    pub fn new(config: &ConsoleConfig) -> Self {
+        let terminal = std::io::stdout().is_terminal();
+        let color = match config.color {
+            ConsoleColor::Auto => terminal,
+            ConsoleColor::Always => true,
+            ConsoleColor::Never => false,
+        };
+
+        Self {
+            inner: Format::default().with_ansi(false),
+            color,
+            // A bell in a redirected log file is only noise
+            bell_on_strategy5: config.bell_on_strategy5 && terminal,
+            dim_diagnostics: config.dim_diagnostics,
+        }
+    }
+
+    fn style(&self, tier: Tier, strategy: Option<u8>) -> String {
+        let strategy_color = match strategy {
+            Some(1) => "\x1b[36m", // cyan
+            Some(2) => "\x1b[34m", // blue
+            Some(3) => "\x1b[35m", // magenta
+            Some(4) => "\x1b[32m", // green
+            Some(5) => "\x1b[91m", // bright red
+            _ => "",
+        };
+        match tier {
+            Tier::Detection => format!("{}{}", BOLD, strategy_color),
+            Tier::Outcome if strategy.is_some() => strategy_color.to_string(),
+            Tier::Outcome => BOLD.to_string(),
+            Tier::Error => format!("{}{}", BOLD, RED),
+            Tier::Warning => YELLOW.to_string(),
+            Tier::Diagnostic if self.dim_diagnostics => DIM.to_string(),
+            Tier::Diagnostic | Tier::Normal => String::new(),
+        }
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for ConsoleFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        if !self.color && !self.bell_on_strategy5 {
+            return FormatEvent::<S, N>::format_event(&self.inner, ctx, writer, event);
+        }
+
+        let mut line = String::new();
+        FormatEvent::<S, N>::format_event(&self.inner, ctx, Writer::new(&mut line), event)?;
+
+        let mut message = MessageVisitor::default();
+        event.record(&mut message);
+        let (tier, strategy) = classify(event.metadata().level(), &message.0);
+
+        if self.bell_on_strategy5 && tier == Tier::Detection && strategy == Some(5) {
+            writer.write_str(BELL)?;
+        }
+
+        let style = if self.color { self.style(tier, strategy) } else { String::new() };
+        if style.is_empty() {
+            writer.write_str(&line)
+        } else {
+            writeln!(writer, "{}{}{}", style, line.trim_end_matches('\n'), RESET)
+        }
+    }
+}
+
+fn classify(level: &Level, message: &str) -> (Tier, Option<u8>) {
+    let strategy = message
+        .strip_prefix("[Strategy")
+        .and_then(|rest| rest.chars().next())
+        .and_then(|c| c.to_digit(10))
+        .map(|d| d as u8);
+
+    let tier = if *level == Level::ERROR {
+        Tier::Error
+    } else if *level == Level::WARN {
+        Tier::Warning
+    } else if message.contains('🚨') || message.contains('🔥') {
+        Tier::Detection
+    } else if message.contains('✅') || message.starts_with("[Alert]") {
+        Tier::Outcome
+    } else if *level > Level::INFO || strategy.is_some() {
+        Tier::Diagnostic
+    } else {
+        Tier::Normal
+    };
+    (tier, strategy)
+}
+
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{:?}", value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0 = value.to_string();
+        }
+    }
+}
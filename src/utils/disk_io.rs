@@ -0,0 +1,117 @@
+use crate::config::DiskIoConfig;
+use anyhow::Result;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tracing::warn;
+
+type DiskJobFn = Box<dyn FnOnce() -> Result<()> + Send>;
+
+/// A queued write; `name` identifies it in failure and slow-write warnings
+struct DiskJob {
+    name: String,
+    run: DiskJobFn,
+}
+
+/// Queue and write statistics since startup
+#[derive(Debug, Clone, Default)]
+pub struct DiskIoStats {
+    pub queued: usize,
+    pub max_queued: u64,
+    pub completed: u64,
+    pub failed: u64,
+    pub slow: u64,
+    pub max_duration: Duration,
+}
+
+#[derive(Default)]
+struct Counters {
+    max_queued: AtomicU64,
+    completed: AtomicU64,
+    failed: AtomicU64,
+    slow: AtomicU64,
+    max_duration_us: AtomicU64,
+}
+
+/// Runs file writes on a dedicated thread fed by a bounded queue, so a stalled disk
+/// delays the files instead of the runtime threads handling market data.
+///
+/// Jobs run one at a time in submission order, which keeps appends to a file in order.
+pub struct DiskWriter {
+    tx: mpsc::Sender<DiskJob>,
+    counters: Arc<Counters>,
+}
+
+impl DiskWriter {
+    pub fn start(config: DiskIoConfig) -> Result<Arc<Self>> {
+        let (tx, mut rx) = mpsc::channel::<DiskJob>(config.queue_capacity.max(1));
+        let counters = Arc::new(Counters::default());
+        let worker_counters = counters.clone();
+        let slow_after = Duration::from_millis(config.slow_write_ms);
+
+        std::thread::Builder::new().name("disk-io".to_string()).spawn(move || {
+            while let Some(job) = rx.blocking_recv() {
+                let started = Instant::now();
+                let result = (job.run)();
+                let elapsed = started.elapsed();
+
+                worker_counters.completed.fetch_add(1, Ordering::Relaxed);
+                worker_counters.max_duration_us.fetch_max(elapsed.as_micros() as u64, Ordering::Relaxed);
+                if let Err(e) = result {
+                    worker_counters.failed.fetch_add(1, Ordering::Relaxed);
+                    warn!("[DiskIo] {} failed: {:?}", job.name, e);
+                }
+                if elapsed > slow_after {
+                    worker_counters.slow.fetch_add(1, Ordering::Relaxed);
+                    warn!("[DiskIo] {} took {}ms", job.name, elapsed.as_millis());
+                }
+            }
+        })?;
+
+        Ok(Arc::new(Self { tx, counters }))
+    }
+
+    /// Queues a write, waiting for queue space if needed; never waits for the write itself
+    pub async fn write(&self, name: impl Into<String>, job: impl FnOnce() -> Result<()> + Send + 'static) {
+        let job = DiskJob {
+            name: name.into(),
+            run: Box::new(job),
+        };
+        if self.tx.send(job).await.is_err() {
+            warn!("[DiskIo] Writer thread is gone, write dropped");
+        }
+        self.record_depth();
+    }
+
+    /// Waits until every write queued before this call has run
+    pub async fn flush(&self) {
+        let (done_tx, done_rx) = oneshot::channel();
+        self.write("flush", move || {
+            let _ = done_tx.send(());
+            Ok(())
+        })
+        .await;
+        let _ = done_rx.await;
+    }
+
+    pub fn stats(&self) -> DiskIoStats {
+        DiskIoStats {
+            queued: self.queued(),
+            max_queued: self.counters.max_queued.load(Ordering::Relaxed),
+            completed: self.counters.completed.load(Ordering::Relaxed),
+            failed: self.counters.failed.load(Ordering::Relaxed),
+            slow: self.counters.slow.load(Ordering::Relaxed),
+            max_duration: Duration::from_micros(self.counters.max_duration_us.load(Ordering::Relaxed)),
+        }
+    }
+
+    fn queued(&self) -> usize {
+        self.tx.max_capacity() - self.tx.capacity()
+    }
+
+    fn record_depth(&self) {
+        self.counters.max_queued.fetch_max(self.queued() as u64, Ordering::Relaxed);
+    }
+}
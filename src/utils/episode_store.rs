@@ -0,0 +1,102 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// A completed episode as reported by one of the strategies
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+pub struct EpisodeRecord {
+    pub id: String,
+    pub strategy: String,
+    pub symbol: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub peak_ratio: f64,
+    pub peak_last_price: f64,
+    pub peak_mark_price: f64,
+    /// Funding rate when the episode started, if funding ingestion is enabled
+    pub funding_rate_at_trigger: Option<f64>,
+    /// Change of the funding rate over the follow-up window, filled in once it has elapsed
+    pub funding_change: Option<f64>,
+    /// Strategy parameters version (`strategy_config_history.jsonl`) the episode fired under
+    pub config_version: Option<u32>,
+}
+
+/// Bounded in-memory history of recently completed episodes, shared by all strategies
+pub struct EpisodeStore {
+    capacity: usize,
+    episodes: Mutex<VecDeque<EpisodeRecord>>,
+    // Start times per symbol over `symbol_window`, kept apart from the capped feed above so
+    // a busy day can't evict the history universe scoring relies on
+    symbol_window: Duration,
+    symbol_starts: Mutex<HashMap<String, VecDeque<DateTime<Utc>>>>,
+}
+
+impl EpisodeStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            episodes: Mutex::new(VecDeque::with_capacity(capacity)),
+            symbol_window: Duration::zero(),
+            symbol_starts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Keeps every symbol's episode start times over `window` for `count_by_symbol`,
+    /// however many episodes the feed capacity lets through
+    pub fn with_symbol_history(mut self, window: Duration) -> Self {
+        self.symbol_window = window;
+        self
+    }
+
+    pub fn push(&self, record: EpisodeRecord) {
+        if self.symbol_window > Duration::zero() {
+            let cutoff = record.end_time - self.symbol_window;
+            let mut symbol_starts = self.symbol_starts.lock().unwrap();
+            let starts = symbol_starts.entry(record.symbol.clone()).or_default();
+            starts.push_back(record.start_time);
+            // Episodes are pushed as they end, so start times aren't strictly ordered
+            starts.retain(|&start| start >= cutoff);
+        }
+
+        let mut episodes = self.episodes.lock().unwrap();
+        episodes.push_back(record);
+
+        while episodes.len() > self.capacity {
+            episodes.pop_front();
+        }
+    }
+
+    pub fn set_funding_change(&self, episode_id: &str, change: f64) {
+        let mut episodes = self.episodes.lock().unwrap();
+        if let Some(episode) = episodes.iter_mut().find(|e| e.id == episode_id) {
+            episode.funding_change = Some(change);
+        }
+    }
+
+    /// Number of episodes per symbol that started at or after `since`, as far back as the
+    /// `with_symbol_history` window reaches
+    pub fn count_by_symbol(&self, since: DateTime<Utc>) -> HashMap<String, usize> {
+        let mut symbol_starts = self.symbol_starts.lock().unwrap();
+        symbol_starts.retain(|_, starts| {
+            starts.retain(|&start| start >= since);
+            !starts.is_empty()
+        });
+        symbol_starts
+            .iter()
+            .map(|(symbol, starts)| (symbol.clone(), starts.len()))
+            .collect()
+    }
+
+    /// Removes and returns every stored episode, oldest first
+    pub fn drain(&self) -> Vec<EpisodeRecord> {
+        self.episodes.lock().unwrap().drain(..).collect()
+    }
+
+    /// Returns up to `limit` episodes, newest first
+    #[cfg(feature = "dashboard")]
+    pub fn recent(&self, limit: usize) -> Vec<EpisodeRecord> {
+        let episodes = self.episodes.lock().unwrap();
+        episodes.iter().rev().take(limit).cloned().collect()
+    }
+}
@@ -0,0 +1,52 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::warn;
+
+/// Periodically records that the process is alive, so the next start can tell
+/// how long the detector was offline.
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    path: PathBuf,
+    interval: Duration,
+}
+
+impl Heartbeat {
+    pub fn new(path: &str, interval_secs: u64) -> Self {
+        Self {
+            path: PathBuf::from(path),
+            interval: Duration::from_secs(interval_secs),
+        }
+    }
+
+    /// When the previous run was last known to be alive, if it ever ran
+    pub fn last_seen(&self) -> Option<DateTime<Utc>> {
+        let contents = fs::read_to_string(&self.path).ok()?;
+        DateTime::parse_from_rfc3339(contents.trim())
+            .map(|t| t.with_timezone(&Utc))
+            .ok()
+    }
+
+    pub fn beat(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write-then-rename so a crash mid-write never leaves a truncated timestamp
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, Utc::now().to_rfc3339())?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.beat() {
+                warn!("[Heartbeat] Failed to write {}: {:?}", self.path.display(), e);
+            }
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use crate::config::IssueBundleConfig;
+use crate::utils::DiskWriter;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tracing::field::{Field, Visit};
+use tracing::{info, warn, Event, Subscriber};
+use tracing_subscriber::layer::{Context as LayerContext, Layer};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+const REDACTED: &str = "<redacted>";
+
+// Config keys and JSON fields whose values never leave the machine
+const SECRET_KEY_PARTS: [&str; 6] = ["token", "secret", "password", "api_key", "apikey", "signature"];
+
+/// Keeps the last application log lines in memory for issue bundles
+#[derive(Clone)]
+pub struct RecentLogs {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    capacity: usize,
+}
+
+impl RecentLogs {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    fn snapshot(&self) -> Vec<String> {
+        self.lines.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for RecentLogs {
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut fields = FieldsVisitor::default();
+        event.record(&mut fields);
+        let metadata = event.metadata();
+        let line = format!(
+            "{} {:>5} {}: {}",
+            Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            metadata.level(),
+            metadata.target(),
+            fields.0
+        );
+
+        let mut lines = self.lines.lock().unwrap();
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+}
+
+#[derive(Default)]
+struct FieldsVisitor(String);
+
+impl Visit for FieldsVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        if field.name() == "message" {
+            let _ = write!(self.0, "{:?}", value);
+        } else {
+            let _ = write!(self.0, "{}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// A message from the exchange that could not be handled
+#[derive(Debug, Clone, Serialize)]
+struct IssueSample {
+    at: DateTime<Utc>,
+    /// `parse_error` or `unknown_message`
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    payload: String,
+}
+
+#[derive(Default)]
+struct CollectorState {
+    samples: VecDeque<IssueSample>,
+    counts: BTreeMap<&'static str, u64>,
+    // When the recent samples arrived, for the automatic trigger
+    recent: VecDeque<Instant>,
+    last_auto_bundle: Option<Instant>,
+}
+
+/// Collects messages that failed to parse or had an unexpected format and writes them,
+/// with a redacted config snapshot, version information and the recent application log,
+/// into a zip file to attach to bug reports.
+///
+/// Bundles are written on request (`mexc-sniper bundle`) or automatically when bad messages
+/// arrive faster than `auto_errors_per_min`.
+pub struct IssueCollector {
+    config: IssueBundleConfig,
+    config_path: PathBuf,
+    environment: String,
+    started: DateTime<Utc>,
+    logs: RecentLogs,
+    disk: Arc<DiskWriter>,
+    state: Mutex<CollectorState>,
+}
+
+impl IssueCollector {
+    pub fn new(
+        config: IssueBundleConfig,
+        config_path: &str,
+        environment: &str,
+        logs: RecentLogs,
+        disk: Arc<DiskWriter>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            config_path: PathBuf::from(config_path),
+            environment: environment.to_string(),
+            started: Utc::now(),
+            logs,
+            disk,
+            state: Mutex::new(CollectorState::default()),
+        })
+    }
+
+    /// Keeps a sample of a bad message; may start an automatic bundle
+    pub fn record(self: &Arc<Self>, kind: &'static str, payload: &str, error: Option<String>) {
+        let now = Instant::now();
+        let auto_bundle = {
+            let mut state = self.state.lock().unwrap();
+            *state.counts.entry(kind).or_default() += 1;
+            if state.samples.len() >= self.config.max_samples.max(1) {
+                state.samples.pop_front();
+            }
+            state.samples.push_back(IssueSample {
+                at: Utc::now(),
+                kind,
+                error,
+                payload: redact_payload(payload),
+            });
+
+            let threshold = self.config.auto_errors_per_min;
+            state.recent.push_back(now);
+            while state.recent.front().is_some_and(|at| now.duration_since(*at) > Duration::from_secs(60)) {
+                state.recent.pop_front();
+            }
+            let interval = Duration::from_secs(self.config.auto_min_interval_mins * 60);
+            let due = state.last_auto_bundle.is_none_or(|at| now.duration_since(at) >= interval);
+            if threshold > 0 && state.recent.len() >= threshold && due {
+                state.last_auto_bundle = Some(now);
+                Some(state.recent.len())
+            } else {
+                None
+            }
+        };
+
+        if let Some(errors) = auto_bundle {
+            warn!("[IssueBundle] {} bad messages in the last minute - writing an issue bundle", errors);
+            let collector = self.clone();
+            tokio::spawn(async move {
+                let reason = format!("automatic: {} bad messages within a minute", errors);
+                if let Err(e) = collector.create_bundle(&reason).await {
+                    warn!("[IssueBundle] Failed to write issue bundle: {:#}", e);
+                }
+            });
+        }
+    }
+
+    /// Writes a bundle and returns its path
+    pub async fn create_bundle(&self, reason: &str) -> Result<PathBuf> {
+        let (samples, counts) = {
+            let state = self.state.lock().unwrap();
+            (state.samples.iter().cloned().collect::<Vec<_>>(), state.counts.clone())
+        };
+        let mut summary = format!(
+            "mexc-sniper {}\nos: {} {}\nenvironment: {}\nrunning since: {}\ncreated: {}\nreason: {}\n",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            self.environment,
+            self.started.to_rfc3339(),
+            Utc::now().to_rfc3339(),
+            reason
+        );
+        for (kind, count) in &counts {
+            let _ = writeln!(summary, "{}: {}", kind, count);
+        }
+        let config_snapshot = match fs::read_to_string(&self.config_path) {
+            Ok(text) => redact_config(&text),
+            Err(e) => format!("# {} could not be read: {}\n", self.config_path.display(), e),
+        };
+        let logs = self.logs.snapshot();
+        let (sample_count, log_count) = (samples.len(), logs.len());
+
+        let path = PathBuf::from(&self.config.dir).join(format!("issue_{}.zip", Utc::now().format("%Y%m%d_%H%M%S_%3f")));
+        let job_path = path.clone();
+        let (done_tx, done_rx) = oneshot::channel();
+        self.disk
+            .write(format!("issue bundle {}", path.display()), move || {
+                let result = write_zip(&job_path, &summary, &config_snapshot, &samples, &logs);
+                let failed = result.as_ref().err().map(|e| anyhow!("{:#}", e));
+                let _ = done_tx.send(result);
+                failed.map_or(Ok(()), Err)
+            })
+            .await;
+        done_rx.await.map_err(|_| anyhow!("disk writer stopped"))??;
+
+        info!("[IssueBundle] Wrote {} ({} samples, {} log lines)", path.display(), sample_count, log_count);
+        Ok(path)
+    }
+}
+
+fn write_zip(path: &Path, summary: &str, config: &str, samples: &[IssueSample], logs: &[String]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let file = fs::File::create(path).with_context(|| format!("creating {}", path.display()))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+
+    zip.start_file("summary.txt", options)?;
+    zip.write_all(summary.as_bytes())?;
+
+    zip.start_file("config.toml", options)?;
+    zip.write_all(config.as_bytes())?;
+
+    zip.start_file("samples.jsonl", options)?;
+    for sample in samples {
+        serde_json::to_writer(&mut zip, sample)?;
+        zip.write_all(b"\n")?;
+    }
+
+    zip.start_file("recent.log", options)?;
+    for line in logs {
+        zip.write_all(line.as_bytes())?;
+        zip.write_all(b"\n")?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SECRET_KEY_PARTS.iter().any(|part| key.contains(part))
+}
+
+/// The config file with the values of secret-looking keys replaced; unparsable files are
+/// left out rather than risk leaking something
+fn redact_config(text: &str) -> String {
+    fn redact(value: &mut toml::Value) {
+        if let toml::Value::Table(table) = value {
+            for (key, value) in table.iter_mut() {
+                if is_secret_key(key) && !value.is_table() {
+                    *value = toml::Value::String(REDACTED.to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+    }
+
+    match text.parse::<toml::Value>() {
+        Ok(mut value) => {
+            redact(&mut value);
+            toml::to_string_pretty(&value).unwrap_or_default()
+        }
+        Err(e) => format!("# config.toml left out: does not parse ({})\n", e.message()),
+    }
+}
+
+/// The payload with secret-looking JSON fields replaced; other text is kept as is
+fn redact_payload(payload: &str) -> String {
+    fn redact(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if is_secret_key(key) {
+                        *value = serde_json::Value::String(REDACTED.to_string());
+                    } else {
+                        redact(value);
+                    }
+                }
+            }
+            serde_json::Value::Array(values) => values.iter_mut().for_each(redact),
+            _ => {}
+        }
+    }
+
+    match serde_json::from_str::<serde_json::Value>(payload) {
+        Ok(mut value) => {
+            redact(&mut value);
+            value.to_string()
+        }
+        Err(_) => payload.to_string(),
+    }
+}
@@ -1,19 +1,166 @@
+use crate::alerts::Alert;
+use crate::config::{EpisodeLogConfig, EpisodeLogFormat, FlushPolicy};
+use crate::detection::{format_moves, Comparison, MarketContext};
+use crate::funding::{format_rate, FundingEpisodeExporter};
+use crate::models::ProcessedOrderbook;
+use crate::utils::{DiskWriter, EpisodeRecord, EpisodeStore};
 use chrono::{DateTime, Utc};
-use std::fs::{self, OpenOptions};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
 use std::io::Write;
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{interval, Duration, MissedTickBehavior};
+use tracing::warn;
+
+// Most lines taken off the queue at once in immediate mode
+const MAX_BATCH_LINES: usize = 256;
+
+enum LogMessage {
+    Line {
+        file_path: PathBuf,
+        file: Arc<Mutex<File>>,
+        line: String,
+    },
+    Flush(oneshot::Sender<()>),
+}
+
+/// Lines waiting to be written to one file
+struct PendingFile {
+    file: Arc<Mutex<File>>,
+    text: String,
+}
+
+/// Buffers the episode log lines of every strategy in a task of its own and hands them to
+/// the disk thread according to the flush policy, so logging an episode is a channel send
+/// and bursts of simultaneous episodes never wait on each other.
+pub struct EpisodeLogWriter {
+    tx: mpsc::UnboundedSender<LogMessage>,
+    format: EpisodeLogFormat,
+}
+
+impl EpisodeLogWriter {
+    pub fn start(config: EpisodeLogConfig, disk: Arc<DiskWriter>) -> Arc<Self> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let format = config.format;
+        tokio::spawn(run_writer(config, disk, rx));
+        Arc::new(Self { tx, format })
+    }
+
+    /// Format of the lines the loggers writing through it produce
+    pub fn format(&self) -> EpisodeLogFormat {
+        self.format
+    }
+
+    fn append(&self, file_path: &Path, file: &Arc<Mutex<File>>, line: String) {
+        let message = LogMessage::Line {
+            file_path: file_path.to_path_buf(),
+            file: file.clone(),
+            line,
+        };
+        if self.tx.send(message).is_err() {
+            warn!("[EpisodeLog] Writer task is gone, line for {} dropped", file_path.display());
+        }
+    }
+
+    /// Writes out everything buffered and waits until it has reached the files
+    pub async fn flush(&self) {
+        let (done_tx, done_rx) = oneshot::channel();
+        if self.tx.send(LogMessage::Flush(done_tx)).is_ok() {
+            let _ = done_rx.await;
+        }
+    }
+}
+
+async fn run_writer(config: EpisodeLogConfig, disk: Arc<DiskWriter>, mut rx: mpsc::UnboundedReceiver<LogMessage>) {
+    let mut pending: HashMap<PathBuf, PendingFile> = HashMap::new();
+    let mut ticker = interval(Duration::from_millis(config.flush_interval_ms.max(1)));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut messages = Vec::with_capacity(MAX_BATCH_LINES);
+
+    loop {
+        tokio::select! {
+            received = rx.recv_many(&mut messages, MAX_BATCH_LINES) => {
+                if received == 0 {
+                    // Every logger is gone
+                    write_pending(&mut pending, &disk, config.fsync).await;
+                    return;
+                }
+                for message in messages.drain(..) {
+                    match message {
+                        LogMessage::Line { file_path, file, line } => {
+                            pending
+                                .entry(file_path)
+                                .or_insert_with(|| PendingFile { file, text: String::new() })
+                                .text
+                                .push_str(&line);
+                        }
+                        LogMessage::Flush(done) => {
+                            write_pending(&mut pending, &disk, config.fsync).await;
+                            disk.flush().await;
+                            let _ = done.send(());
+                        }
+                    }
+                }
+                if config.flush == FlushPolicy::Immediate {
+                    write_pending(&mut pending, &disk, config.fsync).await;
+                }
+            }
+            _ = ticker.tick(), if config.flush == FlushPolicy::Interval => {
+                write_pending(&mut pending, &disk, config.fsync).await;
+            }
+        }
+    }
+}
+
+/// Queues one write per file holding all of its buffered lines
+async fn write_pending(pending: &mut HashMap<PathBuf, PendingFile>, disk: &DiskWriter, fsync: bool) {
+    for (file_path, PendingFile { file, text }) in pending.drain() {
+        let name = format!("episode log {}", file_path.display());
+        disk.write(name, move || {
+            let mut file = file.lock().unwrap();
+            file.write_all(text.as_bytes())?;
+            file.flush()?;
+            if fsync {
+                file.sync_data()?;
+            }
+            Ok(())
+        })
+        .await;
+    }
+}
 
 pub struct EpisodeLogger {
     file_path: PathBuf,
-    file: Mutex<std::fs::File>,
+    file: Arc<Mutex<File>>,
+    writer: Arc<EpisodeLogWriter>,
+    strategy_name: String,
+    store: Arc<EpisodeStore>,
+    funding: Option<Arc<FundingEpisodeExporter>>,
+    market_context: Option<Arc<MarketContext>>,
+    config_version: Option<u32>,
+    format: EpisodeLogFormat,
+    // Episode id -> what it triggered on, until it ends; only kept for JSON lines
+    triggers: Mutex<HashMap<String, serde_json::Value>>,
 }
 
 impl EpisodeLogger {
-    pub fn new(log_dir: &str, strategy_name: &str) -> anyhow::Result<Self> {
+    pub fn new(
+        log_dir: &str,
+        strategy_name: &str,
+        store: Arc<EpisodeStore>,
+        writer: Arc<EpisodeLogWriter>,
+    ) -> anyhow::Result<Self> {
         fs::create_dir_all(log_dir)?;
 
-        let file_path = PathBuf::from(log_dir).join(format!("{}_episodes.log", strategy_name));
+        let format = writer.format();
+        let extension = match format {
+            EpisodeLogFormat::Text => "log",
+            EpisodeLogFormat::Json => "jsonl",
+        };
+        let file_path = PathBuf::from(log_dir).join(format!("{}_episodes.{}", strategy_name, extension));
 
         let file = OpenOptions::new()
             .create(true)
@@ -22,38 +169,195 @@ impl EpisodeLogger {
 
         Ok(Self {
             file_path,
-            file: Mutex::new(file),
+            file: Arc::new(Mutex::new(file)),
+            writer,
+            strategy_name: strategy_name.to_string(),
+            store,
+            funding: None,
+            market_context: None,
+            config_version: None,
+            format,
+            triggers: Mutex::new(HashMap::new()),
         })
     }
 
+    /// Attach the funding rate at trigger time to every episode and export its follow-up change
+    pub fn with_funding(mut self, funding: Arc<FundingEpisodeExporter>) -> Self {
+        self.funding = Some(funding);
+        self
+    }
+
+    /// Name episodes in the store and funding export `<pipeline>/<strategy>`
+    pub fn with_pipeline(mut self, pipeline: &str) -> Self {
+        self.strategy_name = format!("{}/{}", pipeline, self.strategy_name);
+        self
+    }
+
+    /// Append the reference symbols' move over each episode
+    pub fn with_market_context(mut self, market_context: Arc<MarketContext>) -> Self {
+        self.market_context = Some(market_context);
+        self
+    }
+
+    /// Tag every episode with the strategy parameters version it fired under
+    pub fn with_config_version(mut self, version: u32) -> Self {
+        self.config_version = Some(version);
+        self
+    }
+
+    /// Keeps what an episode started on for its JSON line: every trigger value with its
+    /// threshold, the order book and the 24h stats. Call it with the episode's alert.
+    pub fn record_trigger(&self, alert: &Alert, orderbook: Option<&ProcessedOrderbook>) {
+        if self.format != EpisodeLogFormat::Json {
+            return;
+        }
+        let checks: serde_json::Map<String, serde_json::Value> = alert
+            .explanation
+            .checks
+            .iter()
+            .map(|check| {
+                let comparison = match check.comparison {
+                    Comparison::AtLeast => "at_least",
+                    Comparison::AtMost => "at_most",
+                };
+                let value = json!({
+                    "actual": check.actual,
+                    "threshold": check.threshold,
+                    "comparison": comparison,
+                    "passed": check.passed,
+                });
+                (check.name.to_string(), value)
+            })
+            .collect();
+        let book = orderbook.map(|book| {
+            let (bid_depth, ask_depth) = book.side_depths();
+            json!({
+                "best_bid": book.bids.first().map(|level| level.price),
+                "best_ask": book.asks.first().map(|level| level.price),
+                "spread_pct": book.calculate_spread_pct(),
+                "bid_depth_usdt": bid_depth,
+                "ask_depth_usdt": ask_depth,
+                "levels": book.bids.len().max(book.asks.len()),
+                "timestamp": book.timestamp.to_rfc3339(),
+            })
+        });
+        let trigger = json!({
+            "time": alert.timestamp.to_rfc3339(),
+            "source": alert.source.name(),
+            "note": alert.source.note(),
+            "ratio": alert.ratio,
+            "last_price": alert.last_price,
+            "mark_price": alert.mark_price,
+            "checks": checks,
+            "unavailable": alert.explanation.unavailable,
+            "orderbook": book,
+            "stats_24h": alert.stats_24h.map(|stats| json!({
+                "volume_usdt": stats.volume_usdt,
+                "high": stats.high,
+                "low": stats.low,
+                "change_pct": stats.change_pct,
+            })),
+        });
+        self.triggers.lock().unwrap().insert(alert.episode_id.clone(), trigger);
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn log_episode(
         &self,
+        episode_id: &str,
         symbol: &str,
         start_time: DateTime<Utc>,
         end_time: DateTime<Utc>,
         peak_ratio: f64,
         peak_last: f64,
         peak_mark: f64,
+        end_reason: Option<&str>,
     ) -> anyhow::Result<()> {
         let duration = end_time.signed_duration_since(start_time);
-        let duration_str = format!("{}s", duration.num_seconds());
-
-        let log_line = format!(
-            "{} | {} | START={} | END={} | DURATION={} | PEAK_RATIO={:.4} | PEAK_LAST={:.8} | PEAK_MARK={:.8}\n",
-            end_time.format("%Y-%m-%dT%H:%M:%SZ"),
-            symbol,
-            start_time.format("%H:%M:%S"),
-            end_time.format("%H:%M:%S"),
-            duration_str,
+        let funding_rate = self.funding.as_ref().and_then(|f| f.rate_at(symbol, start_time));
+        let moves = self.market_context.as_ref().map(|context| context.moves(start_time, end_time));
+
+        let log_line = match self.format {
+            EpisodeLogFormat::Text => {
+                let funding_str = match self.funding {
+                    Some(_) => format!(" | FUNDING={}", format_rate(funding_rate)),
+                    None => String::new(),
+                };
+
+                let market_str = match moves {
+                    Some(ref moves) if moves.is_empty() => " | MARKET=n/a".to_string(),
+                    Some(ref moves) => format!(" | MARKET={}", format_moves(moves)),
+                    None => String::new(),
+                };
+
+                let config_str = self.config_version.map(|version| format!(" | CONFIG=v{}", version)).unwrap_or_default();
+
+                let reason_str = end_reason.map(|reason| format!(" | END_REASON={}", reason)).unwrap_or_default();
+
+                format!(
+                    "{} | {} | ID={} | START={} | END={} | DURATION={}s | PEAK_RATIO={:.4} | PEAK_LAST={:.8} | PEAK_MARK={:.8}{}{}{}{}\n",
+                    end_time.format("%Y-%m-%dT%H:%M:%SZ"),
+                    symbol,
+                    episode_id,
+                    start_time.format("%H:%M:%S"),
+                    end_time.format("%H:%M:%S"),
+                    duration.num_seconds(),
+                    peak_ratio,
+                    peak_last,
+                    peak_mark,
+                    funding_str,
+                    market_str,
+                    config_str,
+                    reason_str
+                )
+            }
+            EpisodeLogFormat::Json => {
+                let trigger = self.triggers.lock().unwrap().remove(episode_id);
+                let line = json!({
+                    "id": episode_id,
+                    "strategy": self.strategy_name,
+                    "symbol": symbol,
+                    "start": start_time.to_rfc3339(),
+                    "end": end_time.to_rfc3339(),
+                    "duration_secs": duration.num_milliseconds() as f64 / 1000.0,
+                    "peak_ratio": peak_ratio,
+                    "peak_last": peak_last,
+                    "peak_mark": peak_mark,
+                    "trigger": trigger,
+                    "funding_rate": funding_rate,
+                    "market_moves": moves.map(|moves| {
+                        moves
+                            .iter()
+                            .map(|m| (m.symbol.clone(), json!(m.change_pct)))
+                            .collect::<serde_json::Map<_, _>>()
+                    }),
+                    "config_version": self.config_version,
+                    "end_reason": end_reason,
+                });
+                format!("{}\n", line)
+            }
+        };
+
+        self.store.push(EpisodeRecord {
+            id: episode_id.to_string(),
+            strategy: self.strategy_name.clone(),
+            symbol: symbol.to_string(),
+            start_time,
+            end_time,
             peak_ratio,
-            peak_last,
-            peak_mark
-        );
+            peak_last_price: peak_last,
+            peak_mark_price: peak_mark,
+            funding_rate_at_trigger: funding_rate,
+            funding_change: None,
+            config_version: self.config_version,
+        });
 
-        let mut file = self.file.lock().unwrap();
-        file.write_all(log_line.as_bytes())?;
-        file.flush()?;
+        if let Some(ref funding) = self.funding {
+            funding.schedule_followup(episode_id, &self.strategy_name, symbol, start_time, end_time, funding_rate);
+        }
 
+        // Called from the event loop, so the line is only queued here
+        self.writer.append(&self.file_path, &self.file, log_line);
         Ok(())
     }
 }
@@ -0,0 +1,134 @@
+use crate::config::ParsingConfig;
+use crate::utils::DiskWriter;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+const FILE_SUFFIX: &str = ".json";
+
+/// A market message that didn't match the expected schema, as written to its sample file
+#[derive(Debug, Serialize)]
+struct MalformedSample {
+    at: DateTime<Utc>,
+    /// `parse_error`, `unknown_message` or `partial`
+    kind: &'static str,
+    error: String,
+    /// The message as received
+    payload: String,
+}
+
+struct CaptureWindow {
+    started: Instant,
+    written: usize,
+    skipped: usize,
+}
+
+/// Strict parsing mode: writes every market message that failed to parse, arrived on an
+/// unknown channel or lost fields to the lenient parsing, into its own file under
+/// `samples_dir`, so exchange schema changes are noticed before detection quietly degrades.
+///
+/// At most `max_samples_per_min` are written per minute; the others are only counted, and
+/// the count is logged with the first capture of the next minute.
+pub struct MalformedCapture {
+    max_per_min: usize,
+    tx: mpsc::UnboundedSender<MalformedSample>,
+    window: Mutex<CaptureWindow>,
+}
+
+impl MalformedCapture {
+    pub fn start(config: ParsingConfig, disk: Arc<DiskWriter>) -> Result<Arc<Self>> {
+        fs::create_dir_all(&config.samples_dir)?;
+        let (tx, rx) = mpsc::unbounded_channel();
+        let max_per_min = config.max_samples_per_min;
+        tokio::spawn(run_writer(config, disk, rx));
+        Ok(Arc::new(Self {
+            max_per_min,
+            tx,
+            window: Mutex::new(CaptureWindow {
+                started: Instant::now(),
+                written: 0,
+                skipped: 0,
+            }),
+        }))
+    }
+
+    pub fn capture(&self, kind: &'static str, payload: &str, error: &str) {
+        let now = Instant::now();
+        {
+            let mut window = self.window.lock().unwrap();
+            if now.duration_since(window.started) >= Duration::from_secs(60) {
+                let skipped = window.skipped;
+                *window = CaptureWindow {
+                    started: now,
+                    written: 0,
+                    skipped: 0,
+                };
+                if skipped > 0 {
+                    warn!("[Parsing] {} more malformed messages were not captured (max_samples_per_min)", skipped);
+                }
+            }
+            if window.written >= self.max_per_min {
+                window.skipped += 1;
+                return;
+            }
+            window.written += 1;
+        }
+
+        warn!("[Parsing] Captured {} message: {}", kind, error);
+        let sample = MalformedSample {
+            at: Utc::now(),
+            kind,
+            error: error.to_string(),
+            payload: payload.to_string(),
+        };
+        if self.tx.send(sample).is_err() {
+            warn!("[Parsing] Writer task is gone, sample dropped");
+        }
+    }
+}
+
+async fn run_writer(config: ParsingConfig, disk: Arc<DiskWriter>, mut rx: mpsc::UnboundedReceiver<MalformedSample>) {
+    let dir = PathBuf::from(&config.samples_dir);
+    // Keeps the names of samples taken within the same millisecond apart
+    let mut sequence: u64 = 0;
+    while let Some(sample) = rx.recv().await {
+        sequence += 1;
+        let path = dir.join(format!(
+            "{}_{}_{}{}",
+            sample.at.format("%Y%m%d_%H%M%S%.3f"),
+            sequence,
+            sample.kind,
+            FILE_SUFFIX
+        ));
+        let (dir, max_files) = (dir.clone(), config.max_files);
+        disk.write(format!("malformed sample {}", path.display()), move || {
+            fs::write(&path, serde_json::to_string_pretty(&sample)?)?;
+            prune_old_files(&dir, max_files)
+        })
+        .await;
+    }
+}
+
+/// Deletes the oldest samples beyond `max_files`; names start with the capture time, so
+/// they sort oldest first
+fn prune_old_files(dir: &Path, max_files: usize) -> Result<()> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.to_str().is_some_and(|name| name.ends_with(FILE_SUFFIX)))
+        .collect();
+    if files.len() <= max_files {
+        return Ok(());
+    }
+    files.sort();
+    let excess = files.len() - max_files;
+    for path in &files[..excess] {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
@@ -1,3 +1,29 @@
+pub mod clock;
+pub mod config_history;
+pub mod console;
+pub mod disk_io;
+pub mod episode_store;
+pub mod heartbeat;
+pub mod issue_bundle;
 pub mod logger;
+pub mod malformed;
+pub mod persisted;
+pub mod profiling;
+pub mod raw_log;
+pub mod session_stats;
+pub mod supervisor;
 
+pub use clock::*;
+pub use config_history::*;
+pub use console::*;
+pub use disk_io::*;
+pub use episode_store::*;
+pub use heartbeat::*;
+pub use issue_bundle::*;
 pub use logger::*;
+pub use malformed::*;
+pub use persisted::*;
+pub use profiling::*;
+pub use raw_log::*;
+pub use session_stats::*;
+pub use supervisor::*;
@@ -0,0 +1,59 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Key of the format version in files the detector reads back on a later run
+pub const FORMAT_VERSION_KEY: &str = "format_version";
+
+/// How the format of a stored file compares with the one this build writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoredFormat {
+    /// Written by an older build, or before the format was versioned (version 0); migrated on read
+    Older(u32),
+    Current,
+    /// Written by a newer build. Reading it could drop whatever this build doesn't know
+    /// about, so it is left untouched.
+    Newer(u32),
+}
+
+impl StoredFormat {
+    pub fn of(found: u32, current: u32) -> Self {
+        match found.cmp(&current) {
+            std::cmp::Ordering::Less => StoredFormat::Older(found),
+            std::cmp::Ordering::Equal => StoredFormat::Current,
+            std::cmp::Ordering::Greater => StoredFormat::Newer(found),
+        }
+    }
+}
+
+/// The `format_version` of a stored JSON document; 0 when it has none
+pub fn format_version(value: &serde_json::Value) -> u32 {
+    value
+        .get(FORMAT_VERSION_KEY)
+        .and_then(|v| v.as_u64())
+        .map_or(0, |v| v as u32)
+}
+
+/// The error for a file written in a newer format than this build understands
+pub fn newer_format_error(path: &Path, found: u32, current: u32) -> anyhow::Error {
+    anyhow!(
+        "{} is format version {}, but this build only reads up to {} - it was written by a newer mexc-sniper; \
+         upgrade, or move the file away to start over",
+        path.display(),
+        found,
+        current
+    )
+}
+
+/// Renames a state file that can't be read to `<name>.<label>-<timestamp>` next to it, so
+/// starting over doesn't overwrite it
+pub fn set_aside(path: &Path, label: &str) -> Result<PathBuf> {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow!("{} has no file name", path.display()))?;
+    let target = path.with_file_name(format!("{}.{}-{}", name, label, Utc::now().format("%Y%m%d_%H%M%S")));
+    fs::rename(path, &target).with_context(|| format!("moving {} to {}", path.display(), target.display()))?;
+    Ok(target)
+}
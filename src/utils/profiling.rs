@@ -0,0 +1,74 @@
+use crate::config::ProfilingConfig;
+use tracing::warn;
+
+/// Starts the sampling profiler if it is enabled in config and compiled in.
+///
+/// Samples are collected on a dedicated thread and written as a flamegraph SVG
+/// every `report_interval_secs`, so a running instance can be inspected without restarting it.
+pub fn start_profiler(config: &ProfilingConfig) {
+    if !config.enabled {
+        return;
+    }
+
+    #[cfg(feature = "profiling")]
+    {
+        let config = config.clone();
+        std::thread::Builder::new()
+            .name("profiler".to_string())
+            .spawn(move || run_profiler(config))
+            .map(|_| ())
+            .unwrap_or_else(|e| warn!("[Profiler] Failed to start profiler thread: {:?}", e));
+        tracing::info!("[Profiler] Sampling profiler started");
+    }
+
+    #[cfg(not(feature = "profiling"))]
+    warn!("[Profiler] [profiling] is enabled but this binary was built without the `profiling` feature");
+}
+
+#[cfg(feature = "profiling")]
+fn run_profiler(config: ProfilingConfig) {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    if let Err(e) = fs::create_dir_all(&config.output_dir) {
+        warn!("[Profiler] Cannot create {}: {:?}", config.output_dir, e);
+        return;
+    }
+
+    loop {
+        let guard = match pprof::ProfilerGuardBuilder::default()
+            .frequency(config.frequency_hz)
+            .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+            .build()
+        {
+            Ok(guard) => guard,
+            Err(e) => {
+                warn!("[Profiler] Failed to start sampling: {:?}", e);
+                return;
+            }
+        };
+
+        std::thread::sleep(Duration::from_secs(config.report_interval_secs));
+
+        let path = PathBuf::from(&config.output_dir).join(format!(
+            "flamegraph_{}.svg",
+            chrono::Utc::now().format("%Y%m%d_%H%M%S")
+        ));
+
+        let result = guard
+            .report()
+            .build()
+            .map_err(anyhow::Error::from)
+            .and_then(|report| {
+                let file = fs::File::create(&path)?;
+                report.flamegraph(file)?;
+                Ok(())
+            });
+
+        match result {
+            Ok(()) => tracing::info!("[Profiler] Wrote {}", path.display()),
+            Err(e) => warn!("[Profiler] Failed to write flamegraph: {:?}", e),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::config::WsRawLogConfig;
+use crate::utils::DiskWriter;
+use anyhow::Result;
+use chrono::Utc;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+// Messages compressed together into one gzip member
+const MAX_BATCH_MESSAGES: usize = 256;
+
+const FILE_PREFIX: &str = "ws_raw_";
+const FILE_SUFFIX: &str = ".log.gz";
+
+/// Keeps every subscription ack, error and unrecognized message from the exchange
+/// WebSocket in gzip files under `dir`, rotated by size, so rejections and undocumented
+/// error codes can be looked into later.
+///
+/// Each batch of messages is appended as a complete gzip member, so a file is readable
+/// with `zcat` at any time, even after a crash.
+pub struct WsRawLog {
+    tx: mpsc::UnboundedSender<String>,
+}
+
+impl WsRawLog {
+    pub fn start(config: WsRawLogConfig, disk: Arc<DiskWriter>) -> Result<Arc<Self>> {
+        fs::create_dir_all(&config.dir)?;
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(run_writer(config, disk, rx));
+        Ok(Arc::new(Self { tx }))
+    }
+
+    /// Records a raw message as received, prefixed with the receive time
+    pub fn record(&self, text: &str) {
+        let line = format!("{} {}\n", Utc::now().to_rfc3339(), text);
+        if self.tx.send(line).is_err() {
+            warn!("[WsRawLog] Writer task is gone, message dropped");
+        }
+    }
+}
+
+async fn run_writer(config: WsRawLogConfig, disk: Arc<DiskWriter>, mut rx: mpsc::UnboundedReceiver<String>) {
+    let dir = PathBuf::from(&config.dir);
+    let max_file_bytes = config.max_file_mb.max(1) * 1024 * 1024;
+    let mut current = new_file_path(&dir);
+    let mut lines = Vec::with_capacity(MAX_BATCH_MESSAGES);
+
+    while rx.recv_many(&mut lines, MAX_BATCH_MESSAGES).await > 0 {
+        let text: String = lines.drain(..).collect();
+
+        if fs::metadata(&current).is_ok_and(|m| m.len() >= max_file_bytes) {
+            current = new_file_path(&dir);
+        }
+        let path = current.clone();
+        let (dir, max_files) = (dir.clone(), config.max_files);
+        disk.write(format!("ws raw log {}", path.display()), move || {
+            let is_new = !path.exists();
+            let file = OpenOptions::new().create(true).append(true).open(&path)?;
+            let mut encoder = GzEncoder::new(file, Compression::default());
+            encoder.write_all(text.as_bytes())?;
+            encoder.finish()?;
+            if is_new {
+                prune_old_files(&dir, max_files)?;
+            }
+            Ok(())
+        })
+        .await;
+    }
+}
+
+/// Named by creation time, so names sort oldest first
+fn new_file_path(dir: &Path) -> PathBuf {
+    dir.join(format!("{}{}{}", FILE_PREFIX, Utc::now().format("%Y%m%d_%H%M%S%.3f"), FILE_SUFFIX))
+}
+
+/// Deletes the oldest files beyond `max_files`
+fn prune_old_files(dir: &Path, max_files: usize) -> Result<()> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(FILE_PREFIX) && name.ends_with(FILE_SUFFIX))
+        })
+        .collect();
+    files.sort();
+    let excess = files.len().saturating_sub(max_files.max(1));
+    for path in &files[..excess] {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use crate::models::MarketEvent;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Operational counters of the running session, for the periodic status report.
+///
+/// Counters are cumulative; the report turns two snapshots into per-second rates.
+pub struct SessionStats {
+    started: Instant,
+    ticker_events: AtomicU64,
+    mark_price_events: AtomicU64,
+    orderbook_events: AtomicU64,
+    trade_events: AtomicU64,
+    active_episodes: AtomicUsize,
+    event_queue_depth: AtomicUsize,
+    // Highest depth since the last snapshot
+    max_event_queue_depth: AtomicUsize,
+}
+
+/// Cumulative counts at one point in time
+#[derive(Debug, Clone, Copy)]
+pub struct SessionCounts {
+    pub at: Instant,
+    pub ticker_events: u64,
+    pub mark_price_events: u64,
+    pub orderbook_events: u64,
+    pub trade_events: u64,
+    pub strategy_checks: u64,
+}
+
+/// Per-second rates between two snapshots
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SessionRates {
+    pub ticker_events: f64,
+    pub mark_price_events: f64,
+    pub orderbook_events: f64,
+    pub trade_events: f64,
+    pub strategy_checks: f64,
+}
+
+impl SessionCounts {
+    pub fn rates_since(&self, earlier: &SessionCounts) -> SessionRates {
+        let secs = self.at.duration_since(earlier.at).as_secs_f64();
+        if secs <= 0.0 {
+            return SessionRates::default();
+        }
+        let rate = |now: u64, before: u64| now.saturating_sub(before) as f64 / secs;
+        SessionRates {
+            ticker_events: rate(self.ticker_events, earlier.ticker_events),
+            mark_price_events: rate(self.mark_price_events, earlier.mark_price_events),
+            orderbook_events: rate(self.orderbook_events, earlier.orderbook_events),
+            trade_events: rate(self.trade_events, earlier.trade_events),
+            strategy_checks: rate(self.strategy_checks, earlier.strategy_checks),
+        }
+    }
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionStats {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            ticker_events: AtomicU64::new(0),
+            mark_price_events: AtomicU64::new(0),
+            orderbook_events: AtomicU64::new(0),
+            trade_events: AtomicU64::new(0),
+            active_episodes: AtomicUsize::new(0),
+            event_queue_depth: AtomicUsize::new(0),
+            max_event_queue_depth: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Counts an event taken off the market event channel, with the events still queued behind it
+    pub fn record_event(&self, event: &MarketEvent, queue_depth: usize) {
+        let counter = match event {
+            MarketEvent::TickerUpdate { .. } => Some(&self.ticker_events),
+            MarketEvent::MarkPriceUpdate { .. } => Some(&self.mark_price_events),
+            MarketEvent::OrderbookUpdate { .. } => Some(&self.orderbook_events),
+            MarketEvent::TradeUpdate { .. } => Some(&self.trade_events),
+            MarketEvent::FundingRateUpdate { .. }
+            | MarketEvent::ContractStateChange(_)
+            | MarketEvent::SymbolRenamed(_)
+            | MarketEvent::ConnectionStatus(_) => None,
+        };
+        if let Some(counter) = counter {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+        self.event_queue_depth.store(queue_depth, Ordering::Relaxed);
+        self.max_event_queue_depth.fetch_max(queue_depth, Ordering::Relaxed);
+    }
+
+    pub fn set_active_episodes(&self, count: usize) {
+        self.active_episodes.store(count, Ordering::Relaxed);
+    }
+
+    pub fn active_episodes(&self) -> usize {
+        self.active_episodes.load(Ordering::Relaxed)
+    }
+
+    /// Current and highest market event queue depth since the previous call
+    pub fn take_event_queue_depth(&self) -> (usize, usize) {
+        let max = self.max_event_queue_depth.swap(0, Ordering::Relaxed);
+        (self.event_queue_depth.load(Ordering::Relaxed), max)
+    }
+
+    pub fn counts(&self, strategy_checks: u64) -> SessionCounts {
+        SessionCounts {
+            at: Instant::now(),
+            ticker_events: self.ticker_events.load(Ordering::Relaxed),
+            mark_price_events: self.mark_price_events.load(Ordering::Relaxed),
+            orderbook_events: self.orderbook_events.load(Ordering::Relaxed),
+            trade_events: self.trade_events.load(Ordering::Relaxed),
+            strategy_checks,
+        }
+    }
+}
+
+/// Resident memory of the process, where the OS exposes it (Linux)
+pub fn process_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// CPU time (user + system) the process used so far, where the OS exposes it (Linux)
+pub fn process_cpu_secs() -> Option<f64> {
+    // Kernel clock ticks; USER_HZ is 100 on every Linux platform this runs on
+    const TICKS_PER_SEC: f64 = 100.0;
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // The command name in parentheses may contain spaces; utime and stime are the 12th and
+    // 13th fields after it
+    let mut fields = stat.rsplit_once(')')?.1.split_whitespace().skip(11);
+    let utime: u64 = fields.next()?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    Some((utime + stime) as f64 / TICKS_PER_SEC)
+}
+
+/// Formats a byte count as e.g. `12.3 MB`
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Formats an uptime as e.g. `2h 05m 13s`
+pub fn format_uptime(uptime: Duration) -> String {
+    let secs = uptime.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else {
+        format!("{}m {:02}s", minutes, seconds)
+    }
+}
@@ -0,0 +1,102 @@
+use dashmap::DashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+/// Owns long-running background tasks and restarts them with exponential
+/// backoff when they panic, so a bug in one loop doesn't silently stop it.
+///
+/// Tasks that return normally are considered finished and are not restarted.
+#[derive(Clone)]
+pub struct TaskSupervisor {
+    restarts: Arc<DashMap<String, u64>>,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Default for TaskSupervisor {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1), Duration::from_secs(60))
+    }
+}
+
+impl TaskSupervisor {
+    pub fn new(initial_backoff: Duration, max_backoff: Duration) -> Self {
+        Self {
+            restarts: Arc::new(DashMap::new()),
+            initial_backoff,
+            max_backoff,
+        }
+    }
+
+    /// Spawns `factory()` and keeps it running; `factory` is called again for every restart
+    pub fn supervise<F, Fut>(&self, name: impl Into<String>, factory: F) -> JoinHandle<()>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let name = name.into();
+        let supervisor = self.clone();
+
+        tokio::spawn(async move {
+            let mut backoff = supervisor.initial_backoff;
+
+            loop {
+                let started = Instant::now();
+                let handle = tokio::spawn(factory());
+
+                match handle.await {
+                    Ok(()) => {
+                        info!("[Supervisor] Task '{}' finished", name);
+                        return;
+                    }
+                    Err(e) if e.is_cancelled() => {
+                        warn!("[Supervisor] Task '{}' was cancelled", name);
+                        return;
+                    }
+                    Err(e) => {
+                        // A task that ran for a while before failing starts over from the short delay
+                        if started.elapsed() > supervisor.max_backoff {
+                            backoff = supervisor.initial_backoff;
+                        }
+
+                        let count = {
+                            let mut entry = supervisor.restarts.entry(name.clone()).or_insert(0);
+                            *entry += 1;
+                            *entry
+                        };
+                        error!(
+                            "[Supervisor] Task '{}' died after {:.1}s: {} - restart #{} in {:?}",
+                            name,
+                            started.elapsed().as_secs_f64(),
+                            e,
+                            count,
+                            backoff
+                        );
+
+                        tokio::time::sleep(backoff).await;
+                        backoff = (backoff * 2).min(supervisor.max_backoff);
+                    }
+                }
+            }
+        })
+    }
+
+    /// Total restarts across all supervised tasks
+    pub fn total_restarts(&self) -> u64 {
+        self.restarts.iter().map(|entry| *entry.value()).sum()
+    }
+
+    /// Restart count per task name, for tasks that have restarted at least once
+    pub fn restart_counts(&self) -> Vec<(String, u64)> {
+        let mut counts: Vec<(String, u64)> = self
+            .restarts
+            .iter()
+            .map(|entry| (entry.key().clone(), *entry.value()))
+            .collect();
+        counts.sort();
+        counts
+    }
+}
@@ -0,0 +1,123 @@
+use crate::utils::EpisodeRecord;
+use chrono::{DateTime, Utc};
+
+/// Renders recent episodes as an Atom 1.0 document
+pub fn render_atom(title: &str, base_url: &str, episodes: &[EpisodeRecord]) -> String {
+    let updated = episodes
+        .first()
+        .map(|e| e.end_time)
+        .unwrap_or_else(Utc::now);
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("  <title>{}</title>\n", escape_xml(title)));
+    xml.push_str(&format!("  <id>{}/feed.atom</id>\n", escape_xml(base_url)));
+    xml.push_str(&format!(
+        "  <link rel=\"self\" href=\"{}/feed.atom\"/>\n",
+        escape_xml(base_url)
+    ));
+    xml.push_str(&format!("  <updated>{}</updated>\n", updated.to_rfc3339()));
+
+    for episode in episodes {
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <title>{}</title>\n", escape_xml(&entry_title(episode))));
+        xml.push_str(&format!("    <id>{}</id>\n", escape_xml(&entry_id(episode))));
+        xml.push_str(&format!("    <updated>{}</updated>\n", episode.end_time.to_rfc3339()));
+        xml.push_str(&format!("    <published>{}</published>\n", episode.start_time.to_rfc3339()));
+        xml.push_str(&format!("    <category term=\"{}\"/>\n", escape_xml(&episode.strategy)));
+        xml.push_str(&format!(
+            "    <summary>{}</summary>\n",
+            escape_xml(&entry_summary(episode))
+        ));
+        xml.push_str("  </entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+/// Renders recent episodes as an RSS 2.0 document
+pub fn render_rss(title: &str, base_url: &str, episodes: &[EpisodeRecord]) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<rss version=\"2.0\">\n");
+    xml.push_str("  <channel>\n");
+    xml.push_str(&format!("    <title>{}</title>\n", escape_xml(title)));
+    xml.push_str(&format!("    <link>{}/feed.rss</link>\n", escape_xml(base_url)));
+    xml.push_str("    <description>Recently completed pump anomaly episodes</description>\n");
+
+    for episode in episodes {
+        xml.push_str("    <item>\n");
+        xml.push_str(&format!("      <title>{}</title>\n", escape_xml(&entry_title(episode))));
+        xml.push_str(&format!(
+            "      <guid isPermaLink=\"false\">{}</guid>\n",
+            escape_xml(&entry_id(episode))
+        ));
+        xml.push_str(&format!("      <pubDate>{}</pubDate>\n", episode.end_time.to_rfc2822()));
+        xml.push_str(&format!("      <category>{}</category>\n", escape_xml(&episode.strategy)));
+        xml.push_str(&format!(
+            "      <description>{}</description>\n",
+            escape_xml(&entry_summary(episode))
+        ));
+        xml.push_str("    </item>\n");
+    }
+
+    xml.push_str("  </channel>\n");
+    xml.push_str("</rss>\n");
+    xml
+}
+
+fn entry_title(episode: &EpisodeRecord) -> String {
+    format!(
+        "[{}] {} peak ratio {:.4}",
+        episode.strategy, episode.symbol, episode.peak_ratio
+    )
+}
+
+fn entry_id(episode: &EpisodeRecord) -> String {
+    format!("urn:mexc-sniper:episode:{}", episode.id)
+}
+
+fn entry_summary(episode: &EpisodeRecord) -> String {
+    let mut summary = format!(
+        "{} | ID={} | START={} | END={} | DURATION={}s | PEAK_RATIO={:.4} | PEAK_LAST={:.8} | PEAK_MARK={:.8}",
+        episode.symbol,
+        episode.id,
+        format_time(episode.start_time),
+        format_time(episode.end_time),
+        episode.end_time.signed_duration_since(episode.start_time).num_seconds(),
+        episode.peak_ratio,
+        episode.peak_last_price,
+        episode.peak_mark_price
+    );
+    if let Some(rate) = episode.funding_rate_at_trigger {
+        summary.push_str(&format!(" | FUNDING={:.6}", rate));
+    }
+    if let Some(change) = episode.funding_change {
+        summary.push_str(&format!(" | FUNDING_CHANGE={:+.6}", change));
+    }
+    if let Some(version) = episode.config_version {
+        summary.push_str(&format!(" | CONFIG=v{}", version));
+    }
+    summary
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,154 @@
+use crate::alerts::{Alert, AlertDigest, KillSwitchEvent, MissedDigest, Notifier};
+use crate::config::WebConfig;
+use crate::models::{ContractStateChange, NewListing, Symbol, SymbolData};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use dashmap::DashMap;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+// Messages buffered per browser; a browser that falls further behind skips ahead
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Pushes new signals and periodic per-symbol snapshots to the browsers connected to the
+/// web server's `/ws` endpoint, as JSON text messages tagged with their `type`.
+///
+/// Receives alerts like any other notifier; snapshots are only built while at least one
+/// browser is connected.
+#[derive(Clone)]
+pub struct LiveFeed {
+    tx: broadcast::Sender<Arc<str>>,
+    symbol_data: Arc<DashMap<Symbol, SymbolData>>,
+    snapshot_interval_ms: u64,
+    snapshot_max_symbols: usize,
+}
+
+impl LiveFeed {
+    pub fn new(config: &WebConfig, symbol_data: Arc<DashMap<Symbol, SymbolData>>) -> Self {
+        let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            tx,
+            symbol_data,
+            snapshot_interval_ms: config.snapshot_interval_ms,
+            snapshot_max_symbols: config.snapshot_max_symbols,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<str>> {
+        self.tx.subscribe()
+    }
+
+    pub fn connected(&self) -> usize {
+        self.tx.receiver_count()
+    }
+
+    fn publish(&self, message: serde_json::Value) {
+        // Nobody is connected when sending fails
+        let _ = self.tx.send(message.to_string().into());
+    }
+
+    /// Publishes snapshots every `snapshot_interval_ms`, which must be non-zero
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Duration::from_millis(self.snapshot_interval_ms));
+        loop {
+            interval.tick().await;
+            if self.connected() > 0 {
+                self.publish(self.snapshot());
+            }
+        }
+    }
+
+    /// Symbols with a price, the widest last / mark spreads first
+    fn snapshot(&self) -> serde_json::Value {
+        let mut symbols: Vec<(f64, serde_json::Value)> = self
+            .symbol_data
+            .iter()
+            .filter_map(|data| {
+                let (last, mark) = (data.current_last_price?, data.current_mark_price?);
+                let ratio = if mark > 0.0 { last / mark } else { 0.0 };
+                let symbol = json!({
+                    "symbol": &*data.symbol,
+                    "last_price": last,
+                    "mark_price": mark,
+                    "ratio": ratio,
+                    "bid": data.quote.map(|q| q.best_bid),
+                    "ask": data.quote.map(|q| q.best_ask),
+                    "volume_24h_usdt": data.stats_24h.map(|s| s.volume_usdt),
+                    "change_24h_pct": data.stats_24h.map(|s| s.change_pct),
+                    "watched": data.watch.is_some(),
+                    "state": data.contract_state.to_string(),
+                    "updated": data.last_update.to_rfc3339(),
+                });
+                Some((ratio, symbol))
+            })
+            .collect();
+        symbols.sort_by(|a, b| b.0.total_cmp(&a.0));
+        if self.snapshot_max_symbols > 0 {
+            symbols.truncate(self.snapshot_max_symbols);
+        }
+
+        json!({
+            "type": "snapshot",
+            "timestamp": Utc::now().to_rfc3339(),
+            "symbols": symbols.into_iter().map(|(_, symbol)| symbol).collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for LiveFeed {
+    fn name(&self) -> &str {
+        "live"
+    }
+
+    fn is_internal(&self) -> bool {
+        true
+    }
+
+    async fn notify(&self, alert: &Alert) -> Result<()> {
+        self.publish(json!({
+            "type": "signal",
+            "strategy": alert.strategy,
+            "episode_id": alert.episode_id,
+            "symbol": alert.symbol,
+            "ratio": alert.ratio,
+            "last_price": alert.last_price,
+            "mark_price": alert.mark_price,
+            "volume_24h_usdt": alert.stats_24h.map(|s| s.volume_usdt),
+            "timestamp": alert.timestamp.to_rfc3339(),
+            "explanation": alert.explanation.to_string(),
+            "source": alert.source.name(),
+        }));
+        Ok(())
+    }
+
+    async fn notify_digest(&self, _digest: &MissedDigest) -> Result<()> {
+        Ok(())
+    }
+
+    async fn notify_alert_digest(&self, _digest: &AlertDigest) -> Result<()> {
+        Ok(())
+    }
+
+    async fn notify_kill_switch(&self, _event: &KillSwitchEvent) -> Result<()> {
+        Ok(())
+    }
+
+    async fn notify_contract_state(&self, change: &ContractStateChange) -> Result<()> {
+        self.publish(json!({
+            "type": "contract_state",
+            "symbol": change.symbol,
+            "state": change.current.to_string(),
+            "previous": change.previous.to_string(),
+            "timestamp": change.at.to_rfc3339(),
+        }));
+        Ok(())
+    }
+
+    async fn notify_new_listing(&self, _listing: &NewListing) -> Result<()> {
+        Ok(())
+    }
+}
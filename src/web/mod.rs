@@ -0,0 +1,6 @@
+pub mod feed;
+pub mod live;
+pub mod server;
+
+pub use live::*;
+pub use server::*;
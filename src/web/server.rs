@@ -0,0 +1,222 @@
+use crate::config::WebConfig;
+use crate::detection::PrecisionTracker;
+use crate::utils::EpisodeStore;
+use crate::web::{feed, LiveFeed};
+use anyhow::Result;
+use futures_util::{SinkExt, StreamExt};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use tracing::{debug, info, warn};
+
+const MAX_REQUEST_BYTES: usize = 8192;
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn ok(content_type: &'static str, body: String) -> Self {
+        Self {
+            status: "200 OK",
+            content_type,
+            body,
+        }
+    }
+
+    fn not_found() -> Self {
+        Self {
+            status: "404 Not Found",
+            content_type: "text/plain; charset=utf-8",
+            body: "not found\n".to_string(),
+        }
+    }
+
+    fn bad_request() -> Self {
+        Self {
+            status: "400 Bad Request",
+            content_type: "text/plain; charset=utf-8",
+            body: "bad request\n".to_string(),
+        }
+    }
+}
+
+/// Minimal HTTP/1.1 server exposing read-only views of the detector state
+pub struct WebServer {
+    config: WebConfig,
+    episode_store: Arc<EpisodeStore>,
+    precision: Option<PrecisionTracker>,
+    live: Option<LiveFeed>,
+}
+
+impl WebServer {
+    pub fn new(config: WebConfig, episode_store: Arc<EpisodeStore>) -> Self {
+        Self {
+            config,
+            episode_store,
+            precision: None,
+            live: None,
+        }
+    }
+
+    /// Serves the per-strategy rolling precision report at `/precision`
+    pub fn with_precision(mut self, precision: PrecisionTracker) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Pushes signals and symbol snapshots to browsers connecting to `/ws`
+    pub fn with_live_feed(mut self, live: LiveFeed) -> Self {
+        self.live = Some(live);
+        self
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let listener = TcpListener::bind(&self.config.bind_addr).await?;
+        info!(
+            "[Web] Listening on http://{} (feeds: /feed.atom, /feed.rss{})",
+            self.config.bind_addr,
+            if self.live.is_some() { ", live push: /ws" } else { "" }
+        );
+
+        let server = Arc::new(self);
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            let server = server.clone();
+            tokio::spawn(async move {
+                if let Err(e) = server.handle_connection(stream).await {
+                    warn!("[Web] Failed to serve request from {}: {:?}", peer, e);
+                }
+            });
+        }
+    }
+
+    async fn handle_connection(&self, mut stream: TcpStream) -> Result<()> {
+        let mut buf = Vec::with_capacity(1024);
+        let mut chunk = [0u8; 1024];
+
+        // Read until the end of the request headers; bodies are not supported
+        while !buf.windows(4).any(|w| w == b"\r\n\r\n") && buf.len() < MAX_REQUEST_BYTES {
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                break;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+
+        let request = String::from_utf8_lossy(&buf);
+        let mut parts = request.lines().next().unwrap_or("").split_whitespace();
+        let (method, target) = (parts.next(), parts.next());
+
+        let path = target.map(|target| target.split('?').next().unwrap_or(target));
+        if let (Some("GET"), Some("/ws"), Some(live)) = (method, path, &self.live) {
+            if let Some(key) = websocket_key(&request) {
+                return serve_live(stream, &key, live).await;
+            }
+        }
+
+        let response = match (method, target) {
+            (Some("GET"), Some(target)) => self.route(target),
+            _ => Response::bad_request(),
+        };
+
+        let head = format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            response.status,
+            response.content_type,
+            response.body.len()
+        );
+
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(response.body.as_bytes()).await?;
+        stream.shutdown().await?;
+        Ok(())
+    }
+
+    fn route(&self, target: &str) -> Response {
+        let path = target.split('?').next().unwrap_or(target);
+        let base_url = format!("http://{}", self.config.bind_addr);
+
+        match path {
+            "/feed.atom" | "/feed" => {
+                let episodes = self.episode_store.recent(self.config.feed_max_entries);
+                Response::ok(
+                    "application/atom+xml; charset=utf-8",
+                    feed::render_atom(&self.config.feed_title, &base_url, &episodes),
+                )
+            }
+            "/feed.rss" => {
+                let episodes = self.episode_store.recent(self.config.feed_max_entries);
+                Response::ok(
+                    "application/rss+xml; charset=utf-8",
+                    feed::render_rss(&self.config.feed_title, &base_url, &episodes),
+                )
+            }
+            "/precision" => match &self.precision {
+                Some(precision) => {
+                    let mut body = precision.summary_lines().join("\n");
+                    if body.is_empty() {
+                        body.push_str("no triggers yet");
+                    }
+                    body.push('\n');
+                    Response::ok("text/plain; charset=utf-8", body)
+                }
+                None => Response::not_found(),
+            },
+            _ => Response::not_found(),
+        }
+    }
+}
+
+/// `Sec-WebSocket-Key` of a WebSocket upgrade request
+fn websocket_key(request: &str) -> Option<String> {
+    let header = |name: &str| {
+        request.lines().skip(1).find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    };
+    header("upgrade").filter(|value| value.eq_ignore_ascii_case("websocket"))?;
+    header("sec-websocket-key").map(str::to_string)
+}
+
+/// Completes the WebSocket handshake and forwards live feed messages until the browser
+/// disconnects; browsers don't send anything but pings and the close frame
+async fn serve_live(mut stream: TcpStream, key: &str, live: &LiveFeed) -> Result<()> {
+    // Subscribed before the handshake so nothing published in between is missed
+    let mut messages = live.subscribe();
+    let head = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        derive_accept_key(key.as_bytes())
+    );
+    stream.write_all(head.as_bytes()).await?;
+    let peer = stream.peer_addr()?;
+    let mut socket = WebSocketStream::from_raw_socket(stream, Role::Server, None).await;
+    debug!("[Web] {} connected to the live feed ({} connected)", peer, live.connected());
+
+    loop {
+        tokio::select! {
+            message = messages.recv() => match message {
+                Ok(text) => socket.send(Message::Text(text.to_string())).await?,
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!("[Web] {} fell behind, {} live messages skipped", peer, skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+            incoming = socket.next() => match incoming {
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+            },
+        }
+    }
+    debug!("[Web] {} left the live feed", peer);
+    Ok(())
+}